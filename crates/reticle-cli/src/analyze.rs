@@ -0,0 +1,715 @@
+//! `reticle analyze`, `reticle history`, and `reticle changes`
+//!
+//! The analysis itself is done by [`reticle_core::server_analyzer`]; this
+//! module prints it as text for a terminal, JSON for scripts, or Markdown
+//! for CI job summaries and pull request comments. It also saves analyses
+//! to the history, lists them for `reticle history`, and loads two of them
+//! for `reticle changes`.
+
+use crate::AnalyzeFormat;
+use reticle_core::analysis_diff::diff_analyses;
+use reticle_core::server_analyzer::{
+    AnalysisInfo, AnalysisThresholds, ServerAnalysis, ServerAnalyzer, ThresholdViolation,
+};
+use reticle_core::storage::SessionStorage;
+use reticle_core::tokenizer::TokenizerSpec;
+use reticle_core::tool_lint::{LintReport, LintSeverity};
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// JSON output: the analysis plus any thresholds it exceeded
+#[derive(Serialize)]
+pub struct Report<'a> {
+    #[serde(flatten)]
+    pub analysis: &'a ServerAnalysis,
+    pub violations: &'a [ThresholdViolation],
+}
+
+/// Render an analysis as plain text
+pub fn render_text(analysis: &ServerAnalysis, violations: &[ThresholdViolation]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{} {} (protocol {})",
+        analysis.server_name, analysis.server_version, analysis.protocol_version
+    );
+    let _ = writeln!(
+        out,
+        "Context cost: {} tokens ({})",
+        analysis.total_context_tokens, analysis.tokenizer
+    );
+
+    let tools = &analysis.tools;
+    let _ = writeln!(
+        out,
+        "\nTools ({}): {} tokens",
+        tools.count, tools.total_tokens
+    );
+    let width = tools.tools.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    for tool in &tools.tools {
+        let _ = write!(
+            out,
+            "  {:width$}  {:>6}  (description {}, schema {}",
+            tool.name, tool.total_tokens, tool.description_tokens, tool.schema_tokens
+        );
+        if tool.output_schema_tokens > 0 {
+            let _ = write!(out, ", output schema {}", tool.output_schema_tokens);
+        }
+        out.push_str(")\n");
+    }
+
+    let prompts = &analysis.prompts;
+    let _ = writeln!(
+        out,
+        "\nPrompts ({}): {} tokens",
+        prompts.count, prompts.total_tokens
+    );
+    let width = prompts
+        .prompts
+        .iter()
+        .map(|p| p.name.len())
+        .max()
+        .unwrap_or(0);
+    for prompt in &prompts.prompts {
+        let _ = writeln!(out, "  {:width$}  {:>6}", prompt.name, prompt.total_tokens);
+    }
+
+    let resources = &analysis.resources;
+    let _ = writeln!(
+        out,
+        "\nResources ({}): {} tokens",
+        resources.count, resources.total_tokens
+    );
+    let width = resources
+        .resources
+        .iter()
+        .map(|r| r.uri.len())
+        .max()
+        .unwrap_or(0);
+    for resource in &resources.resources {
+        let _ = writeln!(
+            out,
+            "  {:width$}  {:>6}",
+            resource.uri, resource.total_tokens
+        );
+    }
+
+    if !analysis.lint.findings.is_empty() {
+        let _ = writeln!(out, "\nLint: {}", lint_summary(&analysis.lint));
+        for finding in &analysis.lint.findings {
+            let _ = writeln!(out, "  {finding}");
+        }
+    }
+
+    if !violations.is_empty() {
+        out.push('\n');
+        for violation in violations {
+            let _ = writeln!(out, "FAIL {violation}");
+        }
+    }
+    out
+}
+
+/// Render an analysis as Markdown
+pub fn render_markdown(analysis: &ServerAnalysis, violations: &[ThresholdViolation]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "## Context cost: {} {}\n",
+        escape(&analysis.server_name),
+        escape(&analysis.server_version)
+    );
+
+    out.push_str("| | Count | Tokens |\n|---|---:|---:|\n");
+    let _ = writeln!(
+        out,
+        "| Tools | {} | {} |",
+        analysis.tools.count, analysis.tools.total_tokens
+    );
+    let _ = writeln!(
+        out,
+        "| Prompts | {} | {} |",
+        analysis.prompts.count, analysis.prompts.total_tokens
+    );
+    let _ = writeln!(
+        out,
+        "| Resources | {} | {} |",
+        analysis.resources.count, analysis.resources.total_tokens
+    );
+    let _ = writeln!(
+        out,
+        "| **Total** | | **{}** |",
+        analysis.total_context_tokens
+    );
+    let _ = writeln!(
+        out,
+        "\nProtocol {}, counted with `{}`.",
+        escape(&analysis.protocol_version),
+        analysis.tokenizer
+    );
+
+    if !violations.is_empty() {
+        out.push_str("\n### Thresholds exceeded\n\n");
+        for violation in violations {
+            let _ = writeln!(out, "- {}", escape(&violation.to_string()));
+        }
+    }
+
+    if !analysis.tools.tools.is_empty() {
+        out.push_str("\n### Tools\n\n");
+        out.push_str("| Tool | Description | Schema | Total |\n|---|---:|---:|---:|\n");
+        for tool in &analysis.tools.tools {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} |",
+                escape(&tool.name),
+                tool.description_tokens,
+                tool.schema_tokens,
+                tool.total_tokens
+            );
+        }
+    }
+    if !analysis.prompts.prompts.is_empty() {
+        out.push_str("\n### Prompts\n\n| Prompt | Tokens |\n|---|---:|\n");
+        for prompt in &analysis.prompts.prompts {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} |",
+                escape(&prompt.name),
+                prompt.total_tokens
+            );
+        }
+    }
+    if !analysis.resources.resources.is_empty() {
+        out.push_str("\n### Resources\n\n| Resource | Tokens |\n|---|---:|\n");
+        for resource in &analysis.resources.resources {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} |",
+                escape(&resource.uri),
+                resource.total_tokens
+            );
+        }
+    }
+    if !analysis.lint.findings.is_empty() {
+        let _ = writeln!(out, "\n### Lint: {}\n", lint_summary(&analysis.lint));
+        out.push_str("| Severity | Tool | Where | Finding |\n|---|---|---|---|\n");
+        for finding in &analysis.lint.findings {
+            let _ = writeln!(
+                out,
+                "| {} | `{}` | {} | {} |",
+                finding.severity,
+                escape(&finding.tool),
+                finding
+                    .path
+                    .as_deref()
+                    .map(|path| format!("`{}`", escape(path)))
+                    .unwrap_or_default(),
+                escape(&finding.message)
+            );
+        }
+    }
+    out
+}
+
+/// Render saved analyses as a table; `now` is in Unix seconds
+pub fn render_history(history: &[AnalysisInfo], now: u64) -> String {
+    let name_width = history
+        .iter()
+        .map(|info| info.server_name.len())
+        .chain(["SERVER".len()])
+        .max()
+        .unwrap_or(0);
+    let version_width = history
+        .iter()
+        .map(|info| info.server_version.len())
+        .chain(["VERSION".len()])
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:name_width$}  {:version_width$}  {:>5}  {:>7}  {:<12}  ANALYZED",
+        "SERVER", "VERSION", "TOOLS", "TOKENS", "TOKENIZER"
+    );
+    for info in history {
+        let _ = writeln!(
+            out,
+            "{:name_width$}  {:version_width$}  {:>5}  {:>7}  {:<12}  {}",
+            info.server_name,
+            info.server_version,
+            info.tools,
+            info.total_context_tokens,
+            info.tokenizer.to_string(),
+            ago(now.saturating_sub(info.analyzed_at))
+        );
+    }
+    out
+}
+
+/// e.g. "5m ago"
+fn ago(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// e.g. "1 error, 2 warnings"
+fn lint_summary(report: &LintReport) -> String {
+    [
+        (LintSeverity::Error, "error"),
+        (LintSeverity::Warning, "warning"),
+        (LintSeverity::Info, "info"),
+    ]
+    .into_iter()
+    .filter_map(|(severity, label)| match report.count(severity) {
+        0 => None,
+        1 => Some(format!("1 {label}")),
+        n if severity == LintSeverity::Info => Some(format!("{n} {label}")),
+        n => Some(format!("{n} {label}s")),
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Keep server-provided text from breaking table cells
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+/// Run analyze mode, saving the analysis to `storage` if given
+pub async fn run_analyze(
+    analyzer: ServerAnalyzer,
+    format: AnalyzeFormat,
+    tokenizer: TokenizerSpec,
+    thresholds: AnalysisThresholds,
+    storage: Option<PathBuf>,
+) -> ExitCode {
+    let tokenizer = match tokenizer.load() {
+        Ok(tokenizer) => tokenizer,
+        Err(e) => {
+            eprintln!("[reticle analyze] Error: {e}");
+            return ExitCode::from(2);
+        }
+    };
+    let analysis = match analyzer.with_tokenizer(tokenizer).analyze().await {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            eprintln!("[reticle analyze] Error: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Some(storage_path) = storage {
+        match SessionStorage::new(storage_path) {
+            Ok(storage) => save_analysis(&analysis, &storage).await,
+            Err(e) => eprintln!("[reticle analyze] Warning: failed to save analysis: {e}"),
+        }
+    }
+
+    let violations = thresholds.check(&analysis);
+    match format {
+        AnalyzeFormat::Text => print!("{}", render_text(&analysis, &violations)),
+        AnalyzeFormat::Markdown => print!("{}", render_markdown(&analysis, &violations)),
+        AnalyzeFormat::Json => {
+            let report = Report {
+                analysis: &analysis,
+                violations: &violations,
+            };
+            match serde_json::to_string_pretty(&report) {
+                Ok(text) => println!("{text}"),
+                Err(e) => {
+                    eprintln!("[reticle analyze] Error: {e}");
+                    return ExitCode::from(2);
+                }
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Save an analysis to the history, noting what changed since the most
+/// recently analyzed other version of the server
+///
+/// Failing to save only warns: the analysis itself still succeeded.
+async fn save_analysis(analysis: &ServerAnalysis, storage: &SessionStorage) {
+    let previous = storage
+        .list_analyses(Some(&analysis.server_name))
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .find(|info| info.server_version != analysis.server_version);
+    if let Err(e) = storage.save_analysis(analysis).await {
+        eprintln!("[reticle analyze] Warning: failed to save analysis: {e}");
+        return;
+    }
+
+    let Some(previous) = previous else {
+        return;
+    };
+    let Ok(before) = storage
+        .load_analysis(&previous.server_name, &previous.server_version)
+        .await
+    else {
+        return;
+    };
+    let changes = diff_analyses(&before, analysis);
+    if changes.has_differences() {
+        let s = &changes.summary;
+        eprintln!(
+            "[reticle analyze] Changed since {}: {} tool(s) added, {} removed, {} changed, {:+} tokens (see `reticle changes {}`)",
+            previous.server_version,
+            s.tools_added,
+            s.tools_removed,
+            s.tools_changed,
+            s.token_delta,
+            analysis.server_name
+        );
+    }
+}
+
+/// Run history mode
+pub async fn run_history(server: Option<String>, json: bool, storage: Option<PathBuf>) -> ExitCode {
+    let storage_path = storage.unwrap_or_else(SessionStorage::default_path);
+    let history = match SessionStorage::new(storage_path) {
+        Ok(storage) => storage.list_analyses(server.as_deref()).await,
+        Err(e) => Err(e),
+    };
+    let history = match history {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("[reticle history] Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if json {
+        match serde_json::to_string_pretty(&history) {
+            Ok(text) => println!("{text}"),
+            Err(e) => {
+                eprintln!("[reticle history] Error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else if history.is_empty() {
+        eprintln!("[reticle history] No saved analyses; run `reticle analyze` first");
+    } else {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        print!("{}", render_history(&history, now));
+    }
+    ExitCode::SUCCESS
+}
+
+/// Run changes mode
+pub async fn run_changes(
+    a: String,
+    b: Option<String>,
+    json: bool,
+    storage: Option<PathBuf>,
+) -> ExitCode {
+    let storage_path = storage.unwrap_or_else(SessionStorage::default_path);
+    let storage = match SessionStorage::new(storage_path.clone()) {
+        Ok(storage) => storage,
+        Err(e) => {
+            eprintln!("[reticle changes] Error: {}: {e}", storage_path.display());
+            return ExitCode::from(2);
+        }
+    };
+    let (a, b) = match b {
+        Some(b) => (a, b),
+        None => match latest_versions(&a, &storage).await {
+            Ok(pair) => pair,
+            Err(e) => {
+                eprintln!("[reticle changes] Error: {e}");
+                return ExitCode::from(2);
+            }
+        },
+    };
+    let (before, after) = match (
+        load_analysis(&a, &storage).await,
+        load_analysis(&b, &storage).await,
+    ) {
+        (Ok(before), Ok(after)) => (before, after),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("[reticle changes] Error: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let changes = diff_analyses(&before, &after);
+    if json {
+        match serde_json::to_string_pretty(&changes) {
+            Ok(text) => println!("{text}"),
+            Err(e) => {
+                eprintln!("[reticle changes] Error: {e}");
+                return ExitCode::from(2);
+            }
+        }
+    } else {
+        print!("{}", crate::diff::render_analysis(&changes));
+    }
+
+    if changes.has_differences() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// References to the two most recently analyzed versions of a server,
+/// oldest first
+async fn latest_versions(
+    server: &str,
+    storage: &SessionStorage,
+) -> Result<(String, String), String> {
+    let history = storage
+        .list_analyses(Some(server))
+        .await
+        .map_err(|e| e.to_string())?;
+    match history.as_slice() {
+        [newest, previous, ..] => Ok((
+            format!("{server}@{}", previous.server_version),
+            format!("{server}@{}", newest.server_version),
+        )),
+        _ => Err(format!(
+            "Need two saved versions of '{server}' to compare, found {}",
+            history.len()
+        )),
+    }
+}
+
+/// Load an analysis from a `reticle analyze --format json` file or the
+/// history
+///
+/// History references are `NAME@VERSION`, or `NAME` for the newest
+/// analysis of a server.
+async fn load_analysis(
+    reference: &str,
+    storage: &SessionStorage,
+) -> Result<ServerAnalysis, String> {
+    let file = Path::new(reference);
+    if file.is_file() {
+        let text = std::fs::read_to_string(file).map_err(|e| format!("{reference}: {e}"))?;
+        return serde_json::from_str(&text).map_err(|e| format!("{reference}: {e}"));
+    }
+
+    let (name, version) = match reference.rsplit_once('@') {
+        Some((name, version)) if !name.is_empty() => (name, version.to_string()),
+        _ => {
+            let history = storage
+                .list_analyses(Some(reference))
+                .await
+                .map_err(|e| e.to_string())?;
+            match history.first() {
+                Some(newest) => (reference, newest.server_version.clone()),
+                None => return Err(format!("No saved analysis of '{reference}'")),
+            }
+        }
+    };
+    storage
+        .load_analysis(name, &version)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reticle_core::server_analyzer::{
+        PromptsAnalysis, ResourceTokenInfo, ResourcesAnalysis, ThresholdKind, ToolTokenInfo,
+        ToolsAnalysis,
+    };
+    use reticle_core::tool_lint::{LintFinding, LintRule};
+    use std::collections::HashMap;
+
+    fn analysis() -> ServerAnalysis {
+        ServerAnalysis {
+            server_name: "files".to_string(),
+            server_version: "1.2.0".to_string(),
+            protocol_version: "2024-11-05".to_string(),
+            tokenizer: TokenizerSpec::Cl100kBase,
+            total_context_tokens: 305,
+            tools: ToolsAnalysis {
+                count: 1,
+                total_tokens: 300,
+                tools: vec![ToolTokenInfo {
+                    name: "read|file".to_string(),
+                    description: "Read a file".to_string(),
+                    name_tokens: 2,
+                    description_tokens: 120,
+                    schema_tokens: 178,
+                    total_tokens: 300,
+                    input_schema: None,
+                    output_schema_tokens: 0,
+                    output_schema: None,
+                }],
+            },
+            prompts: PromptsAnalysis::default(),
+            resources: ResourcesAnalysis {
+                count: 1,
+                total_tokens: 5,
+                resources: vec![ResourceTokenInfo {
+                    uri: "file:///etc/hosts".to_string(),
+                    name: "hosts".to_string(),
+                    description: None,
+                    total_tokens: 5,
+                }],
+            },
+            lint: LintReport {
+                findings: vec![
+                    LintFinding {
+                        rule: LintRule::InvalidName,
+                        severity: LintSeverity::Error,
+                        tool: "read|file".to_string(),
+                        path: None,
+                        message: "name contains '|'".to_string(),
+                    },
+                    LintFinding {
+                        rule: LintRule::UndescribedProperty,
+                        severity: LintSeverity::Warning,
+                        tool: "read|file".to_string(),
+                        path: Some("properties.path".to_string()),
+                        message: "property 'path' has no description".to_string(),
+                    },
+                ],
+            },
+            token_breakdown: HashMap::new(),
+            analyzed_at: 0,
+        }
+    }
+
+    fn violations() -> Vec<ThresholdViolation> {
+        vec![ThresholdViolation {
+            kind: ThresholdKind::ToolTokens,
+            tool: Some("read|file".to_string()),
+            limit: 200,
+            actual: 300,
+        }]
+    }
+
+    #[test]
+    fn test_render_text() {
+        let text = render_text(&analysis(), &violations());
+        assert!(text.starts_with("files 1.2.0 (protocol 2024-11-05)\n"));
+        assert!(text.contains("Context cost: 305 tokens (cl100k_base)"));
+        assert!(text.contains("  read|file     300  (description 120, schema 178)"));
+        assert!(text.contains("Prompts (0): 0 tokens"));
+        assert!(text.contains("\nLint: 1 error, 1 warning\n  error read|file: name contains '|'\n"));
+        assert!(text.contains(
+            "  warning read|file (properties.path): property 'path' has no description\n"
+        ));
+        assert!(text.ends_with("FAIL tool 'read|file' tokens 300 > 200\n"));
+
+        assert!(!render_text(&analysis(), &[]).contains("FAIL"));
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = render_markdown(&analysis(), &violations());
+        assert!(markdown.starts_with("## Context cost: files 1.2.0\n"));
+        assert!(markdown.contains("| **Total** | | **305** |"));
+        assert!(markdown.contains("| `read\\|file` | 120 | 178 | 300 |"));
+        assert!(markdown.contains("- tool 'read\\|file' tokens 300 > 200"));
+        assert!(markdown.contains("### Resources"));
+        assert!(!markdown.contains("### Prompts"));
+        assert!(markdown.contains("### Lint: 1 error, 1 warning"));
+        assert!(markdown.contains(
+            "| warning | `read\\|file` | `properties.path` | property 'path' has no description |"
+        ));
+    }
+
+    #[test]
+    fn test_render_history() {
+        let mut older = analysis().info();
+        older.server_version = "1.1.0".to_string();
+        older.analyzed_at = 1_000;
+        let mut newer = analysis().info();
+        newer.analyzed_at = 1_000 + 3 * 86400;
+
+        let text = render_history(&[newer, older], 1_000 + 3 * 86400 + 7200);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "SERVER  VERSION  TOOLS   TOKENS  TOKENIZER     ANALYZED",
+                "files   1.2.0        1      305  cl100k_base   2h ago",
+                "files   1.1.0        1      305  cl100k_base   3d ago",
+            ]
+        );
+        assert_eq!(ago(59), "just now");
+        assert_eq!(ago(600), "10m ago");
+    }
+
+    #[test]
+    fn test_report_json() {
+        let analysis = analysis();
+        let violations = violations();
+        let json = serde_json::to_value(Report {
+            analysis: &analysis,
+            violations: &violations,
+        })
+        .unwrap();
+        assert_eq!(json["total_context_tokens"], 305);
+        assert_eq!(json["tokenizer"], "cl100k_base");
+        assert_eq!(json["violations"][0]["kind"], "tool_tokens");
+        assert_eq!(json["lint"]["findings"][0]["rule"], "invalid_name");
+        assert_eq!(json["lint"]["findings"][1]["severity"], "warning");
+    }
+
+    #[tokio::test]
+    async fn test_load_analysis() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = SessionStorage::new(dir.path().join("recordings.db")).unwrap();
+        let analysis = |version: &str, analyzed_at: u64| -> ServerAnalysis {
+            serde_json::from_value(serde_json::json!({
+                "server_name": "files",
+                "server_version": version,
+                "protocol_version": "2024-11-05",
+                "total_context_tokens": 0,
+                "tools": {"count": 0, "total_tokens": 0, "tools": []},
+                "prompts": {"count": 0, "total_tokens": 0, "prompts": []},
+                "resources": {"count": 0, "total_tokens": 0, "resources": []},
+                "token_breakdown": {},
+                "analyzed_at": analyzed_at
+            }))
+            .unwrap()
+        };
+        save_analysis(&analysis("1.0.0", 1), &db).await;
+        assert!(latest_versions("files", &db).await.is_err());
+        save_analysis(&analysis("1.1.0", 2), &db).await;
+
+        assert_eq!(
+            latest_versions("files", &db).await.unwrap(),
+            ("files@1.0.0".to_string(), "files@1.1.0".to_string())
+        );
+        let newest = load_analysis("files", &db).await.unwrap();
+        assert_eq!(newest.server_version, "1.1.0");
+        let pinned = load_analysis("files@1.0.0", &db).await.unwrap();
+        assert_eq!(pinned.server_version, "1.0.0");
+        assert!(load_analysis("other", &db).await.is_err());
+
+        // `reticle analyze --format json` output loads too
+        let file = dir.path().join("analysis.json");
+        let report = Report {
+            analysis: &pinned,
+            violations: &[],
+        };
+        std::fs::write(&file, serde_json::to_string(&report).unwrap()).unwrap();
+        let loaded = load_analysis(file.to_str().unwrap(), &db).await.unwrap();
+        assert_eq!(loaded.server_version, "1.0.0");
+    }
+}
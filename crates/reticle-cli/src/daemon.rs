@@ -6,25 +6,397 @@
 //!
 //! The daemon receives events via the socket protocol and can:
 //! - Log events to stdout/file
+//! - Record each session and save it to the recordings database, as the
+//!   GUI would, with token statistics per session
+//! - Resume messages held at breakpoints (console commands on stdin)
+//! - Serve an HTTP/JSON query API on `--port` (see [`api`]), including a
+//!   live stream of log entries for any number of subscribers
+//! - Serve a web UI on the same port: a session list, live log view, and
+//!   message inspector built on that API
 //! - Forward to a remote collector
-//! - Serve a simple web UI (future)
 //!
 //! Note: Unix sockets are not available on Windows, so the daemon
 //! functionality is only available on Unix-like systems.
 
+#[cfg(unix)]
+mod api;
+
 #[cfg(unix)]
 mod unix_impl {
-    use std::path::Path;
+    use reticle_core::events::{NoOpEventSink, SocketEvent};
+    use reticle_core::protocol::{Direction, LogEntry, McpMessage, MessageType};
+    use reticle_core::session_recorder::{
+        MessageDirection, RecordedSession, ServerIdentifier, SessionRecorder,
+    };
+    use reticle_core::storage::{SessionInfo, SessionStorage};
+    use reticle_core::token_counter::TokenCounter;
+    use std::collections::{HashMap, HashSet};
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
     use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::unix::OwnedWriteHalf;
     use tokio::net::UnixListener;
+    use tokio::sync::{broadcast, Mutex, RwLock};
     use tracing::{debug, error, info, warn};
 
+    type SharedWriter = Arc<Mutex<OwnedWriteHalf>>;
+
+    /// Log entries a stream subscriber may fall behind by before it misses some
+    const LIVE_CAPACITY: usize = 1024;
+
+    /// A message held at a breakpoint in one of the connected CLI sessions
+    #[derive(Debug, Clone, PartialEq)]
+    pub(super) struct PendingHold {
+        pub(super) session_id: String,
+        pub(super) hold_id: String,
+    }
+
+    /// State shared between client connections and the console
+    pub(super) struct DaemonState {
+        /// Map of session_id → write half of the CLI connection
+        pub(super) sessions: RwLock<HashMap<String, SharedWriter>>,
+        /// Messages currently held at breakpoints
+        pub(super) holds: RwLock<Vec<PendingHold>>,
+        /// Recordings of the sessions in progress, by session ID
+        pub(super) recordings: RwLock<HashMap<String, SessionRecorder>>,
+        /// Sessions that have ended on connections still open, so log
+        /// events arriving late don't start a new recording
+        pub(super) finished: RwLock<HashSet<String>>,
+        /// Token statistics of the sessions in progress
+        pub(super) tokens: TokenCounter,
+        /// Recordings database, unless sessions are not saved
+        pub(super) storage: Option<SessionStorage>,
+        /// Log entries from every connected CLI, as they arrive
+        pub(super) live: broadcast::Sender<Arc<LogEntry>>,
+    }
+
+    impl Default for DaemonState {
+        fn default() -> Self {
+            Self {
+                sessions: RwLock::default(),
+                holds: RwLock::default(),
+                recordings: RwLock::default(),
+                finished: RwLock::default(),
+                tokens: TokenCounter::default(),
+                storage: None,
+                live: broadcast::channel(LIVE_CAPACITY).0,
+            }
+        }
+    }
+
+    impl DaemonState {
+        pub(super) fn new(storage: Option<SessionStorage>) -> Self {
+            Self {
+                storage,
+                ..Self::default()
+            }
+        }
+
+        /// Start recording a session
+        pub(super) async fn start_recording(
+            &self,
+            session_id: &str,
+            session_name: &str,
+            server_name: &str,
+        ) {
+            self.finished.write().await.remove(session_id);
+            self.recordings.write().await.insert(
+                session_id.to_string(),
+                new_recorder(session_id, session_name, server_name),
+            );
+        }
+
+        /// Pass a log event on to stream subscribers, if there are any
+        pub(super) fn publish(&self, event: &SocketEvent) {
+            if self.live.receiver_count() == 0 {
+                return;
+            }
+            if let Some(entry) = log_entry(event) {
+                // Subscribers may all have gone since the check
+                let _ = self.live.send(Arc::new(entry));
+            }
+        }
+
+        /// Record a log event in its session and count its tokens
+        ///
+        /// Only JSON-RPC messages are recorded, as in the GUI; stderr and
+        /// other raw output is not.
+        pub(super) async fn record_log(&self, server_name: &str, event: &SocketEvent) {
+            let SocketEvent::Log {
+                id,
+                session_id,
+                direction,
+                content,
+                message_type,
+                original,
+                ..
+            } = event
+            else {
+                return;
+            };
+            if message_type != "jsonrpc" {
+                return;
+            }
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+                return;
+            };
+            let direction = match direction.as_str() {
+                "in" => MessageDirection::ToServer,
+                _ => MessageDirection::ToClient,
+            };
+
+            if self.finished.read().await.contains(session_id) {
+                return;
+            }
+            // Sessions that started before the daemon are recorded from here on
+            let recorder = self
+                .recordings
+                .write()
+                .await
+                .entry(session_id.clone())
+                .or_insert_with(|| new_recorder(session_id, session_id, server_name))
+                .clone();
+            let original = original
+                .as_deref()
+                .and_then(|original| serde_json::from_str(original).ok());
+            // Keep the log entry's ID so the web UI can match recorded
+            // messages against the ones it streams
+            let recorded = recorder
+                .record_logged_message(id.clone(), json.clone(), original, direction)
+                .await;
+            if let Err(e) = recorded {
+                warn!("[{}] Failed to record {}: {}", server_name, id, e);
+            }
+
+            let is_request = direction == MessageDirection::ToServer;
+            self.tokens
+                .record_message(
+                    &NoOpEventSink,
+                    session_id,
+                    id,
+                    &McpMessage::parse(&json),
+                    is_request,
+                )
+                .await;
+        }
+
+        /// Stop recording a session and save it
+        ///
+        /// Returns the session, or `None` if it was not being recorded.
+        pub(super) async fn finish_recording(
+            &self,
+            server_name: &str,
+            session_id: &str,
+        ) -> Option<RecordedSession> {
+            self.finished.write().await.insert(session_id.to_string());
+            let recorder = self.recordings.write().await.remove(session_id)?;
+            let tokens = self
+                .tokens
+                .get_session_stats(session_id)
+                .await
+                .map(|stats| stats.total_tokens)
+                .unwrap_or_default();
+            self.tokens.clear_session(session_id).await;
+            let session = match recorder.finalize().await {
+                Ok(session) => session,
+                Err(e) => {
+                    error!(
+                        "[{}] Failed to finish recording {}: {}",
+                        server_name, session_id, e
+                    );
+                    return None;
+                }
+            };
+
+            let summary = format!(
+                "{} ({} messages, {} tokens)",
+                session.name,
+                session.messages.len(),
+                tokens
+            );
+            // Like the GUI, don't keep sessions that never said anything
+            if session.messages.is_empty() {
+                info!("[{}] Discarding empty session {}", server_name, summary);
+                return Some(session);
+            }
+            match self.save(&session).await {
+                Ok(true) => info!("[{}] Saved session {}", server_name, summary),
+                Ok(false) => info!("[{}] Finished session {}", server_name, summary),
+                Err(e) => error!(
+                    "[{}] Failed to save session {}: {}",
+                    server_name, summary, e
+                ),
+            }
+            Some(session)
+        }
+
+        /// Finish every recording, as the daemon shuts down
+        pub(super) async fn finish_all(&self) {
+            let session_ids: Vec<String> = self.recordings.read().await.keys().cloned().collect();
+            for session_id in session_ids {
+                self.finish_recording("daemon", &session_id).await;
+            }
+        }
+
+        /// Save a session to the recordings database, if there is one
+        async fn save(&self, session: &RecordedSession) -> Result<bool, String> {
+            let Some(ref storage) = self.storage else {
+                return Ok(false);
+            };
+            storage
+                .save_session(session)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(true)
+        }
+
+        /// Find a pending hold by `hold_id` or `session_id/hold_id`
+        pub(super) async fn find_hold(&self, reference: &str) -> Result<PendingHold, String> {
+            let holds = self.holds.read().await;
+            let matches: Vec<&PendingHold> = match reference.split_once('/') {
+                Some((session_id, hold_id)) => holds
+                    .iter()
+                    .filter(|h| h.session_id == session_id && h.hold_id == hold_id)
+                    .collect(),
+                None => holds.iter().filter(|h| h.hold_id == reference).collect(),
+            };
+            match matches.as_slice() {
+                [hold] => Ok((*hold).clone()),
+                [] => Err(format!("No held message '{reference}'")),
+                _ => Err(format!(
+                    "'{reference}' is held in several sessions, use <session_id>/<hold_id>"
+                )),
+            }
+        }
+
+        /// Send a resume command for a held message to its CLI session
+        pub(super) async fn resume(
+            &self,
+            reference: &str,
+            message: Option<String>,
+            drop: bool,
+        ) -> Result<(), String> {
+            let hold = self.find_hold(reference).await?;
+            let event = SocketEvent::ResumeMessage {
+                session_id: hold.session_id.clone(),
+                hold_id: hold.hold_id,
+                message,
+                drop,
+            };
+            self.send_event(&hold.session_id, &event).await
+        }
+
+        /// Send a message to a live CLI session, to forward to its server
+        pub(super) async fn send_to_session(
+            &self,
+            session_id: &str,
+            message: &str,
+        ) -> Result<(), String> {
+            let event = SocketEvent::InjectMessage {
+                session_id: session_id.to_string(),
+                message: message.to_string(),
+            };
+            self.send_event(session_id, &event).await?;
+            info!("Sent inject_message to session {}", session_id);
+            Ok(())
+        }
+
+        /// Write an event to a CLI session's connection
+        async fn send_event(&self, session_id: &str, event: &SocketEvent) -> Result<(), String> {
+            let writer = self
+                .sessions
+                .read()
+                .await
+                .get(session_id)
+                .cloned()
+                .ok_or_else(|| format!("Session {session_id} is not connected"))?;
+
+            let mut json = serde_json::to_string(event)
+                .map_err(|e| format!("Failed to serialize event: {e}"))?;
+            json.push('\n');
+
+            let mut writer = writer.lock().await;
+            writer
+                .write_all(json.as_bytes())
+                .await
+                .map_err(|e| format!("Failed to send event: {e}"))?;
+            writer
+                .flush()
+                .await
+                .map_err(|e| format!("Failed to flush: {e}"))
+        }
+
+        /// Sessions saved to the recordings database, newest first
+        pub(super) async fn recorded_sessions(&self) -> Result<Vec<SessionInfo>, String> {
+            let Some(ref storage) = self.storage else {
+                return Ok(Vec::new());
+            };
+            storage.list_sessions().await.map_err(|e| e.to_string())
+        }
+
+        /// A session, from its recording in progress or the database
+        ///
+        /// Returns the session and whether it is still live, or `None` if
+        /// there is no such session.
+        pub(super) async fn find_session(
+            &self,
+            session_id: &str,
+        ) -> Result<Option<(RecordedSession, bool)>, String> {
+            let recorder = self.recordings.read().await.get(session_id).cloned();
+            if let Some(recorder) = recorder {
+                let session = recorder.snapshot().await.map_err(|e| e.to_string())?;
+                return Ok(Some((session, true)));
+            }
+
+            let Some(ref storage) = self.storage else {
+                return Ok(None);
+            };
+            // Loading fails the same way for a missing session as a broken one
+            Ok(storage
+                .load_session(session_id)
+                .await
+                .ok()
+                .map(|session| (session, false)))
+        }
+    }
+
+    fn open_storage(path: &Path) -> Result<SessionStorage, String> {
+        let storage = SessionStorage::new(path.to_path_buf())
+            .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+        info!("Saving sessions to {}", path.display());
+        Ok(storage)
+    }
+
+    /// Start a recording for a session from a CLI instance
+    ///
+    /// The socket protocol doesn't say which transport the CLI wraps.
+    fn new_recorder(session_id: &str, session_name: &str, server_name: &str) -> SessionRecorder {
+        SessionRecorder::with_server(
+            session_id.to_string(),
+            session_name.to_string(),
+            "unknown".to_string(),
+            ServerIdentifier {
+                name: server_name.to_string(),
+                version: None,
+                command: String::new(),
+                args: Vec::new(),
+                connection_type: "unknown".to_string(),
+            },
+        )
+    }
+
     /// Run the daemon, listening on the specified Unix socket
+    ///
+    /// Sessions are saved to `storage` as they end, and sessions still
+    /// running are saved when the daemon is stopped.
     pub async fn run_daemon(
         socket_path: &str,
-        _port: Option<u16>,
+        port: Option<u16>,
         verbose: bool,
+        storage: Option<PathBuf>,
     ) -> Result<(), String> {
+        let storage = storage.as_deref().map(open_storage).transpose()?;
+
         // Remove existing socket file if it exists
         let path = Path::new(socket_path);
         if path.exists() {
@@ -46,27 +418,68 @@ mod unix_impl {
 
         info!("Daemon listening on {}", socket_path);
 
+        let state = Arc::new(DaemonState::new(storage));
+        tokio::spawn(run_console(state.clone()));
+
+        if let Some(port) = port {
+            let addr = format!("127.0.0.1:{port}");
+            let api = tokio::net::TcpListener::bind(&addr)
+                .await
+                .map_err(|e| format!("Failed to bind to {addr}: {e}"))?;
+            info!("API listening on http://{}", addr);
+            let router = super::api::router(state.clone(), port);
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(api, router).await {
+                    error!("API server error: {e}");
+                }
+            });
+        }
+
+        let shutdown = shutdown_signal();
+        tokio::pin!(shutdown);
+
         // Accept connections
         loop {
-            match listener.accept().await {
-                Ok((stream, _addr)) => {
-                    tokio::spawn(async move {
-                        if let Err(e) = handle_connection(stream, verbose).await {
-                            warn!("Connection error: {e}");
-                        }
-                    });
-                }
-                Err(e) => {
-                    error!("Accept error: {e}");
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _addr)) => {
+                        let state = state.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_connection(stream, verbose, state).await {
+                                warn!("Connection error: {e}");
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        error!("Accept error: {e}");
+                    }
+                },
+                _ = &mut shutdown => {
+                    info!("Shutting down, saving sessions in progress");
+                    state.finish_all().await;
+                    let _ = std::fs::remove_file(path);
+                    return Ok(());
                 }
             }
         }
     }
 
+    /// Wait for SIGINT or SIGTERM
+    async fn shutdown_signal() {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
+        let mut sigint = signal(SignalKind::interrupt()).expect("SIGINT handler");
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = sigint.recv() => {}
+        }
+    }
+
     /// Handle a single client connection
     async fn handle_connection(
         stream: tokio::net::UnixStream,
         verbose: bool,
+        state: Arc<DaemonState>,
     ) -> Result<(), String> {
         let (reader, mut writer) = stream.into_split();
         let mut reader = BufReader::new(reader);
@@ -78,7 +491,20 @@ mod unix_impl {
             .await
             .map_err(|e| format!("Failed to read server name: {e}"))?;
 
-        let server_name = line.trim().to_string();
+        // CLI sinks start straight away with a JSON event instead of a name
+        let mut pending_first = None;
+        let server_name = match serde_json::from_str::<serde_json::Value>(line.trim()) {
+            Ok(event) => {
+                let name = event
+                    .get("server_name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+                pending_first = Some(line.clone());
+                name
+            }
+            Err(_) => line.trim().to_string(),
+        };
         info!("Client connected: {}", server_name);
         line.clear();
 
@@ -88,10 +514,20 @@ mod unix_impl {
             .await
             .map_err(|e| format!("Failed to send ack: {e}"))?;
 
+        let writer: SharedWriter = Arc::new(Mutex::new(writer));
+        let mut connection_sessions: Vec<String> = Vec::new();
+
         // Process events
         loop {
             line.clear();
-            match reader.read_line(&mut line).await {
+            let read = match pending_first.take() {
+                Some(first) => {
+                    line = first;
+                    Ok(line.len())
+                }
+                None => reader.read_line(&mut line).await,
+            };
+            match read {
                 Ok(0) => {
                     // EOF - client disconnected
                     info!("Client disconnected: {}", server_name);
@@ -115,46 +551,23 @@ mod unix_impl {
                             debug!("[{}] Event: {}", server_name, trimmed);
                         }
 
-                        // Handle different event types
-                        if let Some(event_type) = event.get("type").and_then(|t| t.as_str()) {
-                            match event_type {
-                                "session_start" => {
-                                    let name = event
-                                        .get("name")
-                                        .and_then(|n| n.as_str())
-                                        .unwrap_or("unknown");
-                                    info!("[{}] Session started: {}", server_name, name);
-                                }
-                                "session_end" => {
-                                    info!("[{}] Session ended", server_name);
-                                }
-                                "log" => {
-                                    if verbose {
-                                        let method = event
-                                            .get("method")
-                                            .and_then(|m| m.as_str())
-                                            .unwrap_or("-");
-                                        let direction = event
-                                            .get("direction")
-                                            .and_then(|d| d.as_str())
-                                            .unwrap_or("-");
-                                        println!(
-                                            "[{}] {} {} {}",
-                                            server_name,
-                                            if direction == "in" { "→" } else { "←" },
-                                            method,
-                                            event
-                                                .get("content")
-                                                .and_then(|c| c.as_str())
-                                                .unwrap_or("")
-                                        );
-                                    }
-                                }
-                                _ => {
-                                    debug!("[{}] Unknown event type: {}", server_name, event_type);
-                                }
+                        // Remember which connection owns each session so
+                        // console commands can be routed back to it
+                        if let Some(session_id) = event.get("session_id").and_then(|s| s.as_str()) {
+                            if !connection_sessions.iter().any(|s| s == session_id) {
+                                connection_sessions.push(session_id.to_string());
+                                state
+                                    .sessions
+                                    .write()
+                                    .await
+                                    .insert(session_id.to_string(), writer.clone());
                             }
                         }
+
+                        match serde_json::from_value::<SocketEvent>(event) {
+                            Ok(event) => handle_event(&state, &server_name, event, verbose).await,
+                            Err(e) => debug!("[{}] Unknown event: {}", server_name, e),
+                        }
                     } else {
                         warn!("[{}] Invalid JSON: {}", server_name, trimmed);
                     }
@@ -166,12 +579,244 @@ mod unix_impl {
             }
         }
 
+        // Save sessions whose CLI went away without ending them
+        for session_id in &connection_sessions {
+            state.finish_recording(&server_name, session_id).await;
+        }
+
+        // Forget sessions and holds owned by this connection
+        let mut sessions = state.sessions.write().await;
+        for session_id in &connection_sessions {
+            sessions.remove(session_id);
+        }
+        drop(sessions);
+        let mut finished = state.finished.write().await;
+        for session_id in &connection_sessions {
+            finished.remove(session_id);
+        }
+        drop(finished);
+        state
+            .holds
+            .write()
+            .await
+            .retain(|h| !connection_sessions.contains(&h.session_id));
+
         Ok(())
     }
+
+    /// Act on one event from a CLI instance
+    pub(super) async fn handle_event(
+        state: &DaemonState,
+        server_name: &str,
+        event: SocketEvent,
+        verbose: bool,
+    ) {
+        match event {
+            SocketEvent::SessionStarted {
+                ref session_id,
+                ref session_name,
+                server_name: ref name,
+            } => {
+                info!(
+                    "[{}] Session started: {} ({})",
+                    server_name, session_name, session_id
+                );
+                state.start_recording(session_id, session_name, name).await;
+            }
+            SocketEvent::SessionEnded { ref session_id } => {
+                info!("[{}] Session ended: {}", server_name, session_id);
+                state.finish_recording(server_name, session_id).await;
+            }
+            SocketEvent::Log {
+                ref direction,
+                ref method,
+                ref content,
+                ..
+            } => {
+                if verbose {
+                    println!(
+                        "[{}] {} {} {}",
+                        server_name,
+                        arrow(direction),
+                        method.as_deref().unwrap_or("-"),
+                        content
+                    );
+                }
+                state.publish(&event);
+                state.record_log(server_name, &event).await;
+            }
+            SocketEvent::BreakpointHit {
+                session_id,
+                hold_id,
+                breakpoint_id,
+                direction,
+                content,
+                method,
+                ..
+            } => {
+                println!(
+                    "[{server_name}] Breakpoint {breakpoint_id} hit: {} {} (hold {hold_id})",
+                    arrow(&direction),
+                    method.as_deref().unwrap_or("-"),
+                );
+                println!("  {content}");
+                println!("  continue {0} | drop {0} | edit {0} <json>", hold_id);
+                state.holds.write().await.push(PendingHold {
+                    session_id,
+                    hold_id,
+                });
+            }
+            SocketEvent::BreakpointReleased {
+                session_id,
+                hold_id,
+                outcome,
+            } => {
+                println!("[{server_name}] Released {hold_id} ({outcome})");
+                let hold = PendingHold {
+                    session_id,
+                    hold_id,
+                };
+                state.holds.write().await.retain(|h| *h != hold);
+            }
+            SocketEvent::Custom { event_name, .. } => {
+                debug!("[{}] Event: {}", server_name, event_name);
+            }
+            SocketEvent::InjectMessage { .. } | SocketEvent::ResumeMessage { .. } => {
+                debug!("[{}] Ignoring a command sent to the daemon", server_name);
+            }
+        }
+    }
+
+    /// The log entry a CLI sent as a `log` event
+    pub fn log_entry(event: &SocketEvent) -> Option<LogEntry> {
+        let SocketEvent::Log {
+            id,
+            session_id,
+            timestamp,
+            direction,
+            content,
+            method,
+            server_name,
+            message_type,
+            token_count,
+            duration_micros,
+            modified,
+            original,
+            request_log_id,
+        } = event
+        else {
+            return None;
+        };
+        let direction = match direction.as_str() {
+            "in" => Direction::In,
+            "out" => Direction::Out,
+            _ => return None,
+        };
+        let message_type: MessageType =
+            serde_json::from_value(serde_json::Value::String(message_type.clone()))
+                .unwrap_or_default();
+        let message = (message_type == MessageType::JsonRpc)
+            .then(|| serde_json::from_str(content).ok())
+            .flatten()
+            .map(|json| McpMessage::parse(&json));
+
+        Some(LogEntry {
+            id: id.clone(),
+            session_id: session_id.clone(),
+            timestamp: *timestamp,
+            direction,
+            content: content.clone(),
+            method: method.clone(),
+            duration_micros: *duration_micros,
+            message_type,
+            token_count: *token_count,
+            server_name: Some(server_name.clone()),
+            modified: *modified,
+            original: original.clone(),
+            message,
+            request_log_id: request_log_id.clone(),
+        })
+    }
+
+    fn arrow(direction: &str) -> &'static str {
+        if direction == "in" {
+            "→"
+        } else {
+            "←"
+        }
+    }
+
+    /// Read operator commands from stdin
+    ///
+    /// Commands:
+    /// - `holds` - list held messages
+    /// - `continue <hold>` - forward a held message unchanged
+    /// - `edit <hold> <json>` - forward an edited message
+    /// - `drop <hold>` - discard a held message
+    ///
+    /// `<hold>` is a hold ID, or `<session_id>/<hold_id>` when ambiguous.
+    async fn run_console(state: Arc<DaemonState>) {
+        // Blocking stdin reads live on a plain thread so they never hold up
+        // runtime shutdown the way tokio's stdin would
+        let (line_tx, mut line_rx) = tokio::sync::mpsc::channel::<String>(16);
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lines().map_while(Result::ok) {
+                if line_tx.blocking_send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(line) = line_rx.recv().await {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim();
+            let result = match command {
+                "holds" => {
+                    let holds = state.holds.read().await;
+                    if holds.is_empty() {
+                        println!("No held messages");
+                    }
+                    for hold in holds.iter() {
+                        println!("{}/{}", hold.session_id, hold.hold_id);
+                    }
+                    Ok(())
+                }
+                "continue" | "c" => state.resume(rest, None, false).await,
+                "drop" => state.resume(rest, None, true).await,
+                "edit" => match rest.split_once(' ') {
+                    Some((reference, message)) => {
+                        match serde_json::from_str::<serde_json::Value>(message) {
+                            Ok(_) => {
+                                state
+                                    .resume(reference, Some(message.trim().to_string()), false)
+                                    .await
+                            }
+                            Err(e) => Err(format!("Invalid JSON: {e}")),
+                        }
+                    }
+                    None => Err("Usage: edit <hold> <json>".to_string()),
+                },
+                other => Err(format!(
+                    "Unknown command '{other}' (holds, continue, edit, drop)"
+                )),
+            };
+
+            if let Err(e) = result {
+                eprintln!("{e}");
+            }
+        }
+
+        debug!("Daemon console closed");
+    }
 }
 
 #[cfg(unix)]
-pub use unix_impl::run_daemon;
+pub use unix_impl::{log_entry, run_daemon};
 
 /// Windows stub - daemon is not supported on Windows
 #[cfg(windows)]
@@ -179,6 +824,7 @@ pub async fn run_daemon(
     _socket_path: &str,
     _port: Option<u16>,
     _verbose: bool,
+    _storage: Option<std::path::PathBuf>,
 ) -> Result<(), String> {
     Err("The daemon command is not supported on Windows. Unix sockets are required.".to_string())
 }
@@ -213,7 +859,7 @@ mod tests {
         // Run daemon in background, it will block so we just test socket creation
         let handle = tokio::spawn(async move {
             // This will run until cancelled
-            let _ = run_daemon(&socket_path_str, None, false).await;
+            let _ = run_daemon(&socket_path_str, None, false, None).await;
         });
 
         // Give daemon time to start
@@ -238,7 +884,7 @@ mod tests {
         let socket_path_str = socket_path.to_str().unwrap().to_string();
 
         let handle = tokio::spawn(async move {
-            let _ = run_daemon(&socket_path_str, None, false).await;
+            let _ = run_daemon(&socket_path_str, None, false, None).await;
         });
 
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -256,7 +902,7 @@ mod tests {
         let socket_path_str = socket_path.to_str().unwrap().to_string();
 
         let handle = tokio::spawn(async move {
-            let _ = run_daemon(&socket_path_str, None, false).await;
+            let _ = run_daemon(&socket_path_str, None, false, None).await;
         });
 
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -270,19 +916,105 @@ mod tests {
 
     #[test]
     fn test_event_type_parsing() {
+        // The event names the CLI sinks actually send
         let event: serde_json::Value = serde_json::json!({
-            "type": "session_start",
-            "name": "test-session"
+            "type": "session_started",
+            "session_id": "s1",
+            "session_name": "test-session",
+            "server_name": "github"
         });
 
-        assert_eq!(
-            event.get("type").and_then(|t| t.as_str()),
-            Some("session_start")
-        );
-        assert_eq!(
-            event.get("name").and_then(|n| n.as_str()),
-            Some("test-session")
+        match serde_json::from_value(event).unwrap() {
+            reticle_core::events::SocketEvent::SessionStarted { session_name, .. } => {
+                assert_eq!(session_name, "test-session");
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+        let ended = serde_json::json!({"type": "session_ended", "session_id": "s1"});
+        assert!(serde_json::from_value::<reticle_core::events::SocketEvent>(ended).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_daemon_records_sessions() {
+        use reticle_core::events::SocketEvent;
+        use reticle_core::storage::SessionStorage;
+        use unix_impl::{handle_event, DaemonState};
+
+        let dir = tempdir().unwrap();
+        let db = dir.path().join("recordings.db");
+        let state = DaemonState::new(Some(SessionStorage::new(db).unwrap()));
+
+        let event = |json: serde_json::Value| serde_json::from_value::<SocketEvent>(json).unwrap();
+        let log = |id: &str, direction: &str, content: serde_json::Value, message_type: &str| {
+            event(serde_json::json!({
+                "type": "log",
+                "id": id,
+                "session_id": "s1",
+                "timestamp": 0,
+                "direction": direction,
+                "content": content.to_string(),
+                "method": content.get("method"),
+                "server_name": "github",
+                "message_type": message_type,
+                "token_count": 0
+            }))
+        };
+
+        let events = [
+            event(serde_json::json!({
+                "type": "session_started",
+                "session_id": "s1",
+                "session_name": "brave-otter",
+                "server_name": "github"
+            })),
+            log(
+                "log-1",
+                "in",
+                serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+                "jsonrpc",
+            ),
+            log(
+                "log-2",
+                "out",
+                serde_json::json!("server starting"),
+                "stderr",
+            ),
+            log(
+                "log-3",
+                "out",
+                serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": []}}),
+                "jsonrpc",
+            ),
+        ];
+        for event in events {
+            handle_event(&state, "github", event, false).await;
+        }
+        let stats = state.tokens.get_session_stats("s1").await.unwrap();
+        assert!(stats.total_tokens > 0);
+
+        let ended = event(serde_json::json!({"type": "session_ended", "session_id": "s1"}));
+        handle_event(&state, "github", ended, false).await;
+        assert!(state.recordings.read().await.is_empty());
+        // Ended sessions keep no token statistics in memory
+        assert!(state.tokens.get_session_stats("s1").await.is_none());
+
+        // A message arriving after the end doesn't start a new recording
+        let late = log(
+            "log-4",
+            "in",
+            serde_json::json!({"jsonrpc": "2.0", "method": "notifications/cancelled"}),
+            "jsonrpc",
         );
+        handle_event(&state, "github", late, false).await;
+        assert!(state.recordings.read().await.is_empty());
+        assert!(state.tokens.get_session_stats("s1").await.is_none());
+
+        let storage = state.storage.as_ref().unwrap();
+        let session = storage.load_session("s1").await.unwrap();
+        assert_eq!(session.name, "brave-otter");
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[0].id, "log-1");
+        assert_eq!(session.metadata.server_id.unwrap().name, "github");
     }
 
     #[test]
@@ -302,6 +1034,67 @@ mod tests {
         assert_eq!(event.get("direction").and_then(|d| d.as_str()), Some("in"));
     }
 
+    #[tokio::test]
+    async fn test_resume_routes_to_owning_session() {
+        use tokio::io::AsyncBufReadExt;
+        use unix_impl::{DaemonState, PendingHold};
+
+        let (daemon_end, cli_end) = tokio::net::UnixStream::pair().unwrap();
+        let (_, writer) = daemon_end.into_split();
+
+        let state = DaemonState::default();
+        state.sessions.write().await.insert(
+            "session-1".to_string(),
+            std::sync::Arc::new(tokio::sync::Mutex::new(writer)),
+        );
+        state.holds.write().await.push(PendingHold {
+            session_id: "session-1".to_string(),
+            hold_id: "log-3".to_string(),
+        });
+
+        state
+            .resume("log-3", Some(r#"{"id":1}"#.to_string()), false)
+            .await
+            .unwrap();
+
+        let mut lines = tokio::io::BufReader::new(cli_end).lines();
+        let line = lines.next_line().await.unwrap().unwrap();
+        match serde_json::from_str(&line).unwrap() {
+            reticle_core::events::SocketEvent::ResumeMessage {
+                session_id,
+                hold_id,
+                message,
+                drop,
+            } => {
+                assert_eq!(session_id, "session-1");
+                assert_eq!(hold_id, "log-3");
+                assert_eq!(message.as_deref(), Some(r#"{"id":1}"#));
+                assert!(!drop);
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_hold_ambiguous() {
+        use unix_impl::{DaemonState, PendingHold};
+
+        let state = DaemonState::default();
+        for session_id in ["a", "b"] {
+            state.holds.write().await.push(PendingHold {
+                session_id: session_id.to_string(),
+                hold_id: "log-1".to_string(),
+            });
+        }
+
+        assert!(state.find_hold("log-1").await.is_err());
+        assert!(state.find_hold("log-2").await.is_err());
+        let hold = state.find_hold("b/log-1").await.unwrap();
+        assert_eq!(hold.session_id, "b");
+        // Resuming a hold whose session is gone reports an error
+        assert!(state.resume("a/log-1", None, true).await.is_err());
+    }
+
     #[test]
     fn test_invalid_json_handling() {
         let result = serde_json::from_str::<serde_json::Value>("not valid json");
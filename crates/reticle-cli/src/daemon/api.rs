@@ -0,0 +1,849 @@
+//! HTTP/JSON query API and web UI
+//!
+//! Served on `127.0.0.1` when the daemon is started with `--port`, for
+//! dashboards and scripts that want Reticle's data without the GUI:
+//!
+//! - `GET /` - a web UI over the routes below, for inspecting traffic from
+//!   a browser where the GUI can't run (remote containers, SSH hosts)
+//! - `GET /api/sessions` - live and recorded sessions
+//! - `GET /api/sessions/{id}/messages` - a session's messages, a page at a
+//!   time (`offset`, `limit`), optionally filtered by `direction` (`in` or
+//!   `out`) and `method`
+//! - `GET /api/sessions/{id}/stats` - token and latency statistics
+//! - `GET /api/sessions/{id}/export` - the whole session as JSON, in the
+//!   format `reticle replay` and the GUI import
+//! - `POST /api/sessions/{id}/inject` - send the JSON-RPC message in the
+//!   body (`Content-Type: application/json`) to a live session's server
+//! - `GET /api/stream` - log entries from every connected CLI as they
+//!   arrive, as server-sent `log` events, optionally filtered by `server`,
+//!   `session`, `method`, and `direction`. A subscriber that falls too far
+//!   behind is sent a `lagged` event with the number of entries it missed.
+//!
+//! Requests must name the daemon as `127.0.0.1:<port>` or `localhost:<port>`
+//! in `Host`, and any `Origin` must be the daemon itself, so web pages on
+//! other sites can't reach the API through the browser.
+//!
+//! Errors come back as `{"error": "..."}` with a matching status code.
+
+use axum::{
+    extract::{rejection::JsonRejection, Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Response,
+    },
+    routing::{get, post},
+    Json, Router,
+};
+use futures::Stream;
+use reticle_core::events::NoOpEventSink;
+use reticle_core::latency::SessionLatencyStats;
+use reticle_core::protocol::{Direction, LogEntry, McpMessage};
+use reticle_core::session_recorder::{MessageDirection, RecordedMessage, RecordedSession};
+use reticle_core::storage::SessionInfo;
+use reticle_core::token_counter::{SessionTokenStats, TokenCounter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use super::unix_impl::DaemonState;
+
+/// The web UI, a single page with no external assets
+const UI: &str = include_str!("ui.html");
+
+/// Page size when a request doesn't ask for one
+const DEFAULT_LIMIT: usize = 100;
+/// Largest page a request can ask for
+const MAX_LIMIT: usize = 1000;
+/// Requests a stream subscriber waits on responses for before forgetting them
+const MAX_TRACKED_REQUESTS: usize = 1024;
+
+/// The API's routes over the daemon's state, served on `port`
+pub(super) fn router(state: Arc<DaemonState>, port: u16) -> Router {
+    Router::new()
+        .route("/", get(|| async { Html(UI) }))
+        .route("/health", get(|| async { "OK" }))
+        .route("/api/sessions", get(list_sessions))
+        .route("/api/sessions/:id/messages", get(session_messages))
+        .route("/api/sessions/:id/stats", get(session_stats))
+        .route("/api/sessions/:id/export", get(export_session))
+        .route("/api/sessions/:id/inject", post(inject_message))
+        .route("/api/stream", get(stream_logs))
+        .layer(middleware::from_fn_with_state(port, check_origin))
+        .with_state(state)
+}
+
+/// Refuse requests addressed to another host or sent from another origin
+///
+/// A foreign `Host` means the request came through DNS rebinding, and a
+/// foreign `Origin` a page on another site; neither should reach sessions.
+async fn check_origin(
+    State(port): State<u16>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let local = [format!("127.0.0.1:{port}"), format!("localhost:{port}")];
+    let is_local = |authority: &str| local.iter().any(|local| local == authority);
+
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok());
+    if !host.is_some_and(is_local) {
+        return Err(ApiError(
+            StatusCode::FORBIDDEN,
+            format!("Host {} not allowed", host.unwrap_or("(none)")),
+        ));
+    }
+    if let Some(origin) = request.headers().get(header::ORIGIN) {
+        let origin = origin.to_str().unwrap_or_default();
+        if !origin.strip_prefix("http://").is_some_and(is_local) {
+            return Err(ApiError(
+                StatusCode::FORBIDDEN,
+                format!("Origin {origin} not allowed"),
+            ));
+        }
+    }
+    Ok(next.run(request).await)
+}
+
+/// An error response
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn internal(message: String) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+
+    fn not_found(session_id: &str) -> Self {
+        Self(StatusCode::NOT_FOUND, format!("No session {session_id}"))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+/// A session the daemon is recording
+#[derive(Debug, Serialize, Deserialize)]
+struct LiveSession {
+    id: String,
+    name: String,
+    server_name: Option<String>,
+    message_count: usize,
+    duration_seconds: u64,
+    /// Whether its CLI is connected, so messages can be injected
+    connected: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionList {
+    live: Vec<LiveSession>,
+    recorded: Vec<SessionInfo>,
+}
+
+async fn list_sessions(
+    State(state): State<Arc<DaemonState>>,
+) -> Result<Json<SessionList>, ApiError> {
+    let recorders: Vec<_> = state.recordings.read().await.values().cloned().collect();
+    let connected = state.sessions.read().await;
+    let mut live = Vec::new();
+    for recorder in recorders {
+        let stats = recorder.get_stats().await;
+        live.push(LiveSession {
+            connected: connected.contains_key(recorder.session_id()),
+            id: stats.session_id,
+            name: recorder.session_name().to_string(),
+            server_name: recorder.get_server_id().map(|id| id.name.clone()),
+            message_count: stats.message_count,
+            duration_seconds: stats.duration_seconds,
+        });
+    }
+    drop(connected);
+    live.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let recorded = state
+        .recorded_sessions()
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(Json(SessionList { live, recorded }))
+}
+
+async fn find_session(
+    state: &DaemonState,
+    session_id: &str,
+) -> Result<(RecordedSession, bool), ApiError> {
+    state
+        .find_session(session_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::not_found(session_id))
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageQuery {
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+    direction: Option<Direction>,
+    method: Option<String>,
+}
+
+/// One page of a session's messages
+#[derive(Debug, Serialize, Deserialize)]
+struct MessagePage {
+    session_id: String,
+    live: bool,
+    /// Messages matching the filters, across all pages
+    total: usize,
+    offset: usize,
+    limit: usize,
+    messages: Vec<RecordedMessage>,
+}
+
+async fn session_messages(
+    State(state): State<Arc<DaemonState>>,
+    Path(session_id): Path<String>,
+    Query(query): Query<MessageQuery>,
+) -> Result<Json<MessagePage>, ApiError> {
+    let (session, live) = find_session(&state, &session_id).await?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let matching: Vec<RecordedMessage> = session
+        .messages
+        .into_iter()
+        .filter(|m| {
+            query
+                .direction
+                .is_none_or(|d| Direction::from(m.direction) == d)
+        })
+        .filter(|m| {
+            query
+                .method
+                .as_ref()
+                .is_none_or(|method| m.metadata.method.as_ref() == Some(method))
+        })
+        .collect();
+    let total = matching.len();
+    let messages = matching
+        .into_iter()
+        .skip(query.offset)
+        .take(limit)
+        .collect();
+
+    Ok(Json(MessagePage {
+        session_id,
+        live,
+        total,
+        offset: query.offset,
+        limit,
+        messages,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionStats {
+    session_id: String,
+    live: bool,
+    tokens: SessionTokenStats,
+    latency: SessionLatencyStats,
+}
+
+async fn session_stats(
+    State(state): State<Arc<DaemonState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<SessionStats>, ApiError> {
+    let (session, live) = find_session(&state, &session_id).await?;
+
+    // Sessions recorded before the daemon started are counted afresh
+    let tokens = match state.tokens.get_session_stats(&session_id).await {
+        Some(tokens) => tokens,
+        None => count_tokens(&session).await,
+    };
+    let latency = match session.metadata.latency {
+        Some(ref latency) if !live => latency.clone(),
+        _ => SessionLatencyStats::from_session(&session),
+    };
+
+    Ok(Json(SessionStats {
+        session_id,
+        live,
+        tokens,
+        latency,
+    }))
+}
+
+/// Token statistics of a recording, counted as if it were live
+async fn count_tokens(session: &RecordedSession) -> SessionTokenStats {
+    let counter = TokenCounter::new();
+    for message in &session.messages {
+        let is_request = message.direction == MessageDirection::ToServer;
+        counter
+            .record_message(
+                &NoOpEventSink,
+                &session.id,
+                &message.id,
+                &McpMessage::parse(&message.content),
+                is_request,
+            )
+            .await;
+    }
+    counter
+        .get_session_stats(&session.id)
+        .await
+        .unwrap_or_default()
+}
+
+async fn export_session(
+    State(state): State<Arc<DaemonState>>,
+    Path(session_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let (session, _) = find_session(&state, &session_id).await?;
+    let disposition = format!("attachment; filename=\"{session_id}.json\"");
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(session)).into_response())
+}
+
+async fn inject_message(
+    State(state): State<Arc<DaemonState>>,
+    Path(session_id): Path<String>,
+    message: Result<Json<serde_json::Value>, JsonRejection>,
+) -> Result<StatusCode, ApiError> {
+    let Json(message) = message.map_err(|e| ApiError(e.status(), e.body_text()))?;
+    if !state.sessions.read().await.contains_key(&session_id) {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("Session {session_id} is not connected"),
+        ));
+    }
+    // Compact, since stdio servers read one message per line
+    state
+        .send_to_session(&session_id, &message.to_string())
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StreamFilter {
+    server: Option<String>,
+    session: Option<String>,
+    method: Option<String>,
+    direction: Option<Direction>,
+}
+
+/// One stream subscriber's view of the daemon's log entries
+struct Subscription {
+    receiver: broadcast::Receiver<Arc<LogEntry>>,
+    filter: StreamFilter,
+    /// Requests that matched the method filter, by session, direction, and
+    /// id, so their responses are let through too
+    requests: HashSet<(String, Direction, String)>,
+}
+
+impl Subscription {
+    fn admits(&mut self, entry: &LogEntry) -> bool {
+        let filter = &self.filter;
+        if filter.server.is_some() && entry.server_name != filter.server
+            || filter
+                .session
+                .as_ref()
+                .is_some_and(|s| *s != entry.session_id)
+            || filter.direction.is_some_and(|d| d != entry.direction)
+        {
+            return false;
+        }
+        let Some(ref method) = filter.method else {
+            return true;
+        };
+        match entry.message {
+            Some(McpMessage::Request { ref id, .. }) if entry.method.as_ref() == Some(method) => {
+                if self.requests.len() >= MAX_TRACKED_REQUESTS {
+                    self.requests.clear();
+                }
+                self.requests
+                    .insert((entry.session_id.clone(), entry.direction, id.to_string()));
+                true
+            }
+            Some(McpMessage::Response { ref id, .. } | McpMessage::Error { ref id, .. }) => {
+                let request_direction = match entry.direction {
+                    Direction::In => Direction::Out,
+                    Direction::Out => Direction::In,
+                };
+                self.requests
+                    .remove(&(entry.session_id.clone(), request_direction, id.to_string()))
+            }
+            _ => entry.method.as_ref() == Some(method),
+        }
+    }
+
+    /// The next event to send, or `None` once the daemon shuts down
+    async fn next(&mut self) -> Option<Event> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(entry) if self.admits(&entry) => {
+                    match Event::default().event("log").json_data(&*entry) {
+                        Ok(event) => return Some(event),
+                        Err(e) => warn!("Could not serialize log entry {}: {}", entry.id, e),
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    return Some(Event::default().event("lagged").data(missed.to_string()))
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+async fn stream_logs(
+    State(state): State<Arc<DaemonState>>,
+    Query(filter): Query<StreamFilter>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let subscription = Subscription {
+        receiver: state.live.subscribe(),
+        filter,
+        requests: HashSet::new(),
+    };
+    let events = futures::stream::unfold(subscription, |mut subscription| async move {
+        let event = subscription.next().await?;
+        Some((Ok(event), subscription))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use reticle_core::events::SocketEvent;
+    use reticle_core::storage::SessionStorage;
+    use serde_json::json;
+    use tokio::io::AsyncBufReadExt;
+
+    /// Serve the API on an ephemeral port, returning its base URL
+    async fn serve(state: Arc<DaemonState>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state, addr.port())).await });
+        format!("http://{addr}/api")
+    }
+
+    fn log(id: &str, session_id: &str, direction: &str, content: serde_json::Value) -> SocketEvent {
+        serde_json::from_value(json!({
+            "type": "log",
+            "id": id,
+            "session_id": session_id,
+            "timestamp": 0,
+            "direction": direction,
+            "content": content.to_string(),
+            "method": content.get("method"),
+            "server_name": "github",
+            "message_type": "jsonrpc",
+            "token_count": 0
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_query_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("recordings.db");
+        let storage = SessionStorage::new(db).unwrap();
+
+        // One session saved before the daemon started, one still live
+        let recorded = reticle_core::SessionRecorder::new(
+            "old".to_string(),
+            "Old".to_string(),
+            "stdio".to_string(),
+        );
+        recorded
+            .record_message(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}),
+                MessageDirection::ToServer,
+            )
+            .await
+            .unwrap();
+        let recorded = recorded.finalize().await.unwrap();
+        storage.save_session(&recorded).await.unwrap();
+        let state = Arc::new(DaemonState::new(Some(storage)));
+
+        state.start_recording("live", "brave-otter", "github").await;
+        for (i, method) in ["initialize", "tools/list", "tools/call"]
+            .iter()
+            .enumerate()
+        {
+            let request = json!({"jsonrpc": "2.0", "id": i, "method": method});
+            state
+                .record_log("github", &log(&format!("in-{i}"), "live", "in", request))
+                .await;
+            let response = json!({"jsonrpc": "2.0", "id": i, "result": {}});
+            state
+                .record_log("github", &log(&format!("out-{i}"), "live", "out", response))
+                .await;
+        }
+
+        let api = serve(state).await;
+        let client = reqwest::Client::new();
+
+        let list: SessionList = client
+            .get(format!("{api}/sessions"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(list.live.len(), 1);
+        assert_eq!(list.live[0].message_count, 6);
+        assert!(!list.live[0].connected);
+        assert_eq!(list.recorded.len(), 1);
+        assert_eq!(list.recorded[0].id, "old");
+
+        let page: MessagePage = client
+            .get(format!(
+                "{api}/sessions/live/messages?direction=in&offset=1&limit=1"
+            ))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(page.live);
+        assert_eq!(page.total, 3);
+        assert_eq!(page.messages.len(), 1);
+        assert_eq!(
+            page.messages[0].metadata.method.as_deref(),
+            Some("tools/list")
+        );
+
+        let page: MessagePage = client
+            .get(format!("{api}/sessions/old/messages?method=ping"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(!page.live);
+        assert_eq!(page.total, 1);
+
+        let stats: SessionStats = client
+            .get(format!("{api}/sessions/old/stats"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(stats.tokens.total_tokens > 0);
+
+        let export = client
+            .get(format!("{api}/sessions/live/export"))
+            .send()
+            .await
+            .unwrap();
+        assert!(export.headers()[header::CONTENT_DISPOSITION]
+            .to_str()
+            .unwrap()
+            .contains("live.json"));
+        let session: RecordedSession = export.json().await.unwrap();
+        assert_eq!(session.messages.len(), 6);
+        assert!(session.ended_at.is_none());
+
+        let missing = client
+            .get(format!("{api}/sessions/nope/stats"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        let error: serde_json::Value = missing.json().await.unwrap();
+        assert_eq!(error["error"], "No session nope");
+    }
+
+    #[tokio::test]
+    async fn test_serve_ui() {
+        let api = serve(Arc::new(DaemonState::default())).await;
+        let root = api.trim_end_matches("/api");
+        let page = reqwest::get(format!("{root}/")).await.unwrap();
+        assert_eq!(page.status(), StatusCode::OK);
+        assert!(page.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html"));
+        let html = page.text().await.unwrap();
+        assert!(html.contains("/api/sessions"));
+        assert!(html.contains("/api/stream"));
+    }
+
+    #[tokio::test]
+    async fn test_inject_message() {
+        let state = Arc::new(DaemonState::default());
+        let (daemon_end, cli_end) = tokio::net::UnixStream::pair().unwrap();
+        let (_, writer) = daemon_end.into_split();
+        state
+            .sessions
+            .write()
+            .await
+            .insert("s1".to_string(), Arc::new(tokio::sync::Mutex::new(writer)));
+
+        let api = serve(state).await;
+        let client = reqwest::Client::new();
+        let inject = |session: &str, body: &str| {
+            client
+                .post(format!("{api}/sessions/{session}/inject"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body.to_string())
+                .send()
+        };
+
+        let bad = inject("s1", "{not json").await.unwrap();
+        assert_eq!(bad.status(), StatusCode::BAD_REQUEST);
+        let gone = inject("s2", "{}").await.unwrap();
+        assert_eq!(gone.status(), StatusCode::NOT_FOUND);
+
+        let sent = inject(
+            "s1",
+            "{\n  \"jsonrpc\": \"2.0\",\n  \"method\": \"ping\",\n  \"id\": 9\n}",
+        )
+        .await
+        .unwrap();
+        assert_eq!(sent.status(), StatusCode::ACCEPTED);
+
+        let mut lines = tokio::io::BufReader::new(cli_end).lines();
+        let line = lines.next_line().await.unwrap().unwrap();
+        match serde_json::from_str(&line).unwrap() {
+            SocketEvent::InjectMessage {
+                session_id,
+                message,
+            } => {
+                assert_eq!(session_id, "s1");
+                assert!(!message.contains('\n'));
+                assert_eq!(
+                    serde_json::from_str::<serde_json::Value>(&message).unwrap()["id"],
+                    9
+                );
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inject_refuses_cross_site_requests() {
+        let state = Arc::new(DaemonState::default());
+        let (daemon_end, cli_end) = tokio::net::UnixStream::pair().unwrap();
+        let (_, writer) = daemon_end.into_split();
+        state
+            .sessions
+            .write()
+            .await
+            .insert("s1".to_string(), Arc::new(tokio::sync::Mutex::new(writer)));
+
+        let api = serve(state).await;
+        let port = reqwest::Url::parse(&api).unwrap().port().unwrap();
+        let client = reqwest::Client::new();
+        let inject = |id: u64| {
+            client
+                .post(format!("{api}/sessions/s1/inject"))
+                .body(json!({"jsonrpc": "2.0", "method": "ping", "id": id}).to_string())
+        };
+
+        // A form or `fetch` with no preflight can only send text/plain
+        let plain = inject(1)
+            .header(header::CONTENT_TYPE, "text/plain")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(plain.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let foreign = inject(2)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ORIGIN, "https://evil.example")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(foreign.status(), StatusCode::FORBIDDEN);
+        let error: serde_json::Value = foreign.json().await.unwrap();
+        assert!(error["error"].as_str().unwrap().contains("evil.example"));
+        let rebound = inject(3)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::HOST, format!("evil.example:{port}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(rebound.status(), StatusCode::FORBIDDEN);
+
+        // The daemon's own web UI may still inject
+        let local = inject(4)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ORIGIN, format!("http://localhost:{port}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(local.status(), StatusCode::ACCEPTED);
+
+        // Only the allowed message reached the CLI
+        let mut lines = tokio::io::BufReader::new(cli_end).lines();
+        let line = lines.next_line().await.unwrap().unwrap();
+        match serde_json::from_str(&line).unwrap() {
+            SocketEvent::InjectMessage { message, .. } => {
+                assert_eq!(
+                    serde_json::from_str::<serde_json::Value>(&message).unwrap()["id"],
+                    4
+                );
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+
+    /// Read server-sent events until `count` have arrived, as (event, data)
+    async fn read_events(response: reqwest::Response, count: usize) -> Vec<(String, String)> {
+        let mut body = response.bytes_stream();
+        let mut buffer = String::new();
+        let mut events = Vec::new();
+        while events.len() < count {
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), body.next())
+                .await
+                .expect("Timed out waiting for events")
+                .unwrap()
+                .unwrap();
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+            while let Some(end) = buffer.find("\n\n") {
+                let frame: String = buffer.drain(..end + 2).collect();
+                let field = |name: &str| {
+                    frame
+                        .lines()
+                        .find_map(|line| line.strip_prefix(name))
+                        .map(String::from)
+                };
+                // Keep-alive comments have no event name
+                if let (Some(event), Some(data)) = (field("event: "), field("data: ")) {
+                    events.push((event, data));
+                }
+            }
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn test_stream_logs() {
+        let state = Arc::new(DaemonState::default());
+        let api = serve(state.clone()).await;
+        let client = reqwest::Client::new();
+
+        // Two subscribers watching the same traffic through different filters
+        let calls = client
+            .get(format!("{api}/stream?server=github&method=tools/call"))
+            .send()
+            .await
+            .unwrap();
+        let notices = client
+            .get(format!("{api}/stream?session=s2&direction=out"))
+            .send()
+            .await
+            .unwrap();
+
+        let on = |server: &str, mut event: SocketEvent| {
+            if let SocketEvent::Log {
+                ref mut server_name,
+                ..
+            } = event
+            {
+                *server_name = server.to_string();
+            }
+            event
+        };
+        let events = [
+            log(
+                "1",
+                "s1",
+                "in",
+                json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+            ),
+            log(
+                "2",
+                "s1",
+                "in",
+                json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call"}),
+            ),
+            log(
+                "3",
+                "s1",
+                "out",
+                json!({"jsonrpc": "2.0", "id": 1, "result": {}}),
+            ),
+            log(
+                "4",
+                "s1",
+                "out",
+                json!({"jsonrpc": "2.0", "id": 2, "result": {}}),
+            ),
+            on(
+                "slack",
+                log(
+                    "5",
+                    "s3",
+                    "in",
+                    json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call"}),
+                ),
+            ),
+            log(
+                "6",
+                "s2",
+                "out",
+                json!({"jsonrpc": "2.0", "method": "notifications/message"}),
+            ),
+        ];
+        for event in &events {
+            state.publish(event);
+        }
+
+        let calls = read_events(calls, 2).await;
+        let ids: Vec<String> = calls
+            .iter()
+            .map(|(event, data)| {
+                assert_eq!(event, "log");
+                let entry: LogEntry = serde_json::from_str(data).unwrap();
+                assert_eq!(entry.server_name.as_deref(), Some("github"));
+                entry.id
+            })
+            .collect();
+        assert_eq!(ids, ["2", "4"]);
+
+        let notices = read_events(notices, 1).await;
+        let entry: LogEntry = serde_json::from_str(&notices[0].1).unwrap();
+        assert_eq!(entry.id, "6");
+        assert_eq!(entry.direction, Direction::Out);
+    }
+
+    #[test]
+    fn test_subscription_forgets_unanswered_requests() {
+        let state = DaemonState::default();
+        let mut subscription = Subscription {
+            receiver: state.live.subscribe(),
+            filter: StreamFilter {
+                method: Some("tools/call".to_string()),
+                ..StreamFilter::default()
+            },
+            requests: HashSet::new(),
+        };
+
+        // Requests that never get a response don't pile up
+        for id in 0..=MAX_TRACKED_REQUESTS {
+            let request = log(
+                &id.to_string(),
+                "s1",
+                "in",
+                json!({"jsonrpc": "2.0", "id": id, "method": "tools/call"}),
+            );
+            assert!(subscription.admits(&super::super::unix_impl::log_entry(&request).unwrap()));
+        }
+        assert!(subscription.requests.len() <= MAX_TRACKED_REQUESTS);
+    }
+}
@@ -0,0 +1,401 @@
+//! `reticle diff`, and text rendering for `reticle diff` and `reticle changes`
+//!
+//! The diffs themselves are computed by [`reticle_core::session_diff`] and
+//! [`reticle_core::analysis_diff`]; this module loads the two recordings for
+//! `reticle diff` and prints diffs in a compact, unified-diff-like layout.
+
+use reticle_core::analysis_diff::{AnalysisDiff, DefinitionDiff, ToolDiff};
+use reticle_core::session_diff::{diff_sessions, DiffOptions};
+use reticle_core::session_diff::{CallDiff, ChangeKind, DiffStatus, JsonChange, SessionDiff};
+use reticle_core::session_recorder::{MessageDirection, RecordedSession};
+use reticle_core::storage::SessionStorage;
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Longest rendering of a JSON value before it is cut short
+const MAX_VALUE_LEN: usize = 80;
+
+/// Render a session diff as text
+///
+/// Unchanged calls are only listed with `all`, except when their latency
+/// regressed.
+pub fn render(diff: &SessionDiff, a: &RecordedSession, b: &RecordedSession, all: bool) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "--- {} ({})", a.name, a.id);
+    let _ = writeln!(out, "+++ {} ({})", b.name, b.id);
+
+    for call in &diff.calls {
+        if call.status == DiffStatus::Unchanged && !call.latency_regression && !all {
+            continue;
+        }
+        render_call(&mut out, call);
+    }
+
+    let s = &diff.summary;
+    let _ = writeln!(
+        out,
+        "\n{} added, {} removed, {} changed, {} unchanged, {} latency regression(s)",
+        s.added, s.removed, s.changed, s.unchanged, s.latency_regressions
+    );
+    let _ = writeln!(
+        out,
+        "Tokens: {} -> {} ({})",
+        s.tokens_before,
+        s.tokens_after,
+        signed(s.token_delta)
+    );
+    out
+}
+
+fn render_call(out: &mut String, call: &CallDiff) {
+    let marker = marker(call.status);
+    let arrow = match call.direction {
+        MessageDirection::ToServer => "->",
+        MessageDirection::ToClient => "<-",
+    };
+    let mut line = format!("{marker} {arrow} {}", call.method);
+    if let Some(ref tool) = call.tool {
+        let _ = write!(line, " {tool}");
+    }
+    let side = call.after.as_ref().or(call.before.as_ref());
+    if let Some(id) = side.and_then(|s| s.id.as_ref()) {
+        let _ = write!(line, " (id {id})");
+    }
+    if let (Some(before), Some(after), Some(delta)) = (
+        call.before.as_ref().and_then(|s| s.latency_ms),
+        call.after.as_ref().and_then(|s| s.latency_ms),
+        call.latency_delta_ms,
+    ) {
+        if delta != 0 {
+            let _ = write!(line, "  {before}ms -> {after}ms ({}ms)", signed(delta));
+        }
+    }
+    if call.latency_regression {
+        line.push_str(" SLOWER");
+    }
+    if call.token_delta != 0 && call.status != DiffStatus::Unchanged {
+        let _ = write!(line, "  [{} tokens]", signed(call.token_delta));
+    }
+    let _ = writeln!(out, "{line}");
+
+    for change in &call.params {
+        render_change(out, "params", change);
+    }
+    for change in &call.result {
+        render_change(out, "result", change);
+    }
+}
+
+/// Render a diff between two server analyses as text
+pub fn render_analysis(diff: &AnalysisDiff) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "--- {} {}",
+        diff.before.server_name, diff.before.server_version
+    );
+    let _ = writeln!(
+        out,
+        "+++ {} {}",
+        diff.after.server_name, diff.after.server_version
+    );
+
+    for tool in &diff.tools {
+        render_tool(&mut out, tool);
+    }
+    for prompt in &diff.prompts {
+        render_definition(&mut out, "prompt", prompt);
+    }
+    for resource in &diff.resources {
+        render_definition(&mut out, "resource", resource);
+    }
+
+    let s = &diff.summary;
+    let _ = writeln!(
+        out,
+        "\nTools: {} added, {} removed, {} changed, {} unchanged",
+        s.tools_added, s.tools_removed, s.tools_changed, s.tools_unchanged
+    );
+    if s.prompts_changed + s.resources_changed > 0 {
+        let _ = writeln!(
+            out,
+            "Prompts changed: {}, resources changed: {}",
+            s.prompts_changed, s.resources_changed
+        );
+    }
+    let _ = writeln!(
+        out,
+        "Tokens: {} -> {} ({})",
+        s.tokens_before,
+        s.tokens_after,
+        signed(s.token_delta)
+    );
+    if diff.tokenizer_changed() {
+        let _ = writeln!(
+            out,
+            "Note: counted with {} before and {} after, so token deltas are not comparable",
+            diff.before.tokenizer, diff.after.tokenizer
+        );
+    }
+    out
+}
+
+fn render_tool(out: &mut String, tool: &ToolDiff) {
+    let _ = write!(out, "{} {}", marker(tool.status), tool.name);
+    if tool.token_delta != 0 {
+        let _ = write!(out, "  [{} tokens]", signed(tool.token_delta));
+    }
+    out.push('\n');
+
+    if let (Some(before), Some(after)) = (&tool.description_before, &tool.description_after) {
+        let _ = writeln!(
+            out,
+            "      description: {} -> {}",
+            show(Some(&Value::String(before.clone()))),
+            show(Some(&Value::String(after.clone())))
+        );
+    }
+    for change in &tool.schema {
+        render_change(out, "schema", change);
+    }
+}
+
+fn render_definition(out: &mut String, kind: &str, definition: &DefinitionDiff) {
+    let _ = write!(
+        out,
+        "{} {kind} {}",
+        marker(definition.status),
+        definition.name
+    );
+    if definition.token_delta != 0 {
+        let _ = write!(out, "  [{} tokens]", signed(definition.token_delta));
+    }
+    out.push('\n');
+}
+
+fn marker(status: DiffStatus) -> char {
+    match status {
+        DiffStatus::Added => '+',
+        DiffStatus::Removed => '-',
+        DiffStatus::Changed => '~',
+        DiffStatus::Unchanged => ' ',
+    }
+}
+
+fn render_change(out: &mut String, section: &str, change: &JsonChange) {
+    let path = change.path.strip_prefix('$').unwrap_or(&change.path);
+    let detail = match change.kind {
+        ChangeKind::Added => format!("added {}", show(change.after.as_ref())),
+        ChangeKind::Removed => format!("removed {}", show(change.before.as_ref())),
+        ChangeKind::Changed => format!(
+            "{} -> {}",
+            show(change.before.as_ref()),
+            show(change.after.as_ref())
+        ),
+    };
+    let _ = writeln!(out, "      {section}{path}: {detail}");
+}
+
+/// Compact JSON, shortened to [`MAX_VALUE_LEN`] characters
+fn show(value: Option<&Value>) -> String {
+    let text = value.map(Value::to_string).unwrap_or_default();
+    if text.chars().count() <= MAX_VALUE_LEN {
+        text
+    } else {
+        let cut: String = text.chars().take(MAX_VALUE_LEN).collect();
+        format!("{cut}...")
+    }
+}
+
+fn signed(n: i64) -> String {
+    if n > 0 {
+        format!("+{n}")
+    } else {
+        n.to_string()
+    }
+}
+
+/// Run diff mode
+pub async fn run_diff(
+    a: String,
+    b: String,
+    json: bool,
+    all: bool,
+    options: DiffOptions,
+    storage: Option<PathBuf>,
+) -> ExitCode {
+    let storage_path = storage.unwrap_or_else(SessionStorage::default_path);
+    let (before, after) = match (
+        crate::load_recording(&a, &storage_path).await,
+        crate::load_recording(&b, &storage_path).await,
+    ) {
+        (Ok(before), Ok(after)) => (before, after),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("[reticle diff] Error: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let session_diff = diff_sessions(&before, &after, &options);
+    if json {
+        match serde_json::to_string_pretty(&session_diff) {
+            Ok(text) => println!("{text}"),
+            Err(e) => {
+                eprintln!("[reticle diff] Error: {e}");
+                return ExitCode::from(2);
+            }
+        }
+    } else {
+        print!("{}", render(&session_diff, &before, &after, all));
+    }
+
+    if session_diff.has_differences() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reticle_core::analysis_diff::diff_analyses;
+    use reticle_core::server_analyzer::ServerAnalysis;
+    use reticle_core::session_recorder::SessionRecorder;
+    use serde_json::json;
+
+    async fn recording(id: &str, version: &str, extra: bool) -> RecordedSession {
+        let recorder = SessionRecorder::new(id.to_string(), id.to_string(), "stdio".to_string());
+        let mut messages = vec![
+            (
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}),
+                MessageDirection::ToServer,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "id": 1, "result": {"serverInfo": {"version": version}}}),
+                MessageDirection::ToClient,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}),
+                MessageDirection::ToServer,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "id": 2, "result": {}}),
+                MessageDirection::ToClient,
+            ),
+        ];
+        if extra {
+            messages.push((
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                MessageDirection::ToServer,
+            ));
+        }
+        for (message, direction) in messages {
+            recorder.record_message(message, direction).await.unwrap();
+        }
+        recorder.finalize().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_render() {
+        let a = recording("a", "1.0", false).await;
+        let b = recording("b", "1.1", true).await;
+        let diff = diff_sessions(&a, &b, &DiffOptions::default());
+
+        let text = render(&diff, &a, &b, false);
+        assert!(text.starts_with("--- a (a)\n+++ b (b)\n"));
+        assert!(text.contains("~ -> initialize (id 1)"));
+        assert!(text.contains("      result.serverInfo.version: \"1.0\" -> \"1.1\""));
+        assert!(text.contains("+ -> notifications/initialized"));
+        assert!(!text.contains("ping"));
+        assert!(text.contains("1 added, 0 removed, 1 changed, 1 unchanged"));
+
+        assert!(render(&diff, &a, &b, true).contains("  -> ping (id 2)"));
+    }
+
+    fn analysis(version: &str, tokenizer: &str, tools: Value) -> ServerAnalysis {
+        let tools = tools.as_array().unwrap();
+        let total: u64 = tools
+            .iter()
+            .map(|t| t["total_tokens"].as_u64().unwrap())
+            .sum();
+        serde_json::from_value(json!({
+            "server_name": "files",
+            "server_version": version,
+            "protocol_version": "2024-11-05",
+            "tokenizer": tokenizer,
+            "total_context_tokens": total,
+            "tools": {"count": tools.len(), "total_tokens": total, "tools": tools},
+            "prompts": {"count": 0, "total_tokens": 0, "prompts": []},
+            "resources": {
+                "count": 1,
+                "total_tokens": 5,
+                "resources": [{"uri": "file:///a", "name": "a", "total_tokens": 5}]
+            },
+            "token_breakdown": {},
+            "analyzed_at": 0
+        }))
+        .unwrap()
+    }
+
+    fn tool(name: &str, description: &str, schema: Value, total_tokens: u64) -> Value {
+        json!({
+            "name": name,
+            "description": description,
+            "name_tokens": 1,
+            "description_tokens": 1,
+            "schema_tokens": total_tokens - 2,
+            "total_tokens": total_tokens,
+            "input_schema": schema
+        })
+    }
+
+    #[test]
+    fn test_render_analysis() {
+        let before = analysis(
+            "1.0.0",
+            "heuristic",
+            json!([
+                tool("read_file", "Read a file", json!({"type": "object"}), 30),
+                tool("grep", "Search files", json!({"type": "object"}), 20),
+            ]),
+        );
+        let after = analysis(
+            "1.1.0",
+            "heuristic",
+            json!([
+                tool(
+                    "read_file",
+                    "Read a file from disk",
+                    json!({"type": "object", "required": ["path"]}),
+                    36
+                ),
+                tool("search", "Search files", json!({"type": "object"}), 20),
+            ]),
+        );
+
+        let text = render_analysis(&diff_analyses(&before, &after));
+        assert!(text.starts_with("--- files 1.0.0\n+++ files 1.1.0\n- grep  [-20 tokens]\n"));
+        assert!(text.contains(
+            "~ read_file  [+6 tokens]\n      description: \"Read a file\" -> \"Read a file from disk\"\n      schema.required: added [\"path\"]\n"
+        ));
+        assert!(text.contains("+ search  [+20 tokens]\n"));
+        assert!(text.contains("Tools: 1 added, 1 removed, 1 changed, 0 unchanged"));
+        assert!(text.contains("Tokens: 50 -> 56 (+6)"));
+        assert!(!text.contains("resource"));
+        assert!(!text.contains("Note:"));
+
+        let recounted = analysis("1.0.0", "cl100k_base", json!([]));
+        let text = render_analysis(&diff_analyses(&before, &recounted));
+        assert!(text.contains("Note: counted with heuristic before and cl100k_base after"));
+    }
+
+    #[test]
+    fn test_show_truncates() {
+        let long = Value::String("x".repeat(200));
+        assert_eq!(show(Some(&long)).chars().count(), MAX_VALUE_LEN + 3);
+        assert_eq!(show(Some(&json!(1))), "1");
+    }
+}
@@ -4,7 +4,14 @@
 //! telemetry to the Reticle GUI via Unix socket.
 //!
 //! This enables debugging of HTTP-based MCP servers (SSE, Streamable HTTP, WebSocket)
-//! in the same hub-and-spoke architecture as stdio servers.
+//! in the same hub-and-spoke architecture as stdio servers. Request and response
+//! bodies, SSE events, and WebSocket messages all pass through the rules engine.
+//! Chaos faults apply to HTTP requests and their JSON or SSE responses.
+//! Responses are timed against their requests, and requests that never get
+//! a response are reported. Messages over the token budget are reported, and
+//! JSON responses can be truncated or rejected. Every JSON-RPC message is
+//! checked against JSON-RPC 2.0 and the MCP lifecycle, and progress and
+//! cancellation notifications are linked to the requests they refer to.
 
 use axum::{
     body::Body,
@@ -20,12 +27,20 @@ use axum::{
 use bytes::Bytes;
 use futures::{SinkExt, StreamExt};
 use reqwest::Client;
+use reticle_core::budget::TokenBudget;
+use reticle_core::chaos::{ChaosEngine, Injected, Injection};
 use reticle_core::events::{NoOpEventSink, UnixSocketEventSink};
-use reticle_core::protocol::{Direction, LogEntry, MessageType};
+use reticle_core::progress::ProgressTracker;
+use reticle_core::protocol::conformance::{ConformanceChecker, SessionConformance, Violation};
+use reticle_core::protocol::{Direction, LogEntry, McpMessage, MessageType};
+use reticle_core::request_tracker::{RequestTracker, Unanswered, DEFAULT_REQUEST_TIMEOUT};
+use reticle_core::rules::RulesEngine;
 use reticle_core::session_names::{create_session_id, SessionId};
-use reticle_core::token_counter::TokenCounter as TC;
+use reticle_core::token_counter::{MessageTokenStats, TokenCounter as TC};
+use reticle_core::tokenizer::Tokenizer;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio_tungstenite::{connect_async, tungstenite::Message as TungsteniteMessage};
 use tower_http::cors::CorsLayer;
@@ -77,6 +92,37 @@ impl HttpEventSink {
             HttpEventSink::UnixSocket(sink) => sink.emit_session_ended(session_id).await,
         }
     }
+
+    async fn emit_conformance_summary(&self, summary: &SessionConformance) -> Result<(), String> {
+        use reticle_core::events::EventSink;
+        match self {
+            HttpEventSink::NoOp(sink) => sink.emit_conformance_summary(summary).await,
+            HttpEventSink::UnixSocket(sink) => sink.emit_conformance_summary(summary).await,
+        }
+    }
+
+    /// Count a message, emitting any budget violations to this sink
+    async fn record_tokens(
+        &self,
+        tokens: &TC,
+        session_id: &str,
+        message_id: &str,
+        message: &McpMessage,
+        is_request: bool,
+    ) -> MessageTokenStats {
+        match self {
+            HttpEventSink::NoOp(sink) => {
+                tokens
+                    .record_message(sink, session_id, message_id, message, is_request)
+                    .await
+            }
+            HttpEventSink::UnixSocket(sink) => {
+                tokens
+                    .record_message(sink.as_ref(), session_id, message_id, message, is_request)
+                    .await
+            }
+        }
+    }
 }
 
 /// State shared across HTTP proxy handlers
@@ -95,6 +141,33 @@ pub struct HttpProxyState {
     /// Inject receiver for GUI → proxy communication (future use)
     #[allow(dead_code)]
     pub inject_tx: Arc<Mutex<Option<tokio::sync::mpsc::Sender<String>>>>,
+    /// Rules that rewrite, drop, or delay messages
+    pub rules: Arc<RulesEngine>,
+    /// Faults injected for chaos testing
+    pub chaos: Arc<ChaosEngine>,
+    /// In-flight requests, for response latency
+    pub tracker: Arc<RequestTracker>,
+    /// Protocol violations seen in the session
+    pub conformance: Arc<ConformanceChecker>,
+    /// Progress and cancellation of in-flight requests
+    pub progress: Arc<ProgressTracker>,
+    /// Token counts checked against the budget (only when there is one)
+    pub tokens: Option<Arc<TC>>,
+}
+
+/// Optional interception features for the HTTP proxy
+#[derive(Default)]
+pub struct HttpProxyOptions {
+    /// Rules that rewrite, drop, or delay messages before they are forwarded
+    pub rules: RulesEngine,
+    /// Faults injected into the session for chaos testing
+    pub chaos: ChaosEngine,
+    /// Report requests without a response after this long (60s if unset)
+    pub request_timeout: Option<Duration>,
+    /// Token limits for what the server sends back
+    pub budget: TokenBudget,
+    /// Tokenizer for the budget (the heuristic if unset)
+    pub tokenizer: Option<Arc<dyn Tokenizer>>,
 }
 
 /// Run the HTTP proxy
@@ -107,7 +180,16 @@ pub async fn run_http_proxy(
     server_name: String,
     event_sink: HttpEventSink,
     mut inject_rx: Option<tokio::sync::mpsc::Receiver<String>>,
+    options: HttpProxyOptions,
 ) -> Result<(), String> {
+    let HttpProxyOptions {
+        rules,
+        chaos,
+        request_timeout,
+        budget,
+        tokenizer,
+    } = options;
+
     // Generate session ID with beautiful name
     let session = create_session_id(Some(&server_name));
 
@@ -126,6 +208,17 @@ pub async fn run_http_proxy(
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
 
+    let tokens = if budget.is_empty() {
+        None
+    } else {
+        let tokens = TC::new();
+        if let Some(tokenizer) = tokenizer {
+            tokens.set_session_tokenizer(&session.id, tokenizer).await;
+        }
+        tokens.set_session_budget(&session.id, budget).await;
+        Some(Arc::new(tokens))
+    };
+
     // Create channel for inject commands
     let (inject_tx, mut _proxy_inject_rx) = tokio::sync::mpsc::channel::<String>(100);
 
@@ -136,8 +229,32 @@ pub async fn run_http_proxy(
         client,
         event_sink,
         inject_tx: Arc::new(Mutex::new(Some(inject_tx))),
+        rules: Arc::new(rules),
+        chaos: Arc::new(chaos),
+        tracker: Arc::new(RequestTracker::new(
+            request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+        )),
+        conformance: Arc::new(ConformanceChecker::new()),
+        progress: Arc::new(ProgressTracker::new()),
+        tokens,
     };
 
+    // Report requests that have waited too long for a response
+    let state_for_expiry = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(state_for_expiry.tracker.check_interval());
+        loop {
+            interval.tick().await;
+            for request in state_for_expiry.tracker.expire() {
+                warn!(
+                    "No response to {} {} after {}ms",
+                    request.method, request.id, request.elapsed_ms
+                );
+                log_unanswered(&state_for_expiry, &request).await;
+            }
+        }
+    });
+
     // CORS layer - allow all for proxy
     let cors = CorsLayer::new()
         .allow_origin(tower_http::cors::Any)
@@ -189,6 +306,16 @@ pub async fn run_http_proxy(
         .await
         .map_err(|e| format!("Server error: {e}"))?;
 
+    for request in state.tracker.drain() {
+        log_unanswered(&state, &request).await;
+    }
+    log_violations(&state, None, state.conformance.finish()).await;
+    let summary = state.conformance.summary(&state.session.id);
+    if !summary.is_conformant() {
+        warn!("Conformance: {}", summary);
+    }
+    let _ = state.event_sink.emit_conformance_summary(&summary).await;
+
     // Emit session ended
     state
         .event_sink
@@ -255,9 +382,17 @@ async fn proxy_handler(
         }
     };
 
-    // Log incoming request
+    // Apply rules, then log the request as it will be forwarded
+    let Some((body_bytes, original)) = apply_rules(&state, Direction::In, body_bytes).await else {
+        return StatusCode::ACCEPTED.into_response();
+    };
     if !body_bytes.is_empty() {
-        log_message(&state, Direction::In, &body_bytes).await;
+        log_message(&state, Direction::In, &body_bytes, original.as_ref()).await;
+    }
+
+    // Chaos faults may answer the request instead of forwarding it
+    if let Some(response) = apply_request_faults(&state, &body_bytes).await {
+        return response;
     }
 
     // Build upstream request
@@ -271,6 +406,7 @@ async fn proxy_handler(
             || name_str == "connection"
             || name_str == "transfer-encoding"
             || name_str == "upgrade"
+            || name_str == "content-length"
         {
             continue;
         }
@@ -320,9 +456,32 @@ async fn proxy_handler(
         }
     };
 
-    // Log outgoing response
+    // Apply rules, then log the response as it will be returned
+    let Some((mut resp_body, original)) = apply_rules(&state, Direction::Out, resp_body).await
+    else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+    let mut replaced = false;
     if !resp_body.is_empty() {
-        log_message(&state, Direction::Out, &resp_body).await;
+        if let Some(replacement) =
+            log_message(&state, Direction::Out, &resp_body, original.as_ref()).await
+        {
+            resp_body = Bytes::from(replacement.to_string());
+            replaced = true;
+        }
+    }
+
+    // A pending chaos fault swallows the response or cuts it short
+    let mut truncated = false;
+    if let Some(injection) = response_fault(&state, &resp_body) {
+        log_fault(&state, &injection).await;
+        match injection.injected {
+            Injected::Truncate(cut) => {
+                resp_body = Bytes::from(cut);
+                truncated = true;
+            }
+            _ => return StatusCode::ACCEPTED.into_response(),
+        }
     }
 
     // Build response
@@ -331,7 +490,10 @@ async fn proxy_handler(
     // Forward response headers
     for (name, value) in resp_headers.iter() {
         let name_str = name.as_str().to_lowercase();
-        if name_str == "transfer-encoding" || name_str == "connection" {
+        if name_str == "transfer-encoding"
+            || name_str == "connection"
+            || (name_str == "content-length" && (original.is_some() || truncated || replaced))
+        {
             continue;
         }
         response = response.header(name.clone(), value.clone());
@@ -348,25 +510,66 @@ async fn stream_sse_response(state: HttpProxyState, response: reqwest::Response)
     let headers = response.headers().clone();
 
     // Create streaming body
-    let stream = response.bytes_stream().map(move |result| {
-        match result {
-            Ok(chunk) => {
-                // Log each SSE chunk
-                let state_clone = state.clone();
-                let chunk_clone = chunk.clone();
-                tokio::spawn(async move {
-                    log_message(&state_clone, Direction::Out, &chunk_clone).await;
-                });
-                Ok::<_, std::io::Error>(chunk)
+    // Budgets need whole events, so they also take the parsing path
+    let body = if state.rules.is_empty() && state.chaos.is_empty() && state.tokens.is_none() {
+        let stream = response.bytes_stream().map(move |result| {
+            match result {
+                Ok(chunk) => {
+                    // Log each SSE chunk
+                    let state_clone = state.clone();
+                    let chunk_clone = chunk.clone();
+                    tokio::spawn(async move {
+                        log_message(&state_clone, Direction::Out, &chunk_clone, None).await;
+                    });
+                    Ok::<_, std::io::Error>(chunk)
+                }
+                Err(e) => {
+                    error!("SSE stream error: {}", e);
+                    Err(std::io::Error::other(e))
+                }
             }
-            Err(e) => {
-                error!("SSE stream error: {}", e);
-                Err(std::io::Error::new(std::io::ErrorKind::Other, e))
+        });
+        Body::from_stream(stream)
+    } else {
+        // Rewrite events in order, so a delayed event holds back the ones after it
+        let stream = response.bytes_stream().then(move |result| {
+            let state = state.clone();
+            async move {
+                let chunk = result.map_err(|e| {
+                    error!("SSE stream error: {}", e);
+                    std::io::Error::other(e)
+                })?;
+                let text = String::from_utf8_lossy(&chunk);
+                let rewritten = state.rules.apply_sse_chunk(&text, Direction::Out);
+                if !rewritten.delay.is_zero() {
+                    tokio::time::sleep(rewritten.delay).await;
+                }
+                let mut replacements = Vec::with_capacity(rewritten.messages.len());
+                for forwarded in &rewritten.messages {
+                    let body = Bytes::from(forwarded.message.to_string());
+                    replacements.push(
+                        log_message(&state, Direction::Out, &body, forwarded.original.as_ref())
+                            .await,
+                    );
+                }
+                let outgoing = if replacements.iter().any(Option::is_some) {
+                    replace_sse_data(&rewritten.text, &replacements)
+                } else {
+                    rewritten.text
+                };
+                let (forwarded, injections) = state.chaos.on_sse_chunk(&outgoing);
+                for injection in &injections {
+                    log_fault(&state, injection).await;
+                }
+                if forwarded == text {
+                    Ok::<_, std::io::Error>(chunk)
+                } else {
+                    Ok(Bytes::from(forwarded))
+                }
             }
-        }
-    });
-
-    let body = Body::from_stream(stream);
+        });
+        Body::from_stream(stream)
+    };
 
     // Build response
     let mut response_builder =
@@ -410,10 +613,24 @@ async fn handle_websocket(client_socket: WebSocket, upstream_url: String, state:
     let client_to_upstream = tokio::spawn(async move {
         while let Some(msg_result) = client_read.next().await {
             match msg_result {
-                Ok(msg) => {
-                    // Log the message from client
+                Ok(mut msg) => {
+                    // Apply rules and log the message from client
                     if let Some(content) = ws_message_to_bytes(&msg) {
-                        log_ws_message(&state_for_client, Direction::In, &content).await;
+                        let Some((content, original)) =
+                            apply_rules(&state_for_client, Direction::In, content).await
+                        else {
+                            continue;
+                        };
+                        if original.is_some() {
+                            msg = AxumWsMessage::Text(String::from_utf8_lossy(&content).into());
+                        }
+                        log_ws_message(
+                            &state_for_client,
+                            Direction::In,
+                            &content,
+                            original.as_ref(),
+                        )
+                        .await;
                     }
 
                     // Convert axum message to tungstenite message and forward
@@ -438,10 +655,26 @@ async fn handle_websocket(client_socket: WebSocket, upstream_url: String, state:
     let upstream_to_client = tokio::spawn(async move {
         while let Some(msg_result) = upstream_read.next().await {
             match msg_result {
-                Ok(msg) => {
-                    // Log the message from upstream
+                Ok(mut msg) => {
+                    // Apply rules and log the message from upstream
                     if let Some(content) = tungstenite_message_to_bytes(&msg) {
-                        log_ws_message(&state_for_upstream, Direction::Out, &content).await;
+                        let Some((content, original)) =
+                            apply_rules(&state_for_upstream, Direction::Out, content).await
+                        else {
+                            continue;
+                        };
+                        if original.is_some() {
+                            msg = TungsteniteMessage::Text(
+                                String::from_utf8_lossy(&content).into_owned(),
+                            );
+                        }
+                        log_ws_message(
+                            &state_for_upstream,
+                            Direction::Out,
+                            &content,
+                            original.as_ref(),
+                        )
+                        .await;
                     }
 
                     // Convert tungstenite message to axum message and forward
@@ -516,24 +749,245 @@ fn tungstenite_message_to_bytes(msg: &TungsteniteMessage) -> Option<Bytes> {
     }
 }
 
+/// Run a message body through the rules, waiting out any delay
+///
+/// Returns `None` if a rule dropped the message. Rewritten bodies come back
+/// with the original message alongside.
+async fn apply_rules(
+    state: &HttpProxyState,
+    direction: Direction,
+    body: Bytes,
+) -> Option<(Bytes, Option<serde_json::Value>)> {
+    if state.rules.is_empty() {
+        return Some((body, None));
+    }
+    let Ok(text) = std::str::from_utf8(&body) else {
+        return Some((body, None));
+    };
+    match state
+        .rules
+        .forward_text(text.to_string(), direction)
+        .await?
+    {
+        (rewritten, Some(original)) => Some((Bytes::from(rewritten), Some(original))),
+        (_, None) => Some((body, None)),
+    }
+}
+
+/// Log and act on the chaos faults rolled for a request body
+///
+/// Returns the response to send instead of forwarding the request, if a
+/// fault answered it with an error or simulated a crashed server.
+async fn apply_request_faults(state: &HttpProxyState, body: &Bytes) -> Option<Response> {
+    if state.chaos.is_empty() {
+        return None;
+    }
+    let json = serde_json::from_slice::<serde_json::Value>(body).ok()?;
+
+    let mut delay = Duration::ZERO;
+    let mut answer = None;
+    for injection in state.chaos.on_request(&json) {
+        log_fault(state, &injection).await;
+        match injection.injected {
+            Injected::Latency(extra) => delay += extra,
+            Injected::Error(response) => {
+                state
+                    .tracker
+                    .observe(&McpMessage::parse(&response), Direction::Out);
+                answer = Some(
+                    (
+                        [(axum::http::header::CONTENT_TYPE, "application/json")],
+                        response.to_string(),
+                    )
+                        .into_response(),
+                );
+            }
+            Injected::Kill => {
+                // There is no child to kill; fail the way a crashed server would
+                answer = Some(
+                    (StatusCode::BAD_GATEWAY, "Upstream server is not responding").into_response(),
+                );
+            }
+            Injected::Drop | Injected::Truncate(_) => {}
+        }
+    }
+
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+    answer
+}
+
+/// Check a response body for a pending chaos fault
+fn response_fault(state: &HttpProxyState, body: &Bytes) -> Option<Injection> {
+    if state.chaos.is_empty() {
+        return None;
+    }
+    let text = std::str::from_utf8(body).ok()?;
+    let json = serde_json::from_str::<serde_json::Value>(text).ok()?;
+    state.chaos.on_response(text, &json)
+}
+
+/// Log an injected fault to the event sink
+async fn log_fault(state: &HttpProxyState, injection: &Injection) {
+    let mut entry = LogEntry::new_fault(
+        generate_message_id(),
+        state.session.id.clone(),
+        injection.direction(),
+        injection.describe(),
+    );
+    entry.server_name = Some(state.server_name.clone());
+
+    if let Err(e) = state.event_sink.emit_log(&entry).await {
+        warn!("Failed to emit fault log: {}", e);
+    }
+}
+
+/// Log a request that never got a response
+async fn log_unanswered(state: &HttpProxyState, request: &Unanswered) {
+    let mut entry =
+        LogEntry::new_unanswered(generate_message_id(), state.session.id.clone(), request);
+    entry.server_name = Some(state.server_name.clone());
+
+    if let Err(e) = state.event_sink.emit_log(&entry).await {
+        warn!("Failed to emit unanswered request log: {}", e);
+    }
+}
+
+/// Log conformance violations, after the entry of the message they were found in
+async fn log_violations(state: &HttpProxyState, related: Option<&str>, violations: Vec<Violation>) {
+    for violation in violations {
+        warn!(
+            "Conformance violation in {}: {}",
+            related.unwrap_or("session"),
+            violation
+        );
+        let mut entry = LogEntry::new_violation(
+            generate_message_id(),
+            state.session.id.clone(),
+            related,
+            &violation,
+        );
+        entry.server_name = Some(state.server_name.clone());
+
+        if let Err(e) = state.event_sink.emit_log(&entry).await {
+            warn!("Failed to emit violation log: {}", e);
+        }
+    }
+}
+
+/// The JSON-RPC messages in an SSE body, from its `data:` lines
+fn sse_messages(content: &str) -> impl Iterator<Item = McpMessage> + '_ {
+    content
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .filter_map(|data| serde_json::from_str::<serde_json::Value>(data.trim()).ok())
+        .map(|json| McpMessage::parse(&json))
+}
+
+/// Swap the JSON `data:` lines of an SSE chunk for their budget replacements
+///
+/// `replacements` has one entry per JSON `data:` line, in order; lines
+/// without a replacement are kept as they are.
+fn replace_sse_data(text: &str, replacements: &[Option<serde_json::Value>]) -> String {
+    let mut replacements = replacements.iter();
+    let mut replaced = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\r', '\n']);
+        let is_json = body.strip_prefix("data:").is_some_and(|data| {
+            serde_json::from_str::<serde_json::Value>(data.trim_start()).is_ok()
+        });
+        match is_json.then(|| replacements.next()).flatten() {
+            Some(Some(replacement)) => {
+                replaced.push_str("data: ");
+                replaced.push_str(&replacement.to_string());
+                replaced.push_str(&line[body.len()..]);
+            }
+            _ => replaced.push_str(line),
+        }
+    }
+    replaced
+}
+
+/// Track the JSON-RPC messages in a body, returning a response's latency
+///
+/// SSE bodies are scanned for JSON in their `data:` lines.
+fn observe_body(
+    state: &HttpProxyState,
+    direction: Direction,
+    content: &str,
+    message: Option<&McpMessage>,
+) -> Option<Duration> {
+    match message {
+        Some(message) => state.tracker.observe(message, direction),
+        None => sse_messages(content)
+            .filter_map(|message| state.tracker.observe(&message, direction))
+            .max(),
+    }
+}
+
+/// Link the progress or cancellation notifications in a body to their
+/// request, returning the request's log id
+fn link_body(
+    state: &HttpProxyState,
+    direction: Direction,
+    log_id: &str,
+    content: &str,
+    message: Option<&McpMessage>,
+) -> Option<String> {
+    match message {
+        Some(message) => state.progress.observe(message, direction, log_id),
+        None => sse_messages(content)
+            .map(|message| state.progress.observe(&message, direction, log_id))
+            .fold(None, Option::or),
+    }
+}
+
+/// Check the JSON-RPC messages in a body for conformance violations
+///
+/// Bodies that are neither JSON nor SSE (empty `202 Accepted` replies,
+/// error pages) are not checked: HTTP has no line framing to break.
+fn check_body(
+    state: &HttpProxyState,
+    direction: Direction,
+    content: &str,
+    message: Option<&McpMessage>,
+) -> Vec<Violation> {
+    match message {
+        Some(message) => state.conformance.check(message, direction),
+        None => sse_messages(content)
+            .flat_map(|message| state.conformance.check(&message, direction))
+            .collect(),
+    }
+}
+
 /// Log a WebSocket message to the event sink
-async fn log_ws_message(state: &HttpProxyState, direction: Direction, body: &Bytes) {
+async fn log_ws_message(
+    state: &HttpProxyState,
+    direction: Direction,
+    body: &Bytes,
+    original: Option<&serde_json::Value>,
+) {
     let id = generate_message_id();
 
     // Try to parse as JSON
     let content = String::from_utf8_lossy(body);
 
-    // Try to extract method from JSON-RPC
-    let (method, message_type) =
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-            let method = json
-                .get("method")
-                .and_then(|m| m.as_str())
-                .map(String::from);
-            (method, MessageType::JsonRpc)
-        } else {
-            (None, MessageType::Raw)
-        };
+    // Parse JSON-RPC into the MCP model
+    let message = serde_json::from_str::<serde_json::Value>(&content)
+        .ok()
+        .map(|json| McpMessage::parse(&json));
+    let method = message.as_ref().and_then(|m| m.method()).map(String::from);
+    let message_type = match message {
+        Some(_) => MessageType::JsonRpc,
+        None => MessageType::Raw,
+    };
+    let elapsed = observe_body(state, direction, &content, message.as_ref());
+    let request_log_id = link_body(state, direction, &id, &content, message.as_ref());
+    let violations = check_body(state, direction, &content, message.as_ref());
+    if let Some(ref message) = message {
+        check_budget(state, &id, message, direction).await;
+    }
 
     let entry = LogEntry {
         id,
@@ -545,35 +999,61 @@ async fn log_ws_message(state: &HttpProxyState, direction: Direction, body: &Byt
         direction,
         content: content.to_string(),
         method,
-        duration_micros: None,
+        duration_micros: elapsed.map(|d| d.as_micros() as u64),
         message_type,
         token_count: TC::estimate_tokens(&content),
         server_name: Some(state.server_name.clone()),
+        modified: original.is_some(),
+        original: original.map(|o| o.to_string()),
+        message,
+        request_log_id,
     };
 
     if let Err(e) = state.event_sink.emit_log(&entry).await {
         warn!("Failed to emit WebSocket log: {}", e);
     }
+    log_violations(state, Some(&entry.id), violations).await;
 }
 
 /// Log a message to the event sink
-async fn log_message(state: &HttpProxyState, direction: Direction, body: &Bytes) {
+async fn log_message(
+    state: &HttpProxyState,
+    direction: Direction,
+    body: &Bytes,
+    original: Option<&serde_json::Value>,
+) -> Option<serde_json::Value> {
     let id = generate_message_id();
 
     // Try to parse as JSON
-    let content = String::from_utf8_lossy(body);
+    let mut content = String::from_utf8_lossy(body).into_owned();
+    let mut original = original.map(|o| o.to_string());
+
+    // Parse JSON-RPC into the MCP model
+    let mut message = serde_json::from_str::<serde_json::Value>(&content)
+        .ok()
+        .map(|json| McpMessage::parse(&json));
+
+    // A budget may truncate or reject a response; the replacement is what is logged
+    let replacement = match message {
+        Some(ref message) => check_budget(state, &id, message, direction).await,
+        None => None,
+    };
+    if let Some(ref replacement) = replacement {
+        original = original.or(Some(std::mem::replace(
+            &mut content,
+            replacement.to_string(),
+        )));
+        message = Some(McpMessage::parse(replacement));
+    }
 
-    // Try to extract method from JSON-RPC
-    let (method, message_type) =
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-            let method = json
-                .get("method")
-                .and_then(|m| m.as_str())
-                .map(String::from);
-            (method, MessageType::JsonRpc)
-        } else {
-            (None, MessageType::Raw)
-        };
+    let method = message.as_ref().and_then(|m| m.method()).map(String::from);
+    let message_type = match message {
+        Some(_) => MessageType::JsonRpc,
+        None => MessageType::Raw,
+    };
+    let elapsed = observe_body(state, direction, &content, message.as_ref());
+    let request_log_id = link_body(state, direction, &id, &content, message.as_ref());
+    let violations = check_body(state, direction, &content, message.as_ref());
 
     let entry = LogEntry {
         id,
@@ -583,17 +1063,60 @@ async fn log_message(state: &HttpProxyState, direction: Direction, body: &Bytes)
             .unwrap_or_default()
             .as_micros() as u64,
         direction,
-        content: content.to_string(),
+        token_count: TC::estimate_tokens(&content),
+        content,
         method,
-        duration_micros: None,
+        duration_micros: elapsed.map(|d| d.as_micros() as u64),
         message_type,
-        token_count: TC::estimate_tokens(&content),
         server_name: Some(state.server_name.clone()),
+        modified: original.is_some(),
+        original,
+        message,
+        request_log_id,
     };
 
     if let Err(e) = state.event_sink.emit_log(&entry).await {
         warn!("Failed to emit log: {}", e);
     }
+    log_violations(state, Some(&entry.id), violations).await;
+    replacement
+}
+
+/// Count a message against the token budget, if there is one
+///
+/// Returns the message to send instead when the budget truncates or
+/// rejects a response.
+async fn check_budget(
+    state: &HttpProxyState,
+    id: &str,
+    message: &McpMessage,
+    direction: Direction,
+) -> Option<serde_json::Value> {
+    let tokens = state.tokens.as_ref()?;
+    let stats = state
+        .event_sink
+        .record_tokens(
+            tokens,
+            &state.session.id,
+            id,
+            message,
+            direction == Direction::In,
+        )
+        .await;
+    if stats.budget_exceeded.is_empty() || direction == Direction::In {
+        return None;
+    }
+
+    let budget = tokens.session_budget(&state.session.id).await;
+    let tokenizer = tokens.session_tokenizer(&state.session.id).await;
+    let json = message.to_value();
+    let replacement = budget.enforce(tokenizer.as_ref(), &json, &stats.budget_exceeded)?;
+    info!(
+        "{:?} {} for exceeding the token budget",
+        budget.action(),
+        id
+    );
+    Some(replacement)
 }
 
 #[cfg(test)]
@@ -722,6 +1245,54 @@ mod tests {
         let _cloned = sink.clone();
     }
 
+    #[tokio::test]
+    async fn test_apply_rules_to_body() {
+        let rules = RulesEngine::from_toml(
+            r#"
+            [[rules]]
+            match = { method = "tools/call" }
+            set = { "params.arguments.path" = "/tmp/sandbox" }
+
+            [[rules]]
+            match = { method = "ping" }
+            drop = true
+            "#,
+        )
+        .unwrap();
+        let state = HttpProxyState {
+            upstream_url: "http://localhost:8080".to_string(),
+            session: create_session_id(Some("test")),
+            server_name: "test-server".to_string(),
+            client: Client::new(),
+            event_sink: HttpEventSink::NoOp(NoOpEventSink),
+            inject_tx: Arc::new(Mutex::new(None)),
+            rules: Arc::new(rules),
+            chaos: Arc::new(ChaosEngine::default()),
+            tracker: Arc::new(RequestTracker::default()),
+            conformance: Arc::new(ConformanceChecker::new()),
+            progress: Arc::new(ProgressTracker::new()),
+            tokens: None,
+        };
+
+        let call = Bytes::from(
+            r#"{"id":1,"method":"tools/call","params":{"arguments":{"path":"/etc/passwd"}}}"#,
+        );
+        let (body, original) = apply_rules(&state, Direction::In, call).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("/tmp/sandbox"));
+        assert!(original.unwrap().to_string().contains("/etc/passwd"));
+
+        let ping = Bytes::from(r#"{"id":2,"method":"ping"}"#);
+        assert!(apply_rules(&state, Direction::In, ping).await.is_none());
+
+        // Bodies no rule touches are forwarded as-is
+        let raw = Bytes::from("not json");
+        let (body, original) = apply_rules(&state, Direction::Out, raw.clone())
+            .await
+            .unwrap();
+        assert_eq!(body, raw);
+        assert!(original.is_none());
+    }
+
     #[test]
     fn test_http_proxy_state_clone() {
         let state = HttpProxyState {
@@ -731,7 +1302,142 @@ mod tests {
             client: Client::new(),
             event_sink: HttpEventSink::NoOp(NoOpEventSink),
             inject_tx: Arc::new(Mutex::new(None)),
+            rules: Arc::new(RulesEngine::default()),
+            chaos: Arc::new(ChaosEngine::default()),
+            tracker: Arc::new(RequestTracker::default()),
+            conformance: Arc::new(ConformanceChecker::new()),
+            progress: Arc::new(ProgressTracker::new()),
+            tokens: None,
         };
         let _cloned = state.clone();
     }
+
+    #[tokio::test]
+    async fn test_chaos_faults_on_body() {
+        let chaos = ChaosEngine::from_toml(
+            r#"
+            [[faults]]
+            methods = ["tools/call"]
+            probability = 1.0
+            action = "error"
+            codes = [-32000]
+
+            [[faults]]
+            methods = ["tools/list"]
+            probability = 1.0
+            action = "truncate"
+            keep = 5
+            "#,
+        )
+        .unwrap();
+        let state = HttpProxyState {
+            upstream_url: "http://localhost:8080".to_string(),
+            session: create_session_id(Some("test")),
+            server_name: "test-server".to_string(),
+            client: Client::new(),
+            event_sink: HttpEventSink::NoOp(NoOpEventSink),
+            inject_tx: Arc::new(Mutex::new(None)),
+            rules: Arc::new(RulesEngine::default()),
+            chaos: Arc::new(chaos),
+            tracker: Arc::new(RequestTracker::default()),
+            conformance: Arc::new(ConformanceChecker::new()),
+            progress: Arc::new(ProgressTracker::new()),
+            tokens: None,
+        };
+
+        let call = Bytes::from(r#"{"jsonrpc":"2.0","id":1,"method":"tools/call"}"#);
+        let response = apply_request_faults(&state, &call).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Truncation waits for the response to the request it was rolled on
+        let list = Bytes::from(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#);
+        assert!(apply_request_faults(&state, &list).await.is_none());
+        let result = Bytes::from(r#"{"jsonrpc":"2.0","id":2,"result":{"tools":[]}}"#);
+        let injection = response_fault(&state, &result).unwrap();
+        assert_eq!(injection.injected, Injected::Truncate("{\"jso".to_string()));
+        assert!(response_fault(&state, &result).is_none());
+    }
+
+    #[test]
+    fn test_observe_body_latency() {
+        let state = HttpProxyState {
+            upstream_url: "http://localhost:8080".to_string(),
+            session: create_session_id(Some("test")),
+            server_name: "test-server".to_string(),
+            client: Client::new(),
+            event_sink: HttpEventSink::NoOp(NoOpEventSink),
+            inject_tx: Arc::new(Mutex::new(None)),
+            rules: Arc::new(RulesEngine::default()),
+            chaos: Arc::new(ChaosEngine::default()),
+            tracker: Arc::new(RequestTracker::default()),
+            conformance: Arc::new(ConformanceChecker::new()),
+            progress: Arc::new(ProgressTracker::new()),
+            tokens: None,
+        };
+
+        let request: serde_json::Value =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).unwrap();
+        let request = McpMessage::parse(&request);
+        assert!(observe_body(&state, Direction::In, "", Some(&request)).is_none());
+        assert_eq!(state.tracker.in_flight(), 1);
+
+        // Responses streamed as SSE are found in their data lines
+        let sse = "event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n\n";
+        assert!(observe_body(&state, Direction::Out, sse, None).is_some());
+        assert_eq!(state.tracker.in_flight(), 0);
+
+        // The initialize handshake was never seen, and the SSE response repeats id 1
+        let violations = check_body(&state, Direction::In, "", Some(&request));
+        assert_eq!(violations.len(), 1);
+        assert!(check_body(&state, Direction::Out, sse, None).is_empty());
+        assert_eq!(check_body(&state, Direction::Out, sse, None).len(), 1);
+        assert!(check_body(&state, Direction::Out, "Accepted", None).is_empty());
+        assert_eq!(state.conformance.summary("s1").violations, 2);
+    }
+
+    #[test]
+    fn test_link_body_progress() {
+        let state = HttpProxyState {
+            upstream_url: "http://localhost:8080".to_string(),
+            session: create_session_id(Some("test")),
+            server_name: "test-server".to_string(),
+            client: Client::new(),
+            event_sink: HttpEventSink::NoOp(NoOpEventSink),
+            inject_tx: Arc::new(Mutex::new(None)),
+            rules: Arc::new(RulesEngine::default()),
+            chaos: Arc::new(ChaosEngine::default()),
+            tracker: Arc::new(RequestTracker::default()),
+            conformance: Arc::new(ConformanceChecker::new()),
+            progress: Arc::new(ProgressTracker::new()),
+            tokens: None,
+        };
+
+        let request = McpMessage::parse(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {"name": "build", "_meta": {"progressToken": 7}}
+        }));
+        assert!(link_body(&state, Direction::In, "req-1", "", Some(&request)).is_none());
+
+        // Progress streamed ahead of the response in the same SSE body
+        let sse = "data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\",\"params\":{\"progressToken\":7,\"progress\":1}}\n\n\
+                   data: {\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{\"content\":[]}}\n\n";
+        let link = link_body(&state, Direction::Out, "resp-1", sse, None);
+        assert_eq!(link.as_deref(), Some("req-1"));
+        let timelines = state.progress.timelines();
+        assert_eq!(timelines[0].progress.len(), 1);
+        assert!(timelines[0].completed_ms.is_some());
+    }
+
+    #[test]
+    fn test_replace_sse_data() {
+        let text = "event: message\ndata: {\"id\":1}\r\n\ndata: {\"id\":2}\n\n";
+        let replacement = serde_json::json!({"id": 2, "error": {"code": -32000}});
+        let replaced = replace_sse_data(text, &[None, Some(replacement.clone())]);
+        assert_eq!(
+            replaced,
+            format!("event: message\ndata: {{\"id\":1}}\r\n\ndata: {replacement}\n\n")
+        );
+    }
 }
@@ -6,7 +6,14 @@
 //!
 //! - `reticle run [OPTIONS] -- <COMMAND>` - Wrap stdio-based MCP servers
 //! - `reticle proxy` - HTTP reverse proxy for remote MCP servers
+//! - `reticle replay <SESSION> -- <COMMAND>` - Re-send a recorded session to a server
+//! - `reticle mock <SESSION>` - Serve a recorded session's responses as a mock server
+//! - `reticle diff <A> <B>` - Semantic diff between two recorded sessions
+//! - `reticle analyze -- <COMMAND>` - Measure the context cost of an MCP server
+//! - `reticle history [SERVER]` - List saved server analyses
+//! - `reticle changes <A> [B]` - Show what changed in a server between two analyses
 //! - `reticle daemon` - Start the Reticle daemon (hub for CLI instances)
+//! - `reticle tail` - Watch live traffic in the terminal
 //! - `reticle ui` - Launch the Reticle GUI dashboard
 //!
 //! # Architecture: Hub-and-Spoke
@@ -44,13 +51,32 @@
 //! agent functionality is never degraded.
 
 use clap::{Parser, Subcommand};
+use reticle_core::breakpoints::BreakpointSet;
+use reticle_core::budget::{BudgetConfig, TokenBudget};
+use reticle_core::chaos::ChaosEngine;
 use reticle_core::events::{InjectReceiver, NoOpEventSink, StdoutEventSink, UnixSocketEventSink};
+use reticle_core::mock::MockFallback;
+use reticle_core::rules::RulesEngine;
+use reticle_core::server_analyzer::{AnalysisThresholds, ServerAnalyzer};
+use reticle_core::session_diff::DiffOptions;
+use reticle_core::session_recorder::{RecordedSession, ReplayTiming};
+use reticle_core::storage::SessionStorage;
+use reticle_core::tokenizer::TokenizerSpec;
+use reticle_core::tool_lint::LintSeverity;
+use reticle_core::transport::TransportType;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Duration;
 use tracing_subscriber::EnvFilter;
 
+mod analyze;
 mod daemon;
+mod diff;
 mod http_proxy;
+mod mock;
 mod proxy;
+mod replay;
+mod tail;
 
 /// Reticle - The Wireshark for the Model Context Protocol
 ///
@@ -100,6 +126,43 @@ enum Commands {
         #[arg(long, value_enum, default_value = "text")]
         format: LogFormat,
 
+        /// Hold matching messages until resumed from the GUI or daemon
+        ///
+        /// Can be repeated. A bare value matches a method; otherwise use
+        /// comma-separated keys: method, tool, direction (in/out), path
+        /// (JSONPath) and value.
+        ///
+        /// Example: --break tools/call --break "tool=read_file,direction=in"
+        #[arg(long = "break", value_name = "SPEC")]
+        breakpoints: Vec<String>,
+
+        /// Release held messages unchanged after this many seconds
+        #[arg(long, value_name = "SECONDS")]
+        break_timeout: Option<u64>,
+
+        /// Rewrite, drop, or delay messages using a TOML or JSON rules file
+        #[arg(long, value_name = "FILE")]
+        rules: Option<PathBuf>,
+
+        /// Inject latency, errors, dropped or truncated responses, or server
+        /// kills using a TOML or JSON chaos profile
+        #[arg(long, value_name = "FILE")]
+        chaos: Option<PathBuf>,
+
+        /// Report requests that get no response within this many seconds
+        #[arg(long, value_name = "SECONDS", default_value = "60")]
+        request_timeout: u64,
+
+        /// Warn about, truncate, or reject server messages over the token
+        /// limits in a TOML or JSON budget file
+        #[arg(long, value_name = "FILE")]
+        budget: Option<PathBuf>,
+
+        /// Tokenizer for budgets: heuristic, cl100k_base, o200k_base, or
+        /// file:<tokenizer.json>
+        #[arg(long, value_name = "NAME", default_value = "heuristic")]
+        tokenizer: TokenizerSpec,
+
         /// The command and arguments to run
         #[arg(last = true, required = true)]
         command: Vec<String>,
@@ -137,12 +200,280 @@ enum Commands {
         /// Disable telemetry (pure proxy mode)
         #[arg(long)]
         no_telemetry: bool,
+
+        /// Rewrite, drop, or delay messages using a TOML or JSON rules file
+        #[arg(long, value_name = "FILE")]
+        rules: Option<PathBuf>,
+
+        /// Inject latency, errors, or dropped or truncated responses using a
+        /// TOML or JSON chaos profile
+        #[arg(long, value_name = "FILE")]
+        chaos: Option<PathBuf>,
+
+        /// Report requests that get no response within this many seconds
+        #[arg(long, value_name = "SECONDS", default_value = "60")]
+        request_timeout: u64,
+
+        /// Warn about, truncate, or reject server messages over the token
+        /// limits in a TOML or JSON budget file
+        #[arg(long, value_name = "FILE")]
+        budget: Option<PathBuf>,
+
+        /// Tokenizer for budgets: heuristic, cl100k_base, o200k_base, or
+        /// file:<tokenizer.json>
+        #[arg(long, value_name = "NAME", default_value = "heuristic")]
+        tokenizer: TokenizerSpec,
+    },
+
+    /// Replay a recorded session against a live stdio MCP server
+    ///
+    /// Re-sends every client message from the recording with its original
+    /// timing and records the server's responses as a new session. Exits
+    /// with a failure status if any replayed request goes unanswered.
+    ///
+    /// SESSION is a session ID from the recordings database or the path of
+    /// an exported session JSON file.
+    ///
+    /// Example:
+    ///   reticle replay 5f0c... -- npx -y @modelcontextprotocol/server-filesystem /tmp
+    ///   reticle replay session.json --speed 4 --max-gap 500 -- python server.py
+    Replay {
+        /// Session ID, name, or path to an exported session JSON file
+        session: String,
+
+        /// Playback speed multiplier (2 = twice as fast, 0 = no delays)
+        #[arg(long, default_value = "1.0")]
+        speed: f64,
+
+        /// Cap on the pause between two messages, in milliseconds
+        #[arg(long, value_name = "MS")]
+        max_gap: Option<u64>,
+
+        /// Seconds to wait for outstanding responses after the last message
+        #[arg(long, default_value = "10")]
+        timeout: u64,
+
+        /// Recordings database to load from and save to
+        #[arg(long, value_name = "PATH")]
+        storage: Option<PathBuf>,
+
+        /// Server name for the new recording (defaults to the command name)
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Don't save the replayed session
+        #[arg(long)]
+        no_save: bool,
+
+        /// The MCP server command to replay against
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Serve the responses from a recorded session as a mock MCP server
+    ///
+    /// Requests are matched against the recording by method and params
+    /// (ignoring key order and `_meta`). Repeated requests get the recorded
+    /// responses in order. Unmatched requests are handled by `--fallback`:
+    /// `method` reuses a response to the same method with other params,
+    /// `error` answers with a JSON-RPC error, and `empty` with an empty result.
+    ///
+    /// Example:
+    ///   reticle mock 5f0c...                          # stdio, for use as a server command
+    ///   reticle mock session.json --transport http --listen 3001
+    Mock {
+        /// Session ID, name, or path to an exported session JSON file
+        session: String,
+
+        /// Transport to serve on
+        #[arg(long, value_enum, default_value = "stdio")]
+        transport: MockTransport,
+
+        /// Local port to listen on (HTTP transport)
+        #[arg(short, long, default_value = "3001")]
+        listen: u16,
+
+        /// How to answer requests with no exact match: method, error or empty
+        #[arg(long, default_value = "method")]
+        fallback: MockFallback,
+
+        /// Recordings database to load from
+        #[arg(long, value_name = "PATH")]
+        storage: Option<PathBuf>,
+    },
+
+    /// Compare two recorded sessions
+    ///
+    /// Aligns the calls in both sessions by method sequence and JSON-RPC id,
+    /// then reports added and removed calls, structural changes to params
+    /// and results, latency regressions, and token deltas. Exits with a
+    /// failure status when the sessions differ, like `diff`.
+    ///
+    /// Example:
+    ///   reticle diff before.json after.json
+    ///   reticle diff 5f0c... 9a1e... --json
+    Diff {
+        /// Baseline session: ID, name, or exported session JSON file
+        a: String,
+
+        /// Session to compare against the baseline
+        b: String,
+
+        /// Print the diff as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Also list unchanged calls
+        #[arg(long)]
+        all: bool,
+
+        /// Relative slowdown reported as a latency regression (0.2 = 20%)
+        #[arg(long, default_value = "0.2")]
+        latency_threshold: f64,
+
+        /// Ignore slowdowns smaller than this many milliseconds
+        #[arg(long, default_value = "50", value_name = "MS")]
+        min_latency_delta: u64,
+
+        /// Recordings database to load from
+        #[arg(long, value_name = "PATH")]
+        storage: Option<PathBuf>,
+    },
+
+    /// Measure the context cost of an MCP server
+    ///
+    /// Starts a stdio server (or connects to a running one with --url), runs
+    /// `initialize`, and counts the tokens its tool, prompt, and resource
+    /// definitions add to an agent's context. Exits with a failure status
+    /// when any --max-* threshold is exceeded, so it can guard against
+    /// tool-description bloat in CI.
+    ///
+    /// Example:
+    ///   reticle analyze -- npx -y @modelcontextprotocol/server-filesystem /tmp
+    ///   reticle analyze --max-tool-tokens 500 --format markdown -- python server.py
+    ///   reticle analyze --url https://mcp.example.com/mcp -H "Authorization: Bearer $TOKEN"
+    Analyze {
+        /// Analyze a running server at this URL instead of starting one
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Transport for --url (default: websocket for ws:// URLs, otherwise streamable)
+        #[arg(long, value_enum)]
+        transport: Option<AnalyzeTransport>,
+
+        /// Header to send to a remote server (can be repeated)
+        #[arg(short = 'H', long = "header", value_name = "NAME: VALUE", value_parser = parse_header)]
+        headers: Vec<(String, String)>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: AnalyzeFormat,
+
+        /// Tokenizer: heuristic, cl100k_base, o200k_base, or file:<tokenizer.json>
+        #[arg(long, value_name = "NAME", default_value = "heuristic")]
+        tokenizer: TokenizerSpec,
+
+        /// Seconds to wait for each response from the server
+        #[arg(long, value_name = "SECONDS", default_value = "30")]
+        timeout: u64,
+
+        /// Environment variable for the server (can be repeated)
+        #[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+        env: Vec<(String, String)>,
+
+        /// Fail when all definitions together take more tokens than this
+        #[arg(long, value_name = "TOKENS")]
+        max_total_tokens: Option<u64>,
+
+        /// Fail when any one tool definition takes more tokens than this
+        #[arg(long, value_name = "TOKENS")]
+        max_tool_tokens: Option<u64>,
+
+        /// Fail when any one tool description takes more tokens than this
+        #[arg(long, value_name = "TOKENS")]
+        max_description_tokens: Option<u64>,
+
+        /// Fail when the server exposes more tools than this
+        #[arg(long, value_name = "COUNT")]
+        max_tools: Option<u64>,
+
+        /// Fail on any tool lint finding at least this severe (info, warning, error)
+        #[arg(long, value_name = "SEVERITY")]
+        fail_on_lint: Option<LintSeverity>,
+
+        /// Recordings database to save the analysis to
+        #[arg(long, value_name = "PATH")]
+        storage: Option<PathBuf>,
+
+        /// Don't save the analysis to the history
+        #[arg(long)]
+        no_save: bool,
+
+        /// The command and arguments to run
+        #[arg(
+            last = true,
+            required_unless_present = "url",
+            conflicts_with_all = ["url", "transport", "headers"]
+        )]
+        command: Vec<String>,
+    },
+
+    /// List saved server analyses
+    ///
+    /// Every `reticle analyze` run is saved, one entry per server name and
+    /// version, newest first. Use `reticle changes` to compare two of them.
+    ///
+    /// Example:
+    ///   reticle history
+    ///   reticle history github --json
+    History {
+        /// Only list analyses of this server
+        server: Option<String>,
+
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Recordings database to load from
+        #[arg(long, value_name = "PATH")]
+        storage: Option<PathBuf>,
+    },
+
+    /// Show what changed in a server between two saved analyses
+    ///
+    /// Each analysis is NAME@VERSION from the history, NAME for the newest
+    /// analysis of a server, or a file written by `reticle analyze --format
+    /// json`. Given only a server name, compares its two most recently
+    /// analyzed versions. Reports tools added and removed, description and
+    /// input schema changes, and token deltas. Exits with a failure status
+    /// when the analyses differ, like `diff`.
+    ///
+    /// Example:
+    ///   reticle changes github
+    ///   reticle changes github@1.0.0 github@1.1.0 --json
+    ///   reticle changes baseline.json github
+    Changes {
+        /// Baseline analysis, or a server name alone
+        a: String,
+
+        /// Analysis to compare against the baseline
+        b: Option<String>,
+
+        /// Print the diff as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Recordings database to load from
+        #[arg(long, value_name = "PATH")]
+        storage: Option<PathBuf>,
     },
 
     /// Start the Reticle daemon (telemetry hub)
     ///
     /// The daemon listens on a Unix socket and receives telemetry from
     /// all CLI instances. It can forward events to the GUI or operate standalone.
+    /// Each session is recorded and saved to the recordings database when
+    /// it ends, as the GUI would.
     ///
     /// Typically you don't need to run this manually - the Reticle GUI
     /// includes the daemon. Use this for headless/server deployments.
@@ -150,18 +481,68 @@ enum Commands {
     /// Example:
     ///   reticle daemon                          # Default socket
     ///   reticle daemon --socket /tmp/my.sock    # Custom socket
+    ///   reticle daemon --port 9315              # With the web UI and HTTP/JSON API
     Daemon {
         /// Unix socket path to listen on
         #[arg(short, long, default_value = "/tmp/reticle.sock")]
         socket: String,
 
-        /// Optional TCP port for remote connections
+        /// Port for the web UI and HTTP/JSON query API, served on 127.0.0.1
         #[arg(short, long)]
         port: Option<u16>,
 
         /// Output received events to stdout (for debugging)
         #[arg(long)]
         verbose: bool,
+
+        /// Recordings database to save sessions to
+        #[arg(long, value_name = "PATH")]
+        storage: Option<PathBuf>,
+
+        /// Don't save sessions
+        #[arg(long)]
+        no_save: bool,
+    },
+
+    /// Watch live MCP traffic in the terminal
+    ///
+    /// Shows the messages of every wrapped server in one scrollable list,
+    /// each server in its own colour, with responses paired to their
+    /// requests and the selected message pretty-printed beside the list.
+    ///
+    /// By default `tail` watches the live stream of a daemon started with
+    /// `--port 9315`. If nothing is listening there, it listens on the hub
+    /// socket itself, in place of the GUI or daemon, and CLI instances
+    /// connect to it as they would to the hub. --port watches a daemon on
+    /// another port, and --socket always listens on the socket.
+    ///
+    /// Keys: ↑/↓ select, enter jump to the paired message, / filter,
+    /// s cycle servers, d cycle directions, esc clear filters, q quit.
+    ///
+    /// Example:
+    ///   reticle tail                            # Watch a daemon, or listen on the hub socket
+    ///   reticle tail --port 9400                # Watch a daemon on another port
+    ///   reticle tail --server github --direction in
+    Tail {
+        /// Unix socket path to listen on [default: $RETICLE_SOCKET or /tmp/reticle.sock]
+        #[arg(short, long, conflicts_with = "port")]
+        socket: Option<PathBuf>,
+
+        /// Port of a daemon's HTTP/JSON API to stream from [default: 9315, or the hub socket if no daemon is listening]
+        #[arg(short, long)]
+        port: Option<u16>,
+
+        /// Only show messages from this server
+        #[arg(long)]
+        server: Option<String>,
+
+        /// Only show messages in this direction (in: to the server, out: to the client)
+        #[arg(long, value_parser = tail::parse_direction)]
+        direction: Option<reticle_core::protocol::Direction>,
+
+        /// Only show messages containing this text
+        #[arg(long)]
+        filter: Option<String>,
     },
 
     /// Launch the Reticle GUI dashboard
@@ -189,6 +570,47 @@ enum Commands {
     },
 }
 
+#[derive(Debug, Clone, Default, PartialEq, clap::ValueEnum)]
+enum MockTransport {
+    /// JSON-RPC over stdin/stdout
+    #[default]
+    Stdio,
+    /// Streamable HTTP (JSON responses to POST)
+    Http,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum AnalyzeTransport {
+    /// Streamable HTTP (protocol 2025-03-26)
+    Streamable,
+    /// HTTP with SSE (protocol 2024-11-05)
+    #[value(alias = "http")]
+    Sse,
+    /// WebSocket
+    Websocket,
+}
+
+impl From<AnalyzeTransport> for TransportType {
+    fn from(transport: AnalyzeTransport) -> Self {
+        match transport {
+            AnalyzeTransport::Streamable => TransportType::Streamable,
+            AnalyzeTransport::Sse => TransportType::Http,
+            AnalyzeTransport::Websocket => TransportType::WebSocket,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum AnalyzeFormat {
+    /// Human-readable summary
+    #[default]
+    Text,
+    /// The full analysis as JSON
+    Json,
+    /// Markdown tables, for CI summaries and PR comments
+    Markdown,
+}
+
 #[derive(Debug, Clone, Default, clap::ValueEnum)]
 enum LogFormat {
     /// Human-readable text output
@@ -209,8 +631,32 @@ async fn main() -> ExitCode {
             no_telemetry,
             log,
             format,
+            breakpoints,
+            break_timeout,
+            rules,
+            chaos,
+            request_timeout,
+            budget,
+            tokenizer,
             command,
-        } => run_stdio(name, socket, no_telemetry, log, format, command).await,
+        } => {
+            run_stdio(StdioArgs {
+                name,
+                socket,
+                no_telemetry,
+                log,
+                format,
+                breakpoints,
+                break_timeout,
+                rules,
+                chaos,
+                request_timeout: Duration::from_secs(request_timeout),
+                budget,
+                tokenizer,
+                command,
+            })
+            .await
+        }
 
         Commands::Proxy {
             name,
@@ -218,27 +664,225 @@ async fn main() -> ExitCode {
             upstream,
             socket,
             no_telemetry,
-        } => run_proxy(name, listen, upstream, socket, no_telemetry).await,
+            rules,
+            chaos,
+            request_timeout,
+            budget,
+            tokenizer,
+        } => {
+            run_proxy(ProxyArgs {
+                name,
+                listen,
+                upstream,
+                socket,
+                no_telemetry,
+                rules,
+                chaos,
+                request_timeout: Duration::from_secs(request_timeout),
+                budget,
+                tokenizer,
+            })
+            .await
+        }
+
+        Commands::Replay {
+            session,
+            speed,
+            max_gap,
+            timeout,
+            storage,
+            name,
+            no_save,
+            command,
+        } => {
+            let options = replay::ReplayOptions {
+                timing: ReplayTiming {
+                    speed,
+                    max_gap: max_gap.map(Duration::from_millis),
+                },
+                response_timeout: Duration::from_secs(timeout),
+            };
+            replay::run_replay(session, options, storage, name, no_save, command).await
+        }
+
+        Commands::Mock {
+            session,
+            transport,
+            listen,
+            fallback,
+            storage,
+        } => mock::run_mock(session, transport, listen, fallback, storage).await,
+
+        Commands::Diff {
+            a,
+            b,
+            json,
+            all,
+            latency_threshold,
+            min_latency_delta,
+            storage,
+        } => {
+            let options = DiffOptions {
+                latency_threshold,
+                min_latency_delta_ms: min_latency_delta,
+            };
+            diff::run_diff(a, b, json, all, options, storage).await
+        }
+
+        Commands::Analyze {
+            url,
+            transport,
+            headers,
+            format,
+            tokenizer,
+            timeout,
+            env,
+            max_total_tokens,
+            max_tool_tokens,
+            max_description_tokens,
+            max_tools,
+            fail_on_lint,
+            storage,
+            no_save,
+            command,
+        } => {
+            let thresholds = AnalysisThresholds {
+                max_total_tokens,
+                max_tool_tokens,
+                max_description_tokens,
+                max_tools,
+                fail_on_lint,
+            };
+            let analyzer = match url {
+                Some(url) => {
+                    let transport = transport.map_or_else(
+                        || {
+                            if url.starts_with("ws://") || url.starts_with("wss://") {
+                                TransportType::WebSocket
+                            } else {
+                                TransportType::Streamable
+                            }
+                        },
+                        TransportType::from,
+                    );
+                    ServerAnalyzer::remote(url, transport)
+                        .with_headers(headers.into_iter().collect())
+                }
+                None => ServerAnalyzer::new(command[0].clone(), command[1..].to_vec()),
+            };
+            let analyzer = analyzer
+                .with_env(env.into_iter().collect())
+                .with_timeout(timeout);
+            let storage = (!no_save).then(|| storage.unwrap_or_else(SessionStorage::default_path));
+            analyze::run_analyze(analyzer, format, tokenizer, thresholds, storage).await
+        }
+
+        Commands::History {
+            server,
+            json,
+            storage,
+        } => analyze::run_history(server, json, storage).await,
+
+        Commands::Changes {
+            a,
+            b,
+            json,
+            storage,
+        } => analyze::run_changes(a, b, json, storage).await,
 
         Commands::Daemon {
             socket,
             port,
             verbose,
-        } => run_daemon(socket, port, verbose).await,
+            storage,
+            no_save,
+        } => {
+            let storage = (!no_save).then(|| storage.unwrap_or_else(SessionStorage::default_path));
+            run_daemon(socket, port, verbose, storage).await
+        }
+
+        Commands::Tail {
+            socket,
+            port,
+            server,
+            direction,
+            filter,
+        } => {
+            let source = match (port, socket) {
+                (Some(port), _) => tail::Source::Daemon(port),
+                (None, Some(socket)) => tail::Source::Socket(socket),
+                (None, None) => tail::Source::DaemonOrSocket(
+                    tail::DAEMON_PORT,
+                    reticle_core::events::get_socket_path(),
+                ),
+            };
+            let filter = tail::Filter {
+                server,
+                direction,
+                text: filter.unwrap_or_default(),
+            };
+            match tail::run_tail(source, filter).await {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("[reticle tail] Error: {e}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
 
         Commands::Ui { detach, dev } => run_ui(detach, dev).await,
     }
 }
 
-/// Run stdio proxy mode
-async fn run_stdio(
+/// Arguments of `reticle run`
+struct StdioArgs {
     name: Option<String>,
     socket: Option<String>,
     no_telemetry: bool,
     log: bool,
     format: LogFormat,
+    breakpoints: Vec<String>,
+    break_timeout: Option<u64>,
+    rules: Option<PathBuf>,
+    chaos: Option<PathBuf>,
+    request_timeout: Duration,
+    budget: Option<PathBuf>,
+    tokenizer: TokenizerSpec,
     command: Vec<String>,
-) -> ExitCode {
+}
+
+/// Arguments of `reticle proxy`
+struct ProxyArgs {
+    name: String,
+    listen: u16,
+    upstream: String,
+    socket: Option<String>,
+    no_telemetry: bool,
+    rules: Option<PathBuf>,
+    chaos: Option<PathBuf>,
+    request_timeout: Duration,
+    budget: Option<PathBuf>,
+    tokenizer: TokenizerSpec,
+}
+
+/// Run stdio proxy mode
+async fn run_stdio(args: StdioArgs) -> ExitCode {
+    let StdioArgs {
+        name,
+        socket,
+        no_telemetry,
+        log,
+        format,
+        breakpoints,
+        break_timeout,
+        rules,
+        chaos,
+        request_timeout,
+        budget,
+        tokenizer,
+        command,
+    } = args;
+
     if command.is_empty() {
         eprintln!("Error: No command specified");
         eprintln!("Usage: reticle --name <NAME> -- <COMMAND> [ARGS...]");
@@ -249,6 +893,55 @@ async fn run_stdio(
     let args: Vec<&str> = command[1..].iter().map(|s| s.as_str()).collect();
     let server_name = name.unwrap_or_else(|| extract_server_name(cmd));
 
+    let mut options = proxy::ProxyOptions {
+        breakpoints: match BreakpointSet::from_specs(&breakpoints) {
+            Ok(set) => set,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        resume_rx: None,
+        hold_timeout: break_timeout.map(std::time::Duration::from_secs),
+        rules: match load_rules(rules.as_deref()) {
+            Ok(rules) => rules,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        chaos: match load_chaos(chaos.as_deref()) {
+            Ok(chaos) => chaos,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        request_timeout: Some(request_timeout),
+        budget: match load_budget(budget.as_deref(), &server_name) {
+            Ok(budget) => budget,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        tokenizer: match tokenizer.load() {
+            Ok(tokenizer) => Some(tokenizer),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    // Without the Hub nothing can resume a held message
+    if !options.breakpoints.is_empty() && (log || no_telemetry) && options.hold_timeout.is_none() {
+        eprintln!(
+            "Warning: breakpoints can only be resumed through the daemon or GUI; \
+             held messages will wait forever (use --break-timeout)"
+        );
+    }
+
     // Decide which event sink to use
     if log {
         // Standalone log mode - output to stderr
@@ -263,10 +956,10 @@ async fn run_stdio(
         let json_output = matches!(format, LogFormat::Json);
         let event_sink = StdoutEventSink::new(json_output);
         tracing::info!("Starting Reticle for '{}' (log mode)", server_name);
-        run_proxy_with_sink(cmd, &args, &server_name, event_sink, None).await
+        run_proxy_with_sink(cmd, &args, &server_name, event_sink, None, options).await
     } else if no_telemetry {
         // Pure proxy mode - no telemetry
-        run_proxy_with_sink(cmd, &args, &server_name, NoOpEventSink, None).await
+        run_proxy_with_sink(cmd, &args, &server_name, NoOpEventSink, None, options).await
     } else {
         // Connect to daemon (fail-open: continues even if daemon unavailable)
         if let Some(path) = socket {
@@ -274,18 +967,34 @@ async fn run_stdio(
         }
 
         let (event_sink, inject_rx) = UnixSocketEventSink::new(server_name.clone()).await;
-        run_proxy_with_sink(cmd, &args, &server_name, event_sink, Some(inject_rx)).await
+        options.resume_rx = event_sink.take_resume_receiver().await;
+        run_proxy_with_sink(
+            cmd,
+            &args,
+            &server_name,
+            event_sink,
+            Some(inject_rx),
+            options,
+        )
+        .await
     }
 }
 
 /// Run HTTP proxy mode
-async fn run_proxy(
-    name: String,
-    listen: u16,
-    upstream: String,
-    socket: Option<String>,
-    no_telemetry: bool,
-) -> ExitCode {
+async fn run_proxy(args: ProxyArgs) -> ExitCode {
+    let ProxyArgs {
+        name,
+        listen,
+        upstream,
+        socket,
+        no_telemetry,
+        rules,
+        chaos,
+        request_timeout,
+        budget,
+        tokenizer,
+    } = args;
+
     // Initialize tracing
     tracing_subscriber::fmt()
         .with_env_filter(
@@ -295,10 +1004,46 @@ async fn run_proxy(
         .with_writer(std::io::stderr)
         .init();
 
+    let rules = match load_rules(rules.as_deref()) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("[reticle proxy] Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let chaos = match load_chaos(chaos.as_deref()) {
+        Ok(chaos) => chaos,
+        Err(e) => {
+            eprintln!("[reticle proxy] Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let budget = match load_budget(budget.as_deref(), &name) {
+        Ok(budget) => budget,
+        Err(e) => {
+            eprintln!("[reticle proxy] Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let tokenizer = match tokenizer.load() {
+        Ok(tokenizer) => tokenizer,
+        Err(e) => {
+            eprintln!("[reticle proxy] Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let options = http_proxy::HttpProxyOptions {
+        rules,
+        chaos,
+        request_timeout: Some(request_timeout),
+        budget,
+        tokenizer: Some(tokenizer),
+    };
+
     if no_telemetry {
         eprintln!("[reticle proxy] Running in pure proxy mode (no telemetry)");
         let event_sink = http_proxy::HttpEventSink::NoOp(NoOpEventSink);
-        match http_proxy::run_http_proxy(upstream, listen, name, event_sink, None).await {
+        match http_proxy::run_http_proxy(upstream, listen, name, event_sink, None, options).await {
             Ok(()) => ExitCode::SUCCESS,
             Err(e) => {
                 eprintln!("[reticle proxy] Error: {e}");
@@ -314,7 +1059,15 @@ async fn run_proxy(
         let (unix_sink, inject_rx) = UnixSocketEventSink::new(name.clone()).await;
         let event_sink = http_proxy::HttpEventSink::UnixSocket(std::sync::Arc::new(unix_sink));
 
-        match http_proxy::run_http_proxy(upstream, listen, name, event_sink, Some(inject_rx)).await
+        match http_proxy::run_http_proxy(
+            upstream,
+            listen,
+            name,
+            event_sink,
+            Some(inject_rx),
+            options,
+        )
+        .await
         {
             Ok(()) => ExitCode::SUCCESS,
             Err(e) => {
@@ -325,8 +1078,97 @@ async fn run_proxy(
     }
 }
 
+/// Parse a `Name: Value` header
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected 'Name: Value', got '{s}'")),
+    }
+}
+
+/// Parse a `KEY=VALUE` environment variable
+fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{s}'")),
+    }
+}
+
+/// Load a recording from an exported JSON file or the recordings database
+///
+/// Database lookups try the session ID first, then the session name.
+async fn load_recording(session: &str, storage_path: &Path) -> Result<RecordedSession, String> {
+    let file = Path::new(session);
+    if file.is_file() {
+        let text = std::fs::read_to_string(file).map_err(|e| format!("{session}: {e}"))?;
+        return serde_json::from_str(&text).map_err(|e| format!("{session}: {e}"));
+    }
+
+    let storage = SessionStorage::new(storage_path.to_path_buf())
+        .map_err(|e| format!("{}: {e}", storage_path.display()))?;
+    if let Ok(recording) = storage.load_session(session).await {
+        return Ok(recording);
+    }
+    let sessions = storage.list_sessions().await.map_err(|e| e.to_string())?;
+    match sessions.iter().find(|info| info.name == session) {
+        Some(info) => storage
+            .load_session(&info.id)
+            .await
+            .map_err(|e| e.to_string()),
+        None => Err(format!("No recorded session '{session}'")),
+    }
+}
+
+/// Load the rules file, if one was given
+fn load_rules(path: Option<&Path>) -> Result<RulesEngine, String> {
+    let Some(path) = path else {
+        return Ok(RulesEngine::default());
+    };
+    let rules = RulesEngine::from_file(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    tracing::info!(
+        "Loaded {} rule(s) from {}",
+        rules.rules().len(),
+        path.display()
+    );
+    Ok(rules)
+}
+
+/// Load the chaos profile, if one was given
+fn load_chaos(path: Option<&Path>) -> Result<ChaosEngine, String> {
+    let Some(path) = path else {
+        return Ok(ChaosEngine::default());
+    };
+    let chaos = ChaosEngine::from_file(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    tracing::info!(
+        "Loaded {} fault(s) from {}",
+        chaos.faults().len(),
+        path.display()
+    );
+    Ok(chaos)
+}
+
+/// Load the token budget for a server, if a budget file was given
+fn load_budget(path: Option<&Path>, server_name: &str) -> Result<TokenBudget, String> {
+    let Some(path) = path else {
+        return Ok(TokenBudget::default());
+    };
+    let config = BudgetConfig::from_file(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let budget = config.for_server(server_name);
+    if budget.is_empty() {
+        tracing::warn!("{} sets no limits for '{}'", path.display(), server_name);
+    }
+    Ok(budget)
+}
+
 /// Run daemon mode
-async fn run_daemon(socket: String, port: Option<u16>, verbose: bool) -> ExitCode {
+async fn run_daemon(
+    socket: String,
+    port: Option<u16>,
+    verbose: bool,
+    storage: Option<PathBuf>,
+) -> ExitCode {
     let level = if verbose { "debug" } else { "info" };
     tracing_subscriber::fmt()
         .with_env_filter(
@@ -338,10 +1180,11 @@ async fn run_daemon(socket: String, port: Option<u16>, verbose: bool) -> ExitCod
     tracing::info!("Starting Reticle daemon");
     tracing::info!("  Socket: {}", socket);
     if let Some(p) = port {
-        tracing::info!("  TCP port: {}", p);
+        tracing::info!("  Web UI: http://127.0.0.1:{}", p);
+        tracing::info!("  API: http://127.0.0.1:{}/api", p);
     }
 
-    match daemon::run_daemon(&socket, port, verbose).await {
+    match daemon::run_daemon(&socket, port, verbose, storage).await {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
             eprintln!("[reticle daemon] Error: {e}");
@@ -871,8 +1714,9 @@ async fn run_proxy_with_sink<S: reticle_core::events::EventSink + 'static>(
     server_name: &str,
     event_sink: S,
     inject_rx: Option<InjectReceiver>,
+    options: proxy::ProxyOptions,
 ) -> ExitCode {
-    match proxy::run_stdio_proxy(cmd, args, server_name, event_sink, inject_rx).await {
+    match proxy::run_stdio_proxy(cmd, args, server_name, event_sink, inject_rx, options).await {
         Ok(exit_code) => {
             if exit_code == 0 {
                 ExitCode::SUCCESS
@@ -891,6 +1735,7 @@ async fn run_proxy_with_sink<S: reticle_core::events::EventSink + 'static>(
 mod tests {
     use super::*;
     use clap::Parser;
+    use reticle_core::budget::BudgetAction;
 
     #[test]
     fn test_extract_server_name_simple() {
@@ -1107,6 +1952,454 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_run_breakpoints() {
+        let cli = Cli::parse_from([
+            "reticle",
+            "run",
+            "--break",
+            "tools/call",
+            "--break",
+            "tool=read_file,direction=in",
+            "--break-timeout",
+            "30",
+            "--",
+            "node",
+            "index.js",
+        ]);
+        match cli.command {
+            Commands::Run {
+                breakpoints,
+                break_timeout,
+                command,
+                ..
+            } => {
+                assert_eq!(
+                    breakpoints,
+                    vec!["tools/call", "tool=read_file,direction=in"]
+                );
+                assert_eq!(break_timeout, Some(30));
+                assert_eq!(command, vec!["node", "index.js"]);
+                assert!(BreakpointSet::from_specs(&breakpoints).is_ok());
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_run_no_breakpoints_by_default() {
+        let cli = Cli::parse_from(["reticle", "run", "--", "echo"]);
+        match cli.command {
+            Commands::Run {
+                breakpoints,
+                break_timeout,
+                ..
+            } => {
+                assert!(breakpoints.is_empty());
+                assert!(break_timeout.is_none());
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_run_rules() {
+        let cli = Cli::parse_from(["reticle", "run", "--rules", "rules.toml", "--", "echo"]);
+        match cli.command {
+            Commands::Run { rules, .. } => {
+                assert_eq!(rules, Some(PathBuf::from("rules.toml")));
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_load_rules() {
+        assert!(load_rules(None).unwrap().is_empty());
+        assert!(load_rules(Some(Path::new("/nonexistent/rules.toml"))).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rules.toml");
+        std::fs::write(
+            &path,
+            "[[rules]]\nmatch = { method = \"ping\" }\ndrop = true\n",
+        )
+        .unwrap();
+        assert_eq!(load_rules(Some(&path)).unwrap().rules().len(), 1);
+    }
+
+    #[test]
+    fn test_cli_proxy_chaos() {
+        let cli = Cli::parse_from([
+            "reticle",
+            "proxy",
+            "--name",
+            "api",
+            "--upstream",
+            "http://localhost:8080",
+            "--chaos",
+            "chaos.json",
+        ]);
+        match cli.command {
+            Commands::Proxy { chaos, rules, .. } => {
+                assert_eq!(chaos, Some(PathBuf::from("chaos.json")));
+                assert!(rules.is_none());
+            }
+            _ => panic!("Expected Proxy command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_request_timeout() {
+        let cli = Cli::parse_from(["reticle", "run", "--", "echo"]);
+        match cli.command {
+            Commands::Run {
+                request_timeout, ..
+            } => assert_eq!(request_timeout, 60),
+            _ => panic!("Expected Run command"),
+        }
+
+        let cli = Cli::parse_from([
+            "reticle",
+            "proxy",
+            "--name",
+            "api",
+            "--upstream",
+            "http://localhost:8080",
+            "--request-timeout",
+            "5",
+        ]);
+        match cli.command {
+            Commands::Proxy {
+                request_timeout, ..
+            } => assert_eq!(request_timeout, 5),
+            _ => panic!("Expected Proxy command"),
+        }
+    }
+
+    #[test]
+    fn test_load_chaos() {
+        assert!(load_chaos(None).unwrap().is_empty());
+        assert!(load_chaos(Some(Path::new("/nonexistent/chaos.toml"))).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chaos.json");
+        std::fs::write(
+            &path,
+            r#"{"seed": 1, "faults": [{"probability": 0.5, "action": "kill"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(load_chaos(Some(&path)).unwrap().faults().len(), 1);
+    }
+
+    #[test]
+    fn test_cli_budget() {
+        let cli = Cli::parse_from([
+            "reticle",
+            "run",
+            "--budget",
+            "budget.toml",
+            "--tokenizer",
+            "o200k",
+            "--",
+            "echo",
+        ]);
+        match cli.command {
+            Commands::Run {
+                budget, tokenizer, ..
+            } => {
+                assert_eq!(budget, Some(PathBuf::from("budget.toml")));
+                assert_eq!(tokenizer, TokenizerSpec::O200kBase);
+            }
+            _ => panic!("Expected Run command"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["reticle", "run", "--tokenizer", "gpt2", "--", "echo"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_load_budget() {
+        assert!(load_budget(None, "api").unwrap().is_empty());
+        assert!(load_budget(Some(Path::new("/nonexistent/budget.toml")), "api").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("budget.toml");
+        std::fs::write(
+            &path,
+            "tool_result = 1000\n\n[servers.github]\ntool_result = 200\naction = \"truncate\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load_budget(Some(&path), "api").unwrap().tool_result,
+            Some(1000)
+        );
+        let github = load_budget(Some(&path), "github").unwrap();
+        assert_eq!(github.tool_result, Some(200));
+        assert_eq!(github.action(), BudgetAction::Truncate);
+
+        std::fs::write(&path, "tool_result = 0\n").unwrap();
+        assert!(load_budget(Some(&path), "api").is_err());
+    }
+
+    // Analyze subcommand tests
+
+    #[test]
+    fn test_cli_analyze() {
+        let cli = Cli::parse_from([
+            "reticle",
+            "analyze",
+            "--format",
+            "markdown",
+            "--max-tool-tokens",
+            "500",
+            "--fail-on-lint",
+            "warning",
+            "-e",
+            "TOKEN=a=b",
+            "--",
+            "python",
+            "server.py",
+        ]);
+        match cli.command {
+            Commands::Analyze {
+                format,
+                tokenizer,
+                timeout,
+                env,
+                max_tool_tokens,
+                max_total_tokens,
+                fail_on_lint,
+                command,
+                ..
+            } => {
+                assert_eq!(format, AnalyzeFormat::Markdown);
+                assert_eq!(tokenizer, TokenizerSpec::Heuristic);
+                assert_eq!(timeout, 30);
+                assert_eq!(env, vec![("TOKEN".to_string(), "a=b".to_string())]);
+                assert_eq!(max_tool_tokens, Some(500));
+                assert!(max_total_tokens.is_none());
+                assert_eq!(fail_on_lint, Some(LintSeverity::Warning));
+                assert_eq!(command, vec!["python", "server.py"]);
+            }
+            _ => panic!("Expected Analyze command"),
+        }
+
+        assert!(Cli::try_parse_from(["reticle", "analyze"]).is_err());
+        assert!(
+            Cli::try_parse_from(["reticle", "analyze", "--transport", "sse", "--", "x"]).is_err()
+        );
+        assert!(
+            Cli::try_parse_from(["reticle", "analyze", "--url", "http://a", "--", "x"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["reticle", "analyze", "-e", "novalue", "--", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_analyze_url() {
+        let cli = Cli::parse_from([
+            "reticle",
+            "analyze",
+            "--url",
+            "http://localhost:8080/sse",
+            "--transport",
+            "http",
+            "-H",
+            "Authorization: Bearer abc:def",
+        ]);
+        match cli.command {
+            Commands::Analyze {
+                url,
+                transport,
+                headers,
+                command,
+                ..
+            } => {
+                assert_eq!(url.as_deref(), Some("http://localhost:8080/sse"));
+                assert_eq!(transport, Some(AnalyzeTransport::Sse));
+                assert_eq!(
+                    headers,
+                    vec![("Authorization".to_string(), "Bearer abc:def".to_string())]
+                );
+                assert!(command.is_empty());
+            }
+            _ => panic!("Expected Analyze command"),
+        }
+        assert!(parse_header("no-colon").is_err());
+    }
+
+    // Replay subcommand tests
+
+    #[test]
+    fn test_cli_replay() {
+        let cli = Cli::parse_from([
+            "reticle",
+            "replay",
+            "session.json",
+            "--speed",
+            "2",
+            "--max-gap",
+            "500",
+            "--",
+            "python",
+            "server.py",
+        ]);
+        match cli.command {
+            Commands::Replay {
+                session,
+                speed,
+                max_gap,
+                timeout,
+                no_save,
+                command,
+                ..
+            } => {
+                assert_eq!(session, "session.json");
+                assert_eq!(speed, 2.0);
+                assert_eq!(max_gap, Some(500));
+                assert_eq!(timeout, 10);
+                assert!(!no_save);
+                assert_eq!(command, vec!["python", "server.py"]);
+            }
+            _ => panic!("Expected Replay command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_replay_requires_command() {
+        assert!(Cli::try_parse_from(["reticle", "replay", "session.json"]).is_err());
+    }
+
+    // Mock subcommand tests
+
+    #[test]
+    fn test_cli_mock() {
+        let cli = Cli::parse_from(["reticle", "mock", "abc"]);
+        match cli.command {
+            Commands::Mock {
+                session,
+                transport,
+                fallback,
+                ..
+            } => {
+                assert_eq!(session, "abc");
+                assert_eq!(transport, MockTransport::Stdio);
+                assert_eq!(fallback, MockFallback::Method);
+            }
+            _ => panic!("Expected Mock command"),
+        }
+
+        let cli = Cli::parse_from([
+            "reticle",
+            "mock",
+            "session.json",
+            "--transport",
+            "http",
+            "--listen",
+            "4000",
+            "--fallback",
+            "empty",
+        ]);
+        match cli.command {
+            Commands::Mock {
+                transport,
+                listen,
+                fallback,
+                ..
+            } => {
+                assert_eq!(transport, MockTransport::Http);
+                assert_eq!(listen, 4000);
+                assert_eq!(fallback, MockFallback::Empty);
+            }
+            _ => panic!("Expected Mock command"),
+        }
+
+        assert!(Cli::try_parse_from(["reticle", "mock", "abc", "--fallback", "guess"]).is_err());
+    }
+
+    // Diff subcommand tests
+
+    #[test]
+    fn test_cli_diff() {
+        let cli = Cli::parse_from(["reticle", "diff", "a.json", "b.json", "--json"]);
+        match cli.command {
+            Commands::Diff {
+                a,
+                b,
+                json,
+                all,
+                latency_threshold,
+                min_latency_delta,
+                ..
+            } => {
+                assert_eq!(a, "a.json");
+                assert_eq!(b, "b.json");
+                assert!(json);
+                assert!(!all);
+                assert_eq!(latency_threshold, 0.2);
+                assert_eq!(min_latency_delta, 50);
+            }
+            _ => panic!("Expected Diff command"),
+        }
+
+        assert!(Cli::try_parse_from(["reticle", "diff", "a.json"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_history_and_changes() {
+        let cli = Cli::parse_from(["reticle", "history", "github", "--json"]);
+        match cli.command {
+            Commands::History { server, json, .. } => {
+                assert_eq!(server.as_deref(), Some("github"));
+                assert!(json);
+            }
+            _ => panic!("Expected History command"),
+        }
+
+        let cli = Cli::parse_from(["reticle", "changes", "github"]);
+        match cli.command {
+            Commands::Changes { a, b, json, .. } => {
+                assert_eq!(a, "github");
+                assert!(b.is_none());
+                assert!(!json);
+            }
+            _ => panic!("Expected Changes command"),
+        }
+
+        let cli = Cli::parse_from(["reticle", "analyze", "--no-save", "--", "server"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Analyze { no_save: true, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load_recording() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = reticle_core::SessionRecorder::new(
+            "abc".to_string(),
+            "nightly".to_string(),
+            "stdio".to_string(),
+        );
+        let recording = recorder.finalize().await.unwrap();
+
+        let file = dir.path().join("session.json");
+        std::fs::write(&file, serde_json::to_string(&recording).unwrap()).unwrap();
+        let db = dir.path().join("recordings.db");
+        let loaded = load_recording(file.to_str().unwrap(), &db).await.unwrap();
+        assert_eq!(loaded.id, "abc");
+
+        SessionStorage::new(db.clone())
+            .unwrap()
+            .save_session(&recording)
+            .await
+            .unwrap();
+        assert_eq!(load_recording("abc", &db).await.unwrap().id, "abc");
+        assert_eq!(load_recording("nightly", &db).await.unwrap().id, "abc");
+        assert!(load_recording("missing", &db).await.is_err());
+    }
+
     #[test]
     fn test_cli_wrap_alias() {
         // Test "reticle wrap" alias for run
@@ -1172,10 +2465,14 @@ mod tests {
                 socket,
                 port,
                 verbose,
+                storage,
+                no_save,
             } => {
                 assert_eq!(socket, "/tmp/test.sock");
                 assert!(port.is_none());
                 assert!(!verbose);
+                assert!(storage.is_none());
+                assert!(!no_save);
             }
             _ => panic!("Expected Daemon command"),
         }
@@ -1214,6 +2511,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_tail() {
+        let cli = Cli::parse_from([
+            "reticle",
+            "tail",
+            "--server",
+            "github",
+            "--direction",
+            "out",
+        ]);
+        match cli.command {
+            Commands::Tail {
+                socket,
+                port,
+                server,
+                direction,
+                filter,
+            } => {
+                assert!(socket.is_none());
+                assert!(port.is_none());
+                assert_eq!(server.as_deref(), Some("github"));
+                assert_eq!(direction, Some(reticle_core::protocol::Direction::Out));
+                assert!(filter.is_none());
+            }
+            _ => panic!("Expected Tail command"),
+        }
+
+        assert!(Cli::try_parse_from(["reticle", "tail", "--direction", "up"]).is_err());
+        assert!(
+            Cli::try_parse_from(["reticle", "tail", "--port", "9315", "--socket", "/tmp/x"])
+                .is_err()
+        );
+    }
+
     // UI subcommand tests
 
     #[test]
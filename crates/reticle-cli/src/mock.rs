@@ -0,0 +1,253 @@
+//! Mock MCP server for CLI
+//!
+//! Serves the responses captured in a recorded session over stdio or
+//! Streamable HTTP, so clients can be tested offline against realistic
+//! server behaviour. Matching and fallbacks live in
+//! [`reticle_core::mock::MockResponder`]; this module loads the recording and
+//! handles transport.
+
+use crate::MockTransport;
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use reticle_core::mock::{MockFallback, MockMatch, MockResponder};
+use reticle_core::storage::SessionStorage;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::{debug, info, warn};
+use tracing_subscriber::EnvFilter;
+
+/// JSON-RPC "parse error" code
+const PARSE_ERROR: i64 = -32700;
+
+/// Serve recorded responses on stdin/stdout until stdin closes
+pub async fn run_stdio_mock(responder: Arc<MockResponder>) -> Result<(), String> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| format!("Failed to read stdin: {e}"))?
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = answer_text(&responder, &line) {
+            stdout
+                .write_all(format!("{response}\n").as_bytes())
+                .await
+                .map_err(|e| format!("Failed to write stdout: {e}"))?;
+            stdout
+                .flush()
+                .await
+                .map_err(|e| format!("Failed to flush stdout: {e}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Serve recorded responses as a Streamable HTTP server
+pub async fn run_http_mock(responder: Arc<MockResponder>, listen_port: u16) -> Result<(), String> {
+    let app = Router::new()
+        .route("/health", get(health_handler))
+        .route("/", post(mcp_handler).get(no_stream_handler))
+        .route("/*path", post(mcp_handler).get(no_stream_handler))
+        .with_state(responder);
+
+    let addr = format!("127.0.0.1:{listen_port}");
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .map_err(|e| format!("Failed to bind to {addr}: {e}"))?;
+
+    eprintln!("[reticle mock] Listening on http://{addr}");
+    info!("Mock server listening on {}", addr);
+
+    axum::serve(listener, app)
+        .await
+        .map_err(|e| format!("Server error: {e}"))
+}
+
+/// Health check endpoint
+async fn health_handler() -> (StatusCode, &'static str) {
+    (StatusCode::OK, "Mock server is healthy")
+}
+
+/// The mock has no server-initiated messages to stream
+async fn no_stream_handler() -> StatusCode {
+    StatusCode::METHOD_NOT_ALLOWED
+}
+
+/// Answer a POSTed JSON-RPC message or batch
+async fn mcp_handler(State(responder): State<Arc<MockResponder>>, body: Bytes) -> Response {
+    let text = String::from_utf8_lossy(&body);
+    match answer_text(&responder, &text) {
+        Some(response) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/json")],
+            response.to_string(),
+        )
+            .into_response(),
+        // Notifications and responses are acknowledged without a body
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+/// Answer one line of JSON-RPC, which may be a batch
+fn answer_text(responder: &MockResponder, text: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            warn!("Unparseable request: {}", e);
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": {"code": PARSE_ERROR, "message": format!("Parse error: {e}")}
+            }));
+        }
+    };
+    answer(responder, &message)
+}
+
+/// Answer a message or batch; `None` when nothing needs a response
+fn answer(responder: &MockResponder, message: &Value) -> Option<Value> {
+    if let Value::Array(batch) = message {
+        let responses: Vec<Value> = batch
+            .iter()
+            .filter_map(|message| answer(responder, message))
+            .collect();
+        return (!responses.is_empty()).then_some(Value::Array(responses));
+    }
+
+    let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+    let reply = responder.respond(message)?;
+    match reply.matched {
+        MockMatch::Exact => debug!("Answered {} from recording", method),
+        MockMatch::Method => info!("Answered {} with a response to different params", method),
+        MockMatch::Fallback => warn!("No recorded response for {}", method),
+    }
+    Some(reply.response)
+}
+
+/// Run mock server mode
+pub async fn run_mock(
+    session: String,
+    transport: MockTransport,
+    listen: u16,
+    fallback: MockFallback,
+    storage: Option<PathBuf>,
+) -> ExitCode {
+    // stdout carries the protocol in stdio mode, so logs go to stderr
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let storage_path = storage.unwrap_or_else(SessionStorage::default_path);
+    let recording = match crate::load_recording(&session, &storage_path).await {
+        Ok(recording) => recording,
+        Err(e) => {
+            eprintln!("[reticle mock] Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let responder = MockResponder::from_session(&recording, fallback);
+    if responder.is_empty() {
+        eprintln!(
+            "[reticle mock] Warning: '{}' has no answered requests",
+            recording.name
+        );
+    }
+    eprintln!(
+        "[reticle mock] Serving {} recorded request(s) from '{}' ({})",
+        responder.len(),
+        recording.name,
+        responder.methods().join(", ")
+    );
+
+    let responder = Arc::new(responder);
+    let result = match transport {
+        MockTransport::Stdio => run_stdio_mock(responder).await,
+        MockTransport::Http => run_http_mock(responder, listen).await,
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("[reticle mock] Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reticle_core::session_recorder::{MessageDirection, SessionRecorder};
+
+    async fn responder() -> MockResponder {
+        let recorder =
+            SessionRecorder::new("mock".to_string(), "mock".to_string(), "stdio".to_string());
+        recorder
+            .record_message(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+                MessageDirection::ToServer,
+            )
+            .await
+            .unwrap();
+        recorder
+            .record_message(
+                json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": []}}),
+                MessageDirection::ToClient,
+            )
+            .await
+            .unwrap();
+        let session = recorder.finalize().await.unwrap();
+        MockResponder::from_session(&session, MockFallback::Error)
+    }
+
+    #[tokio::test]
+    async fn test_answer_batch() {
+        let responder = responder().await;
+        let response = answer_text(
+            &responder,
+            r#"[{"jsonrpc": "2.0", "id": 7, "method": "tools/list"},
+                {"jsonrpc": "2.0", "method": "notifications/initialized"},
+                {"jsonrpc": "2.0", "id": 8, "method": "ping"}]"#,
+        )
+        .unwrap();
+        let batch = response.as_array().unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(
+            batch[0],
+            json!({"jsonrpc": "2.0", "id": 7, "result": {"tools": []}})
+        );
+        assert_eq!(batch[1]["id"], 8);
+        assert!(batch[1].get("error").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_answer_notifications_and_garbage() {
+        let responder = responder().await;
+        assert!(answer_text(
+            &responder,
+            r#"{"jsonrpc": "2.0", "method": "notifications/initialized"}"#
+        )
+        .is_none());
+
+        let response = answer_text(&responder, "not json").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+    }
+}
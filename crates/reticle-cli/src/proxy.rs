@@ -7,14 +7,135 @@
 //! - Forwarding stdin/stdout/stderr between parent and child
 //! - Emitting telemetry events to the Reticle Hub
 //! - Receiving inject commands from the Hub to send messages to the MCP server
+//! - Holding messages at breakpoints until they are resumed from the Hub
+//! - Rewriting, dropping, or delaying messages with a rules file
+//! - Injecting faults from a chaos profile
+//! - Measuring request latency and reporting requests that never get a response
+//! - Reporting messages that break JSON-RPC 2.0 or the MCP lifecycle
+//! - Linking progress and cancellation notifications to their requests
+//! - Warning about, truncating, or rejecting responses over a token budget
 //! - Proper signal handling for clean shutdown
 
+use reticle_core::breakpoints::{
+    BreakpointRelease, BreakpointSet, HeldMessage, ReleaseOutcome, ResumeAction, ResumeCommand,
+    ResumeReceiver,
+};
+use reticle_core::budget::TokenBudget;
+use reticle_core::chaos::{ChaosEngine, Injected, Injection};
 use reticle_core::events::{EventSink, InjectReceiver};
-use reticle_core::protocol::{Direction, LogEntry, MessageType};
+use reticle_core::progress::ProgressTracker;
+use reticle_core::protocol::conformance::{ConformanceChecker, Violation};
+use reticle_core::protocol::{Direction, LogEntry, McpMessage, MessageType};
+use reticle_core::request_tracker::{RequestTracker, Unanswered, DEFAULT_REQUEST_TIMEOUT};
+use reticle_core::rules::RulesEngine;
 use reticle_core::session_names::create_session_id;
+use reticle_core::token_counter::TokenCounter;
+use reticle_core::tokenizer::Tokenizer;
 use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::Command;
+use tokio::process::{ChildStdin, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Optional interception features for the stdio proxy
+#[derive(Default)]
+pub struct ProxyOptions {
+    /// Breakpoints that hold matching messages until they are resumed
+    pub breakpoints: BreakpointSet,
+    /// Resume commands for held messages (from the Hub)
+    pub resume_rx: Option<ResumeReceiver>,
+    /// Release held messages unchanged after this long (hold forever if unset)
+    pub hold_timeout: Option<Duration>,
+    /// Rules that rewrite, drop, or delay messages before they are forwarded
+    pub rules: RulesEngine,
+    /// Faults injected into the session for chaos testing
+    pub chaos: ChaosEngine,
+    /// Report requests without a response after this long (60s if unset)
+    pub request_timeout: Option<Duration>,
+    /// Token limits for what the server sends back
+    pub budget: TokenBudget,
+    /// Tokenizer for the budget (the heuristic if unset)
+    pub tokenizer: Option<Arc<dyn Tokenizer>>,
+}
+
+/// A message held at a breakpoint
+///
+/// While a message is held, no further messages are read in its direction,
+/// so traffic behind it keeps its original order.
+struct Held {
+    message: HeldMessage,
+    line: String,
+    /// Delay from rules and chaos, applied when the message is released
+    delay: Duration,
+    deadline: Option<Instant>,
+}
+
+/// A line queued for forwarding
+struct Outgoing {
+    line: String,
+    delay: Duration,
+}
+
+impl Outgoing {
+    fn now(line: String) -> Self {
+        Self {
+            line,
+            delay: Duration::ZERO,
+        }
+    }
+
+    async fn wait(&self) {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+}
+
+/// The trackers that watch messages as they are forwarded
+struct Observers<'a> {
+    tracker: &'a RequestTracker,
+    progress: &'a ProgressTracker,
+    conformance: &'a ConformanceChecker,
+}
+
+/// What the observers found in a forwarded message
+#[derive(Default)]
+struct Observed {
+    /// Time since the request, for a response
+    elapsed: Option<Duration>,
+    /// Log id of the request a progress or cancellation notification refers to
+    request: Option<String>,
+    violations: Vec<Violation>,
+}
+
+impl Observers<'_> {
+    /// Observe a message as it is forwarded
+    fn observe(&self, message: &McpMessage, direction: Direction, log_id: &str) -> Observed {
+        Observed {
+            elapsed: self.tracker.observe(message, direction),
+            request: self.progress.observe(message, direction, log_id),
+            violations: self.conformance.check(message, direction),
+        }
+    }
+
+    /// Observe a held line as it is released unchanged
+    fn observe_line(&self, line: &str, direction: Direction, log_id: &str) -> Observed {
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(json) => self.observe(&McpMessage::parse(&json), direction, log_id),
+            Err(_) => Observed::default(),
+        }
+    }
+}
+
+/// A message after the rules ran, ready to be logged and forwarded
+struct Ruled {
+    line: String,
+    original: Option<serde_json::Value>,
+    delay: Duration,
+}
 
 /// Run a stdio proxy for an MCP server
 ///
@@ -26,6 +147,7 @@ pub async fn run_stdio_proxy<E: EventSink>(
     server_name: &str,
     event_sink: E,
     inject_rx: Option<InjectReceiver>,
+    options: ProxyOptions,
 ) -> Result<i32, String> {
     // Generate session ID with beautiful name
     let session = create_session_id(Some(server_name));
@@ -54,10 +176,44 @@ pub async fn run_stdio_proxy<E: EventSink>(
     let mut stdout_reader = BufReader::new(child_stdout).lines();
     let mut stderr_reader = BufReader::new(child_stderr).lines();
     let mut stdin_reader = BufReader::new(tokio::io::stdin()).lines();
-    let mut child_stdin = child_stdin;
     let mut inject_rx = inject_rx;
+    let ProxyOptions {
+        breakpoints,
+        mut resume_rx,
+        hold_timeout,
+        rules,
+        chaos,
+        request_timeout,
+        budget,
+        tokenizer,
+    } = options;
+
+    // Messages are only counted when there is a budget to check them against
+    let tokens = (!budget.is_empty()).then(TokenCounter::new);
+    if let Some(ref tokens) = tokens {
+        if let Some(tokenizer) = tokenizer {
+            tokens.set_session_tokenizer(&session_id, tokenizer).await;
+        }
+        tokens.set_session_budget(&session_id, budget).await;
+    }
+
+    let tracker = RequestTracker::new(request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT));
+    let mut expiry = tokio::time::interval(tracker.check_interval());
+    expiry.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let conformance = ConformanceChecker::new();
+    let progress = ProgressTracker::new();
+    let observers = Observers {
+        tracker: &tracker,
+        progress: &progress,
+        conformance: &conformance,
+    };
+
+    // Forwarded lines go through writer tasks so rule delays don't stall the loop
+    let writers = Writers::spawn(child_stdin);
 
     let mut log_counter = 0u64;
+    let mut held_in: Option<Held> = None;
+    let mut held_out: Option<Held> = None;
 
     // Set up unified signal handler for clean shutdown
     // On Unix, we handle SIGTERM, SIGHUP, and SIGINT
@@ -82,12 +238,21 @@ pub async fn run_stdio_proxy<E: EventSink>(
     tokio::pin!(shutdown_signal);
 
     // Main proxy loop
-    loop {
+    'proxy: loop {
+        let next_deadline = [&held_in, &held_out]
+            .into_iter()
+            .flatten()
+            .filter_map(|h| h.deadline)
+            .min();
+
         tokio::select! {
             // Handle shutdown signals
             signal_name = &mut shutdown_signal => {
                 tracing::info!("Received {}, shutting down...", signal_name);
                 let _ = child.kill().await;
+                report_orphans(&tracker, &event_sink, &session_id, server_name, &mut log_counter).await;
+                report_conformance(&conformance, &event_sink, &session_id, server_name, &mut log_counter).await;
+                drop_held(&event_sink, &session_id, [&mut held_in, &mut held_out]).await;
                 let _ = event_sink.emit_session_ended(&session_id).await;
                 let _ = child.wait().await;
                 return Ok(match signal_name {
@@ -98,7 +263,7 @@ pub async fn run_stdio_proxy<E: EventSink>(
             }
 
             // Read from parent's stdin, write to child's stdin
-            line = stdin_reader.next_line() => {
+            line = stdin_reader.next_line(), if held_in.is_none() => {
                 match line {
                     Ok(Some(line)) => {
                         log_counter += 1;
@@ -106,17 +271,73 @@ pub async fn run_stdio_proxy<E: EventSink>(
 
                         // Parse as JSON if possible and emit log event
                         tracing::trace!("stdin: {} bytes, log_id={}", line.len(), log_id);
+                        let Some(Ruled { line, original, mut delay }) =
+                            apply_rules(&rules, line, Direction::In, &log_id)
+                        else {
+                            continue;
+                        };
                         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) {
-                            let entry = LogEntry::with_server(
+                            let held = hold_if_matched(
+                                &breakpoints, &json, Direction::In, &log_id, &session_id, hold_timeout,
+                            );
+                            let injections = chaos.on_request(&json);
+                            let message = McpMessage::parse(&json);
+                            // Held messages are observed on release, as they are forwarded
+                            let observed = match held {
+                                Some(_) => Observed::default(),
+                                None => observers.observe(&message, Direction::In, &log_id),
+                            };
+                            if let Some(ref tokens) = tokens {
+                                check_budget(tokens, &event_sink, &session_id, &log_id, &message, Direction::In).await;
+                            }
+                            let mut entry = LogEntry::from_message(
                                 log_id.clone(),
                                 session_id.clone(),
                                 Direction::In,
-                                json,
-                                server_name.to_string(),
-                            );
+                                &json,
+                                message,
+                            )
+                            .with_server_name(server_name)
+                            .with_duration(observed.elapsed)
+                            .with_request(observed.request);
+                            if let Some(ref original) = original {
+                                entry = entry.with_original(original);
+                            }
                             if let Err(e) = event_sink.emit_log(&entry).await {
                                 tracing::warn!("emit_log error: {}", e);
                             }
+                            report_violations(&event_sink, &session_id, server_name, Some(&log_id), observed.violations, &mut log_counter).await;
+
+                            // Injected faults may answer the request or kill the server instead
+                            let mut answered = false;
+                            for injection in injections {
+                                log_counter += 1;
+                                let entry = fault_entry(format!("chaos-{log_counter}"), &session_id, server_name, &injection);
+                                let _ = event_sink.emit_log(&entry).await;
+                                match injection.injected {
+                                    Injected::Latency(extra) => delay += extra,
+                                    Injected::Error(response) => {
+                                        tracker.observe(&McpMessage::parse(&response), Direction::Out);
+                                        let _ = writers.to_parent.send(Outgoing { line: response.to_string(), delay });
+                                        answered = true;
+                                    }
+                                    Injected::Kill => {
+                                        tracing::info!("Chaos fault {} killing the server", injection.fault);
+                                        let _ = child.start_kill();
+                                        answered = true;
+                                    }
+                                    Injected::Drop | Injected::Truncate(_) => {}
+                                }
+                            }
+                            if answered {
+                                continue;
+                            }
+
+                            if let Some(message) = held {
+                                let _ = event_sink.emit_breakpoint_hit(&message).await;
+                                held_in = Some(Held { message, line, delay, deadline: next_hold_deadline(hold_timeout) });
+                                continue;
+                            }
                         } else {
                             let entry = LogEntry::new_raw_with_server(
                                 log_id.clone(),
@@ -129,23 +350,22 @@ pub async fn run_stdio_proxy<E: EventSink>(
                             if let Err(e) = event_sink.emit_log(&entry).await {
                                 tracing::warn!("emit_log error: {}", e);
                             }
+                            let violations = conformance.check_raw(&line, Direction::In).into_iter().collect();
+                            report_violations(&event_sink, &session_id, server_name, Some(&log_id), violations, &mut log_counter).await;
                         }
 
                         // Forward to child
-                        if let Err(e) = child_stdin.write_all(line.as_bytes()).await {
-                            tracing::error!("Failed to write to child stdin: {}", e);
-                            break;
-                        }
-                        if let Err(e) = child_stdin.write_all(b"\n").await {
-                            tracing::error!("Failed to write newline to child stdin: {}", e);
+                        if writers.to_child.send(Outgoing { line, delay }).is_err() {
                             break;
                         }
-                        let _ = child_stdin.flush().await;
                     }
                     Ok(None) => {
                         // Parent stdin closed - this means the parent process exited
                         tracing::info!("Parent stdin closed, terminating child process...");
                         let _ = child.kill().await;
+                        report_orphans(&tracker, &event_sink, &session_id, server_name, &mut log_counter).await;
+                        report_conformance(&conformance, &event_sink, &session_id, server_name, &mut log_counter).await;
+                        drop_held(&event_sink, &session_id, [&mut held_in, &mut held_out]).await;
                         let _ = event_sink.emit_session_ended(&session_id).await;
                         // Give child a moment to clean up
                         let status = child.wait().await;
@@ -160,7 +380,7 @@ pub async fn run_stdio_proxy<E: EventSink>(
             }
 
             // Read from child's stdout, write to parent's stdout
-            line = stdout_reader.next_line() => {
+            line = stdout_reader.next_line(), if held_out.is_none() => {
                 match line {
                     Ok(Some(line)) => {
                         log_counter += 1;
@@ -168,17 +388,70 @@ pub async fn run_stdio_proxy<E: EventSink>(
 
                         // Parse as JSON if possible and emit log event
                         tracing::trace!("stdout: {} bytes, log_id={}", line.len(), log_id);
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) {
-                            let entry = LogEntry::with_server(
+                        let Some(Ruled { mut line, mut original, delay }) =
+                            apply_rules(&rules, line, Direction::Out, &log_id)
+                        else {
+                            continue;
+                        };
+                        if let Ok(mut json) = serde_json::from_str::<serde_json::Value>(&line) {
+                            let mut message = McpMessage::parse(&json);
+
+                            // A budget may truncate or reject the response; the replacement
+                            // is what gets logged, held, and forwarded
+                            if let Some(ref tokens) = tokens {
+                                if let Some(replacement) =
+                                    check_budget(tokens, &event_sink, &session_id, &log_id, &message, Direction::Out).await
+                                {
+                                    line = replacement.to_string();
+                                    message = McpMessage::parse(&replacement);
+                                    original = original.or(Some(std::mem::replace(&mut json, replacement)));
+                                }
+                            }
+
+                            let held = hold_if_matched(
+                                &breakpoints, &json, Direction::Out, &log_id, &session_id, hold_timeout,
+                            );
+                            let fault = chaos.on_response(&line, &json);
+                            // Held messages are observed on release, as they are forwarded.
+                            // Responses a fault drops or cuts short never answer the client.
+                            let observed = match (&held, &fault) {
+                                (None, None) => observers.observe(&message, Direction::Out, &log_id),
+                                _ => Observed::default(),
+                            };
+                            let mut entry = LogEntry::from_message(
                                 log_id.clone(),
                                 session_id.clone(),
                                 Direction::Out,
-                                json,
-                                server_name.to_string(),
-                            );
+                                &json,
+                                message,
+                            )
+                            .with_server_name(server_name)
+                            .with_duration(observed.elapsed)
+                            .with_request(observed.request);
+                            if let Some(ref original) = original {
+                                entry = entry.with_original(original);
+                            }
                             if let Err(e) = event_sink.emit_log(&entry).await {
                                 tracing::warn!("emit_log error: {}", e);
                             }
+                            report_violations(&event_sink, &session_id, server_name, Some(&log_id), observed.violations, &mut log_counter).await;
+
+                            // A response fault swallows the response or cuts it short
+                            if let Some(injection) = fault {
+                                log_counter += 1;
+                                let entry = fault_entry(format!("chaos-{log_counter}"), &session_id, server_name, &injection);
+                                let _ = event_sink.emit_log(&entry).await;
+                                match injection.injected {
+                                    Injected::Truncate(cut) => line = cut,
+                                    _ => continue,
+                                }
+                            }
+
+                            if let Some(message) = held {
+                                let _ = event_sink.emit_breakpoint_hit(&message).await;
+                                held_out = Some(Held { message, line, delay, deadline: next_hold_deadline(hold_timeout) });
+                                continue;
+                            }
                         } else {
                             let entry = LogEntry::new_raw_with_server(
                                 log_id.clone(),
@@ -191,10 +464,14 @@ pub async fn run_stdio_proxy<E: EventSink>(
                             if let Err(e) = event_sink.emit_log(&entry).await {
                                 tracing::warn!("emit_log error: {}", e);
                             }
+                            let violations = conformance.check_raw(&line, Direction::Out).into_iter().collect();
+                            report_violations(&event_sink, &session_id, server_name, Some(&log_id), violations, &mut log_counter).await;
                         }
 
                         // Forward to parent stdout
-                        println!("{line}");
+                        if writers.to_parent.send(Outgoing { line, delay }).is_err() {
+                            break;
+                        }
                     }
                     Ok(None) => {
                         // Child stdout closed
@@ -254,39 +531,164 @@ pub async fn run_stdio_proxy<E: EventSink>(
 
                     // Log the injected message
                     if let Ok(json) = serde_json::from_str::<serde_json::Value>(&message) {
-                        let entry = LogEntry::with_server(
+                        let parsed = McpMessage::parse(&json);
+                        let observed = observers.observe(&parsed, Direction::In, &log_id);
+                        let entry = LogEntry::from_message(
                             log_id.clone(),
                             session_id.clone(),
                             Direction::In,
-                            json,
-                            server_name.to_string(),
-                        );
+                            &json,
+                            parsed,
+                        )
+                        .with_server_name(server_name)
+                        .with_request(observed.request);
                         let _ = event_sink.emit_log(&entry).await;
+                        report_violations(&event_sink, &session_id, server_name, Some(&log_id), observed.violations, &mut log_counter).await;
                     }
 
-                    // Write to child's stdin
-                    if let Err(e) = child_stdin.write_all(message.as_bytes()).await {
-                        tracing::error!("Failed to inject message: {}", e);
+                    // Queue for the child's stdin
+                    if writers.to_child.send(Outgoing::now(message)).is_err() {
+                        tracing::error!("Failed to inject message: child stdin is closed");
                     } else {
-                        if let Err(e) = child_stdin.write_all(b"\n").await {
-                            tracing::error!("Failed to write newline after inject: {}", e);
-                        }
-                        let _ = child_stdin.flush().await;
                         tracing::debug!("Injected message successfully");
                     }
                 }
             }
 
+            // Handle breakpoint resume commands from the Hub (if enabled)
+            command = async {
+                if let Some(ref mut rx) = resume_rx {
+                    rx.recv().await
+                } else {
+                    std::future::pending::<Option<ResumeCommand>>().await
+                }
+            } => {
+                let Some(command) = command else {
+                    // Hub went away for good; nothing can resume held messages now
+                    resume_rx = None;
+                    continue;
+                };
+
+                let slot = if held_in.as_ref().is_some_and(|h| h.message.hold_id == command.hold_id) {
+                    &mut held_in
+                } else if held_out.as_ref().is_some_and(|h| h.message.hold_id == command.hold_id) {
+                    &mut held_out
+                } else {
+                    tracing::warn!("Resume for unknown hold {}", command.hold_id);
+                    continue;
+                };
+
+                // Validate edits before releasing so a typo doesn't lose the message
+                let edited = match command.action {
+                    ResumeAction::Edit { ref message } => {
+                        match serde_json::from_str::<serde_json::Value>(message) {
+                            Ok(json) => Some(json),
+                            Err(e) => {
+                                tracing::warn!("Ignoring invalid edit for {}: {}", command.hold_id, e);
+                                continue;
+                            }
+                        }
+                    }
+                    _ => None,
+                };
+
+                let Some(held) = slot.take() else { continue };
+                let direction = held.message.direction;
+                let delay = held.delay;
+                let (line, outcome) = match (command.action, edited) {
+                    (ResumeAction::Drop, _) => (None, ReleaseOutcome::Dropped),
+                    (ResumeAction::Edit { .. }, Some(json)) => {
+                        log_counter += 1;
+                        let (entry, violations) = edited_entry(
+                            &observers,
+                            format!("log-{log_counter}"),
+                            &session_id,
+                            server_name,
+                            direction,
+                            &json,
+                            &held.line,
+                        );
+                        let _ = event_sink.emit_log(&entry).await;
+                        report_violations(&event_sink, &session_id, server_name, Some(&entry.id), violations, &mut log_counter).await;
+                        (Some(entry.content), ReleaseOutcome::Edited)
+                    }
+                    _ => {
+                        let observed = observers.observe_line(&held.line, direction, &held.message.hold_id);
+                        report_violations(&event_sink, &session_id, server_name, Some(&held.message.hold_id), observed.violations, &mut log_counter).await;
+                        (Some(held.line), ReleaseOutcome::Continued)
+                    }
+                };
+
+                let release = BreakpointRelease {
+                    session_id: session_id.clone(),
+                    hold_id: held.message.hold_id,
+                    outcome,
+                };
+                let _ = event_sink.emit_breakpoint_released(&release).await;
+
+                if let Some(line) = line {
+                    if writers.send(direction, Outgoing { line, delay }).is_err() {
+                        break;
+                    }
+                }
+            }
+
+            // Release held messages whose hold timed out
+            _ = async {
+                match next_deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                let now = Instant::now();
+                for slot in [&mut held_in, &mut held_out] {
+                    if slot.as_ref().is_none_or(|h| h.deadline.is_none_or(|d| d > now)) {
+                        continue;
+                    }
+                    let Some(held) = slot.take() else { continue };
+                    tracing::info!("Breakpoint hold {} timed out, releasing", held.message.hold_id);
+                    let observed = observers.observe_line(&held.line, held.message.direction, &held.message.hold_id);
+                    report_violations(&event_sink, &session_id, server_name, Some(&held.message.hold_id), observed.violations, &mut log_counter).await;
+                    let release = BreakpointRelease {
+                        session_id: session_id.clone(),
+                        hold_id: held.message.hold_id.clone(),
+                        outcome: ReleaseOutcome::TimedOut,
+                    };
+                    let _ = event_sink.emit_breakpoint_released(&release).await;
+                    let outgoing = Outgoing { line: held.line, delay: held.delay };
+                    if writers.send(held.message.direction, outgoing).is_err() {
+                        break 'proxy;
+                    }
+                }
+            }
+
+            // Report requests that have waited too long for a response
+            _ = expiry.tick() => {
+                for request in tracker.expire() {
+                    log_counter += 1;
+                    tracing::warn!("No response to {} {} after {}ms", request.method, request.id, request.elapsed_ms);
+                    let entry = unanswered_entry(format!("unanswered-{log_counter}"), &session_id, server_name, &request);
+                    let _ = event_sink.emit_log(&entry).await;
+                }
+            }
+
             // Check if child has exited
             status = child.wait() => {
                 match status {
                     Ok(status) => {
                         tracing::info!("Child process exited with: {}", status);
+                        writers.finish().await;
+                        report_orphans(&tracker, &event_sink, &session_id, server_name, &mut log_counter).await;
+                        report_conformance(&conformance, &event_sink, &session_id, server_name, &mut log_counter).await;
+                        drop_held(&event_sink, &session_id, [&mut held_in, &mut held_out]).await;
                         let _ = event_sink.emit_session_ended(&session_id).await;
                         return Ok(status.code().unwrap_or(0));
                     }
                     Err(e) => {
                         tracing::error!("Error waiting for child: {}", e);
+                        report_orphans(&tracker, &event_sink, &session_id, server_name, &mut log_counter).await;
+                        report_conformance(&conformance, &event_sink, &session_id, server_name, &mut log_counter).await;
+                        drop_held(&event_sink, &session_id, [&mut held_in, &mut held_out]).await;
                         let _ = event_sink.emit_session_ended(&session_id).await;
                         return Err(format!("Error waiting for child: {e}"));
                     }
@@ -297,12 +699,591 @@ pub async fn run_stdio_proxy<E: EventSink>(
 
     // If we broke out of the loop (stdin/stdout error), kill the child and wait
     tracing::info!("Proxy loop ended, terminating child process...");
+    writers.finish().await;
     let _ = child.kill().await;
     let status = child
         .wait()
         .await
         .map_err(|e| format!("Error waiting for child: {e}"))?;
+    report_orphans(
+        &tracker,
+        &event_sink,
+        &session_id,
+        server_name,
+        &mut log_counter,
+    )
+    .await;
+    report_conformance(
+        &conformance,
+        &event_sink,
+        &session_id,
+        server_name,
+        &mut log_counter,
+    )
+    .await;
+    drop_held(&event_sink, &session_id, [&mut held_in, &mut held_out]).await;
     let _ = event_sink.emit_session_ended(&session_id).await;
 
     Ok(status.code().unwrap_or(0))
 }
+
+/// Check a message against the breakpoints, returning the hold record on a match
+fn hold_if_matched(
+    breakpoints: &BreakpointSet,
+    json: &serde_json::Value,
+    direction: Direction,
+    log_id: &str,
+    session_id: &str,
+    hold_timeout: Option<Duration>,
+) -> Option<HeldMessage> {
+    let breakpoint = breakpoints.find_match(json, direction)?;
+    tracing::info!(
+        "Breakpoint {} hit, holding {} (timeout: {:?})",
+        breakpoint.id,
+        log_id,
+        hold_timeout
+    );
+    Some(HeldMessage::new(
+        log_id.to_string(),
+        session_id.to_string(),
+        breakpoint,
+        direction,
+        json,
+    ))
+}
+
+/// Build the log entry for a held message released with edits
+///
+/// The edited message is observed in place of the held one, so an edited
+/// id or method is what the request tracker and conformance checker see.
+fn edited_entry(
+    observers: &Observers,
+    log_id: String,
+    session_id: &str,
+    server_name: &str,
+    direction: Direction,
+    json: &serde_json::Value,
+    held_line: &str,
+) -> (LogEntry, Vec<Violation>) {
+    let message = McpMessage::parse(json);
+    let observed = observers.observe(&message, direction, &log_id);
+    let mut entry =
+        LogEntry::from_message(log_id, session_id.to_string(), direction, json, message)
+            .with_server_name(server_name)
+            .with_duration(observed.elapsed)
+            .with_request(observed.request);
+    if let Ok(original) = serde_json::from_str(held_line) {
+        entry = entry.with_original(&original);
+    }
+    (entry, observed.violations)
+}
+
+/// Count a message against the session's token budget
+///
+/// Violations are emitted as warnings by the counter. Returns the message
+/// to forward instead when the budget truncates or rejects a server message.
+async fn check_budget<E: EventSink>(
+    tokens: &TokenCounter,
+    event_sink: &E,
+    session_id: &str,
+    log_id: &str,
+    message: &McpMessage,
+    direction: Direction,
+) -> Option<serde_json::Value> {
+    let is_request = direction == Direction::In;
+    let stats = tokens
+        .record_message(event_sink, session_id, log_id, message, is_request)
+        .await;
+    if stats.budget_exceeded.is_empty() {
+        return None;
+    }
+
+    let budget = tokens.session_budget(session_id).await;
+    let tokenizer = tokens.session_tokenizer(session_id).await;
+    let json = message.to_value();
+    let replacement = budget.enforce(tokenizer.as_ref(), &json, &stats.budget_exceeded)?;
+    tracing::info!(
+        "{:?} {} for exceeding the token budget",
+        budget.action(),
+        log_id
+    );
+    Some(replacement)
+}
+
+/// Build the log entry that marks an injected fault
+fn fault_entry(
+    log_id: String,
+    session_id: &str,
+    server_name: &str,
+    injection: &Injection,
+) -> LogEntry {
+    let mut entry = LogEntry::new_fault(
+        log_id,
+        session_id.to_string(),
+        injection.direction(),
+        injection.describe(),
+    );
+    entry.server_name = Some(server_name.to_string());
+    entry
+}
+
+/// Build the log entry that reports a request without a response
+fn unanswered_entry(
+    log_id: String,
+    session_id: &str,
+    server_name: &str,
+    request: &Unanswered,
+) -> LogEntry {
+    let mut entry = LogEntry::new_unanswered(log_id, session_id.to_string(), request);
+    entry.server_name = Some(server_name.to_string());
+    entry
+}
+
+/// Log the requests still waiting for a response as the session ends
+async fn report_orphans<E: EventSink>(
+    tracker: &RequestTracker,
+    event_sink: &E,
+    session_id: &str,
+    server_name: &str,
+    log_counter: &mut u64,
+) {
+    for request in tracker.drain() {
+        *log_counter += 1;
+        let entry = unanswered_entry(
+            format!("unanswered-{log_counter}"),
+            session_id,
+            server_name,
+            &request,
+        );
+        let _ = event_sink.emit_log(&entry).await;
+    }
+}
+
+/// Drop the messages still held as the session ends
+///
+/// Each is reported as released with `dropped`, so the GUI and daemon stop
+/// offering to resume it.
+async fn drop_held<E: EventSink>(event_sink: &E, session_id: &str, slots: [&mut Option<Held>; 2]) {
+    for held in slots.into_iter().filter_map(Option::take) {
+        tracing::info!(
+            "Dropping message held at {} as the session ends",
+            held.message.hold_id
+        );
+        let release = BreakpointRelease {
+            session_id: session_id.to_string(),
+            hold_id: held.message.hold_id,
+            outcome: ReleaseOutcome::Dropped,
+        };
+        let _ = event_sink.emit_breakpoint_released(&release).await;
+    }
+}
+
+/// Build the log entry that reports a conformance violation
+///
+/// `related` is the log id of the offending message.
+fn violation_entry(
+    log_id: String,
+    session_id: &str,
+    server_name: &str,
+    related: Option<&str>,
+    violation: &Violation,
+) -> LogEntry {
+    let mut entry = LogEntry::new_violation(log_id, session_id.to_string(), related, violation);
+    entry.server_name = Some(server_name.to_string());
+    entry
+}
+
+/// Log the conformance violations found in a message
+async fn report_violations<E: EventSink>(
+    event_sink: &E,
+    session_id: &str,
+    server_name: &str,
+    related: Option<&str>,
+    violations: Vec<Violation>,
+    log_counter: &mut u64,
+) {
+    for violation in violations {
+        *log_counter += 1;
+        tracing::warn!(
+            "Conformance violation in {}: {}",
+            related.unwrap_or("session"),
+            violation
+        );
+        let entry = violation_entry(
+            format!("violation-{log_counter}"),
+            session_id,
+            server_name,
+            related,
+            &violation,
+        );
+        let _ = event_sink.emit_log(&entry).await;
+    }
+}
+
+/// Log what the session never did and emit its conformance summary
+async fn report_conformance<E: EventSink>(
+    conformance: &ConformanceChecker,
+    event_sink: &E,
+    session_id: &str,
+    server_name: &str,
+    log_counter: &mut u64,
+) {
+    report_violations(
+        event_sink,
+        session_id,
+        server_name,
+        None,
+        conformance.finish(),
+        log_counter,
+    )
+    .await;
+    let summary = conformance.summary(session_id);
+    if !summary.is_conformant() {
+        tracing::warn!("Conformance: {}", summary);
+    }
+    let _ = event_sink.emit_conformance_summary(&summary).await;
+}
+
+fn next_hold_deadline(hold_timeout: Option<Duration>) -> Option<Instant> {
+    hold_timeout.map(|timeout| Instant::now() + timeout)
+}
+
+/// Write a newline-terminated message to the child's stdin
+async fn write_line(child_stdin: &mut ChildStdin, line: &str) -> std::io::Result<()> {
+    child_stdin.write_all(line.as_bytes()).await?;
+    child_stdin.write_all(b"\n").await?;
+    child_stdin.flush().await
+}
+
+/// Run a line through the rules
+///
+/// Returns `None` if a rule dropped it. Rewritten lines come back
+/// re-serialized with the original message alongside.
+fn apply_rules(
+    rules: &RulesEngine,
+    line: String,
+    direction: Direction,
+    log_id: &str,
+) -> Option<Ruled> {
+    let Some(outcome) = rules.apply_str(&line, direction) else {
+        return Some(Ruled {
+            line,
+            original: None,
+            delay: Duration::ZERO,
+        });
+    };
+
+    tracing::debug!("Rules {:?} matched {}", outcome.applied, log_id);
+    if outcome.is_dropped() {
+        tracing::info!("Rules {:?} dropped {}", outcome.applied, log_id);
+        return None;
+    }
+
+    let modified = outcome.is_modified();
+    let line = match outcome.message {
+        Some(ref message) if modified => serde_json::to_string(message).unwrap_or(line),
+        _ => line,
+    };
+    Some(Ruled {
+        line,
+        original: outcome.original.filter(|_| modified),
+        delay: outcome.delay,
+    })
+}
+
+/// Writer tasks for both directions
+///
+/// Each direction is written by its own task so a delayed message holds
+/// back only the traffic behind it, in the same direction.
+struct Writers {
+    to_child: mpsc::UnboundedSender<Outgoing>,
+    to_parent: mpsc::UnboundedSender<Outgoing>,
+    parent_task: JoinHandle<()>,
+}
+
+impl Writers {
+    fn spawn(mut child_stdin: ChildStdin) -> Self {
+        let (to_child, mut child_rx) = mpsc::unbounded_channel::<Outgoing>();
+        tokio::spawn(async move {
+            while let Some(outgoing) = child_rx.recv().await {
+                outgoing.wait().await;
+                if let Err(e) = write_line(&mut child_stdin, &outgoing.line).await {
+                    tracing::error!("Failed to write to child stdin: {}", e);
+                    break;
+                }
+            }
+        });
+
+        let (to_parent, mut parent_rx) = mpsc::unbounded_channel::<Outgoing>();
+        let parent_task = tokio::spawn(async move {
+            while let Some(outgoing) = parent_rx.recv().await {
+                outgoing.wait().await;
+                println!("{}", outgoing.line);
+            }
+        });
+
+        Self {
+            to_child,
+            to_parent,
+            parent_task,
+        }
+    }
+
+    /// Queue a line in the given direction
+    fn send(
+        &self,
+        direction: Direction,
+        outgoing: Outgoing,
+    ) -> Result<(), mpsc::error::SendError<Outgoing>> {
+        match direction {
+            Direction::In => self.to_child.send(outgoing),
+            Direction::Out => self.to_parent.send(outgoing),
+        }
+    }
+
+    /// Wait until everything queued for our stdout has been written
+    async fn finish(self) {
+        drop(self.to_child);
+        drop(self.to_parent);
+        let _ = self.parent_task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hold_if_matched() {
+        let breakpoints = BreakpointSet::from_specs(&["tool=read_file"]).unwrap();
+        let call = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {"name": "read_file", "arguments": {}}
+        });
+
+        let held = hold_if_matched(&breakpoints, &call, Direction::In, "log-1", "s1", None)
+            .expect("breakpoint should match");
+        assert_eq!(held.hold_id, "log-1");
+        assert_eq!(held.session_id, "s1");
+        assert_eq!(held.breakpoint_id, "bp-1");
+
+        assert!(
+            hold_if_matched(&breakpoints, &call, Direction::Out, "log-2", "s1", None).is_some()
+        );
+        let ping = serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "ping"});
+        assert!(hold_if_matched(&breakpoints, &ping, Direction::In, "log-3", "s1", None).is_none());
+    }
+
+    #[test]
+    fn test_edited_entry_observes_the_edit() {
+        let tracker = RequestTracker::default();
+        let progress = ProgressTracker::new();
+        let conformance = ConformanceChecker::new();
+        let observers = Observers {
+            tracker: &tracker,
+            progress: &progress,
+            conformance: &conformance,
+        };
+        let initialize = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": {"protocolVersion": "2025-03-26", "capabilities": {}}
+        });
+        observers.observe(&McpMessage::parse(&initialize), Direction::In, "log-1");
+        let initialized = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": {"protocolVersion": "2025-03-26", "capabilities": {"tools": {}}}
+        });
+        observers.observe(&McpMessage::parse(&initialized), Direction::Out, "log-2");
+        let notified = serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        observers.observe(&McpMessage::parse(&notified), Direction::In, "log-3");
+
+        // The call was held with id 1 and released with id 7
+        let held =
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"read_file"}}"#;
+        let edited = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {"name": "read_file"}
+        });
+        let (entry, violations) = edited_entry(
+            &observers,
+            "log-5".to_string(),
+            "s1",
+            "github",
+            Direction::In,
+            &edited,
+            held,
+        );
+        assert!(violations.is_empty());
+        assert!(entry.modified);
+        assert!(entry.content.contains("\"id\":7"));
+        assert!(entry.original.as_deref().unwrap().contains("\"id\":1"));
+        assert_eq!(entry.server_name.as_deref(), Some("github"));
+        assert_eq!(tracker.in_flight(), 1);
+
+        // The server answers the edited id, which settles the call
+        let response = serde_json::json!({"jsonrpc": "2.0", "id": 7, "result": {"content": []}});
+        let observed = observers.observe(&McpMessage::parse(&response), Direction::Out, "log-6");
+        assert!(observed.elapsed.is_some());
+        assert!(observed.violations.is_empty());
+        assert_eq!(tracker.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_apply_rules() {
+        let rules = RulesEngine::from_toml(
+            r#"
+            [[rules]]
+            match = { tool = "read_file" }
+            set = { "params.arguments.path" = "/tmp/sandbox" }
+            delay_ms = 10
+
+            [[rules]]
+            match = { method = "ping" }
+            drop = true
+            "#,
+        )
+        .unwrap();
+
+        let call = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"read_file","arguments":{"path":"/etc/passwd"}}}"#;
+        let ruled = apply_rules(&rules, call.to_string(), Direction::In, "log-1").unwrap();
+        assert!(ruled.line.contains("/tmp/sandbox"));
+        assert_eq!(ruled.delay, Duration::from_millis(10));
+        assert_eq!(
+            ruled.original,
+            Some(serde_json::from_str::<serde_json::Value>(call).unwrap())
+        );
+
+        let ping = r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#;
+        assert!(apply_rules(&rules, ping.to_string(), Direction::In, "log-2").is_none());
+
+        // Unmatched lines are forwarded byte-for-byte
+        let spaced = r#"{ "jsonrpc": "2.0", "id": 3, "method": "tools/list" }"#;
+        let ruled = apply_rules(&rules, spaced.to_string(), Direction::In, "log-3").unwrap();
+        assert_eq!(ruled.line, spaced);
+        assert!(ruled.original.is_none());
+    }
+
+    #[test]
+    fn test_fault_entry() {
+        let chaos = ChaosEngine::from_toml(
+            "[[faults]]\nprobability = 1.0\naction = \"error\"\ncodes = [-32000]",
+        )
+        .unwrap();
+        let call = serde_json::json!({"jsonrpc": "2.0", "id": 9, "method": "tools/call"});
+        let injection = chaos.on_request(&call).pop().unwrap();
+
+        let entry = fault_entry("chaos-1".to_string(), "s1", "github", &injection);
+        assert_eq!(entry.message_type, MessageType::Fault);
+        assert_eq!(entry.direction, Direction::Out);
+        assert_eq!(entry.method.as_deref(), Some("tools/call"));
+        assert_eq!(entry.server_name.as_deref(), Some("github"));
+        assert!(entry.content.contains("-32000"));
+    }
+
+    #[tokio::test]
+    async fn test_report_orphans() {
+        let tracker = RequestTracker::default();
+        let call = serde_json::json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call"});
+        tracker.observe(&McpMessage::parse(&call), Direction::In);
+
+        let sink = reticle_core::events::NoOpEventSink;
+        let mut log_counter = 10;
+        report_orphans(&tracker, &sink, "s1", "github", &mut log_counter).await;
+        assert_eq!(log_counter, 11);
+        assert_eq!(tracker.in_flight(), 0);
+
+        let request = Unanswered {
+            id: serde_json::json!(4),
+            method: "tools/call".to_string(),
+            direction: Direction::In,
+            elapsed_ms: 60_000,
+            reason: reticle_core::UnansweredReason::Timeout,
+        };
+        let entry = unanswered_entry("unanswered-1".to_string(), "s1", "github", &request);
+        assert_eq!(entry.message_type, MessageType::Unanswered);
+        assert_eq!(entry.method.as_deref(), Some("tools/call"));
+        assert_eq!(entry.server_name.as_deref(), Some("github"));
+        assert_eq!(entry.duration_micros, Some(60_000_000));
+    }
+
+    #[tokio::test]
+    async fn test_report_conformance() {
+        let conformance = ConformanceChecker::new();
+        let sink = reticle_core::events::NoOpEventSink;
+        let mut log_counter = 3;
+
+        let list = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"});
+        let violations = conformance.check(&McpMessage::parse(&list), Direction::In);
+        let entry = violation_entry(
+            "violation-1".to_string(),
+            "s1",
+            "github",
+            Some("log-3"),
+            &violations[0],
+        );
+        assert_eq!(entry.message_type, MessageType::Violation);
+        assert_eq!(entry.server_name.as_deref(), Some("github"));
+        assert!(entry.content.contains("\"log_id\":\"log-3\""));
+
+        report_violations(
+            &sink,
+            "s1",
+            "github",
+            Some("log-3"),
+            violations,
+            &mut log_counter,
+        )
+        .await;
+        let line = "starting server...".to_string();
+        let violations = conformance
+            .check_raw(&line, Direction::Out)
+            .into_iter()
+            .collect();
+        report_violations(
+            &sink,
+            "s1",
+            "github",
+            Some("log-4"),
+            violations,
+            &mut log_counter,
+        )
+        .await;
+        report_conformance(&conformance, &sink, "s1", "github", &mut log_counter).await;
+        assert_eq!(log_counter, 5);
+        assert_eq!(conformance.summary("s1").violations, 2);
+    }
+
+    #[tokio::test]
+    async fn test_drop_held() {
+        let breakpoints = BreakpointSet::from_specs(&["method=ping"]).unwrap();
+        let ping = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "ping"});
+        let message =
+            hold_if_matched(&breakpoints, &ping, Direction::In, "log-1", "s1", None).unwrap();
+        let mut held_in = Some(Held {
+            message,
+            line: ping.to_string(),
+            delay: Duration::from_millis(200),
+            deadline: None,
+        });
+        let mut held_out = None;
+
+        let sink = reticle_core::events::NoOpEventSink;
+        drop_held(&sink, "s1", [&mut held_in, &mut held_out]).await;
+        assert!(held_in.is_none());
+        assert!(held_out.is_none());
+    }
+
+    #[test]
+    fn test_next_hold_deadline() {
+        assert!(next_hold_deadline(None).is_none());
+        let deadline = next_hold_deadline(Some(Duration::from_secs(5))).unwrap();
+        assert!(deadline > Instant::now());
+    }
+}
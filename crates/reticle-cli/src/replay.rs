@@ -0,0 +1,504 @@
+//! Replay a recorded session against a live server
+//!
+//! Spawns a fresh stdio MCP server and re-sends every message the client sent
+//! in a recorded session, following the original spacing (optionally sped up
+//! or with long pauses cut short). The server's new responses are captured as
+//! a new recorded session, so the two runs can be compared.
+//!
+//! Recorded answers to requests the server made (sampling, roots, elicitation)
+//! are re-sent only as answers to the same requests from the new server.
+
+use reticle_core::session_names::create_session_id;
+use reticle_core::session_recorder::{
+    MessageDirection, RecordedMessage, RecordedSession, ReplayTiming, ServerIdentifier,
+    SessionRecorder,
+};
+use reticle_core::storage::SessionStorage;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::process::{ExitCode, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing_subscriber::EnvFilter;
+
+/// Options for a replay run
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Spacing between the replayed messages
+    pub timing: ReplayTiming,
+    /// How long to wait for outstanding responses after the last message
+    pub response_timeout: Duration,
+}
+
+/// The outcome of a replay run
+#[derive(Debug)]
+pub struct ReplaySummary {
+    /// The newly recorded session
+    pub session: RecordedSession,
+    /// Number of messages re-sent to the server
+    pub sent: usize,
+    /// Recorded answers to server requests the new server never made
+    pub skipped: usize,
+    /// Number of messages the server sent back
+    pub received: usize,
+    /// Ids of replayed requests the server never answered
+    pub unanswered: Vec<String>,
+}
+
+/// Replay the client side of `source` against a freshly spawned server
+pub async fn replay_session(
+    source: &RecordedSession,
+    command: &str,
+    args: &[&str],
+    server_name: &str,
+    options: &ReplayOptions,
+) -> Result<ReplaySummary, String> {
+    let schedule = source.replay_schedule(&options.timing);
+    let recorded_requests = server_requests(&source.messages);
+
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start process: {e}"))?;
+    let mut child_stdin = child.stdin.take().ok_or("Failed to get child stdin")?;
+    let child_stdout = child.stdout.take().ok_or("Failed to get child stdout")?;
+
+    let session = create_session_id(Some(server_name));
+    let recorder = SessionRecorder::with_server(
+        session.id,
+        format!("Replay of {}", source.name),
+        "stdio".to_string(),
+        ServerIdentifier {
+            name: server_name.to_string(),
+            version: None,
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            connection_type: "stdio".to_string(),
+        },
+    );
+    recorder.add_tag("replay".to_string()).await;
+    recorder.add_tag(format!("replay-of:{}", source.id)).await;
+
+    // Server output is read on its own task so responses are recorded as they arrive
+    let (response_tx, mut response_rx) = mpsc::unbounded_channel::<Value>();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(child_stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            match serde_json::from_str::<Value>(&line) {
+                Ok(json) => {
+                    if response_tx.send(json).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => tracing::warn!("Ignoring non-JSON server output: {}", line),
+            }
+        }
+    });
+
+    let mut awaiting = HashSet::new();
+    let mut live_requests = LiveRequests::new();
+    let mut sent = 0;
+    let mut skipped = 0;
+    let mut received = 0;
+    let start = Instant::now();
+
+    for (offset, message) in &schedule {
+        // Record responses while waiting for the next send time
+        let send_at = start + *offset;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(send_at) => break,
+                Some(response) = response_rx.recv() => {
+                    received += 1;
+                    record_response(&recorder, &mut awaiting, &mut live_requests, response).await;
+                }
+            }
+        }
+
+        let Some(content) = remap_answer(&message.content, &recorded_requests, &mut live_requests)
+        else {
+            tracing::info!(
+                "Skipping {}: the server made no matching request",
+                message.id
+            );
+            skipped += 1;
+            continue;
+        };
+        let line = serde_json::to_string(&content)
+            .map_err(|e| format!("Failed to serialize message {}: {e}", message.id))?;
+        tracing::debug!("Replaying {} at {:?}", message.id, offset);
+        if let Err(e) = write_line(&mut child_stdin, &line).await {
+            tracing::error!("Server stopped accepting input: {}", e);
+            break;
+        }
+        sent += 1;
+        if let Some(id) = request_id(&content) {
+            awaiting.insert(id);
+        }
+        let _ = recorder
+            .record_message(content, MessageDirection::ToServer)
+            .await;
+    }
+
+    // Give the server a chance to answer whatever is still outstanding
+    let deadline = Instant::now() + options.response_timeout;
+    while !awaiting.is_empty() {
+        tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => break,
+            response = response_rx.recv() => match response {
+                Some(response) => {
+                    received += 1;
+                    record_response(&recorder, &mut awaiting, &mut live_requests, response).await;
+                }
+                None => break,
+            },
+        }
+    }
+
+    drop(child_stdin);
+    let _ = child.kill().await;
+
+    let mut unanswered: Vec<String> = awaiting.into_iter().collect();
+    unanswered.sort();
+    let session = recorder
+        .finalize()
+        .await
+        .map_err(|e| format!("Failed to finalize recording: {e}"))?;
+
+    Ok(ReplaySummary {
+        session,
+        sent,
+        skipped,
+        received,
+        unanswered,
+    })
+}
+
+/// Requests from the live server awaiting a replayed answer, by method
+type LiveRequests = HashMap<String, VecDeque<Value>>;
+
+/// Record a message from the server
+///
+/// Marks the request a response answers, and queues the server's own
+/// requests for the recorded answers to them.
+async fn record_response(
+    recorder: &SessionRecorder,
+    awaiting: &mut HashSet<String>,
+    live_requests: &mut LiveRequests,
+    response: Value,
+) {
+    match response.get("method").and_then(Value::as_str) {
+        Some(method) => {
+            if let Some(id) = response.get("id").filter(|id| !id.is_null()) {
+                live_requests
+                    .entry(method.to_string())
+                    .or_default()
+                    .push_back(id.clone());
+            }
+        }
+        None => {
+            if let Some(id) = response.get("id") {
+                awaiting.remove(&id.to_string());
+            }
+        }
+    }
+    let _ = recorder
+        .record_message(response, MessageDirection::ToClient)
+        .await;
+}
+
+/// The methods of the requests the server made in a recording, by id
+fn server_requests(messages: &[RecordedMessage]) -> HashMap<String, String> {
+    messages
+        .iter()
+        .filter(|m| m.direction == MessageDirection::ToClient)
+        .filter_map(|m| {
+            Some((
+                request_id(&m.content)?,
+                m.content["method"].as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Point a recorded answer to a server request at the live server's request
+///
+/// The recorded id means nothing to the new server, so the answer takes the
+/// id of the oldest unanswered live request of the same method. Returns
+/// `None` if the live server made no such request. Other messages are
+/// returned unchanged.
+fn remap_answer(
+    message: &Value,
+    recorded_requests: &HashMap<String, String>,
+    live_requests: &mut LiveRequests,
+) -> Option<Value> {
+    let is_answer = message.get("method").is_none()
+        && (message.get("result").is_some() || message.get("error").is_some());
+    let recorded_id = message.get("id").filter(|id| !id.is_null());
+    let (true, Some(recorded_id)) = (is_answer, recorded_id) else {
+        return Some(message.clone());
+    };
+
+    let method = recorded_requests.get(&recorded_id.to_string())?;
+    let live_id = live_requests.get_mut(method)?.pop_front()?;
+    let mut answer = message.clone();
+    answer["id"] = live_id;
+    Some(answer)
+}
+
+/// The id of a request that expects a response
+fn request_id(message: &Value) -> Option<String> {
+    message.get("method")?;
+    message
+        .get("id")
+        .filter(|id| !id.is_null())
+        .map(Value::to_string)
+}
+
+/// Write a newline-terminated message to the server's stdin
+async fn write_line(stdin: &mut tokio::process::ChildStdin, line: &str) -> std::io::Result<()> {
+    stdin.write_all(line.as_bytes()).await?;
+    stdin.write_all(b"\n").await?;
+    stdin.flush().await
+}
+
+/// Run replay mode
+pub async fn run_replay(
+    session: String,
+    options: ReplayOptions,
+    storage: Option<PathBuf>,
+    name: Option<String>,
+    no_save: bool,
+    command: Vec<String>,
+) -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let storage_path = storage.unwrap_or_else(SessionStorage::default_path);
+    let source = match crate::load_recording(&session, &storage_path).await {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("[reticle replay] Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let cmd = &command[0];
+    let args: Vec<&str> = command[1..].iter().map(|s| s.as_str()).collect();
+    let server_name = name.unwrap_or_else(|| crate::extract_server_name(cmd));
+
+    eprintln!(
+        "[reticle replay] Replaying '{}' ({} messages) against {}",
+        source.name, source.metadata.message_count, cmd
+    );
+
+    let summary = match replay_session(&source, cmd, &args, &server_name, &options).await {
+        Ok(summary) => summary,
+        Err(e) => {
+            eprintln!("[reticle replay] Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    eprintln!(
+        "[reticle replay] Sent {} message(s), received {}",
+        summary.sent, summary.received
+    );
+    for id in &summary.unanswered {
+        eprintln!("[reticle replay] No response to request {id}");
+    }
+    if summary.skipped > 0 {
+        eprintln!(
+            "[reticle replay] Skipped {} answer(s) to server requests the server did not make",
+            summary.skipped
+        );
+    }
+
+    if !no_save {
+        let saved = match SessionStorage::new(storage_path) {
+            Ok(storage) => storage.save_session(&summary.session).await,
+            Err(e) => Err(e),
+        };
+        match saved {
+            Ok(()) => eprintln!("[reticle replay] Saved session {}", summary.session.id),
+            Err(e) => {
+                eprintln!("[reticle replay] Error: failed to save session: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if summary.unanswered.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reticle_core::session_recorder::{MessageMetadata, SessionMetadata};
+    use serde_json::json;
+
+    fn recorded(
+        relative_time_ms: u64,
+        direction: MessageDirection,
+        content: Value,
+    ) -> RecordedMessage {
+        RecordedMessage {
+            id: format!("msg-{relative_time_ms}"),
+            timestamp_micros: relative_time_ms * 1000,
+            relative_time_ms,
+            direction,
+            metadata: MessageMetadata {
+                method: content["method"].as_str().map(String::from),
+                jsonrpc_id: content.get("id").cloned(),
+                injected: false,
+                modified: false,
+                original: None,
+                size_bytes: 0,
+                schema_errors: Vec::new(),
+            },
+            content,
+        }
+    }
+
+    #[test]
+    fn test_request_id() {
+        assert_eq!(
+            request_id(&json!({"id": 1, "method": "ping"})),
+            Some("1".to_string())
+        );
+        assert_eq!(
+            request_id(&json!({"id": "a", "method": "ping"})),
+            Some("\"a\"".to_string())
+        );
+        assert!(request_id(&json!({"method": "notifications/initialized"})).is_none());
+        assert!(request_id(&json!({"id": 1, "result": {}})).is_none());
+    }
+
+    #[test]
+    fn test_remap_answer() {
+        let source = vec![
+            recorded(
+                0,
+                MessageDirection::ToClient,
+                json!({"jsonrpc": "2.0", "id": 9, "method": "roots/list"}),
+            ),
+            recorded(
+                5,
+                MessageDirection::ToServer,
+                json!({"jsonrpc": "2.0", "id": 9, "result": {"roots": []}}),
+            ),
+        ];
+        let recorded_requests = server_requests(&source);
+        let answer = &source[1].content;
+
+        // No live request to answer yet
+        let mut live = LiveRequests::new();
+        assert!(remap_answer(answer, &recorded_requests, &mut live).is_none());
+
+        // The live server asked with its own id
+        live.entry("roots/list".to_string())
+            .or_default()
+            .push_back(json!("srv-1"));
+        let remapped = remap_answer(answer, &recorded_requests, &mut live).unwrap();
+        assert_eq!(remapped["id"], json!("srv-1"));
+        assert_eq!(remapped["result"], json!({"roots": []}));
+        assert!(live["roots/list"].is_empty());
+
+        // Client requests pass through unchanged
+        let ping = json!({"jsonrpc": "2.0", "id": 1, "method": "ping"});
+        assert_eq!(
+            remap_answer(&ping, &recorded_requests, &mut live),
+            Some(ping)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_replay_against_echo_server() {
+        let source = RecordedSession {
+            id: "source".to_string(),
+            name: "original".to_string(),
+            started_at: 0,
+            ended_at: None,
+            messages: vec![
+                recorded(
+                    0,
+                    MessageDirection::ToServer,
+                    json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}),
+                ),
+                recorded(
+                    5,
+                    MessageDirection::ToClient,
+                    json!({"jsonrpc": "2.0", "id": 1, "result": {}}),
+                ),
+                recorded(
+                    10,
+                    MessageDirection::ToServer,
+                    json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                ),
+                recorded(
+                    15,
+                    MessageDirection::ToClient,
+                    json!({"jsonrpc": "2.0", "id": 0, "method": "roots/list"}),
+                ),
+                recorded(
+                    20,
+                    MessageDirection::ToServer,
+                    json!({"jsonrpc": "2.0", "id": 0, "result": {"roots": []}}),
+                ),
+            ],
+            metadata: SessionMetadata {
+                transport: "stdio".to_string(),
+                message_count: 5,
+                duration_ms: None,
+                client_info: None,
+                server_info: None,
+                protocol_version: None,
+                server_id: None,
+                tags: vec![],
+                latency: None,
+            },
+        };
+        let options = ReplayOptions {
+            timing: ReplayTiming::default(),
+            response_timeout: Duration::from_millis(200),
+        };
+
+        // `cat` echoes requests back instead of answering them
+        let summary = replay_session(&source, "cat", &[], "cat", &options)
+            .await
+            .unwrap();
+        assert_eq!(summary.sent, 2);
+        // `cat` never asks for roots, so the recorded answer is not sent
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.unanswered, vec!["1".to_string()]);
+        assert_eq!(summary.session.name, "Replay of original");
+        assert!(summary
+            .session
+            .metadata
+            .tags
+            .contains(&"replay-of:source".to_string()));
+        assert!(summary
+            .session
+            .messages
+            .iter()
+            .any(|m| m.direction == MessageDirection::ToServer));
+    }
+}
@@ -0,0 +1,1000 @@
+//! Terminal viewer for live traffic (`reticle tail`)
+//!
+//! Log entries come from a running daemon's `/api/stream`, or, when no
+//! daemon is listening, from CLI instances connecting to the hub socket,
+//! which `tail` then listens on in place of the GUI or daemon. They are shown in one list across all servers,
+//! each server in its own colour, with every response linked to the request
+//! it answers and the selected message pretty-printed beside the list.
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use reticle_core::protocol::{Direction, LogEntry, McpMessage, MessageType};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Port of the daemon API `tail` watches when none is given
+pub const DAEMON_PORT: u16 = 9315;
+
+/// Messages kept before the oldest are dropped
+const MAX_ROWS: usize = 10_000;
+
+/// Colours given to servers in the order they are first seen
+const SERVER_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Blue,
+    Color::LightYellow,
+    Color::LightGreen,
+    Color::LightRed,
+];
+
+/// Where log entries come from
+pub enum Source {
+    /// Listen on the hub socket for CLI instances
+    Socket(PathBuf),
+    /// Subscribe to the live stream of a daemon's API on this port
+    Daemon(u16),
+    /// A daemon's stream on this port, or the hub socket if nothing is
+    /// listening on it
+    DaemonOrSocket(u16, PathBuf),
+}
+
+/// What the sources pass to the UI
+#[derive(Debug)]
+enum Feed {
+    Entry(Box<LogEntry>),
+    /// A message for the status line
+    Notice(String),
+}
+
+/// Parse a `--direction` argument
+pub fn parse_direction(value: &str) -> Result<Direction, String> {
+    match value {
+        "in" => Ok(Direction::In),
+        "out" => Ok(Direction::Out),
+        _ => Err(format!("expected `in` or `out`, not `{value}`")),
+    }
+}
+
+/// Which messages are shown
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    pub server: Option<String>,
+    pub direction: Option<Direction>,
+    /// Case-insensitive text to find in the server, method, or content
+    pub text: String,
+}
+
+impl Filter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        if self.server.is_some() && entry.server_name != self.server
+            || self.direction.is_some_and(|d| d != entry.direction)
+        {
+            return false;
+        }
+        if self.text.is_empty() {
+            return true;
+        }
+        let text = self.text.to_lowercase();
+        [
+            entry.server_name.as_deref(),
+            entry.method.as_deref(),
+            Some(entry.content.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(&text))
+    }
+}
+
+/// A message in the list, numbered in the order it arrived
+struct Row {
+    seq: u64,
+    entry: LogEntry,
+    /// The request this message answers, or the response that answered it
+    pair: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Normal,
+    /// Typing a filter; the filter as it was before
+    EditFilter(String),
+}
+
+/// The viewer's state, kept apart from the terminal so it can be tested
+struct App {
+    source: String,
+    rows: VecDeque<Row>,
+    /// Sequence number of the first row still kept
+    first_seq: u64,
+    /// Requests not yet answered, by session, direction, and id
+    pending: HashMap<(String, Direction, String), u64>,
+    filter: Filter,
+    /// Rows shown under the filter
+    visible: VecDeque<u64>,
+    /// Index into `visible` of the selected row
+    selected: usize,
+    /// Keep the newest row selected as messages arrive
+    follow: bool,
+    detail_scroll: u16,
+    colors: HashMap<String, Color>,
+    mode: Mode,
+    notice: Option<String>,
+    started_micros: u64,
+}
+
+impl App {
+    fn new(source: String, filter: Filter) -> Self {
+        Self {
+            source,
+            rows: VecDeque::new(),
+            first_seq: 0,
+            pending: HashMap::new(),
+            filter,
+            visible: VecDeque::new(),
+            selected: 0,
+            follow: true,
+            detail_scroll: 0,
+            colors: HashMap::new(),
+            mode: Mode::Normal,
+            notice: None,
+            started_micros: now_micros(),
+        }
+    }
+
+    fn row(&self, seq: u64) -> Option<&Row> {
+        let index = seq.checked_sub(self.first_seq)?;
+        self.rows.get(index as usize)
+    }
+
+    fn selected_row(&self) -> Option<&Row> {
+        self.visible
+            .get(self.selected)
+            .and_then(|&seq| self.row(seq))
+    }
+
+    fn receive(&mut self, feed: Feed) {
+        match feed {
+            Feed::Entry(entry) => self.push(*entry),
+            Feed::Notice(notice) => self.notice = Some(notice),
+        }
+    }
+
+    fn push(&mut self, mut entry: LogEntry) {
+        let seq = self.first_seq + self.rows.len() as u64;
+        // Entries from the daemon's stream arrive without the parsed message
+        if entry.message.is_none() && entry.message_type == MessageType::JsonRpc {
+            entry.message = serde_json::from_str(&entry.content)
+                .ok()
+                .map(|json| McpMessage::parse(&json));
+        }
+
+        let mut pair = None;
+        match entry.message {
+            Some(McpMessage::Request { ref id, .. }) => {
+                self.pending.insert(
+                    (entry.session_id.clone(), entry.direction, id.to_string()),
+                    seq,
+                );
+            }
+            Some(McpMessage::Response { ref id, .. } | McpMessage::Error { ref id, .. }) => {
+                let key = (
+                    entry.session_id.clone(),
+                    opposite(entry.direction),
+                    id.to_string(),
+                );
+                if let Some(request) = self.pending.remove(&key) {
+                    if let Some(index) = request.checked_sub(self.first_seq) {
+                        if let Some(row) = self.rows.get_mut(index as usize) {
+                            row.pair = Some(seq);
+                            pair = Some(request);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(ref server) = entry.server_name {
+            let next = SERVER_COLORS[self.colors.len() % SERVER_COLORS.len()];
+            self.colors.entry(server.clone()).or_insert(next);
+        }
+        if self.filter.matches(&entry) {
+            self.visible.push_back(seq);
+        }
+        self.rows.push_back(Row { seq, entry, pair });
+
+        while self.rows.len() > MAX_ROWS {
+            let Some(oldest) = self.rows.pop_front() else {
+                break;
+            };
+            self.first_seq += 1;
+            if let Some(McpMessage::Request { ref id, .. }) = oldest.entry.message {
+                self.pending.remove(&(
+                    oldest.entry.session_id,
+                    oldest.entry.direction,
+                    id.to_string(),
+                ));
+            }
+            if self.visible.front() == Some(&oldest.seq) {
+                self.visible.pop_front();
+                self.selected = self.selected.saturating_sub(1);
+            }
+        }
+        if self.follow {
+            self.select(self.visible.len().saturating_sub(1));
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.visible.len().saturating_sub(1));
+        if index != self.selected {
+            self.detail_scroll = 0;
+        }
+        self.selected = index;
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        self.follow = false;
+        self.select(self.selected.saturating_add_signed(delta));
+    }
+
+    fn select_last(&mut self) {
+        self.follow = true;
+        self.select(self.visible.len().saturating_sub(1));
+    }
+
+    /// Select the request or response paired with the selected row
+    fn jump_to_pair(&mut self) {
+        let Some(pair) = self.selected_row().and_then(|row| row.pair) else {
+            return;
+        };
+        match self.visible.binary_search(&pair) {
+            Ok(index) => {
+                self.follow = false;
+                self.select(index);
+            }
+            Err(_) => self.notice = Some("Paired message is hidden by the filter".to_string()),
+        }
+    }
+
+    /// Show the rows matching the filter, keeping the selection if it still
+    /// matches
+    fn refilter(&mut self) {
+        let selected = self.visible.get(self.selected).copied();
+        self.visible = self
+            .rows
+            .iter()
+            .filter(|row| self.filter.matches(&row.entry))
+            .map(|row| row.seq)
+            .collect();
+        match selected.and_then(|seq| self.visible.binary_search(&seq).ok()) {
+            Some(index) if !self.follow => self.select(index),
+            _ => self.select_last(),
+        }
+    }
+
+    fn cycle_direction(&mut self) {
+        self.filter.direction = match self.filter.direction {
+            None => Some(Direction::In),
+            Some(Direction::In) => Some(Direction::Out),
+            Some(Direction::Out) => None,
+        };
+        self.refilter();
+    }
+
+    fn cycle_server(&mut self) {
+        let mut servers: Vec<&String> = self.colors.keys().collect();
+        servers.sort();
+        let next = match self.filter.server {
+            None => servers.first(),
+            Some(ref current) => servers
+                .iter()
+                .position(|s| *s == current)
+                .and_then(|i| servers.get(i + 1)),
+        };
+        self.filter.server = next.map(|s| s.to_string());
+        self.refilter();
+    }
+
+    fn clear(&mut self) {
+        self.first_seq += self.rows.len() as u64;
+        self.rows.clear();
+        self.pending.clear();
+        self.visible.clear();
+        self.select_last();
+    }
+
+    /// Act on a key press, returning false to quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        self.notice = None;
+
+        if let Mode::EditFilter(ref before) = self.mode {
+            match key.code {
+                KeyCode::Enter => self.mode = Mode::Normal,
+                KeyCode::Esc => {
+                    self.filter.text = before.clone();
+                    self.mode = Mode::Normal;
+                    self.refilter();
+                }
+                KeyCode::Backspace => {
+                    self.filter.text.pop();
+                    self.refilter();
+                }
+                KeyCode::Char(c) => {
+                    self.filter.text.push(c);
+                    self.refilter();
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-20),
+            KeyCode::PageDown => self.move_selection(20),
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.follow = false;
+                self.select(0);
+            }
+            KeyCode::End | KeyCode::Char('G') => self.select_last(),
+            KeyCode::Enter | KeyCode::Char('p') => self.jump_to_pair(),
+            KeyCode::Char('K') => self.detail_scroll = self.detail_scroll.saturating_sub(1),
+            KeyCode::Char('J') => self.detail_scroll = self.detail_scroll.saturating_add(1),
+            KeyCode::Char('/') => self.mode = Mode::EditFilter(self.filter.text.clone()),
+            KeyCode::Char('d') => self.cycle_direction(),
+            KeyCode::Char('s') => self.cycle_server(),
+            KeyCode::Char('c') => self.clear(),
+            KeyCode::Esc => {
+                self.filter = Filter::default();
+                self.refilter();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn server_style(&self, entry: &LogEntry) -> Style {
+        let color = entry
+            .server_name
+            .as_ref()
+            .and_then(|server| self.colors.get(server))
+            .copied()
+            .unwrap_or(Color::Gray);
+        Style::default().fg(color)
+    }
+
+    /// What a row is: its method, or the method of the request it answers
+    fn label(&self, row: &Row) -> (String, Style) {
+        let entry = &row.entry;
+        match entry.message_type {
+            MessageType::JsonRpc => {}
+            MessageType::Stderr => {
+                return ("stderr".to_string(), Style::default().fg(Color::DarkGray))
+            }
+            other => {
+                let name = serde_json::to_value(other)
+                    .ok()
+                    .and_then(|v| v.as_str().map(String::from))
+                    .unwrap_or_default();
+                return (name, Style::default().fg(Color::Red));
+            }
+        }
+        if let Some(ref method) = entry.method {
+            return (method.clone(), Style::default());
+        }
+        let answers = row
+            .pair
+            .and_then(|seq| self.row(seq))
+            .and_then(|request| request.entry.method.clone())
+            .unwrap_or_else(|| "response".to_string());
+        let style = if matches!(entry.message, Some(McpMessage::Error { .. })) {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        (format!("↳ {answers}"), style)
+    }
+
+    fn list_item(&self, row: &Row) -> ListItem<'static> {
+        let entry = &row.entry;
+        let elapsed = entry.timestamp.saturating_sub(self.started_micros) as f64 / 1_000_000.0;
+        let server = entry.server_name.as_deref().unwrap_or("-");
+        let (arrow, arrow_color) = match entry.direction {
+            Direction::In => ("→", Color::Green),
+            Direction::Out => ("←", Color::Yellow),
+        };
+        let (label, label_style) = self.label(row);
+        let mut spans = vec![
+            Span::styled(
+                format!("{elapsed:>9.3} "),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(format!("{server:<14.14} "), self.server_style(entry)),
+            Span::styled(format!("{arrow} "), Style::default().fg(arrow_color)),
+            Span::styled(label, label_style),
+        ];
+        if let Some(micros) = entry.duration_micros {
+            spans.push(Span::styled(
+                format!("  {:.1}ms", micros as f64 / 1000.0),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        ListItem::new(Line::from(spans))
+    }
+
+    fn detail(&self, row: &Row) -> Text<'static> {
+        let entry = &row.entry;
+        let field = |name: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{name:<10}"), Style::default().fg(Color::DarkGray)),
+                Span::raw(value),
+            ])
+        };
+        let mut lines = vec![
+            Line::from(Span::styled(
+                entry.server_name.clone().unwrap_or_else(|| "-".to_string()),
+                self.server_style(entry).add_modifier(Modifier::BOLD),
+            )),
+            field("Session", entry.session_id.clone()),
+            field(
+                "Direction",
+                match entry.direction {
+                    Direction::In => "client → server".to_string(),
+                    Direction::Out => "server → client".to_string(),
+                },
+            ),
+        ];
+        if let Some(ref method) = entry.method {
+            lines.push(field("Method", method.clone()));
+        }
+        if let Some(micros) = entry.duration_micros {
+            lines.push(field(
+                "Latency",
+                format!("{:.1} ms", micros as f64 / 1000.0),
+            ));
+        }
+        if entry.token_count > 0 {
+            lines.push(field("Tokens", entry.token_count.to_string()));
+        }
+        if let Some(pair) = row.pair.and_then(|seq| self.row(seq)) {
+            let (name, what) = if pair.seq < row.seq {
+                ("Answers", pair.entry.method.clone().unwrap_or_default())
+            } else {
+                ("Answer", self.label(pair).0)
+            };
+            lines.push(field(name, format!("#{} {what} (enter to jump)", pair.seq)));
+        } else if matches!(entry.message, Some(McpMessage::Request { .. })) {
+            lines.push(field("Answer", "pending".to_string()));
+        }
+        if entry.modified {
+            lines.push(field("Modified", "yes".to_string()));
+        }
+        lines.push(Line::default());
+
+        let content = serde_json::from_str::<serde_json::Value>(&entry.content)
+            .ok()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok())
+            .unwrap_or_else(|| entry.content.clone());
+        lines.extend(content.lines().map(|line| Line::raw(line.to_string())));
+        if let Some(ref original) = entry.original {
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                "As originally received:",
+                Style::default().fg(Color::DarkGray),
+            ));
+            lines.push(Line::raw(original.clone()));
+        }
+        Text::from(lines)
+    }
+
+    fn status(&self) -> Line<'static> {
+        if let Mode::EditFilter(_) = self.mode {
+            return Line::from(vec![
+                Span::styled("Filter: ", Style::default().fg(Color::Yellow)),
+                Span::raw(format!("{}█", self.filter.text)),
+                Span::styled(
+                    "  (enter to keep, esc to cancel)",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]);
+        }
+        let mut spans = vec![Span::styled(
+            format!(" {} ", self.source),
+            Style::default().fg(Color::Black).bg(Color::Gray),
+        )];
+        let mut filters = Vec::new();
+        if let Some(ref server) = self.filter.server {
+            filters.push(format!("server={server}"));
+        }
+        if let Some(direction) = self.filter.direction {
+            filters.push(match direction {
+                Direction::In => "direction=in".to_string(),
+                Direction::Out => "direction=out".to_string(),
+            });
+        }
+        if !self.filter.text.is_empty() {
+            filters.push(format!("/{}", self.filter.text));
+        }
+        if !filters.is_empty() {
+            spans.push(Span::styled(
+                format!(" {}", filters.join(" ")),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        match self.notice {
+            Some(ref notice) => spans.push(Span::styled(
+                format!("  {notice}"),
+                Style::default().fg(Color::Red),
+            )),
+            None => spans.push(Span::styled(
+                "  q quit  / filter  s server  d direction  esc clear filter  enter pair  J/K scroll",
+                Style::default().fg(Color::DarkGray),
+            )),
+        }
+        Line::from(spans)
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(main);
+
+        self.render_list(frame, list_area);
+
+        let detail = match self.selected_row() {
+            Some(row) => self.detail(row),
+            None => Text::styled(
+                "Waiting for messages…",
+                Style::default().fg(Color::DarkGray),
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(detail)
+                .block(Block::bordered().title(" Message "))
+                .wrap(Wrap { trim: false })
+                .scroll((self.detail_scroll, 0)),
+            detail_area,
+        );
+        frame.render_widget(Paragraph::new(self.status()), status);
+    }
+
+    fn render_list(&self, frame: &mut Frame, area: Rect) {
+        // Only the rows that fit are built, keeping the selection in view
+        let height = area.height.saturating_sub(2).max(1) as usize;
+        let first = (self.selected + 1).saturating_sub(height);
+        let items: Vec<ListItem> = self
+            .visible
+            .range(first.min(self.visible.len())..)
+            .take(height)
+            .filter_map(|&seq| self.row(seq))
+            .map(|row| self.list_item(row))
+            .collect();
+
+        let title = format!(
+            " Messages {}/{}{} ",
+            self.visible.len(),
+            self.rows.len(),
+            if self.follow { " · following" } else { "" }
+        );
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default()
+            .with_selected((!self.visible.is_empty()).then_some(self.selected - first));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::In => Direction::Out,
+        Direction::Out => Direction::In,
+    }
+}
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+/// Splits a server-sent event stream into `(event, data)` pairs
+#[derive(Default)]
+struct SseParser {
+    buffer: String,
+}
+
+impl SseParser {
+    fn push(&mut self, chunk: &str) -> Vec<(String, String)> {
+        self.buffer.push_str(&chunk.replace("\r\n", "\n"));
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.find("\n\n") {
+            let frame: String = self.buffer.drain(..end + 2).collect();
+            let mut event = "message".to_string();
+            let mut data = Vec::new();
+            for line in frame.lines() {
+                if let Some(name) = line.strip_prefix("event:") {
+                    event = name.trim_start().to_string();
+                } else if let Some(line) = line.strip_prefix("data:") {
+                    data.push(line.strip_prefix(' ').unwrap_or(line));
+                }
+            }
+            // Comments, such as keep-alives, carry no data
+            if !data.is_empty() {
+                events.push((event, data.join("\n")));
+            }
+        }
+        events
+    }
+}
+
+/// Subscribe to a daemon's live stream
+async fn connect_daemon(port: u16) -> Result<reqwest::Response, reqwest::Error> {
+    reqwest::get(format!("http://127.0.0.1:{port}/api/stream"))
+        .await
+        .and_then(reqwest::Response::error_for_status)
+}
+
+/// Stream log entries from a daemon's API, returning the viewer's label
+fn watch_daemon(port: u16, response: reqwest::Response, feed: mpsc::Sender<Feed>) -> String {
+    tokio::spawn(stream_daemon(response, feed));
+    format!("daemon on port {port}")
+}
+
+/// Listen on the hub socket, returning the viewer's label
+#[cfg(unix)]
+async fn listen(path: &std::path::Path, feed: mpsc::Sender<Feed>) -> Result<String, String> {
+    let listener = socket::bind(path).await?;
+    tokio::spawn(socket::accept(listener, feed));
+    Ok(format!("listening on {}", path.display()))
+}
+
+#[cfg(not(unix))]
+async fn listen(_path: &std::path::Path, _feed: mpsc::Sender<Feed>) -> Result<String, String> {
+    Err("The hub socket is only available on Unix; use --port".to_string())
+}
+
+/// Stream log entries from a daemon's API
+async fn stream_daemon(response: reqwest::Response, feed: mpsc::Sender<Feed>) {
+    use futures::StreamExt;
+
+    let mut body = response.bytes_stream();
+    let mut parser = SseParser::default();
+    while let Some(chunk) = body.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let _ = feed.send(Feed::Notice(format!("Stream failed: {e}"))).await;
+                return;
+            }
+        };
+        for (event, data) in parser.push(&String::from_utf8_lossy(&chunk)) {
+            let item = match event.as_str() {
+                "log" => match serde_json::from_str(&data) {
+                    Ok(entry) => Feed::Entry(Box::new(entry)),
+                    Err(_) => continue,
+                },
+                "lagged" => Feed::Notice(format!("Fell behind; missed {data} messages")),
+                _ => continue,
+            };
+            if feed.send(item).await.is_err() {
+                return;
+            }
+        }
+    }
+    let _ = feed
+        .send(Feed::Notice("The daemon closed the stream".to_string()))
+        .await;
+}
+
+#[cfg(unix)]
+mod socket {
+    use super::Feed;
+    use std::path::Path;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::sync::mpsc;
+
+    /// Listen on the hub socket, unless the GUI or a daemon already is
+    pub(super) async fn bind(path: &Path) -> Result<UnixListener, String> {
+        if path.exists() {
+            if UnixStream::connect(path).await.is_ok() {
+                return Err(format!(
+                    "{} is in use by the GUI or a daemon; start the daemon with `--port {}` to watch it",
+                    path.display(),
+                    super::DAEMON_PORT
+                ));
+            }
+            std::fs::remove_file(path)
+                .map_err(|e| format!("Failed to remove stale socket: {e}"))?;
+        }
+        UnixListener::bind(path)
+            .map_err(|e| format!("Failed to bind to socket {}: {e}", path.display()))
+    }
+
+    /// Pass on the log entries of every CLI instance that connects
+    pub(super) async fn accept(listener: UnixListener, feed: mpsc::Sender<Feed>) {
+        while let Ok((stream, _)) = listener.accept().await {
+            let feed = feed.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stream).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let Ok(event) = serde_json::from_str(&line) else {
+                        continue;
+                    };
+                    let Some(entry) = crate::daemon::log_entry(&event) else {
+                        continue;
+                    };
+                    if feed.send(Feed::Entry(Box::new(entry))).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Run the viewer until the user quits
+pub async fn run_tail(source: Source, filter: Filter) -> Result<(), String> {
+    let (feed, entries) = mpsc::channel(1024);
+    let mut socket_path = None;
+
+    let daemon_error = |port: u16, e: reqwest::Error| {
+        format!("Failed to connect to the daemon on port {port}: {e}")
+    };
+    let label = match source {
+        Source::Socket(path) => {
+            let label = listen(&path, feed).await?;
+            socket_path = Some(path);
+            label
+        }
+        Source::Daemon(port) => {
+            let response = connect_daemon(port)
+                .await
+                .map_err(|e| daemon_error(port, e))?;
+            watch_daemon(port, response, feed)
+        }
+        Source::DaemonOrSocket(port, path) => match connect_daemon(port).await {
+            Ok(response) => watch_daemon(port, response, feed),
+            // No daemon to watch, so stand in for the hub
+            Err(e) if e.is_connect() => {
+                let label = listen(&path, feed).await?;
+                socket_path = Some(path);
+                label
+            }
+            Err(e) => return Err(daemon_error(port, e)),
+        },
+    };
+
+    let app = App::new(label, filter);
+    let result = tokio::task::spawn_blocking(move || run_ui(app, entries))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result.map_err(|e| format!("Terminal error: {e}")));
+
+    if let Some(path) = socket_path {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+/// Draw the viewer and handle keys, on a thread of its own since terminal
+/// input is read blocking
+fn run_ui(mut app: App, mut entries: mpsc::Receiver<Feed>) -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = (|| loop {
+        while let Ok(item) = entries.try_recv() {
+            app.receive(item);
+        }
+        terminal.draw(|frame| app.render(frame))?;
+        if event::poll(Duration::from_millis(50))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !app.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use serde_json::json;
+
+    fn entry(server: &str, direction: Direction, content: serde_json::Value) -> LogEntry {
+        let id = format!("{server}-{}", content);
+        LogEntry::with_server(id, "s1".to_string(), direction, content, server.to_string())
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_pairs_responses_with_requests() {
+        let mut app = App::new("test".to_string(), Filter::default());
+        app.push(entry(
+            "github",
+            Direction::In,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+        ));
+        app.push(entry(
+            "github",
+            Direction::In,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call"}),
+        ));
+        // A server's own request with the same id is not confused with the client's
+        app.push(entry(
+            "github",
+            Direction::Out,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}),
+        ));
+        app.push(entry(
+            "github",
+            Direction::Out,
+            json!({"jsonrpc": "2.0", "id": 2, "result": {}}),
+        ));
+        app.push(entry(
+            "github",
+            Direction::Out,
+            json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -1, "message": "no"}}),
+        ));
+
+        assert_eq!(app.row(1).unwrap().pair, Some(3));
+        assert_eq!(app.row(3).unwrap().pair, Some(1));
+        assert_eq!(app.row(0).unwrap().pair, Some(4));
+        assert_eq!(app.row(2).unwrap().pair, None);
+        assert_eq!(app.label(app.row(3).unwrap()).0, "↳ tools/call");
+
+        // Following the newest row; enter jumps to the request it answers
+        assert_eq!(app.selected, 4);
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.selected, 0);
+        assert!(!app.follow);
+        app.handle_key(key(KeyCode::Char('p')));
+        assert_eq!(app.selected, 4);
+    }
+
+    #[test]
+    fn test_filters() {
+        let mut app = App::new("test".to_string(), Filter::default());
+        app.push(entry(
+            "github",
+            Direction::In,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call"}),
+        ));
+        app.push(entry(
+            "slack",
+            Direction::In,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+        ));
+        app.push(entry(
+            "github",
+            Direction::Out,
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"content": []}}),
+        ));
+        assert_eq!(app.visible, [0, 1, 2]);
+
+        app.handle_key(key(KeyCode::Char('s')));
+        assert_eq!(app.filter.server.as_deref(), Some("github"));
+        assert_eq!(app.visible, [0, 2]);
+        app.handle_key(key(KeyCode::Char('d')));
+        assert_eq!(app.visible, [0]);
+
+        app.handle_key(key(KeyCode::Esc));
+        app.handle_key(key(KeyCode::Char('/')));
+        for c in "LIST".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.visible, [1]);
+        // Cancelling restores the filter from before
+        app.handle_key(key(KeyCode::Esc));
+        assert_eq!(app.visible, [0, 1, 2]);
+
+        // Rows arriving later are filtered too, and each server keeps its colour
+        app.filter.direction = Some(Direction::Out);
+        app.refilter();
+        app.push(entry(
+            "slack",
+            Direction::Out,
+            json!({"jsonrpc": "2.0", "id": 1, "result": {}}),
+        ));
+        assert_eq!(app.visible, [2, 3]);
+        assert_ne!(app.colors["github"], app.colors["slack"]);
+    }
+
+    #[test]
+    fn test_drops_oldest_rows() {
+        let mut app = App::new("test".to_string(), Filter::default());
+        for i in 0..MAX_ROWS + 5 {
+            app.push(entry(
+                "github",
+                Direction::In,
+                json!({"jsonrpc": "2.0", "id": i, "method": "ping"}),
+            ));
+        }
+        assert_eq!(app.rows.len(), MAX_ROWS);
+        assert_eq!(app.first_seq, 5);
+        assert_eq!(app.visible.front(), Some(&5));
+        assert_eq!(app.selected_row().unwrap().seq, (MAX_ROWS + 4) as u64);
+        assert_eq!(app.pending.len(), MAX_ROWS);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut app = App::new("test".to_string(), Filter::default());
+        app.push(entry(
+            "github",
+            Direction::In,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call"}),
+        ));
+        app.push(entry(
+            "github",
+            Direction::Out,
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"ok": true}}),
+        ));
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Messages 2/2"));
+        assert!(screen.contains("↳ tools/call"));
+        assert!(screen.contains("\"ok\": true"));
+        assert!(screen.contains("Answers"));
+    }
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        assert!(parser.push("event: log\ndata: {\"a\"").is_empty());
+        let events = parser.push(":1}\n\n: keep-alive\n\nevent: lagged\ndata: 3\n\n");
+        assert_eq!(
+            events,
+            [
+                ("log".to_string(), "{\"a\":1}".to_string()),
+                ("lagged".to_string(), "3".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_daemon_without_one() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        // Only this error falls back to the hub socket
+        let error = connect_daemon(port).await.unwrap_err();
+        assert!(error.is_connect());
+    }
+}
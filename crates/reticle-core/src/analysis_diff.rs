@@ -0,0 +1,406 @@
+//! Diff between two server analyses
+//!
+//! Compares what an agent sees of a server in two snapshots, typically two
+//! versions of it. Tools are matched by name and compared on description
+//! and input schema; prompts and resources are matched by name and URI and
+//! compared on description and token cost. Token deltas are only meaningful
+//! when both snapshots were counted with the same tokenizer, so a tokenizer
+//! change alone never marks anything as changed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::server_analyzer::{AnalysisInfo, ServerAnalysis, ToolTokenInfo};
+use crate::session_diff::{diff_json, DiffStatus, JsonChange};
+
+/// Comparison of one tool across the two snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDiff {
+    pub name: String,
+    pub status: DiffStatus,
+    /// Description in the first snapshot, when it changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_before: Option<String>,
+    /// Description in the second snapshot, when it changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_after: Option<String>,
+    /// Changes to the input schema
+    pub schema: Vec<JsonChange>,
+    pub tokens_before: u64,
+    pub tokens_after: u64,
+    pub token_delta: i64,
+}
+
+/// Comparison of one prompt or resource across the two snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefinitionDiff {
+    /// Prompt name or resource URI
+    pub name: String,
+    pub status: DiffStatus,
+    pub tokens_before: u64,
+    pub tokens_after: u64,
+    pub token_delta: i64,
+}
+
+/// Totals for an analysis diff
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalysisDiffSummary {
+    pub tools_added: usize,
+    pub tools_removed: usize,
+    pub tools_changed: usize,
+    pub tools_unchanged: usize,
+    /// Prompts added, removed, or changed
+    pub prompts_changed: usize,
+    /// Resources added, removed, or changed
+    pub resources_changed: usize,
+    pub tokens_before: u64,
+    pub tokens_after: u64,
+    pub token_delta: i64,
+}
+
+/// What changed in a server's surface between two analyses
+///
+/// Unchanged tools, prompts, and resources are left out; the summary
+/// counts the unchanged tools.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisDiff {
+    pub before: AnalysisInfo,
+    pub after: AnalysisInfo,
+    pub tools: Vec<ToolDiff>,
+    pub prompts: Vec<DefinitionDiff>,
+    pub resources: Vec<DefinitionDiff>,
+    pub summary: AnalysisDiffSummary,
+}
+
+impl AnalysisDiff {
+    /// Whether any tool, prompt, or resource was added, removed, or changed
+    pub fn has_differences(&self) -> bool {
+        let s = &self.summary;
+        s.tools_added + s.tools_removed + s.tools_changed + s.prompts_changed + s.resources_changed
+            > 0
+    }
+
+    /// Whether the snapshots were counted with different tokenizers
+    pub fn tokenizer_changed(&self) -> bool {
+        self.before.tokenizer != self.after.tokenizer
+    }
+}
+
+/// Compare two analyses of a server
+pub fn diff_analyses(before: &ServerAnalysis, after: &ServerAnalysis) -> AnalysisDiff {
+    let mut summary = AnalysisDiffSummary {
+        tokens_before: before.total_context_tokens,
+        tokens_after: after.total_context_tokens,
+        token_delta: delta(before.total_context_tokens, after.total_context_tokens),
+        ..Default::default()
+    };
+
+    let tools = diff_tools(&before.tools.tools, &after.tools.tools);
+    for tool in &tools {
+        match tool.status {
+            DiffStatus::Added => summary.tools_added += 1,
+            DiffStatus::Removed => summary.tools_removed += 1,
+            DiffStatus::Changed => summary.tools_changed += 1,
+            DiffStatus::Unchanged => summary.tools_unchanged += 1,
+        }
+    }
+    let tools: Vec<ToolDiff> = tools
+        .into_iter()
+        .filter(|tool| tool.status != DiffStatus::Unchanged)
+        .collect();
+
+    let same_tokenizer = before.tokenizer == after.tokenizer;
+    let prompts = diff_definitions(
+        before
+            .prompts
+            .prompts
+            .iter()
+            .map(|p| (p.name.as_str(), (p.description.as_deref(), p.total_tokens))),
+        after
+            .prompts
+            .prompts
+            .iter()
+            .map(|p| (p.name.as_str(), (p.description.as_deref(), p.total_tokens))),
+        same_tokenizer,
+    );
+    let resources = diff_definitions(
+        before
+            .resources
+            .resources
+            .iter()
+            .map(|r| (r.uri.as_str(), (r.description.as_deref(), r.total_tokens))),
+        after
+            .resources
+            .resources
+            .iter()
+            .map(|r| (r.uri.as_str(), (r.description.as_deref(), r.total_tokens))),
+        same_tokenizer,
+    );
+    summary.prompts_changed = prompts.len();
+    summary.resources_changed = resources.len();
+
+    AnalysisDiff {
+        before: before.info(),
+        after: after.info(),
+        tools,
+        prompts,
+        resources,
+        summary,
+    }
+}
+
+fn diff_tools(before: &[ToolTokenInfo], after: &[ToolTokenInfo]) -> Vec<ToolDiff> {
+    // A server with duplicate names is already flagged by the linter; the
+    // first definition wins here
+    let mut tools: BTreeMap<&str, (Option<&ToolTokenInfo>, Option<&ToolTokenInfo>)> =
+        BTreeMap::new();
+    for tool in before {
+        tools.entry(&tool.name).or_default().0.get_or_insert(tool);
+    }
+    for tool in after {
+        tools.entry(&tool.name).or_default().1.get_or_insert(tool);
+    }
+
+    tools
+        .into_iter()
+        .map(|(name, sides)| {
+            let tokens_before = sides.0.map_or(0, |t| t.total_tokens);
+            let tokens_after = sides.1.map_or(0, |t| t.total_tokens);
+            let mut diff = ToolDiff {
+                name: name.to_string(),
+                status: DiffStatus::Unchanged,
+                description_before: None,
+                description_after: None,
+                schema: Vec::new(),
+                tokens_before,
+                tokens_after,
+                token_delta: delta(tokens_before, tokens_after),
+            };
+            match sides {
+                (Some(a), Some(b)) => {
+                    if a.description != b.description {
+                        diff.description_before = Some(a.description.clone());
+                        diff.description_after = Some(b.description.clone());
+                    }
+                    let empty = serde_json::Value::Null;
+                    diff_json(
+                        a.input_schema.as_ref().unwrap_or(&empty),
+                        b.input_schema.as_ref().unwrap_or(&empty),
+                        "$",
+                        &mut diff.schema,
+                    );
+                    if diff.description_after.is_some() || !diff.schema.is_empty() {
+                        diff.status = DiffStatus::Changed;
+                    }
+                }
+                (None, _) => diff.status = DiffStatus::Added,
+                (_, None) => diff.status = DiffStatus::Removed,
+            }
+            diff
+        })
+        .collect()
+}
+
+/// Diff prompts or resources, keyed by name, on description and tokens
+fn diff_definitions<'a>(
+    before: impl Iterator<Item = (&'a str, (Option<&'a str>, u64))>,
+    after: impl Iterator<Item = (&'a str, (Option<&'a str>, u64))>,
+    compare_tokens: bool,
+) -> Vec<DefinitionDiff> {
+    type Side<'a> = Option<(Option<&'a str>, u64)>;
+    let mut definitions: BTreeMap<&str, (Side, Side)> = BTreeMap::new();
+    for (name, definition) in before {
+        definitions
+            .entry(name)
+            .or_default()
+            .0
+            .get_or_insert(definition);
+    }
+    for (name, definition) in after {
+        definitions
+            .entry(name)
+            .or_default()
+            .1
+            .get_or_insert(definition);
+    }
+
+    definitions
+        .into_iter()
+        .filter_map(|(name, sides)| {
+            let status = match sides {
+                (Some(a), Some(b)) if a.0 == b.0 && (a.1 == b.1 || !compare_tokens) => return None,
+                (Some(_), Some(_)) => DiffStatus::Changed,
+                (None, _) => DiffStatus::Added,
+                (_, None) => DiffStatus::Removed,
+            };
+            let tokens_before = sides.0.map_or(0, |(_, tokens)| tokens);
+            let tokens_after = sides.1.map_or(0, |(_, tokens)| tokens);
+            Some(DefinitionDiff {
+                name: name.to_string(),
+                status,
+                tokens_before,
+                tokens_after,
+                token_delta: delta(tokens_before, tokens_after),
+            })
+        })
+        .collect()
+}
+
+fn delta(before: u64, after: u64) -> i64 {
+    after as i64 - before as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server_analyzer::{
+        PromptTokenInfo, PromptsAnalysis, ResourcesAnalysis, ToolsAnalysis,
+    };
+    use crate::session_diff::ChangeKind;
+    use crate::tokenizer::TokenizerSpec;
+    use serde_json::json;
+
+    fn tool(
+        name: &str,
+        description: &str,
+        schema: serde_json::Value,
+        tokens: u64,
+    ) -> ToolTokenInfo {
+        ToolTokenInfo {
+            name: name.to_string(),
+            description: description.to_string(),
+            name_tokens: 1,
+            description_tokens: 1,
+            schema_tokens: tokens - 2,
+            total_tokens: tokens,
+            input_schema: Some(schema),
+            output_schema_tokens: 0,
+            output_schema: None,
+        }
+    }
+
+    fn analysis(
+        version: &str,
+        tools: Vec<ToolTokenInfo>,
+        prompts: &[(&str, u64)],
+    ) -> ServerAnalysis {
+        let prompts: Vec<PromptTokenInfo> = prompts
+            .iter()
+            .map(|&(name, total_tokens)| PromptTokenInfo {
+                name: name.to_string(),
+                description: None,
+                total_tokens,
+            })
+            .collect();
+        let tool_tokens: u64 = tools.iter().map(|t| t.total_tokens).sum();
+        let prompt_tokens: u64 = prompts.iter().map(|p| p.total_tokens).sum();
+        ServerAnalysis {
+            server_name: "files".to_string(),
+            server_version: version.to_string(),
+            protocol_version: "2024-11-05".to_string(),
+            tokenizer: TokenizerSpec::Heuristic,
+            total_context_tokens: tool_tokens + prompt_tokens,
+            tools: ToolsAnalysis {
+                count: tools.len() as u32,
+                total_tokens: tool_tokens,
+                tools,
+            },
+            prompts: PromptsAnalysis {
+                count: prompts.len() as u32,
+                total_tokens: prompt_tokens,
+                prompts,
+            },
+            resources: ResourcesAnalysis::default(),
+            lint: Default::default(),
+            token_breakdown: Default::default(),
+            analyzed_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_diff_analyses() {
+        let path = json!({"type": "object", "properties": {"path": {"type": "string"}}});
+        let before = analysis(
+            "1.0.0",
+            vec![
+                tool("read_file", "Read a file", path.clone(), 30),
+                tool("grep", "Search files", path.clone(), 20),
+                tool("list_dir", "List a directory", path.clone(), 25),
+            ],
+            &[("summarize", 10), ("explain", 8)],
+        );
+        let mut with_encoding = path.clone();
+        with_encoding["properties"]["encoding"] = json!({"type": "string"});
+        let after = analysis(
+            "1.1.0",
+            vec![
+                tool("read_file", "Read a file", with_encoding, 38),
+                tool("search", "Search files by content", path.clone(), 40),
+                tool("list_dir", "List the entries of a directory", path, 30),
+            ],
+            &[("summarize", 12), ("explain", 8)],
+        );
+
+        let diff = diff_analyses(&before, &after);
+        assert!(diff.has_differences());
+        assert!(!diff.tokenizer_changed());
+        assert_eq!(diff.before.server_version, "1.0.0");
+        assert_eq!(diff.after.server_version, "1.1.0");
+
+        let tools: Vec<(&str, DiffStatus, i64)> = diff
+            .tools
+            .iter()
+            .map(|t| (t.name.as_str(), t.status, t.token_delta))
+            .collect();
+        assert_eq!(
+            tools,
+            vec![
+                ("grep", DiffStatus::Removed, -20),
+                ("list_dir", DiffStatus::Changed, 5),
+                ("read_file", DiffStatus::Changed, 8),
+                ("search", DiffStatus::Added, 40),
+            ]
+        );
+        assert_eq!(
+            diff.tools[1].description_after.as_deref(),
+            Some("List the entries of a directory")
+        );
+        assert!(diff.tools[1].schema.is_empty());
+        assert!(diff.tools[2].description_before.is_none());
+        assert_eq!(diff.tools[2].schema[0].path, "$.properties.encoding");
+        assert_eq!(diff.tools[2].schema[0].kind, ChangeKind::Added);
+
+        assert_eq!(diff.prompts.len(), 1);
+        assert_eq!(diff.prompts[0].name, "summarize");
+        assert_eq!(diff.prompts[0].token_delta, 2);
+
+        let s = &diff.summary;
+        assert_eq!(
+            (
+                s.tools_added,
+                s.tools_removed,
+                s.tools_changed,
+                s.tools_unchanged
+            ),
+            (1, 1, 2, 0)
+        );
+        assert_eq!(s.token_delta, 108 + 20 - (75 + 18));
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let schema = json!({"type": "object"});
+        let a = analysis("1.0.0", vec![tool("echo", "Echo", schema, 10)], &[("p", 3)]);
+        let mut b = a.clone();
+        b.tokenizer = TokenizerSpec::Cl100kBase;
+        b.tools.tools[0].total_tokens = 12;
+        b.prompts.prompts[0].total_tokens = 4;
+
+        // A different tokenizer changes counts, not the surface
+        let diff = diff_analyses(&a, &b);
+        assert!(!diff.has_differences());
+        assert!(diff.tokenizer_changed());
+        assert!(diff.tools.is_empty());
+        assert_eq!(diff.summary.tools_unchanged, 1);
+    }
+}
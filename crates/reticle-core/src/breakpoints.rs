@@ -0,0 +1,373 @@
+//! Breakpoints for holding JSON-RPC messages in the proxy
+//!
+//! A breakpoint matches messages with a [`MessagePattern`] (method, tool
+//! name, direction, or a JSONPath expression). When a message matches, the proxy holds it instead
+//! of forwarding it until a resume command arrives from the GUI or the
+//! daemon. Held messages can be released unchanged, replaced with an edited
+//! version, or dropped.
+//!
+//! # Breakpoint specs
+//!
+//! Breakpoints are written as comma-separated `key=value` pairs:
+//!
+//! ```text
+//! tools/call                                  # bare value = method
+//! method=tools/call,tool=read_file            # a specific tool
+//! direction=out,method=tools/list             # server responses only
+//! path=$.params.arguments.path                # any message with this field
+//! path=$.params.arguments.path,value="/etc"   # field with a given JSON value
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+use crate::matcher::{MatchError, MessagePattern};
+use crate::protocol::Direction;
+
+/// A single breakpoint; all configured conditions must match
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    /// Breakpoint ID (reported back when the breakpoint is hit)
+    pub id: String,
+    /// Conditions a message must meet to be held
+    #[serde(flatten)]
+    pub pattern: MessagePattern,
+}
+
+impl Breakpoint {
+    /// Parse a breakpoint from its command-line spec
+    pub fn parse(id: impl Into<String>, spec: &str) -> Result<Self, BreakpointError> {
+        if spec.trim().is_empty() {
+            return Err(BreakpointError::InvalidSpec(
+                "breakpoint spec is empty".to_string(),
+            ));
+        }
+
+        Ok(Breakpoint {
+            id: id.into(),
+            pattern: MessagePattern::parse_spec(spec)?,
+        })
+    }
+
+    /// Check whether this breakpoint matches a message
+    pub fn matches(&self, message: &Value, direction: Direction) -> bool {
+        self.pattern.matches(message, direction)
+    }
+}
+
+/// A set of breakpoints checked against every message
+#[derive(Debug, Clone, Default)]
+pub struct BreakpointSet {
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl BreakpointSet {
+    /// Create an empty breakpoint set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a list of command-line specs, numbering them `bp-1`, `bp-2`, ...
+    pub fn from_specs<S: AsRef<str>>(specs: &[S]) -> Result<Self, BreakpointError> {
+        let breakpoints = specs
+            .iter()
+            .enumerate()
+            .map(|(i, spec)| Breakpoint::parse(format!("bp-{}", i + 1), spec.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { breakpoints })
+    }
+
+    /// Add a breakpoint
+    pub fn add(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    /// Remove a breakpoint by ID, returning whether it existed
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.breakpoints.len();
+        self.breakpoints.retain(|b| b.id != id);
+        self.breakpoints.len() != before
+    }
+
+    /// Whether no breakpoints are configured
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty()
+    }
+
+    /// All configured breakpoints
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Find the first breakpoint matching a message
+    pub fn find_match(&self, message: &Value, direction: Direction) -> Option<&Breakpoint> {
+        self.breakpoints
+            .iter()
+            .find(|b| b.matches(message, direction))
+    }
+}
+
+/// A message held at a breakpoint, waiting to be resumed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldMessage {
+    /// Hold ID (the ID of the log entry for the held message)
+    pub hold_id: String,
+    /// Session the message belongs to
+    pub session_id: String,
+    /// ID of the breakpoint that matched
+    pub breakpoint_id: String,
+    /// Direction the message was travelling
+    pub direction: Direction,
+    /// The held message as originally received
+    pub content: String,
+    /// JSON-RPC method (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// When the message was held (microseconds since UNIX_EPOCH)
+    pub held_at: u64,
+}
+
+impl HeldMessage {
+    /// Create a held message record for a matched breakpoint
+    pub fn new(
+        hold_id: String,
+        session_id: String,
+        breakpoint: &Breakpoint,
+        direction: Direction,
+        content: &Value,
+    ) -> Self {
+        let held_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+
+        Self {
+            hold_id,
+            session_id,
+            breakpoint_id: breakpoint.id.clone(),
+            direction,
+            content: serde_json::to_string(content).unwrap_or_default(),
+            method: content
+                .get("method")
+                .and_then(|m| m.as_str())
+                .map(String::from),
+            held_at,
+        }
+    }
+}
+
+/// What to do with a held message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ResumeAction {
+    /// Forward the message unchanged
+    Continue,
+    /// Forward an edited message instead of the original
+    Edit { message: String },
+    /// Discard the message
+    Drop,
+}
+
+/// A resume command from the GUI or daemon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResumeCommand {
+    /// Hold ID of the message to resume
+    pub hold_id: String,
+    /// What to do with it
+    #[serde(flatten)]
+    pub action: ResumeAction,
+}
+
+impl ResumeCommand {
+    /// Build a command from the wire fields used by the socket protocol
+    pub fn from_wire(hold_id: String, message: Option<String>, drop: bool) -> Self {
+        let action = if drop {
+            ResumeAction::Drop
+        } else if let Some(message) = message {
+            ResumeAction::Edit { message }
+        } else {
+            ResumeAction::Continue
+        };
+        Self { hold_id, action }
+    }
+}
+
+/// Receiver for resume commands
+pub type ResumeReceiver = mpsc::Receiver<ResumeCommand>;
+
+/// How a held message was released
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseOutcome {
+    /// Forwarded unchanged
+    Continued,
+    /// Forwarded after being edited
+    Edited,
+    /// Discarded
+    Dropped,
+    /// Forwarded unchanged because nobody resumed it in time
+    TimedOut,
+}
+
+impl std::fmt::Display for ReleaseOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReleaseOutcome::Continued => write!(f, "continued"),
+            ReleaseOutcome::Edited => write!(f, "edited"),
+            ReleaseOutcome::Dropped => write!(f, "dropped"),
+            ReleaseOutcome::TimedOut => write!(f, "timed_out"),
+        }
+    }
+}
+
+/// Event describing the release of a held message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakpointRelease {
+    pub session_id: String,
+    pub hold_id: String,
+    pub outcome: ReleaseOutcome,
+}
+
+/// Breakpoint errors
+#[derive(Debug, thiserror::Error)]
+pub enum BreakpointError {
+    #[error("Invalid breakpoint: {0}")]
+    InvalidSpec(String),
+
+    #[error(transparent)]
+    Pattern(#[from] MatchError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_call(name: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": {"path": "/etc/hosts"}}
+        })
+    }
+
+    #[test]
+    fn test_parse_bare_method() {
+        let bp = Breakpoint::parse("bp-1", "tools/list").unwrap();
+        assert_eq!(bp.pattern.method.as_deref(), Some("tools/list"));
+        assert!(bp.pattern.tool.is_none());
+    }
+
+    #[test]
+    fn test_parse_full_spec() {
+        let bp = Breakpoint::parse(
+            "bp-1",
+            "method=tools/call,tool=read_file,direction=in,path=$.params.arguments.path",
+        )
+        .unwrap();
+        assert_eq!(bp.pattern.method.as_deref(), Some("tools/call"));
+        assert_eq!(bp.pattern.tool.as_deref(), Some("read_file"));
+        assert_eq!(bp.pattern.direction, Some(Direction::In));
+        assert_eq!(bp.pattern.path.as_deref(), Some("$.params.arguments.path"));
+    }
+
+    #[test]
+    fn test_parse_value_with_commas() {
+        let bp = Breakpoint::parse("bp-1", "path=params.ids,value=[1,2]").unwrap();
+        assert_eq!(bp.pattern.value, Some(json!([1, 2])));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Breakpoint::parse("bp-1", "").is_err());
+        assert!(Breakpoint::parse("bp-1", "direction=sideways").is_err());
+        assert!(Breakpoint::parse("bp-1", "color=red").is_err());
+        assert!(Breakpoint::parse("bp-1", "value=1").is_err());
+        assert!(Breakpoint::parse("bp-1", "path=$.a[").is_err());
+    }
+
+    #[test]
+    fn test_match_tool() {
+        let bp = Breakpoint::parse("bp-1", "tool=read_file").unwrap();
+        assert!(bp.matches(&tool_call("read_file"), Direction::In));
+        assert!(!bp.matches(&tool_call("write_file"), Direction::In));
+        assert!(!bp.matches(&json!({"method": "tools/list"}), Direction::In));
+    }
+
+    #[test]
+    fn test_match_direction() {
+        let bp = Breakpoint::parse("bp-1", "direction=out").unwrap();
+        assert!(bp.matches(&json!({"id": 1, "result": {}}), Direction::Out));
+        assert!(!bp.matches(&json!({"id": 1, "method": "ping"}), Direction::In));
+    }
+
+    #[test]
+    fn test_match_path_and_value() {
+        let exists = Breakpoint::parse("bp-1", "path=$.params.arguments.path").unwrap();
+        assert!(exists.matches(&tool_call("read_file"), Direction::In));
+        assert!(!exists.matches(&json!({"method": "ping"}), Direction::In));
+
+        let equals =
+            Breakpoint::parse("bp-2", "path=params.arguments.path,value=/etc/hosts").unwrap();
+        assert!(equals.matches(&tool_call("read_file"), Direction::In));
+
+        let differs = Breakpoint::parse("bp-3", "path=params.arguments.path,value=/tmp").unwrap();
+        assert!(!differs.matches(&tool_call("read_file"), Direction::In));
+    }
+
+    #[test]
+    fn test_breakpoint_set() {
+        let mut set = BreakpointSet::from_specs(&["tools/list", "tool=read_file"]).unwrap();
+        assert_eq!(set.breakpoints().len(), 2);
+
+        let hit = set
+            .find_match(&tool_call("read_file"), Direction::In)
+            .unwrap();
+        assert_eq!(hit.id, "bp-2");
+        assert!(set
+            .find_match(&json!({"method": "ping"}), Direction::In)
+            .is_none());
+
+        assert!(set.remove("bp-2"));
+        assert!(!set.remove("bp-2"));
+        assert!(set
+            .find_match(&tool_call("read_file"), Direction::In)
+            .is_none());
+    }
+
+    #[test]
+    fn test_resume_command_from_wire() {
+        let cont = ResumeCommand::from_wire("log-1".to_string(), None, false);
+        assert_eq!(cont.action, ResumeAction::Continue);
+
+        let edit = ResumeCommand::from_wire("log-1".to_string(), Some("{}".to_string()), false);
+        assert_eq!(
+            edit.action,
+            ResumeAction::Edit {
+                message: "{}".to_string()
+            }
+        );
+
+        let drop = ResumeCommand::from_wire("log-1".to_string(), Some("{}".to_string()), true);
+        assert_eq!(drop.action, ResumeAction::Drop);
+    }
+
+    #[test]
+    fn test_held_message() {
+        let bp = Breakpoint::parse("bp-1", "tools/call").unwrap();
+        let held = HeldMessage::new(
+            "log-3".to_string(),
+            "session-1".to_string(),
+            &bp,
+            Direction::In,
+            &tool_call("read_file"),
+        );
+        assert_eq!(held.breakpoint_id, "bp-1");
+        assert_eq!(held.method.as_deref(), Some("tools/call"));
+        assert!(held.content.contains("read_file"));
+        assert!(held.held_at > 0);
+    }
+}
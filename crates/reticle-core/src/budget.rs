@@ -0,0 +1,519 @@
+//! Token budgets for LLM context
+//!
+//! A [`TokenBudget`] caps how many tokens a server may put into the LLM
+//! context: tool definitions, a single `tools/call` result, any single
+//! message, or the whole session. [`TokenCounter`] checks every message from
+//! the server against its session's budget and reports each
+//! [`BudgetViolation`] as a `token_budget_exceeded` event. Depending on the
+//! [`BudgetAction`], the proxy then also truncates or rejects the payload.
+//!
+//! Budgets are loaded from TOML or JSON. Top-level limits apply to every
+//! server; a `[servers.<name>]` table overrides them for one server:
+//!
+//! ```toml
+//! tool_definitions = 20000
+//! tool_result = 8000
+//! action = "truncate"
+//!
+//! [servers.github]
+//! tool_result = 4000
+//! action = "reject"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::protocol::McpMessage;
+use crate::token_counter::TokenCounter;
+use crate::tokenizer::{Tokenizer, TokenizerSpec};
+
+/// Event emitted through [`EventSink::emit_custom`](crate::events::EventSink::emit_custom)
+/// for every violation
+pub const BUDGET_EXCEEDED_EVENT: &str = "token_budget_exceeded";
+
+/// JSON-RPC error code for responses rejected by a budget
+pub const BUDGET_ERROR_CODE: i64 = -32000;
+
+/// Token limits for one server or session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenBudget {
+    /// Tokens in a `tools/list` result
+    pub tool_definitions: Option<u64>,
+    /// Tokens in a single `tools/call` result
+    pub tool_result: Option<u64>,
+    /// Tokens in any single message from the server
+    pub message: Option<u64>,
+    /// Tokens recorded for the whole session
+    pub session: Option<u64>,
+    /// What to do with an oversized payload (warn if unset)
+    pub action: Option<BudgetAction>,
+}
+
+/// What the proxy does when a message is over budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// Forward the message and emit a warning
+    #[default]
+    Warn,
+    /// Cut text content down to the limit
+    Truncate,
+    /// Answer with a JSON-RPC error instead
+    Reject,
+}
+
+/// Which limit was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetKind {
+    ToolDefinitions,
+    ToolResult,
+    Message,
+    Session,
+}
+
+impl fmt::Display for BudgetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BudgetKind::ToolDefinitions => "tool definitions",
+            BudgetKind::ToolResult => "tool result",
+            BudgetKind::Message => "message",
+            BudgetKind::Session => "session",
+        })
+    }
+}
+
+/// A limit exceeded by a message, reported as [`BUDGET_EXCEEDED_EVENT`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetViolation {
+    pub session_id: String,
+    /// Message that went over the limit
+    pub message_id: String,
+    pub kind: BudgetKind,
+    pub limit: u64,
+    pub tokens: u64,
+    /// What the proxy does about it
+    pub action: BudgetAction,
+    /// Tokenizer the tokens were counted with
+    pub tokenizer: TokenizerSpec,
+}
+
+impl TokenBudget {
+    /// Whether no limits are set
+    pub fn is_empty(&self) -> bool {
+        self.tool_definitions.is_none()
+            && self.tool_result.is_none()
+            && self.message.is_none()
+            && self.session.is_none()
+    }
+
+    pub fn action(&self) -> BudgetAction {
+        self.action.unwrap_or_default()
+    }
+
+    /// These limits, with any set in `other` taking precedence
+    pub fn merged(&self, other: &TokenBudget) -> TokenBudget {
+        TokenBudget {
+            tool_definitions: other.tool_definitions.or(self.tool_definitions),
+            tool_result: other.tool_result.or(self.tool_result),
+            message: other.message.or(self.message),
+            session: other.session.or(self.session),
+            action: other.action.or(self.action),
+        }
+    }
+
+    /// Check a message from the server against the per-message limits
+    ///
+    /// Tokens are counted the way they reach the LLM (see
+    /// [`TokenCounter::count_mcp_context_tokens_with`]), not as raw JSON.
+    /// Returns each exceeded limit with the token count.
+    pub fn check(
+        &self,
+        tokenizer: &dyn Tokenizer,
+        message: &McpMessage,
+    ) -> Vec<(BudgetKind, u64, u64)> {
+        if self.tool_definitions.is_none() && self.tool_result.is_none() && self.message.is_none() {
+            return Vec::new();
+        }
+
+        let tokens = TokenCounter::count_mcp_context_tokens_with(tokenizer, message);
+        let answers = match message {
+            McpMessage::Response { result, .. } => result.method(),
+            _ => None,
+        };
+        let limits = [
+            (
+                BudgetKind::ToolDefinitions,
+                self.tool_definitions
+                    .filter(|_| answers == Some("tools/list")),
+            ),
+            (
+                BudgetKind::ToolResult,
+                self.tool_result.filter(|_| answers == Some("tools/call")),
+            ),
+            (BudgetKind::Message, self.message),
+        ];
+
+        limits
+            .into_iter()
+            .filter_map(|(kind, limit)| {
+                limit
+                    .filter(|&limit| tokens > limit)
+                    .map(|l| (kind, l, tokens))
+            })
+            .collect()
+    }
+
+    /// The message to forward instead of one that broke a per-message limit
+    ///
+    /// Returns `None` when the message should go through unchanged: the
+    /// action is [`BudgetAction::Warn`] or only the session limit was hit.
+    /// Truncation shortens the `text` of `content` or `contents` items; a
+    /// message with no text to shorten is rejected instead.
+    pub fn enforce(
+        &self,
+        tokenizer: &dyn Tokenizer,
+        message: &Value,
+        violations: &[BudgetViolation],
+    ) -> Option<Value> {
+        let violation = violations
+            .iter()
+            .filter(|v| v.kind != BudgetKind::Session)
+            .min_by_key(|v| v.limit)?;
+
+        match self.action() {
+            BudgetAction::Warn => None,
+            BudgetAction::Truncate => truncate(tokenizer, message, violation.limit)
+                .or_else(|| Some(reject(message, violation))),
+            BudgetAction::Reject => Some(reject(message, violation)),
+        }
+    }
+}
+
+/// Budgets for every server, with per-server overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "BudgetFile")]
+pub struct BudgetConfig {
+    /// Limits for every server
+    #[serde(flatten)]
+    pub default: TokenBudget,
+    /// Overrides by server name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub servers: HashMap<String, TokenBudget>,
+}
+
+/// On-disk layout of [`BudgetConfig`]
+///
+/// Spelled out rather than flattened so misspelled limits are rejected.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BudgetFile {
+    tool_definitions: Option<u64>,
+    tool_result: Option<u64>,
+    message: Option<u64>,
+    session: Option<u64>,
+    action: Option<BudgetAction>,
+    servers: HashMap<String, TokenBudget>,
+}
+
+impl From<BudgetFile> for BudgetConfig {
+    fn from(file: BudgetFile) -> Self {
+        Self {
+            default: TokenBudget {
+                tool_definitions: file.tool_definitions,
+                tool_result: file.tool_result,
+                message: file.message,
+                session: file.session,
+                action: file.action,
+            },
+            servers: file.servers,
+        }
+    }
+}
+
+impl BudgetConfig {
+    /// Parse a budget file from TOML
+    pub fn from_toml(text: &str) -> Result<Self, BudgetError> {
+        let config: BudgetConfig =
+            toml::from_str(text).map_err(|e| BudgetError::Parse(e.to_string()))?;
+        config.validate()
+    }
+
+    /// Parse a budget file from JSON
+    pub fn from_json(text: &str) -> Result<Self, BudgetError> {
+        let config: BudgetConfig =
+            serde_json::from_str(text).map_err(|e| BudgetError::Parse(e.to_string()))?;
+        config.validate()
+    }
+
+    /// Load a budget file; `.json` files are parsed as JSON, anything else as TOML
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, BudgetError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&text),
+            _ => Self::from_toml(&text),
+        }
+    }
+
+    /// The budget that applies to a server
+    pub fn for_server(&self, server_name: &str) -> TokenBudget {
+        match self.servers.get(server_name) {
+            Some(overrides) => self.default.merged(overrides),
+            None => self.default.clone(),
+        }
+    }
+
+    fn validate(self) -> Result<Self, BudgetError> {
+        let budgets = std::iter::once(("(default)", &self.default))
+            .chain(self.servers.iter().map(|(name, b)| (name.as_str(), b)));
+        for (name, budget) in budgets {
+            let limits = [
+                budget.tool_definitions,
+                budget.tool_result,
+                budget.message,
+                budget.session,
+            ];
+            if limits.contains(&Some(0)) {
+                return Err(BudgetError::Invalid(format!(
+                    "limits for {name} must be greater than zero"
+                )));
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Errors that can occur when loading a budget file
+#[derive(Debug, thiserror::Error)]
+pub enum BudgetError {
+    #[error("Failed to read budget file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse budget file: {0}")]
+    Parse(String),
+
+    #[error("Invalid budget: {0}")]
+    Invalid(String),
+}
+
+/// The error response sent in place of a rejected message
+fn reject(message: &Value, violation: &BudgetViolation) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": message.get("id").cloned().unwrap_or(Value::Null),
+        "error": {
+            "code": BUDGET_ERROR_CODE,
+            "message": format!(
+                "Response exceeds the {} token budget ({} > {} tokens)",
+                violation.kind, violation.tokens, violation.limit
+            ),
+            "data": {
+                "kind": violation.kind,
+                "limit": violation.limit,
+                "tokens": violation.tokens,
+            }
+        }
+    })
+}
+
+/// Shorten text content to fit `limit` tokens, dropping items past the cut
+///
+/// Returns `None` if the message carries no text content.
+fn truncate(tokenizer: &dyn Tokenizer, message: &Value, limit: u64) -> Option<Value> {
+    let mut truncated = message.clone();
+    let result = truncated.get_mut("result")?;
+    let key = ["content", "contents"]
+        .into_iter()
+        .find(|key| result.get(*key).is_some_and(Value::is_array))?;
+    let items = result.get_mut(key)?.as_array_mut()?;
+    if !items
+        .iter()
+        .any(|item| item.get("text").is_some_and(Value::is_string))
+    {
+        return None;
+    }
+
+    let marker = format!("\n\n[Truncated by Reticle: over the {limit}-token budget]");
+    let mut remaining = limit.saturating_sub(tokenizer.count_tokens(&marker));
+    let mut keep = items.len();
+    for (i, item) in items.iter_mut().enumerate() {
+        let Some(text) = item.get("text").and_then(Value::as_str) else {
+            continue;
+        };
+        let tokens = tokenizer.count_tokens(text);
+        if tokens <= remaining {
+            remaining -= tokens;
+            continue;
+        }
+        let cut = format!("{}{marker}", prefix_within(tokenizer, text, remaining));
+        item["text"] = Value::String(cut);
+        keep = i + 1;
+        break;
+    }
+    items.truncate(keep);
+    Some(truncated)
+}
+
+/// The longest prefix of `text` that fits in `limit` tokens
+fn prefix_within<'a>(tokenizer: &dyn Tokenizer, text: &'a str, limit: u64) -> &'a str {
+    let boundaries: Vec<usize> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    // Binary search for the last boundary whose prefix fits
+    let (mut lo, mut hi) = (0, boundaries.len() - 1);
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if tokenizer.count_tokens(&text[..boundaries[mid]]) <= limit {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    &text[..boundaries[lo]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::HeuristicTokenizer;
+
+    fn tool_result(text: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "result": {"content": [{"type": "text", "text": text}, {"type": "text", "text": "more"}]}
+        })
+    }
+
+    fn violation(kind: BudgetKind, limit: u64, tokens: u64) -> BudgetViolation {
+        BudgetViolation {
+            session_id: "s1".to_string(),
+            message_id: "log-1".to_string(),
+            kind,
+            limit,
+            tokens,
+            action: BudgetAction::Warn,
+            tokenizer: TokenizerSpec::Heuristic,
+        }
+    }
+
+    #[test]
+    fn test_load_and_merge() {
+        let config = BudgetConfig::from_toml(
+            r#"
+            tool_definitions = 20000
+            tool_result = 8000
+            action = "truncate"
+
+            [servers.github]
+            tool_result = 4000
+            action = "reject"
+            "#,
+        )
+        .unwrap();
+
+        let github = config.for_server("github");
+        assert_eq!(github.tool_definitions, Some(20000));
+        assert_eq!(github.tool_result, Some(4000));
+        assert_eq!(github.action(), BudgetAction::Reject);
+        assert_eq!(config.for_server("other").action(), BudgetAction::Truncate);
+
+        let json = BudgetConfig::from_json(r#"{"message": 100, "servers": {"a": {"session": 5}}}"#)
+            .unwrap();
+        assert_eq!(json.for_server("a").session, Some(5));
+        assert_eq!(json.for_server("a").action(), BudgetAction::Warn);
+
+        assert!(BudgetConfig::from_toml("tool_result = 0").is_err());
+        assert!(BudgetConfig::from_toml("tool_results = 10").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let budget = TokenBudget {
+            tool_definitions: Some(5),
+            tool_result: Some(3),
+            ..Default::default()
+        };
+        let tokenizer = HeuristicTokenizer;
+
+        let big = tool_result("one two three four five six");
+        let kinds: Vec<BudgetKind> = budget
+            .check(&tokenizer, &McpMessage::parse(&big))
+            .into_iter()
+            .map(|(kind, _, _)| kind)
+            .collect();
+        assert_eq!(kinds, vec![BudgetKind::ToolResult]);
+
+        assert!(budget
+            .check(&tokenizer, &McpMessage::parse(&tool_result("ok")))
+            .is_empty());
+
+        let tools = json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": [
+            {"name": "search", "description": "Search the whole web for anything at all"}
+        ]}});
+        assert_eq!(
+            budget.check(&tokenizer, &McpMessage::parse(&tools))[0].0,
+            BudgetKind::ToolDefinitions
+        );
+    }
+
+    #[test]
+    fn test_enforce() {
+        let tokenizer = HeuristicTokenizer;
+        let message = tool_result(&"word ".repeat(200));
+        let violations = [violation(BudgetKind::ToolResult, 50, 201)];
+
+        let warn = TokenBudget::default();
+        assert!(warn.enforce(&tokenizer, &message, &violations).is_none());
+
+        let reject = TokenBudget {
+            action: Some(BudgetAction::Reject),
+            ..Default::default()
+        };
+        let rejected = reject.enforce(&tokenizer, &message, &violations).unwrap();
+        assert_eq!(rejected["id"], 7);
+        assert_eq!(rejected["error"]["code"], BUDGET_ERROR_CODE);
+        assert_eq!(rejected["error"]["data"]["kind"], "tool_result");
+
+        // The session limit alone never blocks a message
+        let session = [violation(BudgetKind::Session, 10, 11)];
+        assert!(reject.enforce(&tokenizer, &message, &session).is_none());
+
+        let truncate = TokenBudget {
+            action: Some(BudgetAction::Truncate),
+            ..Default::default()
+        };
+        let cut = truncate.enforce(&tokenizer, &message, &violations).unwrap();
+        let content = cut["result"]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 1);
+        let text = content[0]["text"].as_str().unwrap();
+        assert!(text.ends_with("[Truncated by Reticle: over the 50-token budget]"));
+        assert!(
+            TokenCounter::count_mcp_context_tokens_with(&tokenizer, &McpMessage::parse(&cut)) <= 50
+        );
+
+        // Nothing to shorten in tool definitions, so they are rejected
+        let tools = json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": []}});
+        let defs = [violation(BudgetKind::ToolDefinitions, 5, 9)];
+        let replaced = truncate.enforce(&tokenizer, &tools, &defs).unwrap();
+        assert!(replaced.get("error").is_some());
+    }
+
+    #[test]
+    fn test_prefix_within() {
+        let tokenizer = HeuristicTokenizer;
+        assert_eq!(
+            prefix_within(&tokenizer, "alpha beta gamma", 3),
+            "alpha beta "
+        );
+        assert_eq!(prefix_within(&tokenizer, "héllo wörld", 0), "");
+        assert_eq!(prefix_within(&tokenizer, "short", 10), "short");
+    }
+}
@@ -0,0 +1,696 @@
+//! Fault injection for chaos testing MCP clients
+//!
+//! A chaos profile is a list of faults, each with a probability and the
+//! request methods it applies to (all methods if none are listed). Faults are
+//! rolled once per request as it reaches the proxy:
+//!
+//! - `latency` holds the request for a random time between `min_ms` and `max_ms`
+//! - `error` answers the request with a synthetic JSON-RPC error instead of
+//!   forwarding it; the code is picked from `codes`
+//! - `drop` forwards the request but swallows the server's response
+//! - `truncate` cuts the response line to `keep` bytes (half by default)
+//! - `kill` terminates the server process
+//!
+//! Set `seed` to make a run reproducible: the same traffic then sees the
+//! same faults.
+//!
+//! # Example
+//!
+//! ```toml
+//! seed = 42
+//!
+//! [[faults]]
+//! name = "slow tools"
+//! methods = ["tools/call"]
+//! probability = 0.3
+//! action = "latency"
+//! min_ms = 200
+//! max_ms = 2000
+//!
+//! [[faults]]
+//! methods = ["tools/call", "resources/read"]
+//! probability = 0.05
+//! action = "error"
+//! codes = [-32603, -32000]
+//! message = "Upstream unavailable"
+//!
+//! [[faults]]
+//! methods = ["tools/list"]
+//! probability = 0.1
+//! action = "truncate"
+//! keep = 64
+//! ```
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::protocol::{Direction, JsonRpcError, JsonRpcResponse};
+
+/// Pending response faults beyond this many are forgotten
+const MAX_PENDING_FAULTS: usize = 1024;
+
+/// Error code used when an `error` fault lists no codes (JSON-RPC internal error)
+const DEFAULT_ERROR_CODE: i64 = -32603;
+
+/// What a fault does when it fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum FaultAction {
+    /// Hold the request before forwarding it
+    Latency {
+        #[serde(default)]
+        min_ms: u64,
+        max_ms: u64,
+    },
+    /// Answer the request with a JSON-RPC error instead of forwarding it
+    Error {
+        #[serde(default)]
+        codes: Vec<i64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// Swallow the response to the request
+    Drop,
+    /// Cut the response line short
+    Truncate {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        keep: Option<usize>,
+    },
+    /// Kill the server process
+    Kill,
+}
+
+impl FaultAction {
+    /// Short name used in fault log entries
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FaultAction::Latency { .. } => "latency",
+            FaultAction::Error { .. } => "error",
+            FaultAction::Drop => "drop",
+            FaultAction::Truncate { .. } => "truncate",
+            FaultAction::Kill => "kill",
+        }
+    }
+
+    /// Whether the fault acts on the response rather than the request
+    fn on_response(&self) -> bool {
+        matches!(self, FaultAction::Drop | FaultAction::Truncate { .. })
+    }
+
+    /// Whether the fault needs a request id (a response to answer or alter)
+    fn needs_id(&self) -> bool {
+        matches!(
+            self,
+            FaultAction::Error { .. } | FaultAction::Drop | FaultAction::Truncate { .. }
+        )
+    }
+}
+
+/// A single fault in a chaos profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fault {
+    /// Name shown in fault log entries (defaults to `fault-N`)
+    #[serde(default)]
+    pub name: String,
+    /// Request methods the fault applies to; empty means all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<String>,
+    /// Chance of firing for each matching request, from 0.0 to 1.0
+    pub probability: f64,
+    /// What the fault does
+    #[serde(flatten)]
+    pub action: FaultAction,
+}
+
+impl Fault {
+    fn applies_to(&self, method: &str) -> bool {
+        self.methods.is_empty() || self.methods.iter().any(|m| m == method)
+    }
+
+    fn validate(&self) -> Result<(), ChaosError> {
+        let invalid = |reason: &str| ChaosError::InvalidFault {
+            name: self.name.clone(),
+            reason: reason.to_string(),
+        };
+
+        if !(0.0..=1.0).contains(&self.probability) {
+            return Err(invalid("probability must be between 0.0 and 1.0"));
+        }
+        if let FaultAction::Latency { min_ms, max_ms } = self.action {
+            if min_ms > max_ms {
+                return Err(invalid("min_ms must not exceed max_ms"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// On-disk chaos profile format
+#[derive(Debug, Default, Deserialize)]
+struct ChaosProfile {
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    faults: Vec<Fault>,
+}
+
+/// The effect of a fault that fired
+#[derive(Debug, Clone, PartialEq)]
+pub enum Injected {
+    /// Hold the request this long before forwarding it
+    Latency(Duration),
+    /// Send this synthetic response to the client instead of forwarding the request
+    Error(Value),
+    /// Kill the server instead of forwarding the request
+    Kill,
+    /// The response was swallowed
+    Drop,
+    /// Forward this cut-down line in place of the response
+    Truncate(String),
+}
+
+/// A fault that fired, with the request it fired on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Injection {
+    /// Name of the fault in the profile
+    pub fault: String,
+    /// Method of the affected request
+    pub method: String,
+    /// Id of the affected request, if it had one
+    pub id: Option<Value>,
+    /// What the proxy has to do
+    pub injected: Injected,
+}
+
+impl Injection {
+    /// The direction of the traffic the fault acted on
+    pub fn direction(&self) -> Direction {
+        match self.injected {
+            Injected::Latency(_) | Injected::Kill => Direction::In,
+            Injected::Error(_) | Injected::Drop | Injected::Truncate(_) => Direction::Out,
+        }
+    }
+
+    /// The delay to add before forwarding, if any
+    pub fn delay(&self) -> Duration {
+        match self.injected {
+            Injected::Latency(delay) => delay,
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// JSON description used as the content of the fault's log entry
+    pub fn describe(&self) -> Value {
+        let mut description = json!({
+            "chaos": self.kind(),
+            "fault": self.fault,
+            "method": self.method,
+        });
+        if let Some(ref id) = self.id {
+            description["id"] = id.clone();
+        }
+        match self.injected {
+            Injected::Latency(delay) => description["delay_ms"] = json!(delay.as_millis() as u64),
+            Injected::Error(ref response) => description["response"] = response.clone(),
+            Injected::Truncate(ref line) => description["kept_bytes"] = json!(line.len()),
+            Injected::Kill | Injected::Drop => {}
+        }
+        description
+    }
+
+    fn kind(&self) -> &'static str {
+        match self.injected {
+            Injected::Latency(_) => "latency",
+            Injected::Error(_) => "error",
+            Injected::Kill => "kill",
+            Injected::Drop => "drop",
+            Injected::Truncate(_) => "truncate",
+        }
+    }
+}
+
+/// A response fault waiting for the server to answer
+#[derive(Debug)]
+struct PendingFault {
+    fault: String,
+    method: String,
+    action: FaultAction,
+}
+
+/// Rolls the faults of a chaos profile for one proxy session
+#[derive(Debug)]
+pub struct ChaosEngine {
+    faults: Vec<Fault>,
+    rng: Mutex<StdRng>,
+    pending: Mutex<HashMap<String, PendingFault>>,
+}
+
+impl Default for ChaosEngine {
+    fn default() -> Self {
+        Self {
+            faults: Vec::new(),
+            rng: Mutex::new(StdRng::from_entropy()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl ChaosEngine {
+    /// Create an engine from a list of faults
+    ///
+    /// With a `seed`, the same sequence of requests always sees the same faults.
+    pub fn new(mut faults: Vec<Fault>, seed: Option<u64>) -> Result<Self, ChaosError> {
+        for (i, fault) in faults.iter_mut().enumerate() {
+            if fault.name.is_empty() {
+                fault.name = format!("fault-{}", i + 1);
+            }
+            fault.validate()?;
+        }
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(Self {
+            faults,
+            rng: Mutex::new(rng),
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Parse a chaos profile from TOML
+    pub fn from_toml(text: &str) -> Result<Self, ChaosError> {
+        let profile: ChaosProfile =
+            toml::from_str(text).map_err(|e| ChaosError::Parse(e.to_string()))?;
+        Self::new(profile.faults, profile.seed)
+    }
+
+    /// Parse a chaos profile from JSON (`{"seed": 1, "faults": [...]}`)
+    pub fn from_json(text: &str) -> Result<Self, ChaosError> {
+        let profile: ChaosProfile =
+            serde_json::from_str(text).map_err(|e| ChaosError::Parse(e.to_string()))?;
+        Self::new(profile.faults, profile.seed)
+    }
+
+    /// Load a chaos profile; `.json` files are parsed as JSON, anything else as TOML
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ChaosError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&text),
+            _ => Self::from_toml(&text),
+        }
+    }
+
+    /// Whether no faults are configured
+    pub fn is_empty(&self) -> bool {
+        self.faults.is_empty()
+    }
+
+    /// All configured faults
+    pub fn faults(&self) -> &[Fault] {
+        &self.faults
+    }
+
+    /// Roll the faults for a request on its way to the server
+    ///
+    /// Returns the faults that act now: latency, a synthetic error, or a
+    /// kill (which is always last). Response faults that fire are kept
+    /// until [`on_response`](Self::on_response) sees the matching id.
+    /// Messages that are not requests are never affected.
+    pub fn on_request(&self, message: &Value) -> Vec<Injection> {
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            return Vec::new();
+        };
+        let id = message.get("id").filter(|id| !id.is_null());
+        let Ok(mut rng) = self.rng.lock() else {
+            return Vec::new();
+        };
+
+        let mut injections = Vec::new();
+        let mut answered = false;
+        let mut response_fault = None;
+        for fault in &self.faults {
+            if !fault.applies_to(method) || (id.is_none() && fault.action.needs_id()) {
+                continue;
+            }
+            if !rng.gen_bool(fault.probability) {
+                continue;
+            }
+
+            let injection = |injected| Injection {
+                fault: fault.name.clone(),
+                method: method.to_string(),
+                id: id.cloned(),
+                injected,
+            };
+            match fault.action {
+                FaultAction::Latency { min_ms, max_ms } => {
+                    let delay = Duration::from_millis(rng.gen_range(min_ms..=max_ms));
+                    injections.push(injection(Injected::Latency(delay)));
+                }
+                FaultAction::Error {
+                    ref codes,
+                    ref message,
+                } if !answered => {
+                    let code = codes.choose(&mut *rng).copied();
+                    let response = error_response(id, code, message.as_deref());
+                    injections.push(injection(Injected::Error(response)));
+                    answered = true;
+                }
+                FaultAction::Kill => {
+                    injections.push(injection(Injected::Kill));
+                    return injections;
+                }
+                ref action if action.on_response() && response_fault.is_none() => {
+                    response_fault = Some(fault);
+                }
+                _ => {}
+            }
+        }
+
+        if let (Some(fault), Some(id), false) = (response_fault, id, answered) {
+            if let Ok(mut pending) = self.pending.lock() {
+                if pending.len() >= MAX_PENDING_FAULTS {
+                    pending.clear();
+                }
+                pending.insert(
+                    id.to_string(),
+                    PendingFault {
+                        fault: fault.name.clone(),
+                        method: method.to_string(),
+                        action: fault.action.clone(),
+                    },
+                );
+            }
+        }
+        injections
+    }
+
+    /// Check a server message for a pending response fault
+    ///
+    /// `line` is the message as it would be forwarded. Returns the drop or
+    /// truncation to apply, if one was rolled for the request it answers.
+    pub fn on_response(&self, line: &str, message: &Value) -> Option<Injection> {
+        if message.get("method").is_some() {
+            return None;
+        }
+        let id = message.get("id")?;
+        let pending = self.pending.lock().ok()?.remove(&id.to_string())?;
+
+        let injected = match pending.action {
+            FaultAction::Truncate { keep } => Injected::Truncate(truncate_line(line, keep)),
+            _ => Injected::Drop,
+        };
+        Some(Injection {
+            fault: pending.fault,
+            method: pending.method,
+            id: Some(id.clone()),
+            injected,
+        })
+    }
+
+    /// Apply pending response faults to the JSON `data:` lines of an SSE chunk
+    ///
+    /// Dropped responses lose their `data:` line and truncated ones are cut
+    /// short. Returns the chunk to forward and the faults that fired.
+    pub fn on_sse_chunk(&self, chunk: &str) -> (String, Vec<Injection>) {
+        let mut text = String::with_capacity(chunk.len());
+        let mut injections = Vec::new();
+
+        for line in chunk.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\r', '\n']);
+            let ending = &line[body.len()..];
+            let data = body.strip_prefix("data:").map(str::trim_start);
+            let injection = data.and_then(|data| {
+                let message = serde_json::from_str::<Value>(data).ok()?;
+                self.on_response(data, &message)
+            });
+
+            match injection {
+                None => text.push_str(line),
+                Some(injection) => {
+                    if let Injected::Truncate(ref cut) = injection.injected {
+                        text.push_str("data: ");
+                        text.push_str(cut);
+                        text.push_str(ending);
+                    }
+                    injections.push(injection);
+                }
+            }
+        }
+
+        (text, injections)
+    }
+}
+
+/// Build the synthetic error response for a request
+fn error_response(id: Option<&Value>, code: Option<i64>, message: Option<&str>) -> Value {
+    let code = code.unwrap_or(DEFAULT_ERROR_CODE);
+    let response = JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id: id.cloned().unwrap_or(Value::Null),
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message: message.map_or_else(|| default_error_message(code), str::to_string),
+            data: None,
+        }),
+    };
+    serde_json::to_value(response).unwrap_or_default()
+}
+
+/// The standard JSON-RPC message for an error code
+fn default_error_message(code: i64) -> String {
+    match code {
+        -32700 => "Parse error",
+        -32600 => "Invalid Request",
+        -32601 => "Method not found",
+        -32602 => "Invalid params",
+        -32603 => "Internal error",
+        _ => "Server error",
+    }
+    .to_string()
+}
+
+/// Cut a line to `keep` bytes (half by default), always losing at least one byte
+fn truncate_line(line: &str, keep: Option<usize>) -> String {
+    let mut end = keep
+        .unwrap_or(line.len() / 2)
+        .min(line.len().saturating_sub(1));
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    line[..end].to_string()
+}
+
+/// Errors that can occur when loading a chaos profile
+#[derive(Debug, thiserror::Error)]
+pub enum ChaosError {
+    #[error("Failed to read chaos profile: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse chaos profile: {0}")]
+    Parse(String),
+
+    #[error("Invalid fault '{name}': {reason}")]
+    InvalidFault { name: String, reason: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(id: u64) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": "search", "arguments": {}}
+        })
+    }
+
+    #[test]
+    fn test_load_toml() {
+        let engine = ChaosEngine::from_toml(
+            r#"
+            seed = 7
+
+            [[faults]]
+            methods = ["tools/call"]
+            probability = 0.5
+            action = "latency"
+            min_ms = 10
+            max_ms = 20
+
+            [[faults]]
+            name = "flaky"
+            probability = 0.1
+            action = "error"
+            codes = [-32000]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(engine.faults().len(), 2);
+        assert_eq!(engine.faults()[0].name, "fault-1");
+        assert_eq!(
+            engine.faults()[0].action,
+            FaultAction::Latency {
+                min_ms: 10,
+                max_ms: 20
+            }
+        );
+        assert_eq!(engine.faults()[1].action.kind(), "error");
+    }
+
+    #[test]
+    fn test_invalid_faults() {
+        let bad_probability = "[[faults]]\nprobability = 1.5\naction = \"drop\"";
+        assert!(matches!(
+            ChaosEngine::from_toml(bad_probability),
+            Err(ChaosError::InvalidFault { .. })
+        ));
+
+        let bad_range =
+            "[[faults]]\nprobability = 1.0\naction = \"latency\"\nmin_ms = 50\nmax_ms = 10";
+        assert!(ChaosEngine::from_toml(bad_range).is_err());
+
+        let unknown = "[[faults]]\nprobability = 1.0\naction = \"explode\"";
+        assert!(matches!(
+            ChaosEngine::from_toml(unknown),
+            Err(ChaosError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_error_fault() {
+        let engine = ChaosEngine::from_json(
+            r#"{"faults": [{"methods": ["tools/call"], "probability": 1.0, "action": "error", "codes": [-32001], "message": "boom"}]}"#,
+        )
+        .unwrap();
+
+        let injections = engine.on_request(&call(3));
+        assert_eq!(injections.len(), 1);
+        let Injected::Error(ref response) = injections[0].injected else {
+            panic!("expected an error injection");
+        };
+        assert_eq!(response["id"], 3);
+        assert_eq!(response["error"]["code"], -32001);
+        assert_eq!(response["error"]["message"], "boom");
+        assert_eq!(injections[0].direction(), Direction::Out);
+        assert_eq!(injections[0].describe()["chaos"], "error");
+
+        // Other methods and notifications are left alone
+        assert!(engine
+            .on_request(&json!({"jsonrpc": "2.0", "id": 4, "method": "ping"}))
+            .is_empty());
+        assert!(engine
+            .on_request(&json!({"jsonrpc": "2.0", "method": "tools/call"}))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_drop_and_truncate_responses() {
+        let engine = ChaosEngine::from_toml(
+            r#"
+            [[faults]]
+            methods = ["tools/call"]
+            probability = 1.0
+            action = "drop"
+
+            [[faults]]
+            methods = ["tools/list"]
+            probability = 1.0
+            action = "truncate"
+            keep = 10
+            "#,
+        )
+        .unwrap();
+
+        assert!(engine.on_request(&call(1)).is_empty());
+        let response = json!({"jsonrpc": "2.0", "id": 1, "result": {}});
+        let line = response.to_string();
+        let injection = engine.on_response(&line, &response).unwrap();
+        assert_eq!(injection.injected, Injected::Drop);
+        assert_eq!(injection.method, "tools/call");
+        // The fault is used up once the response has been seen
+        assert!(engine.on_response(&line, &response).is_none());
+
+        let list = json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"});
+        assert!(engine.on_request(&list).is_empty());
+        let response = json!({"jsonrpc": "2.0", "id": 2, "result": {"tools": []}});
+        let chunk = format!("event: message\ndata: {response}\n\n");
+        let (text, injections) = engine.on_sse_chunk(&chunk);
+        assert_eq!(injections.len(), 1);
+        assert_eq!(
+            text,
+            format!("event: message\ndata: {}\n\n", &response.to_string()[..10])
+        );
+    }
+
+    #[test]
+    fn test_kill_and_latency() {
+        let engine = ChaosEngine::from_toml(
+            r#"
+            [[faults]]
+            probability = 1.0
+            action = "latency"
+            min_ms = 5
+            max_ms = 5
+
+            [[faults]]
+            probability = 1.0
+            action = "kill"
+
+            [[faults]]
+            probability = 1.0
+            action = "error"
+            "#,
+        )
+        .unwrap();
+
+        let injections = engine.on_request(&call(1));
+        assert_eq!(injections.len(), 2);
+        assert_eq!(injections[0].delay(), Duration::from_millis(5));
+        assert_eq!(injections[1].injected, Injected::Kill);
+        assert_eq!(injections[1].direction(), Direction::In);
+    }
+
+    #[test]
+    fn test_seed_is_reproducible() {
+        let profile = r#"
+            seed = 1234
+
+            [[faults]]
+            probability = 0.5
+            action = "latency"
+            min_ms = 0
+            max_ms = 1000
+        "#;
+        let rolls = |engine: ChaosEngine| {
+            (0..50)
+                .map(|id| engine.on_request(&call(id)).first().map(Injection::delay))
+                .collect::<Vec<_>>()
+        };
+
+        let first = rolls(ChaosEngine::from_toml(profile).unwrap());
+        let second = rolls(ChaosEngine::from_toml(profile).unwrap());
+        assert_eq!(first, second);
+        assert!(first.iter().any(Option::is_some));
+        assert!(first.iter().any(Option::is_none));
+    }
+
+    #[test]
+    fn test_truncate_line() {
+        assert_eq!(truncate_line("abcdef", None), "abc");
+        assert_eq!(truncate_line("abcdef", Some(100)), "abcde");
+        assert_eq!(truncate_line("aé", Some(2)), "a");
+    }
+}
@@ -7,6 +7,8 @@
 use async_trait::async_trait;
 use serde::Serialize;
 
+use crate::breakpoints::{BreakpointRelease, HeldMessage};
+use crate::protocol::conformance::SessionConformance;
 use crate::protocol::LogEntry;
 use crate::session_recorder::RecordedSession;
 
@@ -41,6 +43,21 @@ pub trait EventSink: Send + Sync {
         event_name: &str,
         payload: &T,
     ) -> Result<(), String>;
+
+    /// Emit a breakpoint hit event (a message is being held by the proxy)
+    async fn emit_breakpoint_hit(&self, held: &HeldMessage) -> Result<(), String> {
+        self.emit_custom("breakpoint_hit", held).await
+    }
+
+    /// Emit a breakpoint released event (a held message was resumed or dropped)
+    async fn emit_breakpoint_released(&self, release: &BreakpointRelease) -> Result<(), String> {
+        self.emit_custom("breakpoint_released", release).await
+    }
+
+    /// Emit the conformance violations of a session as it ends
+    async fn emit_conformance_summary(&self, summary: &SessionConformance) -> Result<(), String> {
+        self.emit_custom("conformance_summary", summary).await
+    }
 }
 
 /// No-op event sink for testing or CLI mode without event emission
@@ -180,6 +197,44 @@ impl EventSink for StdoutEventSink {
         }
         Ok(())
     }
+
+    async fn emit_breakpoint_hit(&self, held: &HeldMessage) -> Result<(), String> {
+        if self.json_output {
+            return self.emit_custom("breakpoint_hit", held).await;
+        }
+        let direction = match held.direction {
+            crate::protocol::Direction::In => "→",
+            crate::protocol::Direction::Out => "←",
+        };
+        eprintln!(
+            "[{}] Breakpoint {} hit: {} {} (hold {})",
+            format_timestamp(held.held_at),
+            held.breakpoint_id,
+            direction,
+            held.method.as_deref().unwrap_or("-"),
+            held.hold_id
+        );
+        Ok(())
+    }
+
+    async fn emit_breakpoint_released(&self, release: &BreakpointRelease) -> Result<(), String> {
+        if self.json_output {
+            return self.emit_custom("breakpoint_released", release).await;
+        }
+        eprintln!(
+            "Breakpoint released: {} ({})",
+            release.hold_id, release.outcome
+        );
+        Ok(())
+    }
+
+    async fn emit_conformance_summary(&self, summary: &SessionConformance) -> Result<(), String> {
+        if self.json_output {
+            return self.emit_custom("conformance_summary", summary).await;
+        }
+        eprintln!("Conformance: {summary}");
+        Ok(())
+    }
 }
 
 fn format_timestamp(micros: u64) -> String {
@@ -488,6 +543,7 @@ pub mod websocket {
                     "content": entry.content,
                     "method": entry.method,
                     "server_name": Some(&self.server_name),
+                    "request_log_id": entry.request_log_id,
                 }))
                 .await;
 
@@ -592,8 +648,8 @@ pub mod unix_socket {
     /// Event types sent over the socket (newline-delimited JSON)
     ///
     /// This enum is used for BOTH directions:
-    /// - CLI → GUI: SessionStarted, SessionEnded, Log
-    /// - GUI → CLI: InjectMessage
+    /// - CLI → GUI: SessionStarted, SessionEnded, Log, BreakpointHit, BreakpointReleased, Custom
+    /// - GUI → CLI: InjectMessage, ResumeMessage
     #[derive(Debug, Clone, Serialize, serde::Deserialize)]
     #[serde(tag = "type")]
     pub enum SocketEvent {
@@ -619,6 +675,43 @@ pub mod unix_socket {
             message_type: String,
             /// Estimated token count for this message
             token_count: u64,
+            /// Time since the request, for responses
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            duration_micros: Option<u64>,
+            /// Whether the message was edited or rewritten before forwarding
+            #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+            modified: bool,
+            /// The message as originally received, if it was modified
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            original: Option<String>,
+            /// The request a progress or cancellation notification refers to
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            request_log_id: Option<String>,
+        },
+        /// A message is being held at a breakpoint
+        #[serde(rename = "breakpoint_hit")]
+        BreakpointHit {
+            session_id: String,
+            hold_id: String,
+            breakpoint_id: String,
+            direction: String,
+            content: String,
+            method: Option<String>,
+            server_name: String,
+        },
+        /// A held message was released or dropped
+        #[serde(rename = "breakpoint_released")]
+        BreakpointReleased {
+            session_id: String,
+            hold_id: String,
+            /// One of: continued, edited, dropped, timed_out
+            outcome: String,
+        },
+        /// Any other event, such as a token budget warning
+        #[serde(rename = "custom")]
+        Custom {
+            event_name: String,
+            payload: serde_json::Value,
         },
 
         // === GUI → CLI events ===
@@ -630,10 +723,25 @@ pub mod unix_socket {
             /// The JSON-RPC message to inject
             message: String,
         },
+        /// Resume a message held at a breakpoint
+        #[serde(rename = "resume_message")]
+        ResumeMessage {
+            /// Target session ID
+            session_id: String,
+            /// Hold ID reported in `breakpoint_hit`
+            hold_id: String,
+            /// Replacement message (forward the original if absent)
+            #[serde(default)]
+            message: Option<String>,
+            /// Discard the held message instead of forwarding it
+            #[serde(default)]
+            drop: bool,
+        },
     }
 
+    use crate::breakpoints::{ResumeCommand, ResumeReceiver};
     use tokio::io::{AsyncBufReadExt, BufReader};
-    use tokio::net::unix::OwnedWriteHalf;
+    use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
     use tokio::sync::mpsc;
 
     /// Receiver for inject commands from the GUI
@@ -643,7 +751,7 @@ pub mod unix_socket {
     ///
     /// Supports bidirectional communication:
     /// - Outgoing: Session events, log entries (CLI → GUI)
-    /// - Incoming: Inject commands (GUI → CLI)
+    /// - Incoming: Inject and breakpoint resume commands (GUI → CLI)
     pub struct UnixSocketEventSink {
         /// Write half of the socket for sending events
         writer: Arc<Mutex<Option<OwnedWriteHalf>>>,
@@ -652,6 +760,10 @@ pub mod unix_socket {
         session_id: Arc<Mutex<Option<String>>>,
         /// Sender for inject commands received from GUI
         inject_tx: mpsc::Sender<String>,
+        /// Sender for breakpoint resume commands received from GUI
+        resume_tx: mpsc::Sender<ResumeCommand>,
+        /// Resume receiver, handed out once to the proxy
+        resume_rx: Mutex<Option<ResumeReceiver>>,
     }
 
     impl UnixSocketEventSink {
@@ -666,6 +778,7 @@ pub mod unix_socket {
         pub async fn new(server_name: String) -> (Self, InjectReceiver) {
             let socket_path = get_socket_path();
             let (inject_tx, inject_rx) = mpsc::channel(100);
+            let (resume_tx, resume_rx) = mpsc::channel(100);
 
             let sink = Self {
                 writer: Arc::new(Mutex::new(None)),
@@ -673,6 +786,8 @@ pub mod unix_socket {
                 socket_path: socket_path.clone(),
                 session_id: Arc::new(Mutex::new(None)),
                 inject_tx: inject_tx.clone(),
+                resume_tx,
+                resume_rx: Mutex::new(Some(resume_rx)),
             };
 
             // Try to connect immediately (silent on failure)
@@ -693,6 +808,13 @@ pub mod unix_socket {
             *self.session_id.lock().await = Some(session_id);
         }
 
+        /// Take the receiver for breakpoint resume commands
+        ///
+        /// Returns `None` if it has already been taken.
+        pub async fn take_resume_receiver(&self) -> Option<ResumeReceiver> {
+            self.resume_rx.lock().await.take()
+        }
+
         /// Setup bidirectional connection
         async fn setup_connection(&self, stream: UnixStream) {
             let (read_half, write_half) = stream.into_split();
@@ -700,41 +822,14 @@ pub mod unix_socket {
             // Store the write half for sending
             *self.writer.lock().await = Some(write_half);
 
-            // Start reader task for incoming inject commands
-            let inject_tx = self.inject_tx.clone();
-            let session_id = self.session_id.clone();
-            let writer = self.writer.clone();
-
-            tokio::spawn(async move {
-                let reader = BufReader::new(read_half);
-                let mut lines = reader.lines();
-
-                while let Ok(Some(line)) = lines.next_line().await {
-                    if line.is_empty() {
-                        continue;
-                    }
-
-                    // Parse the incoming event
-                    if let Ok(SocketEvent::InjectMessage {
-                        session_id: target_session,
-                        message,
-                    }) = serde_json::from_str::<SocketEvent>(&line)
-                    {
-                        // Check if this message is for our session
-                        let our_session = session_id.lock().await.clone();
-                        if our_session.as_ref() == Some(&target_session) {
-                            tracing::debug!("Received inject command for our session");
-                            if let Err(e) = inject_tx.send(message).await {
-                                tracing::warn!("Failed to forward inject command: {}", e);
-                            }
-                        }
-                    }
-                }
-
-                // Connection closed, clear the writer
-                tracing::debug!("Socket read connection closed");
-                *writer.lock().await = None;
-            });
+            // Start reader task for incoming commands
+            tokio::spawn(read_commands(
+                read_half,
+                self.inject_tx.clone(),
+                self.resume_tx.clone(),
+                self.session_id.clone(),
+                self.writer.clone(),
+            ));
         }
 
         /// Start background task to reconnect if disconnected
@@ -742,6 +837,7 @@ pub mod unix_socket {
             let writer = self.writer.clone();
             let socket_path = self.socket_path.clone();
             let inject_tx = self.inject_tx.clone();
+            let resume_tx = self.resume_tx.clone();
             let session_id = self.session_id.clone();
 
             tokio::spawn(async move {
@@ -761,33 +857,13 @@ pub mod unix_socket {
                         tracing::debug!("Reconnected to Reticle Hub");
 
                         // Start reader task
-                        let inject_tx = inject_tx.clone();
-                        let session_id = session_id.clone();
-                        let writer_for_close = writer.clone();
-
-                        tokio::spawn(async move {
-                            let reader = BufReader::new(read_half);
-                            let mut lines = reader.lines();
-
-                            while let Ok(Some(line)) = lines.next_line().await {
-                                if line.is_empty() {
-                                    continue;
-                                }
-
-                                if let Ok(SocketEvent::InjectMessage {
-                                    session_id: target_session,
-                                    message,
-                                }) = serde_json::from_str::<SocketEvent>(&line)
-                                {
-                                    let our_session = session_id.lock().await.clone();
-                                    if our_session.as_ref() == Some(&target_session) {
-                                        let _ = inject_tx.send(message).await;
-                                    }
-                                }
-                            }
-
-                            *writer_for_close.lock().await = None;
-                        });
+                        tokio::spawn(read_commands(
+                            read_half,
+                            inject_tx.clone(),
+                            resume_tx.clone(),
+                            session_id.clone(),
+                            writer.clone(),
+                        ));
                     }
                 }
             });
@@ -827,6 +903,62 @@ pub mod unix_socket {
         }
     }
 
+    /// Read commands sent by the Hub and route those addressed to our session
+    ///
+    /// Clears the shared writer when the connection closes so the reconnect
+    /// task can take over.
+    async fn read_commands(
+        read_half: OwnedReadHalf,
+        inject_tx: mpsc::Sender<String>,
+        resume_tx: mpsc::Sender<ResumeCommand>,
+        session_id: Arc<Mutex<Option<String>>>,
+        writer: Arc<Mutex<Option<OwnedWriteHalf>>>,
+    ) {
+        let reader = BufReader::new(read_half);
+        let mut lines = reader.lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.is_empty() {
+                continue;
+            }
+
+            let Ok(event) = serde_json::from_str::<SocketEvent>(&line) else {
+                continue;
+            };
+
+            // Check if this command is for our session
+            let our_session = session_id.lock().await.clone();
+            match event {
+                SocketEvent::InjectMessage {
+                    session_id: target_session,
+                    message,
+                } if our_session.as_ref() == Some(&target_session) => {
+                    tracing::debug!("Received inject command for our session");
+                    if let Err(e) = inject_tx.send(message).await {
+                        tracing::warn!("Failed to forward inject command: {}", e);
+                    }
+                }
+                SocketEvent::ResumeMessage {
+                    session_id: target_session,
+                    hold_id,
+                    message,
+                    drop,
+                } if our_session.as_ref() == Some(&target_session) => {
+                    tracing::debug!("Received resume command for {}", hold_id);
+                    let command = ResumeCommand::from_wire(hold_id, message, drop);
+                    if let Err(e) = resume_tx.send(command).await {
+                        tracing::warn!("Failed to forward resume command: {}", e);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Connection closed, clear the writer
+        tracing::debug!("Socket read connection closed");
+        *writer.lock().await = None;
+    }
+
     #[async_trait]
     impl EventSink for UnixSocketEventSink {
         async fn emit_log(&self, entry: &LogEntry) -> Result<(), String> {
@@ -839,6 +971,9 @@ pub mod unix_socket {
                 crate::protocol::MessageType::JsonRpc => "jsonrpc",
                 crate::protocol::MessageType::Raw => "raw",
                 crate::protocol::MessageType::Stderr => "stderr",
+                crate::protocol::MessageType::Fault => "fault",
+                crate::protocol::MessageType::Unanswered => "unanswered",
+                crate::protocol::MessageType::Violation => "violation",
             };
 
             let event = SocketEvent::Log {
@@ -851,6 +986,10 @@ pub mod unix_socket {
                 server_name: self.server_name.clone(),
                 message_type: message_type.to_string(),
                 token_count: entry.token_count,
+                duration_micros: entry.duration_micros,
+                modified: entry.modified,
+                original: entry.original.clone(),
+                request_log_id: entry.request_log_id.clone(),
             };
 
             self.send(&event).await
@@ -861,6 +1000,9 @@ pub mod unix_socket {
             session_id: &str,
             session_name: &str,
         ) -> Result<(), String> {
+            // Commands from the Hub are addressed to this session from now on
+            self.set_session_id(session_id.to_string()).await;
+
             let event = SocketEvent::SessionStarted {
                 session_id: session_id.to_string(),
                 session_name: session_name.to_string(),
@@ -890,11 +1032,42 @@ pub mod unix_socket {
 
         async fn emit_custom<T: Serialize + Send + Sync>(
             &self,
-            _event_name: &str,
-            _payload: &T,
+            event_name: &str,
+            payload: &T,
         ) -> Result<(), String> {
-            // Not needed for CLI bridge
-            Ok(())
+            let event = SocketEvent::Custom {
+                event_name: event_name.to_string(),
+                payload: serde_json::to_value(payload).map_err(|e| e.to_string())?,
+            };
+
+            self.send(&event).await
+        }
+
+        async fn emit_breakpoint_hit(&self, held: &HeldMessage) -> Result<(), String> {
+            let event = SocketEvent::BreakpointHit {
+                session_id: held.session_id.clone(),
+                hold_id: held.hold_id.clone(),
+                breakpoint_id: held.breakpoint_id.clone(),
+                direction: held.direction.to_string(),
+                content: held.content.clone(),
+                method: held.method.clone(),
+                server_name: self.server_name.clone(),
+            };
+
+            self.send(&event).await
+        }
+
+        async fn emit_breakpoint_released(
+            &self,
+            release: &BreakpointRelease,
+        ) -> Result<(), String> {
+            let event = SocketEvent::BreakpointReleased {
+                session_id: release.session_id.clone(),
+                hold_id: release.hold_id.clone(),
+                outcome: release.outcome.to_string(),
+            };
+
+            self.send(&event).await
         }
     }
 }
@@ -937,9 +1110,45 @@ pub mod unix_socket {
             server_name: String,
             message_type: String,
             token_count: u64,
+            #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+            modified: bool,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            original: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            request_log_id: Option<String>,
+        },
+        #[serde(rename = "breakpoint_hit")]
+        BreakpointHit {
+            session_id: String,
+            hold_id: String,
+            breakpoint_id: String,
+            direction: String,
+            content: String,
+            method: Option<String>,
+            server_name: String,
+        },
+        #[serde(rename = "breakpoint_released")]
+        BreakpointReleased {
+            session_id: String,
+            hold_id: String,
+            outcome: String,
+        },
+        #[serde(rename = "custom")]
+        Custom {
+            event_name: String,
+            payload: serde_json::Value,
         },
         #[serde(rename = "inject_message")]
         InjectMessage { session_id: String, message: String },
+        #[serde(rename = "resume_message")]
+        ResumeMessage {
+            session_id: String,
+            hold_id: String,
+            #[serde(default)]
+            message: Option<String>,
+            #[serde(default)]
+            drop: bool,
+        },
     }
 
     /// Receiver for inject commands (stub on Windows)
@@ -966,6 +1175,11 @@ pub mod unix_socket {
 
         /// Set session ID (no-op on Windows)
         pub async fn set_session_id(&self, _session_id: String) {}
+
+        /// Breakpoint resume commands are not available on Windows
+        pub async fn take_resume_receiver(&self) -> Option<crate::breakpoints::ResumeReceiver> {
+            None
+        }
     }
 
     #[async_trait]
@@ -1028,4 +1242,52 @@ mod tests {
         assert_eq!(format_timestamp(1_000_000), "00:00:01.000");
         assert_eq!(format_timestamp(3_661_500_000), "01:01:01.500");
     }
+
+    #[test]
+    fn test_socket_event_resume_message() {
+        let json = r#"{"type":"resume_message","session_id":"s1","hold_id":"log-4","drop":true}"#;
+        match serde_json::from_str::<SocketEvent>(json).unwrap() {
+            SocketEvent::ResumeMessage {
+                session_id,
+                hold_id,
+                message,
+                drop,
+            } => {
+                assert_eq!(session_id, "s1");
+                assert_eq!(hold_id, "log-4");
+                assert!(message.is_none());
+                assert!(drop);
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_socket_event_log_modified_flag() {
+        let event = SocketEvent::Log {
+            id: "log-1".to_string(),
+            session_id: "s1".to_string(),
+            timestamp: 0,
+            direction: "in".to_string(),
+            content: "{}".to_string(),
+            method: None,
+            server_name: "test".to_string(),
+            message_type: "jsonrpc".to_string(),
+            token_count: 0,
+            duration_micros: None,
+            modified: false,
+            original: None,
+            request_log_id: None,
+        };
+        // Unmodified entries keep the original wire format
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(!json.contains("modified"));
+        assert!(!json.contains("original"));
+
+        let parsed: SocketEvent = serde_json::from_str(
+            &json.replace(r#""token_count":0"#, r#""token_count":0,"modified":true"#),
+        )
+        .unwrap();
+        assert!(matches!(parsed, SocketEvent::Log { modified: true, .. }));
+    }
 }
@@ -0,0 +1,427 @@
+//! Latency Statistics Module
+//!
+//! Times request/response pairs and keeps latency histograms per session,
+//! per method, and per tool for `tools/call`, the way [`crate::token_counter`]
+//! keeps token counts.
+//!
+//! Histograms use HDR-style log-linear buckets: exact below 128µs, then 64
+//! buckets per power of two, so every percentile is within ~1.6% of the true
+//! value while a histogram stays a few hundred buckets at most.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::protocol::{Direction, McpMessage};
+use crate::request_tracker::{Answered, RequestTracker};
+use crate::session_recorder::{MessageDirection, RecordedSession};
+
+/// log2 of the number of sub-buckets per power of two
+const SUB_BUCKET_BITS: u32 = 7;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+const HALF_SUB_BUCKETS: u64 = SUB_BUCKETS / 2;
+
+/// Sparse log-linear histogram of latencies in microseconds
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// Sample count per bucket index
+    buckets: BTreeMap<u32, u64>,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, micros: u64) {
+        *self.buckets.entry(bucket_index(micros)).or_default() += 1;
+    }
+
+    /// Highest value equivalent to the sample at `rank` (1-based)
+    fn value_at_rank(&self, rank: u64) -> u64 {
+        let mut seen = 0;
+        for (&index, &count) in &self.buckets {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper(index);
+            }
+        }
+        0
+    }
+}
+
+fn bucket_index(micros: u64) -> u32 {
+    if micros < SUB_BUCKETS {
+        return micros as u32;
+    }
+    // Scale the value into [64, 128) and keep the shift as the exponent
+    let shift = 63 - micros.leading_zeros() - (SUB_BUCKET_BITS - 1);
+    (SUB_BUCKETS + (shift as u64 - 1) * HALF_SUB_BUCKETS + ((micros >> shift) - HALF_SUB_BUCKETS))
+        as u32
+}
+
+fn bucket_upper(index: u32) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKETS {
+        return index;
+    }
+    let shift = (index - SUB_BUCKETS) / HALF_SUB_BUCKETS + 1;
+    let sub_bucket = (index - SUB_BUCKETS) % HALF_SUB_BUCKETS + HALF_SUB_BUCKETS;
+    let upper = ((sub_bucket as u128 + 1) << shift) - 1;
+    upper.min(u64::MAX as u128) as u64
+}
+
+/// Latency distribution for one group of requests
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct LatencyStats {
+    /// Number of request/response pairs
+    pub count: u64,
+    /// Fastest response in microseconds
+    pub min_micros: u64,
+    /// Slowest response in microseconds
+    pub max_micros: u64,
+    /// Mean response time in microseconds
+    pub mean_micros: u64,
+    /// Median response time in microseconds
+    pub p50_micros: u64,
+    /// 90th percentile in microseconds
+    pub p90_micros: u64,
+    /// 99th percentile in microseconds
+    pub p99_micros: u64,
+    /// Sum of all response times in microseconds
+    pub total_micros: u64,
+    /// Bucketed samples the percentiles are read from
+    pub histogram: LatencyHistogram,
+}
+
+impl LatencyStats {
+    /// Add one response time
+    pub fn record(&mut self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.min_micros = if self.count == 0 {
+            micros
+        } else {
+            self.min_micros.min(micros)
+        };
+        self.max_micros = self.max_micros.max(micros);
+        self.count += 1;
+        self.total_micros = self.total_micros.saturating_add(micros);
+        self.histogram.record(micros);
+
+        self.mean_micros = self.total_micros / self.count;
+        self.p50_micros = self.percentile(50.0);
+        self.p90_micros = self.percentile(90.0);
+        self.p99_micros = self.percentile(99.0);
+    }
+
+    /// Response time at percentile `q` (0-100), in microseconds
+    ///
+    /// Accurate to the histogram's bucket width and never outside the
+    /// recorded min and max.
+    pub fn percentile(&self, q: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        if q <= 0.0 {
+            return self.min_micros;
+        }
+        let rank = ((q.min(100.0) / 100.0) * self.count as f64).ceil() as u64;
+        self.histogram
+            .value_at_rank(rank.max(1))
+            .clamp(self.min_micros, self.max_micros)
+    }
+}
+
+/// Latency statistics for a session
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SessionLatencyStats {
+    /// Session ID
+    pub session_id: String,
+    /// Every request/response pair in the session
+    pub overall: LatencyStats,
+    /// Latency breakdown by request method
+    pub latency_by_method: HashMap<String, LatencyStats>,
+    /// Latency of `tools/call` broken down by tool name
+    pub latency_by_tool: HashMap<String, LatencyStats>,
+}
+
+impl SessionLatencyStats {
+    /// Add a request/response pair
+    pub fn record(&mut self, answered: &Answered) {
+        self.overall.record(answered.elapsed);
+        self.latency_by_method
+            .entry(answered.method.clone())
+            .or_default()
+            .record(answered.elapsed);
+        if let Some(ref tool) = answered.tool {
+            self.latency_by_tool
+                .entry(tool.clone())
+                .or_default()
+                .record(answered.elapsed);
+        }
+    }
+
+    /// Compute the statistics of a recording from its message timestamps
+    pub fn from_session(session: &RecordedSession) -> Self {
+        let mut stats = Self {
+            session_id: session.id.clone(),
+            ..Default::default()
+        };
+        // Requests by direction and id: (method, tool, timestamp)
+        let mut pending: HashMap<(MessageDirection, String), (String, Option<String>, u64)> =
+            HashMap::new();
+
+        for message in &session.messages {
+            let parsed = McpMessage::parse(&message.content);
+            for content in parsed.items() {
+                let Some(id) = content.id() else {
+                    continue;
+                };
+                if let Some(method) = content.method() {
+                    let tool = content.tool_name().map(String::from);
+                    pending.insert(
+                        (message.direction, id.to_string()),
+                        (method.to_string(), tool, message.timestamp_micros),
+                    );
+                    continue;
+                }
+                let request_direction = match message.direction {
+                    MessageDirection::ToServer => MessageDirection::ToClient,
+                    MessageDirection::ToClient => MessageDirection::ToServer,
+                };
+                if let Some((method, tool, sent_at)) =
+                    pending.remove(&(request_direction, id.to_string()))
+                {
+                    stats.record(&Answered {
+                        id: id.clone(),
+                        method,
+                        tool,
+                        elapsed: Duration::from_micros(
+                            message.timestamp_micros.saturating_sub(sent_at),
+                        ),
+                    });
+                }
+            }
+        }
+
+        stats
+    }
+}
+
+/// Latency statistics across all sessions
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GlobalLatencyStats {
+    /// Latency stats per session
+    pub sessions: HashMap<String, SessionLatencyStats>,
+}
+
+/// Latency counter state
+///
+/// Feed it every message a proxy sees; it matches responses to requests by
+/// JSON-RPC id on its own.
+pub struct LatencyCounter {
+    /// Global statistics
+    stats: Arc<RwLock<GlobalLatencyStats>>,
+    /// In-flight requests per session
+    trackers: Mutex<HashMap<String, Arc<RequestTracker>>>,
+}
+
+impl LatencyCounter {
+    /// Create a new latency counter
+    pub fn new() -> Self {
+        Self {
+            stats: Arc::new(RwLock::new(GlobalLatencyStats::default())),
+            trackers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a message, returning the requests its response(s) answer
+    pub async fn record_message(
+        &self,
+        session_id: &str,
+        message: &McpMessage,
+        direction: Direction,
+    ) -> Vec<Answered> {
+        let tracker = self
+            .trackers
+            .lock()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_default()
+            .clone();
+        let answered = tracker.answer(message, direction);
+        if answered.is_empty() {
+            return answered;
+        }
+
+        let mut global = self.stats.write().await;
+        let session = global
+            .sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionLatencyStats {
+                session_id: session_id.to_string(),
+                ..Default::default()
+            });
+        for pair in &answered {
+            session.record(pair);
+        }
+        answered
+    }
+
+    /// Get statistics for a specific session
+    pub async fn get_session_stats(&self, session_id: &str) -> Option<SessionLatencyStats> {
+        let global = self.stats.read().await;
+        global.sessions.get(session_id).cloned()
+    }
+
+    /// Get global statistics
+    pub async fn get_global_stats(&self) -> GlobalLatencyStats {
+        let global = self.stats.read().await;
+        global.clone()
+    }
+
+    /// Clear statistics and in-flight requests for a session
+    pub async fn clear_session(&self, session_id: &str) {
+        self.trackers.lock().unwrap().remove(session_id);
+        let mut global = self.stats.write().await;
+        global.sessions.remove(session_id);
+    }
+
+    /// Clear all statistics
+    pub async fn clear_all(&self) {
+        self.trackers.lock().unwrap().clear();
+        let mut global = self.stats.write().await;
+        *global = GlobalLatencyStats::default();
+    }
+}
+
+impl Default for LatencyCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_recorder::SessionRecorder;
+    use serde_json::json;
+
+    #[test]
+    fn test_buckets_cover_values() {
+        let mut previous = 0;
+        for micros in (0..5000).chain([65_535, 1 << 40, u64::MAX]) {
+            let index = bucket_index(micros);
+            assert!(index >= previous, "buckets are ordered");
+            previous = index;
+
+            let upper = bucket_upper(index);
+            assert!(upper >= micros);
+            // Within 1/64 of the value
+            assert!(upper - micros <= micros / HALF_SUB_BUCKETS);
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut stats = LatencyStats::default();
+        assert_eq!(stats.percentile(50.0), 0);
+
+        for ms in 1..=1000 {
+            stats.record(Duration::from_millis(ms));
+        }
+        assert_eq!(stats.count, 1000);
+        assert_eq!(stats.min_micros, 1_000);
+        assert_eq!(stats.max_micros, 1_000_000);
+        assert_eq!(stats.mean_micros, 500_500);
+
+        for (actual, expected) in [
+            (stats.p50_micros, 500_000),
+            (stats.p90_micros, 900_000),
+            (stats.p99_micros, 990_000),
+        ] {
+            let error = actual.abs_diff(expected) as f64 / expected as f64;
+            assert!(error < 0.02, "{actual} vs {expected}");
+        }
+        assert_eq!(stats.percentile(100.0), stats.max_micros);
+        assert_eq!(stats.percentile(0.0), stats.min_micros);
+    }
+
+    #[tokio::test]
+    async fn test_counter_times_pairs() {
+        let counter = LatencyCounter::new();
+        let call = McpMessage::parse(
+            &json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "search"}}),
+        );
+        let list = McpMessage::parse(&json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}));
+
+        assert!(counter
+            .record_message("s1", &call, Direction::In)
+            .await
+            .is_empty());
+        counter.record_message("s1", &list, Direction::In).await;
+        // Same id in another session is tracked separately
+        counter.record_message("s2", &list, Direction::In).await;
+
+        let answered = counter
+            .record_message(
+                "s1",
+                &McpMessage::parse(&json!([
+                    {"jsonrpc": "2.0", "id": 1, "result": {}},
+                    {"jsonrpc": "2.0", "id": 2, "result": {}}
+                ])),
+                Direction::Out,
+            )
+            .await;
+        assert_eq!(answered.len(), 2);
+
+        let stats = counter.get_session_stats("s1").await.unwrap();
+        assert_eq!(stats.overall.count, 2);
+        assert_eq!(stats.latency_by_method["tools/call"].count, 1);
+        assert_eq!(stats.latency_by_method["tools/list"].count, 1);
+        assert_eq!(stats.latency_by_tool["search"].count, 1);
+        assert!(counter.get_session_stats("s2").await.is_none());
+
+        counter.clear_session("s1").await;
+        assert!(counter.get_global_stats().await.sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_from_session() {
+        let recorder = SessionRecorder::new("s".to_string(), "s".to_string(), "stdio".to_string());
+        for (message, direction) in [
+            (
+                json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "read"}}),
+                MessageDirection::ToServer,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "id": 9, "method": "sampling/createMessage"}),
+                MessageDirection::ToClient,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "id": 9, "result": {}}),
+                MessageDirection::ToServer,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "id": 1, "result": {}}),
+                MessageDirection::ToClient,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "id": 3, "method": "ping"}),
+                MessageDirection::ToServer,
+            ),
+        ] {
+            recorder.record_message(message, direction).await.unwrap();
+        }
+        let mut session = recorder.finalize().await.unwrap();
+        session.messages[3].timestamp_micros = session.messages[0].timestamp_micros + 2_500;
+
+        let stats = SessionLatencyStats::from_session(&session);
+        assert_eq!(stats.overall.count, 2);
+        assert_eq!(stats.latency_by_tool["read"].max_micros, 2_500);
+        assert_eq!(stats.latency_by_method["sampling/createMessage"].count, 1);
+        assert!(!stats.latency_by_method.contains_key("ping"));
+
+        let json = serde_json::to_string(&stats).unwrap();
+        let parsed: SessionLatencyStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, stats);
+    }
+}
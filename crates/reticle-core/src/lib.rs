@@ -6,30 +6,78 @@
 //!
 //! # Modules
 //!
-//! - [`protocol`] - JSON-RPC protocol types and message handling
+//! - [`protocol`] - JSON-RPC protocol types, typed MCP params and results, and conformance checks
+//! - [`matcher`] - Message patterns and JSONPath helpers
+//! - [`breakpoints`] - Breakpoints for holding and editing messages in the proxy
+//! - [`rules`] - Rules for rewriting, dropping, or delaying messages in the proxy
+//! - [`chaos`] - Fault injection for chaos testing MCP clients
+//! - [`mock`] - Mock server responses built from recorded sessions
 //! - [`transport`] - Transport configuration types
+//! - [`request_tracker`] - Request/response correlation for latency
+//! - [`progress`] - Progress and cancellation timelines of long-running requests
 //! - [`token_counter`] - Token counting for LLM context profiling
+//! - [`budget`] - Token budgets with warnings, truncation, or rejection
+//! - [`tokenizer`] - Heuristic, BPE, and `tokenizer.json` tokenizers
+//! - [`latency`] - Latency histograms per session, method, and tool
+//! - [`server_analyzer`] - Context cost of a server's tools, prompts, and resources
+//! - [`analysis_diff`] - What changed in a server's surface between two analyses
+//! - [`tool_lint`] - Lint rules for tool names, descriptions, and schemas
+//! - [`tool_schemas`] - Tool call arguments and results checked against advertised schemas
 //! - [`session_recorder`] - Session recording and replay
+//! - [`session_diff`] - Semantic diff between recorded sessions
 //! - [`storage`] - Persistent storage for sessions
 //! - [`events`] - Event sink trait for decoupling from GUI frameworks
 //! - [`session_names`] - Beautiful session name generation
 //! - [`error`] - Error types
 
+pub mod analysis_diff;
+pub mod breakpoints;
+pub mod budget;
+pub mod chaos;
 pub mod error;
 pub mod events;
+pub mod latency;
+pub mod matcher;
+pub mod mock;
+pub mod progress;
 pub mod protocol;
+pub mod request_tracker;
+pub mod rules;
+pub mod server_analyzer;
+pub mod session_diff;
 pub mod session_names;
 pub mod session_recorder;
 pub mod storage;
 pub mod token_counter;
+pub mod tokenizer;
+pub mod tool_lint;
+pub mod tool_schemas;
 pub mod transport;
 
 // Re-export commonly used types
+pub use analysis_diff::{diff_analyses, AnalysisDiff};
+pub use breakpoints::{Breakpoint, BreakpointSet, HeldMessage, ResumeAction, ResumeCommand};
+pub use budget::{BudgetAction, BudgetConfig, BudgetViolation, TokenBudget};
+pub use chaos::{ChaosEngine, Injected, Injection};
 pub use error::{AppError, Result};
 pub use events::EventSink;
-pub use protocol::{Direction, LogEntry, MessageType};
+pub use latency::{LatencyCounter, LatencyStats, SessionLatencyStats};
+pub use matcher::MessagePattern;
+pub use mock::{MockFallback, MockResponder};
+pub use progress::{session_timelines, ProgressTracker, RequestTimeline};
+pub use protocol::conformance::{ConformanceChecker, SessionConformance, Violation};
+pub use protocol::{Direction, LogEntry, McpMessage, MessageType};
+pub use request_tracker::{Answered, RequestTracker, Unanswered, UnansweredReason};
+pub use rules::{Rule, RuleOutcome, RulesEngine};
+pub use server_analyzer::{AnalysisThresholds, ServerAnalysis, ServerAnalyzer};
+pub use session_diff::{diff_sessions, DiffOptions, SessionDiff};
 pub use session_names::{create_session_id, create_session_name, generate_session_name, SessionId};
-pub use session_recorder::{MessageDirection, RecordedMessage, RecordedSession, SessionRecorder};
+pub use session_recorder::{
+    MessageDirection, RecordedMessage, RecordedSession, ReplayTiming, SessionRecorder,
+};
 pub use storage::{SessionFilter, SessionInfo, SessionStorage};
 pub use token_counter::{GlobalTokenStats, SessionTokenStats, TokenCounter};
+pub use tokenizer::{Tokenizer, TokenizerError, TokenizerSpec};
+pub use tool_lint::{LintFinding, LintReport, LintSeverity};
+pub use tool_schemas::{SchemaMismatch, ToolSchemas};
 pub use transport::{TransportConfig, TransportError, TransportType};
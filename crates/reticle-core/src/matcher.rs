@@ -0,0 +1,558 @@
+//! Message patterns and JSONPath helpers
+//!
+//! A [`MessagePattern`] selects JSON-RPC messages by method, tool name,
+//! direction, or a JSONPath expression. Patterns are shared by breakpoints
+//! and rewrite rules.
+//!
+//! # JSONPath subset
+//!
+//! Paths support `$`, `.key`, `['key']`, `[n]` and the wildcards `[*]` /
+//! `.*`. The leading `$` is optional, so `params.name` and `$.params.name`
+//! are equivalent.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::protocol::Direction;
+
+/// Conditions a message must meet; unset conditions match anything
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessagePattern {
+    /// JSON-RPC method to match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Tool name to match (`params.name` of a `tools/call` request)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Message direction to match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+    /// JSONPath expression that must resolve to a value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Expected value at `path` (any value matches if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+impl MessagePattern {
+    /// Parse a pattern from comma-separated `key=value` pairs
+    ///
+    /// A spec without any `key=` part is shorthand for a method.
+    pub fn parse_spec(spec: &str) -> Result<Self, MatchError> {
+        let spec = spec.trim();
+        let mut pattern = MessagePattern::default();
+
+        if !spec.contains('=') {
+            pattern.method = Some(spec.to_string());
+            return Ok(pattern);
+        }
+
+        for (key, value) in split_spec(spec)? {
+            match key.as_str() {
+                "method" => pattern.method = Some(value),
+                "tool" => pattern.tool = Some(value),
+                "direction" | "dir" => {
+                    pattern.direction = Some(match value.as_str() {
+                        "in" | "request" | "to_server" => Direction::In,
+                        "out" | "response" | "to_client" => Direction::Out,
+                        other => {
+                            return Err(MatchError::InvalidSpec(format!(
+                                "unknown direction '{other}' (expected 'in' or 'out')"
+                            )))
+                        }
+                    })
+                }
+                "path" => pattern.path = Some(value),
+                "value" => {
+                    // Accept bare strings as well as JSON literals
+                    pattern.value =
+                        Some(serde_json::from_str(&value).unwrap_or(Value::String(value)));
+                }
+                other => return Err(MatchError::InvalidSpec(format!("unknown key '{other}'"))),
+            }
+        }
+
+        pattern.validate()?;
+        Ok(pattern)
+    }
+
+    /// Check that the path parses and `value` is not used without it
+    pub fn validate(&self) -> Result<(), MatchError> {
+        if let Some(ref path) = self.path {
+            parse_json_path(path)?;
+        }
+        if self.value.is_some() && self.path.is_none() {
+            return Err(MatchError::InvalidSpec(
+                "'value' requires a 'path'".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check whether a message matches this pattern
+    pub fn matches(&self, message: &Value, direction: Direction) -> bool {
+        self.matches_with_request(message, direction, None)
+    }
+
+    /// Check whether a message matches, using its request for responses
+    ///
+    /// Responses carry no method, so `method` and `tool` are checked against
+    /// the request the response answers when one is given.
+    pub fn matches_with_request(
+        &self,
+        message: &Value,
+        direction: Direction,
+        request: Option<&RequestContext>,
+    ) -> bool {
+        if let Some(expected) = self.direction {
+            if expected != direction {
+                return false;
+            }
+        }
+
+        let own = RequestContext::from_message(message);
+        let context = own.as_ref().or(request);
+        let method = context.map(|c| c.method.as_str());
+
+        if let Some(ref expected) = self.method {
+            if method != Some(expected.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(ref expected) = self.tool {
+            let tool = context.and_then(|c| c.tool.as_deref());
+            if method != Some("tools/call") || tool != Some(expected.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(ref path) = self.path {
+            let found = json_path_all(message, path);
+            if found.is_empty() {
+                return false;
+            }
+            if let Some(ref expected) = self.value {
+                if !found.contains(&expected) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// The method and tool of a request, used to match its response
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestContext {
+    pub method: String,
+    pub tool: Option<String>,
+}
+
+impl RequestContext {
+    /// Extract the context of a message that has a method
+    pub fn from_message(message: &Value) -> Option<Self> {
+        let method = message.get("method")?.as_str()?.to_string();
+        let tool = message
+            .get("params")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .map(String::from);
+        Some(Self { method, tool })
+    }
+}
+
+/// Pattern errors
+#[derive(Debug, thiserror::Error)]
+pub enum MatchError {
+    #[error("Invalid pattern: {0}")]
+    InvalidSpec(String),
+
+    #[error("Invalid JSONPath '{0}': {1}")]
+    InvalidPath(String, String),
+}
+
+/// Split `key=value,key=value` into pairs
+///
+/// Commas inside a value are kept when the following segment has no `=`,
+/// so `value=[1,2]` parses as a single pair.
+fn split_spec(spec: &str) -> Result<Vec<(String, String)>, MatchError> {
+    let mut pairs: Vec<(String, String)> = Vec::new();
+
+    for segment in spec.split(',') {
+        match segment.split_once('=') {
+            Some((key, value)) if !key.contains(['"', '[', '{']) => {
+                pairs.push((key.trim().to_lowercase(), value.trim().to_string()));
+            }
+            _ => match pairs.last_mut() {
+                Some((_, value)) => {
+                    value.push(',');
+                    value.push_str(segment);
+                }
+                None => {
+                    return Err(MatchError::InvalidSpec(format!(
+                        "expected key=value, got '{segment}'"
+                    )))
+                }
+            },
+        }
+    }
+
+    Ok(pairs)
+}
+
+/// A single step in a parsed JSONPath expression
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// Parse the supported JSONPath subset
+fn parse_json_path(path: &str) -> Result<Vec<PathSegment>, MatchError> {
+    let invalid = |reason: &str| MatchError::InvalidPath(path.to_string(), reason.to_string());
+
+    let rest = path.trim();
+    let rest = rest.strip_prefix('$').unwrap_or(rest);
+    let mut segments = Vec::new();
+    let mut chars = rest.chars().peekable();
+    // Allow a bare key at the start when `$` was omitted
+    let mut expect_key = !rest.starts_with(['.', '[']);
+
+    while let Some(&c) = chars.peek() {
+        if c == '.' || expect_key {
+            if c == '.' {
+                chars.next();
+            }
+            expect_key = false;
+            let mut key = String::new();
+            while let Some(&c) = chars.peek() {
+                if c == '.' || c == '[' {
+                    break;
+                }
+                key.push(c);
+                chars.next();
+            }
+            match key.as_str() {
+                "" => return Err(invalid("empty key")),
+                "*" => segments.push(PathSegment::Wildcard),
+                _ => segments.push(PathSegment::Key(key)),
+            }
+        } else if c == '[' {
+            chars.next();
+            let mut inner = String::new();
+            loop {
+                match chars.next() {
+                    Some(']') => break,
+                    Some(c) => inner.push(c),
+                    None => return Err(invalid("unclosed '['")),
+                }
+            }
+            let inner = inner.trim();
+            if inner == "*" {
+                segments.push(PathSegment::Wildcard);
+            } else if let Some(quoted) = inner
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+            {
+                segments.push(PathSegment::Key(quoted.to_string()));
+            } else {
+                let index = inner
+                    .parse::<usize>()
+                    .map_err(|_| invalid("index must be a number, '*' or quoted key"))?;
+                segments.push(PathSegment::Index(index));
+            }
+        } else {
+            return Err(invalid("expected '.' or '['"));
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Child values of an array or object, in order
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => map.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn children_mut(value: &mut Value) -> Vec<&mut Value> {
+    match value {
+        Value::Array(items) => items.iter_mut().collect(),
+        Value::Object(map) => map.values_mut().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Resolve a JSONPath expression against a value
+///
+/// Returns the first match, or `None` if the path is invalid or does not
+/// exist in the value.
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    json_path_all(value, path).into_iter().next()
+}
+
+/// Resolve a JSONPath expression, returning every value a wildcard reaches
+pub fn json_path_all<'a>(value: &'a Value, path: &str) -> Vec<&'a Value> {
+    let Ok(segments) = parse_json_path(path) else {
+        return Vec::new();
+    };
+    segments.iter().fold(vec![value], |current, segment| {
+        current
+            .into_iter()
+            .flat_map(|v| match segment {
+                PathSegment::Key(key) => v.get(key.as_str()).into_iter().collect(),
+                PathSegment::Index(index) => v.get(*index).into_iter().collect(),
+                PathSegment::Wildcard => children(v),
+            })
+            .collect()
+    })
+}
+
+/// Set the value at a JSONPath, returning whether anything changed
+///
+/// Missing object keys along the way are created; missing array
+/// elements are not. `$` replaces the whole value.
+pub fn set_json_path(value: &mut Value, path: &str, new_value: &Value) -> Result<bool, MatchError> {
+    let segments = parse_json_path(path)?;
+    if segments.is_empty() {
+        let changed = value != new_value;
+        *value = new_value.clone();
+        return Ok(changed);
+    }
+    Ok(modify_at(
+        value,
+        &segments,
+        true,
+        &mut |parent, last| match last {
+            PathSegment::Key(key) => match parent.as_object_mut() {
+                Some(map) => map.insert(key.clone(), new_value.clone()).as_ref() != Some(new_value),
+                None => false,
+            },
+            PathSegment::Index(index) => match parent.get_mut(*index) {
+                Some(slot) => !std::mem::replace(slot, new_value.clone()).eq(new_value),
+                None => false,
+            },
+            PathSegment::Wildcard => {
+                let mut changed = false;
+                for slot in children_mut(parent) {
+                    changed |= !std::mem::replace(slot, new_value.clone()).eq(new_value);
+                }
+                changed
+            }
+        },
+    ))
+}
+
+/// Remove the value(s) at a JSONPath, returning whether anything was removed
+pub fn remove_json_path(value: &mut Value, path: &str) -> Result<bool, MatchError> {
+    let segments = parse_json_path(path)?;
+    if segments.is_empty() {
+        return Err(MatchError::InvalidPath(
+            path.to_string(),
+            "cannot remove the whole message".to_string(),
+        ));
+    }
+    Ok(modify_at(
+        value,
+        &segments,
+        false,
+        &mut |parent, last| match last {
+            PathSegment::Key(key) => parent
+                .as_object_mut()
+                .and_then(|map| map.remove(key))
+                .is_some(),
+            PathSegment::Index(index) => match parent.as_array_mut() {
+                Some(items) if *index < items.len() => {
+                    items.remove(*index);
+                    true
+                }
+                _ => false,
+            },
+            PathSegment::Wildcard => match parent {
+                Value::Array(items) if !items.is_empty() => {
+                    items.clear();
+                    true
+                }
+                Value::Object(map) if !map.is_empty() => {
+                    map.clear();
+                    true
+                }
+                _ => false,
+            },
+        },
+    ))
+}
+
+/// Walk to every parent of the last segment and apply `op` there
+///
+/// With `create`, missing object keys along the way are added as empty
+/// objects so `set` can add new fields.
+fn modify_at(
+    value: &mut Value,
+    segments: &[PathSegment],
+    create: bool,
+    op: &mut dyn FnMut(&mut Value, &PathSegment) -> bool,
+) -> bool {
+    let Some((first, rest)) = segments.split_first() else {
+        return false;
+    };
+    if rest.is_empty() {
+        return op(value, first);
+    }
+
+    match first {
+        PathSegment::Key(key) => match value.as_object_mut() {
+            Some(map) if create => {
+                let child = map
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::Object(Default::default()));
+                modify_at(child, rest, create, op)
+            }
+            Some(map) => match map.get_mut(key) {
+                Some(child) => modify_at(child, rest, create, op),
+                None => false,
+            },
+            None => false,
+        },
+        PathSegment::Index(index) => match value.get_mut(*index) {
+            Some(child) => modify_at(child, rest, create, op),
+            None => false,
+        },
+        PathSegment::Wildcard => {
+            // Visit every child, not just up to the first change
+            let mut changed = false;
+            for child in children_mut(value) {
+                changed |= modify_at(child, rest, create, op);
+            }
+            changed
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_call(name: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": {"path": "/etc/hosts"}}
+        })
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let pattern = MessagePattern::parse_spec("tools/list").unwrap();
+        assert_eq!(pattern.method.as_deref(), Some("tools/list"));
+
+        let pattern = MessagePattern::parse_spec("path=params.ids,value=[1,2]").unwrap();
+        assert_eq!(pattern.value, Some(json!([1, 2])));
+
+        assert!(MessagePattern::parse_spec("direction=sideways").is_err());
+        assert!(MessagePattern::parse_spec("color=red").is_err());
+        assert!(MessagePattern::parse_spec("value=1").is_err());
+        assert!(MessagePattern::parse_spec("path=$.a[").is_err());
+    }
+
+    #[test]
+    fn test_match_response_by_request() {
+        let pattern = MessagePattern::parse_spec("method=tools/call,tool=read_file").unwrap();
+        let response = json!({"jsonrpc": "2.0", "id": 7, "result": {}});
+        assert!(!pattern.matches(&response, Direction::Out));
+
+        let request = RequestContext::from_message(&tool_call("read_file"));
+        assert!(pattern.matches_with_request(&response, Direction::Out, request.as_ref()));
+
+        let other = RequestContext::from_message(&tool_call("write_file"));
+        assert!(!pattern.matches_with_request(&response, Direction::Out, other.as_ref()));
+    }
+
+    #[test]
+    fn test_json_path() {
+        let value = json!({
+            "result": {"tools": [{"name": "a"}, {"name": "b"}]},
+            "odd key": true
+        });
+        assert_eq!(
+            json_path(&value, "$.result.tools[1].name"),
+            Some(&json!("b"))
+        );
+        assert_eq!(
+            json_path(&value, "result.tools[0]['name']"),
+            Some(&json!("a"))
+        );
+        assert_eq!(json_path(&value, "$['odd key']"), Some(&json!(true)));
+        assert_eq!(json_path(&value, "$"), Some(&value));
+        assert!(json_path(&value, "$.result.tools[5]").is_none());
+        assert!(json_path(&value, "$.missing").is_none());
+    }
+
+    #[test]
+    fn test_json_path_wildcard() {
+        let value = json!({"result": {"tools": [{"name": "a"}, {"name": "b"}]}});
+        assert_eq!(
+            json_path_all(&value, "$.result.tools[*].name"),
+            vec![&json!("a"), &json!("b")]
+        );
+        assert_eq!(json_path(&value, "result.tools.*.name"), Some(&json!("a")));
+
+        let pattern = MessagePattern::parse_spec("path=result.tools[*].name,value=b").unwrap();
+        assert!(pattern.matches(&value, Direction::Out));
+    }
+
+    #[test]
+    fn test_set_json_path() {
+        let mut value = tool_call("read_file");
+        assert!(set_json_path(&mut value, "params.arguments.path", &json!("/tmp/x")).unwrap());
+        assert_eq!(value["params"]["arguments"]["path"], json!("/tmp/x"));
+
+        // Setting the same value again is not a change
+        assert!(!set_json_path(&mut value, "params.arguments.path", &json!("/tmp/x")).unwrap());
+
+        // Missing objects are created
+        assert!(set_json_path(&mut value, "params._meta.tag", &json!(1)).unwrap());
+        assert_eq!(value["params"]["_meta"]["tag"], json!(1));
+
+        // Missing array elements are not
+        assert!(!set_json_path(&mut value, "params.list[3]", &json!(1)).unwrap());
+
+        assert!(set_json_path(&mut value, "$", &json!({})).unwrap());
+        assert_eq!(value, json!({}));
+    }
+
+    #[test]
+    fn test_remove_json_path() {
+        let mut value = json!({
+            "result": {"tools": [
+                {"name": "a", "annotations": {"readOnlyHint": true}},
+                {"name": "b"}
+            ]}
+        });
+        assert!(remove_json_path(&mut value, "$.result.tools[*].annotations").unwrap());
+        assert_eq!(
+            value,
+            json!({"result": {"tools": [{"name": "a"}, {"name": "b"}]}})
+        );
+        assert!(!remove_json_path(&mut value, "$.result.tools[*].annotations").unwrap());
+
+        assert!(remove_json_path(&mut value, "result.tools[0]").unwrap());
+        assert_eq!(value["result"]["tools"], json!([{"name": "b"}]));
+
+        // Removing a missing path doesn't create anything
+        assert!(!remove_json_path(&mut value, "params.arguments.path").unwrap());
+        assert!(value.get("params").is_none());
+
+        assert!(remove_json_path(&mut value, "$").is_err());
+    }
+}
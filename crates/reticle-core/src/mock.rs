@@ -0,0 +1,381 @@
+//! Mock MCP server responses built from a recording
+//!
+//! A [`MockResponder`] pairs every request in a [`RecordedSession`] with the
+//! response the server sent back, then answers new requests from that table.
+//! Requests match on method plus normalized params: object key order and
+//! `_meta` (progress tokens and the like) are ignored.
+//!
+//! When the same request was recorded more than once, its responses are
+//! played back in order and the last one repeats. Requests with no exact
+//! match are handled by the configured [`MockFallback`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::session_recorder::{MessageDirection, RecordedSession};
+
+/// JSON-RPC "method not found" error code
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// What to do with a request that has no exact match in the recording
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MockFallback {
+    /// Answer with a response recorded for the same method, else an error
+    #[default]
+    Method,
+    /// Answer with a JSON-RPC error
+    Error,
+    /// Answer with an empty result
+    Empty,
+}
+
+impl FromStr for MockFallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "method" => Ok(Self::Method),
+            "error" => Ok(Self::Error),
+            "empty" => Ok(Self::Empty),
+            other => Err(format!(
+                "unknown fallback '{other}' (expected method, error or empty)"
+            )),
+        }
+    }
+}
+
+/// How a mock response was chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockMatch {
+    /// Same method and params as a recorded request
+    Exact,
+    /// Same method only
+    Method,
+    /// No recorded response was used
+    Fallback,
+}
+
+/// A response produced by the mock
+#[derive(Debug, Clone)]
+pub struct MockReply {
+    pub response: Value,
+    pub matched: MockMatch,
+}
+
+/// Answers requests with responses captured in a recorded session
+#[derive(Debug, Default)]
+pub struct MockResponder {
+    /// Recorded outcomes (`result` or `error` objects) keyed by method + params
+    exact: HashMap<String, Vec<Value>>,
+    /// Recorded outcomes keyed by method alone
+    by_method: HashMap<String, Vec<Value>>,
+    /// Playback position per key, so repeated requests walk the recording
+    cursors: Mutex<HashMap<(MockMatch, String), usize>>,
+    fallback: MockFallback,
+}
+
+impl MockResponder {
+    /// Build a responder from the request/response pairs in a session
+    pub fn from_session(session: &RecordedSession, fallback: MockFallback) -> Self {
+        let mut pending: HashMap<String, (String, Value)> = HashMap::new();
+        let mut responder = Self {
+            fallback,
+            ..Default::default()
+        };
+
+        for message in &session.messages {
+            let content = &message.content;
+            let Some(id) = content.get("id").filter(|id| !id.is_null()) else {
+                continue;
+            };
+            match message.direction {
+                MessageDirection::ToServer => {
+                    if let Some(method) = content.get("method").and_then(Value::as_str) {
+                        let params = content.get("params").cloned().unwrap_or(Value::Null);
+                        pending.insert(id.to_string(), (method.to_string(), params));
+                    }
+                }
+                MessageDirection::ToClient => {
+                    if content.get("method").is_some() {
+                        // Server-initiated request, nothing to answer
+                        continue;
+                    }
+                    let Some((method, params)) = pending.remove(&id.to_string()) else {
+                        continue;
+                    };
+                    let Some(outcome) = outcome(content) else {
+                        continue;
+                    };
+                    responder
+                        .exact
+                        .entry(request_key(&method, &params))
+                        .or_default()
+                        .push(outcome.clone());
+                    responder.by_method.entry(method).or_default().push(outcome);
+                }
+            }
+        }
+
+        responder
+    }
+
+    /// Number of distinct requests with a recorded response
+    pub fn len(&self) -> usize {
+        self.exact.len()
+    }
+
+    /// Whether the recording had no answered requests
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty()
+    }
+
+    /// Methods with at least one recorded response
+    pub fn methods(&self) -> Vec<&str> {
+        let mut methods: Vec<&str> = self.by_method.keys().map(String::as_str).collect();
+        methods.sort_unstable();
+        methods
+    }
+
+    /// Answer a request; notifications and responses get `None`
+    pub fn respond(&self, request: &Value) -> Option<MockReply> {
+        let method = request.get("method")?.as_str()?;
+        let id = request.get("id").filter(|id| !id.is_null())?;
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let key = request_key(method, &params);
+        if let Some(outcome) = self.next(MockMatch::Exact, &key, self.exact.get(&key)) {
+            return Some(reply(id, outcome, MockMatch::Exact));
+        }
+
+        if self.fallback == MockFallback::Method {
+            if let Some(outcome) = self.next(MockMatch::Method, method, self.by_method.get(method))
+            {
+                return Some(reply(id, outcome, MockMatch::Method));
+            }
+        }
+
+        let outcome = match self.fallback {
+            MockFallback::Empty => json!({"result": {}}),
+            MockFallback::Method | MockFallback::Error => json!({
+                "error": {
+                    "code": METHOD_NOT_FOUND,
+                    "message": format!("No recorded response for {method}"),
+                }
+            }),
+        };
+        Some(reply(id, outcome, MockMatch::Fallback))
+    }
+
+    /// Take the next recorded outcome for a key, repeating the last one
+    fn next(&self, kind: MockMatch, key: &str, outcomes: Option<&Vec<Value>>) -> Option<Value> {
+        let outcomes = outcomes.filter(|o| !o.is_empty())?;
+        let mut cursors = self.cursors.lock().unwrap();
+        let cursor = cursors.entry((kind, key.to_string())).or_insert(0);
+        let outcome = outcomes[(*cursor).min(outcomes.len() - 1)].clone();
+        *cursor += 1;
+        Some(outcome)
+    }
+}
+
+/// The `result` or `error` member of a recorded response
+fn outcome(response: &Value) -> Option<Value> {
+    if let Some(result) = response.get("result") {
+        Some(json!({"result": result}))
+    } else {
+        response.get("error").map(|error| json!({"error": error}))
+    }
+}
+
+/// Build a response to `id` from a recorded outcome
+fn reply(id: &Value, outcome: Value, matched: MockMatch) -> MockReply {
+    let mut response = json!({"jsonrpc": "2.0", "id": id});
+    if let (Some(response), Value::Object(outcome)) = (response.as_object_mut(), outcome) {
+        response.extend(outcome);
+    }
+    MockReply { response, matched }
+}
+
+/// Lookup key for a request: method plus canonical params
+fn request_key(method: &str, params: &Value) -> String {
+    let mut key = format!("{method}:");
+    write_canonical(&normalize_params(params), &mut key);
+    key
+}
+
+/// Drop fields that differ between otherwise identical requests
+fn normalize_params(params: &Value) -> Value {
+    let mut params = params.clone();
+    if let Some(map) = params.as_object_mut() {
+        map.remove("_meta");
+    }
+    params
+}
+
+/// Serialize with object keys sorted so key order doesn't affect matching
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_recorder::SessionRecorder;
+
+    async fn recording(pairs: &[(Value, Value)]) -> RecordedSession {
+        let recorder = SessionRecorder::new(
+            "mock-test".to_string(),
+            "Mock Test".to_string(),
+            "stdio".to_string(),
+        );
+        for (request, response) in pairs {
+            recorder
+                .record_message(request.clone(), MessageDirection::ToServer)
+                .await
+                .unwrap();
+            recorder
+                .record_message(response.clone(), MessageDirection::ToClient)
+                .await
+                .unwrap();
+        }
+        recorder.finalize().await.unwrap()
+    }
+
+    fn call(id: u64, tool: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": tool, "arguments": {"path": "/tmp", "depth": 1}}
+        })
+    }
+
+    fn text(id: u64, text: &str) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "result": {"content": [{"type": "text", "text": text}]}})
+    }
+
+    #[tokio::test]
+    async fn test_exact_match_normalizes_params() {
+        let session = recording(&[
+            (call(1, "list"), text(1, "listing")),
+            (call(2, "read"), text(2, "contents")),
+        ])
+        .await;
+        let mock = MockResponder::from_session(&session, MockFallback::Error);
+        assert_eq!(mock.len(), 2);
+        assert_eq!(mock.methods(), vec!["tools/call"]);
+
+        // Different id, reordered arguments and a progress token still match
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": "req-9",
+            "method": "tools/call",
+            "params": {
+                "_meta": {"progressToken": 5},
+                "arguments": {"depth": 1, "path": "/tmp"},
+                "name": "read"
+            }
+        });
+        let reply = mock.respond(&request).unwrap();
+        assert_eq!(reply.matched, MockMatch::Exact);
+        assert_eq!(reply.response["id"], json!("req-9"));
+        assert_eq!(reply.response["result"]["content"][0]["text"], "contents");
+    }
+
+    #[tokio::test]
+    async fn test_repeated_requests_play_in_order() {
+        let session = recording(&[
+            (call(1, "list"), text(1, "first")),
+            (call(2, "list"), text(2, "second")),
+        ])
+        .await;
+        let mock = MockResponder::from_session(&session, MockFallback::Error);
+
+        let texts: Vec<Value> = (0..3)
+            .map(|i| mock.respond(&call(10 + i, "list")).unwrap().response)
+            .map(|r| r["result"]["content"][0]["text"].clone())
+            .collect();
+        assert_eq!(
+            texts,
+            vec![json!("first"), json!("second"), json!("second")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fallbacks() {
+        let session = recording(&[(call(1, "list"), text(1, "listing"))]).await;
+
+        let mock = MockResponder::from_session(&session, MockFallback::Method);
+        let reply = mock.respond(&call(5, "other")).unwrap();
+        assert_eq!(reply.matched, MockMatch::Method);
+        assert_eq!(reply.response["result"]["content"][0]["text"], "listing");
+        let reply = mock
+            .respond(&json!({"jsonrpc": "2.0", "id": 6, "method": "ping"}))
+            .unwrap();
+        assert_eq!(reply.matched, MockMatch::Fallback);
+        assert_eq!(reply.response["error"]["code"], METHOD_NOT_FOUND);
+
+        let mock = MockResponder::from_session(&session, MockFallback::Error);
+        let reply = mock.respond(&call(5, "other")).unwrap();
+        assert_eq!(reply.matched, MockMatch::Fallback);
+        assert!(reply.response.get("error").is_some());
+
+        let mock = MockResponder::from_session(&session, MockFallback::Empty);
+        let reply = mock.respond(&call(5, "other")).unwrap();
+        assert_eq!(
+            reply.response,
+            json!({"jsonrpc": "2.0", "id": 5, "result": {}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recorded_errors_and_notifications() {
+        let error = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32602, "message": "bad"}});
+        let session = recording(&[(call(1, "list"), error)]).await;
+        let mock = MockResponder::from_session(&session, MockFallback::Error);
+
+        let reply = mock.respond(&call(3, "list")).unwrap();
+        assert_eq!(reply.matched, MockMatch::Exact);
+        assert_eq!(reply.response["error"]["code"], -32602);
+
+        assert!(mock
+            .respond(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .is_none());
+        assert!(mock.respond(&text(1, "response")).is_none());
+    }
+
+    #[test]
+    fn test_fallback_from_str() {
+        assert_eq!("empty".parse::<MockFallback>(), Ok(MockFallback::Empty));
+        assert!("nope".parse::<MockFallback>().is_err());
+    }
+}
@@ -0,0 +1,465 @@
+//! Progress and cancellation of long-running requests
+//!
+//! MCP notifications that refer to an earlier request carry no JSON-RPC id
+//! of their own: `notifications/progress` names the `progressToken` the
+//! request set in `params._meta`, and `notifications/cancelled` names the
+//! request's id. A [`ProgressTracker`] follows one session and links each
+//! of them back to the log entry of the request, and keeps a
+//! [`RequestTimeline`] of every request that reported progress or was
+//! cancelled: when each update arrived, how long the sender waited before
+//! cancelling, and whether the receiver answered anyway.
+//!
+//! [`session_timelines`] runs the same analysis over a recorded session.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::protocol::message::Notification;
+use crate::protocol::{Direction, McpMessage};
+use crate::session_recorder::RecordedSession;
+
+/// One `notifications/progress` for a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressPoint {
+    /// Log entry of the notification
+    pub log_id: String,
+    /// Time since the request
+    pub elapsed_ms: u64,
+    pub progress: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The `notifications/cancelled` for a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cancellation {
+    /// Log entry of the notification
+    pub log_id: String,
+    /// How long the sender waited before cancelling
+    pub elapsed_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Time from the cancellation to a response the receiver sent anyway
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answered_after_ms: Option<u64>,
+}
+
+/// What happened to a request between being sent and being answered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestTimeline {
+    /// Log entry of the request
+    pub log_id: String,
+    /// JSON-RPC id of the request
+    pub id: Value,
+    pub method: String,
+    /// Tool name, for `tools/call`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Direction the request was sent in
+    pub direction: Direction,
+    /// When the request was sent (microseconds since UNIX_EPOCH)
+    pub started_at: u64,
+    pub progress: Vec<ProgressPoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancelled: Option<Cancellation>,
+    /// Time from the request to its response, if one arrived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_ms: Option<u64>,
+}
+
+impl RequestTimeline {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.is_some()
+    }
+}
+
+/// A request waiting for its response
+struct Pending {
+    timeline: RequestTimeline,
+    progress_token: Option<String>,
+    /// When the cancellation arrived, to time a late response
+    cancelled_at: Option<u64>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Requests by the direction they were sent in and id
+    pending: HashMap<(Direction, String), Pending>,
+    /// Progress tokens by the direction of their request, to its id
+    tokens: HashMap<(Direction, String), String>,
+    /// Answered requests that reported progress or were cancelled
+    done: Vec<RequestTimeline>,
+}
+
+/// Links progress and cancellation notifications to their requests
+#[derive(Default)]
+pub struct ProgressTracker {
+    state: Mutex<State>,
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observe a message as it passes, returning the log entry of the
+    /// request it refers to
+    pub fn observe(
+        &self,
+        message: &McpMessage,
+        direction: Direction,
+        log_id: &str,
+    ) -> Option<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+        self.observe_at(message, direction, log_id, now)
+    }
+
+    /// Observe a message sent at `timestamp_micros`
+    pub fn observe_at(
+        &self,
+        message: &McpMessage,
+        direction: Direction,
+        log_id: &str,
+        timestamp_micros: u64,
+    ) -> Option<String> {
+        if let McpMessage::Batch(batch) = message {
+            // Every item counts, but a batch links to its first request
+            return batch
+                .iter()
+                .map(|item| self.observe_at(item, direction, log_id, timestamp_micros))
+                .fold(None, Option::or);
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match message {
+            McpMessage::Notification(Notification::Progress(notification)) => {
+                // Progress comes from the side the request was sent to
+                let request = opposite(direction);
+                let token = (request, notification.progress_token.to_string());
+                let id = state.tokens.get(&token)?.clone();
+                let pending = state.pending.get_mut(&(request, id))?;
+                let timeline = &mut pending.timeline;
+                timeline.progress.push(ProgressPoint {
+                    log_id: log_id.to_string(),
+                    elapsed_ms: timestamp_micros.saturating_sub(timeline.started_at) / 1000,
+                    progress: notification.progress,
+                    total: notification.total,
+                    message: notification.message.clone(),
+                });
+                Some(timeline.log_id.clone())
+            }
+            McpMessage::Notification(Notification::Cancelled(notification)) => {
+                // Only the sender of a request may cancel it
+                let key = (direction, notification.request_id.to_string());
+                let pending = state.pending.get_mut(&key)?;
+                let timeline = &mut pending.timeline;
+                let elapsed_ms = timestamp_micros.saturating_sub(timeline.started_at) / 1000;
+                tracing::info!(
+                    "{} request {} cancelled after {} ms",
+                    timeline.method,
+                    timeline.id,
+                    elapsed_ms
+                );
+                timeline.cancelled = Some(Cancellation {
+                    log_id: log_id.to_string(),
+                    elapsed_ms,
+                    reason: notification.reason.clone(),
+                    answered_after_ms: None,
+                });
+                pending.cancelled_at = Some(timestamp_micros);
+                Some(timeline.log_id.clone())
+            }
+            _ => match (message.method(), message.id()) {
+                (Some(method), Some(id)) => {
+                    let progress_token = message.progress_token().map(Value::to_string);
+                    let key = (direction, id.to_string());
+                    if let Some(ref token) = progress_token {
+                        state
+                            .tokens
+                            .insert((direction, token.clone()), key.1.clone());
+                    }
+                    state.pending.insert(
+                        key,
+                        Pending {
+                            timeline: RequestTimeline {
+                                log_id: log_id.to_string(),
+                                id: id.clone(),
+                                method: method.to_string(),
+                                tool: message.tool_name().map(String::from),
+                                direction,
+                                started_at: timestamp_micros,
+                                progress: Vec::new(),
+                                cancelled: None,
+                                completed_ms: None,
+                            },
+                            progress_token,
+                            cancelled_at: None,
+                        },
+                    );
+                    None
+                }
+                (None, Some(id)) => {
+                    // A response answers a request sent the other way
+                    let request = opposite(direction);
+                    let mut pending = state.pending.remove(&(request, id.to_string()))?;
+                    if let Some(token) = pending.progress_token.take() {
+                        state.tokens.remove(&(request, token));
+                    }
+                    let mut timeline = pending.timeline;
+                    if timeline.progress.is_empty() && timeline.cancelled.is_none() {
+                        return None;
+                    }
+                    timeline.completed_ms =
+                        Some(timestamp_micros.saturating_sub(timeline.started_at) / 1000);
+                    if let (Some(cancellation), Some(at)) =
+                        (timeline.cancelled.as_mut(), pending.cancelled_at)
+                    {
+                        cancellation.answered_after_ms =
+                            Some(timestamp_micros.saturating_sub(at) / 1000);
+                    }
+                    state.done.push(timeline);
+                    None
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// Requests that reported progress or were cancelled, in the order
+    /// they were sent, including those still waiting for a response
+    pub fn timelines(&self) -> Vec<RequestTimeline> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut timelines = state.done.clone();
+        timelines.extend(
+            state
+                .pending
+                .values()
+                .map(|pending| &pending.timeline)
+                .filter(|timeline| !timeline.progress.is_empty() || timeline.is_cancelled())
+                .cloned(),
+        );
+        timelines.sort_by_key(|timeline| timeline.started_at);
+        timelines
+    }
+}
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::In => Direction::Out,
+        Direction::Out => Direction::In,
+    }
+}
+
+/// The progress and cancellation timelines of a recorded session
+///
+/// Log ids in the timelines are the ids of the recorded messages.
+pub fn session_timelines(session: &RecordedSession) -> Vec<RequestTimeline> {
+    let tracker = ProgressTracker::new();
+    for message in &session.messages {
+        tracker.observe_at(
+            &McpMessage::parse(&message.content),
+            message.direction.into(),
+            &message.id,
+            message.timestamp_micros,
+        );
+    }
+    tracker.timelines()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MS: u64 = 1000;
+
+    fn observe(
+        tracker: &ProgressTracker,
+        message: &Value,
+        direction: Direction,
+        log_id: &str,
+        at: u64,
+    ) -> Option<String> {
+        tracker.observe_at(&McpMessage::parse(message), direction, log_id, at)
+    }
+
+    fn call(id: u64, token: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {
+                "name": "index_repo",
+                "arguments": {},
+                "_meta": {"progressToken": token}
+            }
+        })
+    }
+
+    fn progress(token: &str, progress: u64) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": {"progressToken": token, "progress": progress, "total": 100}
+        })
+    }
+
+    #[test]
+    fn test_progress_timeline() {
+        let tracker = ProgressTracker::new();
+        observe(&tracker, &call(1, "t1"), Direction::In, "log-1", 0);
+
+        let link = observe(
+            &tracker,
+            &progress("t1", 40),
+            Direction::Out,
+            "log-2",
+            800 * MS,
+        );
+        assert_eq!(link.as_deref(), Some("log-1"));
+        observe(
+            &tracker,
+            &progress("t1", 90),
+            Direction::Out,
+            "log-3",
+            1500 * MS,
+        );
+        // A token nobody sent, and one sent the other way
+        assert!(observe(
+            &tracker,
+            &progress("t2", 10),
+            Direction::Out,
+            "log-4",
+            1600 * MS
+        )
+        .is_none());
+        assert!(observe(
+            &tracker,
+            &progress("t1", 10),
+            Direction::In,
+            "log-5",
+            1600 * MS
+        )
+        .is_none());
+
+        let response = json!({"jsonrpc": "2.0", "id": 1, "result": {"content": []}});
+        assert!(observe(&tracker, &response, Direction::Out, "log-6", 2000 * MS).is_none());
+
+        let timelines = tracker.timelines();
+        assert_eq!(timelines.len(), 1);
+        let timeline = &timelines[0];
+        assert_eq!(timeline.tool.as_deref(), Some("index_repo"));
+        assert_eq!(timeline.completed_ms, Some(2000));
+        let elapsed: Vec<u64> = timeline.progress.iter().map(|p| p.elapsed_ms).collect();
+        assert_eq!(elapsed, [800, 1500]);
+        assert_eq!(timeline.progress[1].total, Some(100.0));
+        assert!(!timeline.is_cancelled());
+
+        // The token is gone with its request
+        assert!(observe(
+            &tracker,
+            &progress("t1", 100),
+            Direction::Out,
+            "log-7",
+            2100 * MS
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_cancellation_latency() {
+        let tracker = ProgressTracker::new();
+        observe(&tracker, &call(5, "t5"), Direction::In, "log-1", 0);
+        // A plain request without progress is not a timeline
+        observe(&tracker, &call(6, "t6"), Direction::In, "log-2", 0);
+        observe(
+            &tracker,
+            &json!({"jsonrpc": "2.0", "id": 6, "result": {"content": []}}),
+            Direction::Out,
+            "log-3",
+            100 * MS,
+        );
+
+        let cancel = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": {"requestId": 5, "reason": "User pressed stop"}
+        });
+        // Only the client sent request 5, so only it can cancel it
+        assert!(observe(&tracker, &cancel, Direction::Out, "log-4", 2500 * MS).is_none());
+        let link = observe(&tracker, &cancel, Direction::In, "log-5", 3000 * MS);
+        assert_eq!(link.as_deref(), Some("log-1"));
+
+        // Still in flight, but already a timeline
+        let timelines = tracker.timelines();
+        assert_eq!(timelines.len(), 1);
+        let cancellation = timelines[0].cancelled.as_ref().unwrap();
+        assert_eq!(cancellation.elapsed_ms, 3000);
+        assert_eq!(cancellation.reason.as_deref(), Some("User pressed stop"));
+        assert_eq!(cancellation.answered_after_ms, None);
+
+        // The server answers anyway
+        observe(
+            &tracker,
+            &json!({"jsonrpc": "2.0", "id": 5, "result": {"content": []}}),
+            Direction::Out,
+            "log-6",
+            3400 * MS,
+        );
+        let timelines = tracker.timelines();
+        let cancellation = timelines[0].cancelled.as_ref().unwrap();
+        assert_eq!(cancellation.answered_after_ms, Some(400));
+        assert_eq!(timelines[0].completed_ms, Some(3400));
+    }
+
+    #[test]
+    fn test_session_timelines() {
+        let message = |id: &str, at_ms: u64, direction: &str, content: Value| {
+            json!({
+                "id": id,
+                "timestamp_micros": at_ms * MS,
+                "relative_time_ms": at_ms,
+                "direction": direction,
+                "content": content,
+                "metadata": {
+                    "method": content.get("method"),
+                    "jsonrpc_id": content.get("id"),
+                    "injected": false,
+                    "modified": false,
+                    "size_bytes": 0
+                }
+            })
+        };
+        let session: RecordedSession = serde_json::from_value(json!({
+            "id": "s1",
+            "name": "test",
+            "started_at": 0,
+            "ended_at": null,
+            "messages": [
+                message("m1", 0, "toserver", call(1, "t1")),
+                message("m2", 400, "toclient", progress("t1", 10)),
+                message("m3", 1200, "toserver", json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/cancelled",
+                    "params": {"requestId": 1}
+                })),
+            ],
+            "metadata": {"transport": "stdio", "message_count": 3}
+        }))
+        .unwrap();
+
+        let timelines = session_timelines(&session);
+        assert_eq!(timelines.len(), 1);
+        assert_eq!(timelines[0].log_id, "m1");
+        assert_eq!(timelines[0].progress[0].log_id, "m2");
+        assert_eq!(timelines[0].cancelled.as_ref().unwrap().elapsed_ms, 1200);
+    }
+}
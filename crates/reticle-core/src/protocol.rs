@@ -1,15 +1,27 @@
 //! JSON-RPC Protocol Types
 //!
-//! Core types for MCP (Model Context Protocol) JSON-RPC messages.
+//! Core types for MCP (Model Context Protocol) JSON-RPC messages, typed
+//! views of method params and results in [`mcp`], the [`McpMessage`]
+//! model built from them, and [`conformance`] checks of live traffic
+//! against the protocol.
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fmt;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::request_tracker::Unanswered;
 use crate::token_counter::TokenCounter;
 
+pub mod conformance;
+pub mod mcp;
+pub mod message;
+
+use conformance::Violation;
+pub use message::McpMessage;
+
 /// Direction of message flow through the proxy
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum Direction {
     /// From host (client) to child (server) - incoming
@@ -31,6 +43,12 @@ pub enum MessageType {
     Raw,
     /// Error output from stderr
     Stderr,
+    /// Fault injected by the proxy for chaos testing (not real traffic)
+    Fault,
+    /// A request that timed out or was left open when the session ended
+    Unanswered,
+    /// A message that broke JSON-RPC 2.0 or the MCP lifecycle
+    Violation,
 }
 
 impl fmt::Display for Direction {
@@ -71,6 +89,22 @@ pub struct LogEntry {
     /// Server name for multi-server filtering
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub server_name: Option<String>,
+    /// Whether the message was edited by the debugger before being forwarded
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub modified: bool,
+    /// The message as originally received, if it was modified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<String>,
+    /// The message parsed once into the MCP model, for Rust consumers
+    ///
+    /// Not serialized; use [`LogEntry::mcp_message`] on entries read back
+    /// from JSON.
+    #[serde(skip)]
+    pub message: Option<McpMessage>,
+    /// For a progress or cancellation notification, the log entry of the
+    /// request it refers to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_log_id: Option<String>,
 }
 
 impl LogEntry {
@@ -80,17 +114,32 @@ impl LogEntry {
         session_id: String,
         direction: Direction,
         content: serde_json::Value,
+    ) -> Self {
+        let message = McpMessage::parse(&content);
+        Self::from_message(id, session_id, direction, &content, message)
+    }
+
+    /// Create a log entry from a JSON-RPC message parsed by the caller
+    ///
+    /// For proxies that hand the parsed message to the request tracker and
+    /// the other observers before logging it, so it is parsed only once.
+    pub fn from_message(
+        id: String,
+        session_id: String,
+        direction: Direction,
+        content: &serde_json::Value,
+        message: McpMessage,
     ) -> Self {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_micros() as u64;
 
-        let method = extract_method(&content);
-        let content_str = serde_json::to_string(&content).unwrap_or_default();
+        let method = message.method().map(String::from);
+        let content_str = serde_json::to_string(content).unwrap_or_default();
 
         // Count LLM-relevant tokens (extracts payload, not JSON-RPC overhead)
-        let token_count = TokenCounter::count_mcp_context_tokens(&content);
+        let token_count = TokenCounter::count_mcp_context_tokens(&message);
 
         Self {
             id,
@@ -103,6 +152,10 @@ impl LogEntry {
             message_type: MessageType::JsonRpc,
             token_count,
             server_name: None,
+            modified: false,
+            original: None,
+            message: Some(message),
+            request_log_id: None,
         }
     }
 
@@ -119,6 +172,98 @@ impl LogEntry {
         entry
     }
 
+    /// Set the name of the server the entry was exchanged with
+    pub fn with_server_name(mut self, server_name: &str) -> Self {
+        self.server_name = Some(server_name.to_string());
+        self
+    }
+
+    /// Mark the entry as modified, keeping the message as originally received
+    pub fn with_original(mut self, original: &serde_json::Value) -> Self {
+        self.modified = true;
+        self.original = Some(serde_json::to_string(original).unwrap_or_default());
+        self
+    }
+
+    /// Set the time since the request, for a response
+    pub fn with_duration(mut self, elapsed: Option<Duration>) -> Self {
+        self.duration_micros = elapsed.map(|d| d.as_micros() as u64);
+        self
+    }
+
+    /// Link a progress or cancellation notification to its request
+    pub fn with_request(mut self, request_log_id: Option<String>) -> Self {
+        self.request_log_id = request_log_id;
+        self
+    }
+
+    /// The entry's message in the MCP model
+    ///
+    /// Entries built by [`LogEntry::new`] carry it already; others are
+    /// parsed from their content. Raw text and the proxy's own reports
+    /// have none.
+    pub fn mcp_message(&self) -> Option<Cow<'_, McpMessage>> {
+        if let Some(message) = &self.message {
+            return Some(Cow::Borrowed(message));
+        }
+        if self.message_type != MessageType::JsonRpc {
+            return None;
+        }
+        let content = serde_json::from_str(&self.content).ok()?;
+        Some(Cow::Owned(McpMessage::parse(&content)))
+    }
+
+    /// Create a log entry recording a fault injected by the proxy
+    ///
+    /// `description` is a JSON object describing the fault; its `method`
+    /// is the method of the affected request.
+    pub fn new_fault(
+        id: String,
+        session_id: String,
+        direction: Direction,
+        description: serde_json::Value,
+    ) -> Self {
+        let mut entry = Self::new(id, session_id, direction, description);
+        entry.message_type = MessageType::Fault;
+        entry.token_count = 0;
+        entry.message = None;
+        entry
+    }
+
+    /// Create a log entry reporting a request that never got a response
+    ///
+    /// The entry has the request's direction and method, and the time it
+    /// waited as its duration.
+    pub fn new_unanswered(id: String, session_id: String, request: &Unanswered) -> Self {
+        let description = serde_json::to_value(request).unwrap_or_default();
+        let mut entry = Self::new(id, session_id, request.direction, description);
+        entry.message_type = MessageType::Unanswered;
+        entry.token_count = 0;
+        entry.message = None;
+        entry.duration_micros = Some(request.elapsed_ms * 1000);
+        entry
+    }
+
+    /// Create a log entry reporting a conformance violation
+    ///
+    /// `log_id` is the entry of the offending message, when it has one.
+    pub fn new_violation(
+        id: String,
+        session_id: String,
+        log_id: Option<&str>,
+        violation: &Violation,
+    ) -> Self {
+        let mut description = serde_json::to_value(violation).unwrap_or_default();
+        if let (Some(log_id), Some(object)) = (log_id, description.as_object_mut()) {
+            object.insert("log_id".to_string(), log_id.into());
+        }
+        let mut entry = Self::new(id, session_id, violation.direction, description);
+        entry.message_type = MessageType::Violation;
+        entry.token_count = 0;
+        entry.message = None;
+        entry
+    }
+
     /// Create a new log entry from raw text (non-JSON output)
     pub fn new_raw(
         id: String,
@@ -146,6 +291,10 @@ impl LogEntry {
             message_type,
             token_count,
             server_name: None,
+            modified: false,
+            original: None,
+            message: None,
+            request_log_id: None,
         }
     }
 
@@ -164,14 +313,6 @@ impl LogEntry {
     }
 }
 
-/// Extract the method field from a JSON-RPC message if present
-fn extract_method(value: &serde_json::Value) -> Option<String> {
-    value
-        .get("method")
-        .and_then(|m| m.as_str())
-        .map(|s| s.to_string())
-}
-
 /// JSON-RPC 2.0 Request structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
@@ -195,8 +336,7 @@ pub struct JsonRpcResponse {
 }
 
 /// JSON-RPC 2.0 Error structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct JsonRpcError {
     pub code: i64,
     pub message: String,
@@ -263,6 +403,62 @@ mod tests {
         assert!(entry.token_count > 0);
     }
 
+    #[test]
+    fn test_log_entry_message() {
+        use message::{McpResult, Request};
+
+        let content = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "resources/read",
+            "params": { "uri": "file:///tmp/notes.md" },
+            "id": 4
+        });
+        let entry = LogEntry::new(
+            "log-1".to_string(),
+            "session-1".to_string(),
+            Direction::In,
+            content,
+        );
+        let Some(McpMessage::Request {
+            request: Request::ReadResource(request),
+            ..
+        }) = &entry.message
+        else {
+            panic!("expected a resources/read request");
+        };
+        assert_eq!(request.uri, "file:///tmp/notes.md");
+
+        // The parsed message is not serialized, but can be recovered
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(!json.contains("\"message\""));
+        let read_back: LogEntry = serde_json::from_str(&json).unwrap();
+        assert!(read_back.message.is_none());
+        assert_eq!(read_back.mcp_message().as_deref(), entry.message.as_ref());
+
+        let response = LogEntry::new(
+            "log-2".to_string(),
+            "session-1".to_string(),
+            Direction::Out,
+            serde_json::json!({"jsonrpc": "2.0", "id": 4, "result": {"contents": []}}),
+        );
+        assert!(matches!(
+            response.message,
+            Some(McpMessage::Response {
+                result: McpResult::ReadResource(_),
+                ..
+            })
+        ));
+
+        let stderr = LogEntry::new_raw(
+            "log-3".to_string(),
+            "session-1".to_string(),
+            Direction::Out,
+            "warning: cache miss".to_string(),
+            MessageType::Stderr,
+        );
+        assert!(stderr.mcp_message().is_none());
+    }
+
     #[test]
     fn test_log_entry_with_server() {
         let content = serde_json::json!({"method": "test"});
@@ -296,12 +492,12 @@ mod tests {
     fn test_extract_method() {
         let with_method = serde_json::json!({"method": "test/method"});
         assert_eq!(
-            extract_method(&with_method),
-            Some("test/method".to_string())
+            McpMessage::parse(&with_method).method(),
+            Some("test/method")
         );
 
         let without_method = serde_json::json!({"result": {}});
-        assert_eq!(extract_method(&without_method), None);
+        assert_eq!(McpMessage::parse(&without_method).method(), None);
     }
 
     #[test]
@@ -352,4 +548,46 @@ mod tests {
         assert!(json.contains("\"error\""));
         assert!(json.contains("-32600"));
     }
+
+    #[test]
+    fn test_unanswered_entry() {
+        use crate::request_tracker::UnansweredReason;
+
+        let request = Unanswered {
+            id: serde_json::json!(4),
+            method: "tools/call".to_string(),
+            direction: Direction::In,
+            elapsed_ms: 1500,
+            reason: UnansweredReason::Timeout,
+        };
+        let entry = LogEntry::new_unanswered("u-1".to_string(), "s1".to_string(), &request);
+        assert_eq!(entry.message_type, MessageType::Unanswered);
+        assert_eq!(entry.method.as_deref(), Some("tools/call"));
+        assert_eq!(entry.duration_micros, Some(1_500_000));
+        assert!(entry.content.contains("\"reason\":\"timeout\""));
+    }
+
+    #[test]
+    fn test_violation_entry() {
+        let checker = conformance::ConformanceChecker::new();
+        let list = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"});
+        let violation = checker
+            .check(&McpMessage::parse(&list), Direction::In)
+            .pop()
+            .unwrap();
+
+        let entry = LogEntry::new_violation(
+            "v-1".to_string(),
+            "s1".to_string(),
+            Some("log-1"),
+            &violation,
+        );
+        assert_eq!(entry.message_type, MessageType::Violation);
+        assert_eq!(entry.direction, Direction::In);
+        assert_eq!(entry.method.as_deref(), Some("tools/list"));
+        assert_eq!(entry.token_count, 0);
+        let content: serde_json::Value = serde_json::from_str(&entry.content).unwrap();
+        assert_eq!(content["kind"], "request_before_initialize");
+        assert_eq!(content["log_id"], "log-1");
+    }
 }
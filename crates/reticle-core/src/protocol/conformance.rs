@@ -0,0 +1,831 @@
+//! MCP conformance checks on live traffic
+//!
+//! A [`ConformanceChecker`] follows one session through the proxy and
+//! reports where it breaks JSON-RPC 2.0 or the MCP lifecycle: requests
+//! before the `initialize` handshake completes, a missing
+//! `notifications/initialized`, reused request ids, responses with both
+//! `result` and `error`, responses to requests that are not pending, a
+//! `jsonrpc` other than `"2.0"`, methods the other side never declared a
+//! capability for, and non-JSON lines on a stdio stream. Tool calls are
+//! also checked against the schemas the server listed, through
+//! [`ToolSchemas`].
+//!
+//! Violations are only reported. The proxy forwards the message either way,
+//! since how clients cope with a broken server is what is being debugged.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::message::{McpResult, Request};
+use super::{Direction, McpMessage};
+use crate::tool_schemas::{SchemaError, SchemaMismatch, SchemaTarget, ToolSchemas};
+
+/// What rule a message broke
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    /// A request other than `ping` before the handshake allows it
+    RequestBeforeInitialize,
+    /// The client went on without sending `notifications/initialized`
+    MissingInitialized,
+    /// A request id the sender already used in this session
+    DuplicateId,
+    /// A response with both `result` and `error`
+    ResultAndError,
+    /// A response whose id matches no pending request
+    UnknownResponseId,
+    /// `jsonrpc` missing or not `"2.0"`
+    InvalidVersion,
+    /// A method that needs a capability the other side did not declare
+    UndeclaredCapability,
+    /// A line on a stdio stream that is not JSON
+    NonJsonLine,
+    /// `tools/call` arguments that do not match the tool's `inputSchema`
+    InvalidArguments,
+    /// A tool result whose `structuredContent` does not match its `outputSchema`
+    InvalidStructuredContent,
+}
+
+impl ViolationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ViolationKind::RequestBeforeInitialize => "request_before_initialize",
+            ViolationKind::MissingInitialized => "missing_initialized",
+            ViolationKind::DuplicateId => "duplicate_id",
+            ViolationKind::ResultAndError => "result_and_error",
+            ViolationKind::UnknownResponseId => "unknown_response_id",
+            ViolationKind::InvalidVersion => "invalid_version",
+            ViolationKind::UndeclaredCapability => "undeclared_capability",
+            ViolationKind::NonJsonLine => "non_json_line",
+            ViolationKind::InvalidArguments => "invalid_arguments",
+            ViolationKind::InvalidStructuredContent => "invalid_structured_content",
+        }
+    }
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One rule broken by one message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    /// Direction of the offending message
+    pub direction: Direction,
+    /// JSON-RPC id of the offending message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    pub message: String,
+    /// Where a tool call breaks its schema
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<SchemaError>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
+
+/// Violations found in a session so far
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionConformance {
+    pub session_id: String,
+    /// JSON-RPC messages and raw lines checked
+    pub messages_checked: u64,
+    pub violations: u64,
+    pub by_kind: BTreeMap<ViolationKind, u64>,
+}
+
+impl SessionConformance {
+    pub fn is_conformant(&self) -> bool {
+        self.violations == 0
+    }
+}
+
+impl fmt::Display for SessionConformance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_conformant() {
+            return write!(f, "no violations in {} messages", self.messages_checked);
+        }
+        let kinds: Vec<String> = self
+            .by_kind
+            .iter()
+            .map(|(kind, count)| format!("{count} {kind}"))
+            .collect();
+        write!(
+            f,
+            "{} violation{} in {} messages ({})",
+            self.violations,
+            if self.violations == 1 { "" } else { "s" },
+            self.messages_checked,
+            kinds.join(", ")
+        )
+    }
+}
+
+/// Which side of the session must have declared a capability
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Client,
+    Server,
+}
+
+impl Side {
+    fn of(direction: Direction) -> Self {
+        match direction {
+            Direction::In => Side::Client,
+            Direction::Out => Side::Server,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Side::Client => "client",
+            Side::Server => "server",
+        }
+    }
+}
+
+/// A method that may only be used when a capability was declared
+struct Gated {
+    method: &'static str,
+    /// Side that declares the capability
+    side: Side,
+    /// JSON pointer into that side's capabilities
+    capability: &'static str,
+    /// First protocol version with the capability (always gated if unset)
+    since: Option<&'static str>,
+}
+
+const fn gated(method: &'static str, side: Side, capability: &'static str) -> Gated {
+    Gated {
+        method,
+        side,
+        capability,
+        since: None,
+    }
+}
+
+/// Requests need the receiver's capability, notifications the sender's
+const GATED_METHODS: &[Gated] = &[
+    gated("tools/list", Side::Server, "/tools"),
+    gated("tools/call", Side::Server, "/tools"),
+    gated("prompts/list", Side::Server, "/prompts"),
+    gated("prompts/get", Side::Server, "/prompts"),
+    gated("resources/list", Side::Server, "/resources"),
+    gated("resources/templates/list", Side::Server, "/resources"),
+    gated("resources/read", Side::Server, "/resources"),
+    gated("resources/subscribe", Side::Server, "/resources/subscribe"),
+    gated(
+        "resources/unsubscribe",
+        Side::Server,
+        "/resources/subscribe",
+    ),
+    gated("logging/setLevel", Side::Server, "/logging"),
+    Gated {
+        since: Some("2025-03-26"),
+        ..gated("completion/complete", Side::Server, "/completions")
+    },
+    gated(
+        "notifications/tools/list_changed",
+        Side::Server,
+        "/tools/listChanged",
+    ),
+    gated(
+        "notifications/prompts/list_changed",
+        Side::Server,
+        "/prompts/listChanged",
+    ),
+    gated(
+        "notifications/resources/list_changed",
+        Side::Server,
+        "/resources/listChanged",
+    ),
+    gated(
+        "notifications/resources/updated",
+        Side::Server,
+        "/resources/subscribe",
+    ),
+    gated("notifications/message", Side::Server, "/logging"),
+    gated("sampling/createMessage", Side::Client, "/sampling"),
+    gated("roots/list", Side::Client, "/roots"),
+    gated("elicitation/create", Side::Client, "/elicitation"),
+    gated(
+        "notifications/roots/list_changed",
+        Side::Client,
+        "/roots/listChanged",
+    ),
+];
+
+#[derive(Debug, Default)]
+struct State {
+    /// Capabilities from the initialize request
+    client_capabilities: Option<Value>,
+    /// Capabilities from the initialize result; set once the handshake is answered
+    server_capabilities: Option<Value>,
+    protocol_version: Option<String>,
+    /// `notifications/initialized` was sent
+    initialized: bool,
+    missing_initialized_reported: bool,
+    /// Every request id used, by the direction it travelled in
+    used_ids: HashSet<(Direction, String)>,
+    /// Requests waiting for a response: method by direction and id
+    pending: HashMap<(Direction, String), String>,
+    messages_checked: u64,
+    by_kind: BTreeMap<ViolationKind, u64>,
+}
+
+/// Checks one session's traffic against JSON-RPC 2.0 and the MCP lifecycle
+#[derive(Debug, Default)]
+pub struct ConformanceChecker {
+    state: Mutex<State>,
+    schemas: Arc<ToolSchemas>,
+}
+
+impl ConformanceChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check tool calls against schemas shared with another observer of
+    /// the session, such as its recorder
+    pub fn with_tool_schemas(schemas: Arc<ToolSchemas>) -> Self {
+        Self {
+            state: Mutex::default(),
+            schemas,
+        }
+    }
+
+    /// The tool schemas the session's server listed, for sharing
+    pub fn tool_schemas(&self) -> Arc<ToolSchemas> {
+        self.schemas.clone()
+    }
+
+    /// Check a JSON message (or batch) passing through the proxy
+    pub fn check(&self, message: &McpMessage, direction: Direction) -> Vec<Violation> {
+        let mut state = self.state.lock().unwrap();
+        let mut violations = Vec::new();
+        for message in message.items() {
+            state.check(message, direction, &mut violations);
+        }
+        violations.extend(
+            self.schemas
+                .observe(message, direction)
+                .into_iter()
+                .map(|mismatch| schema_violation(mismatch, direction)),
+        );
+        state.count(&violations);
+        violations
+    }
+
+    /// Check a line of a stdio stream that did not parse as JSON
+    ///
+    /// Blank lines are tolerated; anything else breaks the newline-delimited
+    /// framing the stdio transport requires.
+    pub fn check_raw(&self, line: &str, direction: Direction) -> Option<Violation> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        let violation = Violation {
+            kind: ViolationKind::NonJsonLine,
+            direction,
+            id: None,
+            method: None,
+            message: match direction {
+                Direction::In => "client wrote a non-JSON line to stdin".to_string(),
+                Direction::Out => "server wrote a non-JSON line to stdout".to_string(),
+            },
+            errors: Vec::new(),
+        };
+        let mut state = self.state.lock().unwrap();
+        state.messages_checked += 1;
+        state.count(std::slice::from_ref(&violation));
+        Some(violation)
+    }
+
+    /// Report what the session never did, for when it ends
+    pub fn finish(&self) -> Vec<Violation> {
+        let mut state = self.state.lock().unwrap();
+        let mut violations = Vec::new();
+        if state.server_capabilities.is_some()
+            && !state.initialized
+            && !state.missing_initialized_reported
+        {
+            state.missing_initialized_reported = true;
+            violations.push(Violation {
+                kind: ViolationKind::MissingInitialized,
+                direction: Direction::In,
+                id: None,
+                method: None,
+                message: "session ended without notifications/initialized".to_string(),
+                errors: Vec::new(),
+            });
+        }
+        state.count(&violations);
+        violations
+    }
+
+    /// Violations found so far
+    pub fn summary(&self, session_id: &str) -> SessionConformance {
+        let state = self.state.lock().unwrap();
+        SessionConformance {
+            session_id: session_id.to_string(),
+            messages_checked: state.messages_checked,
+            violations: state.by_kind.values().sum(),
+            by_kind: state.by_kind.clone(),
+        }
+    }
+}
+
+/// Report a tool call that does not match its schema
+fn schema_violation(mismatch: SchemaMismatch, direction: Direction) -> Violation {
+    let kind = match mismatch.target {
+        SchemaTarget::Arguments => ViolationKind::InvalidArguments,
+        SchemaTarget::StructuredContent => ViolationKind::InvalidStructuredContent,
+    };
+    Violation {
+        kind,
+        direction,
+        message: mismatch.to_string(),
+        id: mismatch.id,
+        method: (direction == Direction::In).then(|| "tools/call".to_string()),
+        errors: mismatch.errors,
+    }
+}
+
+impl State {
+    fn count(&mut self, violations: &[Violation]) {
+        for violation in violations {
+            *self.by_kind.entry(violation.kind).or_default() += 1;
+        }
+    }
+
+    fn check(&mut self, message: &McpMessage, direction: Direction, out: &mut Vec<Violation>) {
+        // What breaks the envelope only shows in the JSON itself
+        let raw = match message {
+            McpMessage::Raw(Value::Object(object)) => Some(object),
+            McpMessage::Raw(_) => return,
+            _ => None,
+        };
+        self.messages_checked += 1;
+
+        let id = message.id();
+        let method = message.method();
+        let mut report = |kind, message: String| {
+            out.push(Violation {
+                kind,
+                direction,
+                id: id.cloned(),
+                method: method.map(String::from),
+                message,
+                errors: Vec::new(),
+            });
+        };
+
+        if let Some(object) = raw {
+            match object.get("jsonrpc") {
+                Some(Value::String(version)) if version == "2.0" => {}
+                Some(version) => report(
+                    ViolationKind::InvalidVersion,
+                    format!("jsonrpc is {version}, expected \"2.0\""),
+                ),
+                None => report(ViolationKind::InvalidVersion, "jsonrpc is missing".into()),
+            }
+        }
+
+        let sender = Side::of(direction).name();
+        match (method, id) {
+            (Some(method), Some(id)) => {
+                let key = (direction, id.to_string());
+                if !self.used_ids.insert(key.clone()) {
+                    report(
+                        ViolationKind::DuplicateId,
+                        format!("{sender} already used request id {id} in this session"),
+                    );
+                }
+                self.pending.insert(key, method.to_string());
+                if direction == Direction::In && method == "initialize" {
+                    self.client_capabilities = Some(client_capabilities(message));
+                }
+
+                if let Some(problem) = self.lifecycle(method, direction) {
+                    report(problem.0, problem.1);
+                }
+                if let Some(problem) = self.capability(method) {
+                    report(ViolationKind::UndeclaredCapability, problem);
+                }
+            }
+            (Some(method), None) => {
+                if direction == Direction::In && method == "notifications/initialized" {
+                    self.initialized = true;
+                }
+                if let Some(problem) = self.capability(method) {
+                    report(ViolationKind::UndeclaredCapability, problem);
+                }
+            }
+            (None, _) if message.is_response() => {
+                if raw.is_some_and(|o| o.contains_key("result") && o.contains_key("error")) {
+                    report(
+                        ViolationKind::ResultAndError,
+                        "response has both result and error".into(),
+                    );
+                }
+                // A null id answers a request too broken to read an id from
+                let Some(id) = id else { return };
+                let request_direction = match direction {
+                    Direction::In => Direction::Out,
+                    Direction::Out => Direction::In,
+                };
+                match self.pending.remove(&(request_direction, id.to_string())) {
+                    Some(request) if request == "initialize" && direction == Direction::Out => {
+                        if let Some((capabilities, version)) = server_handshake(message) {
+                            self.server_capabilities = Some(capabilities);
+                            self.protocol_version = version;
+                        }
+                    }
+                    Some(_) => {}
+                    None => report(
+                        ViolationKind::UnknownResponseId,
+                        format!("{sender} answered id {id}, which has no pending request"),
+                    ),
+                }
+            }
+            (None, _) => {}
+        }
+    }
+
+    /// Check where a request falls in the initialize handshake
+    fn lifecycle(&mut self, method: &str, direction: Direction) -> Option<(ViolationKind, String)> {
+        if method == "ping" {
+            return None;
+        }
+        match direction {
+            Direction::In if method == "initialize" => None,
+            Direction::In if self.server_capabilities.is_none() => Some((
+                ViolationKind::RequestBeforeInitialize,
+                format!("client sent {method} before the server answered initialize"),
+            )),
+            Direction::In if !self.initialized && !self.missing_initialized_reported => {
+                self.missing_initialized_reported = true;
+                Some((
+                    ViolationKind::MissingInitialized,
+                    format!("client sent {method} without sending notifications/initialized"),
+                ))
+            }
+            Direction::Out if !self.initialized => Some((
+                ViolationKind::RequestBeforeInitialize,
+                format!("server sent {method} before notifications/initialized"),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Check that a method's capability was declared during the handshake
+    fn capability(&self, method: &str) -> Option<String> {
+        let gated = GATED_METHODS.iter().find(|g| g.method == method)?;
+        let capabilities = match gated.side {
+            Side::Client => self.client_capabilities.as_ref(),
+            Side::Server => self.server_capabilities.as_ref(),
+        }?;
+        if let (Some(since), Some(version)) = (gated.since, self.protocol_version.as_deref()) {
+            if version < since {
+                return None;
+            }
+        }
+        let declared = match capabilities.pointer(gated.capability) {
+            None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+            Some(_) => true,
+        };
+        (!declared).then(|| {
+            let capability = gated.capability.trim_start_matches('/').replace('/', ".");
+            format!(
+                "{method} needs the {} to declare the `{capability}` capability",
+                gated.side.name()
+            )
+        })
+    }
+}
+
+/// The capabilities an initialize request declares
+fn client_capabilities(message: &McpMessage) -> Value {
+    match message {
+        McpMessage::Request {
+            request: Request::Initialize(params),
+            ..
+        } => capabilities(Some(&params.capabilities)),
+        McpMessage::Request { request, .. } => capabilities(
+            request
+                .params()
+                .as_ref()
+                .and_then(|p| p.get("capabilities")),
+        ),
+        McpMessage::Raw(value) => capabilities(value.pointer("/params/capabilities")),
+        _ => capabilities(None),
+    }
+}
+
+/// The capabilities and protocol version of a successful initialize response
+fn server_handshake(message: &McpMessage) -> Option<(Value, Option<String>)> {
+    let result = match message {
+        McpMessage::Response {
+            result: McpResult::Initialize(result),
+            ..
+        } => {
+            return Some((
+                capabilities(Some(&result.capabilities)),
+                Some(result.protocol_version.clone()),
+            ))
+        }
+        McpMessage::Response { result, .. } => result.to_value(),
+        McpMessage::Raw(value) => value.get("result")?.clone(),
+        _ => return None,
+    };
+    let version = result.get("protocolVersion").and_then(Value::as_str);
+    Some((
+        capabilities(result.get("capabilities")),
+        version.map(String::from),
+    ))
+}
+
+/// Declared capabilities (none if absent)
+fn capabilities(capabilities: Option<&Value>) -> Value {
+    capabilities
+        .filter(|c| c.is_object())
+        .cloned()
+        .unwrap_or_else(|| Value::Object(Default::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A checker that has been through a clean handshake
+    fn initialized(server_capabilities: Value) -> ConformanceChecker {
+        let checker = ConformanceChecker::new();
+        let handshake = [
+            (
+                json!({"jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {"roots": {"listChanged": true}},
+                    "clientInfo": {"name": "client", "version": "1.0"}
+                }}),
+                Direction::In,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "id": 0, "result": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": server_capabilities,
+                    "serverInfo": {"name": "server", "version": "1.0"}
+                }}),
+                Direction::Out,
+            ),
+            (
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                Direction::In,
+            ),
+        ];
+        for (message, direction) in handshake {
+            assert!(checker
+                .check(&McpMessage::parse(&message), direction)
+                .is_empty());
+        }
+        checker
+    }
+
+    fn kinds(violations: &[Violation]) -> Vec<ViolationKind> {
+        violations.iter().map(|v| v.kind).collect()
+    }
+
+    #[test]
+    fn test_conformant_session() {
+        let checker = initialized(json!({"tools": {}}));
+        let call =
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "x"}});
+        let result = json!({"jsonrpc": "2.0", "id": 1, "result": {"content": []}});
+        let roots = json!({"jsonrpc": "2.0", "id": "s1", "method": "roots/list"});
+        let roots_result = json!({"jsonrpc": "2.0", "id": "s1", "result": {"roots": []}});
+
+        assert!(checker
+            .check(&McpMessage::parse(&call), Direction::In)
+            .is_empty());
+        assert!(checker
+            .check(&McpMessage::parse(&result), Direction::Out)
+            .is_empty());
+        // The server numbers its own requests; it may reuse the client's ids
+        assert!(checker
+            .check(&McpMessage::parse(&roots), Direction::Out)
+            .is_empty());
+        assert!(checker
+            .check(&McpMessage::parse(&roots_result), Direction::In)
+            .is_empty());
+        assert!(checker.finish().is_empty());
+
+        let summary = checker.summary("s1");
+        assert!(summary.is_conformant());
+        assert_eq!(summary.messages_checked, 7);
+        assert_eq!(summary.to_string(), "no violations in 7 messages");
+    }
+
+    #[test]
+    fn test_lifecycle_violations() {
+        let checker = ConformanceChecker::new();
+        let list = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"});
+        let violations = checker.check(&McpMessage::parse(&list), Direction::In);
+        assert_eq!(kinds(&violations), [ViolationKind::RequestBeforeInitialize]);
+        assert_eq!(violations[0].method.as_deref(), Some("tools/list"));
+        assert_eq!(violations[0].id, Some(json!(1)));
+
+        // Pings are allowed at any time
+        let ping = json!({"jsonrpc": "2.0", "id": 2, "method": "ping"});
+        assert!(checker
+            .check(&McpMessage::parse(&ping), Direction::In)
+            .is_empty());
+
+        let init = json!({"jsonrpc": "2.0", "id": 3, "method": "initialize", "params": {}});
+        let init_result =
+            json!({"jsonrpc": "2.0", "id": 3, "result": {"capabilities": {"tools": {}}}});
+        checker.check(&McpMessage::parse(&init), Direction::In);
+        checker.check(&McpMessage::parse(&init_result), Direction::Out);
+
+        // The server may not send requests until the client says it is initialized
+        let sampling = json!({"jsonrpc": "2.0", "id": 1, "method": "sampling/createMessage"});
+        let violations = checker.check(&McpMessage::parse(&sampling), Direction::Out);
+        assert!(violations.contains(&Violation {
+            kind: ViolationKind::RequestBeforeInitialize,
+            direction: Direction::Out,
+            id: Some(json!(1)),
+            method: Some("sampling/createMessage".to_string()),
+            message: "server sent sampling/createMessage before notifications/initialized"
+                .to_string(),
+            errors: Vec::new(),
+        }));
+
+        // Skipping notifications/initialized is reported once
+        let list = json!({"jsonrpc": "2.0", "id": 4, "method": "tools/list"});
+        let again = json!({"jsonrpc": "2.0", "id": 5, "method": "tools/list"});
+        assert_eq!(
+            kinds(&checker.check(&McpMessage::parse(&list), Direction::In)),
+            [ViolationKind::MissingInitialized]
+        );
+        assert!(checker
+            .check(&McpMessage::parse(&again), Direction::In)
+            .is_empty());
+        assert!(checker.finish().is_empty());
+    }
+
+    #[test]
+    fn test_missing_initialized_at_end() {
+        let checker = ConformanceChecker::new();
+        let init = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}});
+        let init_result = json!({"jsonrpc": "2.0", "id": 1, "result": {"capabilities": {}}});
+        checker.check(&McpMessage::parse(&init), Direction::In);
+        checker.check(&McpMessage::parse(&init_result), Direction::Out);
+        assert_eq!(
+            kinds(&checker.finish()),
+            [ViolationKind::MissingInitialized]
+        );
+        assert!(checker.finish().is_empty());
+    }
+
+    #[test]
+    fn test_jsonrpc_violations() {
+        let checker = initialized(json!({"tools": {}}));
+        let call = json!({"jsonrpc": "2.0", "id": 7, "method": "tools/call"});
+        assert!(checker
+            .check(&McpMessage::parse(&call), Direction::In)
+            .is_empty());
+        assert_eq!(
+            kinds(&checker.check(&McpMessage::parse(&call), Direction::In)),
+            [ViolationKind::DuplicateId]
+        );
+
+        let both =
+            json!({"jsonrpc": "2.0", "id": 7, "result": {}, "error": {"code": 1, "message": "x"}});
+        assert_eq!(
+            kinds(&checker.check(&McpMessage::parse(&both), Direction::Out)),
+            [ViolationKind::ResultAndError]
+        );
+
+        let stray = json!({"jsonrpc": "2.0", "id": 99, "result": {}});
+        assert_eq!(
+            kinds(&checker.check(&McpMessage::parse(&stray), Direction::Out)),
+            [ViolationKind::UnknownResponseId]
+        );
+        // A parse error response has no id to match
+        let parse_error =
+            json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32700, "message": "x"}});
+        assert!(checker
+            .check(&McpMessage::parse(&parse_error), Direction::Out)
+            .is_empty());
+
+        let old = json!({"jsonrpc": "1.0", "method": "notifications/progress"});
+        let missing = json!({"method": "notifications/progress"});
+        let violations = checker.check(&McpMessage::parse(&json!([old, missing])), Direction::Out);
+        assert_eq!(
+            kinds(&violations),
+            [ViolationKind::InvalidVersion, ViolationKind::InvalidVersion]
+        );
+        assert_eq!(
+            violations[0].message,
+            "jsonrpc is \"1.0\", expected \"2.0\""
+        );
+        assert_eq!(violations[1].message, "jsonrpc is missing");
+    }
+
+    #[test]
+    fn test_undeclared_capabilities() {
+        let checker = initialized(json!({"tools": {"listChanged": false}, "logging": {}}));
+
+        let prompts = json!({"jsonrpc": "2.0", "id": 1, "method": "prompts/list"});
+        let violations = checker.check(&McpMessage::parse(&prompts), Direction::In);
+        assert_eq!(kinds(&violations), [ViolationKind::UndeclaredCapability]);
+        assert_eq!(
+            violations[0].message,
+            "prompts/list needs the server to declare the `prompts` capability"
+        );
+
+        let changed = json!({"jsonrpc": "2.0", "method": "notifications/tools/list_changed"});
+        assert_eq!(
+            kinds(&checker.check(&McpMessage::parse(&changed), Direction::Out)),
+            [ViolationKind::UndeclaredCapability]
+        );
+        let log = json!({"jsonrpc": "2.0", "method": "notifications/message", "params": {}});
+        assert!(checker
+            .check(&McpMessage::parse(&log), Direction::Out)
+            .is_empty());
+
+        // The client declared roots but not sampling
+        let sampling = json!({"jsonrpc": "2.0", "id": 2, "method": "sampling/createMessage"});
+        let violations = checker.check(&McpMessage::parse(&sampling), Direction::Out);
+        assert_eq!(
+            violations[0].message,
+            "sampling/createMessage needs the client to declare the `sampling` capability"
+        );
+        let roots = json!({"jsonrpc": "2.0", "method": "notifications/roots/list_changed"});
+        assert!(checker
+            .check(&McpMessage::parse(&roots), Direction::In)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_schema_violations() {
+        let checker = initialized(json!({"tools": {}}));
+        let list = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"});
+        let tools = json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": [{
+            "name": "add",
+            "inputSchema": {"type": "object", "properties": {"a": {"type": "number"}}},
+            "outputSchema": {"type": "object", "required": ["sum"]}
+        }]}});
+        let call = json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+            "params": {"name": "add", "arguments": {"a": "one"}}});
+        let result =
+            json!({"jsonrpc": "2.0", "id": 2, "result": {"content": [], "structuredContent": {}}});
+        checker.check(&McpMessage::parse(&list), Direction::In);
+        checker.check(&McpMessage::parse(&tools), Direction::Out);
+
+        let violations = checker.check(&McpMessage::parse(&call), Direction::In);
+        assert_eq!(kinds(&violations), [ViolationKind::InvalidArguments]);
+        assert_eq!(violations[0].errors[0].path, "/a");
+        let violations = checker.check(&McpMessage::parse(&result), Direction::Out);
+        assert_eq!(
+            kinds(&violations),
+            [ViolationKind::InvalidStructuredContent]
+        );
+        assert_eq!(violations[0].id, Some(json!(2)));
+
+        let summary = checker.summary("s1");
+        assert_eq!(summary.by_kind[&ViolationKind::InvalidArguments], 1);
+        assert_eq!(summary.by_kind[&ViolationKind::InvalidStructuredContent], 1);
+    }
+
+    #[test]
+    fn test_non_json_lines() {
+        let checker = ConformanceChecker::new();
+        let violation = checker
+            .check_raw("Server listening on port 3000", Direction::Out)
+            .unwrap();
+        assert_eq!(violation.kind, ViolationKind::NonJsonLine);
+        assert_eq!(violation.message, "server wrote a non-JSON line to stdout");
+        assert!(checker.check_raw("  ", Direction::Out).is_none());
+
+        let summary = checker.summary("s1");
+        assert_eq!(summary.violations, 1);
+        assert_eq!(summary.by_kind[&ViolationKind::NonJsonLine], 1);
+        assert_eq!(
+            summary.to_string(),
+            "1 violation in 1 messages (1 non_json_line)"
+        );
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["by_kind"]["non_json_line"], 1);
+    }
+}
@@ -0,0 +1,835 @@
+//! Typed views of MCP params and results
+//!
+//! These follow the 2025-06-18 schema. [`McpMessage`](super::McpMessage)
+//! parses every message into them once; traffic from a server under
+//! debugging is not guaranteed to be well formed, so what does not fit
+//! stays raw JSON there. Params and results keep their `_meta` in `meta`
+//! and top-level fields they do not know in `extra`, so older and newer
+//! peers still parse and nothing they sent is lost when the types are
+//! serialized again.
+//!
+//! Each request type implements [`McpRequest`], which ties it to its method
+//! name and result type:
+//!
+//! ```
+//! use reticle_core::protocol::mcp::{ElicitRequest, McpRequest};
+//!
+//! let message = serde_json::json!({
+//!     "jsonrpc": "2.0",
+//!     "id": 1,
+//!     "method": "elicitation/create",
+//!     "params": {
+//!         "message": "Which branch?",
+//!         "requestedSchema": {"type": "object", "properties": {}}
+//!     }
+//! });
+//! let request = ElicitRequest::from_message(&message).unwrap();
+//! assert_eq!(request.message, "Which branch?");
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// A request with a known method, params, and result
+pub trait McpRequest: DeserializeOwned {
+    /// The JSON-RPC method
+    const METHOD: &'static str;
+    /// What a successful response carries in `result`
+    type Result: DeserializeOwned;
+
+    /// The typed params of `message`, if it is this request
+    ///
+    /// Missing params parse as an empty object.
+    fn from_message(message: &Value) -> Option<Self> {
+        if message.get("method").and_then(Value::as_str) != Some(Self::METHOD) {
+            return None;
+        }
+        let params = message
+            .get("params")
+            .cloned()
+            .unwrap_or_else(|| Value::Object(Map::new()));
+        serde_json::from_value(params).ok()
+    }
+
+    /// The typed result of a response to this request
+    ///
+    /// Responses carry no method, so the caller has to know which request
+    /// `message` answers.
+    fn result_of(message: &Value) -> Option<Self::Result> {
+        serde_json::from_value(message.get("result")?.clone()).ok()
+    }
+}
+
+/// A result with no fields, as for `logging/setLevel`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmptyResult {
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Params with no fields, as for `ping`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmptyParams {
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Who a message is from in sampling and prompts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+/// One item of content in a tool result, prompt, or sampling message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
+    Audio {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
+    /// A link to a resource the client can read later (2025-06-18)
+    ResourceLink(ResourceLink),
+    /// A resource embedded in the message
+    Resource {
+        resource: ResourceContents,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
+    /// A content type this version of Reticle does not know
+    #[serde(other)]
+    Unknown,
+}
+
+/// A resource referenced from a tool result instead of embedded in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLink {
+    pub uri: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The contents of a resource, as text or base64 `blob`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Name and version of a client or server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Implementation {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `initialize`, the first request of every session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeRequest {
+    pub protocol_version: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub capabilities: Value,
+    pub client_info: Implementation,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for InitializeRequest {
+    const METHOD: &'static str = "initialize";
+    type Result = InitializeResult;
+}
+
+/// The result of `initialize`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResult {
+    pub protocol_version: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub capabilities: Value,
+    pub server_info: Implementation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Params of the `*/list` requests, which page with an opaque cursor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaginatedRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A tool as listed by `tools/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub input_schema: Value,
+    /// Schema of the tool's `structuredContent` (2025-06-18)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The result of `tools/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListToolsResult {
+    pub tools: Vec<Tool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A resource as listed by `resources/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The result of `resources/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourcesResult {
+    pub resources: Vec<Resource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A family of resources described by an RFC 6570 URI template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The result of `resources/templates/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourceTemplatesResult {
+    pub resource_templates: Vec<ResourceTemplate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `resources/read`, and the params of `resources/subscribe` and
+/// `resources/unsubscribe`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceRequest {
+    pub uri: String,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for ResourceRequest {
+    const METHOD: &'static str = "resources/read";
+    type Result = ReadResourceResult;
+}
+
+/// The result of `resources/read`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadResourceResult {
+    pub contents: Vec<ResourceContents>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An argument a prompt template takes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A prompt as listed by `prompts/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Prompt {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Vec<PromptArgument>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The result of `prompts/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPromptsResult {
+    pub prompts: Vec<Prompt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `prompts/get`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetPromptRequest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<BTreeMap<String, String>>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for GetPromptRequest {
+    const METHOD: &'static str = "prompts/get";
+    type Result = GetPromptResult;
+}
+
+/// One message of a rendered prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: Role,
+    pub content: ContentBlock,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The result of `prompts/get`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetPromptResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `tools/call`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallToolRequest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Map<String, Value>>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for CallToolRequest {
+    const METHOD: &'static str = "tools/call";
+    type Result = CallToolResult;
+}
+
+/// The result of `tools/call`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResult {
+    #[serde(default)]
+    pub content: Vec<ContentBlock>,
+    /// Output matching the tool's `outputSchema` (2025-06-18)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl CallToolResult {
+    /// The resource links in the result's content
+    pub fn resource_links(&self) -> impl Iterator<Item = &ResourceLink> {
+        self.content.iter().filter_map(|block| match block {
+            ContentBlock::ResourceLink(link) => Some(link),
+            _ => None,
+        })
+    }
+}
+
+/// `elicitation/create`, sent by the server to ask the user for input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitRequest {
+    /// What to ask the user
+    pub message: String,
+    /// A flat object schema for the answer
+    pub requested_schema: Value,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for ElicitRequest {
+    const METHOD: &'static str = "elicitation/create";
+    type Result = ElicitResult;
+}
+
+/// How the user responded to an elicitation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+/// The result of `elicitation/create`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    /// The user's answer, present when they accepted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Map<String, Value>>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// What a `completion/complete` request completes an argument of
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+}
+
+/// The argument being completed and what has been typed so far
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    pub value: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Arguments already resolved, for completions that depend on them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompletionContext {
+    #[serde(default)]
+    pub arguments: BTreeMap<String, String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `completion/complete`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompleteRequest {
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    pub argument: CompletionArgument,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<CompletionContext>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for CompleteRequest {
+    const METHOD: &'static str = "completion/complete";
+    type Result = CompleteResult;
+}
+
+/// Suggested values for a completion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    pub values: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_more: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The result of `completion/complete`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompleteResult {
+    pub completion: Completion,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Severity of a log message, in syslog order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+/// `logging/setLevel`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetLevelRequest {
+    pub level: LoggingLevel,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for SetLevelRequest {
+    const METHOD: &'static str = "logging/setLevel";
+    type Result = EmptyResult;
+}
+
+/// `roots/list`, sent by the server to ask which directories it may use
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ListRootsRequest {
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for ListRootsRequest {
+    const METHOD: &'static str = "roots/list";
+    type Result = ListRootsResult;
+}
+
+/// A directory or file the client exposes to the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Root {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The result of `roots/list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListRootsResult {
+    pub roots: Vec<Root>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// One message of a sampling conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingMessage {
+    pub role: Role,
+    pub content: ContentBlock,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `sampling/createMessage`, sent by the server to run the client's LLM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageRequest {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    pub max_tokens: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_context: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpRequest for CreateMessageRequest {
+    const METHOD: &'static str = "sampling/createMessage";
+    type Result = CreateMessageResult;
+}
+
+/// The result of `sampling/createMessage`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: Role,
+    pub content: ContentBlock,
+    /// The model the client chose
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `notifications/cancelled`, sent by either side to abandon a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledNotification {
+    /// Id of the request being cancelled
+    pub request_id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `notifications/progress`, about a request that was sent a
+/// `_meta.progressToken`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressNotification {
+    pub progress_token: Value,
+    pub progress: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `notifications/message`, a log message from the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggingMessageNotification {
+    pub level: LoggingLevel,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    pub data: Value,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_result_with_links_and_structured_content() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "result": {
+                "content": [
+                    {"type": "text", "text": "2 matches"},
+                    {
+                        "type": "resource_link",
+                        "uri": "file:///src/main.rs",
+                        "name": "main.rs",
+                        "mimeType": "text/x-rust"
+                    },
+                    {"type": "hologram", "frames": 3}
+                ],
+                "structuredContent": {"matches": 2}
+            }
+        });
+
+        let result = CallToolRequest::result_of(&response).unwrap();
+        assert_eq!(result.content.len(), 3);
+        assert_eq!(result.content[2], ContentBlock::Unknown);
+        assert_eq!(result.structured_content, Some(json!({"matches": 2})));
+        assert_eq!(result.is_error, None);
+
+        let links: Vec<_> = result.resource_links().collect();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].uri, "file:///src/main.rs");
+        assert_eq!(links[0].mime_type.as_deref(), Some("text/x-rust"));
+    }
+
+    #[test]
+    fn test_requests_match_their_method() {
+        let complete = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "completion/complete",
+            "params": {
+                "ref": {"type": "ref/prompt", "name": "code_review"},
+                "argument": {"name": "language", "value": "ru"},
+                "context": {"arguments": {"framework": "axum"}}
+            }
+        });
+        let request = CompleteRequest::from_message(&complete).unwrap();
+        assert_eq!(
+            request.reference,
+            CompletionReference::Prompt {
+                name: "code_review".to_string()
+            }
+        );
+        assert_eq!(request.argument.value, "ru");
+        assert_eq!(request.context.unwrap().arguments["framework"], "axum");
+        assert!(SetLevelRequest::from_message(&complete).is_none());
+
+        let set_level = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "logging/setLevel",
+            "params": {"level": "warning"}
+        });
+        let request = SetLevelRequest::from_message(&set_level).unwrap();
+        assert_eq!(request.level, LoggingLevel::Warning);
+        assert!(request.level > LoggingLevel::Info);
+
+        // roots/list has no params
+        let roots = json!({"jsonrpc": "2.0", "id": 3, "method": "roots/list"});
+        assert!(ListRootsRequest::from_message(&roots).is_some());
+    }
+
+    #[test]
+    fn test_server_to_client_results() {
+        let elicit = json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "result": {"action": "accept", "content": {"branch": "main"}}
+        });
+        let result = ElicitRequest::result_of(&elicit).unwrap();
+        assert_eq!(result.action, ElicitAction::Accept);
+        assert_eq!(result.content.unwrap()["branch"], "main");
+
+        let roots = json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "result": {"roots": [{"uri": "file:///home/demo/project", "name": "project"}]}
+        });
+        let result = ListRootsRequest::result_of(&roots).unwrap();
+        assert_eq!(result.roots[0].name.as_deref(), Some("project"));
+
+        let sampled = json!({
+            "jsonrpc": "2.0",
+            "id": 6,
+            "result": {
+                "role": "assistant",
+                "content": {"type": "text", "text": "Looks good"},
+                "model": "some-model",
+                "stopReason": "endTurn"
+            }
+        });
+        let result = CreateMessageRequest::result_of(&sampled).unwrap();
+        assert_eq!(result.role, Role::Assistant);
+        assert_eq!(result.stop_reason.as_deref(), Some("endTurn"));
+    }
+}
@@ -0,0 +1,841 @@
+//! Typed MCP message model
+//!
+//! [`McpMessage::parse`] turns one JSON-RPC message into a request,
+//! notification, response, or error with typed params and results from
+//! [`super::mcp`], so code built on `reticle-core` does not have to pick
+//! fields out of `serde_json::Value` itself. [`LogEntry`](super::LogEntry)
+//! carries the parsed message next to its raw content, and the request
+//! tracker, token counter, conformance checker, and the rest take it from
+//! there instead of parsing the message again.
+//!
+//! Parsing never fails and is lossless: [`McpMessage::to_value`] gives back
+//! the message as it was sent. Typed params and results keep `_meta` and
+//! unknown fields; a method Reticle does not know, or params that do not
+//! fit the schema, become [`Request::Other`] or [`Notification::Other`] with
+//! the params as they were; a result that does not fit becomes
+//! [`McpResult::Other`]; and anything that is not a well-formed JSON-RPC 2.0
+//! message is kept as [`McpMessage::Raw`]. The one normalization is that
+//! empty `params` come back as no params, which means the same thing.
+//!
+//! A response does not name its method. [`McpMessage::parse_response`]
+//! types the result for a known request; [`McpMessage::parse`] infers the
+//! method from the shape of the result, as the token counter does.
+
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+
+use super::mcp::*;
+use super::JsonRpcError;
+
+/// One JSON-RPC message, parsed into the MCP model
+#[derive(Debug, Clone, PartialEq)]
+pub enum McpMessage {
+    Request {
+        id: Value,
+        request: Request,
+    },
+    Notification(Notification),
+    Response {
+        id: Value,
+        result: McpResult,
+    },
+    Error {
+        id: Value,
+        error: JsonRpcError,
+    },
+    /// A JSON-RPC batch
+    Batch(Vec<McpMessage>),
+    /// Not a well-formed JSON-RPC 2.0 message; the JSON as it was
+    Raw(Value),
+}
+
+impl McpMessage {
+    /// Parse a message, inferring what a response answers from its result
+    pub fn parse(value: &Value) -> Self {
+        Self::parse_with(value, None)
+    }
+
+    /// Parse a response to a `method` request
+    ///
+    /// Messages other than responses parse as with [`McpMessage::parse`].
+    pub fn parse_response(value: &Value, method: &str) -> Self {
+        Self::parse_with(value, Some(method))
+    }
+
+    fn parse_with(value: &Value, method: Option<&str>) -> Self {
+        let object = match value {
+            Value::Object(object) => object,
+            Value::Array(items) if !items.is_empty() => {
+                return McpMessage::Batch(
+                    items
+                        .iter()
+                        .map(|item| Self::parse_with(item, method))
+                        .collect(),
+                )
+            }
+            _ => return McpMessage::Raw(value.clone()),
+        };
+        Self::parse_object(object, method).unwrap_or_else(|| McpMessage::Raw(value.clone()))
+    }
+
+    /// Parse a message object, if its envelope is well formed
+    fn parse_object(object: &Map<String, Value>, method: Option<&str>) -> Option<Self> {
+        if object.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+            return None;
+        }
+        let envelope = |keys: &[&str]| {
+            object
+                .keys()
+                .all(|key| key == "jsonrpc" || keys.contains(&key.as_str()))
+        };
+        let valid_id = |id: &&Value| id.is_string() || id.is_number();
+        let id = object.get("id");
+
+        if let Some(name) = object.get("method").and_then(Value::as_str) {
+            if !envelope(&["id", "method", "params"]) {
+                return None;
+            }
+            let params = object.get("params");
+            return match id {
+                None => Some(McpMessage::Notification(Notification::parse(name, params))),
+                Some(id) => Some(McpMessage::Request {
+                    id: id.clone(),
+                    request: Request::parse(name, params),
+                })
+                .filter(|_| valid_id(&id)),
+            };
+        }
+
+        // An error may have a null id, when the request could not be read
+        if let Some(error) = object.get("error") {
+            let id = id.filter(|id| id.is_null() || valid_id(id))?;
+            if !envelope(&["id", "error"]) {
+                return None;
+            }
+            return Some(McpMessage::Error {
+                id: id.clone(),
+                error: lossless(error)?,
+            });
+        }
+
+        let id = id.filter(valid_id)?;
+        let result = object.get("result")?;
+        if !envelope(&["id", "result"]) {
+            return None;
+        }
+        Some(McpMessage::Response {
+            id: id.clone(),
+            result: McpResult::parse(method.or_else(|| infer_result_method(result)), result),
+        })
+    }
+
+    /// The message as it was sent
+    pub fn to_value(&self) -> Value {
+        let mut object = Map::new();
+        object.insert("jsonrpc".to_string(), "2.0".into());
+        match self {
+            McpMessage::Request { id, request } => {
+                object.insert("id".to_string(), id.clone());
+                object.insert("method".to_string(), request.method().into());
+                if let Some(params) = request.params() {
+                    object.insert("params".to_string(), params);
+                }
+            }
+            McpMessage::Notification(notification) => {
+                object.insert("method".to_string(), notification.method().into());
+                if let Some(params) = notification.params() {
+                    object.insert("params".to_string(), params);
+                }
+            }
+            McpMessage::Response { id, result } => {
+                object.insert("id".to_string(), id.clone());
+                object.insert("result".to_string(), result.to_value());
+            }
+            McpMessage::Error { id, error } => {
+                object.insert("id".to_string(), id.clone());
+                object.insert("error".to_string(), to_value(error));
+            }
+            McpMessage::Batch(items) => return items.iter().map(McpMessage::to_value).collect(),
+            McpMessage::Raw(value) => return value.clone(),
+        }
+        Value::Object(object)
+    }
+
+    /// The method of a request or notification
+    ///
+    /// A raw message that still names a method has it too, so malformed
+    /// traffic can be labelled and checked.
+    pub fn method(&self) -> Option<&str> {
+        match self {
+            McpMessage::Request { request, .. } => Some(request.method()),
+            McpMessage::Notification(notification) => Some(notification.method()),
+            McpMessage::Raw(value) => value.get("method")?.as_str(),
+            _ => None,
+        }
+    }
+
+    /// The id of a request, response, or error
+    ///
+    /// A null id counts as none. A raw message with an id has it too.
+    pub fn id(&self) -> Option<&Value> {
+        let id = match self {
+            McpMessage::Request { id, .. }
+            | McpMessage::Response { id, .. }
+            | McpMessage::Error { id, .. } => id,
+            McpMessage::Raw(value) => value.get("id")?,
+            _ => return None,
+        };
+        Some(id).filter(|id| !id.is_null())
+    }
+
+    /// A response with its result typed as the result of a `method` request
+    ///
+    /// Results whose shape already tells what they answer are borrowed.
+    pub fn answering(&self, method: &str) -> Cow<'_, McpMessage> {
+        match self {
+            McpMessage::Response { id, result } if result.method() != Some(method) => {
+                Cow::Owned(McpMessage::Response {
+                    id: id.clone(),
+                    result: McpResult::parse(Some(method), &result.to_value()),
+                })
+            }
+            message => Cow::Borrowed(message),
+        }
+    }
+
+    /// The params of a request or notification, including a raw one
+    pub fn params(&self) -> Option<Value> {
+        match self {
+            McpMessage::Request { request, .. } => request.params(),
+            McpMessage::Notification(notification) => notification.params(),
+            McpMessage::Raw(value) => value.get("params").cloned(),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a response or an error, including a raw one
+    pub fn is_response(&self) -> bool {
+        match self {
+            McpMessage::Response { .. } | McpMessage::Error { .. } => true,
+            McpMessage::Raw(value) => {
+                value.get("method").is_none()
+                    && (value.get("result").is_some() || value.get("error").is_some())
+            }
+            _ => false,
+        }
+    }
+
+    /// The `_meta` of a request's or notification's params
+    pub fn meta(&self) -> Option<&Map<String, Value>> {
+        match self {
+            McpMessage::Request { request, .. } => request.meta(),
+            McpMessage::Notification(notification) => notification.meta(),
+            _ => None,
+        }
+    }
+
+    /// The token a request asks progress notifications to be sent with
+    pub fn progress_token(&self) -> Option<&Value> {
+        self.meta()?.get("progressToken")
+    }
+
+    /// The tool a `tools/call` request calls
+    pub fn tool_name(&self) -> Option<&str> {
+        match self {
+            McpMessage::Request {
+                request: Request::CallTool(call),
+                ..
+            } => Some(&call.name),
+            _ => None,
+        }
+    }
+
+    /// The messages of a batch, or the message itself
+    pub fn items(&self) -> std::slice::Iter<'_, McpMessage> {
+        match self {
+            McpMessage::Batch(items) => items.iter(),
+            message => std::slice::from_ref(message).iter(),
+        }
+    }
+}
+
+impl Serialize for McpMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+/// Parse `value` as `T`, if `T` gives all of it back
+fn lossless<T: DeserializeOwned + Serialize>(value: &Value) -> Option<T> {
+    let typed: T = serde_json::from_value(value.clone()).ok()?;
+    same(&serde_json::to_value(&typed).ok()?, value).then_some(typed)
+}
+
+/// Parse `params` as `T`, with missing params as an empty object
+fn typed<T: DeserializeOwned + Serialize>(params: Option<&Value>) -> Option<T> {
+    match params {
+        Some(params) => lossless(params),
+        None => lossless(&Value::Object(Map::new())),
+    }
+}
+
+/// Serialize typed params, leaving out empty ones
+fn params_value<T: Serialize>(params: &T) -> Option<Value> {
+    Some(to_value(params)).filter(|params| !matches!(params, Value::Object(o) if o.is_empty()))
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// Whether two values are equal, counting `1` and `1.0` as the same number
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x == y || x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| same(x, y))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(key, x)| y.get(key).is_some_and(|y| same(x, y)))
+        }
+        _ => a == b,
+    }
+}
+
+/// A request from either side, by method
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    Initialize(InitializeRequest),
+    Ping(EmptyParams),
+    ListTools(PaginatedRequest),
+    CallTool(CallToolRequest),
+    ListResources(PaginatedRequest),
+    ListResourceTemplates(PaginatedRequest),
+    ReadResource(ResourceRequest),
+    Subscribe(ResourceRequest),
+    Unsubscribe(ResourceRequest),
+    ListPrompts(PaginatedRequest),
+    GetPrompt(GetPromptRequest),
+    Complete(CompleteRequest),
+    SetLevel(SetLevelRequest),
+    CreateMessage(CreateMessageRequest),
+    Elicit(ElicitRequest),
+    ListRoots(ListRootsRequest),
+    /// An unknown method, or params that do not fit the known one
+    Other {
+        method: String,
+        params: Option<Value>,
+    },
+}
+
+impl Request {
+    fn parse(method: &str, params: Option<&Value>) -> Self {
+        let request = match method {
+            "initialize" => typed(params).map(Request::Initialize),
+            "ping" => typed(params).map(Request::Ping),
+            "tools/list" => typed(params).map(Request::ListTools),
+            "tools/call" => typed(params).map(Request::CallTool),
+            "resources/list" => typed(params).map(Request::ListResources),
+            "resources/templates/list" => typed(params).map(Request::ListResourceTemplates),
+            "resources/read" => typed(params).map(Request::ReadResource),
+            "resources/subscribe" => typed(params).map(Request::Subscribe),
+            "resources/unsubscribe" => typed(params).map(Request::Unsubscribe),
+            "prompts/list" => typed(params).map(Request::ListPrompts),
+            "prompts/get" => typed(params).map(Request::GetPrompt),
+            "completion/complete" => typed(params).map(Request::Complete),
+            "logging/setLevel" => typed(params).map(Request::SetLevel),
+            "sampling/createMessage" => typed(params).map(Request::CreateMessage),
+            "elicitation/create" => typed(params).map(Request::Elicit),
+            "roots/list" => typed(params).map(Request::ListRoots),
+            _ => None,
+        };
+        request.unwrap_or_else(|| Request::Other {
+            method: method.to_string(),
+            params: params.cloned(),
+        })
+    }
+
+    /// The JSON-RPC method
+    pub fn method(&self) -> &str {
+        match self {
+            Request::Initialize(_) => "initialize",
+            Request::Ping(_) => "ping",
+            Request::ListTools(_) => "tools/list",
+            Request::CallTool(_) => "tools/call",
+            Request::ListResources(_) => "resources/list",
+            Request::ListResourceTemplates(_) => "resources/templates/list",
+            Request::ReadResource(_) => "resources/read",
+            Request::Subscribe(_) => "resources/subscribe",
+            Request::Unsubscribe(_) => "resources/unsubscribe",
+            Request::ListPrompts(_) => "prompts/list",
+            Request::GetPrompt(_) => "prompts/get",
+            Request::Complete(_) => "completion/complete",
+            Request::SetLevel(_) => "logging/setLevel",
+            Request::CreateMessage(_) => "sampling/createMessage",
+            Request::Elicit(_) => "elicitation/create",
+            Request::ListRoots(_) => "roots/list",
+            Request::Other { method, .. } => method,
+        }
+    }
+
+    /// The params as they were sent
+    pub fn params(&self) -> Option<Value> {
+        match self {
+            Request::Initialize(params) => params_value(params),
+            Request::Ping(params) => params_value(params),
+            Request::ListTools(params)
+            | Request::ListResources(params)
+            | Request::ListResourceTemplates(params)
+            | Request::ListPrompts(params) => params_value(params),
+            Request::CallTool(params) => params_value(params),
+            Request::ReadResource(params)
+            | Request::Subscribe(params)
+            | Request::Unsubscribe(params) => params_value(params),
+            Request::GetPrompt(params) => params_value(params),
+            Request::Complete(params) => params_value(params),
+            Request::SetLevel(params) => params_value(params),
+            Request::CreateMessage(params) => params_value(params),
+            Request::Elicit(params) => params_value(params),
+            Request::ListRoots(params) => params_value(params),
+            Request::Other { params, .. } => params.clone(),
+        }
+    }
+
+    /// The `_meta` of the params
+    pub fn meta(&self) -> Option<&Map<String, Value>> {
+        match self {
+            Request::Initialize(params) => params.meta.as_ref(),
+            Request::Ping(params) => params.meta.as_ref(),
+            Request::ListTools(params)
+            | Request::ListResources(params)
+            | Request::ListResourceTemplates(params)
+            | Request::ListPrompts(params) => params.meta.as_ref(),
+            Request::CallTool(params) => params.meta.as_ref(),
+            Request::ReadResource(params)
+            | Request::Subscribe(params)
+            | Request::Unsubscribe(params) => params.meta.as_ref(),
+            Request::GetPrompt(params) => params.meta.as_ref(),
+            Request::Complete(params) => params.meta.as_ref(),
+            Request::SetLevel(params) => params.meta.as_ref(),
+            Request::CreateMessage(params) => params.meta.as_ref(),
+            Request::Elicit(params) => params.meta.as_ref(),
+            Request::ListRoots(params) => params.meta.as_ref(),
+            Request::Other { params, .. } => params.as_ref()?.get("_meta")?.as_object(),
+        }
+    }
+}
+
+/// A notification from either side, by method
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    Initialized(EmptyParams),
+    Cancelled(CancelledNotification),
+    Progress(ProgressNotification),
+    Message(LoggingMessageNotification),
+    ResourceUpdated(ResourceRequest),
+    ResourceListChanged(EmptyParams),
+    ToolListChanged(EmptyParams),
+    PromptListChanged(EmptyParams),
+    RootsListChanged(EmptyParams),
+    /// An unknown method, or params that do not fit the known one
+    Other {
+        method: String,
+        params: Option<Value>,
+    },
+}
+
+impl Notification {
+    fn parse(method: &str, params: Option<&Value>) -> Self {
+        let notification = match method {
+            "notifications/initialized" => typed(params).map(Notification::Initialized),
+            "notifications/cancelled" => typed(params).map(Notification::Cancelled),
+            "notifications/progress" => typed(params).map(Notification::Progress),
+            "notifications/message" => typed(params).map(Notification::Message),
+            "notifications/resources/updated" => typed(params).map(Notification::ResourceUpdated),
+            "notifications/resources/list_changed" => {
+                typed(params).map(Notification::ResourceListChanged)
+            }
+            "notifications/tools/list_changed" => typed(params).map(Notification::ToolListChanged),
+            "notifications/prompts/list_changed" => {
+                typed(params).map(Notification::PromptListChanged)
+            }
+            "notifications/roots/list_changed" => typed(params).map(Notification::RootsListChanged),
+            _ => None,
+        };
+        notification.unwrap_or_else(|| Notification::Other {
+            method: method.to_string(),
+            params: params.cloned(),
+        })
+    }
+
+    /// The JSON-RPC method
+    pub fn method(&self) -> &str {
+        match self {
+            Notification::Initialized(_) => "notifications/initialized",
+            Notification::Cancelled(_) => "notifications/cancelled",
+            Notification::Progress(_) => "notifications/progress",
+            Notification::Message(_) => "notifications/message",
+            Notification::ResourceUpdated(_) => "notifications/resources/updated",
+            Notification::ResourceListChanged(_) => "notifications/resources/list_changed",
+            Notification::ToolListChanged(_) => "notifications/tools/list_changed",
+            Notification::PromptListChanged(_) => "notifications/prompts/list_changed",
+            Notification::RootsListChanged(_) => "notifications/roots/list_changed",
+            Notification::Other { method, .. } => method,
+        }
+    }
+
+    /// The params as they were sent
+    pub fn params(&self) -> Option<Value> {
+        match self {
+            Notification::Initialized(params)
+            | Notification::ResourceListChanged(params)
+            | Notification::ToolListChanged(params)
+            | Notification::PromptListChanged(params)
+            | Notification::RootsListChanged(params) => params_value(params),
+            Notification::Cancelled(params) => params_value(params),
+            Notification::Progress(params) => params_value(params),
+            Notification::Message(params) => params_value(params),
+            Notification::ResourceUpdated(params) => params_value(params),
+            Notification::Other { params, .. } => params.clone(),
+        }
+    }
+
+    /// The `_meta` of the params
+    pub fn meta(&self) -> Option<&Map<String, Value>> {
+        match self {
+            Notification::Initialized(params)
+            | Notification::ResourceListChanged(params)
+            | Notification::ToolListChanged(params)
+            | Notification::PromptListChanged(params)
+            | Notification::RootsListChanged(params) => params.meta.as_ref(),
+            Notification::Cancelled(params) => params.meta.as_ref(),
+            Notification::Progress(params) => params.meta.as_ref(),
+            Notification::Message(params) => params.meta.as_ref(),
+            Notification::ResourceUpdated(params) => params.meta.as_ref(),
+            Notification::Other { params, .. } => params.as_ref()?.get("_meta")?.as_object(),
+        }
+    }
+}
+
+/// The result of a successful response, by the method it answers
+#[derive(Debug, Clone, PartialEq)]
+pub enum McpResult {
+    Initialize(InitializeResult),
+    ListTools(ListToolsResult),
+    CallTool(CallToolResult),
+    ListResources(ListResourcesResult),
+    ListResourceTemplates(ListResourceTemplatesResult),
+    ReadResource(ReadResourceResult),
+    ListPrompts(ListPromptsResult),
+    GetPrompt(GetPromptResult),
+    Complete(CompleteResult),
+    CreateMessage(CreateMessageResult),
+    Elicit(ElicitResult),
+    ListRoots(ListRootsResult),
+    /// An empty result, as for `ping` and `logging/setLevel`
+    Empty(EmptyResult),
+    /// A result of an unknown request, or one that does not fit its schema
+    Other(Value),
+}
+
+impl McpResult {
+    fn parse(method: Option<&str>, result: &Value) -> Self {
+        let parse_as = |method| match method {
+            "initialize" => lossless(result).map(McpResult::Initialize),
+            "tools/list" => lossless(result).map(McpResult::ListTools),
+            "tools/call" => lossless(result).map(McpResult::CallTool),
+            "resources/list" => lossless(result).map(McpResult::ListResources),
+            "resources/templates/list" => lossless(result).map(McpResult::ListResourceTemplates),
+            "resources/read" => lossless(result).map(McpResult::ReadResource),
+            "prompts/list" => lossless(result).map(McpResult::ListPrompts),
+            "prompts/get" => lossless(result).map(McpResult::GetPrompt),
+            "completion/complete" => lossless(result).map(McpResult::Complete),
+            "sampling/createMessage" => lossless(result).map(McpResult::CreateMessage),
+            "elicitation/create" => lossless(result).map(McpResult::Elicit),
+            "roots/list" => lossless(result).map(McpResult::ListRoots),
+            _ => None,
+        };
+        if let Some(result) = method.and_then(parse_as) {
+            return result;
+        }
+        // `_meta` alone still counts as empty
+        let empty = result
+            .as_object()
+            .is_some_and(|object| object.keys().all(|key| key == "_meta"));
+        empty
+            .then(|| lossless(result).map(McpResult::Empty))
+            .flatten()
+            .unwrap_or_else(|| McpResult::Other(result.clone()))
+    }
+
+    /// The method this result answers, as far as its type or shape tells
+    pub fn method(&self) -> Option<&'static str> {
+        let method = match self {
+            McpResult::Initialize(_) => "initialize",
+            McpResult::ListTools(_) => "tools/list",
+            McpResult::CallTool(_) => "tools/call",
+            McpResult::ListResources(_) => "resources/list",
+            McpResult::ListResourceTemplates(_) => "resources/templates/list",
+            McpResult::ReadResource(_) => "resources/read",
+            McpResult::ListPrompts(_) => "prompts/list",
+            McpResult::GetPrompt(_) => "prompts/get",
+            McpResult::Complete(_) => "completion/complete",
+            McpResult::CreateMessage(_) => "sampling/createMessage",
+            McpResult::Elicit(_) => "elicitation/create",
+            McpResult::ListRoots(_) => "roots/list",
+            McpResult::Empty(_) => return None,
+            McpResult::Other(result) => return infer_result_method(result),
+        };
+        Some(method)
+    }
+
+    /// The result as it was sent
+    pub fn to_value(&self) -> Value {
+        match self {
+            McpResult::Initialize(result) => to_value(result),
+            McpResult::ListTools(result) => to_value(result),
+            McpResult::CallTool(result) => to_value(result),
+            McpResult::ListResources(result) => to_value(result),
+            McpResult::ListResourceTemplates(result) => to_value(result),
+            McpResult::ReadResource(result) => to_value(result),
+            McpResult::ListPrompts(result) => to_value(result),
+            McpResult::GetPrompt(result) => to_value(result),
+            McpResult::Complete(result) => to_value(result),
+            McpResult::CreateMessage(result) => to_value(result),
+            McpResult::Elicit(result) => to_value(result),
+            McpResult::ListRoots(result) => to_value(result),
+            McpResult::Empty(result) => to_value(result),
+            McpResult::Other(result) => result.clone(),
+        }
+    }
+}
+
+/// Guess which request a result answers from its fields
+pub fn infer_result_method(result: &Value) -> Option<&'static str> {
+    let has_array = |key: &str| result.get(key).is_some_and(Value::is_array);
+    let method = if has_array("tools") {
+        "tools/list"
+    } else if has_array("content") {
+        "tools/call"
+    } else if has_array("contents") {
+        "resources/read"
+    } else if has_array("prompts") {
+        "prompts/list"
+    } else if has_array("messages") {
+        "prompts/get"
+    } else if has_array("resources") {
+        "resources/list"
+    } else if result.get("role").is_some() {
+        "sampling/createMessage"
+    } else if result.get("completion").is_some() {
+        "completion/complete"
+    } else if result.get("action").is_some() {
+        "elicitation/create"
+    } else if has_array("roots") {
+        "roots/list"
+    } else if has_array("resourceTemplates") {
+        "resources/templates/list"
+    } else if result.get("serverInfo").is_some() {
+        "initialize"
+    } else {
+        return None;
+    };
+    Some(method)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_requests_and_notifications() {
+        let call = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {"name": "read_file", "arguments": {"path": "/tmp/a"}}
+        });
+        let message = McpMessage::parse(&call);
+        assert_eq!(message.method(), Some("tools/call"));
+        assert_eq!(message.id(), Some(&json!(1)));
+        let McpMessage::Request {
+            request: Request::CallTool(request),
+            ..
+        } = message
+        else {
+            panic!("expected a tool call, got {message:?}");
+        };
+        assert_eq!(request.name, "read_file");
+
+        let progress = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": {"progressToken": "t1", "progress": 50, "total": 100}
+        });
+        let McpMessage::Notification(Notification::Progress(progress)) =
+            McpMessage::parse(&progress)
+        else {
+            panic!("expected progress");
+        };
+        assert_eq!(progress.progress_token, json!("t1"));
+        assert_eq!(progress.total, Some(100.0));
+    }
+
+    #[test]
+    fn test_parse_falls_back_to_raw_json() {
+        // Known method, params that do not fit
+        let call =
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": {"tool": 1}});
+        assert_eq!(
+            McpMessage::parse(&call),
+            McpMessage::Request {
+                id: json!(2),
+                request: Request::Other {
+                    method: "tools/call".to_string(),
+                    params: Some(json!({"tool": 1})),
+                },
+            }
+        );
+
+        let custom = json!({"jsonrpc": "2.0", "method": "vendor/event"});
+        assert_eq!(
+            McpMessage::parse(&custom),
+            McpMessage::Notification(Notification::Other {
+                method: "vendor/event".to_string(),
+                params: None,
+            })
+        );
+
+        let odd = json!({"jsonrpc": "2.0", "id": 3, "result": {"answer": 42}});
+        let McpMessage::Response { result, .. } = McpMessage::parse(&odd) else {
+            panic!("expected a response");
+        };
+        assert_eq!(result, McpResult::Other(json!({"answer": 42})));
+
+        assert_eq!(
+            McpMessage::parse(&json!("hello")),
+            McpMessage::Raw(json!("hello"))
+        );
+        assert_eq!(McpMessage::parse(&json!({})), McpMessage::Raw(json!({})));
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let tools = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {"tools": [{"name": "echo", "inputSchema": {"type": "object"}}]}
+        });
+        let McpMessage::Response {
+            result: McpResult::ListTools(list),
+            ..
+        } = McpMessage::parse(&tools)
+        else {
+            panic!("expected a tools/list result");
+        };
+        assert_eq!(list.tools[0].name, "echo");
+
+        // Results without fields are empty whatever they answer
+        let empty = json!({"jsonrpc": "2.0", "id": 2, "result": {}});
+        let McpMessage::Response { result, .. } =
+            McpMessage::parse_response(&empty, "logging/setLevel")
+        else {
+            panic!("expected a response");
+        };
+        assert_eq!(result, McpResult::Empty(EmptyResult::default()));
+
+        let error = json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {"code": -32700, "message": "Parse error"}
+        });
+        let McpMessage::Error { id, error } = McpMessage::parse(&error) else {
+            panic!("expected an error");
+        };
+        assert_eq!(id, Value::Null);
+        assert_eq!(error.code, -32700);
+
+        let batch = McpMessage::parse(&json!([tools, empty]));
+        let McpMessage::Batch(items) = batch else {
+            panic!("expected a batch");
+        };
+        assert_eq!(items.len(), 2);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_meta_and_unknown_fields() {
+        let messages = [
+            json!({
+                "jsonrpc": "2.0",
+                "id": "a",
+                "method": "tools/call",
+                "params": {
+                    "_meta": {"progressToken": 7},
+                    "name": "search",
+                    "arguments": {"q": "x"},
+                    "vendorHint": true
+                }
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {"roots": {}},
+                    "clientInfo": {"name": "c", "version": "1"},
+                    "_meta": {"trace": "abc"}
+                }
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": "a",
+                "result": {
+                    "_meta": {"cost": 1.5},
+                    "content": [{"type": "text", "text": "hi", "annotations": {"priority": 1}}],
+                    "structuredContent": {"n": 1},
+                    "isError": false
+                }
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "result": {
+                    "tools": [{"name": "echo", "inputSchema": {"type": "object"}, "x-cost": 3}],
+                    "nextCursor": "c2"
+                }
+            }),
+            json!({"jsonrpc": "2.0", "id": 3, "result": {"_meta": {"k": "v"}}}),
+            json!({"jsonrpc": "2.0", "method": "ping", "params": {"_meta": {"k": 1}}}),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/progress",
+                "params": {"progressToken": 7, "progress": 1, "message": "half", "extra": []}
+            }),
+            json!({"jsonrpc": "2.0", "id": 4, "error": {"code": -1, "message": "no", "data": {"a": 1}}}),
+            json!({"jsonrpc": "1.0", "id": 5, "method": "ping"}),
+            json!({"jsonrpc": "2.0", "id": 6, "method": "ping", "stray": 1}),
+        ];
+        // Numbers may come back as floats, e.g. a progress of `1` as `1.0`
+        for message in &messages {
+            let parsed = McpMessage::parse(message);
+            assert!(same(&parsed.to_value(), message), "{parsed:?}");
+        }
+
+        let call = McpMessage::parse(&messages[0]);
+        assert!(matches!(
+            call,
+            McpMessage::Request {
+                request: Request::CallTool(_),
+                ..
+            }
+        ));
+        assert_eq!(call.progress_token(), Some(&json!(7)));
+        assert_eq!(call.tool_name(), Some("search"));
+    }
+}
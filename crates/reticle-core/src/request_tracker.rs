@@ -0,0 +1,402 @@
+//! Request/response correlation for latency measurement
+//!
+//! A [`RequestTracker`] remembers every in-flight JSON-RPC request by
+//! direction and id. When the matching response passes through, the time
+//! since the request is returned so the proxy can fill in
+//! [`LogEntry::duration_micros`](crate::protocol::LogEntry::duration_micros). Requests that outlive the timeout, or are
+//! still open when the session ends, are reported as [`Unanswered`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::protocol::{Direction, McpMessage};
+
+/// How long a request may wait for its response before it is reported
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Why a request is reported as unanswered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnansweredReason {
+    /// No response within the timeout (a late response still gets a duration)
+    Timeout,
+    /// The session ended with the request still open
+    Orphaned,
+}
+
+/// A request that did not get a response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Unanswered {
+    pub id: Value,
+    pub method: String,
+    /// Direction the request travelled in
+    pub direction: Direction,
+    pub elapsed_ms: u64,
+    pub reason: UnansweredReason,
+}
+
+/// A request matched with its response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answered {
+    pub id: Value,
+    pub method: String,
+    /// Tool name, for `tools/call`
+    pub tool: Option<String>,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug)]
+struct Pending {
+    id: Value,
+    method: String,
+    tool: Option<String>,
+    sent_at: Instant,
+    /// Already reported as timed out
+    reported: bool,
+}
+
+/// Correlates responses with in-flight requests by JSON-RPC id
+#[derive(Debug)]
+pub struct RequestTracker {
+    pending: Mutex<HashMap<(Direction, String), Pending>>,
+    timeout: Duration,
+}
+
+impl Default for RequestTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_REQUEST_TIMEOUT)
+    }
+}
+
+impl RequestTracker {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            timeout,
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// How often [`expire`](Self::expire) should be called
+    pub fn check_interval(&self) -> Duration {
+        (self.timeout / 4).clamp(Duration::from_millis(100), Duration::from_secs(5))
+    }
+
+    /// Number of requests still waiting for a response
+    pub fn in_flight(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Track a message passing through the proxy
+    ///
+    /// Requests start a timer. For a response, returns the time since its
+    /// request; for a batch of responses, the longest of those times.
+    pub fn observe(&self, message: &McpMessage, direction: Direction) -> Option<Duration> {
+        self.observe_at(message, direction, Instant::now())
+    }
+
+    fn observe_at(
+        &self,
+        message: &McpMessage,
+        direction: Direction,
+        now: Instant,
+    ) -> Option<Duration> {
+        self.answer_at(message, direction, now)
+            .into_iter()
+            .map(|answered| answered.elapsed)
+            .max()
+    }
+
+    /// Track a message, returning the requests its response(s) answer
+    pub fn answer(&self, message: &McpMessage, direction: Direction) -> Vec<Answered> {
+        self.answer_at(message, direction, Instant::now())
+    }
+
+    fn answer_at(&self, message: &McpMessage, direction: Direction, now: Instant) -> Vec<Answered> {
+        if let McpMessage::Batch(batch) = message {
+            return batch
+                .iter()
+                .flat_map(|message| self.answer_at(message, direction, now))
+                .collect();
+        }
+
+        let Some(id) = message.id() else {
+            return Vec::new();
+        };
+        let mut pending = self.pending.lock().unwrap();
+
+        if let Some(method) = message.method() {
+            let tool = message.tool_name().map(String::from);
+            pending.insert(
+                (direction, id.to_string()),
+                Pending {
+                    id: id.clone(),
+                    method: method.to_string(),
+                    tool,
+                    sent_at: now,
+                    reported: false,
+                },
+            );
+            return Vec::new();
+        }
+
+        // A response answers a request that travelled the other way
+        match pending.remove(&(opposite(direction), id.to_string())) {
+            Some(request) => vec![Answered {
+                elapsed: now.saturating_duration_since(request.sent_at),
+                id: request.id,
+                method: request.method,
+                tool: request.tool,
+            }],
+            None => {
+                tracing::debug!("Response {} has no matching request", id);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Report requests that passed the timeout since the last call
+    ///
+    /// Each request is reported once. It stays tracked, so a late response
+    /// still gets its duration.
+    pub fn expire(&self) -> Vec<Unanswered> {
+        self.expire_at(Instant::now())
+    }
+
+    fn expire_at(&self, now: Instant) -> Vec<Unanswered> {
+        let mut pending = self.pending.lock().unwrap();
+        let mut expired: Vec<Unanswered> = pending
+            .iter_mut()
+            .filter(|(_, p)| {
+                !p.reported && now.saturating_duration_since(p.sent_at) >= self.timeout
+            })
+            .map(|((direction, _), p)| {
+                p.reported = true;
+                unanswered(p, *direction, now, UnansweredReason::Timeout)
+            })
+            .collect();
+        expired.sort_by_key(|u| std::cmp::Reverse(u.elapsed_ms));
+        expired
+    }
+
+    /// Forget every open request, for when the session ends
+    ///
+    /// Returns the ones not already reported as timed out.
+    pub fn drain(&self) -> Vec<Unanswered> {
+        let now = Instant::now();
+        let mut orphaned: Vec<Unanswered> = self
+            .pending
+            .lock()
+            .unwrap()
+            .drain()
+            .filter(|(_, p)| !p.reported)
+            .map(|((direction, _), p)| unanswered(&p, direction, now, UnansweredReason::Orphaned))
+            .collect();
+        orphaned.sort_by_key(|u| std::cmp::Reverse(u.elapsed_ms));
+        orphaned
+    }
+}
+
+fn unanswered(
+    pending: &Pending,
+    direction: Direction,
+    now: Instant,
+    reason: UnansweredReason,
+) -> Unanswered {
+    Unanswered {
+        id: pending.id.clone(),
+        method: pending.method.clone(),
+        direction,
+        elapsed_ms: now.saturating_duration_since(pending.sent_at).as_millis() as u64,
+        reason,
+    }
+}
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::In => Direction::Out,
+        Direction::Out => Direction::In,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(id: Value, method: &str) -> McpMessage {
+        McpMessage::parse(&json!({"jsonrpc": "2.0", "id": id, "method": method}))
+    }
+
+    fn response(id: Value) -> McpMessage {
+        McpMessage::parse(&json!({"jsonrpc": "2.0", "id": id, "result": {}}))
+    }
+
+    #[test]
+    fn test_response_duration() {
+        let tracker = RequestTracker::default();
+        let start = Instant::now();
+
+        assert!(tracker
+            .observe_at(&request(json!(1), "tools/list"), Direction::In, start)
+            .is_none());
+        // Notifications are not tracked
+        tracker.observe_at(
+            &McpMessage::parse(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"})),
+            Direction::In,
+            start,
+        );
+        assert_eq!(tracker.in_flight(), 1);
+
+        // A response in the same direction doesn't answer the request
+        let later = start + Duration::from_millis(250);
+        assert!(tracker
+            .observe_at(&response(json!(1)), Direction::In, later)
+            .is_none());
+
+        assert_eq!(
+            tracker.observe_at(&response(json!(1)), Direction::Out, later),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(tracker.in_flight(), 0);
+
+        // String and numeric ids are distinct
+        tracker.observe_at(&request(json!("1"), "ping"), Direction::In, start);
+        assert!(tracker
+            .observe_at(&response(json!(1)), Direction::Out, later)
+            .is_none());
+    }
+
+    #[test]
+    fn test_server_requests_and_batches() {
+        let tracker = RequestTracker::default();
+        let start = Instant::now();
+
+        // Server-to-client request answered by the client
+        tracker.observe_at(
+            &request(json!(9), "sampling/createMessage"),
+            Direction::Out,
+            start,
+        );
+        assert_eq!(
+            tracker.observe_at(
+                &response(json!(9)),
+                Direction::In,
+                start + Duration::from_millis(5)
+            ),
+            Some(Duration::from_millis(5))
+        );
+
+        tracker.observe_at(
+            &McpMessage::Batch(vec![request(json!(1), "a"), request(json!(2), "b")]),
+            Direction::In,
+            start,
+        );
+        tracker.observe_at(
+            &request(json!(3), "c"),
+            Direction::In,
+            start + Duration::from_millis(10),
+        );
+        let batch = McpMessage::Batch(vec![response(json!(1)), response(json!(3))]);
+        assert_eq!(
+            tracker.observe_at(&batch, Direction::Out, start + Duration::from_millis(40)),
+            Some(Duration::from_millis(40))
+        );
+        assert_eq!(tracker.in_flight(), 1);
+    }
+
+    #[test]
+    fn test_answer_names_tool() {
+        let tracker = RequestTracker::default();
+        let start = Instant::now();
+        tracker.answer_at(
+            &McpMessage::parse(
+                &json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "search"}}),
+            ),
+            Direction::In,
+            start,
+        );
+        let answered = tracker.answer_at(
+            &response(json!(1)),
+            Direction::Out,
+            start + Duration::from_millis(12),
+        );
+        assert_eq!(
+            answered,
+            vec![Answered {
+                id: json!(1),
+                method: "tools/call".to_string(),
+                tool: Some("search".to_string()),
+                elapsed: Duration::from_millis(12),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_timeouts_and_orphans() {
+        let tracker = RequestTracker::new(Duration::from_secs(5));
+        let start = Instant::now();
+        tracker.observe_at(&request(json!(1), "tools/call"), Direction::In, start);
+        tracker.observe_at(
+            &request(json!(2), "ping"),
+            Direction::In,
+            start + Duration::from_secs(3),
+        );
+
+        assert!(tracker.expire_at(start + Duration::from_secs(4)).is_empty());
+
+        let expired = tracker.expire_at(start + Duration::from_secs(6));
+        assert_eq!(
+            expired,
+            vec![Unanswered {
+                id: json!(1),
+                method: "tools/call".to_string(),
+                direction: Direction::In,
+                elapsed_ms: 6000,
+                reason: UnansweredReason::Timeout,
+            }]
+        );
+        // Reported only once, but a late response is still timed
+        assert!(tracker.expire_at(start + Duration::from_secs(7)).is_empty());
+        assert_eq!(
+            tracker.observe_at(
+                &response(json!(1)),
+                Direction::Out,
+                start + Duration::from_secs(8)
+            ),
+            Some(Duration::from_secs(8))
+        );
+
+        tracker.observe_at(
+            &request(json!(3), "tools/list"),
+            Direction::In,
+            start + Duration::from_secs(1),
+        );
+        assert_eq!(tracker.expire_at(start + Duration::from_secs(7)).len(), 1);
+
+        // Timed-out requests are not reported again as orphans
+        let orphaned = tracker.drain();
+        assert_eq!(orphaned.len(), 1);
+        assert_eq!(orphaned[0].method, "ping");
+        assert_eq!(orphaned[0].reason, UnansweredReason::Orphaned);
+        assert_eq!(tracker.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_check_interval() {
+        assert_eq!(
+            RequestTracker::default().check_interval(),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            RequestTracker::new(Duration::from_millis(10)).check_interval(),
+            Duration::from_millis(100)
+        );
+    }
+}
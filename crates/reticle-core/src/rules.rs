@@ -0,0 +1,598 @@
+//! Rules for rewriting, dropping, or delaying messages in the proxy
+//!
+//! Rules are loaded from a TOML or JSON file and applied to every JSON-RPC
+//! message passing through a proxy. Each rule has a [`MessagePattern`]
+//! under `match` and one or more actions. Every matching rule is applied in
+//! file order, so rewrites compose; a `drop` stops further processing.
+//!
+//! Responses are matched against the method and tool of the request they
+//! answer, so `method = "tools/list"` also selects the `tools/list` result.
+//!
+//! # Example
+//!
+//! ```toml
+//! [[rules]]
+//! name = "sandbox file reads"
+//! match = { method = "tools/call", tool = "read_file" }
+//! set = { "params.arguments.path" = "/tmp/sandbox/hosts" }
+//!
+//! [[rules]]
+//! name = "strip tool annotations"
+//! match = { method = "tools/list", direction = "out" }
+//! remove = ["result.tools[*].annotations"]
+//!
+//! [[rules]]
+//! name = "slow search"
+//! match = { tool = "search" }
+//! delay_ms = 1500
+//!
+//! [[rules]]
+//! name = "swallow pings"
+//! match = { method = "ping" }
+//! drop = true
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::matcher::{remove_json_path, set_json_path, MatchError, MessagePattern, RequestContext};
+use crate::protocol::Direction;
+
+/// Requests older than this many outstanding entries are forgotten
+const MAX_TRACKED_REQUESTS: usize = 1024;
+
+/// A single rewrite rule
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    /// Rule name (reported in logs; defaults to `rule-N`)
+    #[serde(default)]
+    pub name: String,
+    /// Messages this rule applies to
+    #[serde(rename = "match", default)]
+    pub pattern: MessagePattern,
+    /// Values to set, keyed by JSONPath
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub set: BTreeMap<String, Value>,
+    /// JSONPaths to remove
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<String>,
+    /// Discard matching messages instead of forwarding them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub drop: bool,
+    /// Hold matching messages for this many milliseconds before forwarding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u64>,
+}
+
+impl Rule {
+    /// Check the pattern and action paths
+    fn validate(&self) -> Result<(), RulesError> {
+        let invalid = |reason: String| RulesError::InvalidRule {
+            name: self.name.clone(),
+            reason,
+        };
+
+        if self.set.is_empty() && self.remove.is_empty() && !self.drop && self.delay_ms.is_none() {
+            return Err(invalid(
+                "no action (expected set, remove, drop, or delay_ms)".to_string(),
+            ));
+        }
+
+        self.pattern
+            .validate()
+            .map_err(|e| invalid(e.to_string()))?;
+
+        // Dry-run each path against an empty message to catch syntax errors
+        let mut scratch = Value::Null;
+        for path in self.set.keys() {
+            set_json_path(&mut scratch, path, &Value::Null).map_err(|e| invalid(e.to_string()))?;
+        }
+        for path in &self.remove {
+            remove_json_path(&mut scratch, path).map_err(|e| invalid(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply the set/remove actions, returning whether the message changed
+    fn rewrite(&self, message: &mut Value) -> bool {
+        let mut changed = false;
+        for (path, value) in &self.set {
+            changed |= set_json_path(message, path, value).unwrap_or(false);
+        }
+        for path in &self.remove {
+            changed |= remove_json_path(message, path).unwrap_or(false);
+        }
+        changed
+    }
+}
+
+/// On-disk rules file layout
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+/// The result of running a message through the rules
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleOutcome {
+    /// The message to forward, or `None` if a rule dropped it
+    pub message: Option<Value>,
+    /// The message as received, if a rule changed or dropped it
+    pub original: Option<Value>,
+    /// How long to wait before forwarding
+    pub delay: Duration,
+    /// Names of the rules that matched
+    pub applied: Vec<String>,
+}
+
+impl RuleOutcome {
+    /// Whether a rule dropped the message
+    pub fn is_dropped(&self) -> bool {
+        self.message.is_none()
+    }
+
+    /// Whether the forwarded message differs from the original
+    pub fn is_modified(&self) -> bool {
+        self.message.is_some() && self.original.is_some()
+    }
+
+    /// Sleep for the delay requested by the rules, if any
+    pub async fn wait(&self) {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+}
+
+/// A message as forwarded, with the original if a rule changed it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForwardedMessage {
+    pub message: Value,
+    pub original: Option<Value>,
+}
+
+/// An SSE chunk after the rules ran on its `data:` lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseChunk {
+    /// The chunk to forward
+    pub text: String,
+    /// JSON messages carried by the forwarded chunk
+    pub messages: Vec<ForwardedMessage>,
+    /// Total delay requested by the rules
+    pub delay: Duration,
+}
+
+/// Applies a list of rules to the messages of one proxy session
+///
+/// The engine remembers the method and tool of requests in flight so the
+/// matching responses can be selected by them as well.
+#[derive(Debug, Default)]
+pub struct RulesEngine {
+    rules: Vec<Rule>,
+    requests: Mutex<HashMap<String, RequestContext>>,
+}
+
+impl RulesEngine {
+    /// Create an engine from a list of rules
+    pub fn new(mut rules: Vec<Rule>) -> Result<Self, RulesError> {
+        for (i, rule) in rules.iter_mut().enumerate() {
+            if rule.name.is_empty() {
+                rule.name = format!("rule-{}", i + 1);
+            }
+            rule.validate()?;
+        }
+        Ok(Self {
+            rules,
+            requests: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Parse rules from TOML
+    pub fn from_toml(text: &str) -> Result<Self, RulesError> {
+        let file: RulesFile = toml::from_str(text).map_err(|e| RulesError::Parse(e.to_string()))?;
+        Self::new(file.rules)
+    }
+
+    /// Parse rules from JSON (`{"rules": [...]}`)
+    pub fn from_json(text: &str) -> Result<Self, RulesError> {
+        let file: RulesFile =
+            serde_json::from_str(text).map_err(|e| RulesError::Parse(e.to_string()))?;
+        Self::new(file.rules)
+    }
+
+    /// Load rules from a file; `.json` files are parsed as JSON, anything else as TOML
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RulesError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&text),
+            _ => Self::from_toml(&text),
+        }
+    }
+
+    /// Whether no rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// All configured rules
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Run a message through the rules
+    pub fn apply(&self, message: Value, direction: Direction) -> RuleOutcome {
+        let request = self.take_request(&message, direction);
+        let mut current = message;
+        let mut original: Option<Value> = None;
+        let mut delay = Duration::ZERO;
+        let mut applied = Vec::new();
+        let mut dropped = false;
+
+        for rule in &self.rules {
+            if !rule
+                .pattern
+                .matches_with_request(&current, direction, request.as_ref())
+            {
+                continue;
+            }
+            applied.push(rule.name.clone());
+
+            if !rule.set.is_empty() || !rule.remove.is_empty() {
+                let before = original.is_none().then(|| current.clone());
+                if rule.rewrite(&mut current) && original.is_none() {
+                    original = before;
+                }
+            }
+            if let Some(ms) = rule.delay_ms {
+                delay += Duration::from_millis(ms);
+            }
+            if rule.drop {
+                dropped = true;
+                break;
+            }
+        }
+
+        if dropped {
+            return RuleOutcome {
+                message: None,
+                original: Some(original.unwrap_or(current)),
+                delay,
+                applied,
+            };
+        }
+
+        self.track_request(&current, direction);
+        RuleOutcome {
+            message: Some(current),
+            original,
+            delay,
+            applied,
+        }
+    }
+
+    /// Run a raw message through the rules
+    ///
+    /// Returns `None` when no rule matched (or the text is not JSON), so
+    /// callers can forward the original text byte-for-byte.
+    pub fn apply_str(&self, text: &str, direction: Direction) -> Option<RuleOutcome> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let message = serde_json::from_str::<Value>(text).ok()?;
+        let outcome = self.apply(message, direction);
+        (!outcome.applied.is_empty()).then_some(outcome)
+    }
+
+    /// Run a raw message through the rules and wait out any delay
+    ///
+    /// Returns `None` if a rule dropped the message. Otherwise returns the
+    /// text to forward (re-serialized only if a rule changed it) and the
+    /// original message when it was changed.
+    pub async fn forward_text(
+        &self,
+        text: String,
+        direction: Direction,
+    ) -> Option<(String, Option<Value>)> {
+        let Some(outcome) = self.apply_str(&text, direction) else {
+            return Some((text, None));
+        };
+
+        outcome.wait().await;
+        match outcome.message {
+            None => {
+                tracing::info!("Rules {:?} dropped a message", outcome.applied);
+                None
+            }
+            Some(ref message) if outcome.original.is_some() => {
+                let rewritten = serde_json::to_string(message).unwrap_or(text);
+                Some((rewritten, outcome.original))
+            }
+            Some(_) => Some((text, None)),
+        }
+    }
+
+    /// Run the JSON `data:` lines of an SSE chunk through the rules
+    ///
+    /// Dropped messages lose their `data:` line; everything else in the
+    /// chunk is forwarded as is. Events split across chunks are not
+    /// reassembled, so a partial `data:` line passes through untouched.
+    pub fn apply_sse_chunk(&self, chunk: &str, direction: Direction) -> SseChunk {
+        let mut text = String::with_capacity(chunk.len());
+        let mut messages = Vec::new();
+        let mut delay = Duration::ZERO;
+
+        for line in chunk.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\r', '\n']);
+            let ending = &line[body.len()..];
+            let Some(message) = body
+                .strip_prefix("data:")
+                .and_then(|data| serde_json::from_str::<Value>(data.trim_start()).ok())
+            else {
+                text.push_str(line);
+                continue;
+            };
+
+            let outcome = self.apply(message, direction);
+            delay += outcome.delay;
+            let modified = outcome.is_modified();
+            let Some(message) = outcome.message else {
+                continue;
+            };
+
+            if modified {
+                text.push_str("data: ");
+                text.push_str(&serde_json::to_string(&message).unwrap_or_default());
+                text.push_str(ending);
+            } else {
+                text.push_str(line);
+            }
+            messages.push(ForwardedMessage {
+                message,
+                original: outcome.original,
+            });
+        }
+
+        SseChunk {
+            text,
+            messages,
+            delay,
+        }
+    }
+
+    /// Remember a request so its response can be matched by method and tool
+    fn track_request(&self, message: &Value, direction: Direction) {
+        let (Some(id), Some(context)) = (message.get("id"), RequestContext::from_message(message))
+        else {
+            return;
+        };
+        let Ok(mut requests) = self.requests.lock() else {
+            return;
+        };
+        if requests.len() >= MAX_TRACKED_REQUESTS {
+            requests.clear();
+        }
+        requests.insert(request_key(direction, id), context);
+    }
+
+    /// Look up (and forget) the request a response answers
+    fn take_request(&self, message: &Value, direction: Direction) -> Option<RequestContext> {
+        if message.get("method").is_some() {
+            return None;
+        }
+        let id = message.get("id")?;
+        // Requests travel the opposite way to their responses
+        let request_direction = match direction {
+            Direction::In => Direction::Out,
+            Direction::Out => Direction::In,
+        };
+        self.requests
+            .lock()
+            .ok()?
+            .remove(&request_key(request_direction, id))
+    }
+}
+
+fn request_key(direction: Direction, id: &Value) -> String {
+    format!("{direction:?}:{id}")
+}
+
+/// Rules errors
+#[derive(Debug, thiserror::Error)]
+pub enum RulesError {
+    #[error("Failed to read rules file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse rules: {0}")]
+    Parse(String),
+
+    #[error("Invalid rule '{name}': {reason}")]
+    InvalidRule { name: String, reason: String },
+
+    #[error(transparent)]
+    Pattern(#[from] MatchError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const RULES: &str = r#"
+        [[rules]]
+        name = "sandbox file reads"
+        match = { method = "tools/call", tool = "read_file" }
+        set = { "params.arguments.path" = "/tmp/sandbox" }
+
+        [[rules]]
+        name = "strip tool annotations"
+        match = { method = "tools/list", direction = "out" }
+        remove = ["result.tools[*].annotations"]
+
+        [[rules]]
+        match = { tool = "search" }
+        delay_ms = 250
+
+        [[rules]]
+        match = { method = "ping" }
+        drop = true
+    "#;
+
+    fn read_file(id: u64) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": "read_file", "arguments": {"path": "/etc/passwd"}}
+        })
+    }
+
+    #[test]
+    fn test_load_toml() {
+        let engine = RulesEngine::from_toml(RULES).unwrap();
+        assert_eq!(engine.rules().len(), 4);
+        assert_eq!(engine.rules()[2].name, "rule-3");
+        assert_eq!(engine.rules()[1].pattern.direction, Some(Direction::Out));
+    }
+
+    #[test]
+    fn test_load_json() {
+        let engine =
+            RulesEngine::from_json(r#"{"rules": [{"match": {"method": "ping"}, "drop": true}]}"#)
+                .unwrap();
+        assert_eq!(engine.rules().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let no_action = "[[rules]]\nmatch = { method = \"ping\" }";
+        assert!(matches!(
+            RulesEngine::from_toml(no_action),
+            Err(RulesError::InvalidRule { .. })
+        ));
+
+        let bad_path = "[[rules]]\nremove = [\"result[\"]";
+        assert!(RulesEngine::from_toml(bad_path).is_err());
+
+        assert!(matches!(
+            RulesEngine::from_toml("rules = 3"),
+            Err(RulesError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_rewrite_request() {
+        let engine = RulesEngine::from_toml(RULES).unwrap();
+        let outcome = engine.apply(read_file(1), Direction::In);
+
+        assert!(outcome.is_modified());
+        assert_eq!(outcome.applied, vec!["sandbox file reads"]);
+        assert_eq!(
+            outcome.message.unwrap()["params"]["arguments"]["path"],
+            json!("/tmp/sandbox")
+        );
+        assert_eq!(outcome.original, Some(read_file(1)));
+    }
+
+    #[test]
+    fn test_rewrite_response_by_request() {
+        let engine = RulesEngine::from_toml(RULES).unwrap();
+        let list = json!({"jsonrpc": "2.0", "id": 4, "method": "tools/list"});
+        assert!(engine.apply(list, Direction::In).applied.is_empty());
+
+        let result = json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "result": {"tools": [{"name": "a", "annotations": {"readOnlyHint": true}}]}
+        });
+        let outcome = engine.apply(result.clone(), Direction::Out);
+        assert!(outcome.is_modified());
+        assert_eq!(
+            outcome.message.unwrap()["result"]["tools"][0],
+            json!({"name": "a"})
+        );
+
+        // The request is forgotten once answered
+        assert!(!engine.apply(result, Direction::Out).is_modified());
+    }
+
+    #[test]
+    fn test_drop_and_delay() {
+        let engine = RulesEngine::from_toml(RULES).unwrap();
+
+        let outcome = engine.apply(json!({"id": 1, "method": "ping"}), Direction::In);
+        assert!(outcome.is_dropped());
+        assert_eq!(outcome.original, Some(json!({"id": 1, "method": "ping"})));
+
+        let search = json!({"id": 2, "method": "tools/call", "params": {"name": "search"}});
+        let outcome = engine.apply(search, Direction::In);
+        assert!(!outcome.is_modified());
+        assert_eq!(outcome.delay, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_apply_str() {
+        let engine = RulesEngine::from_toml(RULES).unwrap();
+        assert!(engine.apply_str("not json", Direction::In).is_none());
+        assert!(engine
+            .apply_str(r#"{"id":1,"method":"tools/list"}"#, Direction::In)
+            .is_none());
+
+        let text = serde_json::to_string(&read_file(9)).unwrap();
+        assert!(engine
+            .apply_str(&text, Direction::In)
+            .is_some_and(|o| o.is_modified()));
+
+        assert!(RulesEngine::default()
+            .apply_str(&text, Direction::In)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_forward_text() {
+        let engine = RulesEngine::from_toml(RULES).unwrap();
+
+        let text = serde_json::to_string(&read_file(5)).unwrap();
+        let (forwarded, original) = engine.forward_text(text, Direction::In).await.unwrap();
+        assert!(forwarded.contains("/tmp/sandbox"));
+        assert_eq!(original, Some(read_file(5)));
+
+        let ping = r#"{"id":6,"method":"ping"}"#.to_string();
+        assert!(engine.forward_text(ping, Direction::In).await.is_none());
+
+        let spaced = r#"{ "id": 7, "method": "tools/list" }"#.to_string();
+        assert_eq!(
+            engine.forward_text(spaced.clone(), Direction::In).await,
+            Some((spaced, None))
+        );
+    }
+
+    #[test]
+    fn test_apply_sse_chunk() {
+        let engine = RulesEngine::from_toml(RULES).unwrap();
+        let request = serde_json::to_string(&read_file(3)).unwrap();
+        let ping = r#"{"jsonrpc":"2.0","id":4,"method":"ping"}"#;
+        let chunk =
+            format!("event: message\r\ndata: {request}\r\n\r\ndata:{ping}\n\n: keepalive\n");
+
+        let rewritten = engine.apply_sse_chunk(&chunk, Direction::In);
+        assert_eq!(rewritten.messages.len(), 1);
+        assert_eq!(rewritten.messages[0].original, Some(read_file(3)));
+        assert!(rewritten.text.starts_with("event: message\r\ndata: {"));
+        assert!(rewritten.text.contains("/tmp/sandbox"));
+        assert!(!rewritten.text.contains("ping"));
+        assert!(rewritten.text.ends_with("\r\n\r\n\n: keepalive\n"));
+
+        // Untouched chunks come back byte-for-byte
+        let plain = "data: {\"id\": 1, \"result\": {}}\n\n";
+        let passed = engine.apply_sse_chunk(plain, Direction::Out);
+        assert_eq!(passed.text, plain);
+        assert_eq!(passed.messages[0].original, None);
+    }
+}
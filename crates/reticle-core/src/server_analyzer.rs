@@ -0,0 +1,1175 @@
+//! MCP Server Context Analyzer
+//!
+//! Analyzes an MCP server to calculate its context token overhead.
+//! This helps users understand the "context cost" of connecting a server
+//! before using it - useful for identifying context-heavy servers that
+//! might bloat an agent's context window.
+//!
+//! The analyzer connects to the server, fetches all definitions (tools,
+//! prompts, resources), and calculates how many tokens they consume.
+//! With the `remote` feature, [`ServerAnalyzer::remote`] analyzes a running
+//! server over Streamable HTTP, HTTP with SSE, or WebSocket instead.
+//! [`AnalysisThresholds`] turn the result into a pass/fail check, so that
+//! `reticle analyze` can catch tool-description bloat in CI.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::time::{timeout, Duration};
+
+use crate::token_counter::TokenCounter;
+use crate::tokenizer::{HeuristicTokenizer, Tokenizer, TokenizerSpec};
+use crate::tool_lint::{lint_tools, LintOptions, LintReport, LintSeverity};
+#[cfg(feature = "remote")]
+use crate::transport::TransportType;
+
+#[cfg(feature = "remote")]
+mod remote;
+
+/// Analysis result for an MCP server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerAnalysis {
+    /// Server name from initialize response
+    pub server_name: String,
+    /// Server version
+    pub server_version: String,
+    /// Protocol version
+    pub protocol_version: String,
+    /// Tokenizer the counts were made with
+    #[serde(default)]
+    pub tokenizer: TokenizerSpec,
+
+    /// Total context tokens for all definitions
+    pub total_context_tokens: u64,
+
+    /// Tool analysis
+    pub tools: ToolsAnalysis,
+    /// Prompts analysis
+    pub prompts: PromptsAnalysis,
+    /// Resources analysis
+    pub resources: ResourcesAnalysis,
+
+    /// Problems with the tool definitions that hurt tool selection
+    #[serde(default)]
+    pub lint: LintReport,
+
+    /// Breakdown by category
+    pub token_breakdown: HashMap<String, u64>,
+
+    /// Analysis timestamp
+    pub analyzed_at: u64,
+}
+
+impl ServerAnalysis {
+    /// Summary of this analysis for listing snapshots
+    pub fn info(&self) -> AnalysisInfo {
+        AnalysisInfo {
+            server_name: self.server_name.clone(),
+            server_version: self.server_version.clone(),
+            protocol_version: self.protocol_version.clone(),
+            tokenizer: self.tokenizer.clone(),
+            total_context_tokens: self.total_context_tokens,
+            tools: self.tools.count,
+            prompts: self.prompts.count,
+            resources: self.resources.count,
+            analyzed_at: self.analyzed_at,
+        }
+    }
+}
+
+/// A saved analysis, without the per-definition detail
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalysisInfo {
+    pub server_name: String,
+    pub server_version: String,
+    pub protocol_version: String,
+    pub tokenizer: TokenizerSpec,
+    pub total_context_tokens: u64,
+    /// Number of tools
+    pub tools: u32,
+    /// Number of prompts
+    pub prompts: u32,
+    /// Number of resources
+    pub resources: u32,
+    pub analyzed_at: u64,
+}
+
+/// Tool definitions analysis
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ToolsAnalysis {
+    /// Number of tools
+    pub count: u32,
+    /// Total tokens for all tool definitions
+    pub total_tokens: u64,
+    /// Per-tool token breakdown
+    pub tools: Vec<ToolTokenInfo>,
+}
+
+/// Individual tool token information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolTokenInfo {
+    /// Tool name
+    pub name: String,
+    /// Tool description
+    pub description: String,
+    /// Tokens for name
+    pub name_tokens: u64,
+    /// Tokens for description
+    pub description_tokens: u64,
+    /// Tokens for input schema
+    pub schema_tokens: u64,
+    /// Total tokens for this tool
+    pub total_tokens: u64,
+    /// The tool's input schema, kept for diffing snapshots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+    /// Tokens for the output schema of structured results, not part of the
+    /// total since clients do not put it in the model's context
+    #[serde(default)]
+    pub output_schema_tokens: u64,
+    /// The tool's output schema, if it returns structured content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+/// Prompts analysis
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PromptsAnalysis {
+    /// Number of prompts
+    pub count: u32,
+    /// Total tokens for all prompt definitions
+    pub total_tokens: u64,
+    /// Per-prompt token breakdown
+    pub prompts: Vec<PromptTokenInfo>,
+}
+
+/// Individual prompt token information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTokenInfo {
+    /// Prompt name
+    pub name: String,
+    /// Prompt description
+    pub description: Option<String>,
+    /// Total tokens for this prompt definition
+    pub total_tokens: u64,
+}
+
+/// Resources analysis
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ResourcesAnalysis {
+    /// Number of resources
+    pub count: u32,
+    /// Total tokens for all resource definitions
+    pub total_tokens: u64,
+    /// Per-resource token breakdown
+    pub resources: Vec<ResourceTokenInfo>,
+}
+
+/// Individual resource token information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceTokenInfo {
+    /// Resource URI
+    pub uri: String,
+    /// Resource name
+    pub name: String,
+    /// Resource description
+    pub description: Option<String>,
+    /// Total tokens for this resource definition
+    pub total_tokens: u64,
+}
+
+/// Error types for server analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AnalysisError {
+    /// Failed to start the server process
+    ProcessStartFailed(String),
+    /// Server initialization failed
+    InitializationFailed(String),
+    /// Timeout waiting for response
+    Timeout(String),
+    /// Invalid response from server
+    InvalidResponse(String),
+    /// IO error
+    IoError(String),
+    /// Could not connect to a remote server, or it answered with an HTTP error
+    ConnectionFailed(String),
+}
+
+impl std::fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisError::ProcessStartFailed(msg) => write!(f, "Process start failed: {msg}"),
+            AnalysisError::InitializationFailed(msg) => {
+                write!(f, "Initialization failed: {msg}")
+            }
+            AnalysisError::Timeout(msg) => write!(f, "Timeout: {msg}"),
+            AnalysisError::InvalidResponse(msg) => write!(f, "Invalid response: {msg}"),
+            AnalysisError::IoError(msg) => write!(f, "IO error: {msg}"),
+            AnalysisError::ConnectionFailed(msg) => write!(f, "Connection failed: {msg}"),
+        }
+    }
+}
+
+/// Where the analyzed server lives
+#[derive(Debug, Clone)]
+enum Target {
+    /// A stdio server the analyzer launches
+    Command { command: String, args: Vec<String> },
+    /// A running server reached over HTTP, SSE, or WebSocket
+    #[cfg(feature = "remote")]
+    Url {
+        url: String,
+        transport: TransportType,
+        headers: HashMap<String, String>,
+    },
+}
+
+/// MCP Server Analyzer
+pub struct ServerAnalyzer {
+    /// Server to analyze
+    target: Target,
+    /// Environment variables
+    env: HashMap<String, String>,
+    /// Timeout for operations
+    timeout_secs: u64,
+    /// Tokenizer for the definitions
+    tokenizer: Arc<dyn Tokenizer>,
+    /// Limits for linting tool definitions
+    lint_options: LintOptions,
+}
+
+impl ServerAnalyzer {
+    /// Create a new analyzer for the given server command
+    pub fn new(command: String, args: Vec<String>) -> Self {
+        Self {
+            target: Target::Command { command, args },
+            env: HashMap::new(),
+            timeout_secs: 30,
+            tokenizer: Arc::new(HeuristicTokenizer),
+            lint_options: LintOptions::default(),
+        }
+    }
+
+    /// Create an analyzer for a running server at `url`
+    ///
+    /// `Http` is the legacy SSE transport: `url` is the SSE endpoint, and
+    /// messages are posted to the endpoint it announces. `Streamable` posts
+    /// to `url` directly, and `WebSocket` expects a `ws://` or `wss://` URL.
+    #[cfg(feature = "remote")]
+    pub fn remote(url: String, transport: TransportType) -> Self {
+        Self {
+            target: Target::Url {
+                url,
+                transport,
+                headers: HashMap::new(),
+            },
+            env: HashMap::new(),
+            timeout_secs: 30,
+            tokenizer: Arc::new(HeuristicTokenizer),
+            lint_options: LintOptions::default(),
+        }
+    }
+
+    /// Set environment variables for the server
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Send these headers (e.g. `Authorization`) with every request to a
+    /// remote server
+    #[cfg(feature = "remote")]
+    pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        if let Target::Url {
+            headers: ref mut target_headers,
+            ..
+        } = self.target
+        {
+            *target_headers = headers;
+        }
+        self
+    }
+
+    /// Set timeout for operations
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = secs;
+        self
+    }
+
+    /// Count tokens with this tokenizer instead of the heuristic
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Lint tool definitions against these limits instead of the defaults
+    pub fn with_lint_options(mut self, options: LintOptions) -> Self {
+        self.lint_options = options;
+        self
+    }
+
+    /// Analyze the MCP server and return context token information
+    pub async fn analyze(&self) -> Result<ServerAnalysis, AnalysisError> {
+        let mut conn = self.connect().await?;
+        let result = self.analyze_connection(&mut conn).await;
+        conn.close().await;
+        result
+    }
+
+    async fn analyze_connection(
+        &self,
+        conn: &mut Connection,
+    ) -> Result<ServerAnalysis, AnalysisError> {
+        // Initialize the server
+        let init_response = self.initialize(conn).await?;
+
+        // Extract server info
+        let server_name = init_response
+            .get("result")
+            .and_then(|r| r.get("serverInfo"))
+            .and_then(|s| s.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let server_version = init_response
+            .get("result")
+            .and_then(|r| r.get("serverInfo"))
+            .and_then(|s| s.get("version"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let protocol_version = init_response
+            .get("result")
+            .and_then(|r| r.get("protocolVersion"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        // Send initialized notification
+        self.send_initialized(conn).await?;
+
+        // Fetch and analyze tools
+        let (tools, lint) = self.analyze_tools(conn).await?;
+
+        // Fetch and analyze prompts
+        let prompts = self.analyze_prompts(conn).await?;
+
+        // Fetch and analyze resources
+        let resources = self.analyze_resources(conn).await?;
+
+        // Calculate totals
+        let total_context_tokens =
+            tools.total_tokens + prompts.total_tokens + resources.total_tokens;
+
+        // Build breakdown
+        let mut token_breakdown = HashMap::new();
+        token_breakdown.insert("tools".to_string(), tools.total_tokens);
+        token_breakdown.insert("prompts".to_string(), prompts.total_tokens);
+        token_breakdown.insert("resources".to_string(), resources.total_tokens);
+
+        Ok(ServerAnalysis {
+            server_name,
+            server_version,
+            protocol_version,
+            tokenizer: self.tokenizer.spec(),
+            total_context_tokens,
+            tools,
+            prompts,
+            resources,
+            lint,
+            token_breakdown,
+            analyzed_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    /// Start or connect to the server
+    async fn connect(&self) -> Result<Connection, AnalysisError> {
+        match &self.target {
+            Target::Command { command, args } => self.start_server(command, args).await,
+            #[cfg(feature = "remote")]
+            Target::Url {
+                url,
+                transport,
+                headers,
+            } => timeout(
+                Duration::from_secs(self.timeout_secs),
+                remote::RemoteConnection::connect(url, *transport, headers),
+            )
+            .await
+            .map_err(|_| AnalysisError::Timeout(format!("Connecting to {url}")))?
+            .map(Connection::Remote),
+        }
+    }
+
+    /// Start the server process
+    async fn start_server(
+        &self,
+        command: &str,
+        args: &[String],
+    ) -> Result<Connection, AnalysisError> {
+        let mut cmd = Command::new(command);
+        cmd.args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| AnalysisError::ProcessStartFailed(e.to_string()))?;
+
+        // Get stdin/stdout handles
+        let writer = child
+            .stdin
+            .take()
+            .ok_or_else(|| AnalysisError::IoError("Failed to get stdin".to_string()))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| AnalysisError::IoError("Failed to get stdout".to_string()))?;
+
+        Ok(Connection::Stdio {
+            child,
+            writer,
+            reader: BufReader::new(stdout),
+        })
+    }
+
+    /// Send a JSON-RPC request and wait for its response
+    async fn send_request(
+        &self,
+        conn: &mut Connection,
+        request: serde_json::Value,
+    ) -> Result<serde_json::Value, AnalysisError> {
+        let method = request
+            .get("method")
+            .and_then(|m| m.as_str())
+            .unwrap_or("request")
+            .to_string();
+        timeout(
+            Duration::from_secs(self.timeout_secs),
+            conn.request(request),
+        )
+        .await
+        .map_err(|_| AnalysisError::Timeout(format!("Waiting for {method} response")))?
+    }
+
+    /// Initialize the server
+    async fn initialize(&self, conn: &mut Connection) -> Result<serde_json::Value, AnalysisError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "reticle-analyzer",
+                    "version": "1.0.0"
+                }
+            }
+        });
+
+        let response = self.send_request(conn, request).await?;
+
+        if response.get("error").is_some() {
+            return Err(AnalysisError::InitializationFailed(
+                response
+                    .get("error")
+                    .and_then(|e| e.get("message"))
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown error")
+                    .to_string(),
+            ));
+        }
+
+        Ok(response)
+    }
+
+    /// Send initialized notification
+    async fn send_initialized(&self, conn: &mut Connection) -> Result<(), AnalysisError> {
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized"
+        });
+
+        conn.notify(notification).await
+    }
+
+    /// Analyze and lint tools from the server
+    async fn analyze_tools(
+        &self,
+        conn: &mut Connection,
+    ) -> Result<(ToolsAnalysis, LintReport), AnalysisError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/list",
+            "params": {}
+        });
+
+        let response = self.send_request(conn, request).await?;
+
+        // Handle case where tools/list is not supported
+        if response.get("error").is_some() {
+            return Ok((ToolsAnalysis::default(), LintReport::default()));
+        }
+
+        let tools = response
+            .get("result")
+            .and_then(|r| r.get("tools"))
+            .and_then(|t| t.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let lint = lint_tools(&tools, &self.lint_options);
+
+        let mut analysis = ToolsAnalysis {
+            count: tools.len() as u32,
+            total_tokens: 0,
+            tools: Vec::new(),
+        };
+
+        for tool in tools {
+            let name = tool
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("")
+                .to_string();
+            let description = tool
+                .get("description")
+                .and_then(|d| d.as_str())
+                .unwrap_or("")
+                .to_string();
+
+            let name_tokens = self.tokenizer.count_tokens(&name);
+            let description_tokens = self.tokenizer.count_tokens(&description);
+            let input_schema = tool.get("inputSchema").cloned();
+            let schema_tokens = input_schema
+                .as_ref()
+                .map(|schema| TokenCounter::count_json_tokens_with(self.tokenizer.as_ref(), schema))
+                .unwrap_or(0);
+            let output_schema = tool.get("outputSchema").cloned();
+            let output_schema_tokens = output_schema
+                .as_ref()
+                .map(|schema| TokenCounter::count_json_tokens_with(self.tokenizer.as_ref(), schema))
+                .unwrap_or(0);
+
+            let total_tokens = name_tokens + description_tokens + schema_tokens;
+            analysis.total_tokens += total_tokens;
+
+            analysis.tools.push(ToolTokenInfo {
+                name,
+                description,
+                name_tokens,
+                description_tokens,
+                schema_tokens,
+                total_tokens,
+                input_schema,
+                output_schema_tokens,
+                output_schema,
+            });
+        }
+
+        // Sort by token count descending
+        analysis
+            .tools
+            .sort_by_key(|t| std::cmp::Reverse(t.total_tokens));
+
+        Ok((analysis, lint))
+    }
+
+    /// Analyze prompts from the server
+    async fn analyze_prompts(
+        &self,
+        conn: &mut Connection,
+    ) -> Result<PromptsAnalysis, AnalysisError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "prompts/list",
+            "params": {}
+        });
+
+        let response = self.send_request(conn, request).await?;
+
+        // Handle case where prompts/list is not supported
+        if response.get("error").is_some() {
+            return Ok(PromptsAnalysis::default());
+        }
+
+        let prompts = response
+            .get("result")
+            .and_then(|r| r.get("prompts"))
+            .and_then(|p| p.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut analysis = PromptsAnalysis {
+            count: prompts.len() as u32,
+            total_tokens: 0,
+            prompts: Vec::new(),
+        };
+
+        for prompt in prompts {
+            let name = prompt
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("")
+                .to_string();
+            let description = prompt
+                .get("description")
+                .and_then(|d| d.as_str())
+                .map(|s| s.to_string());
+
+            let name_tokens = self.tokenizer.count_tokens(&name);
+            let description_tokens = description
+                .as_ref()
+                .map(|d| self.tokenizer.count_tokens(d))
+                .unwrap_or(0);
+
+            // Also count arguments if present
+            let args_tokens = prompt
+                .get("arguments")
+                .and_then(|a| a.as_array())
+                .map(|args| {
+                    args.iter()
+                        .map(|arg| {
+                            let arg_name = arg.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                            let arg_desc = arg
+                                .get("description")
+                                .and_then(|d| d.as_str())
+                                .unwrap_or("");
+                            self.tokenizer.count_tokens(arg_name)
+                                + self.tokenizer.count_tokens(arg_desc)
+                        })
+                        .sum::<u64>()
+                })
+                .unwrap_or(0);
+
+            let total_tokens = name_tokens + description_tokens + args_tokens;
+            analysis.total_tokens += total_tokens;
+
+            analysis.prompts.push(PromptTokenInfo {
+                name,
+                description,
+                total_tokens,
+            });
+        }
+
+        // Sort by token count descending
+        analysis
+            .prompts
+            .sort_by_key(|t| std::cmp::Reverse(t.total_tokens));
+
+        Ok(analysis)
+    }
+
+    /// Analyze resources from the server
+    async fn analyze_resources(
+        &self,
+        conn: &mut Connection,
+    ) -> Result<ResourcesAnalysis, AnalysisError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "resources/list",
+            "params": {}
+        });
+
+        let response = self.send_request(conn, request).await?;
+
+        // Handle case where resources/list is not supported
+        if response.get("error").is_some() {
+            return Ok(ResourcesAnalysis::default());
+        }
+
+        let resources = response
+            .get("result")
+            .and_then(|r| r.get("resources"))
+            .and_then(|r| r.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut analysis = ResourcesAnalysis {
+            count: resources.len() as u32,
+            total_tokens: 0,
+            resources: Vec::new(),
+        };
+
+        for resource in resources {
+            let uri = resource
+                .get("uri")
+                .and_then(|u| u.as_str())
+                .unwrap_or("")
+                .to_string();
+            let name = resource
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("")
+                .to_string();
+            let description = resource
+                .get("description")
+                .and_then(|d| d.as_str())
+                .map(|s| s.to_string());
+
+            let name_tokens = self.tokenizer.count_tokens(&name);
+            let description_tokens = description
+                .as_ref()
+                .map(|d| self.tokenizer.count_tokens(d))
+                .unwrap_or(0);
+
+            let total_tokens = name_tokens + description_tokens;
+            analysis.total_tokens += total_tokens;
+
+            analysis.resources.push(ResourceTokenInfo {
+                uri,
+                name,
+                description,
+                total_tokens,
+            });
+        }
+
+        // Sort by token count descending
+        analysis
+            .resources
+            .sort_by_key(|t| std::cmp::Reverse(t.total_tokens));
+
+        Ok(analysis)
+    }
+}
+
+/// A server the analyzer is talking JSON-RPC to
+enum Connection {
+    Stdio {
+        /// Killed when the connection is dropped
+        child: Child,
+        writer: ChildStdin,
+        reader: BufReader<ChildStdout>,
+    },
+    #[cfg(feature = "remote")]
+    Remote(remote::RemoteConnection),
+}
+
+impl Connection {
+    /// Send a request and wait for the response with the same id
+    ///
+    /// Notifications, log output, and server-initiated requests that
+    /// arrive in the meantime are skipped.
+    async fn request(
+        &mut self,
+        request: serde_json::Value,
+    ) -> Result<serde_json::Value, AnalysisError> {
+        match self {
+            Connection::Stdio { writer, reader, .. } => {
+                write_line(writer, &request).await?;
+                let id = request.get("id").cloned().unwrap_or_default();
+                let method = request
+                    .get("method")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("request");
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    let n = reader
+                        .read_line(&mut line)
+                        .await
+                        .map_err(|e| AnalysisError::IoError(e.to_string()))?;
+                    if n == 0 {
+                        return Err(AnalysisError::IoError(format!(
+                            "Server closed stdout before answering {method}"
+                        )));
+                    }
+                    let Ok(message) = serde_json::from_str(line.trim()) else {
+                        continue;
+                    };
+                    if let Some(response) = find_response(message, &id) {
+                        return Ok(response);
+                    }
+                }
+            }
+            #[cfg(feature = "remote")]
+            Connection::Remote(remote) => remote.request(request).await,
+        }
+    }
+
+    /// Send a notification (no response expected)
+    async fn notify(&mut self, notification: serde_json::Value) -> Result<(), AnalysisError> {
+        match self {
+            Connection::Stdio { writer, .. } => write_line(writer, &notification).await,
+            #[cfg(feature = "remote")]
+            Connection::Remote(remote) => remote.notify(notification).await,
+        }
+    }
+
+    /// Stop the server or end the remote session
+    async fn close(self) {
+        match self {
+            Connection::Stdio { mut child, .. } => {
+                let _ = child.kill().await;
+            }
+            #[cfg(feature = "remote")]
+            Connection::Remote(remote) => remote.close().await,
+        }
+    }
+}
+
+/// Write a message as one line of newline-delimited JSON
+async fn write_line(
+    writer: &mut ChildStdin,
+    message: &serde_json::Value,
+) -> Result<(), AnalysisError> {
+    let mut line = serde_json::to_string(message)
+        .map_err(|e| AnalysisError::InvalidResponse(e.to_string()))?;
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| AnalysisError::IoError(e.to_string()))?;
+    writer
+        .flush()
+        .await
+        .map_err(|e| AnalysisError::IoError(e.to_string()))
+}
+
+/// The response to request `id` in a message or batch, if there is one
+fn find_response(message: serde_json::Value, id: &serde_json::Value) -> Option<serde_json::Value> {
+    match message {
+        serde_json::Value::Array(batch) => batch
+            .into_iter()
+            .find_map(|message| find_response(message, id)),
+        message if message.get("method").is_none() && message.get("id") == Some(id) => {
+            Some(message)
+        }
+        _ => None,
+    }
+}
+
+/// Convenience function to analyze a server by command
+pub async fn analyze_server(
+    command: String,
+    args: Vec<String>,
+    env: Option<HashMap<String, String>>,
+    timeout_secs: Option<u64>,
+) -> Result<ServerAnalysis, AnalysisError> {
+    let mut analyzer = ServerAnalyzer::new(command, args);
+
+    if let Some(env) = env {
+        analyzer = analyzer.with_env(env);
+    }
+
+    if let Some(timeout) = timeout_secs {
+        analyzer = analyzer.with_timeout(timeout);
+    }
+
+    analyzer.analyze().await
+}
+
+/// Limits on a server's context cost, for failing CI on tool bloat
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalysisThresholds {
+    /// Most tokens all definitions together may take
+    pub max_total_tokens: Option<u64>,
+    /// Most tokens any one tool definition may take
+    pub max_tool_tokens: Option<u64>,
+    /// Most tokens any one tool description may take
+    pub max_description_tokens: Option<u64>,
+    /// Most tools the server may expose
+    pub max_tools: Option<u64>,
+    /// Fail on any lint finding at least this severe
+    pub fail_on_lint: Option<LintSeverity>,
+}
+
+/// Which threshold was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdKind {
+    TotalTokens,
+    ToolTokens,
+    DescriptionTokens,
+    Tools,
+    Lint,
+}
+
+/// A threshold the analysis went over
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdViolation {
+    pub kind: ThresholdKind,
+    /// The tool, for per-tool thresholds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    pub limit: u64,
+    pub actual: u64,
+}
+
+impl fmt::Display for ThresholdViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tool = self.tool.as_deref().unwrap_or_default();
+        match self.kind {
+            ThresholdKind::TotalTokens => write!(f, "total context tokens"),
+            ThresholdKind::ToolTokens => write!(f, "tool '{tool}' tokens"),
+            ThresholdKind::DescriptionTokens => write!(f, "tool '{tool}' description tokens"),
+            ThresholdKind::Tools => write!(f, "tool count"),
+            ThresholdKind::Lint => write!(f, "lint findings"),
+        }?;
+        write!(f, " {} > {}", self.actual, self.limit)
+    }
+}
+
+impl AnalysisThresholds {
+    /// Whether no thresholds are set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Every threshold the analysis exceeds, largest tools first
+    pub fn check(&self, analysis: &ServerAnalysis) -> Vec<ThresholdViolation> {
+        let mut violations = Vec::new();
+        let over = |kind, tool: Option<&str>, limit: Option<u64>, actual: u64| {
+            limit
+                .filter(|limit| actual > *limit)
+                .map(|limit| ThresholdViolation {
+                    kind,
+                    tool: tool.map(String::from),
+                    limit,
+                    actual,
+                })
+        };
+
+        violations.extend(over(
+            ThresholdKind::TotalTokens,
+            None,
+            self.max_total_tokens,
+            analysis.total_context_tokens,
+        ));
+        violations.extend(over(
+            ThresholdKind::Tools,
+            None,
+            self.max_tools,
+            analysis.tools.count as u64,
+        ));
+        // Tools are already sorted by total tokens
+        for tool in &analysis.tools.tools {
+            violations.extend(over(
+                ThresholdKind::ToolTokens,
+                Some(&tool.name),
+                self.max_tool_tokens,
+                tool.total_tokens,
+            ));
+            violations.extend(over(
+                ThresholdKind::DescriptionTokens,
+                Some(&tool.name),
+                self.max_description_tokens,
+                tool.description_tokens,
+            ));
+        }
+        if let Some(severity) = self.fail_on_lint {
+            let findings = analysis
+                .lint
+                .findings
+                .iter()
+                .filter(|finding| finding.severity >= severity)
+                .count();
+            violations.extend(over(ThresholdKind::Lint, None, Some(0), findings as u64));
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_token_calculation() {
+        let tool = serde_json::json!({
+            "name": "read_file",
+            "description": "Read the contents of a file from the filesystem",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "The path to the file to read"
+                    }
+                },
+                "required": ["path"]
+            }
+        });
+
+        let name = tool.get("name").and_then(|n| n.as_str()).unwrap();
+        let desc = tool.get("description").and_then(|d| d.as_str()).unwrap();
+        let schema = tool.get("inputSchema").unwrap();
+
+        let name_tokens = TokenCounter::estimate_tokens(name);
+        let desc_tokens = TokenCounter::estimate_tokens(desc);
+        let schema_tokens = TokenCounter::count_json_tokens(schema);
+
+        let total = name_tokens + desc_tokens + schema_tokens;
+
+        // Should have reasonable token counts
+        assert!(name_tokens >= 1);
+        assert!(desc_tokens >= 5);
+        assert!(schema_tokens >= 10);
+        assert!(total >= 20);
+    }
+
+    fn analysis(tools: &[(&str, u64, u64)]) -> ServerAnalysis {
+        let tools: Vec<ToolTokenInfo> = tools
+            .iter()
+            .map(|&(name, description_tokens, total_tokens)| ToolTokenInfo {
+                name: name.to_string(),
+                description: String::new(),
+                name_tokens: 1,
+                description_tokens,
+                schema_tokens: total_tokens - description_tokens - 1,
+                total_tokens,
+                input_schema: None,
+                output_schema_tokens: 0,
+                output_schema: None,
+            })
+            .collect();
+        let total: u64 = tools.iter().map(|t| t.total_tokens).sum();
+        ServerAnalysis {
+            server_name: "test".to_string(),
+            server_version: "1.0.0".to_string(),
+            protocol_version: "2024-11-05".to_string(),
+            tokenizer: TokenizerSpec::Heuristic,
+            total_context_tokens: total,
+            tools: ToolsAnalysis {
+                count: tools.len() as u32,
+                total_tokens: total,
+                tools,
+            },
+            prompts: PromptsAnalysis::default(),
+            resources: ResourcesAnalysis::default(),
+            lint: LintReport::default(),
+            token_breakdown: HashMap::new(),
+            analyzed_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let analysis = analysis(&[("search", 120, 300), ("read_file", 10, 40)]);
+        assert!(AnalysisThresholds::default().is_empty());
+        assert!(AnalysisThresholds::default().check(&analysis).is_empty());
+
+        let thresholds = AnalysisThresholds {
+            max_total_tokens: Some(400),
+            max_tool_tokens: Some(200),
+            max_description_tokens: Some(100),
+            max_tools: Some(2),
+            fail_on_lint: None,
+        };
+        let violations = thresholds.check(&analysis);
+        assert_eq!(
+            violations,
+            vec![
+                ThresholdViolation {
+                    kind: ThresholdKind::ToolTokens,
+                    tool: Some("search".to_string()),
+                    limit: 200,
+                    actual: 300,
+                },
+                ThresholdViolation {
+                    kind: ThresholdKind::DescriptionTokens,
+                    tool: Some("search".to_string()),
+                    limit: 100,
+                    actual: 120,
+                },
+            ]
+        );
+        assert_eq!(violations[0].to_string(), "tool 'search' tokens 300 > 200");
+
+        let strict = AnalysisThresholds {
+            max_total_tokens: Some(100),
+            max_tools: Some(1),
+            ..Default::default()
+        };
+        let kinds: Vec<_> = strict.check(&analysis).iter().map(|v| v.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![ThresholdKind::TotalTokens, ThresholdKind::Tools]
+        );
+
+        let mut linted = analysis.clone();
+        linted.lint = lint_tools(
+            &[serde_json::json!({"name": "search", "description": "Search"})],
+            &LintOptions::default(),
+        );
+        let lint = AnalysisThresholds {
+            fail_on_lint: Some(LintSeverity::Warning),
+            ..Default::default()
+        };
+        assert!(lint.check(&analysis).is_empty());
+        let violations = lint.check(&linted);
+        assert_eq!(violations[0].kind, ThresholdKind::Lint);
+        assert_eq!(violations[0].to_string(), "lint findings 1 > 0");
+        let errors_only = AnalysisThresholds {
+            fail_on_lint: Some(LintSeverity::Error),
+            ..Default::default()
+        };
+        assert!(errors_only.check(&linted).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_analyze_skips_notifications() {
+        // Answers ids 1-4 in order, with noise the analyzer has to skip
+        let script = r#"
+            printf '%s\n' \
+              '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2024-11-05","serverInfo":{"name":"fake","version":"0.1.0"}}}' \
+              'not json' \
+              '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info"}}' \
+              '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo","description":"Echo the input back","inputSchema":{"type":"object"}}]}}' \
+              '{"jsonrpc":"2.0","id":3,"error":{"code":-32601,"message":"Method not found"}}' \
+              '{"jsonrpc":"2.0","id":4,"result":{"resources":[{"uri":"file:///a","name":"a"}]}}'
+            cat > /dev/null
+        "#;
+        let analysis =
+            ServerAnalyzer::new("sh".to_string(), vec!["-c".to_string(), script.to_string()])
+                .with_timeout(5)
+                .analyze()
+                .await
+                .unwrap();
+
+        assert_eq!(analysis.server_name, "fake");
+        assert_eq!(analysis.server_version, "0.1.0");
+        assert_eq!(analysis.tools.count, 1);
+        assert_eq!(analysis.tools.tools[0].name, "echo");
+        assert_eq!(
+            analysis.lint.findings[0].rule,
+            crate::tool_lint::LintRule::ShortDescription
+        );
+        assert_eq!(analysis.prompts.count, 0);
+        assert_eq!(analysis.resources.count, 1);
+        assert_eq!(
+            analysis.total_context_tokens,
+            analysis.tools.total_tokens + analysis.resources.total_tokens
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_analyze_server_exits() {
+        let err = ServerAnalyzer::new("true".to_string(), Vec::new())
+            .analyze()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AnalysisError::IoError(_)), "{err}");
+    }
+}
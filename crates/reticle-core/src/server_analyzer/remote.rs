@@ -0,0 +1,576 @@
+//! Remote transports for the server analyzer
+//!
+//! Speaks just enough of each MCP transport to run the analyzer's
+//! request/response exchange against a server that is already running:
+//!
+//! - Streamable HTTP: every message is POSTed to the server URL, and the
+//!   response comes back as JSON or as an SSE stream. The `Mcp-Session-Id`
+//!   from `initialize` is sent with later requests.
+//! - HTTP with SSE (legacy): a GET opens the event stream, whose `endpoint`
+//!   event names the URL to POST messages to. Responses arrive on the stream.
+//! - WebSocket: one JSON-RPC message per text frame.
+
+use super::{find_response, AnalysisError};
+use crate::transport::TransportType;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::{Client, Response, Url};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Header carrying the Streamable HTTP session
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Longest error body quoted in an error message
+const MAX_ERROR_BODY: usize = 200;
+
+/// A connection to a running MCP server
+pub(super) enum RemoteConnection {
+    Streamable {
+        client: Client,
+        url: Url,
+        headers: HeaderMap,
+        session_id: Option<HeaderValue>,
+    },
+    Sse {
+        client: Client,
+        /// Where messages are POSTed, from the `endpoint` event
+        endpoint: Url,
+        headers: HeaderMap,
+        events: mpsc::UnboundedReceiver<SseEvent>,
+        reader: JoinHandle<()>,
+    },
+    WebSocket(Box<WebSocketStream<MaybeTlsStream<TcpStream>>>),
+}
+
+impl RemoteConnection {
+    pub(super) async fn connect(
+        url: &str,
+        transport: TransportType,
+        headers: &HashMap<String, String>,
+    ) -> Result<Self, AnalysisError> {
+        match transport {
+            TransportType::Stdio => Err(AnalysisError::ConnectionFailed(
+                "stdio servers are analyzed by command, not URL".to_string(),
+            )),
+            TransportType::Streamable => Ok(RemoteConnection::Streamable {
+                client: Client::new(),
+                url: parse_url(url)?,
+                headers: header_map(headers)?,
+                session_id: None,
+            }),
+            TransportType::Http => Self::connect_sse(parse_url(url)?, header_map(headers)?).await,
+            TransportType::WebSocket => {
+                let mut request = url
+                    .into_client_request()
+                    .map_err(|e| AnalysisError::ConnectionFailed(e.to_string()))?;
+                request.headers_mut().extend(header_map(headers)?);
+                let (socket, _) = tokio_tungstenite::connect_async(request)
+                    .await
+                    .map_err(|e| AnalysisError::ConnectionFailed(e.to_string()))?;
+                Ok(RemoteConnection::WebSocket(Box::new(socket)))
+            }
+        }
+    }
+
+    /// Open the event stream and wait for the message endpoint
+    async fn connect_sse(url: Url, headers: HeaderMap) -> Result<Self, AnalysisError> {
+        let client = Client::new();
+        let response = client
+            .get(url.clone())
+            .headers(headers.clone())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(connection_failed)?;
+        let response = check_status(response).await?;
+
+        let (tx, mut events) = mpsc::unbounded_channel();
+        let reader = tokio::spawn(async move {
+            let mut stream = response.bytes_stream();
+            let mut parser = SseParser::default();
+            while let Some(Ok(chunk)) = stream.next().await {
+                for event in parser.push(&chunk) {
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        let endpoint = loop {
+            match events.recv().await {
+                Some(event) if event.event.as_deref() == Some("endpoint") => {
+                    break url.join(event.data.trim()).map_err(|e| {
+                        AnalysisError::InvalidResponse(format!("Bad endpoint event: {e}"))
+                    })?;
+                }
+                Some(_) => continue,
+                None => {
+                    return Err(AnalysisError::ConnectionFailed(
+                        "SSE stream closed before announcing the message endpoint".to_string(),
+                    ))
+                }
+            }
+        };
+
+        Ok(RemoteConnection::Sse {
+            client,
+            endpoint,
+            headers,
+            events,
+            reader,
+        })
+    }
+
+    /// Send a request and wait for the response with the same id
+    pub(super) async fn request(&mut self, request: Value) -> Result<Value, AnalysisError> {
+        let id = request.get("id").cloned().unwrap_or_default();
+        match self {
+            RemoteConnection::Streamable {
+                client,
+                url,
+                headers,
+                session_id,
+            } => {
+                let response =
+                    post_streamable(client, url, headers, session_id.as_ref(), &request).await?;
+                if let Some(id) = response.headers().get(SESSION_HEADER) {
+                    *session_id = Some(id.clone());
+                }
+
+                let is_sse = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .is_some_and(|v| v.starts_with("text/event-stream"));
+                if !is_sse {
+                    let message = response.json().await.map_err(|e| {
+                        AnalysisError::InvalidResponse(format!("Expected JSON: {e}"))
+                    })?;
+                    return find_response(message, &id).ok_or_else(|| {
+                        AnalysisError::InvalidResponse(format!("No response to request {id}"))
+                    });
+                }
+
+                let mut stream = response.bytes_stream();
+                let mut parser = SseParser::default();
+                while let Some(chunk) = stream.next().await {
+                    let chunk = chunk.map_err(|e| AnalysisError::IoError(e.to_string()))?;
+                    for event in parser.push(&chunk) {
+                        if let Some(response) = parse_response(&event.data, &id) {
+                            return Ok(response);
+                        }
+                    }
+                }
+                Err(AnalysisError::IoError(format!(
+                    "SSE stream ended before answering request {id}"
+                )))
+            }
+            RemoteConnection::Sse {
+                client,
+                endpoint,
+                headers,
+                events,
+                ..
+            } => {
+                post_json(client, endpoint, headers, &request).await?;
+                while let Some(event) = events.recv().await {
+                    if event.event.as_deref().unwrap_or("message") != "message" {
+                        continue;
+                    }
+                    if let Some(response) = parse_response(&event.data, &id) {
+                        return Ok(response);
+                    }
+                }
+                Err(AnalysisError::IoError(format!(
+                    "SSE stream closed before answering request {id}"
+                )))
+            }
+            RemoteConnection::WebSocket(socket) => {
+                send_ws(socket, &request).await?;
+                while let Some(frame) = socket.next().await {
+                    match frame.map_err(|e| AnalysisError::IoError(e.to_string()))? {
+                        Message::Text(text) => {
+                            if let Some(response) = parse_response(&text, &id) {
+                                return Ok(response);
+                            }
+                        }
+                        Message::Close(_) => break,
+                        _ => {}
+                    }
+                }
+                Err(AnalysisError::IoError(format!(
+                    "WebSocket closed before answering request {id}"
+                )))
+            }
+        }
+    }
+
+    /// Send a notification (no response expected)
+    pub(super) async fn notify(&mut self, notification: Value) -> Result<(), AnalysisError> {
+        match self {
+            RemoteConnection::Streamable {
+                client,
+                url,
+                headers,
+                session_id,
+            } => post_streamable(client, url, headers, session_id.as_ref(), &notification)
+                .await
+                .map(drop),
+            RemoteConnection::Sse {
+                client,
+                endpoint,
+                headers,
+                ..
+            } => post_json(client, endpoint, headers, &notification)
+                .await
+                .map(drop),
+            RemoteConnection::WebSocket(socket) => send_ws(socket, &notification).await,
+        }
+    }
+
+    /// End the session
+    pub(super) async fn close(self) {
+        match self {
+            RemoteConnection::Streamable {
+                client,
+                url,
+                headers,
+                session_id: Some(session_id),
+            } => {
+                // Servers may not support ending sessions, so errors are ignored
+                let _ = client
+                    .delete(url)
+                    .headers(headers)
+                    .header(SESSION_HEADER, session_id)
+                    .send()
+                    .await;
+            }
+            RemoteConnection::Streamable { .. } => {}
+            RemoteConnection::Sse { reader, .. } => reader.abort(),
+            RemoteConnection::WebSocket(mut socket) => {
+                let _ = socket.as_mut().close(None).await;
+            }
+        }
+    }
+}
+
+/// POST a message the Streamable HTTP way
+async fn post_streamable(
+    client: &Client,
+    url: &Url,
+    headers: &HeaderMap,
+    session_id: Option<&HeaderValue>,
+    message: &Value,
+) -> Result<Response, AnalysisError> {
+    let mut request = client
+        .post(url.clone())
+        .headers(headers.clone())
+        .header(ACCEPT, "application/json, text/event-stream")
+        .json(message);
+    if let Some(session_id) = session_id {
+        request = request.header(SESSION_HEADER, session_id);
+    }
+    check_status(request.send().await.map_err(connection_failed)?).await
+}
+
+/// POST a message to a legacy SSE server's endpoint
+async fn post_json(
+    client: &Client,
+    url: &Url,
+    headers: &HeaderMap,
+    message: &Value,
+) -> Result<Response, AnalysisError> {
+    let request = client
+        .post(url.clone())
+        .headers(headers.clone())
+        .json(message);
+    check_status(request.send().await.map_err(connection_failed)?).await
+}
+
+async fn send_ws(
+    socket: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
+    message: &Value,
+) -> Result<(), AnalysisError> {
+    socket
+        .send(Message::Text(message.to_string()))
+        .await
+        .map_err(|e| AnalysisError::IoError(e.to_string()))
+}
+
+/// Turn an HTTP error status into an error that quotes the body
+async fn check_status(response: Response) -> Result<Response, AnalysisError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let url = response.url().clone();
+    let body = response.text().await.unwrap_or_default();
+    let body: String = body.trim().chars().take(MAX_ERROR_BODY).collect();
+    Err(AnalysisError::ConnectionFailed(if body.is_empty() {
+        format!("{url} returned {status}")
+    } else {
+        format!("{url} returned {status}: {body}")
+    }))
+}
+
+fn parse_response(data: &str, id: &Value) -> Option<Value> {
+    serde_json::from_str(data)
+        .ok()
+        .and_then(|message| find_response(message, id))
+}
+
+fn parse_url(url: &str) -> Result<Url, AnalysisError> {
+    Url::parse(url).map_err(|e| AnalysisError::ConnectionFailed(format!("Invalid URL {url}: {e}")))
+}
+
+fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, AnalysisError> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = HeaderName::try_from(name.as_str()).map_err(|e| {
+                AnalysisError::ConnectionFailed(format!("Invalid header name {name}: {e}"))
+            })?;
+            let value = HeaderValue::try_from(value.as_str()).map_err(|e| {
+                AnalysisError::ConnectionFailed(format!("Invalid value for header {name}: {e}"))
+            })?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+fn connection_failed(e: reqwest::Error) -> AnalysisError {
+    AnalysisError::ConnectionFailed(e.to_string())
+}
+
+/// One server-sent event
+#[derive(Debug, PartialEq)]
+pub(super) struct SseEvent {
+    /// The `event:` field, if any
+    event: Option<String>,
+    /// The `data:` lines, joined with newlines
+    data: String,
+}
+
+/// Splits an SSE byte stream into events
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    /// Add a chunk from the stream, returning the events it completes
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend(chunk.iter().filter(|&&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let text = String::from_utf8_lossy(&block);
+            let mut event = None;
+            let mut data = Vec::new();
+            for line in text.lines() {
+                if let Some(value) = line.strip_prefix("data:") {
+                    data.push(value.strip_prefix(' ').unwrap_or(value));
+                } else if let Some(value) = line.strip_prefix("event:") {
+                    event = Some(value.trim().to_string());
+                }
+            }
+            if !data.is_empty() {
+                events.push(SseEvent {
+                    event,
+                    data: data.join("\n"),
+                });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ServerAnalyzer;
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// The answer a test server gives to each request
+    fn answer(message: &Value) -> Option<Value> {
+        let id = message.get("id")?;
+        let result = match message["method"].as_str()? {
+            "initialize" => serde_json::json!({
+                "protocolVersion": "2025-03-26",
+                "serverInfo": {"name": "remote", "version": "2.0.0"}
+            }),
+            "tools/list" => serde_json::json!({
+                "tools": [{"name": "deploy", "description": "Deploy a build", "inputSchema": {"type": "object"}}]
+            }),
+            _ => {
+                return Some(serde_json::json!({
+                    "jsonrpc": "2.0", "id": id, "error": {"code": -32601, "message": "Method not found"}
+                }))
+            }
+        };
+        Some(serde_json::json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    }
+
+    /// A Streamable HTTP server that requires a bearer token and the
+    /// session id, and answers `tools/list` over SSE
+    async fn serve_streamable(listener: TcpListener) {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut reader = BufReader::new(stream);
+                loop {
+                    let mut head = Vec::new();
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        if line == "\r\n" {
+                            break;
+                        }
+                        head.push(line.to_ascii_lowercase());
+                    }
+                    let length = head
+                        .iter()
+                        .find_map(|h| h.strip_prefix("content-length:"))
+                        .map_or(0, |v| v.trim().parse().unwrap());
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).await.unwrap();
+
+                    let has = |h: &str| head.iter().any(|line| line.trim() == h);
+                    let message: Value = serde_json::from_slice(&body).unwrap_or_default();
+                    let initialize = message["method"] == "initialize";
+                    let (status, headers, body) = if !has("authorization: bearer secret") {
+                        (
+                            "401 Unauthorized",
+                            String::new(),
+                            "missing token".to_string(),
+                        )
+                    } else if !initialize && !has("mcp-session-id: abc") {
+                        ("400 Bad Request", String::new(), String::new())
+                    } else {
+                        match answer(&message) {
+                            None => ("202 Accepted", String::new(), String::new()),
+                            Some(response) if message["method"] == "tools/list" => (
+                                "200 OK",
+                                "content-type: text/event-stream\r\n".to_string(),
+                                format!(
+                                    "event: message\r\ndata: {}\r\n\r\ndata: {response}\r\n\r\n",
+                                    serde_json::json!({"jsonrpc": "2.0", "method": "notifications/progress"})
+                                ),
+                            ),
+                            Some(response) => (
+                                "200 OK",
+                                "content-type: application/json\r\nmcp-session-id: abc\r\n"
+                                    .to_string(),
+                                response.to_string(),
+                            ),
+                        }
+                    };
+                    let reply = format!(
+                        "HTTP/1.1 {status}\r\n{headers}content-length: {}\r\n\r\n{body}",
+                        body.len()
+                    );
+                    reader.get_mut().write_all(reply.as_bytes()).await.unwrap();
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        assert!(parser
+            .push(b"event: endpoint\r\ndata: /messages")
+            .is_empty());
+        assert_eq!(
+            parser.push(b"?session=1\r\n\r\n: keep-alive\n\ndata: {\"a\":\ndata: 1}\n\n"),
+            vec![
+                SseEvent {
+                    event: Some("endpoint".to_string()),
+                    data: "/messages?session=1".to_string(),
+                },
+                SseEvent {
+                    event: None,
+                    data: "{\"a\":\n1}".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analyze_streamable() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(serve_streamable(listener));
+
+        let analyzer = ServerAnalyzer::remote(url.clone(), TransportType::Streamable)
+            .with_timeout(5)
+            .with_headers(HashMap::from([(
+                "Authorization".to_string(),
+                "Bearer secret".to_string(),
+            )]));
+        let analysis = analyzer.analyze().await.unwrap();
+        assert_eq!(analysis.server_name, "remote");
+        assert_eq!(analysis.protocol_version, "2025-03-26");
+        assert_eq!(analysis.tools.count, 1);
+        assert_eq!(analysis.tools.tools[0].name, "deploy");
+
+        let err = ServerAnalyzer::remote(url, TransportType::Streamable)
+            .with_timeout(5)
+            .analyze()
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, AnalysisError::ConnectionFailed(ref msg) if msg.contains("401") && msg.contains("missing token")),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analyze_websocket() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = socket.next().await {
+                let message: Value = serde_json::from_str(&text).unwrap();
+                if let Some(response) = answer(&message) {
+                    socket
+                        .send(Message::Text(response.to_string()))
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+
+        let analysis = ServerAnalyzer::remote(url, TransportType::WebSocket)
+            .with_timeout(5)
+            .analyze()
+            .await
+            .unwrap();
+        assert_eq!(analysis.server_name, "remote");
+        assert_eq!(analysis.tools.count, 1);
+        assert_eq!(analysis.prompts.count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stdio_transport_needs_command() {
+        let err = ServerAnalyzer::remote("http://localhost".to_string(), TransportType::Stdio)
+            .analyze()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AnalysisError::ConnectionFailed(_)));
+    }
+}
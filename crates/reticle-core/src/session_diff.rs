@@ -0,0 +1,718 @@
+//! Semantic diff between two recorded sessions
+//!
+//! Each session is reduced to a list of calls: a request or notification
+//! paired with its response, if any. The two lists are aligned on the
+//! sequence of methods (and tool names for `tools/call`), preferring pairs
+//! that also share a JSON-RPC id. Aligned calls are compared structurally,
+//! so a reordered object or a new field shows up as a precise path rather
+//! than a changed line.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::protocol::McpMessage;
+use crate::session_recorder::{MessageDirection, RecordedSession};
+use crate::token_counter::TokenCounter;
+
+/// Thresholds for reporting latency regressions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffOptions {
+    /// Relative slowdown that counts as a regression (0.2 = 20% slower)
+    pub latency_threshold: f64,
+    /// Slowdowns smaller than this are ignored, however large relatively
+    pub min_latency_delta_ms: u64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            latency_threshold: 0.2,
+            min_latency_delta_ms: 50,
+        }
+    }
+}
+
+/// How a call differs between the two sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffStatus {
+    /// Only in the second session
+    Added,
+    /// Only in the first session
+    Removed,
+    /// In both, with different params or results
+    Changed,
+    /// In both, with identical params and results
+    Unchanged,
+}
+
+/// Kind of change at a JSON path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single structural difference between two JSON values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonChange {
+    /// JSONPath of the changed value
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+/// One side of an aligned call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallSummary {
+    /// Position of the call within its session
+    pub index: usize,
+    /// JSON-RPC id (absent for notifications)
+    pub id: Option<Value>,
+    /// Time from request to response
+    pub latency_ms: Option<u64>,
+    /// Estimated LLM context tokens of the request and response
+    pub tokens: u64,
+}
+
+/// Comparison of one call across the two sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallDiff {
+    pub status: DiffStatus,
+    pub method: String,
+    pub tool: Option<String>,
+    pub direction: MessageDirection,
+    pub before: Option<CallSummary>,
+    pub after: Option<CallSummary>,
+    /// Changes to the request params (`_meta` is ignored)
+    pub params: Vec<JsonChange>,
+    /// Changes to the response `result` or `error`
+    pub result: Vec<JsonChange>,
+    pub latency_delta_ms: Option<i64>,
+    pub latency_regression: bool,
+    pub token_delta: i64,
+}
+
+/// Totals for a session diff
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    pub latency_regressions: usize,
+    pub tokens_before: u64,
+    pub tokens_after: u64,
+    pub token_delta: i64,
+}
+
+/// Semantic diff between two recorded sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionDiff {
+    /// Id of the first (baseline) session
+    pub before: String,
+    /// Id of the second session
+    pub after: String,
+    pub calls: Vec<CallDiff>,
+    pub summary: DiffSummary,
+}
+
+impl SessionDiff {
+    /// Whether the sessions differ in calls, payloads, or latency
+    pub fn has_differences(&self) -> bool {
+        let s = &self.summary;
+        s.added + s.removed + s.changed + s.latency_regressions > 0
+    }
+}
+
+/// A request or notification with its response
+#[derive(Debug)]
+struct Call {
+    method: String,
+    tool: Option<String>,
+    direction: MessageDirection,
+    id: Option<Value>,
+    params: Value,
+    outcome: Option<Value>,
+    latency_ms: Option<u64>,
+    tokens: u64,
+}
+
+impl Call {
+    fn same_kind(&self, other: &Call) -> bool {
+        self.direction == other.direction && self.method == other.method && self.tool == other.tool
+    }
+
+    fn summary(&self, index: usize) -> CallSummary {
+        CallSummary {
+            index,
+            id: self.id.clone(),
+            latency_ms: self.latency_ms,
+            tokens: self.tokens,
+        }
+    }
+}
+
+/// Compare two sessions call by call
+pub fn diff_sessions(
+    before: &RecordedSession,
+    after: &RecordedSession,
+    options: &DiffOptions,
+) -> SessionDiff {
+    let a = extract_calls(before);
+    let b = extract_calls(after);
+    let mut calls = Vec::new();
+    let mut summary = DiffSummary::default();
+
+    for (i, j) in align(&a, &b) {
+        let diff = match (i, j) {
+            (Some(i), Some(j)) => compare(&a[i], i, &b[j], j, options),
+            (Some(i), None) => one_sided(&a[i], DiffStatus::Removed, Some(a[i].summary(i)), None),
+            (None, Some(j)) => one_sided(&b[j], DiffStatus::Added, None, Some(b[j].summary(j))),
+            (None, None) => continue,
+        };
+        match diff.status {
+            DiffStatus::Added => summary.added += 1,
+            DiffStatus::Removed => summary.removed += 1,
+            DiffStatus::Changed => summary.changed += 1,
+            DiffStatus::Unchanged => summary.unchanged += 1,
+        }
+        if diff.latency_regression {
+            summary.latency_regressions += 1;
+        }
+        calls.push(diff);
+    }
+
+    summary.tokens_before = a.iter().map(|c| c.tokens).sum();
+    summary.tokens_after = b.iter().map(|c| c.tokens).sum();
+    summary.token_delta = summary.tokens_after as i64 - summary.tokens_before as i64;
+
+    SessionDiff {
+        before: before.id.clone(),
+        after: after.id.clone(),
+        calls,
+        summary,
+    }
+}
+
+/// Pair requests with their responses, in request order
+fn extract_calls(session: &RecordedSession) -> Vec<Call> {
+    let mut calls: Vec<Call> = Vec::new();
+    // Open requests by direction and id, pointing into `calls`
+    let mut pending: HashMap<(bool, String), (usize, u64)> = HashMap::new();
+
+    for message in &session.messages {
+        let content = McpMessage::parse(&message.content);
+        let id = content.id();
+        let to_server = message.direction == MessageDirection::ToServer;
+
+        if let Some(method) = content.method() {
+            if let Some(id) = id {
+                pending.insert(
+                    (to_server, id.to_string()),
+                    (calls.len(), message.timestamp_micros),
+                );
+            }
+            calls.push(Call {
+                method: method.to_string(),
+                tool: content.tool_name().map(String::from),
+                direction: message.direction,
+                id: id.cloned(),
+                params: content.params().unwrap_or(Value::Null),
+                outcome: None,
+                latency_ms: None,
+                tokens: TokenCounter::count_mcp_context_tokens(&content),
+            });
+        } else if let Some(id) = id {
+            // A response answers a request sent the other way
+            let Some((index, sent_at)) = pending.remove(&(!to_server, id.to_string())) else {
+                continue;
+            };
+            let call = &mut calls[index];
+            call.outcome = match &content {
+                McpMessage::Response { result, .. } => Some(result.to_value()),
+                McpMessage::Error { error, .. } => serde_json::to_value(error).ok(),
+                _ => None,
+            };
+            call.latency_ms = Some(message.timestamp_micros.saturating_sub(sent_at) / 1000);
+            call.tokens += TokenCounter::count_mcp_context_tokens(&content.answering(&call.method));
+        }
+    }
+
+    calls
+}
+
+/// Align two call lists, returning index pairs in order
+///
+/// Common leading and trailing calls are matched directly; the rest is
+/// aligned with a weighted LCS where calls of the same kind score 2 and a
+/// shared JSON-RPC id adds 1.
+fn align(a: &[Call], b: &[Call]) -> Vec<(Option<usize>, Option<usize>)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x.same_kind(y)).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x.same_kind(y))
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let score = |x: &Call, y: &Call| -> u32 {
+        if !x.same_kind(y) {
+            0
+        } else if x.id.is_some() && x.id == y.id {
+            3
+        } else {
+            2
+        }
+    };
+
+    // best[i][j] = best score aligning a_mid[i..] with b_mid[j..]
+    let (n, m) = (a_mid.len(), b_mid.len());
+    let mut best = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            let s = score(&a_mid[i], &b_mid[j]);
+            let paired = if s > 0 { s + best[i + 1][j + 1] } else { 0 };
+            best[i][j] = paired.max(best[i + 1][j]).max(best[i][j + 1]);
+        }
+    }
+
+    let mut pairs: Vec<_> = (0..prefix).map(|i| (Some(i), Some(i))).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        let s = score(&a_mid[i], &b_mid[j]);
+        if s > 0 && best[i][j] == s + best[i + 1][j + 1] {
+            pairs.push((Some(prefix + i), Some(prefix + j)));
+            i += 1;
+            j += 1;
+        } else if best[i + 1][j] >= best[i][j + 1] {
+            pairs.push((Some(prefix + i), None));
+            i += 1;
+        } else {
+            pairs.push((None, Some(prefix + j)));
+            j += 1;
+        }
+    }
+    pairs.extend((i..n).map(|i| (Some(prefix + i), None)));
+    pairs.extend((j..m).map(|j| (None, Some(prefix + j))));
+    pairs.extend((0..suffix).map(|k| (Some(a.len() - suffix + k), Some(b.len() - suffix + k))));
+    pairs
+}
+
+fn one_sided(
+    call: &Call,
+    status: DiffStatus,
+    before: Option<CallSummary>,
+    after: Option<CallSummary>,
+) -> CallDiff {
+    let token_delta = if before.is_some() {
+        -(call.tokens as i64)
+    } else {
+        call.tokens as i64
+    };
+    CallDiff {
+        status,
+        method: call.method.clone(),
+        tool: call.tool.clone(),
+        direction: call.direction,
+        before,
+        after,
+        params: Vec::new(),
+        result: Vec::new(),
+        latency_delta_ms: None,
+        latency_regression: false,
+        token_delta,
+    }
+}
+
+fn compare(a: &Call, i: usize, b: &Call, j: usize, options: &DiffOptions) -> CallDiff {
+    let mut params = Vec::new();
+    diff_json(
+        &without_meta(&a.params),
+        &without_meta(&b.params),
+        "$",
+        &mut params,
+    );
+
+    let mut result = Vec::new();
+    match (&a.outcome, &b.outcome) {
+        (Some(x), Some(y)) => diff_json(x, y, "$", &mut result),
+        (x, y) if x != y => result.push(JsonChange {
+            path: "$".to_string(),
+            kind: if x.is_some() {
+                ChangeKind::Removed
+            } else {
+                ChangeKind::Added
+            },
+            before: x.clone(),
+            after: y.clone(),
+        }),
+        _ => {}
+    }
+
+    let latency_delta_ms = match (a.latency_ms, b.latency_ms) {
+        (Some(x), Some(y)) => Some(y as i64 - x as i64),
+        _ => None,
+    };
+    let latency_regression = match (a.latency_ms, latency_delta_ms) {
+        (Some(base), Some(delta)) => {
+            delta > 0
+                && delta as u64 >= options.min_latency_delta_ms
+                && delta as f64 > base as f64 * options.latency_threshold
+        }
+        _ => false,
+    };
+
+    CallDiff {
+        status: if params.is_empty() && result.is_empty() {
+            DiffStatus::Unchanged
+        } else {
+            DiffStatus::Changed
+        },
+        method: a.method.clone(),
+        tool: a.tool.clone(),
+        direction: a.direction,
+        before: Some(a.summary(i)),
+        after: Some(b.summary(j)),
+        params,
+        result,
+        latency_delta_ms,
+        latency_regression,
+        token_delta: b.tokens as i64 - a.tokens as i64,
+    }
+}
+
+/// Params without `_meta`, which carries per-run progress tokens
+fn without_meta(params: &Value) -> Value {
+    let mut params = params.clone();
+    if let Some(map) = params.as_object_mut() {
+        map.remove("_meta");
+    }
+    params
+}
+
+/// Record every structural difference between two values
+pub fn diff_json(before: &Value, after: &Value, path: &str, changes: &mut Vec<JsonChange>) {
+    match (before, after) {
+        (Value::Object(x), Value::Object(y)) => {
+            let keys: BTreeSet<&String> = x.keys().chain(y.keys()).collect();
+            for key in keys {
+                let child = child_path(path, key);
+                match (x.get(key), y.get(key)) {
+                    (Some(a), Some(b)) => diff_json(a, b, &child, changes),
+                    (Some(a), None) => changes.push(JsonChange {
+                        path: child,
+                        kind: ChangeKind::Removed,
+                        before: Some(a.clone()),
+                        after: None,
+                    }),
+                    (None, Some(b)) => changes.push(JsonChange {
+                        path: child,
+                        kind: ChangeKind::Added,
+                        before: None,
+                        after: Some(b.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(x), Value::Array(y)) => {
+            for index in 0..x.len().max(y.len()) {
+                let child = format!("{path}[{index}]");
+                match (x.get(index), y.get(index)) {
+                    (Some(a), Some(b)) => diff_json(a, b, &child, changes),
+                    (Some(a), None) => changes.push(JsonChange {
+                        path: child,
+                        kind: ChangeKind::Removed,
+                        before: Some(a.clone()),
+                        after: None,
+                    }),
+                    (None, Some(b)) => changes.push(JsonChange {
+                        path: child,
+                        kind: ChangeKind::Added,
+                        before: None,
+                        after: Some(b.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (a, b) if a != b => changes.push(JsonChange {
+            path: path.to_string(),
+            kind: ChangeKind::Changed,
+            before: Some(a.clone()),
+            after: Some(b.clone()),
+        }),
+        _ => {}
+    }
+}
+
+/// Append an object key to a JSONPath, quoting keys that need it
+fn child_path(path: &str, key: &str) -> String {
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if plain {
+        format!("{path}.{key}")
+    } else {
+        format!("{path}['{key}']")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_recorder::{MessageMetadata, RecordedMessage, SessionMetadata};
+    use serde_json::json;
+
+    /// Build a session from (time in ms, direction, message) triples
+    fn session(id: &str, messages: Vec<(u64, MessageDirection, Value)>) -> RecordedSession {
+        RecordedSession {
+            id: id.to_string(),
+            name: id.to_string(),
+            started_at: 0,
+            ended_at: None,
+            messages: messages
+                .into_iter()
+                .enumerate()
+                .map(|(i, (ms, direction, content))| RecordedMessage {
+                    id: format!("{id}-{i}"),
+                    timestamp_micros: ms * 1000,
+                    relative_time_ms: ms,
+                    direction,
+                    metadata: MessageMetadata {
+                        method: None,
+                        jsonrpc_id: None,
+                        injected: false,
+                        modified: false,
+                        original: None,
+                        size_bytes: 0,
+                        schema_errors: Vec::new(),
+                    },
+                    content,
+                })
+                .collect(),
+            metadata: SessionMetadata {
+                transport: "stdio".to_string(),
+                message_count: 0,
+                duration_ms: None,
+                client_info: None,
+                server_info: None,
+                protocol_version: None,
+                server_id: None,
+                tags: vec![],
+                latency: None,
+            },
+        }
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+    }
+
+    fn response(id: u64, result: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "result": result})
+    }
+
+    use MessageDirection::{ToClient, ToServer};
+
+    #[test]
+    fn test_identical_sessions() {
+        let messages = vec![
+            (0, ToServer, request(1, "tools/list", json!({}))),
+            (10, ToClient, response(1, json!({"tools": []}))),
+        ];
+        let diff = diff_sessions(
+            &session("a", messages.clone()),
+            &session("b", messages),
+            &DiffOptions::default(),
+        );
+        assert!(!diff.has_differences());
+        assert_eq!(diff.summary.unchanged, 1);
+        assert_eq!(diff.summary.token_delta, 0);
+    }
+
+    #[test]
+    fn test_added_removed_and_changed_calls() {
+        let a = session(
+            "a",
+            vec![
+                (0, ToServer, request(1, "initialize", json!({}))),
+                (
+                    5,
+                    ToClient,
+                    response(1, json!({"serverInfo": {"version": "1.0"}})),
+                ),
+                (10, ToServer, request(2, "prompts/list", json!({}))),
+                (15, ToClient, response(2, json!({"prompts": []}))),
+                (
+                    20,
+                    ToServer,
+                    request(
+                        3,
+                        "tools/call",
+                        json!({"name": "read", "arguments": {"path": "/a"}}),
+                    ),
+                ),
+                (
+                    25,
+                    ToClient,
+                    response(3, json!({"content": [{"type": "text", "text": "x"}]})),
+                ),
+            ],
+        );
+        let b = session(
+            "b",
+            vec![
+                (0, ToServer, request(1, "initialize", json!({}))),
+                (
+                    5,
+                    ToClient,
+                    response(1, json!({"serverInfo": {"version": "1.1"}})),
+                ),
+                (
+                    10,
+                    ToServer,
+                    request(
+                        2,
+                        "tools/call",
+                        json!({"name": "read", "arguments": {"path": "/b"}, "_meta": {"progressToken": 1}}),
+                    ),
+                ),
+                (
+                    15,
+                    ToClient,
+                    response(2, json!({"content": [{"type": "text", "text": "x"}]})),
+                ),
+                (
+                    20,
+                    ToServer,
+                    json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                ),
+            ],
+        );
+
+        let diff = diff_sessions(&a, &b, &DiffOptions::default());
+        let statuses: Vec<_> = diff
+            .calls
+            .iter()
+            .map(|c| (c.status, c.method.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (DiffStatus::Changed, "initialize"),
+                (DiffStatus::Removed, "prompts/list"),
+                (DiffStatus::Changed, "tools/call"),
+                (DiffStatus::Added, "notifications/initialized"),
+            ]
+        );
+
+        assert_eq!(
+            diff.calls[0].result,
+            vec![JsonChange {
+                path: "$.serverInfo.version".to_string(),
+                kind: ChangeKind::Changed,
+                before: Some(json!("1.0")),
+                after: Some(json!("1.1")),
+            }]
+        );
+        // `_meta` is not reported as a params change
+        let call = &diff.calls[2];
+        assert_eq!(call.tool.as_deref(), Some("read"));
+        assert_eq!(call.params.len(), 1);
+        assert_eq!(call.params[0].path, "$.arguments.path");
+        assert!(call.result.is_empty());
+    }
+
+    #[test]
+    fn test_alignment_prefers_matching_ids() {
+        let a = session(
+            "a",
+            vec![
+                (0, ToServer, request(1, "ping", json!({}))),
+                (1, ToClient, response(1, json!({}))),
+                (2, ToServer, request(2, "tools/list", json!({}))),
+                (3, ToClient, response(2, json!({}))),
+            ],
+        );
+        let b = session(
+            "b",
+            vec![
+                (0, ToServer, request(2, "tools/list", json!({}))),
+                (1, ToClient, response(2, json!({}))),
+                (2, ToServer, request(3, "ping", json!({}))),
+                (3, ToClient, response(3, json!({}))),
+            ],
+        );
+        let diff = diff_sessions(&a, &b, &DiffOptions::default());
+        let tools = diff
+            .calls
+            .iter()
+            .find(|c| c.method == "tools/list" && c.status == DiffStatus::Unchanged)
+            .unwrap();
+        assert_eq!(tools.before.as_ref().unwrap().id, Some(json!(2)));
+        assert_eq!(diff.summary.added + diff.summary.removed, 2);
+    }
+
+    #[test]
+    fn test_latency_regressions() {
+        let calls = |latency: u64| {
+            vec![
+                (0, ToServer, request(1, "tools/list", json!({}))),
+                (latency, ToClient, response(1, json!({}))),
+            ]
+        };
+        let options = DiffOptions::default();
+
+        let diff = diff_sessions(
+            &session("a", calls(100)),
+            &session("b", calls(400)),
+            &options,
+        );
+        assert!(diff.calls[0].latency_regression);
+        assert_eq!(diff.calls[0].latency_delta_ms, Some(300));
+        assert_eq!(diff.summary.latency_regressions, 1);
+        assert!(diff.has_differences());
+
+        // Below the absolute floor
+        let diff = diff_sessions(&session("a", calls(10)), &session("b", calls(40)), &options);
+        assert!(!diff.calls[0].latency_regression);
+
+        // Below the relative threshold
+        let diff = diff_sessions(
+            &session("a", calls(1000)),
+            &session("b", calls(1100)),
+            &options,
+        );
+        assert!(!diff.calls[0].latency_regression);
+    }
+
+    #[test]
+    fn test_diff_json() {
+        let mut changes = Vec::new();
+        diff_json(
+            &json!({"a": [1, 2], "odd key": 1, "same": true}),
+            &json!({"a": [1], "new": null, "same": true, "odd key": 2}),
+            "$",
+            &mut changes,
+        );
+        let paths: Vec<_> = changes.iter().map(|c| (c.path.as_str(), c.kind)).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("$.a[1]", ChangeKind::Removed),
+                ("$.new", ChangeKind::Added),
+                ("$['odd key']", ChangeKind::Changed),
+            ]
+        );
+    }
+}
@@ -2,13 +2,23 @@
 //!
 //! This module provides functionality to record complete MCP sessions,
 //! including all messages exchanged between client and server, along
-//! with timing information for accurate replay.
+//! with timing information for accurate replay. The `initialize` handshake
+//! is picked out as it is recorded, so every session knows which client and
+//! server took part, the protocol version they agreed on, and the
+//! capabilities each declared. Tool calls whose arguments or results do not
+//! match the schemas from `tools/list` are flagged on the recorded message.
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex;
 
+use crate::latency::SessionLatencyStats;
+use crate::protocol::mcp::{InitializeRequest, InitializeResult};
+use crate::protocol::message::{McpResult, Request};
+use crate::protocol::{Direction, McpMessage};
+use crate::tool_schemas::{SchemaMismatch, ToolSchemas};
+
 /// A complete recorded session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordedSession {
@@ -20,6 +30,58 @@ pub struct RecordedSession {
     pub metadata: SessionMetadata,
 }
 
+/// How to space out messages when replaying a session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayTiming {
+    /// Playback speed; 2.0 replays twice as fast, 0.0 sends back to back
+    pub speed: f64,
+    /// Upper bound on the pause between two messages
+    pub max_gap: Option<Duration>,
+}
+
+impl Default for ReplayTiming {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            max_gap: None,
+        }
+    }
+}
+
+impl RecordedSession {
+    /// The messages sent to the server, each with its send time relative
+    /// to the start of a replay
+    ///
+    /// Gaps between consecutive messages follow their `relative_time_ms`,
+    /// scaled by the playback speed and capped at `max_gap`.
+    pub fn replay_schedule(&self, timing: &ReplayTiming) -> Vec<(Duration, &RecordedMessage)> {
+        let mut schedule = Vec::new();
+        let mut offset = Duration::ZERO;
+        let mut previous_ms = 0;
+
+        for message in &self.messages {
+            if message.direction != MessageDirection::ToServer {
+                continue;
+            }
+            let gap_ms = message.relative_time_ms.saturating_sub(previous_ms);
+            previous_ms = previous_ms.max(message.relative_time_ms);
+
+            let mut gap = if timing.speed > 0.0 {
+                Duration::from_secs_f64(gap_ms as f64 / 1000.0 / timing.speed)
+            } else {
+                Duration::ZERO
+            };
+            if let Some(max_gap) = timing.max_gap {
+                gap = gap.min(max_gap);
+            }
+            offset += gap;
+            schedule.push((offset, message));
+        }
+
+        schedule
+    }
+}
+
 /// Individual recorded message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordedMessage {
@@ -43,7 +105,7 @@ pub struct RecordedMessage {
 }
 
 /// Message direction
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum MessageDirection {
     /// Message from client to server
@@ -61,6 +123,15 @@ impl std::fmt::Display for MessageDirection {
     }
 }
 
+impl From<MessageDirection> for Direction {
+    fn from(direction: MessageDirection) -> Self {
+        match direction {
+            MessageDirection::ToServer => Direction::In,
+            MessageDirection::ToClient => Direction::Out,
+        }
+    }
+}
+
 /// Metadata about a recorded message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageMetadata {
@@ -76,8 +147,16 @@ pub struct MessageMetadata {
     /// Whether this message was modified by the debugger
     pub modified: bool,
 
+    /// The message as received, for messages modified before forwarding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<serde_json::Value>,
+
     /// Size in bytes
     pub size_bytes: usize,
+
+    /// Where a tool call or its result breaks the tool's advertised schema
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schema_errors: Vec<SchemaMismatch>,
 }
 
 /// Session metadata
@@ -92,12 +171,17 @@ pub struct SessionMetadata {
     /// Session duration in milliseconds
     pub duration_ms: Option<u64>,
 
-    /// Client information (if available)
+    /// Client information from the `initialize` request (if recorded)
     pub client_info: Option<ClientInfo>,
 
-    /// Server information (if available)
+    /// Server information from the `initialize` response (if recorded)
     pub server_info: Option<ServerInfo>,
 
+    /// Protocol version the server accepted, or the one the client asked
+    /// for if the response wasn't recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+
     /// Server identifier for multi-server support
     #[serde(default)]
     pub server_id: Option<ServerIdentifier>,
@@ -105,6 +189,10 @@ pub struct SessionMetadata {
     /// Custom tags for filtering and organization
     #[serde(default)]
     pub tags: Vec<String>,
+
+    /// Response times, computed when the recording is finalized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<SessionLatencyStats>,
 }
 
 /// Server identifier for multi-server tracking
@@ -128,16 +216,112 @@ pub struct ServerIdentifier {
     pub connection_type: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The client's half of the `initialize` handshake
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ClientInfo {
     pub name: String,
     pub version: String,
+
+    /// Protocol version the client asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+
+    /// The client's `capabilities` object, as sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The server's half of the `initialize` handshake
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServerInfo {
     pub name: String,
     pub version: String,
+
+    /// Protocol version the server chose
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+
+    /// The server's `capabilities` object, as sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<serde_json::Value>,
+
+    /// Usage instructions for the model, if the server sent any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+impl ClientInfo {
+    /// Read the params of an `initialize` request
+    pub fn from_initialize(params: &InitializeRequest) -> Self {
+        Self {
+            name: params.client_info.name.clone(),
+            version: params.client_info.version.clone(),
+            protocol_version: Some(params.protocol_version.clone()),
+            capabilities: declared(&params.capabilities),
+        }
+    }
+}
+
+impl ServerInfo {
+    /// Read the result of an `initialize` response
+    pub fn from_initialize(result: &InitializeResult) -> Self {
+        Self {
+            name: result.server_info.name.clone(),
+            version: result.server_info.version.clone(),
+            protocol_version: Some(result.protocol_version.clone()),
+            capabilities: declared(&result.capabilities),
+            instructions: result.instructions.clone(),
+        }
+    }
+}
+
+fn declared(capabilities: &serde_json::Value) -> Option<serde_json::Value> {
+    Some(capabilities.clone()).filter(|c| !c.is_null())
+}
+
+/// What the recorder has seen of the `initialize` handshake
+#[derive(Debug, Clone, Default)]
+struct Handshake {
+    /// Id of the last `initialize` request, to match its response
+    request_id: Option<serde_json::Value>,
+    client: Option<ClientInfo>,
+    server: Option<ServerInfo>,
+}
+
+impl Handshake {
+    fn observe(&mut self, message: &McpMessage, direction: MessageDirection) {
+        match (message, direction) {
+            (
+                McpMessage::Request {
+                    id,
+                    request: Request::Initialize(params),
+                },
+                MessageDirection::ToServer,
+            ) => {
+                self.client = Some(ClientInfo::from_initialize(params));
+                self.request_id = Some(id.clone());
+            }
+            (
+                McpMessage::Response {
+                    id,
+                    result: McpResult::Initialize(result),
+                },
+                MessageDirection::ToClient,
+            ) => {
+                let answers_request = match self.request_id {
+                    Some(ref request_id) => id == request_id,
+                    // Recording started after the request; fall back to
+                    // recognizing the response by its shape
+                    None => self.server.is_none(),
+                };
+                if answers_request {
+                    self.server = Some(ServerInfo::from_initialize(result));
+                    self.request_id = None;
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 /// Active session recorder
@@ -153,6 +337,8 @@ pub struct SessionRecorder {
     transport_type: String,
     server_id: Option<ServerIdentifier>,
     tags: Arc<Mutex<Vec<String>>>,
+    handshake: Arc<Mutex<Handshake>>,
+    schemas: Arc<ToolSchemas>,
 }
 
 impl SessionRecorder {
@@ -166,6 +352,8 @@ impl SessionRecorder {
             transport_type,
             server_id: None,
             tags: Arc::new(Mutex::new(Vec::new())),
+            handshake: Arc::new(Mutex::new(Handshake::default())),
+            schemas: Arc::new(ToolSchemas::new()),
         }
     }
 
@@ -184,9 +372,18 @@ impl SessionRecorder {
             transport_type,
             server_id: Some(server_id),
             tags: Arc::new(Mutex::new(Vec::new())),
+            handshake: Arc::new(Mutex::new(Handshake::default())),
+            schemas: Arc::new(ToolSchemas::new()),
         }
     }
 
+    /// Check tool calls against schemas shared with another observer of
+    /// the session, such as its conformance checker
+    pub fn with_tool_schemas(mut self, schemas: Arc<ToolSchemas>) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
     /// Get session ID
     pub fn session_id(&self) -> &str {
         &self.session_id
@@ -221,11 +418,56 @@ impl SessionRecorder {
         self.server_id.as_ref()
     }
 
+    /// Client information, once the `initialize` request is recorded
+    pub async fn client_info(&self) -> Option<ClientInfo> {
+        self.handshake.lock().await.client.clone()
+    }
+
+    /// Server information, once the `initialize` response is recorded
+    pub async fn server_info(&self) -> Option<ServerInfo> {
+        self.handshake.lock().await.server.clone()
+    }
+
     /// Record a message
     pub async fn record_message(
         &self,
         content: serde_json::Value,
         direction: MessageDirection,
+    ) -> Result<(), RecorderError> {
+        self.push_message(generate_uuid(), content, direction, None)
+            .await
+    }
+
+    /// Record a message that was modified before being forwarded,
+    /// keeping the original next to it
+    pub async fn record_modified_message(
+        &self,
+        content: serde_json::Value,
+        original: serde_json::Value,
+        direction: MessageDirection,
+    ) -> Result<(), RecorderError> {
+        self.push_message(generate_uuid(), content, direction, Some(original))
+            .await
+    }
+
+    /// Record a message under the ID of the log entry it was seen in, so
+    /// the recording and the live log can be matched up
+    pub async fn record_logged_message(
+        &self,
+        id: String,
+        content: serde_json::Value,
+        original: Option<serde_json::Value>,
+        direction: MessageDirection,
+    ) -> Result<(), RecorderError> {
+        self.push_message(id, content, direction, original).await
+    }
+
+    async fn push_message(
+        &self,
+        id: String,
+        content: serde_json::Value,
+        direction: MessageDirection,
+        original: Option<serde_json::Value>,
     ) -> Result<(), RecorderError> {
         let now = SystemTime::now();
         let timestamp_micros = now
@@ -238,20 +480,19 @@ impl SessionRecorder {
             .map_err(|e| RecorderError::TimeError(e.to_string()))?
             .as_millis() as u64;
 
-        // Extract metadata from message
-        let method = content
-            .get("method")
-            .and_then(|v| v.as_str())
-            .map(String::from);
+        let message = McpMessage::parse(&content);
+        let method = message.method().map(String::from);
+        let jsonrpc_id = message.id().cloned();
 
-        let jsonrpc_id = content.get("id").cloned();
+        self.handshake.lock().await.observe(&message, direction);
+        let schema_errors = self.schemas.observe(&message, direction.into());
 
         let content_str = serde_json::to_string(&content)
             .map_err(|e| RecorderError::SerializationError(e.to_string()))?;
         let size_bytes = content_str.len();
 
         let message = RecordedMessage {
-            id: generate_uuid(),
+            id,
             timestamp_micros,
             relative_time_ms,
             direction,
@@ -260,8 +501,10 @@ impl SessionRecorder {
                 method,
                 jsonrpc_id,
                 injected: false,
-                modified: false,
+                modified: original.is_some(),
+                original,
                 size_bytes,
+                schema_errors,
             },
         };
 
@@ -273,7 +516,16 @@ impl SessionRecorder {
 
     /// Finalize the recording and return the complete session
     pub async fn finalize(self) -> Result<RecordedSession, RecorderError> {
-        let ended_at = SystemTime::now()
+        self.session(true).await
+    }
+
+    /// The session recorded so far, leaving the recording running
+    pub async fn snapshot(&self) -> Result<RecordedSession, RecorderError> {
+        self.session(false).await
+    }
+
+    async fn session(&self, ended: bool) -> Result<RecordedSession, RecorderError> {
+        let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map_err(|e| RecorderError::TimeError(e.to_string()))?
             .as_micros() as u64;
@@ -288,24 +540,47 @@ impl SessionRecorder {
         let message_count = messages.len();
         let tags = self.tags.lock().await.clone();
 
-        let duration_ms = (ended_at - started_at) / 1000;
+        let duration_ms = (now - started_at) / 1000;
+
+        let handshake = self.handshake.lock().await.clone();
+        let protocol_version = handshake
+            .server
+            .as_ref()
+            .and_then(|s| s.protocol_version.clone())
+            .or_else(|| {
+                handshake
+                    .client
+                    .as_ref()
+                    .and_then(|c| c.protocol_version.clone())
+            });
+        let mut server_id = self.server_id.clone();
+        if let (Some(id), Some(info)) = (&mut server_id, &handshake.server) {
+            if id.version.is_none() && !info.version.is_empty() {
+                id.version = Some(info.version.clone());
+            }
+        }
 
-        Ok(RecordedSession {
-            id: self.session_id,
-            name: self.session_name,
+        let mut session = RecordedSession {
+            id: self.session_id.clone(),
+            name: self.session_name.clone(),
             started_at,
-            ended_at: Some(ended_at),
+            ended_at: ended.then_some(now),
             messages,
             metadata: SessionMetadata {
-                transport: self.transport_type,
+                transport: self.transport_type.clone(),
                 message_count,
                 duration_ms: Some(duration_ms),
-                client_info: None, // Will be populated from initialize message
-                server_info: None, // Will be populated from initialize response
-                server_id: self.server_id,
+                client_info: handshake.client,
+                server_info: handshake.server,
+                protocol_version,
+                server_id,
                 tags,
+                latency: None,
             },
-        })
+        };
+        session.metadata.latency = Some(SessionLatencyStats::from_session(&session));
+
+        Ok(session)
     }
 
     /// Get current session statistics
@@ -393,6 +668,83 @@ mod tests {
         assert_eq!(MessageDirection::ToClient.to_string(), "to_client");
     }
 
+    fn message_at(relative_time_ms: u64, direction: MessageDirection) -> RecordedMessage {
+        RecordedMessage {
+            id: format!("msg-{relative_time_ms}"),
+            timestamp_micros: relative_time_ms * 1000,
+            relative_time_ms,
+            direction,
+            content: serde_json::json!({"jsonrpc": "2.0", "method": "ping"}),
+            metadata: MessageMetadata {
+                method: Some("ping".to_string()),
+                jsonrpc_id: None,
+                injected: false,
+                modified: false,
+                original: None,
+                size_bytes: 0,
+                schema_errors: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_replay_schedule() {
+        let session = RecordedSession {
+            id: "s1".to_string(),
+            name: "test".to_string(),
+            started_at: 0,
+            ended_at: None,
+            messages: vec![
+                message_at(100, MessageDirection::ToServer),
+                message_at(150, MessageDirection::ToClient),
+                message_at(300, MessageDirection::ToServer),
+                message_at(5300, MessageDirection::ToServer),
+            ],
+            metadata: SessionMetadata {
+                transport: "stdio".to_string(),
+                message_count: 4,
+                duration_ms: None,
+                client_info: None,
+                server_info: None,
+                protocol_version: None,
+                server_id: None,
+                tags: vec![],
+                latency: None,
+            },
+        };
+
+        let offsets = |timing: ReplayTiming| {
+            session
+                .replay_schedule(&timing)
+                .into_iter()
+                .map(|(offset, _)| offset.as_millis() as u64)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(offsets(ReplayTiming::default()), vec![100, 300, 5300]);
+        assert_eq!(
+            offsets(ReplayTiming {
+                speed: 2.0,
+                max_gap: None
+            }),
+            vec![50, 150, 2650]
+        );
+        assert_eq!(
+            offsets(ReplayTiming {
+                speed: 1.0,
+                max_gap: Some(Duration::from_secs(1))
+            }),
+            vec![100, 300, 1300]
+        );
+        assert_eq!(
+            offsets(ReplayTiming {
+                speed: 0.0,
+                max_gap: None
+            }),
+            vec![0, 0, 0]
+        );
+    }
+
     #[test]
     fn test_generate_uuid() {
         let uuid1 = generate_uuid();
@@ -463,6 +815,45 @@ mod tests {
         assert_eq!(stats.message_count, 1);
         assert_eq!(stats.to_server_count, 1);
         assert_eq!(stats.to_client_count, 0);
+
+        // A snapshot leaves the recording running
+        let snapshot = recorder.snapshot().await.unwrap();
+        assert_eq!(snapshot.messages.len(), 1);
+        assert!(snapshot.ended_at.is_none());
+        let session = recorder.finalize().await.unwrap();
+        assert_eq!(session.messages.len(), 1);
+        assert!(session.ended_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_record_modified_message() {
+        let recorder = SessionRecorder::new(
+            "session-1".to_string(),
+            "Test".to_string(),
+            "stdio".to_string(),
+        );
+
+        let content = serde_json::json!({"jsonrpc": "2.0", "method": "tools/call", "id": 1});
+        let edited = serde_json::json!({"jsonrpc": "2.0", "method": "tools/list", "id": 1});
+        recorder
+            .record_message(content.clone(), MessageDirection::ToServer)
+            .await
+            .unwrap();
+        recorder
+            .record_modified_message(edited, content.clone(), MessageDirection::ToServer)
+            .await
+            .unwrap();
+
+        let session = recorder.finalize().await.unwrap();
+        assert!(!session.messages[0].metadata.modified);
+        assert!(session.messages[0].metadata.original.is_none());
+        assert!(session.messages[1].metadata.modified);
+        assert!(!session.messages[1].metadata.injected);
+        assert_eq!(
+            session.messages[1].metadata.method.as_deref(),
+            Some("tools/list")
+        );
+        assert_eq!(session.messages[1].metadata.original, Some(content));
     }
 
     #[tokio::test]
@@ -515,5 +906,185 @@ mod tests {
         assert_eq!(session.metadata.message_count, 2);
         assert_eq!(session.metadata.transport, "stdio");
         assert!(session.metadata.tags.contains(&"test-tag".to_string()));
+        assert!(session.metadata.client_info.is_none());
+        assert!(session.metadata.server_info.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_records_handshake() {
+        let recorder = SessionRecorder::with_server(
+            "session-1".to_string(),
+            "Test Session".to_string(),
+            "stdio".to_string(),
+            ServerIdentifier {
+                name: "files".to_string(),
+                version: None,
+                command: "files-server".to_string(),
+                args: vec![],
+                connection_type: "stdio".to_string(),
+            },
+        );
+        let capabilities = serde_json::json!({"tools": {"listChanged": true}, "logging": {}});
+        for (message, direction) in [
+            (
+                serde_json::json!({"jsonrpc": "2.0", "id": 7, "method": "initialize", "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {"roots": {}},
+                    "clientInfo": {"name": "claude-desktop", "version": "0.9.2"}
+                }}),
+                MessageDirection::ToServer,
+            ),
+            // A response to some other request doesn't count
+            (
+                serde_json::json!({"jsonrpc": "2.0", "id": 6, "result": {"serverInfo": {"name": "x", "version": "0"}}}),
+                MessageDirection::ToClient,
+            ),
+            (
+                serde_json::json!({"jsonrpc": "2.0", "id": 7, "result": {
+                    "protocolVersion": "2025-03-26",
+                    "capabilities": capabilities,
+                    "serverInfo": {"name": "files-server", "version": "1.4.0"},
+                    "instructions": "Paths are relative to the workspace"
+                }}),
+                MessageDirection::ToClient,
+            ),
+        ] {
+            recorder.record_message(message, direction).await.unwrap();
+        }
+        assert_eq!(recorder.server_info().await.unwrap().version, "1.4.0");
+
+        let session = recorder.finalize().await.unwrap();
+        let metadata = &session.metadata;
+        assert_eq!(
+            metadata.client_info,
+            Some(ClientInfo {
+                name: "claude-desktop".to_string(),
+                version: "0.9.2".to_string(),
+                protocol_version: Some("2025-06-18".to_string()),
+                capabilities: Some(serde_json::json!({"roots": {}})),
+            })
+        );
+        let server = metadata.server_info.as_ref().unwrap();
+        assert_eq!(server.name, "files-server");
+        assert_eq!(server.capabilities, Some(capabilities));
+        assert_eq!(
+            server.instructions.as_deref(),
+            Some("Paths are relative to the workspace")
+        );
+        // The server's answer is what was negotiated
+        assert_eq!(metadata.protocol_version.as_deref(), Some("2025-03-26"));
+        assert_eq!(
+            metadata.server_id.as_ref().unwrap().version.as_deref(),
+            Some("1.4.0")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_records_handshake_response_only() {
+        // Proxies attached mid-handshake only see the response
+        let recorder = SessionRecorder::new(
+            "session-1".to_string(),
+            "Late".to_string(),
+            "http".to_string(),
+        );
+        recorder
+            .record_message(
+                serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": {}}),
+                MessageDirection::ToClient,
+            )
+            .await
+            .unwrap();
+        recorder
+            .record_message(
+                serde_json::json!({"jsonrpc": "2.0", "id": 2, "result": {
+                    "protocolVersion": "2024-11-05",
+                    "serverInfo": {"name": "api", "version": "2.0"}
+                }}),
+                MessageDirection::ToClient,
+            )
+            .await
+            .unwrap();
+
+        let session = recorder.finalize().await.unwrap();
+        assert!(session.metadata.client_info.is_none());
+        assert_eq!(session.metadata.server_info.unwrap().version, "2.0");
+        assert_eq!(
+            session.metadata.protocol_version.as_deref(),
+            Some("2024-11-05")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flags_schema_mismatches() {
+        let recorder = SessionRecorder::new(
+            "session-1".to_string(),
+            "Schemas".to_string(),
+            "stdio".to_string(),
+        );
+        let messages = [
+            (
+                serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": [{
+                    "name": "echo",
+                    "inputSchema": {"type": "object", "required": ["text"]}
+                }]}}),
+                MessageDirection::ToClient,
+            ),
+            (
+                serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                    "params": {"name": "echo", "arguments": {"text": "hi"}}}),
+                MessageDirection::ToServer,
+            ),
+            (
+                serde_json::json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                    "params": {"name": "echo", "arguments": {}}}),
+                MessageDirection::ToServer,
+            ),
+        ];
+        for (content, direction) in messages {
+            recorder.record_message(content, direction).await.unwrap();
+        }
+
+        let session = recorder.finalize().await.unwrap();
+        assert!(session.messages[1].metadata.schema_errors.is_empty());
+        let flagged = &session.messages[2].metadata.schema_errors;
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].tool, "echo");
+
+        // Only flagged messages carry the field
+        let json = serde_json::to_value(&session.messages).unwrap();
+        assert!(json[1]["metadata"].get("schema_errors").is_none());
+        assert_eq!(
+            json[2]["metadata"]["schema_errors"][0]["target"],
+            "arguments"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shares_tool_schemas() {
+        // A recording started after the listing still checks calls
+        let checker = crate::protocol::conformance::ConformanceChecker::new();
+        let list = serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": [{
+            "name": "echo",
+            "inputSchema": {"type": "object", "required": ["text"]}
+        }]}});
+        checker.check(&McpMessage::parse(&list), crate::protocol::Direction::Out);
+
+        let recorder = SessionRecorder::new(
+            "session-1".to_string(),
+            "Shared".to_string(),
+            "stdio".to_string(),
+        )
+        .with_tool_schemas(checker.tool_schemas());
+        let call = serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+            "params": {"name": "echo", "arguments": {}}});
+        let violations = checker.check(&McpMessage::parse(&call), crate::protocol::Direction::In);
+        assert_eq!(violations.len(), 2);
+        recorder
+            .record_message(call, MessageDirection::ToServer)
+            .await
+            .unwrap();
+
+        let session = recorder.finalize().await.unwrap();
+        assert_eq!(session.messages[0].metadata.schema_errors.len(), 1);
     }
 }
@@ -1,9 +1,11 @@
 //! Storage layer for session recordings using sled
 //!
 //! This module provides sled-based persistence for recorded sessions,
-//! allowing sessions to be saved, loaded, and queried efficiently.
+//! allowing sessions to be saved, loaded, and queried efficiently. Server
+//! analyses are kept alongside them, one per server name and version.
 
 use crate::error::{AppError, Result};
+use crate::server_analyzer::{AnalysisInfo, ServerAnalysis};
 use crate::session_recorder::RecordedSession;
 use serde::{Deserialize, Serialize};
 use sled::Db;
@@ -24,6 +26,16 @@ impl SessionStorage {
         Ok(Self { db: Arc::new(db) })
     }
 
+    /// Default database location for the CLI: `<data dir>/reticle/recordings.db`
+    ///
+    /// The desktop app keeps its own database next to this one.
+    pub fn default_path() -> PathBuf {
+        let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("reticle");
+        path.push("recordings.db");
+        path
+    }
+
     /// Save a recorded session
     pub async fn save_session(&self, session: &RecordedSession) -> Result<()> {
         let sessions_tree = self
@@ -56,6 +68,24 @@ impl SessionStorage {
             duration_ms: session.metadata.duration_ms,
             transport: session.metadata.transport.clone(),
             server_name: session.metadata.server_id.as_ref().map(|s| s.name.clone()),
+            server_version: session
+                .metadata
+                .server_info
+                .as_ref()
+                .map(|s| s.version.clone())
+                .or_else(|| {
+                    session
+                        .metadata
+                        .server_id
+                        .as_ref()
+                        .and_then(|s| s.version.clone())
+                }),
+            client_name: session
+                .metadata
+                .client_info
+                .as_ref()
+                .map(|c| c.name.clone()),
+            protocol_version: session.metadata.protocol_version.clone(),
             tags: session.metadata.tags.clone(),
         };
 
@@ -215,6 +245,19 @@ impl SessionStorage {
                     }
                 }
 
+                // Filter by what the initialize handshake recorded
+                let handshake = [
+                    (&filter.server_version, &session.server_version),
+                    (&filter.client_name, &session.client_name),
+                    (&filter.protocol_version, &session.protocol_version),
+                ];
+                if handshake
+                    .iter()
+                    .any(|(wanted, actual)| wanted.is_some() && wanted != actual)
+                {
+                    return false;
+                }
+
                 // Filter by tags (session must have ALL specified tags)
                 for tag in &filter.tags {
                     if !session.tags.contains(tag) {
@@ -285,6 +328,109 @@ impl SessionStorage {
 
         Ok(server_names)
     }
+
+    /// Save a server analysis, replacing any earlier one of the same server
+    /// name and version
+    pub async fn save_analysis(&self, analysis: &ServerAnalysis) -> Result<()> {
+        let analyses_tree = self.analyses_tree()?;
+
+        let stored = StoredAnalysis {
+            sequence: self
+                .db
+                .generate_id()
+                .map_err(|e| AppError::StorageError(format!("Failed to generate id: {e}")))?,
+            analysis: analysis.clone(),
+        };
+        let analysis_bytes = serde_json::to_vec(&stored).map_err(|e| {
+            AppError::SerializationError(format!("Failed to serialize analysis: {e}"))
+        })?;
+        analyses_tree
+            .insert(
+                analysis_key(&analysis.server_name, &analysis.server_version),
+                analysis_bytes,
+            )
+            .map_err(|e| AppError::StorageError(format!("Failed to insert analysis: {e}")))?;
+
+        self.db
+            .flush_async()
+            .await
+            .map_err(|e| AppError::StorageError(format!("Failed to flush database: {e}")))?;
+
+        tracing::info!(
+            "Saved analysis of {} {}",
+            analysis.server_name,
+            analysis.server_version
+        );
+        Ok(())
+    }
+
+    /// Load the saved analysis of a server version
+    pub async fn load_analysis(
+        &self,
+        server_name: &str,
+        server_version: &str,
+    ) -> Result<ServerAnalysis> {
+        let analysis_bytes = self
+            .analyses_tree()?
+            .get(analysis_key(server_name, server_version))
+            .map_err(|e| AppError::StorageError(format!("Failed to get analysis: {e}")))?
+            .ok_or_else(|| {
+                AppError::StorageError(format!(
+                    "Analysis not found: {server_name}@{server_version}"
+                ))
+            })?;
+
+        let stored: StoredAnalysis = serde_json::from_slice(&analysis_bytes).map_err(|e| {
+            AppError::SerializationError(format!("Failed to deserialize analysis: {e}"))
+        })?;
+        Ok(stored.analysis)
+    }
+
+    /// List saved analyses (newest first), optionally of one server only
+    pub async fn list_analyses(&self, server_name: Option<&str>) -> Result<Vec<AnalysisInfo>> {
+        let analyses_tree = self.analyses_tree()?;
+        let items = match server_name {
+            Some(name) => analyses_tree.scan_prefix(analysis_key(name, "")),
+            None => analyses_tree.scan_prefix(""),
+        };
+
+        let mut analyses = Vec::new();
+        for item in items {
+            let (_key, value) = item
+                .map_err(|e| AppError::StorageError(format!("Failed to iterate analyses: {e}")))?;
+            let stored: StoredAnalysis = serde_json::from_slice(&value).map_err(|e| {
+                AppError::SerializationError(format!("Failed to deserialize analysis: {e}"))
+            })?;
+            analyses.push(stored);
+        }
+
+        // Analyses made within the same second keep the order they were saved in
+        analyses.sort_by_key(|stored| {
+            std::cmp::Reverse((stored.analysis.analyzed_at, stored.sequence))
+        });
+        Ok(analyses
+            .into_iter()
+            .map(|stored| stored.analysis.info())
+            .collect())
+    }
+
+    fn analyses_tree(&self) -> Result<sled::Tree> {
+        self.db
+            .open_tree("analyses")
+            .map_err(|e| AppError::StorageError(format!("Failed to open analyses tree: {e}")))
+    }
+}
+
+/// A saved analysis with its position in save order
+#[derive(Serialize, Deserialize)]
+struct StoredAnalysis {
+    sequence: u64,
+    analysis: ServerAnalysis,
+}
+
+/// Key for a server version in the analyses tree: `name\0version`
+fn analysis_key(server_name: &str, server_version: &str) -> Vec<u8> {
+    format!("{server_name}\0{server_version}").into_bytes()
 }
 
 /// Session information for listing
@@ -300,6 +446,15 @@ pub struct SessionInfo {
     /// Server name for multi-server filtering
     #[serde(default)]
     pub server_name: Option<String>,
+    /// Server version from the `initialize` response
+    #[serde(default)]
+    pub server_version: Option<String>,
+    /// Client name from the `initialize` request
+    #[serde(default)]
+    pub client_name: Option<String>,
+    /// Negotiated protocol version
+    #[serde(default)]
+    pub protocol_version: Option<String>,
     /// Custom tags for filtering
     #[serde(default)]
     pub tags: Vec<String>,
@@ -324,13 +479,22 @@ pub struct SessionFilter {
     /// Filter by transport type
     #[serde(default)]
     pub transport: Option<String>,
+    /// Filter by server version
+    #[serde(default)]
+    pub server_version: Option<String>,
+    /// Filter by client name
+    #[serde(default)]
+    pub client_name: Option<String>,
+    /// Filter by negotiated protocol version
+    #[serde(default)]
+    pub protocol_version: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::session_recorder::{
-        MessageDirection, MessageMetadata, RecordedMessage, SessionMetadata,
+        ClientInfo, MessageDirection, MessageMetadata, RecordedMessage, ServerInfo, SessionMetadata,
     };
     use tempfile::TempDir;
 
@@ -351,7 +515,9 @@ mod tests {
                     jsonrpc_id: Some(serde_json::json!(1)),
                     injected: false,
                     modified: false,
+                    original: None,
                     size_bytes: 20,
+                    schema_errors: Vec::new(),
                 },
             }],
             metadata: SessionMetadata {
@@ -360,8 +526,10 @@ mod tests {
                 duration_ms: Some(1000),
                 client_info: None,
                 server_info: None,
+                protocol_version: None,
                 server_id: None,
                 tags,
+                latency: None,
             },
         }
     }
@@ -430,6 +598,61 @@ mod tests {
         assert_eq!(filtered[0].id, "s1");
     }
 
+    #[tokio::test]
+    async fn test_storage_filter_by_handshake() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = SessionStorage::new(temp_dir.path().to_path_buf()).unwrap();
+
+        for (id, version) in [("s1", "1.0.0"), ("s2", "1.1.0"), ("s3", "1.1.0")] {
+            let mut session = create_test_session(id, id, vec![]);
+            session.metadata.server_info = Some(ServerInfo {
+                name: "files".to_string(),
+                version: version.to_string(),
+                protocol_version: Some("2025-03-26".to_string()),
+                capabilities: None,
+                instructions: None,
+            });
+            session.metadata.client_info = Some(ClientInfo {
+                name: if id == "s3" {
+                    "cursor"
+                } else {
+                    "claude-desktop"
+                }
+                .to_string(),
+                version: "1.0".to_string(),
+                protocol_version: None,
+                capabilities: None,
+            });
+            session.metadata.protocol_version = Some("2025-03-26".to_string());
+            storage.save_session(&session).await.unwrap();
+        }
+
+        let filter = SessionFilter {
+            server_version: Some("1.1.0".to_string()),
+            ..Default::default()
+        };
+        let mut ids: Vec<String> = storage
+            .list_sessions_filtered(&filter)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["s2", "s3"]);
+
+        let filter = SessionFilter {
+            server_version: Some("1.1.0".to_string()),
+            client_name: Some("cursor".to_string()),
+            protocol_version: Some("2025-03-26".to_string()),
+            ..Default::default()
+        };
+        let filtered = storage.list_sessions_filtered(&filter).await.unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, "s3");
+        assert_eq!(filtered[0].client_name.as_deref(), Some("cursor"));
+    }
+
     #[tokio::test]
     async fn test_storage_add_remove_tags() {
         let temp_dir = TempDir::new().unwrap();
@@ -471,4 +694,51 @@ mod tests {
         let tags = storage.get_all_tags().await.unwrap();
         assert_eq!(tags, vec!["a", "b", "c"]);
     }
+
+    fn create_test_analysis(name: &str, version: &str, analyzed_at: u64) -> ServerAnalysis {
+        serde_json::from_value(serde_json::json!({
+            "server_name": name,
+            "server_version": version,
+            "protocol_version": "2024-11-05",
+            "total_context_tokens": analyzed_at,
+            "tools": {"count": 0, "total_tokens": 0, "tools": []},
+            "prompts": {"count": 0, "total_tokens": 0, "prompts": []},
+            "resources": {"count": 0, "total_tokens": 0, "resources": []},
+            "token_breakdown": {},
+            "analyzed_at": analyzed_at
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_storage_analyses() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = SessionStorage::new(temp_dir.path().to_path_buf()).unwrap();
+
+        for analysis in [
+            create_test_analysis("files", "1.0.0", 100),
+            create_test_analysis("files", "1.1.0", 200),
+            create_test_analysis("files-extra", "1.0.0", 300),
+            // Re-analyzing a version replaces it
+            create_test_analysis("files", "1.0.0", 150),
+            create_test_analysis("files", "1.2.0", 200),
+        ] {
+            storage.save_analysis(&analysis).await.unwrap();
+        }
+
+        let history = storage.list_analyses(Some("files")).await.unwrap();
+        let versions: Vec<(&str, u64)> = history
+            .iter()
+            .map(|info| (info.server_version.as_str(), info.analyzed_at))
+            .collect();
+        assert_eq!(
+            versions,
+            vec![("1.2.0", 200), ("1.1.0", 200), ("1.0.0", 150)]
+        );
+        assert_eq!(storage.list_analyses(None).await.unwrap().len(), 4);
+
+        let loaded = storage.load_analysis("files", "1.0.0").await.unwrap();
+        assert_eq!(loaded.total_context_tokens, 150);
+        assert!(storage.load_analysis("files", "2.0.0").await.is_err());
+    }
 }
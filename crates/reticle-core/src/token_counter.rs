@@ -4,10 +4,12 @@
 //! understand LLM context consumption. Extracts and counts only the payload
 //! content that actually goes to the LLM, not the JSON-RPC protocol overhead.
 //!
-//! The estimation is based on the cl100k_base tokenizer (used by GPT-4/Claude):
-//! - Average of ~4 characters per token for English text
-//! - JSON structure adds overhead (brackets, quotes, colons)
-//! - Numbers and special characters often become individual tokens
+//! Counting goes through a [`Tokenizer`]. The heuristic estimate is the
+//! default; exact BPE tokenizers can be chosen per session with
+//! [`TokenCounter::set_session_tokenizer`]. See [`crate::tokenizer`].
+//!
+//! Sessions can also have a [`TokenBudget`]; messages that exceed it are
+//! reported through the event sink passed to [`TokenCounter::record_message`].
 //!
 //! LLM-relevant content extraction:
 //! - tools/list response: Tool schemas (name, description, inputSchema)
@@ -15,12 +17,28 @@
 //! - resources/read response: Resource text content
 //! - prompts/get response: Prompt messages
 //! - sampling/createMessage request: Messages and system prompt
+//! - elicitation/create request: The question and the schema of the answer
+//! - completion/complete: The partial argument and the suggested values
+//!
+//! Messages are counted from their [`McpMessage`] model. Responses carry no
+//! method. [`TokenCounter::record_message`] remembers the method of each
+//! pending request, so a response's tokens are attributed to the method it
+//! answers; a response parsed with [`McpMessage::parse_response`] is counted
+//! as the result of its request, and otherwise the kind of result is
+//! inferred from its shape.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::budget::{BudgetKind, BudgetViolation, TokenBudget, BUDGET_EXCEEDED_EVENT};
+use crate::events::EventSink;
+use crate::protocol::mcp::{ContentBlock, ElicitResult};
+use crate::protocol::message::{infer_result_method, McpResult, Request};
+use crate::protocol::McpMessage;
+use crate::tokenizer::{HeuristicTokenizer, Tokenizer, TokenizerSpec};
+
 /// Token statistics for a single message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageTokenStats {
@@ -34,6 +52,9 @@ pub struct MessageTokenStats {
     pub char_count: u64,
     /// Timestamp in microseconds
     pub timestamp: u64,
+    /// Budget limits this message went over
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub budget_exceeded: Vec<BudgetViolation>,
 }
 
 /// Token statistics for a session
@@ -41,6 +62,9 @@ pub struct MessageTokenStats {
 pub struct SessionTokenStats {
     /// Session ID
     pub session_id: String,
+    /// Tokenizer the counts were made with
+    #[serde(default)]
+    pub tokenizer: TokenizerSpec,
     /// Total tokens sent to server (requests)
     pub tokens_to_server: u64,
     /// Total tokens from server (responses)
@@ -85,398 +109,405 @@ pub struct GlobalTokenStats {
     pub sessions: HashMap<String, SessionTokenStats>,
 }
 
+/// Methods of a session's pending requests, by whether they were sent to
+/// the server and by id
+type PendingMethods = HashMap<(bool, String), String>;
+
 /// Token counter state
 pub struct TokenCounter {
     /// Global statistics
     stats: Arc<RwLock<GlobalTokenStats>>,
+    /// Tokenizer for sessions without their own
+    default_tokenizer: Arc<dyn Tokenizer>,
+    /// Per-session tokenizer choices
+    session_tokenizers: RwLock<HashMap<String, Arc<dyn Tokenizer>>>,
+    /// Per-session token budgets
+    session_budgets: RwLock<HashMap<String, TokenBudget>>,
+    /// Method of each pending request per session
+    pending_methods: RwLock<HashMap<String, PendingMethods>>,
 }
 
 impl TokenCounter {
-    /// Create a new token counter
+    /// Create a new token counter using the heuristic tokenizer
     pub fn new() -> Self {
+        Self::with_tokenizer(Arc::new(HeuristicTokenizer))
+    }
+
+    /// Create a token counter with a different default tokenizer
+    pub fn with_tokenizer(default_tokenizer: Arc<dyn Tokenizer>) -> Self {
         Self {
             stats: Arc::new(RwLock::new(GlobalTokenStats::default())),
+            default_tokenizer,
+            session_tokenizers: RwLock::new(HashMap::new()),
+            session_budgets: RwLock::new(HashMap::new()),
+            pending_methods: RwLock::new(HashMap::new()),
         }
     }
 
-    /// Estimate token count for a string
+    /// Count a session's tokens with `tokenizer` from now on
     ///
-    /// Uses a heuristic based on cl100k_base tokenizer patterns:
-    /// - ~4 characters per token for regular text
-    /// - JSON punctuation often becomes separate tokens
-    /// - Numbers are typically 1-2 tokens per number
-    pub fn estimate_tokens(text: &str) -> u64 {
-        if text.is_empty() {
-            return 0;
-        }
+    /// Counts already recorded for the session are kept as they are.
+    pub async fn set_session_tokenizer(&self, session_id: &str, tokenizer: Arc<dyn Tokenizer>) {
+        let spec = tokenizer.spec();
+        self.session_tokenizers
+            .write()
+            .await
+            .insert(session_id.to_string(), tokenizer);
 
-        let mut tokens = 0u64;
-        let chars: Vec<char> = text.chars().collect();
-        let mut i = 0;
-
-        while i < chars.len() {
-            let c = chars[i];
-
-            if c.is_whitespace() {
-                // Whitespace is often merged with adjacent tokens
-                i += 1;
-                continue;
-            }
-
-            if c == '"' || c == '{' || c == '}' || c == '[' || c == ']' || c == ':' || c == ',' {
-                // JSON punctuation - often separate tokens
-                tokens += 1;
-                i += 1;
-                continue;
-            }
+        let mut global = self.stats.write().await;
+        if let Some(session) = global.sessions.get_mut(session_id) {
+            session.tokenizer = spec;
+        }
+    }
 
-            if c.is_ascii_digit() || c == '-' || c == '.' {
-                // Numbers - count as roughly 1 token per 3 digits
-                let mut num_len = 0;
-                while i + num_len < chars.len() {
-                    let nc = chars[i + num_len];
-                    if nc.is_ascii_digit() || nc == '.' || nc == '-' || nc == 'e' || nc == 'E' {
-                        num_len += 1;
-                    } else {
-                        break;
-                    }
-                }
-                if num_len > 0 {
-                    tokens += ((num_len as u64) + 2) / 3; // ~3 chars per token for numbers
-                    i += num_len;
-                    continue;
-                }
-            }
+    /// The tokenizer a session is counted with
+    pub async fn session_tokenizer(&self, session_id: &str) -> Arc<dyn Tokenizer> {
+        self.session_tokenizers
+            .read()
+            .await
+            .get(session_id)
+            .cloned()
+            .unwrap_or_else(|| self.default_tokenizer.clone())
+    }
 
-            // Regular text - count word-like sequences
-            let mut word_len = 0;
-            while i + word_len < chars.len() {
-                let wc = chars[i + word_len];
-                if wc.is_alphanumeric() || wc == '_' || wc == '-' {
-                    word_len += 1;
-                } else {
-                    break;
-                }
-            }
+    /// Check a session's messages against `budget` from now on
+    pub async fn set_session_budget(&self, session_id: &str, budget: TokenBudget) {
+        self.session_budgets
+            .write()
+            .await
+            .insert(session_id.to_string(), budget);
+    }
 
-            if word_len > 0 {
-                // ~4 characters per token for regular words
-                // But common words are often 1 token
-                tokens += if word_len <= 4 {
-                    1
-                } else {
-                    ((word_len as u64) + 3) / 4
-                };
-                i += word_len;
-            } else {
-                // Single special character
-                tokens += 1;
-                i += 1;
-            }
-        }
+    /// The budget a session is checked against (no limits if unset)
+    pub async fn session_budget(&self, session_id: &str) -> TokenBudget {
+        self.session_budgets
+            .read()
+            .await
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default()
+    }
 
-        // Minimum 1 token for non-empty strings
-        tokens.max(1)
+    /// Estimate token count for a string with the heuristic tokenizer
+    pub fn estimate_tokens(text: &str) -> u64 {
+        HeuristicTokenizer.count_tokens(text)
     }
 
     /// Count tokens in a JSON value
     #[allow(dead_code)]
     pub fn count_json_tokens(value: &serde_json::Value) -> u64 {
+        Self::count_json_tokens_with(&HeuristicTokenizer, value)
+    }
+
+    /// Count tokens in a JSON value with a specific tokenizer
+    pub fn count_json_tokens_with(tokenizer: &dyn Tokenizer, value: &serde_json::Value) -> u64 {
         let json_str = serde_json::to_string(value).unwrap_or_default();
-        Self::estimate_tokens(&json_str)
+        tokenizer.count_tokens(&json_str)
     }
 
     /// Extract and count tokens for LLM-relevant content from an MCP message.
     ///
     /// This extracts only the payload that actually goes to the LLM context,
     /// not the JSON-RPC protocol overhead.
-    pub fn count_mcp_context_tokens(content: &serde_json::Value) -> u64 {
-        // Try to extract method from request or find it's a response
-        let method = content.get("method").and_then(|m| m.as_str());
-        let is_response = content.get("result").is_some() || content.get("error").is_some();
-
-        // For requests, check specific methods
-        if let Some(method) = method {
-            return Self::count_request_tokens(method, content);
-        }
+    pub fn count_mcp_context_tokens(message: &McpMessage) -> u64 {
+        Self::count_mcp_context_tokens_with(&HeuristicTokenizer, message)
+    }
 
-        // For responses, we need to infer what was requested
-        // We look at the structure of the result to determine the type
-        if is_response {
-            return Self::count_response_tokens(content);
+    /// Count LLM-relevant tokens in an MCP message with a specific tokenizer
+    ///
+    /// A response parsed with [`McpMessage::parse_response`] is counted as
+    /// the result of its request; otherwise the kind of result is inferred
+    /// from its shape.
+    pub fn count_mcp_context_tokens_with(tokenizer: &dyn Tokenizer, message: &McpMessage) -> u64 {
+        match message {
+            McpMessage::Request { request, .. } => Self::count_request_tokens(tokenizer, request),
+            McpMessage::Notification(notification) => {
+                Self::count_params_tokens(tokenizer, notification.params())
+            }
+            McpMessage::Response { result, .. } => Self::count_result_tokens(tokenizer, result),
+            // Handle errors - the error message might be shown
+            McpMessage::Error { error, .. } => tokenizer.count_tokens(&error.message).max(1),
+            McpMessage::Batch(items) => items
+                .iter()
+                .map(|item| Self::count_mcp_context_tokens_with(tokenizer, item))
+                .sum(),
+            // Fallback: count the whole message (shouldn't happen often)
+            McpMessage::Raw(value) => Self::count_json_tokens_with(tokenizer, value),
         }
-
-        // Fallback: count the whole message (shouldn't happen often)
-        Self::count_json_tokens(content)
     }
 
     /// Count tokens for request payloads
-    fn count_request_tokens(method: &str, content: &serde_json::Value) -> u64 {
-        match method {
+    fn count_request_tokens(tokenizer: &dyn Tokenizer, request: &Request) -> u64 {
+        match request {
             // sampling/createMessage - messages and systemPrompt go to LLM
-            "sampling/createMessage" => {
-                let mut tokens = 0u64;
-
-                if let Some(params) = content.get("params") {
-                    // Count system prompt
-                    if let Some(system) = params.get("systemPrompt").and_then(|s| s.as_str()) {
-                        tokens += Self::estimate_tokens(system);
-                    }
-
-                    // Count messages
-                    if let Some(messages) = params.get("messages").and_then(|m| m.as_array()) {
-                        for msg in messages {
-                            tokens += Self::count_message_content(msg);
-                        }
-                    }
-                }
-
-                tokens.max(1)
+            Request::CreateMessage(request) => {
+                let system = request
+                    .system_prompt
+                    .as_deref()
+                    .map_or(0, |system| tokenizer.count_tokens(system));
+                let messages: u64 = request
+                    .messages
+                    .iter()
+                    .map(|msg| Self::count_message_content(tokenizer, &msg.content))
+                    .sum();
+                (system + messages).max(1)
             }
 
             // tools/call - the arguments are shown to LLM in tool use context
-            "tools/call" => {
-                let mut tokens = 0u64;
-
-                if let Some(params) = content.get("params") {
-                    // Tool name
-                    if let Some(name) = params.get("name").and_then(|n| n.as_str()) {
-                        tokens += Self::estimate_tokens(name);
-                    }
-
-                    // Arguments (serialized)
-                    if let Some(args) = params.get("arguments") {
-                        tokens += Self::count_json_tokens(args);
-                    }
-                }
-
-                tokens.max(1)
+            Request::CallTool(call) => {
+                let arguments = call.arguments.clone().map(serde_json::Value::Object);
+                (tokenizer.count_tokens(&call.name)
+                    + arguments.map_or(0, |args| Self::count_json_tokens_with(tokenizer, &args)))
+                .max(1)
             }
 
             // prompts/get - arguments sent to prompt
-            "prompts/get" => {
-                if let Some(params) = content.get("params") {
-                    if let Some(args) = params.get("arguments") {
-                        return Self::count_json_tokens(args).max(1);
-                    }
+            Request::GetPrompt(request) => match &request.arguments {
+                Some(args) => {
+                    let args = serde_json::to_value(args).unwrap_or_default();
+                    Self::count_json_tokens_with(tokenizer, &args).max(1)
                 }
-                1
-            }
+                None => 1,
+            },
 
             // resources/read - URI is minimal
-            "resources/read" => {
-                if let Some(params) = content.get("params") {
-                    if let Some(uri) = params.get("uri").and_then(|u| u.as_str()) {
-                        return Self::estimate_tokens(uri).max(1);
-                    }
-                }
-                1
-            }
+            Request::ReadResource(request) => tokenizer.count_tokens(&request.uri).max(1),
+
+            // elicitation/create - the question shown to the user and the
+            // schema their answer has to follow
+            Request::Elicit(request) => (tokenizer.count_tokens(&request.message)
+                + Self::count_json_tokens_with(tokenizer, &request.requested_schema))
+            .max(1),
+
+            // completion/complete - only what has been typed so far
+            Request::Complete(request) => tokenizer.count_tokens(&request.argument.value).max(1),
 
             // Protocol messages - minimal context impact
-            "initialize" | "initialized" | "ping" | "cancelled" => 1,
+            Request::Initialize(_) | Request::Ping(_) | Request::SetLevel(_) => 1,
 
             // List operations - no content sent to LLM
-            "tools/list" | "resources/list" | "prompts/list" | "resources/templates/list" => 1,
+            Request::ListTools(_)
+            | Request::ListResources(_)
+            | Request::ListPrompts(_)
+            | Request::ListResourceTemplates(_)
+            | Request::ListRoots(_) => 1,
 
             // Other methods - count params if present
-            _ => {
-                if let Some(params) = content.get("params") {
-                    Self::count_json_tokens(params).max(1)
-                } else {
-                    1
-                }
-            }
+            request => Self::count_params_tokens(tokenizer, request.params()),
         }
     }
 
-    /// Count tokens for response payloads
-    fn count_response_tokens(content: &serde_json::Value) -> u64 {
-        // Handle errors - the error message might be shown
-        if let Some(error) = content.get("error") {
-            if let Some(msg) = error.get("message").and_then(|m| m.as_str()) {
-                return Self::estimate_tokens(msg).max(1);
-            }
-            return 1;
-        }
-
-        let result = match content.get("result") {
-            Some(r) => r,
-            None => return 1,
-        };
+    /// Count a request's params as JSON
+    fn count_params_tokens(tokenizer: &dyn Tokenizer, params: Option<serde_json::Value>) -> u64 {
+        params
+            .map(|params| Self::count_json_tokens_with(tokenizer, &params).max(1))
+            .unwrap_or(1)
+    }
 
-        // tools/list response - tool definitions go into system prompt
-        if let Some(tools) = result.get("tools").and_then(|t| t.as_array()) {
-            let mut tokens = 0u64;
-            for tool in tools {
-                // Name and description
-                if let Some(name) = tool.get("name").and_then(|n| n.as_str()) {
-                    tokens += Self::estimate_tokens(name);
-                }
-                if let Some(desc) = tool.get("description").and_then(|d| d.as_str()) {
-                    tokens += Self::estimate_tokens(desc);
-                }
-                // Input schema (important for tool definitions)
-                if let Some(schema) = tool.get("inputSchema") {
-                    tokens += Self::count_json_tokens(schema);
+    /// Count tokens for response payloads
+    fn count_result_tokens(tokenizer: &dyn Tokenizer, result: &McpResult) -> u64 {
+        match result {
+            // tools/list response - tool definitions go into system prompt
+            McpResult::ListTools(list) => {
+                let mut tokens = 0u64;
+                for tool in &list.tools {
+                    // Name and description
+                    tokens += tokenizer.count_tokens(&tool.name);
+                    if let Some(ref desc) = tool.description {
+                        tokens += tokenizer.count_tokens(desc);
+                    }
+                    // Input schema (important for tool definitions)
+                    if !tool.input_schema.is_null() {
+                        tokens += Self::count_json_tokens_with(tokenizer, &tool.input_schema);
+                    }
                 }
+                tokens.max(1)
             }
-            return tokens.max(1);
-        }
-
-        // tools/call response - content array
-        if let Some(content_arr) = result.get("content").and_then(|c| c.as_array()) {
-            let mut tokens = 0u64;
-            for item in content_arr {
-                tokens += Self::count_content_item(item);
-            }
-            return tokens.max(1);
-        }
 
-        // resources/read response - text content
-        if let Some(contents) = result.get("contents").and_then(|c| c.as_array()) {
-            let mut tokens = 0u64;
-            for content_item in contents {
-                if let Some(text) = content_item.get("text").and_then(|t| t.as_str()) {
-                    tokens += Self::estimate_tokens(text);
+            // tools/call response - content array and structured output
+            McpResult::CallTool(call) => {
+                let mut tokens = 0u64;
+                for item in &call.content {
+                    tokens += Self::count_content_item(tokenizer, item);
                 }
-                // Blob content is typically base64 - count as roughly 1 token per 4 chars
-                if let Some(blob) = content_item.get("blob").and_then(|b| b.as_str()) {
-                    tokens += (blob.len() as u64) / 4;
+
+                // Servers should repeat structured output as a text block for
+                // older clients; only count it when they did not, so it is
+                // not counted twice
+                let has_text = call
+                    .content
+                    .iter()
+                    .any(|block| matches!(block, ContentBlock::Text { .. }));
+                if let (Some(structured), false) = (&call.structured_content, has_text) {
+                    tokens += Self::count_json_tokens_with(tokenizer, structured);
                 }
+                tokens.max(1)
             }
-            return tokens.max(1);
-        }
 
-        // prompts/list response - prompt definitions
-        if let Some(prompts) = result.get("prompts").and_then(|p| p.as_array()) {
-            let mut tokens = 0u64;
-            for prompt in prompts {
-                if let Some(name) = prompt.get("name").and_then(|n| n.as_str()) {
-                    tokens += Self::estimate_tokens(name);
-                }
-                if let Some(desc) = prompt.get("description").and_then(|d| d.as_str()) {
-                    tokens += Self::estimate_tokens(desc);
+            // resources/read response - text content
+            McpResult::ReadResource(read) => {
+                let mut tokens = 0u64;
+                for content_item in &read.contents {
+                    if let Some(ref text) = content_item.text {
+                        tokens += tokenizer.count_tokens(text);
+                    }
+                    // Blob content is typically base64 - count as roughly 1 token per 4 chars
+                    if let Some(ref blob) = content_item.blob {
+                        tokens += (blob.len() as u64) / 4;
+                    }
                 }
+                tokens.max(1)
             }
-            return tokens.max(1);
-        }
 
-        // prompts/get response - messages
-        if let Some(messages) = result.get("messages").and_then(|m| m.as_array()) {
-            let mut tokens = 0u64;
-            for msg in messages {
-                tokens += Self::count_message_content(msg);
+            // prompts/list response - prompt definitions
+            McpResult::ListPrompts(list) => {
+                let mut tokens = 0u64;
+                for prompt in &list.prompts {
+                    tokens += tokenizer.count_tokens(&prompt.name);
+                    if let Some(ref desc) = prompt.description {
+                        tokens += tokenizer.count_tokens(desc);
+                    }
+                }
+                tokens.max(1)
             }
-            return tokens.max(1);
-        }
 
-        // resources/list response - resource metadata
-        if let Some(resources) = result.get("resources").and_then(|r| r.as_array()) {
-            let mut tokens = 0u64;
-            for resource in resources {
-                if let Some(name) = resource.get("name").and_then(|n| n.as_str()) {
-                    tokens += Self::estimate_tokens(name);
-                }
-                if let Some(desc) = resource.get("description").and_then(|d| d.as_str()) {
-                    tokens += Self::estimate_tokens(desc);
+            // prompts/get response - messages
+            McpResult::GetPrompt(prompt) => {
+                let mut tokens = 0u64;
+                for msg in &prompt.messages {
+                    tokens += Self::count_message_content(tokenizer, &msg.content);
                 }
+                tokens.max(1)
             }
-            return tokens.max(1);
-        }
-
-        // sampling/createMessage response - assistant message
-        if result.get("role").is_some() {
-            return Self::count_message_content(result);
-        }
 
-        // completion/complete response
-        if let Some(completion) = result.get("completion") {
-            if let Some(values) = completion.get("values").and_then(|v| v.as_array()) {
+            // resources/list response - resource metadata
+            McpResult::ListResources(list) => {
                 let mut tokens = 0u64;
-                for val in values {
-                    if let Some(s) = val.as_str() {
-                        tokens += Self::estimate_tokens(s);
+                for resource in &list.resources {
+                    tokens += tokenizer.count_tokens(&resource.name);
+                    if let Some(ref desc) = resource.description {
+                        tokens += tokenizer.count_tokens(desc);
                     }
                 }
-                return tokens.max(1);
+                tokens.max(1)
             }
-        }
 
-        // Default: minimal for other responses
-        1
-    }
-
-    /// Count tokens in a message content object
-    fn count_message_content(msg: &serde_json::Value) -> u64 {
-        let mut tokens = 0u64;
-
-        // Handle content field which can be text or structured
-        if let Some(content) = msg.get("content") {
-            // Direct text content
-            if let Some(text) = content.get("text").and_then(|t| t.as_str()) {
-                tokens += Self::estimate_tokens(text);
-            }
-            // Content type (adds a bit for the type indicator)
-            if content.get("type").is_some() {
-                tokens += 1;
-            }
-            // Image content - estimate based on typical token usage
-            if content.get("data").is_some() {
-                // Images typically use ~85 tokens for low-res, ~765 for high-res
-                // Use a middle estimate
-                tokens += 200;
+            // sampling/createMessage response - assistant message
+            McpResult::CreateMessage(message) => {
+                Self::count_message_content(tokenizer, &message.content)
             }
-        }
 
-        tokens
-    }
+            // completion/complete response - suggested values
+            McpResult::Complete(complete) => complete
+                .completion
+                .values
+                .iter()
+                .map(|value| tokenizer.count_tokens(value))
+                .sum::<u64>()
+                .max(1),
+
+            // elicitation/create response - the user's answer, if they gave one
+            McpResult::Elicit(ElicitResult {
+                content: Some(answer),
+                ..
+            }) => {
+                Self::count_json_tokens_with(tokenizer, &serde_json::Value::Object(answer.clone()))
+                    .max(1)
+            }
 
-    /// Count tokens in a content item (from tools/call response)
-    fn count_content_item(item: &serde_json::Value) -> u64 {
-        let mut tokens = 0u64;
+            // roots/list response - the directories the client exposes
+            McpResult::ListRoots(list) => list
+                .roots
+                .iter()
+                .map(|root| {
+                    tokenizer.count_tokens(&root.uri)
+                        + root
+                            .name
+                            .as_deref()
+                            .map_or(0, |name| tokenizer.count_tokens(name))
+                })
+                .sum::<u64>()
+                .max(1),
+
+            // A result shaped like a known one that does not fit its schema
+            // is counted whole rather than guessed at
+            McpResult::Other(result) if infer_result_method(result).is_some() => {
+                Self::count_json_tokens_with(tokenizer, result).max(1)
+            }
 
-        // Text content
-        if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-            tokens += Self::estimate_tokens(text);
+            // Default: minimal for other responses, including logging/setLevel
+            _ => 1,
         }
+    }
 
-        // Image content
-        if item.get("data").is_some() {
-            tokens += 200; // Estimate for image
+    /// Count tokens in the content of a prompt or sampling message
+    fn count_message_content(tokenizer: &dyn Tokenizer, content: &ContentBlock) -> u64 {
+        // The content type adds a bit for the type indicator
+        match content {
+            ContentBlock::Text { text, .. } => tokenizer.count_tokens(text) + 1,
+            // Images typically use ~85 tokens for low-res, ~765 for high-res
+            // Use a middle estimate
+            ContentBlock::Image { .. } | ContentBlock::Audio { .. } => 1 + 200,
+            _ => 1,
         }
+    }
 
-        // Resource content (embedded)
-        if let Some(resource) = item.get("resource") {
-            if let Some(text) = resource.get("text").and_then(|t| t.as_str()) {
-                tokens += Self::estimate_tokens(text);
+    /// Count tokens in a content item (from tools/call response)
+    fn count_content_item(tokenizer: &dyn Tokenizer, item: &ContentBlock) -> u64 {
+        let tokens = match item {
+            ContentBlock::Text { text, .. } => tokenizer.count_tokens(text),
+            // Estimate for an image
+            ContentBlock::Image { .. } | ContentBlock::Audio { .. } => 200,
+            // Resource content (embedded)
+            ContentBlock::Resource { resource, .. } => resource
+                .text
+                .as_deref()
+                .map_or(0, |text| tokenizer.count_tokens(text)),
+            // Resource link - only the reference, the client reads it later
+            ContentBlock::ResourceLink(link) => {
+                let mut tokens =
+                    tokenizer.count_tokens(&link.uri) + tokenizer.count_tokens(&link.name);
+                for text in [&link.title, &link.description].into_iter().flatten() {
+                    tokens += tokenizer.count_tokens(text);
+                }
+                tokens
             }
-        }
-
+            ContentBlock::Unknown => 0,
+        };
         tokens.max(1)
     }
 
     /// Record a message and update statistics
-    #[allow(dead_code)]
-    pub async fn record_message(
+    ///
+    /// Messages from the server are checked against the session's budget.
+    /// Each exceeded limit is emitted as a [`BUDGET_EXCEEDED_EVENT`] on
+    /// `sink` and listed in the returned stats, so the proxy can enforce it.
+    pub async fn record_message<S: EventSink>(
         &self,
+        sink: &S,
         session_id: &str,
         message_id: &str,
-        content: &serde_json::Value,
+        message: &McpMessage,
         is_request: bool,
     ) -> MessageTokenStats {
-        let json_str = serde_json::to_string(content).unwrap_or_default();
-        let token_count = Self::estimate_tokens(&json_str);
+        let tokenizer = self.session_tokenizer(session_id).await;
+        let budget = self.session_budget(session_id).await;
+        let json_str = serde_json::to_string(message).unwrap_or_default();
+        let token_count = tokenizer.count_tokens(&json_str);
         let char_count = json_str.len() as u64;
 
-        // Extract method name
-        let method = content
-            .get("method")
-            .and_then(|m| m.as_str())
-            .map(|s| s.to_string());
+        // Extract method name; a response is attributed to its request
+        let method = self.attribute_method(session_id, message, is_request).await;
+        let is_response = message.method().is_none();
+
+        let mut exceeded = if is_request {
+            Vec::new()
+        } else {
+            budget.check(tokenizer.as_ref(), message)
+        };
 
         // Create message stats
-        let stats = MessageTokenStats {
+        let mut stats = MessageTokenStats {
             message_id: message_id.to_string(),
             method: method.clone(),
             token_count,
@@ -485,6 +516,7 @@ impl TokenCounter {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_micros() as u64,
+            budget_exceeded: Vec::new(),
         };
 
         // Update global stats
@@ -497,10 +529,18 @@ impl TokenCounter {
             .entry(session_id.to_string())
             .or_insert_with(|| SessionTokenStats {
                 session_id: session_id.to_string(),
+                tokenizer: tokenizer.spec(),
                 ..Default::default()
             });
 
+        // The session limit is reported once, when it is crossed
+        let before = session.total_tokens;
         session.total_tokens += token_count;
+        if let Some(limit) = budget.session {
+            if before <= limit && session.total_tokens > limit {
+                exceeded.push((BudgetKind::Session, limit, session.total_tokens));
+            }
+        }
 
         if is_request {
             session.tokens_to_server += token_count;
@@ -516,56 +556,104 @@ impl TokenCounter {
                 .or_default();
 
             method_stats.total_tokens += token_count;
-            method_stats.call_count += 1;
-
-            if is_request {
-                method_stats.request_tokens += token_count;
-            } else {
+            if is_response {
                 method_stats.response_tokens += token_count;
+            } else {
+                method_stats.call_count += 1;
+                method_stats.request_tokens += token_count;
             }
         }
 
         // Check for special responses that define tools/prompts/resources
         if !is_request {
-            self.analyze_definition_response(session, content, token_count);
+            self.analyze_definition_response(session, message, token_count);
+        }
+        drop(global);
+
+        stats.budget_exceeded = exceeded
+            .into_iter()
+            .map(|(kind, limit, tokens)| BudgetViolation {
+                session_id: session_id.to_string(),
+                message_id: message_id.to_string(),
+                kind,
+                limit,
+                tokens,
+                action: budget.action(),
+                tokenizer: tokenizer.spec(),
+            })
+            .collect();
+        for violation in &stats.budget_exceeded {
+            tracing::warn!(
+                "Session {} over its {} budget: {} > {} tokens",
+                session_id,
+                violation.kind,
+                violation.tokens,
+                violation.limit
+            );
+            if let Err(e) = sink.emit_custom(BUDGET_EXCEEDED_EVENT, violation).await {
+                tracing::warn!("Failed to emit budget warning: {}", e);
+            }
         }
 
         stats
     }
 
+    /// The method a message counts toward
+    ///
+    /// Requests are remembered by direction and id until their response,
+    /// which counts toward the same method.
+    async fn attribute_method(
+        &self,
+        session_id: &str,
+        message: &McpMessage,
+        is_request: bool,
+    ) -> Option<String> {
+        let id = message.id();
+        if let Some(method) = message.method() {
+            if let Some(id) = id {
+                self.pending_methods
+                    .write()
+                    .await
+                    .entry(session_id.to_string())
+                    .or_default()
+                    .insert((is_request, id.to_string()), method.to_string());
+            }
+            return Some(method.to_string());
+        }
+
+        // A response answers a request sent the other way
+        let id = id?;
+        self.pending_methods
+            .write()
+            .await
+            .get_mut(session_id)?
+            .remove(&(!is_request, id.to_string()))
+    }
+
     /// Analyze response for tool/prompt/resource definitions
-    #[allow(dead_code)]
     fn analyze_definition_response(
         &self,
         session: &mut SessionTokenStats,
-        content: &serde_json::Value,
+        message: &McpMessage,
         token_count: u64,
     ) {
-        // Check if this is a response (has result field, no method)
-        if content.get("method").is_some() || content.get("result").is_none() {
+        let McpMessage::Response { result, .. } = message else {
             return;
-        }
-
-        let result = content.get("result");
-
-        // Check for tools/list response
-        if let Some(result) = result {
-            if let Some(tools) = result.get("tools").and_then(|t| t.as_array()) {
+        };
+        match result {
+            McpResult::ListTools(list) => {
                 session.tool_definitions_tokens = token_count;
-                session.tool_count = tools.len() as u32;
+                session.tool_count = list.tools.len() as u32;
             }
-
-            // Check for prompts/list response
-            if let Some(prompts) = result.get("prompts").and_then(|p| p.as_array()) {
+            McpResult::ListPrompts(list) => {
                 session.prompt_definitions_tokens = token_count;
-                session.prompt_count = prompts.len() as u32;
+                session.prompt_count = list.prompts.len() as u32;
             }
-
-            // Check for resources/list response
-            if let Some(resources) = result.get("resources").and_then(|r| r.as_array()) {
+            McpResult::ListResources(list) => {
                 session.resource_definitions_tokens = token_count;
-                session.resource_count = resources.len() as u32;
+                session.resource_count = list.resources.len() as u32;
             }
+            _ => {}
         }
     }
 
@@ -587,12 +675,14 @@ impl TokenCounter {
         if let Some(session) = global.sessions.remove(session_id) {
             global.total_tokens = global.total_tokens.saturating_sub(session.total_tokens);
         }
+        self.pending_methods.write().await.remove(session_id);
     }
 
     /// Clear all statistics
     pub async fn clear_all(&self) {
         let mut global = self.stats.write().await;
         *global = GlobalTokenStats::default();
+        self.pending_methods.write().await.clear();
     }
 }
 
@@ -605,6 +695,7 @@ impl Default for TokenCounter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::NoOpEventSink;
 
     #[test]
     fn test_estimate_tokens_empty() {
@@ -650,7 +741,13 @@ mod tests {
         });
 
         let stats = counter
-            .record_message("session-1", "msg-1", &content, true)
+            .record_message(
+                &NoOpEventSink,
+                "session-1",
+                "msg-1",
+                &McpMessage::parse(&content),
+                true,
+            )
             .await;
 
         assert_eq!(stats.method, Some("tools/call".to_string()));
@@ -678,7 +775,13 @@ mod tests {
         });
 
         counter
-            .record_message("session-1", "msg-1", &response, false)
+            .record_message(
+                &NoOpEventSink,
+                "session-1",
+                "msg-1",
+                &McpMessage::parse(&response),
+                false,
+            )
             .await;
 
         let session_stats = counter.get_session_stats("session-1").await.unwrap();
@@ -686,6 +789,109 @@ mod tests {
         assert!(session_stats.tool_definitions_tokens > 0);
     }
 
+    #[tokio::test]
+    async fn test_session_tokenizer() {
+        let counter = TokenCounter::new();
+        let content = serde_json::json!({"jsonrpc": "2.0", "method": "ping", "id": 1});
+
+        counter
+            .set_session_tokenizer("exact", TokenizerSpec::Cl100kBase.load().unwrap())
+            .await;
+        counter
+            .record_message(
+                &NoOpEventSink,
+                "exact",
+                "msg-1",
+                &McpMessage::parse(&content),
+                true,
+            )
+            .await;
+        counter
+            .record_message(
+                &NoOpEventSink,
+                "estimated",
+                "msg-1",
+                &McpMessage::parse(&content),
+                true,
+            )
+            .await;
+
+        let exact = counter.get_session_stats("exact").await.unwrap();
+        assert_eq!(exact.tokenizer, TokenizerSpec::Cl100kBase);
+        let json = serde_json::to_string(&content).unwrap();
+        let bpe = crate::tokenizer::BpeTokenizer::cl100k_base();
+        assert_eq!(exact.total_tokens, bpe.count_tokens(&json));
+
+        let estimated = counter.get_session_stats("estimated").await.unwrap();
+        assert_eq!(estimated.tokenizer, TokenizerSpec::Heuristic);
+        assert_eq!(estimated.total_tokens, TokenCounter::estimate_tokens(&json));
+
+        // Switching later relabels the session
+        counter
+            .set_session_tokenizer("estimated", TokenizerSpec::O200kBase.load().unwrap())
+            .await;
+        let estimated = counter.get_session_stats("estimated").await.unwrap();
+        assert_eq!(estimated.tokenizer, TokenizerSpec::O200kBase);
+    }
+
+    #[tokio::test]
+    async fn test_budget_exceeded() {
+        let counter = TokenCounter::new();
+        counter
+            .set_session_budget(
+                "s1",
+                TokenBudget {
+                    tool_result: Some(5),
+                    session: Some(60),
+                    ..Default::default()
+                },
+            )
+            .await;
+        let result = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {"content": [{"type": "text", "text": "a b c d e f g h i j k l"}]}
+        });
+
+        let stats = counter
+            .record_message(
+                &NoOpEventSink,
+                "s1",
+                "msg-1",
+                &McpMessage::parse(&result),
+                false,
+            )
+            .await;
+        assert_eq!(stats.budget_exceeded.len(), 1);
+        let violation = &stats.budget_exceeded[0];
+        assert_eq!(violation.kind, BudgetKind::ToolResult);
+        assert_eq!((violation.limit, violation.tokens), (5, 12));
+
+        // Requests are not checked against per-message limits
+        let stats = counter
+            .record_message(
+                &NoOpEventSink,
+                "s1",
+                "msg-2",
+                &McpMessage::parse(&result),
+                true,
+            )
+            .await;
+        assert_eq!(stats.budget_exceeded[0].kind, BudgetKind::Session);
+
+        // The session limit is only reported when first crossed
+        let stats = counter
+            .record_message(
+                &NoOpEventSink,
+                "s1",
+                "msg-3",
+                &McpMessage::parse(&result),
+                true,
+            )
+            .await;
+        assert!(stats.budget_exceeded.is_empty());
+    }
+
     #[test]
     fn test_count_mcp_context_tokens_protocol_messages() {
         // Protocol messages should have minimal token count
@@ -700,7 +906,162 @@ mod tests {
             "id": 1
         });
 
-        let tokens = TokenCounter::count_mcp_context_tokens(&init);
+        let tokens = TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&init));
         assert_eq!(tokens, 1); // Minimal - protocol overhead doesn't go to LLM
     }
+
+    #[test]
+    fn test_count_newer_features() {
+        // A resource link counts its reference, not the resource
+        let linked = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "content": [{
+                    "type": "resource_link",
+                    "uri": "file:///project/report.md",
+                    "name": "report.md",
+                    "description": "The full quarterly report"
+                }]
+            }
+        });
+        assert!(TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&linked)) > 5);
+
+        // Structured output is counted unless it is repeated as text
+        let structured = serde_json::json!({"temperature": 22.5, "conditions": "sunny"});
+        let structured_only = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": {"content": [], "structuredContent": structured}
+        });
+        let tokens = TokenCounter::count_mcp_context_tokens(&McpMessage::parse_response(
+            &structured_only,
+            "tools/call",
+        ));
+        assert_eq!(tokens, TokenCounter::count_json_tokens(&structured));
+        let repeated = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": {
+                "content": [{"type": "text", "text": structured.to_string()}],
+                "structuredContent": structured
+            }
+        });
+        assert_eq!(
+            TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&repeated)),
+            TokenCounter::estimate_tokens(&structured.to_string())
+        );
+
+        // Elicitation: the question and schema, then the user's answer
+        let elicit = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "elicitation/create",
+            "params": {
+                "message": "Which branch should be deployed?",
+                "requestedSchema": {
+                    "type": "object",
+                    "properties": {"branch": {"type": "string"}}
+                }
+            }
+        });
+        assert!(TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&elicit)) > 10);
+        let declined =
+            serde_json::json!({"jsonrpc": "2.0", "id": 3, "result": {"action": "decline"}});
+        assert_eq!(
+            TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&declined)),
+            1
+        );
+        let accepted = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "result": {"action": "accept", "content": {"branch": "release/2.4"}}
+        });
+        assert!(TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&accepted)) > 1);
+
+        // Completion requests only count what was typed
+        let complete = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "completion/complete",
+            "params": {
+                "ref": {"type": "ref/prompt", "name": "code_review"},
+                "argument": {"name": "language", "value": "py"}
+            }
+        });
+        assert_eq!(
+            TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&complete)),
+            TokenCounter::estimate_tokens("py").max(1)
+        );
+
+        let set_level = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "logging/setLevel",
+            "params": {"level": "debug"}
+        });
+        assert_eq!(
+            TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&set_level)),
+            1
+        );
+
+        let roots = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 6,
+            "result": {"roots": [{"uri": "file:///home/demo/project", "name": "project"}]}
+        });
+        assert!(TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&roots)) > 1);
+    }
+
+    #[tokio::test]
+    async fn test_responses_attributed_to_request_method() {
+        let counter = TokenCounter::new();
+        // The server asks the client for input
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "elicitation/create",
+            "params": {"message": "Name?", "requestedSchema": {"type": "object"}}
+        });
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "result": {"action": "accept", "content": {"name": "demo"}}
+        });
+        // A client request that happens to reuse the id
+        let ping = serde_json::json!({"jsonrpc": "2.0", "id": 7, "method": "ping"});
+
+        counter
+            .record_message(
+                &NoOpEventSink,
+                "s1",
+                "m1",
+                &McpMessage::parse(&request),
+                false,
+            )
+            .await;
+        counter
+            .record_message(&NoOpEventSink, "s1", "m2", &McpMessage::parse(&ping), true)
+            .await;
+        let stats = counter
+            .record_message(
+                &NoOpEventSink,
+                "s1",
+                "m3",
+                &McpMessage::parse(&response),
+                true,
+            )
+            .await;
+        assert_eq!(stats.method.as_deref(), Some("elicitation/create"));
+
+        let session = counter.get_session_stats("s1").await.unwrap();
+        let elicitation = &session.tokens_by_method["elicitation/create"];
+        assert_eq!(elicitation.call_count, 1);
+        assert_eq!(elicitation.response_tokens, stats.token_count);
+        assert_eq!(
+            elicitation.total_tokens,
+            elicitation.request_tokens + elicitation.response_tokens
+        );
+        assert_eq!(session.tokens_by_method["ping"].response_tokens, 0);
+    }
 }
@@ -0,0 +1,357 @@
+//! Pluggable tokenizers for token counting
+//!
+//! A [`Tokenizer`] turns text into a token count. Three kinds are available:
+//!
+//! - `heuristic` - the original character-based estimate, fast but approximate
+//! - `cl100k_base` / `o200k_base` - exact BPE counts from the OpenAI
+//!   vocabularies, bundled in the binary so no download is needed
+//! - `file:<path>` - any Hugging Face `tokenizer.json`, for other model families
+//!
+//! A [`TokenizerSpec`] names one of these and round-trips through a string,
+//! so it can be stored in [`SessionTokenStats`](crate::token_counter::SessionTokenStats)
+//! and passed on the command line.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Counts the tokens in a piece of text
+pub trait Tokenizer: Send + Sync {
+    /// Which tokenizer this is
+    fn spec(&self) -> TokenizerSpec;
+
+    /// Number of tokens `text` encodes to
+    fn count_tokens(&self, text: &str) -> u64;
+}
+
+/// Names a tokenizer
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum TokenizerSpec {
+    /// Character-based estimate
+    #[default]
+    Heuristic,
+    /// GPT-4 / GPT-3.5 vocabulary
+    Cl100kBase,
+    /// GPT-4o vocabulary
+    O200kBase,
+    /// Hugging Face `tokenizer.json`
+    File(PathBuf),
+}
+
+impl TokenizerSpec {
+    /// The tokenizers that need no external files
+    pub const BUILTIN: [TokenizerSpec; 3] = [
+        TokenizerSpec::Heuristic,
+        TokenizerSpec::Cl100kBase,
+        TokenizerSpec::O200kBase,
+    ];
+
+    /// Build the tokenizer
+    pub fn load(&self) -> Result<Arc<dyn Tokenizer>, TokenizerError> {
+        Ok(match self {
+            TokenizerSpec::Heuristic => Arc::new(HeuristicTokenizer),
+            TokenizerSpec::Cl100kBase => Arc::new(BpeTokenizer::cl100k_base()),
+            TokenizerSpec::O200kBase => Arc::new(BpeTokenizer::o200k_base()),
+            TokenizerSpec::File(path) => Arc::new(HuggingFaceTokenizer::from_file(path)?),
+        })
+    }
+}
+
+impl fmt::Display for TokenizerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenizerSpec::Heuristic => f.write_str("heuristic"),
+            TokenizerSpec::Cl100kBase => f.write_str("cl100k_base"),
+            TokenizerSpec::O200kBase => f.write_str("o200k_base"),
+            TokenizerSpec::File(path) => write!(f, "file:{}", path.display()),
+        }
+    }
+}
+
+impl FromStr for TokenizerSpec {
+    type Err = TokenizerError;
+
+    /// Parse a tokenizer name, `file:<path>`, or a path to a `.json` file
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "heuristic" => Ok(TokenizerSpec::Heuristic),
+            "cl100k_base" | "cl100k" => Ok(TokenizerSpec::Cl100kBase),
+            "o200k_base" | "o200k" => Ok(TokenizerSpec::O200kBase),
+            _ => {
+                if let Some(path) = s.strip_prefix("file:") {
+                    Ok(TokenizerSpec::File(PathBuf::from(path)))
+                } else if s.ends_with(".json") {
+                    Ok(TokenizerSpec::File(PathBuf::from(s)))
+                } else {
+                    Err(TokenizerError::Unknown(s.to_string()))
+                }
+            }
+        }
+    }
+}
+
+impl From<TokenizerSpec> for String {
+    fn from(spec: TokenizerSpec) -> Self {
+        spec.to_string()
+    }
+}
+
+impl TryFrom<String> for TokenizerSpec {
+    type Error = TokenizerError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Errors from choosing or loading a tokenizer
+#[derive(Debug, thiserror::Error)]
+pub enum TokenizerError {
+    #[error("Unknown tokenizer '{0}' (expected heuristic, cl100k_base, o200k_base, or file:<tokenizer.json>)")]
+    Unknown(String),
+
+    #[error("Failed to load tokenizer from {path}: {reason}")]
+    Load { path: PathBuf, reason: String },
+}
+
+/// Character-based estimate modelled on cl100k_base
+///
+/// - ~4 characters per token for regular text
+/// - JSON punctuation often becomes separate tokens
+/// - Numbers are typically 1-2 tokens per number
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn spec(&self) -> TokenizerSpec {
+        TokenizerSpec::Heuristic
+    }
+
+    fn count_tokens(&self, text: &str) -> u64 {
+        if text.is_empty() {
+            return 0;
+        }
+
+        let mut tokens = 0u64;
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+
+            if c.is_whitespace() {
+                // Whitespace is often merged with adjacent tokens
+                i += 1;
+                continue;
+            }
+
+            if c == '"' || c == '{' || c == '}' || c == '[' || c == ']' || c == ':' || c == ',' {
+                // JSON punctuation - often separate tokens
+                tokens += 1;
+                i += 1;
+                continue;
+            }
+
+            if c.is_ascii_digit() || c == '-' || c == '.' {
+                // Numbers - count as roughly 1 token per 3 digits
+                let mut num_len = 0;
+                while i + num_len < chars.len() {
+                    let nc = chars[i + num_len];
+                    if nc.is_ascii_digit() || nc == '.' || nc == '-' || nc == 'e' || nc == 'E' {
+                        num_len += 1;
+                    } else {
+                        break;
+                    }
+                }
+                if num_len > 0 {
+                    tokens += (num_len as u64).div_ceil(3); // ~3 chars per token for numbers
+                    i += num_len;
+                    continue;
+                }
+            }
+
+            // Regular text - count word-like sequences
+            let mut word_len = 0;
+            while i + word_len < chars.len() {
+                let wc = chars[i + word_len];
+                if wc.is_alphanumeric() || wc == '_' || wc == '-' {
+                    word_len += 1;
+                } else {
+                    break;
+                }
+            }
+
+            if word_len > 0 {
+                // ~4 characters per token for regular words
+                // But common words are often 1 token
+                tokens += if word_len <= 4 {
+                    1
+                } else {
+                    (word_len as u64).div_ceil(4)
+                };
+                i += word_len;
+            } else {
+                // Single special character
+                tokens += 1;
+                i += 1;
+            }
+        }
+
+        // Minimum 1 token for non-empty strings
+        tokens.max(1)
+    }
+}
+
+/// Exact counts from a bundled OpenAI BPE vocabulary
+///
+/// The vocabularies are parsed once per process and shared.
+pub struct BpeTokenizer {
+    spec: TokenizerSpec,
+    bpe: &'static tiktoken_rs::CoreBPE,
+}
+
+impl BpeTokenizer {
+    pub fn cl100k_base() -> Self {
+        Self {
+            spec: TokenizerSpec::Cl100kBase,
+            bpe: tiktoken_rs::cl100k_base_singleton(),
+        }
+    }
+
+    pub fn o200k_base() -> Self {
+        Self {
+            spec: TokenizerSpec::O200kBase,
+            bpe: tiktoken_rs::o200k_base_singleton(),
+        }
+    }
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn spec(&self) -> TokenizerSpec {
+        self.spec.clone()
+    }
+
+    fn count_tokens(&self, text: &str) -> u64 {
+        // Special-token markers in payloads are plain text to the model
+        self.bpe.encode_ordinary(text).len() as u64
+    }
+}
+
+/// A Hugging Face `tokenizer.json`, for model families without a bundled vocabulary
+pub struct HuggingFaceTokenizer {
+    path: PathBuf,
+    inner: tokenizers::Tokenizer,
+}
+
+impl HuggingFaceTokenizer {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, TokenizerError> {
+        let path = path.as_ref();
+        let inner = tokenizers::Tokenizer::from_file(path).map_err(|e| TokenizerError::Load {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            inner,
+        })
+    }
+}
+
+impl Tokenizer for HuggingFaceTokenizer {
+    fn spec(&self) -> TokenizerSpec {
+        TokenizerSpec::File(self.path.clone())
+    }
+
+    fn count_tokens(&self, text: &str) -> u64 {
+        match self.inner.encode(text, false) {
+            Ok(encoding) => encoding.len() as u64,
+            Err(e) => {
+                tracing::debug!(
+                    "Tokenizer {} failed, estimating: {}",
+                    self.path.display(),
+                    e
+                );
+                HeuristicTokenizer.count_tokens(text)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_round_trip() {
+        for spec in TokenizerSpec::BUILTIN {
+            assert_eq!(spec.to_string().parse::<TokenizerSpec>().unwrap(), spec);
+        }
+        let file: TokenizerSpec = "file:/models/llama/tokenizer.json".parse().unwrap();
+        assert_eq!(
+            file,
+            TokenizerSpec::File(PathBuf::from("/models/llama/tokenizer.json"))
+        );
+        assert_eq!(
+            "models/tokenizer.json".parse::<TokenizerSpec>().unwrap(),
+            TokenizerSpec::File(PathBuf::from("models/tokenizer.json"))
+        );
+        assert!("gpt2".parse::<TokenizerSpec>().is_err());
+
+        assert_eq!(
+            serde_json::to_value(&TokenizerSpec::O200kBase).unwrap(),
+            "o200k_base"
+        );
+        assert_eq!(
+            serde_json::from_value::<TokenizerSpec>("cl100k_base".into()).unwrap(),
+            TokenizerSpec::Cl100kBase
+        );
+    }
+
+    #[test]
+    fn test_bpe_counts() {
+        let cl100k = TokenizerSpec::Cl100kBase.load().unwrap();
+        assert_eq!(cl100k.count_tokens(""), 0);
+        assert_eq!(cl100k.count_tokens("hello world"), 2);
+        assert!(cl100k.count_tokens("<|endoftext|>") > 1);
+
+        let o200k = TokenizerSpec::O200kBase.load().unwrap();
+        assert_eq!(o200k.count_tokens("hello world"), 2);
+        assert_eq!(o200k.spec(), TokenizerSpec::O200kBase);
+    }
+
+    #[test]
+    fn test_tokenizer_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokenizer.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "version": "1.0",
+                "truncation": null,
+                "padding": null,
+                "added_tokens": [],
+                "normalizer": null,
+                "pre_tokenizer": {"type": "Whitespace"},
+                "post_processor": null,
+                "decoder": null,
+                "model": {
+                    "type": "WordLevel",
+                    "vocab": {"hello": 0, "world": 1, "[UNK]": 2},
+                    "unk_token": "[UNK]"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let spec = TokenizerSpec::File(path.clone());
+        let tokenizer = spec.load().unwrap();
+        assert_eq!(tokenizer.count_tokens("hello brave new world"), 4);
+        assert_eq!(tokenizer.spec(), spec);
+
+        let missing = TokenizerSpec::File(dir.path().join("missing.json"));
+        assert!(matches!(missing.load(), Err(TokenizerError::Load { .. })));
+    }
+}
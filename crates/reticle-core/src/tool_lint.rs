@@ -0,0 +1,537 @@
+//! Lint rules for MCP tool definitions
+//!
+//! An agent picks tools by name, description, and input schema alone, so
+//! gaps there turn into wrong or missed tool calls. [`lint_tools`] checks a
+//! `tools/list` result for:
+//!
+//! - missing or very short descriptions
+//! - duplicate names, and names that differ only in case, separators, or
+//!   one character
+//! - names that clients reject (only `A-Z a-z 0-9 _ -`, at most 64 characters)
+//! - schema properties without a `description`
+//! - enums too large to be useful in context
+//! - object schemas that leave `additionalProperties` open
+//! - object schemas with properties but no `required` list
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// How much a finding matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    /// Worth knowing, often intentional
+    Info,
+    /// Likely to hurt tool selection
+    Warning,
+    /// Breaks clients or makes the tool unusable
+    Error,
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintSeverity::Info => "info",
+            LintSeverity::Warning => "warning",
+            LintSeverity::Error => "error",
+        })
+    }
+}
+
+impl std::str::FromStr for LintSeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(LintSeverity::Info),
+            "warning" | "warn" => Ok(LintSeverity::Warning),
+            "error" => Ok(LintSeverity::Error),
+            _ => Err(format!(
+                "Unknown severity '{s}' (expected info, warning, or error)"
+            )),
+        }
+    }
+}
+
+/// Which check produced a finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    MissingDescription,
+    ShortDescription,
+    DuplicateName,
+    SimilarName,
+    InvalidName,
+    UndescribedProperty,
+    LargeEnum,
+    OpenAdditionalProperties,
+    MissingRequired,
+}
+
+/// One problem with one tool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintFinding {
+    pub rule: LintRule,
+    pub severity: LintSeverity,
+    /// The tool the finding refers to
+    pub tool: String,
+    /// Where in the input schema, e.g. `properties.options.properties.mode`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.severity, self.tool)?;
+        if let Some(ref path) = self.path {
+            write!(f, " ({path})")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Every finding for a server's tools, most severe first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintReport {
+    pub findings: Vec<LintFinding>,
+}
+
+impl LintReport {
+    /// Number of findings with this severity
+    pub fn count(&self, severity: LintSeverity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    /// The most severe finding, if there are any
+    pub fn max_severity(&self) -> Option<LintSeverity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+}
+
+/// Limits the lint rules check against
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintOptions {
+    /// Descriptions shorter than this many characters are flagged
+    pub min_description_chars: usize,
+    /// Enums with more values than this are flagged
+    pub max_enum_values: usize,
+    /// Longest tool name clients accept
+    pub max_name_len: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            min_description_chars: 20,
+            max_enum_values: 20,
+            max_name_len: 64,
+        }
+    }
+}
+
+/// Lint the `tools` array of a `tools/list` result
+pub fn lint_tools(tools: &[Value], options: &LintOptions) -> LintReport {
+    let mut findings = Vec::new();
+    let names: Vec<&str> = tools
+        .iter()
+        .map(|tool| tool.get("name").and_then(Value::as_str).unwrap_or(""))
+        .collect();
+
+    for (tool, name) in tools.iter().zip(&names) {
+        lint_name(name, options, &mut findings);
+        lint_description(tool, name, options, &mut findings);
+        if let Some(schema) = tool.get("inputSchema") {
+            lint_schema(schema, name, "", options, &mut findings);
+        }
+    }
+    lint_name_collisions(&names, &mut findings);
+
+    // Most severe first, keeping tool order within a severity
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    LintReport { findings }
+}
+
+fn finding(
+    rule: LintRule,
+    severity: LintSeverity,
+    tool: &str,
+    path: Option<String>,
+    message: String,
+) -> LintFinding {
+    LintFinding {
+        rule,
+        severity,
+        tool: tool.to_string(),
+        path,
+        message,
+    }
+}
+
+fn lint_name(name: &str, options: &LintOptions, findings: &mut Vec<LintFinding>) {
+    let problem = if name.is_empty() {
+        Some("name is empty".to_string())
+    } else if name.chars().count() > options.max_name_len {
+        Some(format!(
+            "name is {} characters, over the {} many clients allow",
+            name.chars().count(),
+            options.max_name_len
+        ))
+    } else {
+        name.chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-'))
+            .map(|c| {
+                format!("name contains '{c}'; clients only allow letters, digits, '_' and '-'")
+            })
+    };
+    if let Some(message) = problem {
+        findings.push(finding(
+            LintRule::InvalidName,
+            LintSeverity::Error,
+            name,
+            None,
+            message,
+        ));
+    }
+}
+
+fn lint_description(
+    tool: &Value,
+    name: &str,
+    options: &LintOptions,
+    findings: &mut Vec<LintFinding>,
+) {
+    let description = tool
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or("");
+    if description.is_empty() {
+        findings.push(finding(
+            LintRule::MissingDescription,
+            LintSeverity::Error,
+            name,
+            None,
+            "no description; the model has only the name to go on".to_string(),
+        ));
+    } else if description.chars().count() < options.min_description_chars {
+        findings.push(finding(
+            LintRule::ShortDescription,
+            LintSeverity::Warning,
+            name,
+            None,
+            format!(
+                "description is only {} characters: \"{description}\"",
+                description.chars().count()
+            ),
+        ));
+    }
+}
+
+fn lint_name_collisions(names: &[&str], findings: &mut Vec<LintFinding>) {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for name in names {
+        *counts.entry(name).or_default() += 1;
+    }
+
+    let mut reported = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        let count = counts[name];
+        if count > 1 {
+            if !reported.contains(name) {
+                reported.push(*name);
+                findings.push(finding(
+                    LintRule::DuplicateName,
+                    LintSeverity::Error,
+                    name,
+                    None,
+                    format!("{count} tools share this name"),
+                ));
+            }
+            continue;
+        }
+        // Compare each pair once, against the earlier tool
+        if let Some(other) = names[..i]
+            .iter()
+            .find(|other| counts[*other] == 1 && similar(name, other))
+        {
+            findings.push(finding(
+                LintRule::SimilarName,
+                LintSeverity::Warning,
+                name,
+                None,
+                format!("easily confused with '{other}'"),
+            ));
+        }
+    }
+}
+
+/// Whether two distinct names differ only in case and separators, or by
+/// a single edit
+fn similar(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| -> Vec<char> {
+        s.chars()
+            .filter(|c| !matches!(c, '_' | '-' | '.' | ' '))
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+    a == b || (a.len().min(b.len()) >= 4 && edit_distance(&a, &b) <= 1)
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+fn lint_schema(
+    schema: &Value,
+    tool: &str,
+    path: &str,
+    options: &LintOptions,
+    findings: &mut Vec<LintFinding>,
+) {
+    let at = |suffix: &str| -> String {
+        if path.is_empty() {
+            suffix.to_string()
+        } else {
+            format!("{path}.{suffix}")
+        }
+    };
+    let here = || (!path.is_empty()).then(|| path.to_string());
+
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        if values.len() > options.max_enum_values {
+            findings.push(finding(
+                LintRule::LargeEnum,
+                LintSeverity::Warning,
+                tool,
+                here(),
+                format!(
+                    "enum has {} values (more than {}); consider a free-form string",
+                    values.len(),
+                    options.max_enum_values
+                ),
+            ));
+        }
+    }
+
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        if !properties.is_empty() {
+            if schema.get("required").is_none() {
+                findings.push(finding(
+                    LintRule::MissingRequired,
+                    LintSeverity::Warning,
+                    tool,
+                    here(),
+                    "no 'required' list, so every property looks optional".to_string(),
+                ));
+            }
+            if !matches!(schema.get("additionalProperties"), Some(Value::Bool(false))) {
+                findings.push(finding(
+                    LintRule::OpenAdditionalProperties,
+                    LintSeverity::Info,
+                    tool,
+                    here(),
+                    "'additionalProperties' is not false, so unknown arguments are accepted"
+                        .to_string(),
+                ));
+            }
+        }
+
+        for (name, property) in properties {
+            let property_path = at(&format!("properties.{name}"));
+            let described = property
+                .get("description")
+                .and_then(Value::as_str)
+                .is_some_and(|d| !d.trim().is_empty());
+            if !described {
+                findings.push(finding(
+                    LintRule::UndescribedProperty,
+                    LintSeverity::Warning,
+                    tool,
+                    Some(property_path.clone()),
+                    format!("property '{name}' has no description"),
+                ));
+            }
+            lint_schema(property, tool, &property_path, options, findings);
+        }
+    }
+
+    if let Some(items) = schema.get("items") {
+        lint_schema(items, tool, &at("items"), options, findings);
+    }
+    for key in ["anyOf", "oneOf", "allOf"] {
+        if let Some(variants) = schema.get(key).and_then(Value::as_array) {
+            for (i, variant) in variants.iter().enumerate() {
+                lint_schema(
+                    variant,
+                    tool,
+                    &at(&format!("{key}[{i}]")),
+                    options,
+                    findings,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(report: &LintReport, tool: &str) -> Vec<LintRule> {
+        report
+            .findings
+            .iter()
+            .filter(|f| f.tool == tool)
+            .map(|f| f.rule)
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_tool() {
+        let tools = [json!({
+            "name": "read_file",
+            "description": "Read the contents of a file from the filesystem",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"path": {"type": "string", "description": "Absolute path"}},
+                "required": ["path"],
+                "additionalProperties": false
+            }
+        })];
+        assert_eq!(
+            lint_tools(&tools, &LintOptions::default()),
+            LintReport::default()
+        );
+    }
+
+    #[test]
+    fn test_descriptions_and_names() {
+        let tools = [
+            json!({"name": "search", "inputSchema": {"type": "object"}}),
+            json!({"name": "fetch", "description": "Fetch a URL"}),
+            json!({"name": "get.user", "description": "Look up a user by their id"}),
+            json!({"name": "a".repeat(65), "description": "An overly long tool name"}),
+        ];
+        let report = lint_tools(&tools, &LintOptions::default());
+        assert_eq!(rules(&report, "search"), vec![LintRule::MissingDescription]);
+        assert_eq!(rules(&report, "fetch"), vec![LintRule::ShortDescription]);
+        assert_eq!(rules(&report, "get.user"), vec![LintRule::InvalidName]);
+        assert_eq!(rules(&report, &"a".repeat(65)), vec![LintRule::InvalidName]);
+
+        // Errors sort before warnings
+        assert_eq!(report.max_severity(), Some(LintSeverity::Error));
+        assert_eq!(report.findings[0].severity, LintSeverity::Error);
+        assert_eq!(
+            report.findings.last().unwrap().rule,
+            LintRule::ShortDescription
+        );
+        assert_eq!(report.count(LintSeverity::Error), 3);
+    }
+
+    #[test]
+    fn test_name_collisions() {
+        let described =
+            |name: &str| json!({"name": name, "description": "A tool with a fine description"});
+        let tools = [
+            described("list_files"),
+            described("list_files"),
+            described("get_user"),
+            described("getUser"),
+            described("search_docs"),
+            described("search_doc"),
+            described("run"),
+            described("ran"),
+        ];
+        let report = lint_tools(&tools, &LintOptions::default());
+        assert_eq!(rules(&report, "list_files"), vec![LintRule::DuplicateName]);
+        assert_eq!(rules(&report, "getUser"), vec![LintRule::SimilarName]);
+        assert_eq!(rules(&report, "search_doc"), vec![LintRule::SimilarName]);
+        assert!(rules(&report, "get_user").is_empty());
+        // Short names are allowed to be one letter apart
+        assert!(rules(&report, "ran").is_empty());
+        assert_eq!(report.findings.len(), 3);
+    }
+
+    #[test]
+    fn test_schema_rules() {
+        let tools = [json!({
+            "name": "deploy",
+            "description": "Deploy a build to an environment",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "region": {"type": "string", "enum": (0..30).map(|i| format!("r{i}")).collect::<Vec<_>>()},
+                    "options": {
+                        "type": "object",
+                        "description": "Deployment options",
+                        "properties": {"force": {"type": "boolean"}},
+                        "required": [],
+                        "additionalProperties": false
+                    },
+                    "tags": {
+                        "type": "array",
+                        "description": "Tags to apply",
+                        "items": {"anyOf": [{"type": "string", "enum": ["a"]}]}
+                    }
+                },
+                "required": ["region"]
+            }
+        })];
+        let report = lint_tools(&tools, &LintOptions::default());
+        let found: Vec<(LintRule, Option<&str>)> = report
+            .findings
+            .iter()
+            .map(|f| (f.rule, f.path.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    LintRule::UndescribedProperty,
+                    Some("properties.options.properties.force")
+                ),
+                (LintRule::UndescribedProperty, Some("properties.region")),
+                (LintRule::LargeEnum, Some("properties.region")),
+                (LintRule::OpenAdditionalProperties, None),
+            ]
+        );
+        assert_eq!(
+            report.findings[2].to_string(),
+            "warning deploy (properties.region): enum has 30 values (more than 20); consider a free-form string"
+        );
+    }
+
+    #[test]
+    fn test_severity_parse() {
+        assert_eq!(
+            "warn".parse::<LintSeverity>().unwrap(),
+            LintSeverity::Warning
+        );
+        assert!("fatal".parse::<LintSeverity>().is_err());
+        assert!(LintSeverity::Error > LintSeverity::Warning);
+    }
+}
@@ -0,0 +1,483 @@
+//! Tool call validation against advertised schemas
+//!
+//! [`ToolSchemas`] remembers the `inputSchema` and `outputSchema` of every
+//! tool in a `tools/list` response that passes through, then checks each
+//! later `tools/call`: the request's `arguments` against the input schema,
+//! and the `structuredContent` of its result against the output schema.
+//! That catches malformed arguments from a model and a server whose output
+//! drifted from what it advertises, while the session is running.
+//!
+//! Schemas are compiled once, when the tool is listed. `$ref`s are only
+//! resolved inside the schema itself; nothing is fetched. Several observers
+//! of one session (a conformance checker and a recorder) can share one
+//! [`ToolSchemas`], so each message may be observed more than once: relisting
+//! an unchanged tool does not recompile it, and a call is remembered after
+//! its result so every observer can check it. Only the most recent
+//! [`MAX_CALLS`] calls are remembered.
+
+use jsonschema::Validator;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::protocol::mcp::Tool;
+use crate::protocol::message::{McpResult, Request};
+use crate::protocol::{Direction, McpMessage};
+
+/// Errors kept per mismatch; the rest are counted
+const MAX_ERRORS: usize = 10;
+
+/// Tool calls remembered for checking their results
+pub const MAX_CALLS: usize = 1024;
+
+/// What part of a tool call failed validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaTarget {
+    /// The request's `arguments`, against `inputSchema`
+    Arguments,
+    /// The result's `structuredContent`, against `outputSchema`
+    StructuredContent,
+}
+
+impl fmt::Display for SchemaTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaTarget::Arguments => write!(f, "arguments"),
+            SchemaTarget::StructuredContent => write!(f, "structuredContent"),
+        }
+    }
+}
+
+/// One place where a value breaks its schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaError {
+    /// JSON pointer into the validated value (empty for the value itself)
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// A tool call that does not match the tool's advertised schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaMismatch {
+    pub tool: String,
+    pub target: SchemaTarget,
+    /// JSON-RPC id of the `tools/call`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    /// The first few errors
+    pub errors: Vec<SchemaError>,
+    /// Errors beyond those listed
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub more: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (verb, schema) = match self.target {
+            SchemaTarget::Arguments => ("do", "inputSchema"),
+            SchemaTarget::StructuredContent => ("does", "outputSchema"),
+        };
+        write!(
+            f,
+            "{} {} {verb} not match its {schema}",
+            self.tool, self.target
+        )?;
+        if let Some(first) = self.errors.first() {
+            write!(f, ": {first}")?;
+        }
+        let others = self.errors.len().saturating_sub(1) + self.more;
+        if others > 0 {
+            write!(f, " (and {others} more)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Compiled {
+    /// The schemas as listed, to tell whether a relisting changed them
+    input_schema: Value,
+    output_schema: Option<Value>,
+    input: Option<Validator>,
+    output: Option<Validator>,
+}
+
+impl Compiled {
+    fn is_listed_as(&self, tool: &Tool) -> bool {
+        self.input_schema == tool.input_schema && self.output_schema == tool.output_schema
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    tools: HashMap<String, Compiled>,
+    /// Tool name of each recent `tools/call`, by request id
+    calls: HashMap<String, String>,
+    /// Request ids in `calls`, oldest first
+    call_order: VecDeque<String>,
+}
+
+impl State {
+    fn remember_call(&mut self, id: String, tool: String) {
+        if self.calls.insert(id.clone(), tool).is_none() {
+            self.call_order.push_back(id);
+        }
+        while self.calls.len() > MAX_CALLS {
+            let Some(oldest) = self.call_order.pop_front() else {
+                break;
+            };
+            self.calls.remove(&oldest);
+        }
+    }
+}
+
+/// The schemas a session's server advertised, and checks against them
+#[derive(Debug, Default)]
+pub struct ToolSchemas {
+    state: Mutex<State>,
+}
+
+impl ToolSchemas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of tools with a schema to check against
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().tools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Track a message passing through the proxy
+    ///
+    /// Listed tools are remembered; tool calls and their results are
+    /// checked against what was listed. Calls to tools that were never
+    /// listed are not checked.
+    pub fn observe(&self, message: &McpMessage, direction: Direction) -> Vec<SchemaMismatch> {
+        if let McpMessage::Batch(batch) = message {
+            return batch
+                .iter()
+                .flat_map(|message| self.observe(message, direction))
+                .collect();
+        }
+
+        let mut state = self.state.lock().unwrap();
+        match (message, direction) {
+            (
+                McpMessage::Response {
+                    result: McpResult::ListTools(list),
+                    ..
+                },
+                Direction::Out,
+            ) => {
+                // Pages of a paginated list add to what is known
+                for tool in &list.tools {
+                    let unchanged = state
+                        .tools
+                        .get(&tool.name)
+                        .is_some_and(|compiled| compiled.is_listed_as(tool));
+                    if !unchanged {
+                        state.tools.insert(tool.name.clone(), compile(tool));
+                    }
+                }
+                Vec::new()
+            }
+            (
+                McpMessage::Request {
+                    id,
+                    request: Request::CallTool(call),
+                },
+                Direction::In,
+            ) => {
+                state.remember_call(id.to_string(), call.name.clone());
+                let Some(validator) = state.tools.get(&call.name).and_then(|t| t.input.as_ref())
+                else {
+                    return Vec::new();
+                };
+                // Arguments may be left out when a tool takes none
+                let arguments = Value::Object(call.arguments.clone().unwrap_or_default());
+                mismatch(
+                    validator,
+                    &arguments,
+                    &call.name,
+                    SchemaTarget::Arguments,
+                    id,
+                )
+                .into_iter()
+                .collect()
+            }
+            (McpMessage::Response { id, result }, Direction::Out) => {
+                let Some(name) = state.calls.get(&id.to_string()).cloned() else {
+                    return Vec::new();
+                };
+                let Some(validator) = state.tools.get(&name).and_then(|t| t.output.as_ref()) else {
+                    return Vec::new();
+                };
+                let (is_error, structured) = match result {
+                    McpResult::CallTool(result) => (
+                        result.is_error == Some(true),
+                        result.structured_content.as_ref(),
+                    ),
+                    // A result too broken to type is still checked
+                    McpResult::Other(result) => (
+                        result.get("isError") == Some(&Value::Bool(true)),
+                        result.get("structuredContent"),
+                    ),
+                    _ => (false, None),
+                };
+                // Tool errors are reported as content, not structured output
+                if is_error {
+                    return Vec::new();
+                }
+                match structured {
+                    Some(content) => mismatch(
+                        validator,
+                        content,
+                        &name,
+                        SchemaTarget::StructuredContent,
+                        id,
+                    )
+                    .into_iter()
+                    .collect(),
+                    None => vec![SchemaMismatch {
+                        tool: name,
+                        target: SchemaTarget::StructuredContent,
+                        id: Some(id.clone()),
+                        errors: vec![SchemaError {
+                            path: String::new(),
+                            message: "structuredContent is missing".to_string(),
+                        }],
+                        more: 0,
+                    }],
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Compile a listed tool's schemas, skipping any that are not valid schemas
+fn compile(tool: &Tool) -> Compiled {
+    let validator = |key: &str, schema: Option<&Value>| {
+        let schema = schema.filter(|schema| !schema.is_null())?;
+        match jsonschema::validator_for(schema) {
+            Ok(validator) => Some(validator),
+            Err(e) => {
+                tracing::debug!("Not checking {} {}: {}", tool.name, key, e);
+                None
+            }
+        }
+    };
+    Compiled {
+        input_schema: tool.input_schema.clone(),
+        output_schema: tool.output_schema.clone(),
+        input: validator("inputSchema", Some(&tool.input_schema)),
+        output: validator("outputSchema", tool.output_schema.as_ref()),
+    }
+}
+
+fn mismatch(
+    validator: &Validator,
+    value: &Value,
+    tool: &str,
+    target: SchemaTarget,
+    id: &Value,
+) -> Option<SchemaMismatch> {
+    let mut errors = Vec::new();
+    let mut more = 0;
+    for error in validator.iter_errors(value) {
+        if errors.len() < MAX_ERRORS {
+            errors.push(SchemaError {
+                path: error.instance_path().to_string(),
+                message: error.to_string(),
+            });
+        } else {
+            more += 1;
+        }
+    }
+    (!errors.is_empty()).then(|| SchemaMismatch {
+        tool: tool.to_string(),
+        target,
+        id: Some(id.clone()),
+        errors,
+        more,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn listed() -> ToolSchemas {
+        let schemas = ToolSchemas::new();
+        let list = json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": [
+            {
+                "name": "read_file",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {"path": {"type": "string"}, "limit": {"type": "integer", "minimum": 1}},
+                    "required": ["path"]
+                }
+            },
+            {
+                "name": "stat",
+                "inputSchema": {"type": "object"},
+                "outputSchema": {
+                    "type": "object",
+                    "properties": {"size": {"type": "integer"}},
+                    "required": ["size"]
+                }
+            },
+            {"name": "broken", "inputSchema": {"type": 42}}
+        ]}});
+        assert!(schemas
+            .observe(&McpMessage::parse(&list), Direction::Out)
+            .is_empty());
+        schemas
+    }
+
+    #[test]
+    fn test_validates_arguments() {
+        let schemas = listed();
+        assert_eq!(schemas.len(), 3);
+
+        let good = json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+            "params": {"name": "read_file", "arguments": {"path": "/tmp/a"}}});
+        assert!(schemas
+            .observe(&McpMessage::parse(&good), Direction::In)
+            .is_empty());
+
+        let bad = json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+            "params": {"name": "read_file", "arguments": {"path": 7, "limit": 0}}});
+        let mismatches = schemas.observe(&McpMessage::parse(&bad), Direction::In);
+        assert_eq!(mismatches.len(), 1);
+        let mismatch = &mismatches[0];
+        assert_eq!(mismatch.tool, "read_file");
+        assert_eq!(mismatch.target, SchemaTarget::Arguments);
+        assert_eq!(mismatch.id, Some(json!(3)));
+        let paths: Vec<&str> = mismatch.errors.iter().map(|e| e.path.as_str()).collect();
+        assert!(paths.contains(&"/path") && paths.contains(&"/limit"));
+        assert!(mismatch
+            .to_string()
+            .starts_with("read_file arguments do not match its inputSchema: /"));
+        assert!(mismatch.to_string().ends_with("(and 1 more)"));
+
+        // Missing arguments are validated as an empty object
+        let missing = json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call",
+            "params": {"name": "read_file"}});
+        let mismatches = schemas.observe(&McpMessage::parse(&missing), Direction::In);
+        assert_eq!(mismatches[0].errors[0].path, "");
+
+        // Unlisted tools and invalid schemas are not checked
+        for name in ["unknown", "broken"] {
+            let call = json!({"jsonrpc": "2.0", "id": 5, "method": "tools/call",
+                "params": {"name": name, "arguments": {"x": 1}}});
+            assert!(schemas
+                .observe(&McpMessage::parse(&call), Direction::In)
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_validates_structured_content() {
+        let schemas = listed();
+        let call = |id: u64| {
+            json!({"jsonrpc": "2.0", "id": id, "method": "tools/call",
+                "params": {"name": "stat", "arguments": {}}})
+        };
+        let result = |id: u64, result: Value| json!({"jsonrpc": "2.0", "id": id, "result": result});
+
+        schemas.observe(&McpMessage::parse(&call(1)), Direction::In);
+        let ok = result(1, json!({"content": [], "structuredContent": {"size": 10}}));
+        assert!(schemas
+            .observe(&McpMessage::parse(&ok), Direction::Out)
+            .is_empty());
+
+        schemas.observe(&McpMessage::parse(&call(2)), Direction::In);
+        let drifted = result(
+            2,
+            json!({"content": [], "structuredContent": {"size": "10"}}),
+        );
+        let mismatches = schemas.observe(&McpMessage::parse(&drifted), Direction::Out);
+        assert_eq!(mismatches[0].target, SchemaTarget::StructuredContent);
+        assert_eq!(mismatches[0].errors[0].path, "/size");
+
+        schemas.observe(&McpMessage::parse(&call(3)), Direction::In);
+        let missing = result(3, json!({"content": []}));
+        let mismatches = schemas.observe(&McpMessage::parse(&missing), Direction::Out);
+        assert_eq!(
+            mismatches[0].to_string(),
+            "stat structuredContent does not match its outputSchema: structuredContent is missing"
+        );
+
+        // Tool errors carry no structured output
+        schemas.observe(&McpMessage::parse(&call(4)), Direction::In);
+        let error = result(4, json!({"content": [], "isError": true}));
+        assert!(schemas
+            .observe(&McpMessage::parse(&error), Direction::Out)
+            .is_empty());
+
+        // Another observer sharing the schemas checks the same result
+        let mismatches = schemas.observe(&McpMessage::parse(&drifted), Direction::Out);
+        assert_eq!(mismatches[0].errors[0].path, "/size");
+    }
+
+    #[test]
+    fn test_forgets_oldest_calls() {
+        let schemas = listed();
+        for id in 0..=MAX_CALLS as u64 {
+            let call = json!({"jsonrpc": "2.0", "id": id, "method": "tools/call",
+                "params": {"name": "stat", "arguments": {}}});
+            schemas.observe(&McpMessage::parse(&call), Direction::In);
+        }
+        assert_eq!(schemas.state.lock().unwrap().calls.len(), MAX_CALLS);
+
+        let result = |id: u64| json!({"jsonrpc": "2.0", "id": id, "result": {"content": []}});
+        assert!(schemas
+            .observe(&McpMessage::parse(&result(0)), Direction::Out)
+            .is_empty());
+        assert_eq!(
+            schemas
+                .observe(&McpMessage::parse(&result(1)), Direction::Out)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_relisting_replaces_schemas() {
+        let schemas = listed();
+        let relist = json!({"jsonrpc": "2.0", "id": 9, "result": {"tools": [
+            {"name": "read_file", "inputSchema": {"type": "object"}}
+        ]}});
+        schemas.observe(&McpMessage::parse(&relist), Direction::Out);
+        assert_eq!(schemas.len(), 3);
+
+        let call = json!({"jsonrpc": "2.0", "id": 10, "method": "tools/call",
+            "params": {"name": "read_file", "arguments": {"path": 7}}});
+        assert!(schemas
+            .observe(&McpMessage::parse(&call), Direction::In)
+            .is_empty());
+    }
+}
@@ -12,13 +12,14 @@ import { Sidebar } from '@/components/Sidebar'
 import { ControlBar } from '@/components/ControlBar'
 import { RequestComposer } from '@/components/RequestComposer'
 import { KeyboardShortcuts } from '@/components/KeyboardShortcuts'
+import { HeldMessages } from '@/components/HeldMessages'
 import { useReticleStore } from '@/store'
 import { useTheme } from '@/components/theme-provider'
-import { LogEntry } from '@/types'
+import { LogEntry, HeldMessage, BreakpointRelease } from '@/types'
 import './styles/globals.css'
 
 function App() {
-  const { addLog, setConnected, addSession } = useReticleStore()
+  const { addLog, setConnected, addSession, addHeldMessage, removeHeldMessage } = useReticleStore()
   const { resolvedTheme } = useTheme()
 
   useEffect(() => {
@@ -27,6 +28,8 @@ function App() {
 
     let unsubscribe: (() => void) | null = null
     let unsubscribeSession: (() => void) | null = null
+    let unsubscribeHit: (() => void) | null = null
+    let unsubscribeReleased: (() => void) | null = null
 
     // Set up listeners
     const setupListeners = async () => {
@@ -67,6 +70,19 @@ function App() {
           })
         })
 
+        // Listen for messages held at breakpoints in CLI sessions
+        unsubscribeHit = await listen<HeldMessage>('breakpoint-hit', (event) => {
+          addHeldMessage(event.payload)
+        })
+        unsubscribeReleased = await listen<BreakpointRelease>('breakpoint-released', (event) => {
+          removeHeldMessage(event.payload.session_id, event.payload.hold_id)
+          if (event.payload.outcome === 'timed_out') {
+            toast.info('Breakpoint timed out', {
+              description: `Held message ${event.payload.hold_id} was forwarded unchanged`,
+            })
+          }
+        })
+
         console.log('Tauri event listeners ready!')
       } catch (error) {
         console.error('Failed to set up event listeners:', error)
@@ -84,8 +100,10 @@ function App() {
     return () => {
       if (unsubscribe) unsubscribe()
       if (unsubscribeSession) unsubscribeSession()
+      if (unsubscribeHit) unsubscribeHit()
+      if (unsubscribeReleased) unsubscribeReleased()
     }
-  }, [addLog, setConnected, addSession])
+  }, [addLog, setConnected, addSession, addHeldMessage, removeHeldMessage])
 
   return (
     <div className="h-screen w-screen bg-background text-foreground overflow-hidden flex flex-col">
@@ -137,6 +155,7 @@ function App() {
           className="min-w-[350px]"
         >
           <div className="flex flex-col h-full">
+            <HeldMessages />
             <div className="flex-1 overflow-hidden">
               <Inspector />
             </div>
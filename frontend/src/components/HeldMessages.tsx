@@ -0,0 +1,162 @@
+import { useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { PauseCircle, Play, Pencil, Trash2, Send, X } from 'lucide-react'
+import { toast } from 'sonner'
+import { useReticleStore } from '@/store'
+import { HeldMessage } from '@/types'
+import { Button } from '@/components/ui/button'
+
+/**
+ * Messages held at breakpoints in CLI sessions
+ *
+ * Each held message can be forwarded unchanged, edited and forwarded,
+ * or dropped. The panel is hidden while nothing is held.
+ */
+export function HeldMessages() {
+  const heldMessages = useReticleStore((state) => state.heldMessages)
+
+  if (heldMessages.length === 0) return null
+
+  return (
+    <div className="border-b border-border bg-[#D97706]/5 dark:bg-[#FCEE09]/5 max-h-[40%] overflow-y-auto">
+      <div className="flex items-center gap-2 px-4 py-2 border-b border-border">
+        <PauseCircle className="w-4 h-4 text-[#D97706] dark:text-[#FCEE09]" />
+        <h2 className="text-sm font-semibold text-foreground">Breakpoints</h2>
+        <span className="text-xs text-muted-foreground">{heldMessages.length} held</span>
+      </div>
+      {heldMessages.map((held) => (
+        <HeldMessageRow key={`${held.session_id}:${held.hold_id}`} held={held} />
+      ))}
+    </div>
+  )
+}
+
+function HeldMessageRow({ held }: { held: HeldMessage }) {
+  const [isEditing, setIsEditing] = useState(false)
+  const [editedContent, setEditedContent] = useState('')
+  const [isSending, setIsSending] = useState(false)
+
+  const resume = async (args: { message?: string; drop?: boolean }) => {
+    setIsSending(true)
+    try {
+      await invoke('resume_cli_breakpoint', {
+        sessionId: held.session_id,
+        holdId: held.hold_id,
+        ...args,
+      })
+    } catch (error) {
+      const errorMessage = typeof error === 'string' ? error : (error instanceof Error ? error.message : 'Unknown error')
+      toast.error('Failed to resume message', {
+        description: errorMessage,
+      })
+    } finally {
+      setIsSending(false)
+    }
+  }
+
+  const handleEdit = () => {
+    setEditedContent(formatJSON(held.content))
+    setIsEditing(true)
+  }
+
+  const handleSendEdited = () => {
+    try {
+      JSON.parse(editedContent)
+    } catch {
+      toast.error('Invalid JSON')
+      return
+    }
+    resume({ message: editedContent })
+  }
+
+  return (
+    <div className="px-4 py-2 border-b border-border last:border-b-0 space-y-2">
+      <div className="flex items-center justify-between gap-2 text-xs">
+        <div className="flex items-center gap-2 min-w-0">
+          <span className="inline-flex items-center px-1.5 py-0.5 rounded-md font-mono font-medium bg-secondary text-secondary-foreground border border-border truncate">
+            {held.method || 'response'}
+          </span>
+          <span className="text-muted-foreground truncate">
+            {held.direction === 'in' ? 'to' : 'from'} {held.server_name} · {held.breakpoint_id}
+          </span>
+        </div>
+        {!isEditing && (
+          <div className="flex items-center gap-1 flex-shrink-0">
+            <Button
+              variant="ghost"
+              size="sm"
+              onClick={() => resume({})}
+              disabled={isSending}
+              className="h-8 px-2 border border-[#059669]/50 dark:border-[#00FF9F]/50 hover:bg-[#059669]/10 dark:hover:bg-[#00FF9F]/10"
+              title="Forward unchanged"
+            >
+              <Play className="w-3.5 h-3.5 text-[#059669] dark:text-[#00FF9F]" />
+            </Button>
+            <Button
+              variant="ghost"
+              size="sm"
+              onClick={handleEdit}
+              disabled={isSending}
+              className="h-8 px-2 border border-[#00808F]/50 dark:border-[#00F0FF]/50 hover:bg-[#00808F]/10 dark:hover:bg-[#00F0FF]/10"
+              title="Edit and forward"
+            >
+              <Pencil className="w-3.5 h-3.5 text-[#00808F] dark:text-[#00F0FF]" />
+            </Button>
+            <Button
+              variant="ghost"
+              size="sm"
+              onClick={() => resume({ drop: true })}
+              disabled={isSending}
+              className="h-8 px-2 border border-[#DC2626]/50 dark:border-[#FF003C]/50 hover:bg-[#DC2626]/10 dark:hover:bg-[#FF003C]/10"
+              title="Drop"
+            >
+              <Trash2 className="w-3.5 h-3.5 text-[#DC2626] dark:text-[#FF003C]" />
+            </Button>
+          </div>
+        )}
+      </div>
+      {isEditing && (
+        <>
+          <textarea
+            value={editedContent}
+            onChange={(e) => setEditedContent(e.target.value)}
+            spellCheck={false}
+            className="w-full h-32 p-2 text-xs font-mono bg-background border border-border rounded-md resize-y focus:outline-none focus:ring-1 focus:ring-[#00808F] dark:focus:ring-[#00F0FF]"
+          />
+          <div className="flex justify-end gap-2">
+            <Button
+              variant="ghost"
+              size="sm"
+              onClick={handleSendEdited}
+              disabled={isSending}
+              className="h-8 px-3 border border-[#059669]/50 dark:border-[#00FF9F]/50 hover:bg-[#059669]/10 dark:hover:bg-[#00FF9F]/10"
+            >
+              <Send className="w-3.5 h-3.5 mr-2 text-[#059669] dark:text-[#00FF9F]" />
+              <span className="text-xs text-[#059669] dark:text-[#00FF9F]">Forward</span>
+            </Button>
+            <Button
+              variant="ghost"
+              size="sm"
+              onClick={() => setIsEditing(false)}
+              className="h-8 px-3 border border-border hover:bg-muted"
+            >
+              <X className="w-3.5 h-3.5 mr-2 text-muted-foreground" />
+              <span className="text-xs text-foreground">Cancel</span>
+            </Button>
+          </div>
+        </>
+      )}
+    </div>
+  )
+}
+
+/**
+ * Format JSON string with proper indentation
+ */
+function formatJSON(jsonStr: string): string {
+  try {
+    return JSON.stringify(JSON.parse(jsonStr), null, 2)
+  } catch {
+    return jsonStr
+  }
+}
@@ -1,7 +1,7 @@
 import { useEffect, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import Editor from '@monaco-editor/react'
-import { Copy, Check, FileJson, ArrowRight, AlertTriangle, Terminal, Play, Pencil, X, Send } from 'lucide-react'
+import { Copy, Check, FileJson, ArrowRight, AlertTriangle, Terminal, Play, Pencil, X, Send, Zap, Clock, ShieldAlert } from 'lucide-react'
 import { toast } from 'sonner'
 import {
   useReticleStore,
@@ -21,12 +21,16 @@ export function Inspector() {
   const [isEditMode, setIsEditMode] = useState(false)
   const [editedContent, setEditedContent] = useState('')
   const [isSending, setIsSending] = useState(false)
+  const [showOriginal, setShowOriginal] = useState(false)
   const { resolvedTheme } = useTheme()
 
   // Check message type
   const isRawMessage = selectedLog?.message_type === 'raw'
   const isStderrMessage = selectedLog?.message_type === 'stderr'
   const isNonJsonRpc = isRawMessage || isStderrMessage
+  const isFault = selectedLog?.message_type === 'fault'
+  const isUnanswered = selectedLog?.message_type === 'unanswered'
+  const isViolation = selectedLog?.message_type === 'violation'
 
   // Check if this is a response and find correlated request
   const parsed = selectedLog && !isNonJsonRpc ? parseLogMessage(selectedLog) : null
@@ -41,6 +45,7 @@ export function Inspector() {
     setCopied(false)
     setIsEditMode(false)
     setEditedContent('')
+    setShowOriginal(false)
   }, [selectedLog?.id])
 
   // Check if this is a replayable request (incoming JSON-RPC with method)
@@ -128,10 +133,10 @@ export function Inspector() {
     }
   }
 
-  // Format JSON for display
-  const formattedJSON = selectedLog
-    ? formatJSON(selectedLog.content)
-    : ''
+  // Format JSON for display, as forwarded or as originally received
+  const shownContent =
+    showOriginal && selectedLog?.original ? selectedLog.original : selectedLog?.content
+  const formattedJSON = shownContent ? formatJSON(shownContent) : ''
 
   return (
     <div className="flex flex-col h-full bg-background border-l border-border">
@@ -142,11 +147,27 @@ export function Inspector() {
             <AlertTriangle className="w-4 h-4 text-[#DC2626] dark:text-[#FF003C]" />
           ) : isRawMessage ? (
             <Terminal className="w-4 h-4 text-[#D97706] dark:text-[#FCEE09]" />
+          ) : isFault ? (
+            <Zap className="w-4 h-4 text-[#7C3AED] dark:text-[#BF5AF2]" />
+          ) : isUnanswered ? (
+            <Clock className="w-4 h-4 text-[#EA580C] dark:text-[#FF9F0A]" />
+          ) : isViolation ? (
+            <ShieldAlert className="w-4 h-4 text-[#DB2777] dark:text-[#FF2D95]" />
           ) : (
             <FileJson className="w-4 h-4 text-[#00808F] dark:text-[#00F0FF]" />
           )}
           <h2 className="text-sm font-semibold text-foreground">
-            {isStderrMessage ? 'Stderr Output' : isRawMessage ? 'Raw Output' : 'Inspector'}
+            {isStderrMessage
+              ? 'Stderr Output'
+              : isRawMessage
+              ? 'Raw Output'
+              : isFault
+              ? 'Injected Fault'
+              : isUnanswered
+              ? 'Unanswered Request'
+              : isViolation
+              ? 'Protocol Violation'
+              : 'Inspector'}
           </h2>
         </div>
         {selectedLog && (
@@ -246,6 +267,30 @@ export function Inspector() {
                 </span>
               </div>
             )}
+            {isFault && (
+              <div className="flex items-center gap-2 px-3 py-2 bg-[#7C3AED]/10 dark:bg-[#BF5AF2]/10 border border-[#7C3AED]/30 dark:border-[#BF5AF2]/30 rounded-md mb-2">
+                <Zap className="w-4 h-4 text-[#7C3AED] dark:text-[#BF5AF2] flex-shrink-0" />
+                <span className="text-xs text-[#7C3AED] dark:text-[#BF5AF2]">
+                  This fault was injected by a chaos profile, not sent by the client or server
+                </span>
+              </div>
+            )}
+            {isUnanswered && (
+              <div className="flex items-center gap-2 px-3 py-2 bg-[#EA580C]/10 dark:bg-[#FF9F0A]/10 border border-[#EA580C]/30 dark:border-[#FF9F0A]/30 rounded-md mb-2">
+                <Clock className="w-4 h-4 text-[#EA580C] dark:text-[#FF9F0A] flex-shrink-0" />
+                <span className="text-xs text-[#EA580C] dark:text-[#FF9F0A]">
+                  The proxy reports this request never got a response; the request itself is logged separately
+                </span>
+              </div>
+            )}
+            {isViolation && (
+              <div className="flex items-center gap-2 px-3 py-2 bg-[#DB2777]/10 dark:bg-[#FF2D95]/10 border border-[#DB2777]/30 dark:border-[#FF2D95]/30 rounded-md mb-2">
+                <ShieldAlert className="w-4 h-4 text-[#DB2777] dark:text-[#FF2D95] flex-shrink-0" />
+                <span className="text-xs text-[#DB2777] dark:text-[#FF2D95]">
+                  The proxy reports a protocol rule broken by another message, named by its log_id
+                </span>
+              </div>
+            )}
             <div className="flex items-center justify-between text-xs">
               <span className="text-muted-foreground font-medium">Timestamp</span>
               <span className="font-mono text-foreground tabular-nums">
@@ -261,10 +306,26 @@ export function Inspector() {
                     ? 'bg-[#DC2626]/20 dark:bg-[#FF003C]/20 text-[#DC2626] dark:text-[#FF003C] border-[#DC2626]/30 dark:border-[#FF003C]/30'
                     : isRawMessage
                     ? 'bg-[#D97706]/20 dark:bg-[#FCEE09]/20 text-[#D97706] dark:text-[#FCEE09] border-[#D97706]/30 dark:border-[#FCEE09]/30'
+                    : isFault
+                    ? 'bg-[#7C3AED]/20 dark:bg-[#BF5AF2]/20 text-[#7C3AED] dark:text-[#BF5AF2] border-[#7C3AED]/30 dark:border-[#BF5AF2]/30'
+                    : isUnanswered
+                    ? 'bg-[#EA580C]/20 dark:bg-[#FF9F0A]/20 text-[#EA580C] dark:text-[#FF9F0A] border-[#EA580C]/30 dark:border-[#FF9F0A]/30'
+                    : isViolation
+                    ? 'bg-[#DB2777]/20 dark:bg-[#FF2D95]/20 text-[#DB2777] dark:text-[#FF2D95] border-[#DB2777]/30 dark:border-[#FF2D95]/30'
                     : 'bg-secondary text-secondary-foreground border-border'
                 )}
               >
-                {isStderrMessage ? 'stderr' : isRawMessage ? 'raw' : 'json-rpc'}
+                {isStderrMessage
+                  ? 'stderr'
+                  : isRawMessage
+                  ? 'raw'
+                  : isFault
+                  ? 'fault'
+                  : isUnanswered
+                  ? 'unanswered'
+                  : isViolation
+                  ? 'violation'
+                  : 'json-rpc'}
               </span>
             </div>
             <div className="flex items-center justify-between text-xs">
@@ -289,6 +350,27 @@ export function Inspector() {
                 ...{selectedLog.session_id.slice(-8)}
               </span>
             </div>
+            {selectedLog.modified && (
+              <div className="flex items-center justify-between text-xs">
+                <span className="text-muted-foreground font-medium">Modified</span>
+                {selectedLog.original ? (
+                  <Button
+                    variant="ghost"
+                    size="sm"
+                    onClick={() => setShowOriginal(!showOriginal)}
+                    disabled={isEditMode}
+                    className="h-8 px-3 text-xs border border-border hover:bg-muted"
+                    title="Toggle between the forwarded and the original message"
+                  >
+                    <span className="text-[#00808F] dark:text-[#00F0FF]">
+                      {showOriginal ? 'Showing original' : 'Showing forwarded'}
+                    </span>
+                  </Button>
+                ) : (
+                  <span className="font-mono text-[#00808F] dark:text-[#00F0FF]">forwarded changed</span>
+                )}
+              </div>
+            )}
             {(latency !== null || selectedLog.duration_micros !== undefined) && (
               <div className="flex items-center justify-between text-xs">
                 <span className="text-muted-foreground font-medium">
@@ -350,7 +432,7 @@ export function Inspector() {
             <Editor
               height="100%"
               defaultLanguage={isNonJsonRpc ? 'plaintext' : 'json'}
-              value={isEditMode ? editedContent : (isNonJsonRpc ? selectedLog.content : formattedJSON)}
+              value={isEditMode ? editedContent : (isNonJsonRpc ? shownContent : formattedJSON)}
               onChange={(value) => isEditMode && setEditedContent(value || '')}
               theme={resolvedTheme === 'dark' ? 'vs-dark' : 'light'}
               options={{
@@ -21,7 +21,13 @@ import {
   findCorrelatedRequest,
   calculateLatency,
 } from '@/store'
-import { LogEntry, ParsedMessage } from '@/types'
+import {
+  LogEntry,
+  ParsedMessage,
+  FaultDescription,
+  UnansweredDescription,
+  ViolationDescription,
+} from '@/types'
 import { cn, formatTimestamp, truncate, formatDuration } from '@/lib/utils'
 import { Input } from '@/components/ui/input'
 import { Button } from '@/components/ui/button'
@@ -40,11 +46,20 @@ const LogRow = memo(({ log }: { log: LogEntry; index: number }) => {
   const isRawMessage = log.message_type === 'raw'
   const isStderrMessage = log.message_type === 'stderr'
   const isNonJsonRpc = isRawMessage || isStderrMessage
+  // Faults injected by a chaos profile describe the fault, not a message
+  const isFault = log.message_type === 'fault'
+  // Requests the proxy reports as never answered; the request has its own row
+  const isUnanswered = log.message_type === 'unanswered'
+  // Protocol rules broken by a message; the message has its own row
+  const isViolation = log.message_type === 'violation'
 
   const parsed = isNonJsonRpc ? null : parseLogMessage(log)
+  const fault = isFault ? parseReport<FaultDescription>(log) : null
+  const unanswered = isUnanswered ? parseReport<UnansweredDescription>(log) : null
+  const violation = isViolation ? parseReport<ViolationDescription>(log) : null
   const isError = parsed?.error !== undefined || isStderrMessage
   const isRequest = parsed?.method !== undefined && !parsed.result && !parsed.error
-  const isResponse = (parsed?.result !== undefined || parsed?.error !== undefined) && !parsed.method
+  const isResponse = (parsed?.result !== undefined || parsed?.error !== undefined) && !parsed?.method
 
   // Find correlated request for responses
   const correlatedRequest = isResponse ? findCorrelatedRequest(log, logs) : null
@@ -55,11 +70,31 @@ const LogRow = memo(({ log }: { log: LogEntry; index: number }) => {
     ? 'stderr'
     : isRawMessage
     ? 'raw'
+    : isFault
+    ? `chaos:${fault?.chaos ?? 'fault'}`
+    : isUnanswered
+    ? 'unanswered'
+    : isViolation
+    ? `violation:${violation?.kind ?? 'unknown'}`
     : parsed?.method || (correlatedRequest ? parseLogMessage(correlatedRequest)?.method || 'response' : 'response')
-  const summary = isNonJsonRpc ? log.content : getSummary(parsed)
+  const summary = isNonJsonRpc
+    ? log.content
+    : isFault
+    ? getFaultSummary(fault)
+    : isUnanswered
+    ? getUnansweredSummary(unanswered)
+    : isViolation
+    ? getViolationSummary(violation)
+    : getSummary(parsed)
 
   // Get JSON-RPC id for correlation display
-  const rpcId = parsed?.id
+  const rpcId = isFault
+    ? fault?.id
+    : isUnanswered
+    ? unanswered?.id ?? undefined
+    : isViolation
+    ? violation?.id
+    : parsed?.id
 
   // Copy JSON to clipboard
   const handleCopyJson = (e: React.MouseEvent) => {
@@ -74,6 +109,9 @@ const LogRow = memo(({ log }: { log: LogEntry; index: number }) => {
   const getStatusColor = () => {
     if (isStderrMessage) return 'bg-[#DC2626] dark:bg-[#FF003C]' // Stderr is always red
     if (isRawMessage) return 'bg-[#D97706] dark:bg-[#FCEE09]' // Raw output is warning-colored
+    if (isFault) return 'bg-[#7C3AED] dark:bg-[#BF5AF2]' // Injected faults are not real traffic
+    if (isUnanswered) return 'bg-[#EA580C] dark:bg-[#FF9F0A]' // Neither are reports of missing responses
+    if (isViolation) return 'bg-[#DB2777] dark:bg-[#FF2D95]' // Nor protocol violations
     if (isError) return 'bg-[#DC2626] dark:bg-[#FF003C]'
     if (isRequest) return 'bg-[#00808F] dark:bg-[#00F0FF]'
     return 'bg-[#059669] dark:bg-[#00FF9F]'
@@ -152,6 +190,12 @@ const LogRow = memo(({ log }: { log: LogEntry; index: number }) => {
             ? 'bg-[#DC2626]/20 dark:bg-[#FF003C]/20 text-[#DC2626] dark:text-[#FF003C] border border-[#DC2626]/30 dark:border-[#FF003C]/30'
             : isRawMessage
             ? 'bg-[#D97706]/20 dark:bg-[#FCEE09]/20 text-[#D97706] dark:text-[#FCEE09] border border-[#D97706]/30 dark:border-[#FCEE09]/30'
+            : isFault
+            ? 'bg-[#7C3AED]/20 dark:bg-[#BF5AF2]/20 text-[#7C3AED] dark:text-[#BF5AF2] border border-[#7C3AED]/30 dark:border-[#BF5AF2]/30'
+            : isUnanswered
+            ? 'bg-[#EA580C]/20 dark:bg-[#FF9F0A]/20 text-[#EA580C] dark:text-[#FF9F0A] border border-[#EA580C]/30 dark:border-[#FF9F0A]/30'
+            : isViolation
+            ? 'bg-[#DB2777]/20 dark:bg-[#FF2D95]/20 text-[#DB2777] dark:text-[#FF2D95] border border-[#DB2777]/30 dark:border-[#FF2D95]/30'
             : 'bg-secondary text-secondary-foreground border border-border'
         )}
       >
@@ -166,6 +210,16 @@ const LogRow = memo(({ log }: { log: LogEntry; index: number }) => {
         {summary}
       </span>
 
+      {/* Changed before forwarding */}
+      {log.modified && (
+        <span
+          className="text-[10px] font-mono flex-shrink-0 px-1.5 py-0.5 rounded bg-[#00808F]/10 dark:bg-[#00F0FF]/10 text-[#00808F] dark:text-[#00F0FF]"
+          title="Edited at a breakpoint or rewritten by a rule before forwarding"
+        >
+          modified
+        </span>
+      )}
+
       {/* Token count */}
       {log.token_count !== undefined && log.token_count > 0 && (
         <span
@@ -212,6 +266,63 @@ const LogRow = memo(({ log }: { log: LogEntry; index: number }) => {
 })
 LogRow.displayName = 'LogRow'
 
+/**
+ * Parse the description in a proxy report (an injected fault, an
+ * unanswered request, or a conformance violation)
+ */
+function parseReport<T>(log: LogEntry): T | null {
+  try {
+    return JSON.parse(log.content) as T
+  } catch {
+    return null
+  }
+}
+
+/**
+ * Get summary text for an injected fault
+ */
+function getFaultSummary(fault: FaultDescription | null): string {
+  if (!fault) return 'Invalid fault'
+
+  const name = fault.fault
+  const target = fault.method ? ` on ${fault.method}` : ''
+  switch (fault.chaos) {
+    case 'latency':
+      return `${name}: delayed ${fault.delay_ms ?? 0}ms${target}`
+    case 'error':
+      return `${name}: answered with an error${target}`
+    case 'kill':
+      return `${name}: killed the server${target}`
+    case 'drop':
+      return `${name}: dropped the response${target}`
+    case 'truncate':
+      return `${name}: cut the response to ${fault.kept_bytes ?? 0} bytes${target}`
+    default:
+      return `${name}${target}`
+  }
+}
+
+/**
+ * Get summary text for a request that never got a response
+ */
+function getUnansweredSummary(unanswered: UnansweredDescription | null): string {
+  if (!unanswered) return 'Invalid report'
+
+  return unanswered.reason === 'orphaned'
+    ? `${unanswered.method} was still waiting when the session ended`
+    : `${unanswered.method} got no response in ${formatDuration(unanswered.elapsed_ms * 1000)}`
+}
+
+/**
+ * Get summary text for a conformance violation
+ */
+function getViolationSummary(violation: ViolationDescription | null): string {
+  if (!violation) return 'Invalid report'
+
+  const errors = violation.errors?.map((e) => (e.path ? `${e.path}: ${e.message}` : e.message))
+  return errors?.length ? `${violation.message} (${errors.join('; ')})` : violation.message
+}
+
 /**
  * Get a human-readable summary of the log message
  */
@@ -1,6 +1,13 @@
 import { describe, it, expect, beforeEach } from 'vitest'
-import { useReticleStore, parseLogMessage, extractMethod, findCorrelatedRequest, calculateLatency } from './index'
-import type { LogEntry } from '@/types'
+import {
+  useReticleStore,
+  parseLogMessage,
+  extractMethod,
+  findCorrelatedRequest,
+  calculateLatency,
+  isProxyReport,
+} from './index'
+import type { LogEntry, HeldMessage } from '@/types'
 
 // Helper to reset store between tests
 function resetStore() {
@@ -8,6 +15,7 @@ function resetStore() {
     isConnected: false,
     logs: [],
     selectedLogId: null,
+    heldMessages: [],
     sessions: [],
     currentSession: null,
     availableServers: [],
@@ -183,6 +191,36 @@ describe('useReticleStore', () => {
     })
   })
 
+  describe('held messages', () => {
+    const held: HeldMessage = {
+      session_id: 'session-1',
+      hold_id: 'log-3',
+      breakpoint_id: 'bp-1',
+      direction: 'in',
+      content: '{"jsonrpc":"2.0","id":1,"method":"tools/call"}',
+      method: 'tools/call',
+      server_name: 'github',
+    }
+
+    it('adds a held message once per hold', () => {
+      useReticleStore.getState().addHeldMessage(held)
+      useReticleStore.getState().addHeldMessage(held)
+
+      expect(useReticleStore.getState().heldMessages).toHaveLength(1)
+    })
+
+    it('removes a released message from its own session only', () => {
+      useReticleStore.getState().addHeldMessage(held)
+      useReticleStore.getState().addHeldMessage({ ...held, session_id: 'session-2' })
+
+      useReticleStore.getState().removeHeldMessage('session-1', 'log-3')
+
+      const { heldMessages } = useReticleStore.getState()
+      expect(heldMessages).toHaveLength(1)
+      expect(heldMessages[0].session_id).toBe('session-2')
+    })
+  })
+
   describe('filters', () => {
     it('starts with empty filters', () => {
       const { filters } = useReticleStore.getState()
@@ -318,6 +356,16 @@ describe('parseLogMessage', () => {
 
     expect(parsed).toBeNull()
   })
+
+  it('returns null for an unanswered request report', () => {
+    const log = createMockLog({
+      message_type: 'unanswered',
+      content: '{"id":1,"method":"tools/call","direction":"in","elapsed_ms":60000,"reason":"timeout"}'
+    })
+
+    expect(isProxyReport(log)).toBe(true)
+    expect(parseLogMessage(log)).toBeNull()
+  })
 })
 
 describe('extractMethod', () => {
@@ -392,6 +440,59 @@ describe('findCorrelatedRequest', () => {
 
     expect(correlated).toBeNull()
   })
+
+  it('pairs a late response with its request, not the unanswered report', () => {
+    const request = createMockLog({
+      id: 'log-1',
+      direction: 'in',
+      content: '{"jsonrpc":"2.0","method":"tools/call","id":7}',
+      timestamp: 1000
+    })
+    const unanswered = createMockLog({
+      id: 'unanswered-2',
+      direction: 'in',
+      message_type: 'unanswered',
+      content: '{"id":7,"method":"tools/call","direction":"in","elapsed_ms":60000,"reason":"timeout"}',
+      timestamp: 60001000
+    })
+    const response = createMockLog({
+      id: 'log-3',
+      direction: 'out',
+      content: '{"jsonrpc":"2.0","result":{},"id":7}',
+      timestamp: 61000000
+    })
+
+    const correlated = findCorrelatedRequest(response, [request, unanswered, response])
+
+    expect(correlated).toEqual(request)
+  })
+
+  it('does not count a violation entry as a request', () => {
+    const request = createMockLog({
+      id: 'log-1',
+      direction: 'in',
+      content: '{"jsonrpc":"2.0","method":"tools/call","id":3}',
+      timestamp: 1000
+    })
+    const violation = createMockLog({
+      id: 'violation-2',
+      direction: 'in',
+      message_type: 'violation',
+      content: '{"kind":"duplicate_id","direction":"in","id":3,"method":"tools/call","message":"Request id 3 was already used","log_id":"log-1"}',
+      timestamp: 1500
+    })
+    const response = createMockLog({
+      id: 'log-3',
+      direction: 'out',
+      content: '{"jsonrpc":"2.0","result":{},"id":3}',
+      timestamp: 2000
+    })
+
+    expect(isProxyReport(violation)).toBe(true)
+    expect(parseLogMessage(violation)).toBeNull()
+    expect(findCorrelatedRequest(response, [request, violation, response])).toEqual(request)
+    expect(findCorrelatedRequest(response, [violation, response])).toBeNull()
+  })
 })
 
 describe('calculateLatency', () => {
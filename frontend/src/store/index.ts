@@ -1,8 +1,11 @@
 import { create } from 'zustand'
-import { LogEntry, Session, FilterOptions, ParsedMessage } from '@/types'
+import { LogEntry, Session, FilterOptions, ParsedMessage, HeldMessage, MessageType } from '@/types'
 
 const MAX_LOGS = 10000
 
+/** Entries the proxy writes about traffic rather than forwarding it */
+const PROXY_REPORTS: MessageType[] = ['fault', 'unanswered', 'violation']
+
 interface ReticleStore {
   // Connection state
   isConnected: boolean
@@ -21,6 +24,11 @@ interface ReticleStore {
   selectedLogId: string | null
   selectLog: (id: string | null) => void
 
+  // Messages held at breakpoints in CLI sessions
+  heldMessages: HeldMessage[]
+  addHeldMessage: (held: HeldMessage) => void
+  removeHeldMessage: (sessionId: string, holdId: string) => void
+
   // Sessions
   sessions: Session[]
   currentSession: Session | null
@@ -99,6 +107,24 @@ export const useReticleStore = create<ReticleStore>((set, get) => ({
   selectedLogId: null,
   selectLog: (id) => set({ selectedLogId: id }),
 
+  // Held messages (hold IDs are only unique within a session)
+  heldMessages: [],
+  addHeldMessage: (held) =>
+    set((state) => ({
+      heldMessages: [
+        ...state.heldMessages.filter(
+          (h) => h.session_id !== held.session_id || h.hold_id !== held.hold_id
+        ),
+        held,
+      ],
+    })),
+  removeHeldMessage: (sessionId, holdId) =>
+    set((state) => ({
+      heldMessages: state.heldMessages.filter(
+        (h) => h.session_id !== sessionId || h.hold_id !== holdId
+      ),
+    })),
+
   // Sessions
   sessions: [],
   currentSession: null,
@@ -202,10 +228,23 @@ export const useReticleStore = create<ReticleStore>((set, get) => ({
   },
 }))
 
+/**
+ * Whether a log entry is a report from the proxy (an injected fault, an
+ * unanswered request, or a conformance violation) rather than a message it
+ * forwarded
+ *
+ * Reports name the request they are about in their content, so they are
+ * never parsed as JSON-RPC or paired with responses.
+ */
+export function isProxyReport(log: LogEntry): boolean {
+  return log.message_type !== undefined && PROXY_REPORTS.includes(log.message_type)
+}
+
 /**
  * Parse JSON-RPC message from log entry
  */
 export function parseLogMessage(log: LogEntry): ParsedMessage | null {
+  if (isProxyReport(log)) return null
   try {
     return JSON.parse(log.content) as ParsedMessage
   } catch {
@@ -5,7 +5,7 @@
 export type Direction = 'in' | 'out'
 
 /** Type of message content */
-export type MessageType = 'jsonrpc' | 'raw' | 'stderr'
+export type MessageType = 'jsonrpc' | 'raw' | 'stderr' | 'fault' | 'unanswered' | 'violation'
 
 export interface LogEntry {
   id: string
@@ -15,9 +15,64 @@ export interface LogEntry {
   content: string // Raw JSON-RPC message or raw text
   method?: string // Extracted method name for quick filtering
   duration_micros?: number // For responses, time since request
-  message_type?: MessageType // Type of content (jsonrpc, raw, stderr)
+  message_type?: MessageType // Type of content (jsonrpc, raw, stderr, or a proxy report)
   token_count?: number // Estimated token count for this message
   server_name?: string // Server name for multi-server filtering
+  modified?: boolean // Edited at a breakpoint or rewritten by a rule before forwarding
+  original?: string // The message as originally received, if modified
+  request_log_id?: string // For progress and cancellation notifications, the request's log entry
+}
+
+/** Payload of the `breakpoint-hit` event: a message held in a CLI session */
+export interface HeldMessage {
+  session_id: string
+  /** Log entry ID of the held message */
+  hold_id: string
+  breakpoint_id: string
+  direction: Direction
+  content: string // The held message as originally received
+  method?: string
+  server_name: string
+}
+
+export type ReleaseOutcome = 'continued' | 'edited' | 'dropped' | 'timed_out'
+
+/** Payload of the `breakpoint-released` event */
+export interface BreakpointRelease {
+  session_id: string
+  hold_id: string
+  outcome: ReleaseOutcome
+}
+
+/** Content of a `fault` log entry: a fault injected by a chaos profile */
+export interface FaultDescription {
+  chaos: 'latency' | 'error' | 'kill' | 'drop' | 'truncate'
+  fault: string // Name of the fault in the chaos profile
+  method?: string // Method of the affected request
+  id?: string | number
+  delay_ms?: number
+  response?: unknown // Error response sent in place of the server's
+  kept_bytes?: number
+}
+
+/** Content of an `unanswered` entry: a request that never got a response */
+export interface UnansweredDescription {
+  id: string | number | null
+  method: string
+  direction: Direction
+  elapsed_ms: number
+  reason: 'timeout' | 'orphaned' // Timed out, or still open when the session ended
+}
+
+/** Content of a `violation` entry: a protocol rule broken by a message */
+export interface ViolationDescription {
+  kind: string // e.g. 'duplicate_id', 'invalid_arguments'
+  direction: Direction
+  id?: string | number // JSON-RPC id of the offending message
+  method?: string
+  message: string
+  errors?: { path: string; message: string }[] // Where a tool call breaks its schema
+  log_id?: string // Log entry of the offending message
 }
 
 export interface ParsedMessage {
@@ -70,6 +125,8 @@ export interface MethodTokenStats {
 /** Token statistics for a session */
 export interface SessionTokenStats {
   session_id: string
+  /** 'heuristic', 'cl100k_base', 'o200k_base', or 'file:<path>' */
+  tokenizer: string
   tokens_to_server: number
   tokens_from_server: number
   total_tokens: number
@@ -82,6 +139,17 @@ export interface SessionTokenStats {
   resource_count: number
 }
 
+/** Payload of the `token-budget-exceeded` event */
+export interface BudgetViolation {
+  session_id: string
+  message_id: string
+  kind: 'tool_definitions' | 'tool_result' | 'message' | 'session'
+  limit: number
+  tokens: number
+  action: 'warn' | 'truncate' | 'reject'
+  tokenizer: string
+}
+
 /** Global token statistics */
 export interface GlobalTokenStats {
   total_tokens: number
@@ -96,6 +164,12 @@ export interface ToolTokenInfo {
   description_tokens: number
   schema_tokens: number
   total_tokens: number
+  /** The tool's input schema, kept for diffing snapshots */
+  input_schema?: unknown
+  /** Tokens for the output schema, not part of total_tokens */
+  output_schema_tokens?: number
+  /** The tool's output schema, if it returns structured content */
+  output_schema?: unknown
 }
 
 /** Tools analysis */
@@ -139,14 +213,34 @@ export interface ServerAnalysis {
   server_name: string
   server_version: string
   protocol_version: string
+  /** Tokenizer the counts were made with */
+  tokenizer: string
   total_context_tokens: number
   tools: ToolsAnalysis
   prompts: PromptsAnalysis
   resources: ResourcesAnalysis
+  /** Problems with the tool definitions that hurt tool selection */
+  lint: LintReport
   token_breakdown: Record<string, number>
   analyzed_at: number
 }
 
+export type LintSeverity = 'info' | 'warning' | 'error'
+
+/** One problem with one tool definition */
+export interface LintFinding {
+  rule: string
+  severity: LintSeverity
+  tool: string
+  /** Where in the input schema, e.g. `properties.path` */
+  path?: string
+  message: string
+}
+
+export interface LintReport {
+  findings: LintFinding[]
+}
+
 /** Session info from storage (for listing) */
 export interface SessionInfo {
   id: string
@@ -157,6 +251,12 @@ export interface SessionInfo {
   duration_ms?: number
   transport: string
   server_name?: string
+  /** Server version from the initialize response */
+  server_version?: string
+  /** Client name from the initialize request */
+  client_name?: string
+  /** Negotiated protocol version */
+  protocol_version?: string
   tags: string[]
 }
 
@@ -165,6 +265,26 @@ export interface SessionFilter {
   server_name?: string
   tags?: string[]
   transport?: string
+  server_version?: string
+  client_name?: string
+  protocol_version?: string
+}
+
+/** Client side of the initialize handshake */
+export interface ClientInfo {
+  name: string
+  version: string
+  protocol_version?: string
+  capabilities?: Record<string, unknown>
+}
+
+/** Server side of the initialize handshake */
+export interface ServerInfo {
+  name: string
+  version: string
+  protocol_version?: string
+  capabilities?: Record<string, unknown>
+  instructions?: string
 }
 
 /** Session metadata response from backend */
@@ -178,6 +298,9 @@ export interface SessionMetadata {
   server_version?: string
   server_command?: string
   connection_type?: string
+  client_info?: ClientInfo
+  server_info?: ServerInfo
+  protocol_version?: string
   tags: string[]
   message_count: number
   duration_ms?: number
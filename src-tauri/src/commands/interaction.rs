@@ -7,7 +7,8 @@ use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, Ordering};
 use tauri::{AppHandle, Emitter, State};
 
-use crate::core::protocol::{Direction, LogEntry};
+use crate::core::protocol::{Direction, LogEntry, McpMessage};
+use crate::core::proxy::emit_violations;
 use crate::core::session_recorder::MessageDirection;
 use crate::state::AppState;
 
@@ -85,25 +86,40 @@ pub async fn send_request(
     // Send via stdio if available
     if proxy_state.is_stdio() {
         proxy_state.send_message(&request_str).await?;
+        let parsed = McpMessage::parse(&request);
+        state.request_tracker.observe(&parsed, Direction::In);
 
         // Log the sent message
         let session_id = proxy_state
             .session_id
             .clone()
             .unwrap_or_else(|| "unknown".to_string());
+        state
+            .latency_counter
+            .record_message(&session_id, &parsed, Direction::In)
+            .await;
         let log_id = format!("sent-{}", REQUEST_COUNTER.load(Ordering::SeqCst));
+        let violations = proxy_state.conformance.check(&parsed, Direction::In);
 
-        let entry = LogEntry::new(
+        let entry = LogEntry::from_message(
             log_id,
             session_id.clone(),
             Direction::In, // Direction::In means we sent it TO the server
-            request.clone(),
+            &request,
+            parsed,
         );
 
         // Emit to frontend so user sees their sent request
         if let Err(e) = app_handle.emit("log-event", &entry) {
             eprintln!("[INTERACTION] Failed to emit sent request: {e}");
         }
+        emit_violations(
+            &app_handle,
+            &session_id,
+            &proxy_state.conformance,
+            Some(&entry.id),
+            violations,
+        );
 
         // Record if recording is active
         let recorder_lock = state.recorder.lock().await;
@@ -182,19 +198,34 @@ pub async fn send_raw_message(
 
     if proxy_state.is_stdio() {
         proxy_state.send_message(&message).await?;
+        let parsed = McpMessage::parse(&json);
+        state.request_tracker.observe(&parsed, Direction::In);
 
         // Log the sent message
         let session_id = proxy_state
             .session_id
             .clone()
             .unwrap_or_else(|| "unknown".to_string());
+        state
+            .latency_counter
+            .record_message(&session_id, &parsed, Direction::In)
+            .await;
         let log_id = format!("raw-{}", REQUEST_COUNTER.fetch_add(1, Ordering::SeqCst));
+        let violations = proxy_state.conformance.check(&parsed, Direction::In);
 
-        let entry = LogEntry::new(log_id, session_id, Direction::In, json.clone());
+        let entry =
+            LogEntry::from_message(log_id, session_id.clone(), Direction::In, &json, parsed);
 
         if let Err(e) = app_handle.emit("log-event", &entry) {
             eprintln!("[INTERACTION] Failed to emit raw message: {e}");
         }
+        emit_violations(
+            &app_handle,
+            &session_id,
+            &proxy_state.conformance,
+            Some(&entry.id),
+            violations,
+        );
 
         // Record if recording is active
         let recorder_lock = state.recorder.lock().await;
@@ -313,6 +344,34 @@ pub async fn send_to_cli_session(
     Ok(())
 }
 
+/// Resume a message held at a breakpoint in a CLI session
+///
+/// Forwards the held message unchanged, forwards `message` in its place,
+/// or discards it when `drop` is set.
+#[tauri::command]
+pub async fn resume_cli_breakpoint(
+    session_id: String,
+    hold_id: String,
+    message: Option<String>,
+    drop: Option<bool>,
+) -> Result<(), String> {
+    // Validate edits before they reach the CLI
+    if let Some(ref message) = message {
+        serde_json::from_str::<serde_json::Value>(message)
+            .map_err(|e| format!("Invalid JSON: {e}"))?;
+    }
+
+    let bridge = crate::core::socket_bridge::get_socket_bridge();
+
+    if !bridge.has_session(&session_id).await {
+        return Err(format!("CLI session {session_id} not found"));
+    }
+
+    bridge
+        .resume_held_message(&session_id, &hold_id, message, drop.unwrap_or(false))
+        .await
+}
+
 /// Get common MCP methods for quick access
 #[tauri::command]
 pub fn get_mcp_methods() -> Vec<McpMethodInfo> {
@@ -321,10 +380,11 @@ pub fn get_mcp_methods() -> Vec<McpMethodInfo> {
             method: "initialize".to_string(),
             description: "Initialize the MCP connection".to_string(),
             example_params: Some(serde_json::json!({
-                "protocolVersion": "2024-11-05",
+                "protocolVersion": "2025-06-18",
                 "capabilities": {
                     "roots": { "listChanged": true },
-                    "sampling": {}
+                    "sampling": {},
+                    "elicitation": {}
                 },
                 "clientInfo": {
                     "name": "reticle",
@@ -363,6 +423,18 @@ pub fn get_mcp_methods() -> Vec<McpMethodInfo> {
                 "uri": "file:///example.txt"
             })),
         },
+        McpMethodInfo {
+            method: "resources/templates/list".to_string(),
+            description: "List resource URI templates".to_string(),
+            example_params: None,
+        },
+        McpMethodInfo {
+            method: "resources/subscribe".to_string(),
+            description: "Get notified when a resource changes".to_string(),
+            example_params: Some(serde_json::json!({
+                "uri": "file:///example.txt"
+            })),
+        },
         McpMethodInfo {
             method: "prompts/list".to_string(),
             description: "List available prompts".to_string(),
@@ -376,6 +448,21 @@ pub fn get_mcp_methods() -> Vec<McpMethodInfo> {
                 "arguments": {}
             })),
         },
+        McpMethodInfo {
+            method: "completion/complete".to_string(),
+            description: "Complete a prompt or resource template argument".to_string(),
+            example_params: Some(serde_json::json!({
+                "ref": { "type": "ref/prompt", "name": "example_prompt" },
+                "argument": { "name": "language", "value": "py" }
+            })),
+        },
+        McpMethodInfo {
+            method: "logging/setLevel".to_string(),
+            description: "Set the minimum level of log messages from the server".to_string(),
+            example_params: Some(serde_json::json!({
+                "level": "debug"
+            })),
+        },
         McpMethodInfo {
             method: "ping".to_string(),
             description: "Ping the server (keep-alive)".to_string(),
@@ -508,6 +595,21 @@ mod tests {
         assert!(ping.unwrap().example_params.is_none());
     }
 
+    #[test]
+    fn test_get_mcp_methods_examples_parse() {
+        use crate::core::protocol::mcp::{CompleteRequest, McpRequest, SetLevelRequest};
+
+        let request = |method: &str| {
+            let info = get_mcp_methods()
+                .into_iter()
+                .find(|m| m.method == method)
+                .unwrap();
+            serde_json::json!({ "method": method, "params": info.example_params })
+        };
+        assert!(CompleteRequest::from_message(&request("completion/complete")).is_some());
+        assert!(SetLevelRequest::from_message(&request("logging/setLevel")).is_some());
+    }
+
     #[test]
     fn test_mcp_method_info_serialization() {
         let info = McpMethodInfo {
@@ -0,0 +1,82 @@
+//! Latency profiling commands
+//!
+//! Tauri commands for per-session, per-method, and per-tool latency
+//! percentiles, and for the progress and cancellation timelines of
+//! long-running requests.
+
+use tauri::State;
+
+use crate::core::latency::{GlobalLatencyStats, SessionLatencyStats};
+use crate::core::progress::{session_timelines, RequestTimeline};
+use crate::state::AppState;
+
+/// Get latency statistics for a live session
+#[tauri::command]
+pub async fn get_session_latency_stats(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<SessionLatencyStats>, String> {
+    Ok(state.latency_counter.get_session_stats(&session_id).await)
+}
+
+/// Get latency statistics across all live sessions
+#[tauri::command]
+pub async fn get_global_latency_stats(
+    state: State<'_, AppState>,
+) -> Result<GlobalLatencyStats, String> {
+    Ok(state.latency_counter.get_global_stats().await)
+}
+
+/// Get latency statistics for a recorded session
+///
+/// Recordings made before latency was persisted are computed on the fly.
+#[tauri::command]
+pub async fn get_recorded_latency_stats(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<SessionLatencyStats, String> {
+    let session = state
+        .storage
+        .load_session(&session_id)
+        .await
+        .map_err(|e| format!("Failed to load session: {e}"))?;
+
+    Ok(session
+        .metadata
+        .latency
+        .clone()
+        .unwrap_or_else(|| SessionLatencyStats::from_session(&session)))
+}
+
+/// Get the requests of a recorded session that reported progress or were
+/// cancelled
+#[tauri::command]
+pub async fn get_request_timelines(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<RequestTimeline>, String> {
+    let session = state
+        .storage
+        .load_session(&session_id)
+        .await
+        .map_err(|e| format!("Failed to load session: {e}"))?;
+
+    Ok(session_timelines(&session))
+}
+
+/// Clear latency statistics for a specific session
+#[tauri::command]
+pub async fn clear_session_latency_stats(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.latency_counter.clear_session(&session_id).await;
+    Ok(())
+}
+
+/// Clear all latency statistics
+#[tauri::command]
+pub async fn clear_all_latency_stats(state: State<'_, AppState>) -> Result<(), String> {
+    state.latency_counter.clear_all().await;
+    Ok(())
+}
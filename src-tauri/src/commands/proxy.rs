@@ -1,10 +1,13 @@
+use reticle_core::rules::RulesEngine;
 use reticle_core::session_names::{create_session_name, generate_session_name};
+use std::sync::Arc;
 use tauri::{AppHandle, State};
 use tokio::process::Command;
 
 use crate::commands::demo::load_demo_data;
 use crate::core::{
-    run_proxy, start_sse_proxy, start_streamable_proxy, start_websocket_proxy, TransportConfig,
+    run_proxy, start_sse_proxy, start_streamable_proxy, start_websocket_proxy, ProxySession,
+    TransportConfig,
 };
 use crate::error::AppError;
 use crate::events::session_events::emit_session_start;
@@ -80,7 +83,7 @@ pub async fn start_proxy(
 ) -> std::result::Result<String, String> {
     // Delegate to start_proxy_v2 with stdio transport config
     let transport_config = TransportConfig::Stdio { command, args };
-    start_proxy_v2(transport_config, None, app_handle, state).await
+    start_proxy_v2(transport_config, None, None, app_handle, state).await
 }
 
 /// Tauri command to stop the proxy/demo
@@ -150,10 +153,16 @@ fn extract_hostname(url: &str) -> String {
 /// * `transport_config` - Configuration for the transport type
 /// * `session_name` - Optional human-readable name for the session. If not provided,
 ///                    a default name is generated based on the transport type.
+/// * `rules_path` - Optional TOML or JSON rules file applied to proxied messages.
+///                  Only the network transports forward through the rules.
+/// * `tokenizer` - Optional tokenizer for the session's token stats
+///                 (`cl100k_base`, `o200k_base`, `file:<tokenizer.json>`, ...)
 #[tauri::command]
 pub async fn start_proxy_v2(
     transport_config: TransportConfig,
     session_name: Option<String>,
+    rules_path: Option<String>,
+    tokenizer: Option<String>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> std::result::Result<String, String> {
@@ -165,9 +174,29 @@ pub async fn start_proxy_v2(
         return Err(AppError::ProxyAlreadyRunning.to_string());
     }
 
+    // Load rules up front so a bad file fails before anything starts
+    let rules = match rules_path {
+        Some(path) => RulesEngine::from_file(&path)
+            .map_err(|e| format!("Failed to load rules from {path}: {e}"))?,
+        None => RulesEngine::default(),
+    };
+    let rules = Arc::new(rules);
+
+    let tokenizer = match tokenizer {
+        Some(spec) => Some(super::tokens::load_tokenizer(&state, spec).await?),
+        None => None,
+    };
+
     // Generate cryptographically secure session ID
     let session_id = generate_secure_session_id();
 
+    if let Some(tokenizer) = tokenizer {
+        state
+            .token_counter
+            .set_session_tokenizer(&session_id, tokenizer)
+            .await;
+    }
+
     // Generate session name (use provided or generate default)
     let name = session_name.unwrap_or_else(|| default_session_name(&transport_config));
 
@@ -252,15 +281,28 @@ pub async fn start_proxy_v2(
 
             // Update proxy state with stdin handle for interaction
             proxy_state.start_with_stdin(session_id.clone(), child_stdin);
+            let conformance = proxy_state.conformance.clone();
             drop(proxy_state); // Release lock before spawning async task
 
             // Run stdio proxy in background
             let app_handle_clone = app_handle.clone();
             let session_id_clone = session_id.clone();
             let recorder_clone = state.recorder.clone();
+            let tracker = state.request_tracker.clone();
+            let latency = state.latency_counter.clone();
 
             tauri::async_runtime::spawn(async move {
-                match run_proxy(child, session_id_clone, app_handle_clone, recorder_clone).await {
+                match run_proxy(
+                    child,
+                    session_id_clone,
+                    app_handle_clone,
+                    recorder_clone,
+                    tracker,
+                    latency,
+                    conformance,
+                )
+                .await
+                {
                     Ok(_) => {
                         println!("Stdio proxy completed successfully");
                     }
@@ -280,13 +322,17 @@ pub async fn start_proxy_v2(
             eprintln!("[HTTP PROXY] Starting on port {proxy_port} -> {server_url}");
 
             // Start SSE proxy server (legacy transport)
-            let recorder_clone = state.recorder.clone();
             match start_sse_proxy(
                 server_url.clone(),
                 proxy_port,
-                session_id.clone(),
-                app_handle.clone(),
-                recorder_clone,
+                ProxySession {
+                    session_id: session_id.clone(),
+                    app_handle: app_handle.clone(),
+                    recorder: state.recorder.clone(),
+                    latency: state.latency_counter.clone(),
+                    rules: rules.clone(),
+                    conformance: proxy_state.conformance.clone(),
+                },
             )
             .await
             {
@@ -314,13 +360,17 @@ pub async fn start_proxy_v2(
             eprintln!("[STREAMABLE PROXY] Starting on port {proxy_port} -> {server_url}");
 
             // Start Streamable HTTP proxy server (MCP 2025-03-26)
-            let recorder_clone = state.recorder.clone();
             match start_streamable_proxy(
                 server_url.clone(),
                 proxy_port,
-                session_id.clone(),
-                app_handle.clone(),
-                recorder_clone,
+                ProxySession {
+                    session_id: session_id.clone(),
+                    app_handle: app_handle.clone(),
+                    recorder: state.recorder.clone(),
+                    latency: state.latency_counter.clone(),
+                    rules: rules.clone(),
+                    conformance: proxy_state.conformance.clone(),
+                },
             )
             .await
             {
@@ -348,13 +398,17 @@ pub async fn start_proxy_v2(
             eprintln!("[WEBSOCKET PROXY] Starting on port {proxy_port} -> {server_url}");
 
             // Start WebSocket proxy server
-            let recorder_clone = state.recorder.clone();
             match start_websocket_proxy(
                 server_url.clone(),
                 proxy_port,
-                session_id.clone(),
-                app_handle.clone(),
-                recorder_clone,
+                ProxySession {
+                    session_id: session_id.clone(),
+                    app_handle: app_handle.clone(),
+                    recorder: state.recorder.clone(),
+                    latency: state.latency_counter.clone(),
+                    rules: rules.clone(),
+                    conformance: proxy_state.conformance.clone(),
+                },
             )
             .await
             {
@@ -389,12 +443,16 @@ pub async fn start_proxy_v2(
 /// * `proxy_port` - The local port to run the proxy on
 /// * `session_name` - Optional human-readable name for the session
 /// * `use_legacy_sse` - If true, use legacy SSE transport instead of Streamable HTTP (for http:// URLs only)
+/// * `rules_path` - Optional TOML or JSON rules file applied to proxied messages
+/// * `tokenizer` - Optional tokenizer for the session's token stats
 #[tauri::command]
 pub async fn start_remote_proxy(
     server_url: String,
     proxy_port: u16,
     session_name: Option<String>,
     use_legacy_sse: Option<bool>,
+    rules_path: Option<String>,
+    tokenizer: Option<String>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> std::result::Result<String, String> {
@@ -410,7 +468,15 @@ pub async fn start_remote_proxy(
     let transport_config = create_transport_config(detected, server_url, proxy_port);
 
     // Delegate to start_proxy_v2
-    start_proxy_v2(transport_config, session_name, app_handle, state).await
+    start_proxy_v2(
+        transport_config,
+        session_name,
+        rules_path,
+        tokenizer,
+        app_handle,
+        state,
+    )
+    .await
 }
 
 #[cfg(test)]
@@ -7,6 +7,7 @@ use crate::core::session_recorder::SessionRecorder;
 use crate::security::generate_secure_session_id;
 use crate::state::AppState;
 use crate::storage::SessionInfo;
+use reticle_core::session_diff::{diff_sessions, DiffOptions, SessionDiff};
 use tauri::State;
 
 /// Start recording a new session
@@ -15,6 +16,9 @@ pub async fn start_recording(
     state: State<'_, AppState>,
     session_name: Option<String>,
 ) -> Result<String, String> {
+    // Share the proxy's tool schemas so tools listed before recording began
+    // are still checked; the proxy lock is released before the recorder's
+    let schemas = state.proxy.lock().await.conformance.tool_schemas();
     let mut recorder_state = state.recorder.lock().await;
 
     if recorder_state.is_some() {
@@ -27,7 +31,8 @@ pub async fn start_recording(
     // Get transport type from proxy state
     let transport_type = "stdio".to_string(); // TODO: Get from actual transport
 
-    let recorder = SessionRecorder::new(session_id.clone(), name, transport_type);
+    let recorder =
+        SessionRecorder::new(session_id.clone(), name, transport_type).with_tool_schemas(schemas);
 
     *recorder_state = Some(recorder);
 
@@ -158,6 +163,28 @@ pub async fn export_session(
     Ok(())
 }
 
+/// Compare two recorded sessions call by call
+#[tauri::command]
+pub async fn diff_recorded_sessions(
+    state: State<'_, AppState>,
+    session_a: String,
+    session_b: String,
+    options: Option<DiffOptions>,
+) -> Result<SessionDiff, String> {
+    let before = state
+        .storage
+        .load_session(&session_a)
+        .await
+        .map_err(|e| format!("Failed to load session: {e}"))?;
+    let after = state
+        .storage
+        .load_session(&session_b)
+        .await
+        .map_err(|e| format!("Failed to load session: {e}"))?;
+
+    Ok(diff_sessions(&before, &after, &options.unwrap_or_default()))
+}
+
 /// Export a session to CSV file
 #[tauri::command]
 pub async fn export_session_csv(
@@ -208,10 +235,24 @@ fn session_to_csv(
 
     let mut csv = String::new();
 
+    // Cancelled requests, and the requests notifications refer to
+    let timelines = reticle_core::progress::session_timelines(session);
+    let mut cancelled = std::collections::HashMap::new();
+    let mut related = std::collections::HashMap::new();
+    for timeline in &timelines {
+        for point in &timeline.progress {
+            related.insert(point.log_id.as_str(), timeline.log_id.as_str());
+        }
+        if let Some(cancellation) = &timeline.cancelled {
+            cancelled.insert(timeline.log_id.as_str(), cancellation.elapsed_ms);
+            related.insert(cancellation.log_id.as_str(), timeline.log_id.as_str());
+        }
+    }
+
     // CSV header
     writeln!(
         csv,
-        "id,timestamp,relative_time_ms,direction,method,jsonrpc_id,size_bytes,content"
+        "id,timestamp,relative_time_ms,direction,method,jsonrpc_id,size_bytes,request_log_id,cancelled_after_ms,content"
     )
     .map_err(|e| format!("Failed to write CSV header: {e}"))?;
 
@@ -233,10 +274,15 @@ fn session_to_csv(
         // Escape content for CSV (double quotes, escape existing quotes)
         let content = msg.content.to_string();
         let escaped_content = content.replace('"', "\"\"");
+        let request_log_id = related.get(msg.id.as_str()).copied().unwrap_or("");
+        let cancelled_after_ms = cancelled
+            .get(msg.id.as_str())
+            .map(|ms| ms.to_string())
+            .unwrap_or_default();
 
         writeln!(
             csv,
-            "{},{},{},{},{},{},{},\"{}\"",
+            "{},{},{},{},{},{},{},{},{},\"{}\"",
             msg.id,
             msg.timestamp_micros,
             msg.relative_time_ms,
@@ -244,6 +290,8 @@ fn session_to_csv(
             method,
             jsonrpc_id,
             msg.metadata.size_bytes,
+            request_log_id,
+            cancelled_after_ms,
             escaped_content
         )
         .map_err(|e| format!("Failed to write CSV row: {e}"))?;
@@ -263,6 +311,13 @@ fn session_to_har(
     // We pair requests with responses based on JSON-RPC ID
     let mut entries: Vec<serde_json::Value> = Vec::new();
 
+    // Progress and cancellation of long-running requests, by request
+    let timelines = reticle_core::progress::session_timelines(session);
+    let timelines: std::collections::HashMap<&str, _> = timelines
+        .iter()
+        .map(|timeline| (timeline.log_id.as_str(), timeline))
+        .collect();
+
     // Create a map of responses by JSON-RPC ID
     let mut response_map: std::collections::HashMap<
         String,
@@ -306,8 +361,21 @@ fn session_to_har(
         // Convert timestamp to ISO 8601
         let started_datetime = timestamp_to_iso8601(msg.timestamp_micros);
 
+        // Cancelled requests get the 499 nginx uses for closed requests
+        let timeline = timelines.get(msg.id.as_str());
+        let cancelled = timeline.and_then(|t| t.cancelled.as_ref());
+        let (status, status_text) = match (cancelled, response) {
+            (Some(_), _) => (499, "Cancelled"),
+            (None, Some(_)) => (200, "OK"),
+            (None, None) => (0, "No Response"),
+        };
+        let comment = match cancelled {
+            Some(c) => format!("MCP {} call, cancelled after {} ms", method, c.elapsed_ms),
+            None => format!("MCP {} call", method),
+        };
+
         // Build HAR entry
-        let entry = json!({
+        let mut entry = json!({
             "startedDateTime": started_datetime,
             "time": wait_time,
             "request": {
@@ -327,8 +395,8 @@ fn session_to_har(
                 "bodySize": msg.metadata.size_bytes
             },
             "response": {
-                "status": if response.is_some() { 200 } else { 0 },
-                "statusText": if response.is_some() { "OK" } else { "No Response" },
+                "status": status,
+                "statusText": status_text,
                 "httpVersion": "MCP/1.0",
                 "cookies": [],
                 "headers": [
@@ -349,9 +417,19 @@ fn session_to_har(
                 "wait": wait_time,
                 "receive": 0
             },
-            "comment": format!("MCP {} call", method)
+            "comment": comment
         });
 
+        // Custom fields, which HAR allows with a leading underscore
+        if let Some(timeline) = timeline {
+            if !timeline.progress.is_empty() {
+                entry["_progress"] = json!(timeline.progress);
+            }
+            if let Some(cancellation) = cancelled {
+                entry["_cancelled"] = json!(cancellation);
+            }
+        }
+
         entries.push(entry);
     }
 
@@ -520,6 +598,66 @@ fn chrono_format(session_id: &str) -> String {
 mod tests {
     use super::*;
 
+    fn cancelled_session() -> reticle_core::session_recorder::RecordedSession {
+        let message = |id: &str, at_ms: u64, direction: &str, content: serde_json::Value| {
+            serde_json::json!({
+                "id": id,
+                "timestamp_micros": at_ms * 1000,
+                "relative_time_ms": at_ms,
+                "direction": direction,
+                "metadata": {
+                    "method": content.get("method"),
+                    "jsonrpc_id": content.get("id"),
+                    "injected": false,
+                    "modified": false,
+                    "size_bytes": content.to_string().len()
+                },
+                "content": content,
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "id": "s1",
+            "name": "test",
+            "started_at": 0,
+            "ended_at": null,
+            "messages": [
+                message("m1", 0, "toserver", serde_json::json!({
+                    "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                    "params": {"name": "index", "_meta": {"progressToken": "t1"}}
+                })),
+                message("m2", 400, "toclient", serde_json::json!({
+                    "jsonrpc": "2.0", "method": "notifications/progress",
+                    "params": {"progressToken": "t1", "progress": 10}
+                })),
+                message("m3", 1200, "toserver", serde_json::json!({
+                    "jsonrpc": "2.0", "method": "notifications/cancelled",
+                    "params": {"requestId": 1}
+                })),
+            ],
+            "metadata": {"transport": "stdio", "message_count": 3}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_exports_mark_cancelled_requests() {
+        let session = cancelled_session();
+
+        let har: serde_json::Value =
+            serde_json::from_str(&session_to_har(&session).unwrap()).unwrap();
+        let entry = &har["log"]["entries"][0];
+        assert_eq!(entry["response"]["status"], 499);
+        assert_eq!(entry["_cancelled"]["elapsed_ms"], 1200);
+        assert_eq!(entry["_progress"][0]["elapsed_ms"], 400);
+
+        let csv = session_to_csv(&session).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert!(rows[1].starts_with("m1,0,0,request,tools/call,1,"));
+        assert!(rows[1].contains(",,1200,"));
+        assert!(rows[2].contains(",m1,,"));
+        assert!(rows[3].contains(",m1,,"));
+    }
+
     #[test]
     fn test_recording_status_not_recording() {
         let status = RecordingStatus {
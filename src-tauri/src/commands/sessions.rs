@@ -3,6 +3,7 @@
 //! This module provides Tauri commands for managing session tags,
 //! filtering sessions by server and tags, and multi-server support.
 
+use crate::core::session_recorder::{ClientInfo, ServerInfo};
 use crate::state::AppState;
 use crate::storage::{SessionFilter, SessionInfo};
 use tauri::State;
@@ -89,9 +90,16 @@ pub async fn get_session_metadata(
         server_name: session.metadata.server_id.as_ref().map(|s| s.name.clone()),
         server_version: session
             .metadata
-            .server_id
+            .server_info
             .as_ref()
-            .and_then(|s| s.version.clone()),
+            .map(|s| s.version.clone())
+            .or_else(|| {
+                session
+                    .metadata
+                    .server_id
+                    .as_ref()
+                    .and_then(|s| s.version.clone())
+            }),
         server_command: session
             .metadata
             .server_id
@@ -102,6 +110,9 @@ pub async fn get_session_metadata(
             .server_id
             .as_ref()
             .map(|s| s.connection_type.clone()),
+        client_info: session.metadata.client_info,
+        server_info: session.metadata.server_info,
+        protocol_version: session.metadata.protocol_version,
         tags: session.metadata.tags,
         message_count: session.metadata.message_count,
         duration_ms: session.metadata.duration_ms,
@@ -120,6 +131,15 @@ pub struct SessionMetadataResponse {
     pub server_version: Option<String>,
     pub server_command: Option<String>,
     pub connection_type: Option<String>,
+    /// Client side of the `initialize` handshake
+    #[serde(default)]
+    pub client_info: Option<ClientInfo>,
+    /// Server side of the `initialize` handshake, with its capabilities
+    #[serde(default)]
+    pub server_info: Option<ServerInfo>,
+    /// Negotiated protocol version
+    #[serde(default)]
+    pub protocol_version: Option<String>,
     pub tags: Vec<String>,
     pub message_count: usize,
     pub duration_ms: Option<u64>,
@@ -141,6 +161,9 @@ mod tests {
             server_version: Some("1.0.0".to_string()),
             server_command: Some("node server.js".to_string()),
             connection_type: Some("stdio".to_string()),
+            client_info: None,
+            server_info: None,
+            protocol_version: None,
             tags: vec!["production".to_string(), "debug".to_string()],
             message_count: 42,
             duration_ms: Some(1000),
@@ -167,6 +190,9 @@ mod tests {
             server_version: None,
             server_command: None,
             connection_type: None,
+            client_info: None,
+            server_info: None,
+            protocol_version: None,
             tags: vec![],
             message_count: 0,
             duration_ms: None,
@@ -214,6 +240,9 @@ mod tests {
             server_version: None,
             server_command: None,
             connection_type: None,
+            client_info: None,
+            server_info: None,
+            protocol_version: None,
             tags: vec!["cloned".to_string()],
             message_count: 10,
             duration_ms: Some(500),
@@ -237,6 +266,9 @@ mod tests {
             server_version: None,
             server_command: None,
             connection_type: None,
+            client_info: None,
+            server_info: None,
+            protocol_version: None,
             tags: vec![],
             message_count: 0,
             duration_ms: None,
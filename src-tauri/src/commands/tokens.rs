@@ -3,11 +3,14 @@
 //! Tauri commands for accessing token statistics and context profiling data.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use tauri::State;
 
-use crate::core::server_analyzer::{self, ServerAnalysis};
+use crate::core::server_analyzer::{ServerAnalysis, ServerAnalyzer};
 use crate::core::token_counter::{GlobalTokenStats, SessionTokenStats};
+use crate::core::tokenizer::{Tokenizer, TokenizerSpec};
 use crate::state::AppState;
+use reticle_core::transport::TransportType;
 
 /// Get token statistics for a specific session
 #[tauri::command]
@@ -49,6 +52,65 @@ pub fn estimate_tokens(text: String) -> u64 {
     crate::core::TokenCounter::estimate_tokens(&text)
 }
 
+/// Count tokens for a given text with a specific tokenizer
+#[tauri::command]
+pub async fn count_tokens(
+    text: String,
+    tokenizer: String,
+    state: State<'_, AppState>,
+) -> Result<u64, String> {
+    let tokenizer = load_tokenizer(&state, tokenizer).await?;
+    Ok(tokenizer.count_tokens(&text))
+}
+
+/// List the tokenizers available without a `tokenizer.json` file
+#[tauri::command]
+pub fn list_tokenizers() -> Vec<TokenizerSpec> {
+    TokenizerSpec::BUILTIN.to_vec()
+}
+
+/// Choose the tokenizer a session's token stats are counted with
+#[tauri::command]
+pub async fn set_session_tokenizer(
+    session_id: String,
+    tokenizer: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let tokenizer = load_tokenizer(&state, tokenizer).await?;
+    state
+        .token_counter
+        .set_session_tokenizer(&session_id, tokenizer)
+        .await;
+    Ok(())
+}
+
+/// Parse and load a tokenizer off the async runtime
+///
+/// Parsing a vocabulary or `tokenizer.json` takes long enough to stall
+/// other commands, so each spec is loaded once and kept in app state.
+pub(crate) async fn load_tokenizer(
+    state: &AppState,
+    spec: String,
+) -> Result<Arc<dyn Tokenizer>, String> {
+    let spec: TokenizerSpec = spec.parse().map_err(|e| format!("{e}"))?;
+    if let Some(tokenizer) = state.tokenizers.lock().await.get(&spec) {
+        return Ok(tokenizer.clone());
+    }
+
+    let key = spec.clone();
+    let tokenizer = tokio::task::spawn_blocking(move || spec.load())
+        .await
+        .map_err(|e| format!("Tokenizer task failed: {e}"))?
+        .map_err(|e| e.to_string())?;
+    Ok(state
+        .tokenizers
+        .lock()
+        .await
+        .entry(key)
+        .or_insert(tokenizer)
+        .clone())
+}
+
 /// Analyze an MCP server to calculate its context token overhead
 ///
 /// This connects to the server, fetches all definitions (tools, prompts, resources),
@@ -59,10 +121,43 @@ pub async fn analyze_mcp_server(
     args: Vec<String>,
     env: Option<HashMap<String, String>>,
     timeout_secs: Option<u64>,
+    tokenizer: Option<String>,
+    state: State<'_, AppState>,
 ) -> Result<ServerAnalysis, String> {
-    server_analyzer::analyze_server(command, args, env, timeout_secs)
-        .await
-        .map_err(|e| e.to_string())
+    let mut analyzer = ServerAnalyzer::new(command, args);
+    if let Some(env) = env {
+        analyzer = analyzer.with_env(env);
+    }
+    if let Some(secs) = timeout_secs {
+        analyzer = analyzer.with_timeout(secs);
+    }
+    if let Some(spec) = tokenizer {
+        analyzer = analyzer.with_tokenizer(load_tokenizer(&state, spec).await?);
+    }
+    analyzer.analyze().await.map_err(|e| e.to_string())
+}
+
+/// Analyze a running MCP server over HTTP/SSE, Streamable HTTP, or WebSocket
+///
+/// `headers` are sent with every request, e.g. for `Authorization`.
+#[tauri::command]
+pub async fn analyze_remote_mcp_server(
+    server_url: String,
+    transport: TransportType,
+    headers: Option<HashMap<String, String>>,
+    timeout_secs: Option<u64>,
+    tokenizer: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ServerAnalysis, String> {
+    let mut analyzer =
+        ServerAnalyzer::remote(server_url, transport).with_headers(headers.unwrap_or_default());
+    if let Some(secs) = timeout_secs {
+        analyzer = analyzer.with_timeout(secs);
+    }
+    if let Some(spec) = tokenizer {
+        analyzer = analyzer.with_tokenizer(load_tokenizer(&state, spec).await?);
+    }
+    analyzer.analyze().await.map_err(|e| e.to_string())
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@
 //!
 //! This module contains the core proxy implementation and transport abstractions:
 //! - `protocol`: MCP protocol message parsing and validation (from reticle-core)
+//! - `request_tracker`: Response latency and unanswered requests (from reticle-core)
 //! - `proxy`: stdio-based proxy implementation
 //! - `sse_proxy`: HTTP/SSE-based proxy implementation (legacy, protocol 2024-11-05)
 //! - `streamable_proxy`: Streamable HTTP proxy implementation (protocol 2025-03-26)
@@ -10,17 +11,24 @@
 //! - `socket_bridge`: Unix socket server for sub-10ms CLI-to-GUI communication
 //! - `session_recorder`: Session recording and replay (from reticle-core)
 //! - `token_counter`: Token counting and context profiling (from reticle-core)
-//! - `server_analyzer`: MCP server context analysis
+//! - `tokenizer`: Heuristic, BPE, and `tokenizer.json` tokenizers (from reticle-core)
+//! - `latency`: Latency histograms per session, method, and tool (from reticle-core)
+//! - `progress`: Progress and cancellation timelines (from reticle-core)
+//! - `server_analyzer`: MCP server context analysis (from reticle-core)
 
 // Re-export from reticle-core
+pub use reticle_core::latency;
+pub use reticle_core::progress;
 pub use reticle_core::protocol;
+pub use reticle_core::request_tracker;
+pub use reticle_core::server_analyzer;
 pub use reticle_core::session_recorder;
 pub use reticle_core::token_counter;
+pub use reticle_core::tokenizer;
 
 // Local proxy implementations (use Tauri event emission)
 pub mod cli_bridge;
 pub mod proxy;
-pub mod server_analyzer;
 pub mod socket_bridge;
 pub mod sse_proxy;
 pub mod streamable_proxy;
@@ -28,7 +36,8 @@ pub mod websocket_proxy;
 
 // Re-export core types and functions
 pub use cli_bridge::start_cli_bridge;
-pub use proxy::run_proxy;
+pub use proxy::{run_proxy, ProxySession};
+pub use reticle_core::latency::LatencyCounter;
 pub use reticle_core::session_recorder::SessionRecorder;
 pub use reticle_core::token_counter::TokenCounter;
 pub use reticle_core::transport::TransportConfig;
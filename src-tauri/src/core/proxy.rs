@@ -8,12 +8,27 @@ use tokio::process::Child;
 use tokio::sync::Mutex;
 use tracing::{debug, error, trace, warn};
 
-use super::protocol::{Direction, LogEntry, MessageType};
+use super::latency::LatencyCounter;
+use super::protocol::conformance::{ConformanceChecker, Violation};
+use super::protocol::{Direction, LogEntry, McpMessage, MessageType};
+use super::request_tracker::{RequestTracker, Unanswered};
 use super::session_recorder::{MessageDirection, SessionRecorder};
+use reticle_core::rules::RulesEngine;
 
 /// Global message counter for generating unique IDs
 static MESSAGE_COUNTER: AtomicU64 = AtomicU64::new(0);
 
+/// Per-session handles passed to the HTTP and WebSocket proxies
+pub struct ProxySession {
+    pub session_id: String,
+    pub app_handle: AppHandle,
+    pub recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    pub latency: Arc<LatencyCounter>,
+    pub rules: Arc<RulesEngine>,
+    /// The session's checker; its tool schemas are shared with the recorder
+    pub conformance: Arc<ConformanceChecker>,
+}
+
 /// Main proxy loop for Tauri desktop app
 ///
 /// This is the heart of Reticle. It monitors the MCP server child process
@@ -26,22 +41,28 @@ static MESSAGE_COUNTER: AtomicU64 = AtomicU64::new(0);
 /// - Non-blocking partial line handling to maintain low latency
 /// - Graceful EOF/error handling without panics
 /// - Emits events to Tauri frontend for real-time updates
+/// - Times responses against the requests sent through `tracker`, and feeds
+///   the session's latency histograms
+/// - Checks the server's side of the session with `conformance`
 pub async fn run_proxy(
     mut child: Child,
     session_id: String,
     app_handle: AppHandle,
     recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    tracker: Arc<RequestTracker>,
+    latency: Arc<LatencyCounter>,
+    conformance: Arc<ConformanceChecker>,
 ) -> Result<(), io::Error> {
     // Get child's stdio handles (only stdout and stderr for monitoring)
     let mut child_stdout = child
         .stdout
         .take()
-        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Failed to open child stdout"))?;
+        .ok_or_else(|| io::Error::other("Failed to open child stdout"))?;
 
     let mut child_stderr = child
         .stderr
         .take()
-        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Failed to open child stderr"))?;
+        .ok_or_else(|| io::Error::other("Failed to open child stderr"))?;
 
     // For Tauri desktop app, we don't have host stdin/stdout
     // We just monitor the child's stdout/stderr and emit events
@@ -53,6 +74,9 @@ pub async fn run_proxy(
     let mut child_stdout_read_buf = vec![0u8; 4096];
     let mut child_stderr_read_buf = vec![0u8; 4096];
 
+    // Periodic check for requests that never got a response
+    let mut expiry = tokio::time::interval(tracker.check_interval());
+
     eprintln!("[PROXY] Proxy loop started for session {session_id}");
     debug!("Proxy loop started for session {}", session_id);
 
@@ -69,6 +93,8 @@ pub async fn run_proxy(
                         eprintln!("[PROXY] Buffer had {} bytes remaining", stdout_buf.len());
                         debug!("Child stdout closed (EOF)");
                         // Child process finished
+                        emit_unanswered(&app_handle, &session_id, tracker.drain());
+                        finish_conformance(&app_handle, &session_id, &conformance);
                         return Ok(());
                     }
                     Ok(n) => {
@@ -94,12 +120,20 @@ pub async fn run_proxy(
                                         eprintln!("[PROXY DEBUG] Parsed JSON, emitting log-event");
                                         debug!("Out: {}", line_str);
 
-                                        let entry = LogEntry::new(
+                                        let message = McpMessage::parse(&json);
+                                        let elapsed = tracker.observe(&message, Direction::Out);
+                                        latency
+                                            .record_message(&session_id, &message, Direction::Out)
+                                            .await;
+                                        let violations = conformance.check(&message, Direction::Out);
+                                        let entry = LogEntry::from_message(
                                             id,
                                             session_id.clone(),
                                             Direction::Out,
-                                            json.clone(),
-                                        );
+                                            &json,
+                                            message,
+                                        )
+                                        .with_duration(elapsed);
 
                                         // Emit to frontend
                                         if let Err(e) = app_handle.emit("log-event", &entry) {
@@ -108,6 +142,7 @@ pub async fn run_proxy(
                                         } else {
                                             eprintln!("[PROXY DEBUG] Successfully emitted log-event");
                                         }
+                                        emit_violations(&app_handle, &session_id, &conformance, Some(&entry.id), violations);
 
                                         // Record message if recording is active
                                         let recorder_lock = recorder.lock().await;
@@ -137,6 +172,8 @@ pub async fn run_proxy(
                                         } else {
                                             eprintln!("[PROXY DEBUG] Successfully emitted raw log-event");
                                         }
+                                        let violations = conformance.check_raw(line_str, Direction::Out).into_iter().collect();
+                                        emit_violations(&app_handle, &session_id, &conformance, Some(&entry.id), violations);
                                     }
                                 }
                             }
@@ -151,6 +188,8 @@ pub async fn run_proxy(
                     Err(e) => {
                         if e.kind() != io::ErrorKind::Interrupted {
                             error!("Error reading from child stdout: {}", e);
+                            emit_unanswered(&app_handle, &session_id, tracker.drain());
+                            finish_conformance(&app_handle, &session_id, &conformance);
                             return Err(e);
                         }
                     }
@@ -197,10 +236,113 @@ pub async fn run_proxy(
                     }
                 }
             }
+
+            // Requests that have waited too long for a response
+            _ = expiry.tick() => {
+                emit_unanswered(&app_handle, &session_id, tracker.expire());
+            }
+        }
+    }
+}
+
+/// Emit a log entry for each request that never got a response
+pub(crate) fn emit_unanswered(app_handle: &AppHandle, session_id: &str, requests: Vec<Unanswered>) {
+    for request in requests {
+        warn!(
+            "No response to {} {} after {}ms",
+            request.method, request.id, request.elapsed_ms
+        );
+        let counter = MESSAGE_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let entry = LogEntry::new_unanswered(
+            format!("unanswered-{counter}"),
+            session_id.to_string(),
+            &request,
+        );
+        if let Err(e) = app_handle.emit("log-event", &entry) {
+            warn!("Failed to emit unanswered request: {}", e);
+        }
+    }
+}
+
+/// Emit a log entry for each conformance violation found in a message,
+/// then the session's updated conformance summary
+///
+/// `related` is the log id of the offending message. The HTTP proxies never
+/// see their session end, so the summary is re-sent as violations are found
+/// rather than only once at the end.
+pub(crate) fn emit_violations(
+    app_handle: &AppHandle,
+    session_id: &str,
+    conformance: &ConformanceChecker,
+    related: Option<&str>,
+    violations: Vec<Violation>,
+) {
+    if violations.is_empty() {
+        return;
+    }
+    log_violations(app_handle, session_id, related, violations);
+    emit_conformance_summary(app_handle, session_id, conformance);
+}
+
+/// Log what a session never did and emit its final conformance summary
+pub(crate) fn finish_conformance(
+    app_handle: &AppHandle,
+    session_id: &str,
+    conformance: &ConformanceChecker,
+) {
+    log_violations(app_handle, session_id, None, conformance.finish());
+    emit_conformance_summary(app_handle, session_id, conformance);
+}
+
+/// Emit a log entry for each conformance violation
+fn log_violations(
+    app_handle: &AppHandle,
+    session_id: &str,
+    related: Option<&str>,
+    violations: Vec<Violation>,
+) {
+    for violation in violations {
+        warn!("Conformance violation: {}", violation);
+        let counter = MESSAGE_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let entry = LogEntry::new_violation(
+            format!("violation-{counter}"),
+            session_id.to_string(),
+            related,
+            &violation,
+        );
+        if let Err(e) = app_handle.emit("log-event", &entry) {
+            warn!("Failed to emit conformance violation: {}", e);
         }
     }
 }
 
+/// Emit the violations found in a session so far
+fn emit_conformance_summary(
+    app_handle: &AppHandle,
+    session_id: &str,
+    conformance: &ConformanceChecker,
+) {
+    let summary = conformance.summary(session_id);
+    if let Err(e) = app_handle.emit("conformance-summary", &summary) {
+        warn!("Failed to emit conformance summary: {}", e);
+    }
+}
+
+/// Report timed-out requests for as long as the returned future is polled
+///
+/// The HTTP proxies run this alongside their server, so it stops with them.
+pub(crate) async fn watch_unanswered(
+    tracker: Arc<RequestTracker>,
+    session_id: String,
+    app_handle: AppHandle,
+) {
+    let mut expiry = tokio::time::interval(tracker.check_interval());
+    loop {
+        expiry.tick().await;
+        emit_unanswered(&app_handle, &session_id, tracker.expire());
+    }
+}
+
 /// Find the position of the first newline in the buffer
 #[inline]
 fn find_newline(buf: &BytesMut) -> Option<usize> {
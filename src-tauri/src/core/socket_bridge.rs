@@ -3,7 +3,8 @@
 //! This module provides a Unix domain socket server that CLI instances can connect to
 //! for bidirectional communication:
 //! - CLI → GUI: Session events, log entries (telemetry)
-//! - GUI → CLI: Inject commands (send messages to MCP server)
+//! - GUI → CLI: Inject commands (send messages to MCP server) and
+//!   resume commands for messages held at breakpoints
 //!
 //! Architecture:
 //! - GUI creates socket at /tmp/reticle.sock on startup
@@ -44,6 +45,43 @@ pub enum SocketEvent {
         message_type: String,
         /// Estimated token count for this message
         token_count: u64,
+        /// Whether the message was edited or rewritten before forwarding
+        #[serde(default)]
+        modified: bool,
+        /// The message as originally received, if it was modified
+        #[serde(default)]
+        original: Option<String>,
+        /// Time since the matching request, for responses
+        #[serde(default)]
+        duration_micros: Option<u64>,
+        /// The request a progress or cancellation notification refers to
+        #[serde(default)]
+        request_log_id: Option<String>,
+    },
+    /// A message is being held at a breakpoint
+    #[serde(rename = "breakpoint_hit")]
+    BreakpointHit {
+        session_id: String,
+        hold_id: String,
+        breakpoint_id: String,
+        direction: String,
+        content: String,
+        method: Option<String>,
+        server_name: String,
+    },
+    /// A held message was released or dropped
+    #[serde(rename = "breakpoint_released")]
+    BreakpointReleased {
+        session_id: String,
+        hold_id: String,
+        /// One of: continued, edited, dropped, timed_out
+        outcome: String,
+    },
+    /// A named event with an arbitrary payload, such as `token_budget_exceeded`
+    #[serde(rename = "custom")]
+    Custom {
+        event_name: String,
+        payload: serde_json::Value,
     },
 
     // === GUI → CLI events ===
@@ -55,6 +93,20 @@ pub enum SocketEvent {
         /// The JSON-RPC message to inject
         message: String,
     },
+    /// Resume a message held at a breakpoint
+    #[serde(rename = "resume_message")]
+    ResumeMessage {
+        /// Target session ID
+        session_id: String,
+        /// Hold ID reported in `breakpoint_hit`
+        hold_id: String,
+        /// Replacement message (forward the original if absent)
+        #[serde(default)]
+        message: Option<String>,
+        /// Discard the held message instead of forwarding it
+        #[serde(default)]
+        drop: bool,
+    },
 }
 
 /// Default socket path
@@ -75,13 +127,21 @@ pub fn get_socket_path() -> PathBuf {
 mod unix_impl {
     use super::*;
     use std::collections::HashMap;
-    use tauri::{AppHandle, Emitter};
+    use tauri::{AppHandle, Emitter, Manager};
     use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
     use tokio::net::unix::OwnedWriteHalf;
     use tokio::net::{UnixListener, UnixStream};
     use tokio::sync::{broadcast, Mutex, RwLock};
     use tracing::{debug, error, info, warn};
 
+    use crate::core::protocol::{Direction, McpMessage};
+    use crate::state::AppState;
+    use reticle_core::budget::BUDGET_EXCEEDED_EVENT;
+
+    /// Custom CLI events passed on to the frontend; any other name is dropped
+    /// so socket clients can't fire arbitrary events into the webview
+    const CUSTOM_EVENTS: [&str; 2] = [BUDGET_EXCEEDED_EVENT, "conformance_summary"];
+
     /// Active CLI session with its write handle for sending commands back
     #[allow(dead_code)] // Fields kept for debugging/logging context
     struct CliSession {
@@ -138,6 +198,43 @@ mod unix_impl {
 
         /// Send a message to a CLI session
         pub async fn send_to_session(&self, session_id: &str, message: &str) -> Result<(), String> {
+            let event = SocketEvent::InjectMessage {
+                session_id: session_id.to_string(),
+                message: message.to_string(),
+            };
+            self.send_event(session_id, &event).await?;
+
+            info!("Sent inject_message to session {}", session_id);
+            Ok(())
+        }
+
+        /// Resume a message held at a breakpoint in a CLI session
+        ///
+        /// `message` replaces the held message; `drop` discards it instead.
+        pub async fn resume_held_message(
+            &self,
+            session_id: &str,
+            hold_id: &str,
+            message: Option<String>,
+            drop: bool,
+        ) -> Result<(), String> {
+            let event = SocketEvent::ResumeMessage {
+                session_id: session_id.to_string(),
+                hold_id: hold_id.to_string(),
+                message,
+                drop,
+            };
+            self.send_event(session_id, &event).await?;
+
+            info!(
+                "Sent resume_message for {} to session {}",
+                hold_id, session_id
+            );
+            Ok(())
+        }
+
+        /// Write an event to a CLI session's socket
+        async fn send_event(&self, session_id: &str, event: &SocketEvent) -> Result<(), String> {
             let sessions = self.sessions.read().await;
             let session = sessions
                 .get(session_id)
@@ -147,14 +244,8 @@ mod unix_impl {
 
             let mut session_guard = session.lock().await;
 
-            // Create the inject message event
-            let event = SocketEvent::InjectMessage {
-                session_id: session_id.to_string(),
-                message: message.to_string(),
-            };
-
-            let mut json = serde_json::to_string(&event)
-                .map_err(|e| format!("Failed to serialize inject message: {e}"))?;
+            let mut json = serde_json::to_string(event)
+                .map_err(|e| format!("Failed to serialize event: {e}"))?;
             json.push('\n');
 
             session_guard
@@ -169,7 +260,6 @@ mod unix_impl {
                 .await
                 .map_err(|e| format!("Failed to flush: {e}"))?;
 
-            info!("Sent inject_message to session {}", session_id);
             Ok(())
         }
     }
@@ -377,6 +467,10 @@ mod unix_impl {
                 server_name,
                 message_type,
                 token_count,
+                modified,
+                original,
+                duration_micros,
+                request_log_id,
             } => {
                 info!(
                     "CLI log event: {} {} {} tokens={} (id={})",
@@ -387,6 +481,26 @@ mod unix_impl {
                     id
                 );
 
+                // Time CLI traffic like the GUI's own proxies
+                let json = (message_type == "jsonrpc")
+                    .then(|| serde_json::from_str::<serde_json::Value>(&content).ok())
+                    .flatten();
+                if let Some(json) = json {
+                    let direction = match direction.as_str() {
+                        "in" => Some(Direction::In),
+                        "out" => Some(Direction::Out),
+                        _ => None,
+                    };
+                    if let (Some(direction), Some(state)) =
+                        (direction, app_handle.try_state::<AppState>())
+                    {
+                        state
+                            .latency_counter
+                            .record_message(&session_id, &McpMessage::parse(&json), direction)
+                            .await;
+                    }
+                }
+
                 app_handle
                     .emit(
                         "log-event",
@@ -400,14 +514,83 @@ mod unix_impl {
                             "server_name": server_name,
                             "message_type": message_type,
                             "token_count": token_count,
+                            "modified": modified,
+                            "original": original,
+                            "duration_micros": duration_micros,
+                            "request_log_id": request_log_id,
                             "from_cli": true
                         }),
                     )
                     .map_err(|e| e.to_string())?;
             }
-            SocketEvent::InjectMessage { .. } => {
-                // This is a GUI → CLI event, shouldn't be received here
-                warn!("Received InjectMessage from CLI (unexpected)");
+            SocketEvent::BreakpointHit {
+                session_id,
+                hold_id,
+                breakpoint_id,
+                direction,
+                content,
+                method,
+                server_name,
+            } => {
+                info!(
+                    "CLI breakpoint {} hit: {} {} (hold {})",
+                    breakpoint_id,
+                    direction,
+                    method.as_deref().unwrap_or("-"),
+                    hold_id
+                );
+
+                app_handle
+                    .emit(
+                        "breakpoint-hit",
+                        serde_json::json!({
+                            "session_id": session_id,
+                            "hold_id": hold_id,
+                            "breakpoint_id": breakpoint_id,
+                            "direction": direction,
+                            "content": content,
+                            "method": method,
+                            "server_name": server_name
+                        }),
+                    )
+                    .map_err(|e| e.to_string())?;
+            }
+            SocketEvent::BreakpointReleased {
+                session_id,
+                hold_id,
+                outcome,
+            } => {
+                info!("CLI breakpoint hold {} released ({})", hold_id, outcome);
+
+                app_handle
+                    .emit(
+                        "breakpoint-released",
+                        serde_json::json!({
+                            "session_id": session_id,
+                            "hold_id": hold_id,
+                            "outcome": outcome
+                        }),
+                    )
+                    .map_err(|e| e.to_string())?;
+            }
+            SocketEvent::Custom {
+                event_name,
+                payload,
+            } => {
+                if !CUSTOM_EVENTS.contains(&event_name.as_str()) {
+                    warn!("Dropping unknown CLI event {}", event_name);
+                    return Ok(());
+                }
+                debug!("CLI event {}", event_name);
+
+                // Frontend event names are kebab-case
+                app_handle
+                    .emit(&event_name.replace('_', "-"), payload)
+                    .map_err(|e| e.to_string())?;
+            }
+            SocketEvent::InjectMessage { .. } | SocketEvent::ResumeMessage { .. } => {
+                // These are GUI → CLI events, shouldn't be received here
+                warn!("Received GUI → CLI command from CLI (unexpected)");
             }
         }
 
@@ -456,6 +639,17 @@ mod windows_impl {
         ) -> Result<(), String> {
             Err("Socket bridge is not supported on Windows".to_string())
         }
+
+        /// Resume a held message (not supported on Windows)
+        pub async fn resume_held_message(
+            &self,
+            _session_id: &str,
+            _hold_id: &str,
+            _message: Option<String>,
+            _drop: bool,
+        ) -> Result<(), String> {
+            Err("Socket bridge is not supported on Windows".to_string())
+        }
     }
 
     /// Global socket bridge state stub
@@ -16,8 +16,13 @@ use tokio::sync::Mutex;
 use tower_http::cors::CorsLayer;
 use tracing::{debug, error, info, warn};
 
-use super::protocol::{Direction, LogEntry, MessageType};
+use super::latency::LatencyCounter;
+use super::protocol::conformance::ConformanceChecker;
+use super::protocol::{Direction, LogEntry, McpMessage, MessageType};
+use super::proxy::{emit_violations, watch_unanswered, ProxySession};
+use super::request_tracker::RequestTracker;
 use super::session_recorder::{MessageDirection, SessionRecorder};
+use reticle_core::rules::RulesEngine;
 
 /// Global message counter for generating unique IDs
 static SSE_MESSAGE_COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -29,6 +34,12 @@ pub struct SseProxyState {
     pub session_id: String,
     pub app_handle: AppHandle,
     pub recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    pub latency: Arc<LatencyCounter>,
+    pub rules: Arc<RulesEngine>,
+    /// Requests sent via POST, answered over the SSE stream
+    pub tracker: Arc<RequestTracker>,
+    /// Protocol violations seen in the session
+    pub conformance: Arc<ConformanceChecker>,
 }
 
 /// Start the SSE proxy server
@@ -38,10 +49,16 @@ pub struct SseProxyState {
 pub async fn start_sse_proxy(
     server_url: String,
     proxy_port: u16,
-    session_id: String,
-    app_handle: AppHandle,
-    recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    session: ProxySession,
 ) -> Result<tokio::task::JoinHandle<()>, String> {
+    let ProxySession {
+        session_id,
+        app_handle,
+        recorder,
+        latency,
+        rules,
+        conformance,
+    } = session;
     info!(
         "Starting SSE proxy on port {} -> {}",
         proxy_port, server_url
@@ -52,7 +69,16 @@ pub async fn start_sse_proxy(
         session_id: session_id.clone(),
         app_handle: app_handle.clone(),
         recorder,
+        latency,
+        rules,
+        tracker: Arc::new(RequestTracker::default()),
+        conformance,
     };
+    let watch = watch_unanswered(
+        state.tracker.clone(),
+        session_id.clone(),
+        app_handle.clone(),
+    );
 
     // CORS layer - restricted to localhost origins for security
     let cors = CorsLayer::new()
@@ -84,9 +110,14 @@ pub async fn start_sse_proxy(
 
     // Spawn server in background
     let handle = tokio::spawn(async move {
-        if let Err(e) = axum::serve(listener, app).await {
-            error!("SSE proxy server error: {}", e);
-            eprintln!("[SSE PROXY ERROR] Server error: {e}");
+        tokio::select! {
+            result = axum::serve(listener, app) => {
+                if let Err(e) = result {
+                    error!("SSE proxy server error: {}", e);
+                    eprintln!("[SSE PROXY ERROR] Server error: {e}");
+                }
+            }
+            _ = watch => {}
         }
     });
 
@@ -132,94 +163,146 @@ async fn sse_proxy_handler(
     let session_id = state.session_id.clone();
     let app_handle = state.app_handle.clone();
     let recorder = state.recorder.clone();
-
-    let stream = response.bytes_stream().map(move |chunk_result| {
-        match chunk_result {
-            Ok(chunk) => {
-                let data = String::from_utf8_lossy(&chunk);
-
-                // Parse SSE event format
-                if let Some(json_str) = parse_sse_data(&data) {
-                    eprintln!(
-                        "[SSE PROXY DEBUG] Received data: {}",
-                        &json_str[..json_str.len().min(100)]
-                    );
-
-                    let id = generate_sse_message_id();
-
-                    // Try to parse as JSON-RPC
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                        debug!("Parsed JSON-RPC message");
-
-                        // Create log entry
-                        let entry = LogEntry::new(
-                            id,
-                            session_id.clone(),
-                            Direction::Out, // SSE is server → client (outgoing)
-                            json.clone(),
+    let rules = state.rules.clone();
+    let tracker = state.tracker.clone();
+    let latency = state.latency.clone();
+    let conformance = state.conformance.clone();
+
+    let stream = response.bytes_stream().then(move |chunk_result| {
+        let session_id = session_id.clone();
+        let app_handle = app_handle.clone();
+        let recorder = recorder.clone();
+        let rules = rules.clone();
+        let tracker = tracker.clone();
+        let latency = latency.clone();
+        let conformance = conformance.clone();
+        async move {
+            match chunk_result {
+                Ok(chunk) => {
+                    let data = String::from_utf8_lossy(&chunk);
+
+                    // Parse SSE event format
+                    if let Some(json_str) = parse_sse_data(&data) {
+                        eprintln!(
+                            "[SSE PROXY DEBUG] Received data: {}",
+                            &json_str[..json_str.len().min(100)]
                         );
 
-                        // Emit to frontend
-                        if let Err(e) = app_handle.emit("log-event", &entry) {
-                            warn!("Failed to emit log event: {}", e);
-                            eprintln!("[SSE PROXY ERROR] Failed to emit: {e}");
-                        } else {
-                            debug!("Emitted log event: {}", entry.id);
-                            eprintln!("[SSE PROXY DEBUG] Emitted log-event: {}", entry.id);
-                        }
-
-                        // Record message if recording is active
-                        let recorder_clone = recorder.clone();
-                        let json_clone = json.clone();
-                        tokio::spawn(async move {
-                            let recorder_lock = recorder_clone.lock().await;
-                            if let Some(ref rec) = *recorder_lock {
-                                if let Err(e) = rec
-                                    .record_message(json_clone, MessageDirection::ToClient)
-                                    .await
-                                {
-                                    warn!("Failed to record SSE message: {}", e);
-                                }
+                        // Apply rules before logging so the UI shows what is forwarded
+                        let Some((json_str, original)) =
+                            rules.forward_text(json_str, Direction::Out).await
+                        else {
+                            return Ok(Event::default().comment("dropped by rule"));
+                        };
+
+                        let id = generate_sse_message_id();
+
+                        // Try to parse as JSON-RPC
+                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&json_str) {
+                            debug!("Parsed JSON-RPC message");
+
+                            // Create log entry
+                            let message = McpMessage::parse(&json);
+                            let elapsed = tracker.observe(&message, Direction::Out);
+                            latency
+                                .record_message(&session_id, &message, Direction::Out)
+                                .await;
+                            let violations = conformance.check(&message, Direction::Out);
+                            let mut entry = LogEntry::from_message(
+                                id,
+                                session_id.clone(),
+                                Direction::Out, // SSE is server → client (outgoing)
+                                &json,
+                                message,
+                            )
+                            .with_duration(elapsed);
+                            if let Some(ref original) = original {
+                                entry = entry.with_original(original);
                             }
-                        });
 
-                        // Forward to client
-                        return Ok(Event::default().data(json_str));
-                    } else {
-                        // Non-JSON data - emit as raw message for debugging
-                        eprintln!(
-                            "[SSE PROXY DEBUG] Non-JSON SSE data, emitting as raw: {}",
-                            &json_str[..json_str.len().min(100)]
-                        );
-                        debug!("Non-JSON data in SSE event: {}", json_str);
-
-                        let entry = LogEntry::new_raw(
-                            id,
-                            session_id.clone(),
-                            Direction::Out,
-                            json_str.clone(),
-                            MessageType::Raw,
-                        );
+                            // Emit to frontend
+                            if let Err(e) = app_handle.emit("log-event", &entry) {
+                                warn!("Failed to emit log event: {}", e);
+                                eprintln!("[SSE PROXY ERROR] Failed to emit: {e}");
+                            } else {
+                                debug!("Emitted log event: {}", entry.id);
+                                eprintln!("[SSE PROXY DEBUG] Emitted log-event: {}", entry.id);
+                            }
+                            emit_violations(
+                                &app_handle,
+                                &session_id,
+                                &conformance,
+                                Some(&entry.id),
+                                violations,
+                            );
+
+                            // Record message if recording is active
+                            let recorder_clone = recorder.clone();
+                            let json_clone = json.clone();
+                            tokio::spawn(async move {
+                                let recorder_lock = recorder_clone.lock().await;
+                                if let Some(ref rec) = *recorder_lock {
+                                    let recorded = match original {
+                                        Some(original) => {
+                                            rec.record_modified_message(
+                                                json_clone,
+                                                original,
+                                                MessageDirection::ToClient,
+                                            )
+                                            .await
+                                        }
+                                        None => {
+                                            rec.record_message(
+                                                json_clone,
+                                                MessageDirection::ToClient,
+                                            )
+                                            .await
+                                        }
+                                    };
+                                    if let Err(e) = recorded {
+                                        warn!("Failed to record SSE message: {}", e);
+                                    }
+                                }
+                            });
 
-                        // Emit to frontend
-                        if let Err(e) = app_handle.emit("log-event", &entry) {
-                            warn!("Failed to emit raw log event: {}", e);
-                            eprintln!("[SSE PROXY ERROR] Failed to emit raw: {e}");
+                            // Forward to client
+                            return Ok(Event::default().data(json_str));
                         } else {
-                            eprintln!("[SSE PROXY DEBUG] Emitted raw log-event: {}", entry.id);
-                        }
+                            // Non-JSON data - emit as raw message for debugging
+                            eprintln!(
+                                "[SSE PROXY DEBUG] Non-JSON SSE data, emitting as raw: {}",
+                                &json_str[..json_str.len().min(100)]
+                            );
+                            debug!("Non-JSON data in SSE event: {}", json_str);
+
+                            let entry = LogEntry::new_raw(
+                                id,
+                                session_id.clone(),
+                                Direction::Out,
+                                json_str.clone(),
+                                MessageType::Raw,
+                            );
+
+                            // Emit to frontend
+                            if let Err(e) = app_handle.emit("log-event", &entry) {
+                                warn!("Failed to emit raw log event: {}", e);
+                                eprintln!("[SSE PROXY ERROR] Failed to emit raw: {e}");
+                            } else {
+                                eprintln!("[SSE PROXY DEBUG] Emitted raw log-event: {}", entry.id);
+                            }
 
-                        // Forward to client
-                        return Ok(Event::default().data(json_str));
+                            // Forward to client
+                            return Ok(Event::default().data(json_str));
+                        }
                     }
-                }
 
-                // Forward raw event if not JSON-RPC (no data: prefix found)
-                Ok(Event::default().data(&data))
-            }
-            Err(e) => {
-                error!("Error reading SSE stream: {}", e);
-                Ok(Event::default().comment(format!("error: {e}")))
+                    // Forward raw event if not JSON-RPC (no data: prefix found)
+                    Ok(Event::default().data(&data))
+                }
+                Err(e) => {
+                    error!("Error reading SSE stream: {}", e);
+                    Ok(Event::default().comment(format!("error: {e}")))
+                }
             }
         }
     });
@@ -251,7 +334,7 @@ pub struct SendMessageResponse {
 /// and returns the response.
 async fn send_message_handler(
     State(state): State<SseProxyState>,
-    Json(request): Json<SendMessageRequest>,
+    Json(mut request): Json<SendMessageRequest>,
 ) -> Result<Json<SendMessageResponse>, (StatusCode, String)> {
     debug!("Sending message to MCP server: {:?}", request.message);
     eprintln!(
@@ -259,28 +342,72 @@ async fn send_message_handler(
         serde_json::to_string(&request.message).unwrap_or_default()
     );
 
+    // Apply rules before logging so the UI shows what is forwarded
+    let outcome = state.rules.apply(request.message, Direction::In);
+    outcome.wait().await;
+    let Some(message) = outcome.message else {
+        info!("Rules {:?} dropped a sent message", outcome.applied);
+        return Ok(Json(SendMessageResponse {
+            success: true,
+            response: None,
+            error: None,
+        }));
+    };
+    request.message = message;
+    let original = outcome.original;
+
     // Log the outgoing request
     let id = generate_sse_message_id();
-    let entry = LogEntry::new(
+    let message = McpMessage::parse(&request.message);
+    let elapsed = state.tracker.observe(&message, Direction::In);
+    state
+        .latency
+        .record_message(&state.session_id, &message, Direction::In)
+        .await;
+    let violations = state.conformance.check(&message, Direction::In);
+    let mut entry = LogEntry::from_message(
         id.clone(),
         state.session_id.clone(),
         Direction::In, // Direction::In means we sent it TO the server
-        request.message.clone(),
-    );
+        &request.message,
+        message,
+    )
+    .with_duration(elapsed);
+    if let Some(ref original) = original {
+        entry = entry.with_original(original);
+    }
 
     // Emit to frontend so user sees their sent request
     if let Err(e) = state.app_handle.emit("log-event", &entry) {
         warn!("Failed to emit sent request: {}", e);
     }
+    emit_violations(
+        &state.app_handle,
+        &state.session_id,
+        &state.conformance,
+        Some(&entry.id),
+        violations,
+    );
 
     // Record if recording is active
     {
         let recorder_lock = state.recorder.lock().await;
         if let Some(ref rec) = *recorder_lock {
-            if let Err(e) = rec
-                .record_message(request.message.clone(), MessageDirection::ToServer)
-                .await
-            {
+            let recorded = match original {
+                Some(original) => {
+                    rec.record_modified_message(
+                        request.message.clone(),
+                        original,
+                        MessageDirection::ToServer,
+                    )
+                    .await
+                }
+                None => {
+                    rec.record_message(request.message.clone(), MessageDirection::ToServer)
+                        .await
+                }
+            };
+            if let Err(e) = recorded {
                 warn!("Failed to record sent message: {}", e);
             }
         }
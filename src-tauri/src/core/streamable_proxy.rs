@@ -31,8 +31,13 @@ use tokio::sync::{Mutex, RwLock};
 use tower_http::cors::CorsLayer;
 use tracing::{debug, error, info, warn};
 
-use super::protocol::{Direction, LogEntry};
+use super::latency::LatencyCounter;
+use super::protocol::conformance::ConformanceChecker;
+use super::protocol::{Direction, LogEntry, McpMessage};
+use super::proxy::{emit_violations, watch_unanswered, ProxySession};
+use super::request_tracker::RequestTracker;
 use super::session_recorder::{MessageDirection, SessionRecorder};
+use reticle_core::rules::RulesEngine;
 
 /// Global message counter for generating unique IDs
 static STREAMABLE_MESSAGE_COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -51,12 +56,20 @@ pub struct StreamableProxyState {
     pub app_handle: AppHandle,
     /// Session recorder for capturing messages
     pub recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    /// Latency histograms for the session
+    pub latency: Arc<LatencyCounter>,
     /// HTTP client for making requests to upstream server
     pub client: Client,
     /// Active MCP session ID from upstream server (if assigned)
     pub mcp_session_id: Arc<RwLock<Option<String>>>,
     /// SSE event ID counter for resumability
     pub event_counter: Arc<AtomicU64>,
+    /// Rules that rewrite, drop, or delay messages in flight
+    pub rules: Arc<RulesEngine>,
+    /// In-flight requests, for response latency
+    pub tracker: Arc<RequestTracker>,
+    /// Protocol violations seen in the session
+    pub conformance: Arc<ConformanceChecker>,
 }
 
 /// Response wrapper for JSON-RPC messages
@@ -83,10 +96,16 @@ pub struct JsonRpcMessage {
 pub async fn start_streamable_proxy(
     server_url: String,
     proxy_port: u16,
-    session_id: String,
-    app_handle: AppHandle,
-    recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    session: ProxySession,
 ) -> Result<tokio::task::JoinHandle<()>, String> {
+    let ProxySession {
+        session_id,
+        app_handle,
+        recorder,
+        latency,
+        rules,
+        conformance,
+    } = session;
     info!(
         "Starting Streamable HTTP proxy on port {} -> {}",
         proxy_port, server_url
@@ -101,10 +120,19 @@ pub async fn start_streamable_proxy(
         session_id: session_id.clone(),
         app_handle: app_handle.clone(),
         recorder,
+        latency,
         client,
         mcp_session_id: Arc::new(RwLock::new(None)),
         event_counter: Arc::new(AtomicU64::new(0)),
+        rules,
+        tracker: Arc::new(RequestTracker::default()),
+        conformance,
     };
+    let watch = watch_unanswered(
+        state.tracker.clone(),
+        session_id.clone(),
+        app_handle.clone(),
+    );
 
     // CORS layer - restricted to localhost origins for security
     let cors = CorsLayer::new()
@@ -141,9 +169,14 @@ pub async fn start_streamable_proxy(
 
     // Spawn server in background
     let handle = tokio::spawn(async move {
-        if let Err(e) = axum::serve(listener, app).await {
-            error!("Streamable HTTP proxy server error: {}", e);
-            eprintln!("[STREAMABLE PROXY ERROR] Server error: {e}");
+        tokio::select! {
+            result = axum::serve(listener, app) => {
+                if let Err(e) = result {
+                    error!("Streamable HTTP proxy server error: {}", e);
+                    eprintln!("[STREAMABLE PROXY ERROR] Server error: {e}");
+                }
+            }
+            _ = watch => {}
         }
     });
 
@@ -198,24 +231,71 @@ async fn handle_post(
         }
     };
 
+    // Apply rules to each message; a batch keeps whatever was not dropped
+    let is_batch = body.trim_start().starts_with('[');
+    let mut forwarded = Vec::with_capacity(messages.len());
+    let mut changed = false;
+    for msg in messages {
+        let outcome = state.rules.apply(msg, Direction::In);
+        outcome.wait().await;
+        changed |= outcome.original.is_some();
+        if let Some(message) = outcome.message {
+            forwarded.push((message, outcome.original));
+        }
+    }
+    if changed && forwarded.is_empty() {
+        eprintln!("[STREAMABLE PROXY] All messages dropped by rules");
+        return StatusCode::ACCEPTED.into_response();
+    }
+    let body = if !changed {
+        body
+    } else if is_batch {
+        let batch: Vec<_> = forwarded.iter().map(|(msg, _)| msg).collect();
+        serde_json::to_string(&batch).unwrap_or_default()
+    } else {
+        serde_json::to_string(&forwarded[0].0).unwrap_or_default()
+    };
+
     // Log incoming messages
-    for msg in &messages {
+    for (msg, original) in forwarded {
         let id = generate_message_id();
-        let entry = LogEntry::new(id, state.session_id.clone(), Direction::In, msg.clone());
+        let message = McpMessage::parse(&msg);
+        let elapsed = state.tracker.observe(&message, Direction::In);
+        state
+            .latency
+            .record_message(&state.session_id, &message, Direction::In)
+            .await;
+        let violations = state.conformance.check(&message, Direction::In);
+        let mut entry =
+            LogEntry::from_message(id, state.session_id.clone(), Direction::In, &msg, message)
+                .with_duration(elapsed);
+        if let Some(ref original) = original {
+            entry = entry.with_original(original);
+        }
         if let Err(e) = state.app_handle.emit("log-event", &entry) {
             warn!("Failed to emit log event: {}", e);
         }
+        emit_violations(
+            &state.app_handle,
+            &state.session_id,
+            &state.conformance,
+            Some(&entry.id),
+            violations,
+        );
 
         // Record if recording is active
         let recorder_clone = state.recorder.clone();
-        let msg_clone = msg.clone();
         tokio::spawn(async move {
             let recorder_lock = recorder_clone.lock().await;
             if let Some(ref rec) = *recorder_lock {
-                if let Err(e) = rec
-                    .record_message(msg_clone, MessageDirection::ToServer)
-                    .await
-                {
+                let recorded = match original {
+                    Some(original) => {
+                        rec.record_modified_message(msg, original, MessageDirection::ToServer)
+                            .await
+                    }
+                    None => rec.record_message(msg, MessageDirection::ToServer).await,
+                };
+                if let Err(e) = recorded {
                     warn!("Failed to record message: {}", e);
                 }
             }
@@ -283,9 +363,16 @@ async fn handle_post(
                             &text[..text.len().min(200)]
                         );
 
+                        // Apply rules before logging so the UI shows what is forwarded
+                        let Some((text, original)) =
+                            state.rules.forward_text(text, Direction::Out).await
+                        else {
+                            return StatusCode::ACCEPTED.into_response();
+                        };
+
                         // Log response
                         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                            log_outgoing_message(&state, json.clone()).await;
+                            log_outgoing_message(&state, json, original).await;
                         }
 
                         let axum_status =
@@ -362,57 +449,99 @@ async fn handle_sse_response(state: StreamableProxyState, response: reqwest::Res
     let app_handle = state.app_handle.clone();
     let recorder = state.recorder.clone();
     let event_counter = state.event_counter.clone();
+    let rules = state.rules.clone();
+    let tracker = state.tracker.clone();
+    let latency = state.latency.clone();
+    let conformance = state.conformance.clone();
+
+    let stream = response.bytes_stream().then(move |chunk_result| {
+        let session_id = session_id.clone();
+        let app_handle = app_handle.clone();
+        let recorder = recorder.clone();
+        let event_counter = event_counter.clone();
+        let rules = rules.clone();
+        let tracker = tracker.clone();
+        let latency = latency.clone();
+        let conformance = conformance.clone();
+        async move {
+            match chunk_result {
+                Ok(chunk) => {
+                    let data = String::from_utf8_lossy(&chunk);
+
+                    // Parse SSE events, applying rules to each message
+                    let sse = rules.apply_sse_chunk(&data, Direction::Out);
+                    if !sse.delay.is_zero() {
+                        tokio::time::sleep(sse.delay).await;
+                    }
 
-    let stream = response.bytes_stream().map(move |chunk_result| {
-        match chunk_result {
-            Ok(chunk) => {
-                let data = String::from_utf8_lossy(&chunk);
+                    for forwarded in sse.messages {
+                        let _event_id = event_counter.fetch_add(1, Ordering::SeqCst);
 
-                // Parse SSE events
-                for line in data.lines() {
-                    if let Some(json_str) = line.strip_prefix("data: ") {
-                        if json_str.trim().is_empty() {
-                            continue;
+                        let msg_id = generate_message_id();
+                        let message = McpMessage::parse(&forwarded.message);
+                        let elapsed = tracker.observe(&message, Direction::Out);
+                        latency
+                            .record_message(&session_id, &message, Direction::Out)
+                            .await;
+                        let violations = conformance.check(&message, Direction::Out);
+                        let mut entry = LogEntry::from_message(
+                            msg_id,
+                            session_id.clone(),
+                            Direction::Out,
+                            &forwarded.message,
+                            message,
+                        )
+                        .with_duration(elapsed);
+                        if let Some(ref original) = forwarded.original {
+                            entry = entry.with_original(original);
                         }
 
-                        let _event_id = event_counter.fetch_add(1, Ordering::SeqCst);
-
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(json_str) {
-                            let msg_id = generate_message_id();
-                            let entry = LogEntry::new(
-                                msg_id,
-                                session_id.clone(),
-                                Direction::Out,
-                                json.clone(),
-                            );
-
-                            if let Err(e) = app_handle.emit("log-event", &entry) {
-                                warn!("Failed to emit log event: {}", e);
-                            }
+                        if let Err(e) = app_handle.emit("log-event", &entry) {
+                            warn!("Failed to emit log event: {}", e);
+                        }
+                        emit_violations(
+                            &app_handle,
+                            &session_id,
+                            &conformance,
+                            Some(&entry.id),
+                            violations,
+                        );
 
-                            // Record message
-                            let recorder_clone = recorder.clone();
-                            let json_clone = json.clone();
-                            tokio::spawn(async move {
-                                let recorder_lock = recorder_clone.lock().await;
-                                if let Some(ref rec) = *recorder_lock {
-                                    if let Err(e) = rec
-                                        .record_message(json_clone, MessageDirection::ToClient)
+                        // Record message
+                        let recorder_clone = recorder.clone();
+                        tokio::spawn(async move {
+                            let recorder_lock = recorder_clone.lock().await;
+                            if let Some(ref rec) = *recorder_lock {
+                                let recorded = match forwarded.original {
+                                    Some(original) => {
+                                        rec.record_modified_message(
+                                            forwarded.message,
+                                            original,
+                                            MessageDirection::ToClient,
+                                        )
                                         .await
-                                    {
-                                        warn!("Failed to record message: {}", e);
                                     }
+                                    None => {
+                                        rec.record_message(
+                                            forwarded.message,
+                                            MessageDirection::ToClient,
+                                        )
+                                        .await
+                                    }
+                                };
+                                if let Err(e) = recorded {
+                                    warn!("Failed to record message: {}", e);
                                 }
-                            });
-                        }
+                            }
+                        });
                     }
-                }
 
-                Ok::<_, std::convert::Infallible>(Event::default().data(&data))
-            }
-            Err(e) => {
-                error!("Error reading SSE stream: {}", e);
-                Ok(Event::default().comment(format!("error: {e}")))
+                    Ok::<_, std::convert::Infallible>(Event::default().data(&sse.text))
+                }
+                Err(e) => {
+                    error!("Error reading SSE stream: {}", e);
+                    Ok(Event::default().comment(format!("error: {e}")))
+                }
             }
         }
     });
@@ -565,19 +694,49 @@ async fn handle_delete(
     }
 }
 
-/// Log an outgoing message
-async fn log_outgoing_message(state: &StreamableProxyState, json: serde_json::Value) {
+/// Log an outgoing message, with the original if a rule changed it
+async fn log_outgoing_message(
+    state: &StreamableProxyState,
+    json: serde_json::Value,
+    original: Option<serde_json::Value>,
+) {
     let id = generate_message_id();
-    let entry = LogEntry::new(id, state.session_id.clone(), Direction::Out, json.clone());
+    let message = McpMessage::parse(&json);
+    let elapsed = state.tracker.observe(&message, Direction::Out);
+    state
+        .latency
+        .record_message(&state.session_id, &message, Direction::Out)
+        .await;
+    let violations = state.conformance.check(&message, Direction::Out);
+    let mut entry =
+        LogEntry::from_message(id, state.session_id.clone(), Direction::Out, &json, message)
+            .with_duration(elapsed);
+    if let Some(ref original) = original {
+        entry = entry.with_original(original);
+    }
 
     if let Err(e) = state.app_handle.emit("log-event", &entry) {
         warn!("Failed to emit log event: {}", e);
     }
+    emit_violations(
+        &state.app_handle,
+        &state.session_id,
+        &state.conformance,
+        Some(&entry.id),
+        violations,
+    );
 
     // Record if recording is active
     let recorder_lock = state.recorder.lock().await;
     if let Some(ref rec) = *recorder_lock {
-        if let Err(e) = rec.record_message(json, MessageDirection::ToClient).await {
+        let recorded = match original {
+            Some(original) => {
+                rec.record_modified_message(json, original, MessageDirection::ToClient)
+                    .await
+            }
+            None => rec.record_message(json, MessageDirection::ToClient).await,
+        };
+        if let Err(e) = recorded {
             warn!("Failed to record message: {}", e);
         }
     }
@@ -30,8 +30,16 @@ use tokio_tungstenite::{connect_async, tungstenite::Message as TungsteniteMessag
 use tower_http::cors::CorsLayer;
 use tracing::{debug, error, info, warn};
 
-use super::protocol::{Direction, LogEntry};
+use super::latency::LatencyCounter;
+use super::protocol::conformance::ConformanceChecker;
+use super::protocol::{Direction, LogEntry, McpMessage};
+use super::proxy::{
+    emit_unanswered, emit_violations, finish_conformance, watch_unanswered, ProxySession,
+};
+use super::request_tracker::RequestTracker;
 use super::session_recorder::{MessageDirection, SessionRecorder};
+use reticle_core::rules::RulesEngine;
+use reticle_core::tool_schemas::ToolSchemas;
 
 /// Global message counter for generating unique IDs
 static WS_MESSAGE_COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -47,8 +55,14 @@ pub struct WebSocketProxyState {
     pub app_handle: AppHandle,
     /// Session recorder for capturing messages
     pub recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    /// Latency histograms for the session
+    pub latency: Arc<LatencyCounter>,
     /// Connection status
     pub is_connected: Arc<RwLock<bool>>,
+    /// Rules that rewrite, drop, or delay messages in flight
+    pub rules: Arc<RulesEngine>,
+    /// Tool schemas shared by every connection's conformance checker
+    pub schemas: Arc<ToolSchemas>,
 }
 
 /// Start the WebSocket proxy server
@@ -58,10 +72,16 @@ pub struct WebSocketProxyState {
 pub async fn start_websocket_proxy(
     server_url: String,
     proxy_port: u16,
-    session_id: String,
-    app_handle: AppHandle,
-    recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    session: ProxySession,
 ) -> Result<tokio::task::JoinHandle<()>, String> {
+    let ProxySession {
+        session_id,
+        app_handle,
+        recorder,
+        latency,
+        rules,
+        conformance,
+    } = session;
     info!(
         "Starting WebSocket proxy on port {} -> {}",
         proxy_port, server_url
@@ -72,7 +92,10 @@ pub async fn start_websocket_proxy(
         session_id: session_id.clone(),
         app_handle: app_handle.clone(),
         recorder,
+        latency,
         is_connected: Arc::new(RwLock::new(false)),
+        rules,
+        schemas: conformance.tool_schemas(),
     };
 
     // CORS layer - restricted to localhost origins for security
@@ -187,11 +210,20 @@ async fn handle_websocket(client_socket: WebSocket, state: WebSocketProxyState)
     let session_id = state.session_id.clone();
     let app_handle = state.app_handle.clone();
     let recorder = state.recorder.clone();
+    let rules = state.rules.clone();
+    // Request ids and the MCP lifecycle are scoped to this connection; tool
+    // schemas are shared across the session
+    let tracker = Arc::new(RequestTracker::default());
+    let conformance = Arc::new(ConformanceChecker::with_tool_schemas(state.schemas.clone()));
 
     // Spawn task to read from client and send to upstream
     let session_id_clone = session_id.clone();
     let app_handle_clone = app_handle.clone();
     let recorder_clone = recorder.clone();
+    let rules_clone = rules.clone();
+    let tracker_clone = tracker.clone();
+    let latency_clone = state.latency.clone();
+    let conformance_clone = conformance.clone();
     let client_read_handle = tokio::spawn(async move {
         while let Some(msg_result) = client_read.next().await {
             match msg_result {
@@ -201,26 +233,60 @@ async fn handle_websocket(client_socket: WebSocket, state: WebSocketProxyState)
                         &text[..text.len().min(100)]
                     );
 
+                    // Apply rules before logging so the UI shows what is forwarded
+                    let Some((text, original)) =
+                        rules_clone.forward_text(text, Direction::In).await
+                    else {
+                        continue;
+                    };
+
                     // Log the message
                     if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
                         let id = generate_message_id();
-                        let entry = LogEntry::new(
+                        let message = McpMessage::parse(&json);
+                        let elapsed = tracker_clone.observe(&message, Direction::In);
+                        latency_clone
+                            .record_message(&session_id_clone, &message, Direction::In)
+                            .await;
+                        let violations = conformance_clone.check(&message, Direction::In);
+                        let mut entry = LogEntry::from_message(
                             id,
                             session_id_clone.clone(),
                             Direction::In,
-                            json.clone(),
-                        );
+                            &json,
+                            message,
+                        )
+                        .with_duration(elapsed);
+                        if let Some(ref original) = original {
+                            entry = entry.with_original(original);
+                        }
 
                         if let Err(e) = app_handle_clone.emit("log-event", &entry) {
                             warn!("Failed to emit log event: {}", e);
                         }
+                        emit_violations(
+                            &app_handle_clone,
+                            &session_id_clone,
+                            &conformance_clone,
+                            Some(&entry.id),
+                            violations,
+                        );
 
                         // Record message
                         let recorder_lock = recorder_clone.lock().await;
                         if let Some(ref rec) = *recorder_lock {
-                            if let Err(e) =
-                                rec.record_message(json, MessageDirection::ToServer).await
-                            {
+                            let recorded = match original {
+                                Some(original) => {
+                                    rec.record_modified_message(
+                                        json,
+                                        original,
+                                        MessageDirection::ToServer,
+                                    )
+                                    .await
+                                }
+                                None => rec.record_message(json, MessageDirection::ToServer).await,
+                            };
+                            if let Err(e) = recorded {
                                 warn!("Failed to record message: {}", e);
                             }
                         }
@@ -239,18 +305,42 @@ async fn handle_websocket(client_socket: WebSocket, state: WebSocketProxyState)
                             &text[..text.len().min(100)]
                         );
 
+                        let Some((text, original)) =
+                            rules_clone.forward_text(text, Direction::In).await
+                        else {
+                            continue;
+                        };
+
                         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
                             let id = generate_message_id();
-                            let entry = LogEntry::new(
+                            let message = McpMessage::parse(&json);
+                            let elapsed = tracker_clone.observe(&message, Direction::In);
+                            latency_clone
+                                .record_message(&session_id_clone, &message, Direction::In)
+                                .await;
+                            let violations = conformance_clone.check(&message, Direction::In);
+                            let mut entry = LogEntry::from_message(
                                 id,
                                 session_id_clone.clone(),
                                 Direction::In,
-                                json.clone(),
-                            );
+                                &json,
+                                message,
+                            )
+                            .with_duration(elapsed);
+                            if let Some(ref original) = original {
+                                entry = entry.with_original(original);
+                            }
 
                             if let Err(e) = app_handle_clone.emit("log-event", &entry) {
                                 warn!("Failed to emit log event: {}", e);
                             }
+                            emit_violations(
+                                &app_handle_clone,
+                                &session_id_clone,
+                                &conformance_clone,
+                                Some(&entry.id),
+                                violations,
+                            );
                         }
 
                         if client_to_upstream_tx.send(text).await.is_err() {
@@ -297,6 +387,10 @@ async fn handle_websocket(client_socket: WebSocket, state: WebSocketProxyState)
     let session_id_clone = session_id.clone();
     let app_handle_clone = app_handle.clone();
     let recorder_clone = recorder.clone();
+    let rules_clone = rules.clone();
+    let tracker_clone = tracker.clone();
+    let latency_clone = state.latency.clone();
+    let conformance_clone = conformance.clone();
     let upstream_read_handle = tokio::spawn(async move {
         while let Some(msg_result) = upstream_read.next().await {
             match msg_result {
@@ -306,26 +400,60 @@ async fn handle_websocket(client_socket: WebSocket, state: WebSocketProxyState)
                         &text[..text.len().min(100)]
                     );
 
+                    // Apply rules before logging so the UI shows what is forwarded
+                    let Some((text, original)) =
+                        rules_clone.forward_text(text, Direction::Out).await
+                    else {
+                        continue;
+                    };
+
                     // Log the message
                     if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
                         let id = generate_message_id();
-                        let entry = LogEntry::new(
+                        let message = McpMessage::parse(&json);
+                        let elapsed = tracker_clone.observe(&message, Direction::Out);
+                        latency_clone
+                            .record_message(&session_id_clone, &message, Direction::Out)
+                            .await;
+                        let violations = conformance_clone.check(&message, Direction::Out);
+                        let mut entry = LogEntry::from_message(
                             id,
                             session_id_clone.clone(),
                             Direction::Out,
-                            json.clone(),
-                        );
+                            &json,
+                            message,
+                        )
+                        .with_duration(elapsed);
+                        if let Some(ref original) = original {
+                            entry = entry.with_original(original);
+                        }
 
                         if let Err(e) = app_handle_clone.emit("log-event", &entry) {
                             warn!("Failed to emit log event: {}", e);
                         }
+                        emit_violations(
+                            &app_handle_clone,
+                            &session_id_clone,
+                            &conformance_clone,
+                            Some(&entry.id),
+                            violations,
+                        );
 
                         // Record message
                         let recorder_lock = recorder_clone.lock().await;
                         if let Some(ref rec) = *recorder_lock {
-                            if let Err(e) =
-                                rec.record_message(json, MessageDirection::ToClient).await
-                            {
+                            let recorded = match original {
+                                Some(original) => {
+                                    rec.record_modified_message(
+                                        json,
+                                        original,
+                                        MessageDirection::ToClient,
+                                    )
+                                    .await
+                                }
+                                None => rec.record_message(json, MessageDirection::ToClient).await,
+                            };
+                            if let Err(e) = recorded {
                                 warn!("Failed to record message: {}", e);
                             }
                         }
@@ -343,18 +471,42 @@ async fn handle_websocket(client_socket: WebSocket, state: WebSocketProxyState)
                             &text[..text.len().min(100)]
                         );
 
+                        let Some((text, original)) =
+                            rules_clone.forward_text(text, Direction::Out).await
+                        else {
+                            continue;
+                        };
+
                         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
                             let id = generate_message_id();
-                            let entry = LogEntry::new(
+                            let message = McpMessage::parse(&json);
+                            let elapsed = tracker_clone.observe(&message, Direction::Out);
+                            latency_clone
+                                .record_message(&session_id_clone, &message, Direction::Out)
+                                .await;
+                            let violations = conformance_clone.check(&message, Direction::Out);
+                            let mut entry = LogEntry::from_message(
                                 id,
                                 session_id_clone.clone(),
                                 Direction::Out,
-                                json.clone(),
-                            );
+                                &json,
+                                message,
+                            )
+                            .with_duration(elapsed);
+                            if let Some(ref original) = original {
+                                entry = entry.with_original(original);
+                            }
 
                             if let Err(e) = app_handle_clone.emit("log-event", &entry) {
                                 warn!("Failed to emit log event: {}", e);
                             }
+                            emit_violations(
+                                &app_handle_clone,
+                                &session_id_clone,
+                                &conformance_clone,
+                                Some(&entry.id),
+                                violations,
+                            );
                         }
 
                         if upstream_to_client_tx.send(text).await.is_err() {
@@ -436,7 +588,10 @@ async fn handle_websocket(client_socket: WebSocket, state: WebSocketProxyState)
         _ = client_write_handle => {
             eprintln!("[WEBSOCKET PROXY] Client write task ended");
         }
+        _ = watch_unanswered(tracker.clone(), session_id.clone(), app_handle.clone()) => {}
     }
+    emit_unanswered(&app_handle, &session_id, tracker.drain());
+    finish_conformance(&app_handle, &session_id, &conformance);
 
     // Update connection status
     {
@@ -25,13 +25,16 @@ mod state;
 mod storage;
 
 use commands::{
-    add_recording_tag, add_session_tags, analyze_mcp_server, can_interact, clear_all_token_stats,
-    clear_session_token_stats, delete_recorded_session, estimate_tokens, export_session,
-    export_session_csv, export_session_har, get_all_server_names, get_all_tags,
-    get_cli_bridge_status, get_cli_sessions, get_global_token_stats, get_mcp_methods,
-    get_recording_status, get_recording_tags, get_session_metadata, get_session_token_stats,
-    list_recorded_sessions, list_sessions_filtered, load_recorded_session, remove_recording_tag,
-    remove_session_tags, send_raw_message, send_request, send_to_cli_session,
+    add_recording_tag, add_session_tags, analyze_mcp_server, analyze_remote_mcp_server,
+    can_interact, clear_all_latency_stats, clear_all_token_stats, clear_session_latency_stats,
+    clear_session_token_stats, count_tokens, delete_recorded_session, diff_recorded_sessions,
+    estimate_tokens, export_session, export_session_csv, export_session_har, get_all_server_names,
+    get_all_tags, get_cli_bridge_status, get_cli_sessions, get_global_latency_stats,
+    get_global_token_stats, get_mcp_methods, get_recorded_latency_stats, get_recording_status,
+    get_recording_tags, get_request_timelines, get_session_latency_stats, get_session_metadata,
+    get_session_token_stats, list_recorded_sessions, list_sessions_filtered, list_tokenizers,
+    load_recorded_session, remove_recording_tag, remove_session_tags, resume_cli_breakpoint,
+    send_raw_message, send_request, send_to_cli_session, set_session_tokenizer,
     start_cli_bridge_server, start_proxy, start_proxy_v2, start_recording, start_remote_proxy,
     stop_cli_bridge_server, stop_proxy, stop_recording,
 };
@@ -99,6 +102,7 @@ fn main() {
             export_session,
             export_session_csv,
             export_session_har,
+            diff_recorded_sessions,
             // Interaction commands
             send_request,
             send_raw_message,
@@ -106,13 +110,25 @@ fn main() {
             get_mcp_methods,
             get_cli_sessions,
             send_to_cli_session,
+            resume_cli_breakpoint,
             // Token profiling commands
             get_session_token_stats,
             get_global_token_stats,
             clear_session_token_stats,
             clear_all_token_stats,
             estimate_tokens,
+            count_tokens,
+            list_tokenizers,
+            set_session_tokenizer,
             analyze_mcp_server,
+            analyze_remote_mcp_server,
+            // Latency profiling commands
+            get_session_latency_stats,
+            get_global_latency_stats,
+            get_recorded_latency_stats,
+            get_request_timelines,
+            clear_session_latency_stats,
+            clear_all_latency_stats,
             // Session management commands
             add_session_tags,
             remove_session_tags,
@@ -1,3 +1,4 @@
+use crate::core::protocol::McpMessage;
 use crate::core::TokenCounter;
 use crate::events::{LogEvent, SessionStartEvent};
 use reticle_core::session_names::generate_session_name;
@@ -38,7 +39,7 @@ impl MockData {
             // Parse JSON to count LLM-relevant tokens (not raw JSON-RPC overhead)
             let token_count = if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content)
             {
-                TokenCounter::count_mcp_context_tokens(&json)
+                TokenCounter::count_mcp_context_tokens(&McpMessage::parse(&json))
             } else {
                 TokenCounter::estimate_tokens(&content)
             };
@@ -57,8 +58,8 @@ impl MockData {
         };
 
         // 1. Initialize handshake
-        add_log(0, "in", r#"{"jsonrpc":"2.0","method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{"roots":{"listChanged":true},"sampling":{}},"clientInfo":{"name":"Claude Desktop","version":"1.0.0"}},"id":1}"#.to_string(), Some("initialize"), None);
-        add_log(150, "out", r#"{"jsonrpc":"2.0","result":{"protocolVersion":"2024-11-05","capabilities":{"logging":{},"prompts":{"listChanged":true},"resources":{"subscribe":true,"listChanged":true},"tools":{"listChanged":true}},"serverInfo":{"name":"filesystem-server","version":"0.1.0"}},"id":1}"#.to_string(), None, Some(150000));
+        add_log(0, "in", r#"{"jsonrpc":"2.0","method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{"roots":{"listChanged":true},"sampling":{},"elicitation":{}},"clientInfo":{"name":"Claude Desktop","version":"1.0.0"}},"id":1}"#.to_string(), Some("initialize"), None);
+        add_log(150, "out", r#"{"jsonrpc":"2.0","result":{"protocolVersion":"2025-06-18","capabilities":{"completions":{},"logging":{},"prompts":{"listChanged":true},"resources":{"subscribe":true,"listChanged":true},"tools":{"listChanged":true}},"serverInfo":{"name":"filesystem-server","version":"0.1.0"}},"id":1}"#.to_string(), None, Some(150000));
 
         add_log(
             200,
@@ -193,7 +194,7 @@ impl MockData {
         add_log(
             9520,
             "out",
-            r#"{"jsonrpc":"2.0","result":{"level":"debug"},"id":30}"#.to_string(),
+            r#"{"jsonrpc":"2.0","result":{},"id":30}"#.to_string(),
             None,
             Some(20000),
         );
@@ -249,6 +250,24 @@ impl MockData {
         add_log(17000, "in", r#"{"jsonrpc":"2.0","method":"resources/read","params":{"uri":"postgres://localhost/db/table"},"id":59}"#.to_string(), Some("resources/read"), None);
         add_log(20000, "out", r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Connection timeout","data":{"uri":"postgres://localhost/db/table","timeout":"3000ms"}},"id":59}"#.to_string(), None, Some(3000000));
 
+        // 22. Structured tool output with resource links (2025-06-18)
+        add_log(20200, "in", r#"{"jsonrpc":"2.0","method":"tools/call","params":{"name":"find_files","arguments":{"pattern":"*.rs","directory":"/Users/demo/project/src"}},"id":60}"#.to_string(), Some("tools/call"), None);
+        add_log(20450, "out", r#"{"jsonrpc":"2.0","result":{"content":[{"type":"text","text":"{\"count\":2,\"files\":[\"main.rs\",\"lib.rs\"]}"},{"type":"resource_link","uri":"file:///Users/demo/project/src/main.rs","name":"main.rs","mimeType":"text/x-rust"},{"type":"resource_link","uri":"file:///Users/demo/project/src/lib.rs","name":"lib.rs","mimeType":"text/x-rust"}],"structuredContent":{"count":2,"files":["main.rs","lib.rs"]}},"id":60}"#.to_string(), None, Some(250000));
+
+        // 23. Server asks the user for input through the client
+        add_log(21000, "out", r#"{"jsonrpc":"2.0","method":"elicitation/create","params":{"message":"Overwrite /Users/demo/output.txt?","requestedSchema":{"type":"object","properties":{"confirm":{"type":"boolean","title":"Overwrite"}},"required":["confirm"]}},"id":"srv-1"}"#.to_string(), Some("elicitation/create"), None);
+        add_log(24500, "in", r#"{"jsonrpc":"2.0","result":{"action":"accept","content":{"confirm":true}},"id":"srv-1"}"#.to_string(), None, Some(3500000));
+
+        // 24. Server asks which directories it may use
+        add_log(
+            24600,
+            "out",
+            r#"{"jsonrpc":"2.0","method":"roots/list","id":"srv-2"}"#.to_string(),
+            Some("roots/list"),
+            None,
+        );
+        add_log(24610, "in", r#"{"jsonrpc":"2.0","result":{"roots":[{"uri":"file:///Users/demo/project","name":"project"}]},"id":"srv-2"}"#.to_string(), None, Some(10000));
+
         MockData { session, logs }
     }
 }
@@ -458,6 +477,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mock_data_newer_features() {
+        let data = MockData::generate();
+
+        for method in ["elicitation/create", "roots/list", "completion/complete"] {
+            assert!(
+                data.logs
+                    .iter()
+                    .any(|l| l.method.as_deref() == Some(method)),
+                "Should have {method} requests"
+            );
+        }
+        assert!(data.logs.iter().any(
+            |l| l.content.contains("resource_link") && l.content.contains("structuredContent")
+        ));
+    }
+
     #[test]
     fn test_mock_data_prompts_list() {
         let data = MockData::generate();
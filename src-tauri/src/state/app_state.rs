@@ -1,9 +1,12 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{broadcast, Mutex};
 
 use crate::config::AppConfig;
-use crate::core::{SessionRecorder, TokenCounter};
+use crate::core::request_tracker::RequestTracker;
+use crate::core::tokenizer::{Tokenizer, TokenizerSpec};
+use crate::core::{LatencyCounter, SessionRecorder, TokenCounter};
 use crate::state::ProxyState;
 use crate::storage::SessionStorage;
 
@@ -47,6 +50,15 @@ pub struct AppState {
     /// Token counter for context profiling
     pub token_counter: Arc<TokenCounter>,
 
+    /// Latency histograms for live sessions
+    pub latency_counter: Arc<LatencyCounter>,
+
+    /// Requests sent to the stdio server that await a response
+    pub request_tracker: Arc<RequestTracker>,
+
+    /// Tokenizers loaded so far, by spec
+    pub tokenizers: Arc<Mutex<HashMap<TokenizerSpec, Arc<dyn Tokenizer>>>>,
+
     /// CLI bridge state (WebSocket server for CLI instances)
     pub cli_bridge: Arc<Mutex<CliBridgeState>>,
 }
@@ -65,6 +77,9 @@ impl AppState {
             recorder: Arc::new(Mutex::new(None)),
             storage: Arc::new(storage),
             token_counter: Arc::new(TokenCounter::new()),
+            latency_counter: Arc::new(LatencyCounter::new()),
+            request_tracker: Arc::new(RequestTracker::default()),
+            tokenizers: Arc::new(Mutex::new(HashMap::new())),
             cli_bridge: Arc::new(Mutex::new(CliBridgeState::default())),
         }
     }
@@ -82,6 +97,9 @@ impl AppState {
             recorder: Arc::new(Mutex::new(None)),
             storage: Arc::new(storage),
             token_counter: Arc::new(TokenCounter::new()),
+            latency_counter: Arc::new(LatencyCounter::new()),
+            request_tracker: Arc::new(RequestTracker::default()),
+            tokenizers: Arc::new(Mutex::new(HashMap::new())),
             cli_bridge: Arc::new(Mutex::new(CliBridgeState::default())),
         }
     }
@@ -1,3 +1,4 @@
+use crate::core::protocol::conformance::ConformanceChecker;
 use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
 use tokio::process::ChildStdin;
@@ -24,6 +25,9 @@ pub struct ProxyState {
     /// HTTP proxy URL for sending messages (HTTP/SSE transport only)
     /// Format: "http://localhost:3001" (the proxy port)
     pub http_proxy_url: Option<String>,
+
+    /// Protocol violations seen in the session (fresh for each `start`)
+    pub conformance: Arc<ConformanceChecker>,
 }
 
 impl ProxyState {
@@ -36,6 +40,7 @@ impl ProxyState {
     pub fn start(&mut self, session_id: String) {
         self.session_id = Some(session_id);
         self.is_running = true;
+        self.conformance = Arc::new(ConformanceChecker::new());
     }
 
     /// Start the proxy with stdin handle for interaction support (stdio transport)
@@ -56,6 +56,24 @@ impl SessionStorage {
             duration_ms: session.metadata.duration_ms,
             transport: session.metadata.transport.clone(),
             server_name: session.metadata.server_id.as_ref().map(|s| s.name.clone()),
+            server_version: session
+                .metadata
+                .server_info
+                .as_ref()
+                .map(|s| s.version.clone())
+                .or_else(|| {
+                    session
+                        .metadata
+                        .server_id
+                        .as_ref()
+                        .and_then(|s| s.version.clone())
+                }),
+            client_name: session
+                .metadata
+                .client_info
+                .as_ref()
+                .map(|c| c.name.clone()),
+            protocol_version: session.metadata.protocol_version.clone(),
             tags: session.metadata.tags.clone(),
         };
 
@@ -215,6 +233,19 @@ impl SessionStorage {
                     }
                 }
 
+                // Filter by what the initialize handshake recorded
+                let handshake = [
+                    (&filter.server_version, &session.server_version),
+                    (&filter.client_name, &session.client_name),
+                    (&filter.protocol_version, &session.protocol_version),
+                ];
+                if handshake
+                    .iter()
+                    .any(|(wanted, actual)| wanted.is_some() && wanted != actual)
+                {
+                    return false;
+                }
+
                 // Filter by tags (session must have ALL specified tags)
                 for tag in &filter.tags {
                     if !session.tags.contains(tag) {
@@ -300,6 +331,15 @@ pub struct SessionInfo {
     /// Server name for multi-server filtering
     #[serde(default)]
     pub server_name: Option<String>,
+    /// Server version from the `initialize` response
+    #[serde(default)]
+    pub server_version: Option<String>,
+    /// Client name from the `initialize` request
+    #[serde(default)]
+    pub client_name: Option<String>,
+    /// Negotiated protocol version
+    #[serde(default)]
+    pub protocol_version: Option<String>,
     /// Custom tags for filtering
     #[serde(default)]
     pub tags: Vec<String>,
@@ -324,6 +364,15 @@ pub struct SessionFilter {
     /// Filter by transport type
     #[serde(default)]
     pub transport: Option<String>,
+    /// Filter by server version
+    #[serde(default)]
+    pub server_version: Option<String>,
+    /// Filter by client name
+    #[serde(default)]
+    pub client_name: Option<String>,
+    /// Filter by negotiated protocol version
+    #[serde(default)]
+    pub protocol_version: Option<String>,
 }
 
 // bincode support - add to dependencies
@@ -358,8 +407,10 @@ mod tests {
                 duration_ms: Some(1000),
                 client_info: None,
                 server_info: None,
+                protocol_version: None,
                 server_id: None,
                 tags: vec![],
+                latency: None,
             },
         }
     }
@@ -375,6 +426,9 @@ mod tests {
             duration_ms: Some(1000),
             transport: "stdio".to_string(),
             server_name: Some("test-server".to_string()),
+            server_version: Some("1.0.0".to_string()),
+            client_name: None,
+            protocol_version: None,
             tags: vec!["tag1".to_string(), "tag2".to_string()],
         };
 
@@ -402,6 +456,7 @@ mod tests {
 
         let info: SessionInfo = serde_json::from_str(json).unwrap();
         assert!(info.server_name.is_none());
+        assert!(info.server_version.is_none());
         assert!(info.tags.is_empty());
     }
 
@@ -432,6 +487,7 @@ mod tests {
             server_name: Some("filesystem".to_string()),
             tags: vec!["production".to_string()],
             transport: Some("stdio".to_string()),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&filter).unwrap();
@@ -631,6 +687,9 @@ mod tests {
             duration_ms: Some(100),
             transport: "stdio".to_string(),
             server_name: None,
+            server_version: None,
+            client_name: None,
+            protocol_version: None,
             tags: vec!["a".to_string()],
         };
 